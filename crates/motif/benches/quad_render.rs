@@ -27,6 +27,8 @@ struct BenchStats {
     frame_times: Vec<Duration>,
     scene_build_times: Vec<Duration>,
     render_times: Vec<Duration>,
+    /// GPU execution time, for frames where the renderer reported one.
+    gpu_times: Vec<Duration>,
 }
 
 impl BenchStats {
@@ -35,13 +37,21 @@ impl BenchStats {
             frame_times: Vec::with_capacity(SAMPLE_FRAMES),
             scene_build_times: Vec::with_capacity(SAMPLE_FRAMES),
             render_times: Vec::with_capacity(SAMPLE_FRAMES),
+            gpu_times: Vec::with_capacity(SAMPLE_FRAMES),
         }
     }
 
-    fn record(&mut self, frame: Duration, scene_build: Duration, render: Duration) {
+    fn record(
+        &mut self,
+        frame: Duration,
+        scene_build: Duration,
+        render: Duration,
+        gpu: Option<Duration>,
+    ) {
         self.frame_times.push(frame);
         self.scene_build_times.push(scene_build);
         self.render_times.push(render);
+        self.gpu_times.extend(gpu);
     }
 
     fn report(&self, quad_count: usize) {
@@ -65,6 +75,13 @@ impl BenchStats {
 
         // JSON output for CI
         if std::env::var("MOTIF_BENCH_JSON").is_ok() {
+            let gpu = (!self.gpu_times.is_empty()).then(|| {
+                serde_json::json!({
+                    "avg": avg(&self.gpu_times).as_micros(),
+                    "min": min(&self.gpu_times).as_micros(),
+                    "max": max(&self.gpu_times).as_micros(),
+                })
+            });
             let json = serde_json::json!({
                 "timestamp": chrono_lite_timestamp(),
                 "benchmark": "quad_render",
@@ -87,6 +104,7 @@ impl BenchStats {
                     "min": min(&self.render_times).as_micros(),
                     "max": max(&self.render_times).as_micros(),
                 },
+                "gpu_us": gpu,
                 "fps": fps,
                 "throughput_mquads_sec": throughput,
             });
@@ -128,6 +146,16 @@ impl BenchStats {
             min(&self.render_times),
             max(&self.render_times)
         );
+        if !self.gpu_times.is_empty() {
+            println!();
+            println!("GPU:");
+            println!("  avg: {:>8.2?}", avg(&self.gpu_times));
+            println!(
+                "  min: {:>8.2?}  max: {:>8.2?}",
+                min(&self.gpu_times),
+                max(&self.gpu_times)
+            );
+        }
         println!();
         println!("Throughput: {:.2}M quads/sec", throughput);
     }
@@ -279,7 +307,12 @@ impl ApplicationHandler for App {
 
                         // Skip warmup frames
                         if self.frame_count > WARMUP_FRAMES {
-                            self.stats.record(frame_time, scene_build_time, render_time);
+                            self.stats.record(
+                                frame_time,
+                                scene_build_time,
+                                render_time,
+                                renderer.last_frame_gpu_time(),
+                            );
 
                             // Progress indicator
                             let samples = self.stats.frame_times.len();
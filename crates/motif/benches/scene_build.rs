@@ -1,7 +1,7 @@
 //! CPU benchmarks for scene building via DrawContext.
 
 use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
-use motif_core::{DrawContext, Point, Rect, ScaleFactor, Scene, Size, Srgba};
+use motif_core::{DrawContext, Point, Rect, ScaleFactor, Scene, SceneBuilder, Size, Srgba};
 use rand::rngs::SmallRng;
 use rand::{Rng, SeedableRng};
 
@@ -61,6 +61,23 @@ fn bench_draw_context_paint(c: &mut Criterion) {
         });
     });
 
+    // Four panels painted on separate threads and merged.
+    let panels: Vec<&[(Rect, Srgba)]> = quads.chunks(QUAD_COUNT / 4).collect();
+    let mut builder = SceneBuilder::new();
+    group.bench_function("parallel_regions", |b| {
+        b.iter(|| {
+            let mut scene = Scene::new();
+            builder.build(&mut scene, panels.len(), |index, region| {
+                let mut cx = DrawContext::new(region, ScaleFactor(2.0));
+                for (rect, color) in panels[index] {
+                    cx.paint_quad(*rect, *color);
+                }
+            });
+
+            black_box(scene.quad_count())
+        });
+    });
+
     group.finish();
 }
 
@@ -5,13 +5,14 @@
 //!
 //! Run with: cargo run --example buttons
 
+use std::sync::Arc;
+
 use motif_core::{
     button,
     element::{LayoutContext, PaintContext},
     input::{InputState, MouseButton},
-    metal::{MetalRenderer, MetalSurface},
-    DrawContext, Element, ElementId, HitTree, LayoutEngine, Point, Rect, Renderer, ScaleFactor,
-    Scene, Size, Srgba, TextContext,
+    select_backend, Backend, DrawContext, Element, ElementId, HitTree, LayoutEngine, Point, Rect,
+    ScaleFactor, Scene, Size, Srgba, TextContext,
 };
 use motif_debug::{DebugServer, InputStateSnapshot, SceneSnapshot};
 use winit::{
@@ -28,9 +29,8 @@ const GAP: f32 = 20.0;
 const MARGIN: f32 = 40.0;
 
 struct App {
-    window: Option<Window>,
-    renderer: Option<MetalRenderer>,
-    surface: Option<MetalSurface>,
+    window: Option<Arc<Window>>,
+    backend: Option<Box<dyn Backend>>,
     scene: Scene,
     text_ctx: TextContext,
     hit_tree: HitTree,
@@ -45,8 +45,7 @@ impl Default for App {
     fn default() -> Self {
         Self {
             window: None,
-            renderer: None,
-            surface: None,
+            backend: None,
             scene: Scene::new(),
             text_ctx: TextContext::new(),
             hit_tree: HitTree::new(),
@@ -84,21 +83,22 @@ impl ApplicationHandler for App {
                 .with_title("Motif — Button Demo")
                 .with_inner_size(winit::dpi::LogicalSize::new(width, height))
                 .with_resizable(false);
-            let window = event_loop.create_window(attrs).unwrap();
-
-            let renderer = MetalRenderer::new();
-            let surface = unsafe { MetalSurface::new(&window, renderer.device()) };
+            let window = Arc::new(event_loop.create_window(attrs).unwrap());
+            let backend = select_backend(window.clone(), None).expect("no usable renderer backend");
 
             if let Some(ref debug_server) = self.debug_server {
+                #[cfg(target_os = "macos")]
                 if let Some(id) = motif_core::metal::window_id(&window) {
                     debug_server.set_window_id(id);
                 }
+                debug_server.set_renderer_info(backend.kind().name(), backend.device_name());
+                let redraw_window = Arc::clone(&window);
+                debug_server.enable_frame_capture(move || redraw_window.request_redraw());
             }
 
             window.request_redraw();
             self.window = Some(window);
-            self.renderer = Some(renderer);
-            self.surface = Some(surface);
+            self.backend = Some(backend);
         }
     }
 
@@ -106,9 +106,7 @@ impl ApplicationHandler for App {
         match event {
             WindowEvent::CloseRequested => event_loop.exit(),
             WindowEvent::RedrawRequested => {
-                if let (Some(renderer), Some(surface), Some(window)) =
-                    (&mut self.renderer, &mut self.surface, &self.window)
-                {
+                if let (Some(backend), Some(window)) = (&mut self.backend, &self.window) {
                     self.scene.clear();
                     self.hit_tree.clear();
 
@@ -201,7 +199,7 @@ impl ApplicationHandler for App {
                         );
                     }
 
-                    renderer.render(&self.scene, surface);
+                    backend.render(&self.scene);
 
                     // Update debug server
                     if let Some(ref debug_server) = self.debug_server {
@@ -209,6 +207,13 @@ impl ApplicationHandler for App {
                         let viewport = (phys.width as f32, phys.height as f32);
                         let snapshot = SceneSnapshot::from_scene(&self.scene, viewport, scale.0);
                         debug_server.update_scene(snapshot);
+                        debug_server.set_gpu_time(backend.last_frame_gpu_time());
+                        if debug_server.capture_requested() {
+                            debug_server.provide_capture(backend.render_to_image(&self.scene));
+                        }
+                        if debug_server.atlas_requested() {
+                            debug_server.provide_atlas(backend.dump_glyph_atlas());
+                        }
 
                         if let Ok(inner_pos) = window.inner_position() {
                             debug_server.set_window_position(
@@ -219,9 +224,6 @@ impl ApplicationHandler for App {
                         }
                     }
                 }
-                if let Some(window) = &self.window {
-                    window.request_redraw();
-                }
             }
             WindowEvent::CursorMoved { position, .. } => {
                 let scale = self
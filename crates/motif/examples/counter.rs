@@ -0,0 +1,52 @@
+//! The smallest app built on `motif::App`: a counter and a button.
+//!
+//! Run with: cargo run --example counter
+
+use motif::{
+    button, text, v_stack, App, AppError, ElementId, IntoElement, ParentElement, Render, Size,
+    View, ViewContext, WindowOptions,
+};
+
+struct Counter {
+    count: u32,
+}
+
+impl Render for Counter {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        text(format!("Clicked {} times", self.count)).font_size(24.0)
+    }
+}
+
+struct Root {
+    counter: View<Counter>,
+}
+
+impl Render for Root {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let theme = cx.theme().clone();
+        let counter = self.counter.clone();
+        v_stack()
+            .size(Size::new(400.0, 240.0))
+            .padding(24.0)
+            .gap(16.0)
+            .background(theme.background)
+            .text_color(theme.text)
+            .child(self.counter.render(cx))
+            .child(
+                button("Increment", ElementId(1))
+                    .on_click(move || counter.update(|c| c.count += 1)),
+            )
+    }
+}
+
+fn main() -> Result<(), AppError> {
+    App::new()
+        .window(
+            WindowOptions::new()
+                .title("Motif — Counter")
+                .size(Size::new(400.0, 240.0)),
+        )
+        .run(|cx| Root {
+            counter: cx.new_view(|_| Counter { count: 0 }),
+        })
+}
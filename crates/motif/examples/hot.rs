@@ -4,9 +4,10 @@
 //!
 //! Try changing the background color or text while running!
 
+use std::sync::Arc;
+
 use motif_core::{
-    metal::{MetalRenderer, MetalSurface},
-    DrawContext, Point, Rect, Renderer, ScaleFactor, Scene, Size, Srgba, TextContext,
+    select_backend, Backend, DrawContext, Point, Rect, ScaleFactor, Scene, Size, Srgba, TextContext,
 };
 use winit::{
     application::ApplicationHandler,
@@ -16,9 +17,8 @@ use winit::{
 };
 
 struct App {
-    window: Option<Window>,
-    renderer: Option<MetalRenderer>,
-    surface: Option<MetalSurface>,
+    window: Option<Arc<Window>>,
+    backend: Option<Box<dyn Backend>>,
     scene: Scene,
     text_ctx: TextContext,
 }
@@ -27,8 +27,7 @@ impl Default for App {
     fn default() -> Self {
         Self {
             window: None,
-            renderer: None,
-            surface: None,
+            backend: None,
             scene: Scene::new(),
             text_ctx: TextContext::new(),
         }
@@ -88,15 +87,12 @@ impl ApplicationHandler for App {
             let attrs = Window::default_attributes()
                 .with_title("Motif — Hot Reload")
                 .with_inner_size(winit::dpi::LogicalSize::new(500.0, 300.0));
-            let window = event_loop.create_window(attrs).unwrap();
-
-            let renderer = MetalRenderer::new();
-            let surface = unsafe { MetalSurface::new(&window, renderer.device()) };
+            let window = Arc::new(event_loop.create_window(attrs).unwrap());
+            let backend = select_backend(window.clone(), None).expect("no usable renderer backend");
 
             window.request_redraw();
             self.window = Some(window);
-            self.renderer = Some(renderer);
-            self.surface = Some(surface);
+            self.backend = Some(backend);
         }
     }
 
@@ -104,9 +100,7 @@ impl ApplicationHandler for App {
         match event {
             WindowEvent::CloseRequested => event_loop.exit(),
             WindowEvent::RedrawRequested => {
-                if let (Some(renderer), Some(surface), Some(window)) =
-                    (&mut self.renderer, &mut self.surface, &self.window)
-                {
+                if let (Some(backend), Some(window)) = (&mut self.backend, &self.window) {
                     self.scene.clear();
                     let scale = ScaleFactor(window.scale_factor() as f32);
                     let phys = window.inner_size();
@@ -128,8 +122,10 @@ impl ApplicationHandler for App {
                     #[cfg(not(feature = "hot"))]
                     render(&mut self.scene, &mut self.text_ctx, scale, size);
 
-                    renderer.render(&self.scene, surface);
+                    backend.render(&self.scene);
                 }
+                // Keep drawing so patched code shows up without input.
+                #[cfg(feature = "hot")]
                 if let Some(window) = &self.window {
                     window.request_redraw();
                 }
@@ -145,6 +141,8 @@ fn main() {
     cargo_hot::connect();
 
     let event_loop = EventLoop::new().unwrap();
+    event_loop.set_control_flow(ControlFlow::Wait);
+    #[cfg(feature = "hot")]
     event_loop.set_control_flow(ControlFlow::Poll); // Poll for hot reload responsiveness
     let mut app = App::default();
     event_loop.run_app(&mut app).unwrap();
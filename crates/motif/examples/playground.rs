@@ -5,17 +5,20 @@
 //!
 //! Run with: cargo run --example playground
 
+use std::cell::Cell;
+use std::rc::Rc;
+use std::sync::Arc;
+
 use motif_core::{
-    checkbox, div,
+    button, checkbox, div,
     element::{Element, LayoutContext, PaintContext},
     focus::{FocusEvent, FocusHandle, FocusState},
-    input::{InputState, MouseButton, ScrollDelta, TextEditState},
-    metal::{MetalRenderer, MetalSurface},
-    text, text_input, ArcStr, DrawContext, ElementId, HitTree, IntoElement, LayoutEngine,
-    ParentElement, Point, Rect, Render, RenderOnce, Renderer, ScaleFactor, Scene, Size, Srgba,
-    TextContext, ViewContext, WindowContext,
+    input::{CursorIcon, InputState, MouseButton, ScrollDelta, TextEditState},
+    select_backend, text, text_input, ArcStr, Backend, CallbackRegistry, DrawContext, ElementId,
+    HitTree, IntoElement, LayoutEngine, ParentElement, Point, Rect, Render, RenderOnce,
+    ScaleFactor, Scene, Size, Srgba, TextContext, ViewContext, WindowContext,
 };
-use motif_debug::{DebugServer, InputStateSnapshot, SceneSnapshot};
+use motif_debug::{DebugServer, InputStateSnapshot, QuadPatch, SceneSnapshot};
 use winit::{
     application::ApplicationHandler,
     event::WindowEvent,
@@ -241,9 +244,8 @@ impl RenderOnce for StatusCard {
 // ============================================================================
 
 struct App {
-    window: Option<Window>,
-    renderer: Option<MetalRenderer>,
-    surface: Option<MetalSurface>,
+    window: Option<Arc<Window>>,
+    backend: Option<Box<dyn Backend>>,
     scene: Scene,
     text_ctx: TextContext,
     hit_tree: HitTree,
@@ -251,11 +253,15 @@ struct App {
     element_demo: ElementDemo,
     debug_server: Option<DebugServer>,
     input_state: InputState,
+    /// Cursor currently set on the window
+    cursor: CursorIcon,
     focus_state: FocusState,
     /// Focus handles for demo input fields
     input_handles: [FocusHandle; 3],
-    /// Click counter for demo
-    click_count: u32,
+    /// Click handlers registered by elements during paint
+    callbacks: CallbackRegistry,
+    /// Click counter for demo, shared with the button's click handler
+    click_count: Rc<Cell<u32>>,
     // --- Controls demo state ---
     checkbox_states: [bool; 3],
     text_edit_state: TextEditState,
@@ -267,8 +273,7 @@ impl Default for App {
         let debug_server = DebugServer::new().ok();
         Self {
             window: None,
-            renderer: None,
-            surface: None,
+            backend: None,
             scene: Scene::new(),
             text_ctx: TextContext::new(),
             hit_tree: HitTree::new(),
@@ -276,9 +281,11 @@ impl Default for App {
             element_demo: ElementDemo { frame: 0 },
             debug_server,
             input_state: InputState::new(),
+            cursor: CursorIcon::Default,
             focus_state: FocusState::new(),
             input_handles: [FocusHandle::new(), FocusHandle::new(), FocusHandle::new()],
-            click_count: 0,
+            callbacks: CallbackRegistry::new(),
+            click_count: Rc::new(Cell::new(0)),
             checkbox_states: [true, false, false],
             text_edit_state: {
                 let mut state = TextEditState::new();
@@ -297,22 +304,23 @@ impl ApplicationHandler for App {
             let attrs = Window::default_attributes()
                 .with_title("Motif — Playground")
                 .with_inner_size(winit::dpi::LogicalSize::new(1200.0, 900.0));
-            let window = event_loop.create_window(attrs).unwrap();
-
-            let renderer = MetalRenderer::new();
-            let surface = unsafe { MetalSurface::new(&window, renderer.device()) };
+            let window = Arc::new(event_loop.create_window(attrs).unwrap());
+            let backend = select_backend(window.clone(), None).expect("no usable renderer backend");
 
             // Pass the window ID to the debug server for native screenshots
             if let Some(ref debug_server) = self.debug_server {
+                #[cfg(target_os = "macos")]
                 if let Some(id) = motif_core::metal::window_id(&window) {
                     debug_server.set_window_id(id);
                 }
+                debug_server.set_renderer_info(backend.kind().name(), backend.device_name());
+                let redraw_window = Arc::clone(&window);
+                debug_server.enable_frame_capture(move || redraw_window.request_redraw());
             }
 
             window.request_redraw();
             self.window = Some(window);
-            self.renderer = Some(renderer);
-            self.surface = Some(surface);
+            self.backend = Some(backend);
         }
     }
 
@@ -322,16 +330,15 @@ impl ApplicationHandler for App {
                 event_loop.exit();
             }
             WindowEvent::Resized(size) => {
-                if let Some(surface) = &mut self.surface {
-                    surface.resize(size.width as f32, size.height as f32);
+                if let Some(backend) = &mut self.backend {
+                    backend.resize(size.width as f32, size.height as f32);
                 }
             }
             WindowEvent::RedrawRequested => {
-                if let (Some(renderer), Some(surface), Some(window)) =
-                    (&mut self.renderer, &mut self.surface, &self.window)
-                {
+                if let (Some(backend), Some(window)) = (&mut self.backend, &self.window) {
                     self.scene.clear();
                     self.hit_tree.clear();
+                    self.callbacks.clear();
 
                     let scale = ScaleFactor(window.scale_factor() as f32);
 
@@ -468,39 +475,49 @@ impl ApplicationHandler for App {
                             500.0,
                             200.0,
                         );
-
-                        // Paint an interactive button
+                    }
+                    {
+                        // Interactive button: the element tracks its own
+                        // visual state and registers its click handler.
                         let button_id = ElementId(1000); // Fixed ID for the demo button
-                        let button_bounds =
-                            Rect::new(Point::new(500.0, 220.0), Size::new(180.0, 50.0));
-
-                        // Determine button visual state
-                        let is_hovered = self.input_state.hovered() == Some(button_id);
-                        let is_pressed = self.input_state.pressed() == Some(button_id);
-
-                        let button_color = if is_pressed {
-                            Srgba::new(0.2, 0.5, 0.9, 1.0) // Pressed: darker blue
-                        } else if is_hovered {
-                            Srgba::new(0.4, 0.7, 1.0, 1.0) // Hover: lighter blue
-                        } else {
-                            Srgba::new(0.3, 0.6, 0.95, 1.0) // Normal: blue
-                        };
-
-                        cx.paint_quad(button_bounds, button_color);
-                        self.hit_tree.push(button_id, button_bounds);
+                        let count = Rc::clone(&self.click_count);
+                        let demo = button(format!("Clicks: {}", count.get()), button_id)
+                            .font_size(18.0)
+                            .hovered(self.input_state.hovered() == Some(button_id))
+                            .pressed(self.input_state.pressed() == Some(button_id))
+                            .on_click(move || count.set(count.get() + 1));
+                        let disabled = button("Disabled", ElementId(1001))
+                            .font_size(18.0)
+                            .disabled(true);
+
+                        let mut x = 500.0;
+                        for mut el in [demo, disabled] {
+                            let mut layout_cx = LayoutContext::new(
+                                &mut self.layout_engine,
+                                &mut self.text_ctx,
+                                scale,
+                            );
+                            let node_id = el.request_layout(&mut layout_cx);
+                            self.layout_engine.compute_layout(
+                                node_id,
+                                800.0,
+                                600.0,
+                                &mut self.text_ctx,
+                            );
+                            let size = self.layout_engine.layout_bounds(node_id).size;
+                            let bounds = Rect::new(Point::new(x, 220.0), size);
 
-                        // Button label
-                        let label = format!("Clicks: {}", self.click_count);
-                        cx.paint_text(
-                            &label,
-                            Point::new(
-                                button_bounds.origin.x + 20.0,
-                                button_bounds.origin.y + 16.0,
-                            ),
-                            18.0,
-                            Srgba::new(1.0, 1.0, 1.0, 1.0),
-                            &mut self.text_ctx,
-                        );
+                            let mut pcx = PaintContext::new(
+                                &mut self.scene,
+                                &mut self.text_ctx,
+                                &mut self.hit_tree,
+                                &self.layout_engine,
+                                scale,
+                            )
+                            .with_callbacks(&mut self.callbacks);
+                            el.paint(bounds, &mut pcx);
+                            x += size.width + 12.0;
+                        }
                     }
 
                     // --- Section: Focus Demo ---
@@ -702,44 +719,31 @@ impl ApplicationHandler for App {
                         );
                     }
 
+                    // Apply quad overrides from `scene.patch` before the
+                    // overlays, so quad indices match the app's own scene.
+                    if let Some(ref debug_server) = self.debug_server {
+                        QuadPatch::apply_all(&debug_server.patches(), &mut self.scene);
+                    }
+
                     // --- Debug overlays ---
                     // Paint any debug overlay quads on top of the scene.
                     if let Some(ref debug_server) = self.debug_server {
                         let mut cx = DrawContext::new(&mut self.scene, scale);
                         for overlay in debug_server.overlays() {
-                            let mut quad = motif_core::Quad::new(
-                                motif_core::DeviceRect::new(
-                                    motif_core::DevicePoint::new(
-                                        overlay.x * scale.0,
-                                        overlay.y * scale.0,
-                                    ),
-                                    motif_core::DeviceSize::new(
-                                        overlay.w * scale.0,
-                                        overlay.h * scale.0,
-                                    ),
-                                ),
-                                Srgba::new(
-                                    overlay.color.r,
-                                    overlay.color.g,
-                                    overlay.color.b,
-                                    overlay.color.a,
-                                ),
-                            );
-                            quad.border_color = Srgba::new(
-                                overlay.border_color.r,
-                                overlay.border_color.g,
-                                overlay.border_color.b,
-                                overlay.border_color.a,
+                            cx.paint(overlay.to_quad(scale.0));
+                        }
+                        for label in debug_server.overlay_labels() {
+                            cx.paint_text(
+                                &label.text,
+                                Point::new(label.x, label.y),
+                                12.0,
+                                label.color.to_srgba(),
+                                &mut self.text_ctx,
                             );
-                            quad.border_widths =
-                                motif_core::Edges::all(overlay.border_width * scale.0);
-                            quad.corner_radii =
-                                motif_core::Corners::all(overlay.corner_radius * scale.0);
-                            cx.paint(quad);
                         }
                     }
 
-                    renderer.render(&self.scene, surface);
+                    backend.render(&self.scene);
 
                     // Update the debug server with the current scene state.
                     if let Some(ref debug_server) = self.debug_server {
@@ -747,6 +751,13 @@ impl ApplicationHandler for App {
                         let viewport = (phys.width as f32, phys.height as f32);
                         let snapshot = SceneSnapshot::from_scene(&self.scene, viewport, scale.0);
                         debug_server.update_scene(snapshot);
+                        debug_server.set_gpu_time(backend.last_frame_gpu_time());
+                        if debug_server.capture_requested() {
+                            debug_server.provide_capture(backend.render_to_image(&self.scene));
+                        }
+                        if debug_server.atlas_requested() {
+                            debug_server.provide_atlas(backend.dump_glyph_atlas());
+                        }
 
                         // Update window position for input simulation
                         // Use inner_position (content area) not outer_position (includes title bar)
@@ -758,11 +769,13 @@ impl ApplicationHandler for App {
                                 scale.0,
                             );
                         }
+
+                        // Keep the frame-rate widget live.
+                        if debug_server.fps_overlay_enabled() {
+                            window.request_redraw();
+                        }
                     }
                 }
-                if let Some(window) = &self.window {
-                    window.request_redraw();
-                }
             }
             // --- Input events ---
             WindowEvent::CursorMoved { position, .. } => {
@@ -773,11 +786,27 @@ impl ApplicationHandler for App {
                     .unwrap_or(1.0);
                 self.input_state
                     .handle_cursor_moved(position.x, position.y, scale);
+                if let Some(ref debug_server) = self.debug_server {
+                    let pointer = self.input_state.cursor_position;
+                    debug_server.set_mouse_position(pointer.map(|p| (p.x, p.y)));
+                }
 
                 // Update hover state from hit tree
                 if let Some(pos) = self.input_state.cursor_position {
+                    let previous = self.input_state.hovered();
                     let hovered = self.hit_tree.hit_test(pos);
                     self.input_state.set_hovered(hovered);
+                    if hovered != previous {
+                        self.callbacks.dispatch_hover(previous, hovered);
+                    }
+
+                    let cursor = self.hit_tree.cursor_at(pos);
+                    if cursor != self.cursor {
+                        self.cursor = cursor;
+                        if let Some(window) = &self.window {
+                            window.set_cursor(cursor);
+                        }
+                    }
                 }
 
                 // Request redraw for hover feedback
@@ -791,6 +820,13 @@ impl ApplicationHandler for App {
             WindowEvent::CursorLeft { .. } => {
                 self.input_state.handle_cursor_left();
                 self.input_state.set_hovered(None);
+                if let Some(ref debug_server) = self.debug_server {
+                    if debug_server.set_mouse_position(None) {
+                        if let Some(window) = &self.window {
+                            window.request_redraw();
+                        }
+                    }
+                }
             }
             WindowEvent::MouseInput { state, button, .. } => {
                 let btn = MouseButton::from_winit(button);
@@ -804,10 +840,8 @@ impl ApplicationHandler for App {
                     if let Some(clicked_element) = self.input_state.end_press() {
                         let id = clicked_element.0;
 
-                        // Check if clicked on button
-                        if clicked_element == ElementId(1000) {
-                            self.click_count += 1;
-                        }
+                        // Run any element click handler (e.g. the demo button)
+                        self.callbacks.dispatch_click(clicked_element);
                         // Check if clicked on focusable inputs (IDs 2000-2002)
                         if (2000..2003).contains(&id) {
                             let index = (id - 2000) as usize;
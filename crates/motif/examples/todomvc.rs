@@ -2,13 +2,14 @@
 //!
 //! Run with: cargo run --example todomvc
 
+use std::sync::Arc;
+
 use motif_core::{
     checkbox,
     element::{Element, LayoutContext, PaintContext},
     input::{HandleKeyResult, InputState, MouseButton, TextEditState},
-    metal::{MetalRenderer, MetalSurface},
-    text_input, DrawContext, ElementId, HitTree, LayoutEngine, Point, Rect, Renderer, ScaleFactor,
-    Scene, Size, Srgba, TextContext,
+    select_backend, text_input, Backend, DrawContext, ElementId, HitTree, LayoutEngine, Point,
+    Rect, ScaleFactor, Scene, Size, Srgba, TextContext,
 };
 use winit::{
     application::ApplicationHandler,
@@ -33,9 +34,8 @@ struct Todo {
 // ============================================================================
 
 struct TodoApp {
-    window: Option<Window>,
-    surface: Option<MetalSurface>,
-    renderer: Option<MetalRenderer>,
+    window: Option<Arc<Window>>,
+    backend: Option<Box<dyn Backend>>,
     scene: Scene,
     hit_tree: HitTree,
     text_ctx: TextContext,
@@ -55,8 +55,7 @@ impl TodoApp {
     fn new() -> Self {
         Self {
             window: None,
-            surface: None,
-            renderer: None,
+            backend: None,
             scene: Scene::new(),
             hit_tree: HitTree::new(),
             text_ctx: TextContext::new(),
@@ -344,13 +343,11 @@ impl ApplicationHandler for TodoApp {
         let window_attrs = Window::default_attributes()
             .with_title("TodoMVC - motif")
             .with_inner_size(winit::dpi::LogicalSize::new(600, 500));
-        let window = event_loop.create_window(window_attrs).unwrap();
-        let renderer = MetalRenderer::new();
-        let surface = unsafe { MetalSurface::new(&window, renderer.device()) };
+        let window = Arc::new(event_loop.create_window(window_attrs).unwrap());
+        let backend = select_backend(window.clone(), None).expect("no usable renderer backend");
 
         self.window = Some(window);
-        self.renderer = Some(renderer);
-        self.surface = Some(surface);
+        self.backend = Some(backend);
     }
 
     fn window_event(&mut self, event_loop: &ActiveEventLoop, _id: WindowId, event: WindowEvent) {
@@ -359,19 +356,19 @@ impl ApplicationHandler for TodoApp {
                 event_loop.exit();
             }
             WindowEvent::RedrawRequested => {
-                if self.surface.is_none() || self.renderer.is_none() {
+                if self.backend.is_none() {
                     return;
                 }
 
                 self.paint();
 
-                if let (Some(surface), Some(renderer)) = (&mut self.surface, &mut self.renderer) {
-                    renderer.render(&self.scene, surface);
+                if let Some(backend) = &mut self.backend {
+                    backend.render(&self.scene);
                 }
             }
             WindowEvent::Resized(size) => {
-                if let Some(surface) = &mut self.surface {
-                    surface.resize(size.width as f32, size.height as f32);
+                if let Some(backend) = &mut self.backend {
+                    backend.resize(size.width as f32, size.height as f32);
                 }
             }
             WindowEvent::CursorMoved { position, .. } => {
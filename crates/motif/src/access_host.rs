@@ -0,0 +1,155 @@
+//! Screen reader support for a winit window.
+//!
+//! [`AccessibilityHost`] wraps the accesskit_winit adapter and the state its
+//! handlers share with the app, so an app only hands it each frame's tree:
+//!
+//! ```ignore
+//! // Before the window is first shown:
+//! let window = Arc::new(event_loop.create_window(attrs.with_visible(false))?);
+//! let mut access = AccessibilityHost::new(event_loop, window.clone());
+//! window.set_visible(true);
+//!
+//! // For every window event, before handling it:
+//! access.process_event(&window, &event);
+//!
+//! // After painting with `PaintContext::with_access_tree(&mut tree)`:
+//! access.set_tree(&tree);
+//! access.dispatch_actions(&mut dispatcher, &mut callbacks);
+//! ```
+
+use std::sync::{Arc, Mutex};
+
+use accesskit::{ActionHandler, ActionRequest, ActivationHandler, DeactivationHandler, TreeUpdate};
+use accesskit_winit::Adapter;
+use motif_core::{AccessId, AccessTree, CallbackRegistry, EventDispatcher};
+use winit::event::WindowEvent;
+use winit::event_loop::ActiveEventLoop;
+use winit::window::Window;
+
+/// State shared between the app and the adapter's handlers, which the
+/// platform may call from other threads.
+#[derive(Default)]
+struct AccessState {
+    /// The last tree the app set, handed out when a screen reader starts.
+    tree: Option<TreeUpdate>,
+    /// Actions requested since the app last took them.
+    actions: Vec<ActionRequest>,
+    active: bool,
+}
+
+/// Owns a window's accessibility adapter.
+pub struct AccessibilityHost {
+    adapter: Adapter,
+    state: Arc<Mutex<AccessState>>,
+    focus: Option<AccessId>,
+}
+
+impl AccessibilityHost {
+    /// Create the adapter for `window`.
+    ///
+    /// Panics if the window is already visible: accesskit needs the adapter
+    /// to exist before the window is first shown, so create the window with
+    /// `with_visible(false)` and show it afterwards.
+    pub fn new(event_loop: &ActiveEventLoop, window: Arc<Window>) -> Self {
+        let state = Arc::new(Mutex::new(AccessState::default()));
+        let adapter = Adapter::with_direct_handlers(
+            event_loop,
+            &window,
+            Activation(state.clone()),
+            Actions {
+                state: state.clone(),
+                window: window.clone(),
+            },
+            Deactivation(state.clone()),
+        );
+        Self {
+            adapter,
+            state,
+            focus: None,
+        }
+    }
+
+    /// Let the adapter see a window event. Call for every event, before the
+    /// app handles it.
+    pub fn process_event(&mut self, window: &Window, event: &WindowEvent) {
+        self.adapter.process_event(window, event);
+    }
+
+    /// Whether a screen reader or other assistive technology is listening.
+    pub fn is_active(&self) -> bool {
+        self.lock().active
+    }
+
+    /// The node assistive technology should treat as focused. Defaults to
+    /// the tree's root.
+    pub fn set_focus(&mut self, focus: Option<AccessId>) {
+        self.focus = focus;
+    }
+
+    /// Publish this frame's tree.
+    pub fn set_tree(&mut self, tree: &AccessTree) {
+        let update = tree.build_initial_update(self.focus);
+        self.lock().tree = Some(update.clone());
+        self.adapter.update_if_active(|| update);
+    }
+
+    /// Actions requested by assistive technology since the last call. The
+    /// window is asked to redraw whenever one arrives.
+    pub fn take_actions(&mut self) -> Vec<ActionRequest> {
+        std::mem::take(&mut self.lock().actions)
+    }
+
+    /// Route pending actions to element handlers through `dispatcher`.
+    /// Returns true if any was handled.
+    pub fn dispatch_actions(
+        &mut self,
+        dispatcher: &mut EventDispatcher,
+        callbacks: &mut CallbackRegistry,
+    ) -> bool {
+        let mut handled = false;
+        for request in self.take_actions() {
+            handled |= dispatcher.handle_access_action(&request, callbacks);
+        }
+        handled
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, AccessState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+struct Activation(Arc<Mutex<AccessState>>);
+
+impl ActivationHandler for Activation {
+    fn request_initial_tree(&mut self) -> Option<TreeUpdate> {
+        let mut state = self.0.lock().ok()?;
+        state.active = true;
+        state.tree.clone()
+    }
+}
+
+struct Actions {
+    state: Arc<Mutex<AccessState>>,
+    window: Arc<Window>,
+}
+
+impl ActionHandler for Actions {
+    fn do_action(&mut self, request: ActionRequest) {
+        if let Ok(mut state) = self.state.lock() {
+            state.actions.push(request);
+        }
+        self.window.request_redraw();
+    }
+}
+
+struct Deactivation(Arc<Mutex<AccessState>>);
+
+impl DeactivationHandler for Deactivation {
+    fn deactivate_accessibility(&mut self) {
+        if let Ok(mut state) = self.0.lock() {
+            state.active = false;
+        }
+    }
+}
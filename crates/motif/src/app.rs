@@ -0,0 +1,1427 @@
+//! Running a motif app: windows, renderers and the event loop.
+//!
+//! ```ignore
+//! fn main() -> Result<(), motif::AppError> {
+//!     App::new()
+//!         .window(WindowOptions::new().title("Counter").size(Size::new(400.0, 300.0)))
+//!         .run(|_cx| Counter { count: 0 })
+//! }
+//! ```
+//!
+//! Each window has its own scene, surface, scale factor and root view; the
+//! runner routes window events to the window they belong to, dispatches
+//! input to the handlers elements register while painting, and redraws a
+//! window when input was handled or one of its [`View`](motif_core::View)s
+//! changed or asked for an animation frame. More windows are opened and closed through [`Windows`]:
+//!
+//! ```ignore
+//! let app = App::new();
+//! let windows = app.windows();
+//! app.run(move |_cx| Editor { windows: windows.clone() })
+//!
+//! // Later, e.g. in a click handler:
+//! let inspector = windows.open(WindowOptions::new().title("Inspector"), |_cx| Inspector::new());
+//! windows.close(inspector);
+//! ```
+//!
+//! The app exits when its last window closes. With the `debug` feature the
+//! first window is served to the motif debug server.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+
+use motif_core::{
+    render_view, select_backend, Backend, BackendError, CallbackRegistry, Clock, DirtyFlag,
+    EventDispatcher, FrameProfiler, HitTree, ImageCache, LayoutEngine, Point, Render, ScaleFactor,
+    Scene, Size, Srgba, Tasks, TextContext, Theme, ThemeWatcher, Timers, WindowContext,
+};
+use winit::application::ApplicationHandler;
+use winit::error::{EventLoopError, OsError};
+use winit::event::WindowEvent;
+use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop, EventLoopProxy};
+use winit::window::{Window, WindowId, WindowLevel};
+
+use crate::menu::{self, MenuAction, MenuBar};
+
+/// How the app's window is created.
+#[derive(Clone, Debug)]
+pub struct WindowOptions {
+    pub title: String,
+    /// Initial inner size in logical pixels.
+    pub size: Size,
+    pub resizable: bool,
+    /// Let whatever is behind the window show through where the background
+    /// and content are translucent.
+    pub transparent: bool,
+    /// Color the window is cleared to each frame. Defaults to opaque black,
+    /// or fully transparent for transparent windows.
+    pub background: Option<Srgba>,
+    /// Keep the window above normal windows, e.g. for HUDs and palettes.
+    pub always_on_top: bool,
+    /// Show the title bar and frame. `false` makes a borderless window.
+    pub decorations: bool,
+    /// Extend the content under a transparent, title-less title bar (macOS),
+    /// keeping the window controls.
+    pub full_size_content: bool,
+    /// Where the close, minimize and zoom buttons go, from the window's
+    /// top-left corner in logical pixels (macOS).
+    pub traffic_light_position: Option<Point>,
+    /// Blur what's behind the window. Uses the given material on macOS and
+    /// the compositor's blur, where there is one, elsewhere. Implies
+    /// `transparent`.
+    pub vibrancy: Option<Vibrancy>,
+}
+
+impl Default for WindowOptions {
+    fn default() -> Self {
+        Self {
+            title: "motif".into(),
+            size: Size::new(800.0, 600.0),
+            resizable: true,
+            transparent: false,
+            background: None,
+            always_on_top: false,
+            decorations: true,
+            full_size_content: false,
+            traffic_light_position: None,
+            vibrancy: None,
+        }
+    }
+}
+
+/// Background blur materials, after macOS's `NSVisualEffectMaterial`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Vibrancy {
+    Titlebar,
+    Menu,
+    Popover,
+    Sidebar,
+    HeaderView,
+    Sheet,
+    WindowBackground,
+    HudWindow,
+    FullScreenUi,
+    Tooltip,
+    UnderWindowBackground,
+}
+
+impl WindowOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    pub fn size(mut self, size: Size) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn resizable(mut self, resizable: bool) -> Self {
+        self.resizable = resizable;
+        self
+    }
+
+    pub fn transparent(mut self, transparent: bool) -> Self {
+        self.transparent = transparent;
+        self
+    }
+
+    pub fn background(mut self, color: impl Into<Srgba>) -> Self {
+        self.background = Some(color.into());
+        self
+    }
+
+    pub fn always_on_top(mut self, always_on_top: bool) -> Self {
+        self.always_on_top = always_on_top;
+        self
+    }
+
+    pub fn decorations(mut self, decorations: bool) -> Self {
+        self.decorations = decorations;
+        self
+    }
+
+    pub fn full_size_content(mut self, full_size_content: bool) -> Self {
+        self.full_size_content = full_size_content;
+        self
+    }
+
+    pub fn traffic_light_position(mut self, position: Point) -> Self {
+        self.traffic_light_position = Some(position);
+        self
+    }
+
+    pub fn vibrancy(mut self, vibrancy: Vibrancy) -> Self {
+        self.vibrancy = Some(vibrancy);
+        self
+    }
+
+    fn is_transparent(&self) -> bool {
+        self.transparent || self.vibrancy.is_some()
+    }
+
+    /// The color frames are cleared to.
+    fn clear_color(&self) -> Srgba {
+        match self.background {
+            Some(color) => color,
+            None if self.is_transparent() => Srgba::new(0.0, 0.0, 0.0, 0.0),
+            None => Srgba::new(0.0, 0.0, 0.0, 1.0),
+        }
+    }
+
+    fn attributes(&self) -> winit::window::WindowAttributes {
+        let level = if self.always_on_top {
+            WindowLevel::AlwaysOnTop
+        } else {
+            WindowLevel::Normal
+        };
+        let attributes = Window::default_attributes()
+            .with_title(self.title.clone())
+            .with_inner_size(winit::dpi::LogicalSize::new(
+                self.size.width,
+                self.size.height,
+            ))
+            .with_resizable(self.resizable)
+            .with_transparent(self.is_transparent())
+            .with_blur(self.vibrancy.is_some())
+            .with_decorations(self.decorations)
+            .with_window_level(level);
+
+        #[cfg(target_os = "macos")]
+        let attributes = {
+            use winit::platform::macos::WindowAttributesExtMacOS;
+            attributes
+                .with_fullsize_content_view(self.full_size_content)
+                .with_titlebar_transparent(self.full_size_content)
+                .with_title_hidden(self.full_size_content)
+        };
+        attributes
+    }
+}
+
+/// Why an app stopped running.
+#[derive(Debug)]
+pub enum AppError {
+    EventLoop(EventLoopError),
+    Window(OsError),
+    Backend(BackendError),
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::EventLoop(err) => write!(f, "event loop failed: {err}"),
+            AppError::Window(err) => write!(f, "failed to create window: {err}"),
+            AppError::Backend(err) => write!(f, "failed to create renderer: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl From<EventLoopError> for AppError {
+    fn from(err: EventLoopError) -> Self {
+        AppError::EventLoop(err)
+    }
+}
+
+/// How often a watched theme file is checked for changes.
+const THEME_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Identifies a window opened through [`Windows`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct AppWindowId(u64);
+
+type BuildRoot = Box<dyn FnOnce(&mut WindowContext) -> Box<dyn RootView>>;
+
+struct PendingWindow {
+    id: AppWindowId,
+    options: WindowOptions,
+    build: BuildRoot,
+}
+
+#[derive(Default)]
+struct WindowQueue {
+    next_id: u64,
+    to_open: Vec<PendingWindow>,
+    to_close: Vec<AppWindowId>,
+    open: Vec<AppWindowId>,
+}
+
+impl WindowQueue {
+    /// Take the open and close requests made since the last call, or `None`
+    /// if there are none.
+    fn take_requests(&mut self) -> Option<(Vec<PendingWindow>, Vec<AppWindowId>)> {
+        if self.to_open.is_empty() && self.to_close.is_empty() {
+            return None;
+        }
+        Some((
+            std::mem::take(&mut self.to_open),
+            std::mem::take(&mut self.to_close),
+        ))
+    }
+
+    /// Forget the open windows `still_open` rejects, keeping the rest in
+    /// the order they opened.
+    fn retain_open(&mut self, still_open: impl Fn(AppWindowId) -> bool) {
+        self.open.retain(|&id| still_open(id));
+    }
+
+    /// Whether every window has closed and none are waiting to open, at
+    /// which point the app exits.
+    fn all_closed(&self) -> bool {
+        self.open.is_empty() && self.to_open.is_empty()
+    }
+}
+
+/// Opens and closes the app's windows. Clones share the same windows.
+///
+/// Requests are carried out once the current event has been handled.
+#[derive(Clone, Default)]
+pub struct Windows {
+    queue: Rc<RefCell<WindowQueue>>,
+}
+
+impl Windows {
+    /// Open a window whose root view is created by `build`.
+    pub fn open<V, F>(&self, options: WindowOptions, build: F) -> AppWindowId
+    where
+        V: Render + 'static,
+        F: FnOnce(&mut WindowContext) -> V + 'static,
+    {
+        let mut queue = self.queue.borrow_mut();
+        let id = AppWindowId(queue.next_id);
+        queue.next_id += 1;
+        queue.to_open.push(PendingWindow {
+            id,
+            options,
+            build: Box::new(move |cx| Box::new(build(cx))),
+        });
+        id
+    }
+
+    /// Close the window `id`, if it's open.
+    pub fn close(&self, id: AppWindowId) {
+        self.queue.borrow_mut().to_close.push(id);
+    }
+
+    pub fn is_open(&self, id: AppWindowId) -> bool {
+        self.queue.borrow().open.contains(&id)
+    }
+
+    /// Open windows, oldest first.
+    pub fn ids(&self) -> Vec<AppWindowId> {
+        self.queue.borrow().open.clone()
+    }
+}
+
+impl fmt::Debug for Windows {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let queue = self.queue.borrow();
+        f.debug_struct("Windows")
+            .field("open", &queue.open)
+            .field("pending", &queue.to_open.len())
+            .finish()
+    }
+}
+
+/// A root view with its type erased, so windows with different root views
+/// can live side by side.
+trait RootView {
+    fn render(
+        &mut self,
+        cx: &mut WindowContext,
+        layout_engine: &mut LayoutEngine,
+        hit_tree: &mut HitTree,
+        size: Size,
+    );
+}
+
+impl<V: Render> RootView for V {
+    fn render(
+        &mut self,
+        cx: &mut WindowContext,
+        layout_engine: &mut LayoutEngine,
+        hit_tree: &mut HitTree,
+        size: Size,
+    ) {
+        render_view(self, cx, layout_engine, hit_tree, size);
+    }
+}
+
+/// A motif app.
+#[derive(Debug)]
+pub struct App {
+    window: WindowOptions,
+    theme: Theme,
+    theme_file: Option<PathBuf>,
+    windows: Windows,
+    menu_bar: Option<MenuBar>,
+    menu_handler: Option<MenuHandler>,
+    /// Shared by every window's timers and animations.
+    clock: Clock,
+}
+
+impl Default for App {
+    fn default() -> Self {
+        Self {
+            window: WindowOptions::default(),
+            theme: Theme::default(),
+            theme_file: None,
+            windows: Windows::default(),
+            menu_bar: None,
+            menu_handler: None,
+            clock: Clock::scaled(1.0),
+        }
+    }
+}
+
+/// Handles app-defined menu actions.
+struct MenuHandler(Box<dyn FnMut(&MenuAction)>);
+
+impl fmt::Debug for MenuHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("MenuHandler")
+    }
+}
+
+impl App {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Options for the first window.
+    pub fn window(mut self, options: WindowOptions) -> Self {
+        self.window = options;
+        self
+    }
+
+    /// The theme views read from [`WindowContext::theme`]. Defaults to
+    /// [`Theme::dark`].
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Load the theme from the [theme file](motif_core::theme_file) at
+    /// `path`, and reload it and redraw whenever the file changes, so
+    /// colors and metrics can be tuned without recompiling. Replaces
+    /// [`theme`](Self::theme) once the file loads; errors are printed and
+    /// the previous theme kept.
+    pub fn watch_theme(mut self, path: impl Into<PathBuf>) -> Self {
+        self.theme_file = Some(path.into());
+        self
+    }
+
+    /// Install `bar` as the app's menu bar. Only macOS has one; elsewhere
+    /// it's ignored.
+    pub fn menu_bar(mut self, bar: MenuBar) -> Self {
+        self.menu_bar = Some(bar);
+        self
+    }
+
+    /// Handle [`MenuAction::Named`] menu selections.
+    pub fn on_menu_action(mut self, handler: impl FnMut(&MenuAction) + 'static) -> Self {
+        self.menu_handler = Some(MenuHandler(Box::new(handler)));
+        self
+    }
+
+    /// A handle for opening and closing windows while the app runs.
+    pub fn windows(&self) -> Windows {
+        self.windows.clone()
+    }
+
+    /// The clock every window's timers and animations run on, also
+    /// available to views from [`WindowContext::clock`].
+    pub fn clock(&self) -> Clock {
+        self.clock.clone()
+    }
+
+    /// Run timers and animations at `scale` times normal speed, e.g. `0.1`
+    /// to watch an animation at 10%, or `0` to pause them. Can also be
+    /// changed while running through [`clock`](Self::clock) or the debug
+    /// server's `debug.time_scale`.
+    ///
+    /// # Panics
+    ///
+    /// If `scale` is negative or not finite.
+    pub fn set_time_scale(&self, scale: f64) {
+        self.clock.set_time_scale(scale);
+    }
+
+    /// Open the first window and run until the last one is closed. `build`
+    /// creates the first window's root view once the window exists.
+    pub fn run<V, F>(self, build: F) -> Result<(), AppError>
+    where
+        V: Render + 'static,
+        F: FnOnce(&mut WindowContext) -> V + 'static,
+    {
+        let event_loop = EventLoop::new()?;
+        event_loop.set_control_flow(ControlFlow::Wait);
+        self.windows.open(self.window, build);
+        let mut runner = Runner::new(
+            self.theme,
+            self.clock,
+            self.windows,
+            event_loop.create_proxy(),
+        );
+        runner.theme_watcher = self.theme_file.map(ThemeWatcher::new);
+        runner.menu_bar = self.menu_bar;
+        runner.menu_handler = self.menu_handler;
+        event_loop.run_app(&mut runner)?;
+        match runner.error {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Everything one window renders with.
+struct WindowState {
+    id: AppWindowId,
+    window: Arc<Window>,
+    backend: Box<dyn Backend>,
+    root: Box<dyn RootView>,
+    scene: Scene,
+    layout_engine: LayoutEngine,
+    hit_tree: HitTree,
+    callbacks: CallbackRegistry,
+    dispatcher: EventDispatcher,
+    dirty: DirtyFlag,
+    timers: Timers,
+    tasks: Tasks,
+    profiler: FrameProfiler,
+    /// Quad overrides from the debug server's `scene.patch`.
+    #[cfg(feature = "debug")]
+    quad_patches: Vec<motif_debug::QuadPatch>,
+    /// Debug server overlays painted above the scene.
+    #[cfg(feature = "debug")]
+    overlay_quads: Vec<motif_debug::OverlayQuad>,
+    #[cfg(feature = "debug")]
+    overlay_labels: Vec<motif_debug::OverlayLabel>,
+    #[cfg(target_os = "macos")]
+    traffic_light_position: Option<Point>,
+}
+
+impl WindowState {
+    fn scale_factor(&self) -> ScaleFactor {
+        ScaleFactor(self.window.scale_factor() as f32)
+    }
+
+    /// The window's size in logical pixels.
+    fn logical_size(&self) -> Size {
+        let scale = self.scale_factor();
+        let physical = self.window.inner_size();
+        Size::new(
+            physical.width as f32 / scale.0,
+            physical.height as f32 / scale.0,
+        )
+    }
+
+    fn draw(&mut self, text_ctx: &mut TextContext, theme: &Theme) {
+        self.profiler.begin_frame();
+        // Animations step before the frame that shows them.
+        self.timers.run_frame_callbacks();
+        self.scene.clear();
+        self.hit_tree.clear();
+        self.callbacks.clear();
+        self.dirty.take();
+
+        let scale = self.scale_factor();
+        let size = self.logical_size();
+        let mut cx = WindowContext::new(&mut self.scene, text_ctx, scale)
+            .with_dirty_flag(self.dirty.clone())
+            .with_timers(self.timers.clone())
+            .with_tasks(self.tasks.clone())
+            .with_theme(theme.clone())
+            .with_callbacks(&mut self.callbacks);
+        self.root
+            .render(&mut cx, &mut self.layout_engine, &mut self.hit_tree, size);
+        #[cfg(feature = "debug")]
+        motif_debug::QuadPatch::apply_all(&self.quad_patches, &mut self.scene);
+        #[cfg(feature = "debug")]
+        paint_debug_overlays(
+            &mut self.scene,
+            text_ctx,
+            scale,
+            &self.overlay_quads,
+            &self.overlay_labels,
+        );
+        self.backend.render(&self.scene);
+        self.profiler.end_frame();
+
+        // Elements may have moved under a still pointer.
+        if let Some(cursor) = self.dispatcher.update_cursor(&self.hit_tree) {
+            self.window.set_cursor(cursor);
+        }
+    }
+
+    /// Whether the last frame has debug overlays painted over it, so it
+    /// isn't what the app alone drew.
+    #[cfg(feature = "debug")]
+    fn shows_debug_overlays(&self) -> bool {
+        !self.overlay_quads.is_empty() || !self.overlay_labels.is_empty()
+    }
+
+    /// Build the root view's scene at the scale and size a debug
+    /// `screenshot` asked for, leaving the window's own frame alone.
+    /// Returns the scene with the logical size and scale it was built at.
+    #[cfg(feature = "debug")]
+    fn capture_scene(
+        &mut self,
+        text_ctx: &mut TextContext,
+        theme: &Theme,
+        options: motif_debug::CaptureOptions,
+    ) -> (Scene, Size, ScaleFactor) {
+        let scale = options.scale.map_or(self.scale_factor(), ScaleFactor);
+        let size = options.size.map_or(self.logical_size(), |(width, height)| {
+            Size::new(width, height)
+        });
+        let mut scene = Scene::new();
+        scene.set_background(self.scene.background());
+        let mut layout_engine = LayoutEngine::new();
+        let mut hit_tree = HitTree::new();
+        let mut callbacks = CallbackRegistry::new();
+        let mut cx = WindowContext::new(&mut scene, text_ctx, scale)
+            .with_dirty_flag(self.dirty.clone())
+            .with_timers(self.timers.clone())
+            .with_tasks(self.tasks.clone())
+            .with_theme(theme.clone())
+            .with_callbacks(&mut callbacks);
+        self.root
+            .render(&mut cx, &mut layout_engine, &mut hit_tree, size);
+        (scene, size, scale)
+    }
+
+    /// Dispatch an input event. Returns true if the window should redraw.
+    fn handle_input(&mut self, event: &WindowEvent) -> bool {
+        let hovered = self.dispatcher.input().hovered();
+        let scale = self.scale_factor();
+        let handled = self.dispatcher.handle_window_event(
+            event,
+            scale.0,
+            &self.hit_tree,
+            &mut self.callbacks,
+        );
+        if let Some(cursor) = self.dispatcher.update_cursor(&self.hit_tree) {
+            self.window.set_cursor(cursor);
+        }
+        handled || self.dirty.is_dirty() || self.dispatcher.input().hovered() != hovered
+    }
+}
+
+struct Runner {
+    theme: Theme,
+    clock: Clock,
+    theme_watcher: Option<ThemeWatcher>,
+    /// Wakes the event loop from other threads.
+    proxy: EventLoopProxy<()>,
+    windows: Windows,
+    open: HashMap<WindowId, WindowState>,
+    /// The window with keyboard focus, which Edit menu actions go to.
+    focused: Option<WindowId>,
+    menu_bar: Option<MenuBar>,
+    menu_handler: Option<MenuHandler>,
+    menu_installed: bool,
+    text_ctx: TextContext,
+    error: Option<AppError>,
+    #[cfg(feature = "debug")]
+    debug_server: Option<motif_debug::DebugServer>,
+    /// The window served to the debug server.
+    #[cfg(feature = "debug")]
+    debug_window: Option<WindowId>,
+}
+
+impl Runner {
+    fn new(theme: Theme, clock: Clock, windows: Windows, proxy: EventLoopProxy<()>) -> Self {
+        Self {
+            theme,
+            clock,
+            theme_watcher: None,
+            proxy,
+            windows,
+            open: HashMap::new(),
+            focused: None,
+            menu_bar: None,
+            menu_handler: None,
+            menu_installed: false,
+            text_ctx: TextContext::new(),
+            error: None,
+            #[cfg(feature = "debug")]
+            debug_server: motif_debug::DebugServer::new().ok(),
+            #[cfg(feature = "debug")]
+            debug_window: None,
+        }
+    }
+
+    /// Carry out open and close requests, exiting once no windows remain.
+    fn sync_windows(&mut self, event_loop: &ActiveEventLoop) {
+        loop {
+            let Some((to_open, to_close)) = self.windows.queue.borrow_mut().take_requests() else {
+                break;
+            };
+            for pending in to_open {
+                if let Err(err) = self.open_window(event_loop, pending) {
+                    self.error = Some(err);
+                    event_loop.exit();
+                    return;
+                }
+            }
+            for id in to_close {
+                self.close_window(|state| state.id == id);
+            }
+        }
+        if self.windows.queue.borrow().all_closed() {
+            event_loop.exit();
+        }
+    }
+
+    fn open_window(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        pending: PendingWindow,
+    ) -> Result<(), AppError> {
+        let window = Arc::new(
+            event_loop
+                .create_window(pending.options.attributes())
+                .map_err(AppError::Window)?,
+        );
+        let backend = select_backend(window.clone(), None).map_err(AppError::Backend)?;
+
+        let dirty = DirtyFlag::new();
+        let timers = Timers::with_clock(self.clock.clone());
+        let tasks = Tasks::new();
+        // Wake the event loop when a task finishes so its completion runs.
+        let proxy = self.proxy.clone();
+        tasks.set_waker(move || {
+            let _ = proxy.send_event(());
+        });
+        #[cfg(target_os = "macos")]
+        {
+            if let Some(vibrancy) = pending.options.vibrancy {
+                crate::macos::apply_vibrancy(&window, vibrancy);
+            }
+            if let Some(position) = pending.options.traffic_light_position {
+                crate::macos::position_traffic_lights(&window, position);
+            }
+        }
+
+        let scale = ScaleFactor(window.scale_factor() as f32);
+        let mut scene = Scene::new();
+        scene.set_background(pending.options.clear_color());
+        let root = {
+            let mut cx = WindowContext::new(&mut scene, &mut self.text_ctx, scale)
+                .with_dirty_flag(dirty.clone())
+                .with_timers(timers.clone())
+                .with_tasks(tasks.clone())
+                .with_theme(self.theme.clone());
+            (pending.build)(&mut cx)
+        };
+
+        window.request_redraw();
+        let window_id = window.id();
+        self.windows.queue.borrow_mut().open.push(pending.id);
+        self.open.insert(
+            window_id,
+            WindowState {
+                id: pending.id,
+                window,
+                backend,
+                root,
+                scene,
+                layout_engine: LayoutEngine::new(),
+                hit_tree: HitTree::new(),
+                callbacks: CallbackRegistry::new(),
+                dispatcher: EventDispatcher::new(),
+                dirty,
+                timers,
+                tasks,
+                profiler: FrameProfiler::new(),
+                #[cfg(feature = "debug")]
+                quad_patches: Vec::new(),
+                #[cfg(feature = "debug")]
+                overlay_quads: Vec::new(),
+                #[cfg(feature = "debug")]
+                overlay_labels: Vec::new(),
+                #[cfg(target_os = "macos")]
+                traffic_light_position: pending.options.traffic_light_position,
+            },
+        );
+        #[cfg(feature = "debug")]
+        if self.debug_window.is_none() {
+            self.attach_debug_server(window_id);
+        }
+        self.redraw_on_image_load();
+        Ok(())
+    }
+
+    /// Serve `window_id` to the debug server: snapshots, input state and
+    /// captures come from it from now on.
+    #[cfg(feature = "debug")]
+    fn attach_debug_server(&mut self, window_id: WindowId) {
+        let (Some(debug_server), Some(state)) = (&self.debug_server, self.open.get(&window_id))
+        else {
+            return;
+        };
+        #[cfg(target_os = "macos")]
+        if let Some(id) = motif_core::metal::window_id(&state.window) {
+            debug_server.set_window_id(id);
+        }
+        debug_server.set_renderer_info(state.backend.kind().name(), state.backend.device_name());
+        let redraw_window = Arc::clone(&state.window);
+        debug_server.enable_frame_capture(move || redraw_window.request_redraw());
+        state.window.request_redraw();
+        self.debug_window = Some(window_id);
+    }
+
+    /// Images decode on a background thread; redraw every window when one
+    /// is ready, since nothing else would.
+    fn redraw_on_image_load(&self) {
+        let windows: Vec<_> = self
+            .open
+            .values()
+            .map(|state| Arc::downgrade(&state.window))
+            .collect();
+        ImageCache::global().on_load(move || {
+            for window in windows.iter().filter_map(Weak::upgrade) {
+                window.request_redraw();
+            }
+        });
+    }
+
+    fn close_window(&mut self, matches: impl Fn(&WindowState) -> bool) {
+        self.open.retain(|_, state| !matches(state));
+        let open: Vec<_> = self.open.values().map(|state| state.id).collect();
+        self.windows
+            .queue
+            .borrow_mut()
+            .retain_open(|id| open.contains(&id));
+
+        // Hand the debug server to the oldest window left, so it keeps
+        // getting snapshots.
+        #[cfg(feature = "debug")]
+        if self
+            .debug_window
+            .is_some_and(|id| !self.open.contains_key(&id))
+        {
+            self.debug_window = None;
+            let oldest = self.windows.ids().first().copied();
+            let next = self
+                .open
+                .iter()
+                .find(|(_, state)| Some(state.id) == oldest)
+                .map(|(&window_id, _)| window_id);
+            if let Some(window_id) = next {
+                self.attach_debug_server(window_id);
+            }
+        }
+    }
+
+    /// Pass the debug server what may change with any window event: input
+    /// state, the pointer and the window position. Frames are reported
+    /// separately, once drawn, by [`report_frame`](Self::report_frame).
+    #[cfg(feature = "debug")]
+    fn update_debug_server(&self, window_id: WindowId) {
+        let (Some(debug_server), Some(state)) = (&self.debug_server, self.open.get(&window_id))
+        else {
+            return;
+        };
+        if self.debug_window != Some(window_id) {
+            return;
+        }
+
+        let scale = state.scale_factor();
+        if send_debug_input(debug_server, state.dispatcher.input()) {
+            state.window.request_redraw();
+        }
+        if let Ok(position) = state.window.inner_position() {
+            debug_server.set_window_position(
+                position.x as f32 / scale.0,
+                position.y as f32 / scale.0,
+                scale.0,
+            );
+        }
+    }
+
+    #[cfg(feature = "debug")]
+    fn provide_capture(&mut self, window_id: WindowId) {
+        let (Some(debug_server), Some(state)) = (&self.debug_server, self.open.get_mut(&window_id))
+        else {
+            return;
+        };
+        if self.debug_window != Some(window_id) {
+            return;
+        }
+        for options in debug_server.capture_requests() {
+            let image = if options.is_window_frame() && !state.shows_debug_overlays() {
+                state.backend.render_to_image(&state.scene)
+            } else {
+                let (mut scene, size, scale) =
+                    state.capture_scene(&mut self.text_ctx, &self.theme, options);
+                if options.include_overlays {
+                    motif_debug::screenshot::composite_overlays(
+                        &mut scene,
+                        &debug_server.overlays(),
+                        scale.0,
+                    );
+                }
+                state
+                    .backend
+                    .render_to_image_at(&scene, (size.width, size.height), scale.0)
+            };
+            debug_server.provide_capture_for(options, image);
+        }
+        if debug_server.atlas_requested() {
+            debug_server.provide_atlas(state.backend.dump_glyph_atlas());
+        }
+    }
+
+    /// Pass the frame just drawn to the debug server: its scene, GPU time
+    /// and `scene.profile` timings.
+    #[cfg(feature = "debug")]
+    fn report_frame(&self, window_id: WindowId) {
+        let (Some(debug_server), Some(state)) = (&self.debug_server, self.open.get(&window_id))
+        else {
+            return;
+        };
+        if self.debug_window != Some(window_id) {
+            return;
+        }
+        let physical = state.window.inner_size();
+        let viewport = (physical.width as f32, physical.height as f32);
+        send_debug_frame(debug_server, &state.scene, viewport, state.scale_factor());
+        debug_server.set_gpu_time(state.backend.last_frame_gpu_time());
+        if let Some(profile) = state.profiler.last() {
+            debug_server.push_frame_profile(*profile);
+        }
+        // Keep the frame-rate widget live once it's on screen.
+        if debug_server.fps_overlay_enabled() && state.shows_debug_overlays() {
+            state.window.request_redraw();
+        }
+    }
+}
+
+/// Paint debug server overlay quads and labels into the scene's overlay
+/// layer, above everything the app drew.
+#[cfg(feature = "debug")]
+fn paint_debug_overlays(
+    scene: &mut Scene,
+    text_ctx: &mut TextContext,
+    scale: ScaleFactor,
+    quads: &[motif_debug::OverlayQuad],
+    labels: &[motif_debug::OverlayLabel],
+) {
+    if quads.is_empty() && labels.is_empty() {
+        return;
+    }
+    let mut cx = motif_core::DrawContext::new(scene.overlay_mut(), scale);
+    for overlay in quads {
+        cx.paint(overlay.to_quad(scale.0));
+    }
+    for label in labels {
+        cx.paint_text(
+            &label.text,
+            Point::new(label.x, label.y),
+            12.0,
+            label.color.to_srgba(),
+            text_ctx,
+        );
+    }
+}
+
+/// Snapshot a drawn frame for the debug server, with glyph positions only
+/// for the text run being inspected. Counts towards `debug.fps`.
+#[cfg(feature = "debug")]
+fn send_debug_frame(
+    debug_server: &motif_debug::DebugServer,
+    scene: &Scene,
+    viewport: (f32, f32),
+    scale: ScaleFactor,
+) {
+    let mut snapshot = motif_debug::SceneSnapshot::from_scene(scene, viewport, scale.0);
+    if let Some(index) = debug_server.inspected_text_run() {
+        snapshot.capture_text_glyphs(scene, index);
+    }
+    debug_server.update_scene(snapshot);
+}
+
+/// Pass input state to the debug server after a window event. Returns
+/// whether the crosshair moved.
+#[cfg(feature = "debug")]
+fn send_debug_input(
+    debug_server: &motif_debug::DebugServer,
+    input: &motif_core::input::InputState,
+) -> bool {
+    debug_server.update_input(motif_debug::InputStateSnapshot::from_input_state(input));
+    let pointer = input.cursor_position;
+    debug_server.set_mouse_position(pointer.map(|p| (p.x, p.y)))
+}
+
+impl Runner {
+    /// Route menu items selected since the last call.
+    fn handle_menu_selections(&mut self, event_loop: &ActiveEventLoop) {
+        let Some(bar) = &self.menu_bar else {
+            return;
+        };
+        for tag in menu::take_selections() {
+            let Some(action) = bar.actions().get(tag).map(|&action| action.clone()) else {
+                continue;
+            };
+            match action {
+                MenuAction::Input(action) => {
+                    let Some(state) = self.focused.and_then(|id| self.open.get_mut(&id)) else {
+                        continue;
+                    };
+                    if state
+                        .dispatcher
+                        .handle_input_action(action, &mut state.callbacks)
+                    {
+                        state.window.request_redraw();
+                    }
+                }
+                MenuAction::Quit => event_loop.exit(),
+                action => {
+                    if let Some(handler) = &mut self.menu_handler {
+                        (handler.0)(&action);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Pick up changes to the watched theme file.
+    fn reload_theme(&mut self) {
+        let Some(watcher) = &mut self.theme_watcher else {
+            return;
+        };
+        match watcher.poll() {
+            Some(Ok(theme)) => {
+                self.theme = theme;
+                for state in self.open.values() {
+                    state.dirty.mark();
+                }
+            }
+            Some(Err(err)) => eprintln!("{}: {err}", watcher.path().display()),
+            None => {}
+        }
+    }
+}
+
+impl ApplicationHandler for Runner {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if let (Some(bar), false) = (&self.menu_bar, self.menu_installed) {
+            let proxy = self.proxy.clone();
+            menu::install(bar, move || {
+                let _ = proxy.send_event(());
+            });
+            self.menu_installed = true;
+        }
+        self.sync_windows(event_loop);
+    }
+
+    fn window_event(&mut self, event_loop: &ActiveEventLoop, id: WindowId, event: WindowEvent) {
+        let Some(state) = self.open.get_mut(&id) else {
+            return;
+        };
+        match event {
+            WindowEvent::CloseRequested => self.close_window(|state| state.window.id() == id),
+            WindowEvent::Resized(size) => {
+                state.backend.resize(size.width as f32, size.height as f32);
+                #[cfg(target_os = "macos")]
+                if let Some(position) = state.traffic_light_position {
+                    crate::macos::position_traffic_lights(&state.window, position);
+                }
+                state.window.request_redraw();
+            }
+            WindowEvent::ScaleFactorChanged { .. } => state.window.request_redraw(),
+            WindowEvent::Focused(focused) => {
+                if focused {
+                    self.focused = Some(id);
+                } else if self.focused == Some(id) {
+                    self.focused = None;
+                }
+            }
+            WindowEvent::RedrawRequested => {
+                #[cfg(feature = "debug")]
+                if let (Some(debug_server), true) =
+                    (&self.debug_server, self.debug_window == Some(id))
+                {
+                    state.backend.set_render_mode(debug_server.render_mode());
+                    state.quad_patches = debug_server.patches();
+                    state.overlay_quads = debug_server.overlays();
+                    state.overlay_labels = debug_server.overlay_labels();
+                }
+                state.draw(&mut self.text_ctx, &self.theme);
+                #[cfg(feature = "debug")]
+                self.provide_capture(id);
+                #[cfg(feature = "debug")]
+                self.report_frame(id);
+            }
+            event => {
+                if state.handle_input(&event) {
+                    state.window.request_redraw();
+                }
+            }
+        }
+
+        #[cfg(feature = "debug")]
+        self.update_debug_server(id);
+
+        self.sync_windows(event_loop);
+    }
+
+    /// Run due timers and finished tasks' completions, redraw only windows
+    /// that changed or have a frame due, then sleep until the next timer or
+    /// scheduled frame.
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        self.reload_theme();
+        self.handle_menu_selections(event_loop);
+
+        #[cfg(feature = "debug")]
+        if let Some(scale) = self
+            .debug_server
+            .as_ref()
+            .and_then(|server| server.take_time_scale())
+        {
+            self.clock.set_time_scale(scale);
+        }
+
+        // Timers and scheduled frames are due on the app clock, which may
+        // run slower than the system clock the event loop sleeps on.
+        let now = self.clock.now();
+        let mut next_wake = self
+            .theme_watcher
+            .as_ref()
+            .map(|_| Instant::now() + THEME_POLL_INTERVAL);
+        for state in self.open.values() {
+            state.timers.run_due(now);
+            state.tasks.run_completed();
+            // Handlers may have changed views in other windows too.
+            if state.dirty.needs_redraw(now) || state.timers.has_frame_callbacks() {
+                state.window.request_redraw();
+            }
+            let deadlines = [state.dirty.next_frame(), state.timers.next_deadline()];
+            for at in deadlines
+                .into_iter()
+                .flatten()
+                .filter_map(|at| self.clock.real_instant(at))
+            {
+                next_wake = Some(next_wake.map_or(at, |next| next.min(at)));
+            }
+        }
+        event_loop.set_control_flow(match next_wake {
+            Some(at) => ControlFlow::WaitUntil(at),
+            None => ControlFlow::Wait,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::menu::Menu;
+    use motif_core::{
+        div, text_input, ElementId, InputAction, IntoElement, ParentElement, Rect, ViewContext,
+    };
+    use motif_test::TestWindow;
+    use winit::dpi::PhysicalPosition;
+    use winit::event::{DeviceId, ElementState, MouseButton};
+
+    const INPUT: ElementId = ElementId(1);
+
+    struct Blank;
+
+    impl Render for Blank {
+        fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+            div()
+        }
+    }
+
+    #[test]
+    fn clear_color_follows_background_and_transparency() {
+        let opaque = Srgba::new(0.0, 0.0, 0.0, 1.0);
+        let clear = Srgba::new(0.0, 0.0, 0.0, 0.0);
+        let red = Srgba::new(1.0, 0.0, 0.0, 0.5);
+
+        let options = WindowOptions::new();
+        assert!(!options.is_transparent());
+        assert_eq!(options.clear_color(), opaque);
+
+        let options = WindowOptions::new().transparent(true);
+        assert!(options.is_transparent());
+        assert_eq!(options.clear_color(), clear);
+
+        let options = WindowOptions::new().vibrancy(Vibrancy::Sidebar);
+        assert!(options.is_transparent(), "vibrancy implies transparent");
+        assert_eq!(options.clear_color(), clear);
+
+        let options = WindowOptions::new().transparent(true).background(red);
+        assert_eq!(options.clear_color(), red);
+        assert_eq!(WindowOptions::new().background(red).clear_color(), red);
+    }
+
+    #[test]
+    fn attributes_carry_window_options() {
+        let attributes = WindowOptions::new().attributes();
+        assert_eq!(attributes.title, "motif");
+        assert_eq!(
+            attributes.inner_size,
+            Some(winit::dpi::LogicalSize::new(800.0, 600.0).into())
+        );
+        assert!(attributes.resizable);
+        assert!(attributes.decorations);
+        assert!(!attributes.transparent);
+        assert!(!attributes.blur);
+        assert_eq!(attributes.window_level, WindowLevel::Normal);
+
+        let attributes = WindowOptions::new()
+            .title("palette")
+            .size(Size::new(320.0, 240.0))
+            .resizable(false)
+            .decorations(false)
+            .always_on_top(true)
+            .vibrancy(Vibrancy::HudWindow)
+            .attributes();
+        assert_eq!(attributes.title, "palette");
+        assert_eq!(
+            attributes.inner_size,
+            Some(winit::dpi::LogicalSize::new(320.0, 240.0).into())
+        );
+        assert!(!attributes.resizable);
+        assert!(!attributes.decorations);
+        assert!(attributes.transparent);
+        assert!(attributes.blur);
+        assert_eq!(attributes.window_level, WindowLevel::AlwaysOnTop);
+    }
+
+    #[test]
+    fn exits_once_the_last_window_closes() {
+        let windows = Windows::default();
+        assert!(windows.queue.borrow().all_closed());
+
+        let first = windows.open(WindowOptions::new(), |_| Blank);
+        let second = windows.open(WindowOptions::new(), |_| Blank);
+        assert!(
+            !windows.queue.borrow().all_closed(),
+            "windows waiting to open keep the app alive"
+        );
+        let (to_open, _) = windows.queue.borrow_mut().take_requests().unwrap();
+        windows
+            .queue
+            .borrow_mut()
+            .open
+            .extend(to_open.iter().map(|p| p.id));
+
+        for (id, last) in [(first, false), (second, true)] {
+            windows.close(id);
+            let (_, to_close) = windows.queue.borrow_mut().take_requests().unwrap();
+            let mut queue = windows.queue.borrow_mut();
+            queue.retain_open(|id| !to_close.contains(&id));
+            assert_eq!(queue.all_closed(), last);
+        }
+    }
+
+    #[cfg(feature = "debug")]
+    #[test]
+    fn only_drawn_frames_count_as_debug_frames() {
+        let path = std::env::temp_dir().join(format!("motif-app-{}.sock", std::process::id()));
+        let server = motif_debug::DebugServer::with_path(path).unwrap();
+        let mut window = TestWindow::new(Size::new(200.0, 100.0), |_| Blank);
+        window.draw();
+
+        window.move_mouse(Point::new(10.0, 10.0));
+        send_debug_input(&server, window.dispatcher().input());
+        window.move_mouse(Point::new(20.0, 10.0));
+        window.press_named_key(winit::keyboard::NamedKey::Tab);
+        send_debug_input(&server, window.dispatcher().input());
+        assert_eq!(server.frame_count(), 0, "input events aren't frames");
+
+        send_debug_frame(
+            &server,
+            window.scene(),
+            (200.0, 100.0),
+            window.scale_factor(),
+        );
+        assert_eq!(server.frame_count(), 1);
+    }
+
+    #[cfg(feature = "debug")]
+    #[test]
+    fn debug_overlays_paint_above_the_scene() {
+        use motif_debug::snapshot::ColorInfo;
+
+        let mut window = TestWindow::new(Size::new(100.0, 100.0), |_| Blank);
+        window.draw();
+        assert_eq!(window.pixel(Point::new(15.0, 15.0)), [0, 0, 0, 255]);
+
+        let red = ColorInfo {
+            r: 1.0,
+            g: 0.0,
+            b: 0.0,
+            a: 1.0,
+        };
+        let overlay = motif_debug::OverlayQuad {
+            id: 0,
+            x: 10.0,
+            y: 10.0,
+            w: 20.0,
+            h: 20.0,
+            color: red.clone(),
+            border_color: red,
+            border_width: 0.0,
+            corner_radius: 0.0,
+        };
+        let scale = window.scale_factor();
+        let mut text_ctx = TextContext::new();
+        paint_debug_overlays(window.scene_mut(), &mut text_ctx, scale, &[overlay], &[]);
+        assert_eq!(window.scene().overlay().unwrap().quads().len(), 1);
+        assert_eq!(window.pixel(Point::new(15.0, 15.0)), [255, 0, 0, 255]);
+        assert_eq!(window.pixel(Point::new(35.0, 15.0)), [0, 0, 0, 255]);
+    }
+
+    #[cfg(feature = "debug")]
+    #[test]
+    fn crosshair_moves_with_the_pointer_into_the_painted_frame() {
+        use std::io::{BufRead, BufReader, Write};
+        use std::os::unix::net::UnixStream;
+
+        let path =
+            std::env::temp_dir().join(format!("motif-app-crosshair-{}.sock", std::process::id()));
+        let server = motif_debug::DebugServer::with_path(path.clone()).unwrap();
+        let mut stream = UnixStream::connect(&path).unwrap();
+        writeln!(
+            stream,
+            r#"{{"method":"debug.crosshair","params":{{"on":true}},"id":1}}"#
+        )
+        .unwrap();
+        let mut response = String::new();
+        BufReader::new(&stream).read_line(&mut response).unwrap();
+        assert!(response.contains(r#""on":true"#), "{response}");
+
+        let mut window = TestWindow::new(Size::new(100.0, 100.0), |_| Blank);
+        window.draw();
+        let scale = window.scale_factor();
+        send_debug_frame(&server, window.scene(), (100.0, 100.0), scale);
+
+        window.move_mouse(Point::new(40.0, 60.0));
+        assert!(
+            send_debug_input(&server, window.dispatcher().input()),
+            "a moved crosshair needs a frame"
+        );
+        assert!(!send_debug_input(&server, window.dispatcher().input()));
+
+        paint_debug_overlays(
+            window.scene_mut(),
+            &mut TextContext::new(),
+            scale,
+            &server.overlays(),
+            &server.overlay_labels(),
+        );
+        let on_line = window.pixel(Point::new(10.0, 60.0));
+        assert!(on_line[0] > 0 && on_line[2] > 0, "{on_line:?}");
+        assert_eq!(window.pixel(Point::new(10.0, 10.0)), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn windows_track_requests_and_open_ids() {
+        let windows = Windows::default();
+        let first = windows.open(WindowOptions::new(), |_| Blank);
+        let second = windows.open(WindowOptions::new().title("second"), |_| Blank);
+        assert_ne!(first, second);
+        assert!(
+            !windows.is_open(first),
+            "not open until the runner opens it"
+        );
+
+        let mut queue = windows.queue.borrow_mut();
+        let (to_open, to_close) = queue.take_requests().unwrap();
+        assert_eq!(
+            to_open.iter().map(|p| p.id).collect::<Vec<_>>(),
+            vec![first, second]
+        );
+        assert_eq!(to_open[1].options.title, "second");
+        assert!(to_close.is_empty());
+        assert!(queue.take_requests().is_none());
+        queue.open.extend([first, second]);
+        drop(queue);
+        assert_eq!(windows.ids(), vec![first, second]);
+
+        // Clones share the queue.
+        windows.clone().close(first);
+        let (to_open, to_close) = windows.queue.borrow_mut().take_requests().unwrap();
+        assert!(to_open.is_empty());
+        assert_eq!(to_close, vec![first]);
+        windows
+            .queue
+            .borrow_mut()
+            .retain_open(|id| !to_close.contains(&id));
+        assert!(!windows.is_open(first));
+        assert!(windows.is_open(second));
+        assert_eq!(windows.ids(), vec![second]);
+    }
+
+    /// A text input recording the editing actions it receives.
+    struct Editor {
+        received: Rc<RefCell<Vec<InputAction>>>,
+    }
+
+    impl Render for Editor {
+        fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+            let received = self.received.clone();
+            div().child(
+                text_input("", INPUT)
+                    .bounds(Rect::new(Point::new(0.0, 0.0), Size::new(200.0, 32.0)))
+                    .on_input_action(move |action| received.borrow_mut().push(action)),
+            )
+        }
+    }
+
+    #[test]
+    fn edit_menu_actions_reach_a_clicked_text_input() {
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let mut window = TestWindow::with_scale_factor(Size::new(300.0, 100.0), 2.0, |_| Editor {
+            received: received.clone(),
+        });
+        window.draw();
+
+        // Look the item up by tag, as `handle_menu_selections` does.
+        let bar = MenuBar::new().menu(Menu::edit());
+        let paste = bar
+            .actions()
+            .into_iter()
+            .position(|action| *action == MenuAction::Input(InputAction::Paste))
+            .unwrap();
+        let send = |window: &mut TestWindow<Editor>| match bar.actions()[paste] {
+            MenuAction::Input(action) => window.input_action(*action),
+            _ => unreachable!(),
+        };
+        assert!(!send(&mut window), "nothing is focused before a click");
+
+        // The runner feeds winit events in physical pixels.
+        let device_id = DeviceId::dummy();
+        window.handle_window_event(&WindowEvent::CursorMoved {
+            device_id,
+            position: PhysicalPosition::new(40.0, 20.0),
+        });
+        for state in [ElementState::Pressed, ElementState::Released] {
+            window.handle_window_event(&WindowEvent::MouseInput {
+                device_id,
+                state,
+                button: MouseButton::Left,
+            });
+        }
+        assert_eq!(window.dispatcher().focused(), Some(INPUT));
+
+        assert!(send(&mut window));
+        assert_eq!(*received.borrow(), vec![InputAction::Paste]);
+    }
+}
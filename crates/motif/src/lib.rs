@@ -1 +1,11 @@
 pub use motif_core::*;
+
+mod access_host;
+mod app;
+#[cfg(target_os = "macos")]
+mod macos;
+mod menu;
+
+pub use access_host::AccessibilityHost;
+pub use app::{App, AppError, AppWindowId, Vibrancy, WindowOptions, Windows};
+pub use menu::{Menu, MenuAction, MenuBar, MenuItem};
@@ -0,0 +1,107 @@
+//! AppKit window tweaks winit doesn't cover.
+
+use motif_core::Point;
+use objc2::rc::Retained;
+use objc2::Message;
+use objc2_app_kit::{
+    NSAutoresizingMaskOptions, NSView, NSVisualEffectBlendingMode, NSVisualEffectMaterial,
+    NSVisualEffectState, NSVisualEffectView, NSWindowButton, NSWindowOrderingMode,
+};
+use objc2_foundation::{MainThreadMarker, NSPoint, NSRect};
+use winit::raw_window_handle::{HasWindowHandle, RawWindowHandle};
+use winit::window::Window;
+
+use crate::Vibrancy;
+
+fn ns_view(window: &Window) -> Option<Retained<NSView>> {
+    let handle = window.window_handle().ok()?;
+    let RawWindowHandle::AppKit(handle) = handle.as_raw() else {
+        return None;
+    };
+    let ns_view: &NSView = unsafe { (handle.ns_view.as_ptr() as *const NSView).as_ref()? };
+    Some(ns_view.retain())
+}
+
+/// Move the close, minimize and zoom buttons so the first sits at
+/// `position` from the window's top-left corner. AppKit lays them out again
+/// on resize, so this is repeated then.
+pub(crate) fn position_traffic_lights(window: &Window, position: Point) {
+    let Some(ns_window) = ns_view(window).and_then(|view| view.window()) else {
+        return;
+    };
+    let buttons = [
+        NSWindowButton::NSWindowCloseButton,
+        NSWindowButton::NSWindowMiniaturizeButton,
+        NSWindowButton::NSWindowZoomButton,
+    ]
+    .map(|kind| ns_window.standardWindowButton(kind));
+    let [Some(close), Some(miniaturize), Some(zoom)] = buttons else {
+        return;
+    };
+    // The buttons live in the title bar's container view; grow it to reach
+    // the requested position, then place them inside it.
+    unsafe {
+        let Some(container) = close.superview().and_then(|view| view.superview()) else {
+            return;
+        };
+        let (x, y) = (position.x as f64, position.y as f64);
+        let mut frame: NSRect = container.frame();
+        frame.size.height = close.frame().size.height + y;
+        frame.origin.y = ns_window.frame().size.height - frame.size.height;
+        container.setFrame(frame);
+
+        let spacing = miniaturize.frame().origin.x - close.frame().origin.x;
+        for (index, button) in [close, miniaturize, zoom].iter().enumerate() {
+            let origin = NSPoint::new(x + index as f64 * spacing, button.frame().origin.y);
+            button.setFrameOrigin(origin);
+        }
+    }
+}
+
+/// Put a blurred `NSVisualEffectView` behind the window's content.
+pub(crate) fn apply_vibrancy(window: &Window, vibrancy: Vibrancy) {
+    let Some(mtm) = MainThreadMarker::new() else {
+        return;
+    };
+    let Some(ns_view) = ns_view(window) else {
+        return;
+    };
+    let Some(superview) = (unsafe { ns_view.superview() }) else {
+        return;
+    };
+    let effect = unsafe { NSVisualEffectView::initWithFrame(mtm.alloc(), ns_view.frame()) };
+    unsafe {
+        effect.setMaterial(material(vibrancy));
+        effect.setBlendingMode(NSVisualEffectBlendingMode::NSVisualEffectBlendingModeBehindWindow);
+        effect.setState(NSVisualEffectState::NSVisualEffectStateActive);
+        effect.setAutoresizingMask(
+            NSAutoresizingMaskOptions::NSViewWidthSizable
+                | NSAutoresizingMaskOptions::NSViewHeightSizable,
+        );
+        superview.addSubview_positioned_relativeTo(
+            &effect,
+            NSWindowOrderingMode::NSWindowBelow,
+            Some(&ns_view),
+        );
+    }
+}
+
+fn material(vibrancy: Vibrancy) -> NSVisualEffectMaterial {
+    match vibrancy {
+        Vibrancy::Titlebar => NSVisualEffectMaterial::NSVisualEffectMaterialTitlebar,
+        Vibrancy::Menu => NSVisualEffectMaterial::NSVisualEffectMaterialMenu,
+        Vibrancy::Popover => NSVisualEffectMaterial::NSVisualEffectMaterialPopover,
+        Vibrancy::Sidebar => NSVisualEffectMaterial::NSVisualEffectMaterialSidebar,
+        Vibrancy::HeaderView => NSVisualEffectMaterial::NSVisualEffectMaterialHeaderView,
+        Vibrancy::Sheet => NSVisualEffectMaterial::NSVisualEffectMaterialSheet,
+        Vibrancy::WindowBackground => {
+            NSVisualEffectMaterial::NSVisualEffectMaterialWindowBackground
+        }
+        Vibrancy::HudWindow => NSVisualEffectMaterial::NSVisualEffectMaterialHUDWindow,
+        Vibrancy::FullScreenUi => NSVisualEffectMaterial::NSVisualEffectMaterialFullScreenUI,
+        Vibrancy::Tooltip => NSVisualEffectMaterial::NSVisualEffectMaterialToolTip,
+        Vibrancy::UnderWindowBackground => {
+            NSVisualEffectMaterial::NSVisualEffectMaterialUnderWindowBackground
+        }
+    }
+}
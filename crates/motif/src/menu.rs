@@ -0,0 +1,341 @@
+//! The application menu bar.
+//!
+//! ```ignore
+//! let menus = MenuBar::new()
+//!     .menu(Menu::app("Notes"))
+//!     .menu(
+//!         Menu::new("File")
+//!             .item_with_key("New Note", "n", MenuAction::named("new-note"))
+//!             .separator()
+//!             .item("Export…", MenuAction::named("export")),
+//!     )
+//!     .menu(Menu::edit());
+//!
+//! App::new()
+//!     .menu_bar(menus)
+//!     .on_menu_action(|action| println!("{action:?}"))
+//!     .run(|_cx| Notes::default())
+//! ```
+//!
+//! On macOS the bar becomes the app's `NSMenu`. Selecting an item sends its
+//! [`MenuAction`] to the app: [`MenuAction::Input`] actions (the standard
+//! Edit menu) go to the focused element's
+//! [`on_input_action`](motif_core::CallbackRegistry::on_input_action)
+//! handler, [`MenuAction::Quit`] exits, and the rest reach the
+//! app's [`on_menu_action`](crate::App::on_menu_action) handler. Other
+//! platforms have no global menu bar and ignore it.
+
+use motif_core::{ArcStr, InputAction};
+
+/// What selecting a menu item does.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MenuAction {
+    /// An app-defined action, identified by name.
+    Named(ArcStr),
+    /// A text-editing action for the focused element.
+    Input(InputAction),
+    /// Quit the app.
+    Quit,
+}
+
+impl MenuAction {
+    pub fn named(name: impl Into<ArcStr>) -> Self {
+        MenuAction::Named(name.into())
+    }
+}
+
+/// An entry in a [`Menu`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum MenuItem {
+    Action {
+        label: ArcStr,
+        action: MenuAction,
+        /// Key that selects the item with Cmd held, e.g. `"s"`; an uppercase
+        /// letter adds Shift.
+        key: Option<ArcStr>,
+    },
+    Separator,
+    Submenu(Menu),
+}
+
+/// A titled list of items.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Menu {
+    pub title: ArcStr,
+    pub items: Vec<MenuItem>,
+}
+
+impl Menu {
+    pub fn new(title: impl Into<ArcStr>) -> Self {
+        Self {
+            title: title.into(),
+            items: Vec::new(),
+        }
+    }
+
+    /// The application menu, with Quit. macOS titles the first menu with the
+    /// app's name whatever it's called.
+    pub fn app(name: impl Into<ArcStr>) -> Self {
+        let name = name.into();
+        Menu::new(name.clone()).item_with_key(format!("Quit {name}"), "q", MenuAction::Quit)
+    }
+
+    /// The standard Edit menu, sending its actions to the focused element.
+    pub fn edit() -> Self {
+        Menu::new("Edit")
+            .item_with_key("Undo", "z", MenuAction::Input(InputAction::Undo))
+            .item_with_key("Redo", "Z", MenuAction::Input(InputAction::Redo))
+            .separator()
+            .item_with_key("Cut", "x", MenuAction::Input(InputAction::Cut))
+            .item_with_key("Copy", "c", MenuAction::Input(InputAction::Copy))
+            .item_with_key("Paste", "v", MenuAction::Input(InputAction::Paste))
+            .item_with_key("Select All", "a", MenuAction::Input(InputAction::SelectAll))
+    }
+
+    pub fn item(mut self, label: impl Into<ArcStr>, action: MenuAction) -> Self {
+        self.items.push(MenuItem::Action {
+            label: label.into(),
+            action,
+            key: None,
+        });
+        self
+    }
+
+    /// Add an item with a key equivalent; see [`MenuItem::Action`].
+    pub fn item_with_key(
+        mut self,
+        label: impl Into<ArcStr>,
+        key: impl Into<ArcStr>,
+        action: MenuAction,
+    ) -> Self {
+        self.items.push(MenuItem::Action {
+            label: label.into(),
+            action,
+            key: Some(key.into()),
+        });
+        self
+    }
+
+    pub fn separator(mut self) -> Self {
+        self.items.push(MenuItem::Separator);
+        self
+    }
+
+    pub fn submenu(mut self, menu: Menu) -> Self {
+        self.items.push(MenuItem::Submenu(menu));
+        self
+    }
+}
+
+/// The menus along the top of the screen, in order.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MenuBar {
+    pub menus: Vec<Menu>,
+}
+
+impl MenuBar {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn menu(mut self, menu: Menu) -> Self {
+        self.menus.push(menu);
+        self
+    }
+
+    /// Every item's action, depth first. An item's index here is the tag the
+    /// platform menu reports when it's selected.
+    pub fn actions(&self) -> Vec<&MenuAction> {
+        fn collect<'a>(items: &'a [MenuItem], out: &mut Vec<&'a MenuAction>) {
+            for item in items {
+                match item {
+                    MenuItem::Action { action, .. } => out.push(action),
+                    MenuItem::Separator => {}
+                    MenuItem::Submenu(menu) => collect(&menu.items, out),
+                }
+            }
+        }
+        let mut actions = Vec::new();
+        for menu in &self.menus {
+            collect(&menu.items, &mut actions);
+        }
+        actions
+    }
+}
+
+/// Make `bar` the app's menu bar. Selections are queued for
+/// [`take_selections`] and `wake` is called so the event loop picks them up.
+#[cfg(target_os = "macos")]
+pub(crate) fn install(bar: &MenuBar, wake: impl Fn() + 'static) {
+    macos::install(bar, Box::new(wake));
+}
+
+#[cfg(not(target_os = "macos"))]
+pub(crate) fn install(_bar: &MenuBar, _wake: impl Fn() + 'static) {}
+
+/// Tags of the items selected since the last call, in order.
+#[cfg(target_os = "macos")]
+pub(crate) fn take_selections() -> Vec<usize> {
+    macos::SELECTED.with(|selected| std::mem::take(&mut *selected.borrow_mut()))
+}
+
+#[cfg(not(target_os = "macos"))]
+pub(crate) fn take_selections() -> Vec<usize> {
+    Vec::new()
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use std::cell::{Cell, RefCell};
+
+    use objc2::rc::Retained;
+    use objc2::runtime::NSObject;
+    use objc2::{declare_class, msg_send_id, mutability, sel, ClassType, DeclaredClass};
+    use objc2_app_kit::{NSApplication, NSEventModifierFlags, NSMenu, NSMenuItem};
+    use objc2_foundation::{MainThreadMarker, NSString};
+
+    use super::{MenuBar, MenuItem};
+
+    thread_local! {
+        /// Tags of selected items, drained by the app.
+        pub(super) static SELECTED: RefCell<Vec<usize>> = const { RefCell::new(Vec::new()) };
+        static WAKE: Cell<Option<&'static dyn Fn()>> = const { Cell::new(None) };
+        /// Keeps the items' target alive; NSMenuItem holds it weakly.
+        static TARGET: RefCell<Option<Retained<MenuTarget>>> = const { RefCell::new(None) };
+    }
+
+    declare_class!(
+        struct MenuTarget;
+
+        unsafe impl ClassType for MenuTarget {
+            type Super = NSObject;
+            type Mutability = mutability::MainThreadOnly;
+            const NAME: &'static str = "MotifMenuTarget";
+        }
+
+        impl DeclaredClass for MenuTarget {}
+
+        unsafe impl MenuTarget {
+            #[method(menuItemSelected:)]
+            fn menu_item_selected(&self, item: &NSMenuItem) {
+                let tag = unsafe { item.tag() } as usize;
+                SELECTED.with(|selected| selected.borrow_mut().push(tag));
+                if let Some(wake) = WAKE.get() {
+                    wake();
+                }
+            }
+        }
+    );
+
+    impl MenuTarget {
+        fn new(mtm: MainThreadMarker) -> Retained<Self> {
+            let this = mtm.alloc::<Self>().set_ivars(());
+            unsafe { msg_send_id![super(this), init] }
+        }
+    }
+
+    pub(super) fn install(bar: &MenuBar, wake: Box<dyn Fn()>) {
+        let Some(mtm) = MainThreadMarker::new() else {
+            return;
+        };
+        WAKE.set(Some(Box::leak(wake)));
+        let target = MenuTarget::new(mtm);
+        let mut next_tag = 0;
+
+        let main = NSMenu::new(mtm);
+        for menu in &bar.menus {
+            let item = NSMenuItem::new(mtm);
+            item.setSubmenu(Some(&build(
+                mtm,
+                &menu.title,
+                &menu.items,
+                &target,
+                &mut next_tag,
+            )));
+            main.addItem(&item);
+        }
+        NSApplication::sharedApplication(mtm).setMainMenu(Some(&main));
+        TARGET.with(|slot| *slot.borrow_mut() = Some(target));
+    }
+
+    /// Build an NSMenu, tagging action items in the order of
+    /// [`MenuBar::actions`].
+    fn build(
+        mtm: MainThreadMarker,
+        title: &str,
+        items: &[MenuItem],
+        target: &MenuTarget,
+        next_tag: &mut usize,
+    ) -> Retained<NSMenu> {
+        let menu = unsafe { NSMenu::initWithTitle(mtm.alloc(), &NSString::from_str(title)) };
+        for item in items {
+            match item {
+                MenuItem::Action { label, key, .. } => {
+                    let key = key.as_deref().unwrap_or("");
+                    let ns_item = unsafe {
+                        NSMenuItem::initWithTitle_action_keyEquivalent(
+                            mtm.alloc(),
+                            &NSString::from_str(label),
+                            Some(sel!(menuItemSelected:)),
+                            &NSString::from_str(key),
+                        )
+                    };
+                    unsafe {
+                        ns_item.setTarget(Some(target));
+                        ns_item.setTag(*next_tag as isize);
+                    }
+                    if !key.is_empty() {
+                        ns_item.setKeyEquivalentModifierMask(
+                            NSEventModifierFlags::NSEventModifierFlagCommand,
+                        );
+                    }
+                    *next_tag += 1;
+                    menu.addItem(&ns_item);
+                }
+                MenuItem::Separator => menu.addItem(&NSMenuItem::separatorItem(mtm)),
+                MenuItem::Submenu(submenu) => {
+                    let ns_item = NSMenuItem::new(mtm);
+                    unsafe {
+                        ns_item.setTitle(&NSString::from_str(&submenu.title));
+                    }
+                    ns_item.setSubmenu(Some(&build(
+                        mtm,
+                        &submenu.title,
+                        &submenu.items,
+                        target,
+                        next_tag,
+                    )));
+                    menu.addItem(&ns_item);
+                }
+            }
+        }
+        menu
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn actions_are_numbered_depth_first() {
+        let bar = MenuBar::new()
+            .menu(Menu::app("Notes"))
+            .menu(
+                Menu::new("File")
+                    .item("New", MenuAction::named("new"))
+                    .separator()
+                    .submenu(Menu::new("Recent").item("a.txt", MenuAction::named("open-a")))
+                    .item("Close", MenuAction::named("close")),
+            )
+            .menu(Menu::edit());
+
+        let actions = bar.actions();
+        assert_eq!(actions[0], &MenuAction::Quit);
+        assert_eq!(actions[1], &MenuAction::named("new"));
+        assert_eq!(actions[2], &MenuAction::named("open-a"));
+        assert_eq!(actions[3], &MenuAction::named("close"));
+        assert_eq!(actions[6], &MenuAction::Input(InputAction::Cut));
+        assert_eq!(actions.len(), 10);
+    }
+}
@@ -0,0 +1,61 @@
+//! Precompiles the Metal shaders into a `.metallib` on macOS.
+//!
+//! When the Metal toolchain is available (full Xcode, not just the Command
+//! Line Tools), the library is written to `OUT_DIR` and the `motif_metallib`
+//! cfg is set so the renderer embeds it. Otherwise the renderer falls back to
+//! compiling `shaders.metal` at runtime.
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const SHADER_PATH: &str = "src/metal/shaders.metal";
+
+fn main() {
+    println!("cargo:rustc-check-cfg=cfg(motif_metallib)");
+    println!("cargo:rerun-if-changed={SHADER_PATH}");
+    println!("cargo:rerun-if-env-changed=MOTIF_RUNTIME_SHADERS");
+
+    if env::var("CARGO_CFG_TARGET_OS").as_deref() != Ok("macos") {
+        return;
+    }
+    // Opt out of precompilation and always compile shaders at runtime.
+    if env::var_os("MOTIF_RUNTIME_SHADERS").is_some() {
+        return;
+    }
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR").expect("OUT_DIR is set by cargo"));
+    match compile_metallib(Path::new(SHADER_PATH), &out_dir) {
+        Ok(()) => println!("cargo:rustc-cfg=motif_metallib"),
+        Err(e) => println!("cargo:warning=Metal shaders will be compiled at runtime: {e}"),
+    }
+}
+
+/// Compile `source` to `shaders.air` and link it into `shaders.metallib`.
+fn compile_metallib(source: &Path, out_dir: &Path) -> Result<(), String> {
+    let air = out_dir.join("shaders.air");
+    let metallib = out_dir.join("shaders.metallib");
+
+    run(Command::new("xcrun")
+        .args(["-sdk", "macosx", "metal", "-c"])
+        .arg(source)
+        .arg("-o")
+        .arg(&air))?;
+    run(Command::new("xcrun")
+        .args(["-sdk", "macosx", "metallib"])
+        .arg(&air)
+        .arg("-o")
+        .arg(&metallib))
+}
+
+fn run(command: &mut Command) -> Result<(), String> {
+    let output = command
+        .output()
+        .map_err(|e| format!("failed to run xcrun: {e}"))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(stderr.lines().next().unwrap_or("xcrun failed").to_string())
+    }
+}
@@ -1,12 +1,36 @@
 //! Accessibility types for screen reader support via AccessKit.
-
-use crate::Rect;
+//!
+//! Elements add nodes to an [`AccessTree`] as they paint when the paint
+//! context has one (see [`PaintContext::with_access_tree`](crate::PaintContext::with_access_tree)):
+//! divs with click handlers become buttons, text becomes labels, and so on.
+//! `.accessibility_label()` and `.accessibility_hidden()` override what an
+//! element reports.
+
+use crate::{ElementId, Rect};
 use std::collections::HashMap;
+use std::ops::RangeInclusive;
 
 /// Unique identifier for an accessible element.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct AccessId(pub u64);
 
+impl AccessId {
+    /// Set on ids generated for elements without an [`ElementId`], so they
+    /// never collide with ids derived from one.
+    pub const GENERATED: u64 = 1 << 63;
+
+    /// The element this node was created for, unless its id was generated.
+    pub fn element_id(self) -> Option<ElementId> {
+        (self.0 & Self::GENERATED == 0).then_some(ElementId(self.0))
+    }
+}
+
+impl From<ElementId> for AccessId {
+    fn from(id: ElementId) -> Self {
+        AccessId(id.0)
+    }
+}
+
 impl From<AccessId> for accesskit::NodeId {
     fn from(id: AccessId) -> Self {
         accesskit::NodeId(id.0)
@@ -17,9 +41,39 @@ impl From<AccessId> for accesskit::NodeId {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AccessRole {
     Button,
+    Cell,
+    CheckBox,
+    ComboBox,
+    Dialog,
     Group,
+    Heading,
+    Image,
     Label,
+    Link,
+    List,
+    ListItem,
+    Menu,
+    MenuBar,
+    MenuItem,
+    Paragraph,
+    ProgressIndicator,
+    RadioButton,
+    RadioGroup,
+    Row,
+    ScrollBar,
+    ScrollView,
+    Slider,
+    Splitter,
+    Switch,
+    Tab,
+    TabList,
+    TabPanel,
+    Table,
     TextInput,
+    Toolbar,
+    Tooltip,
+    Tree,
+    TreeItem,
     Window,
 }
 
@@ -27,14 +81,107 @@ impl From<AccessRole> for accesskit::Role {
     fn from(role: AccessRole) -> Self {
         match role {
             AccessRole::Button => accesskit::Role::Button,
+            AccessRole::Cell => accesskit::Role::Cell,
+            AccessRole::CheckBox => accesskit::Role::CheckBox,
+            AccessRole::ComboBox => accesskit::Role::ComboBox,
+            AccessRole::Dialog => accesskit::Role::Dialog,
             AccessRole::Group => accesskit::Role::Group,
+            AccessRole::Heading => accesskit::Role::Heading,
+            AccessRole::Image => accesskit::Role::Image,
             AccessRole::Label => accesskit::Role::Label,
+            AccessRole::Link => accesskit::Role::Link,
+            AccessRole::List => accesskit::Role::List,
+            AccessRole::ListItem => accesskit::Role::ListItem,
+            AccessRole::Menu => accesskit::Role::Menu,
+            AccessRole::MenuBar => accesskit::Role::MenuBar,
+            AccessRole::MenuItem => accesskit::Role::MenuItem,
+            AccessRole::Paragraph => accesskit::Role::Paragraph,
+            AccessRole::ProgressIndicator => accesskit::Role::ProgressIndicator,
+            AccessRole::RadioButton => accesskit::Role::RadioButton,
+            AccessRole::RadioGroup => accesskit::Role::RadioGroup,
+            AccessRole::Row => accesskit::Role::Row,
+            AccessRole::ScrollBar => accesskit::Role::ScrollBar,
+            AccessRole::ScrollView => accesskit::Role::ScrollView,
+            AccessRole::Slider => accesskit::Role::Slider,
+            AccessRole::Splitter => accesskit::Role::Splitter,
+            AccessRole::Switch => accesskit::Role::Switch,
+            AccessRole::Tab => accesskit::Role::Tab,
+            AccessRole::TabList => accesskit::Role::TabList,
+            AccessRole::TabPanel => accesskit::Role::TabPanel,
+            AccessRole::Table => accesskit::Role::Table,
             AccessRole::TextInput => accesskit::Role::TextInput,
+            AccessRole::Toolbar => accesskit::Role::Toolbar,
+            AccessRole::Tooltip => accesskit::Role::Tooltip,
+            AccessRole::Tree => accesskit::Role::Tree,
+            AccessRole::TreeItem => accesskit::Role::TreeItem,
             AccessRole::Window => accesskit::Role::Window,
         }
     }
 }
 
+/// A value assistive technology asks an element to take, e.g. text typed
+/// by dictation or a slider position.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AccessValue {
+    Text(String),
+    Numeric(f64),
+}
+
+impl AccessRole {
+    /// Whether assistive technology can activate the element with a click.
+    pub fn is_clickable(self) -> bool {
+        matches!(
+            self,
+            AccessRole::Button
+                | AccessRole::CheckBox
+                | AccessRole::Link
+                | AccessRole::ListItem
+                | AccessRole::MenuItem
+                | AccessRole::RadioButton
+                | AccessRole::Switch
+                | AccessRole::Tab
+                | AccessRole::TreeItem
+        )
+    }
+
+    /// Whether assistive technology can set the element's value.
+    pub fn accepts_value(self) -> bool {
+        matches!(
+            self,
+            AccessRole::ComboBox | AccessRole::Slider | AccessRole::TextInput
+        )
+    }
+}
+
+/// Whether a checkbox, switch or toggle button is on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckedState {
+    False,
+    True,
+    /// Partially checked, e.g. a parent checkbox with some children checked.
+    Mixed,
+}
+
+impl From<bool> for CheckedState {
+    fn from(checked: bool) -> Self {
+        if checked {
+            CheckedState::True
+        } else {
+            CheckedState::False
+        }
+    }
+}
+
+impl From<CheckedState> for accesskit::Toggled {
+    fn from(state: CheckedState) -> Self {
+        match state {
+            CheckedState::False => accesskit::Toggled::False,
+            CheckedState::True => accesskit::Toggled::True,
+            CheckedState::Mixed => accesskit::Toggled::Mixed,
+        }
+    }
+}
+
 /// A node in the accessibility tree.
 #[derive(Debug, Clone)]
 pub struct AccessNode {
@@ -43,6 +190,24 @@ pub struct AccessNode {
     pub name: String,
     pub bounds: Option<Rect>,
     pub children: Vec<AccessId>,
+    /// Checked state, for checkboxes, switches and toggle buttons.
+    pub checked: Option<CheckedState>,
+    pub disabled: bool,
+    /// Selection state, for list items, tabs and options. `None` if the
+    /// element can't be selected.
+    pub selected: Option<bool>,
+    /// Current value of a slider, progress bar or similar.
+    pub numeric_value: Option<f64>,
+    /// The range `numeric_value` falls in.
+    pub numeric_range: Option<RangeInclusive<f64>>,
+    /// Text contents, for text inputs.
+    pub value: Option<String>,
+    /// Keyboard shortcut that activates the element, e.g. "Ctrl+S".
+    pub shortcut: Option<String>,
+    /// Explicit place in the tab order. Positive values come first, in
+    /// ascending order; zero makes any node focusable in reading order;
+    /// negative values take the node out of the tab order.
+    pub tab_index: Option<i32>,
 }
 
 impl AccessNode {
@@ -53,6 +218,14 @@ impl AccessNode {
             name,
             bounds: None,
             children: Vec::new(),
+            checked: None,
+            disabled: false,
+            selected: None,
+            numeric_value: None,
+            numeric_range: None,
+            value: None,
+            shortcut: None,
+            tab_index: None,
         }
     }
 
@@ -66,6 +239,50 @@ impl AccessNode {
         self
     }
 
+    pub fn with_checked(mut self, checked: impl Into<CheckedState>) -> Self {
+        self.checked = Some(checked.into());
+        self
+    }
+
+    pub fn with_disabled(mut self, disabled: bool) -> Self {
+        self.disabled = disabled;
+        self
+    }
+
+    pub fn with_selected(mut self, selected: bool) -> Self {
+        self.selected = Some(selected);
+        self
+    }
+
+    pub fn with_numeric_value(mut self, value: f64, range: RangeInclusive<f64>) -> Self {
+        self.numeric_value = Some(value);
+        self.numeric_range = Some(range);
+        self
+    }
+
+    pub fn with_value(mut self, value: impl Into<String>) -> Self {
+        self.value = Some(value.into());
+        self
+    }
+
+    pub fn with_shortcut(mut self, shortcut: impl Into<String>) -> Self {
+        self.shortcut = Some(shortcut.into());
+        self
+    }
+
+    pub fn with_tab_index(mut self, index: i32) -> Self {
+        self.tab_index = Some(index);
+        self
+    }
+
+    /// Whether Tab should stop on this node: interactive, enabled and not
+    /// taken out of the order with a negative tab index.
+    pub fn is_focusable(&self) -> bool {
+        let interactive =
+            self.role.is_clickable() || self.role.accepts_value() || self.tab_index.is_some();
+        interactive && !self.disabled && self.tab_index.unwrap_or(0) >= 0
+    }
+
     /// Convert to an AccessKit Node.
     pub fn to_accesskit_node(&self) -> accesskit::Node {
         let mut node = accesskit::Node::new(self.role.into());
@@ -80,6 +297,39 @@ impl AccessNode {
             });
         }
 
+        if let Some(checked) = self.checked {
+            node.set_toggled(checked.into());
+        }
+        if self.disabled {
+            node.set_disabled();
+        }
+        if let Some(selected) = self.selected {
+            node.set_selected(selected);
+        }
+        if let Some(value) = self.numeric_value {
+            node.set_numeric_value(value);
+        }
+        if let Some(range) = &self.numeric_range {
+            node.set_min_numeric_value(*range.start());
+            node.set_max_numeric_value(*range.end());
+        }
+        if let Some(value) = &self.value {
+            node.set_value(value.clone());
+        }
+        if let Some(shortcut) = &self.shortcut {
+            node.set_keyboard_shortcut(shortcut.clone());
+        }
+
+        if !self.disabled {
+            if self.role.is_clickable() {
+                node.add_action(accesskit::Action::Click);
+            }
+            if self.role.accepts_value() {
+                node.add_action(accesskit::Action::Focus);
+                node.add_action(accesskit::Action::SetValue);
+            }
+        }
+
         if !self.children.is_empty() {
             let children: Vec<accesskit::NodeId> =
                 self.children.iter().map(|id| (*id).into()).collect();
@@ -97,6 +347,9 @@ impl AccessNode {
 pub struct AccessTree {
     root_id: AccessId,
     nodes: HashMap<AccessId, AccessNode>,
+    /// Nodes that children are attached to while painting, innermost last.
+    parent_stack: Vec<AccessId>,
+    next_generated_id: u64,
 }
 
 impl AccessTree {
@@ -105,6 +358,8 @@ impl AccessTree {
         Self {
             root_id,
             nodes: HashMap::new(),
+            parent_stack: Vec::new(),
+            next_generated_id: 0,
         }
     }
 
@@ -123,6 +378,111 @@ impl AccessTree {
         self.nodes.get(&id)
     }
 
+    pub fn get_mut(&mut self, id: AccessId) -> Option<&mut AccessNode> {
+        self.nodes.get_mut(&id)
+    }
+
+    /// An id for a node with no [`ElementId`], unique until the next
+    /// [`clear`](Self::clear).
+    pub fn generate_id(&mut self) -> AccessId {
+        let id = AccessId(AccessId::GENERATED | self.next_generated_id);
+        self.next_generated_id += 1;
+        id
+    }
+
+    /// Add a node as the last child of the innermost
+    /// [`push_parent`](Self::push_parent), or of the root.
+    pub fn push_child(&mut self, node: AccessNode) -> &mut AccessNode {
+        let parent = self.parent_stack.last().copied().unwrap_or(self.root_id);
+        if let Some(parent) = self.nodes.get_mut(&parent) {
+            parent.children.push(node.id);
+        }
+        let id = node.id;
+        self.nodes.insert(id, node);
+        self.nodes.get_mut(&id).unwrap()
+    }
+
+    /// Attach nodes added by [`push_child`](Self::push_child) to `id` until
+    /// the matching [`pop_parent`](Self::pop_parent).
+    pub fn push_parent(&mut self, id: AccessId) {
+        self.parent_stack.push(id);
+    }
+
+    pub fn pop_parent(&mut self) {
+        self.parent_stack.pop();
+    }
+
+    /// Focusable nodes in tab order: nodes with a positive
+    /// [`tab_index`](AccessNode::tab_index) first, in ascending order, then
+    /// the rest in reading order (rows top to bottom, left to right within
+    /// a row). Nodes without bounds follow in tree order.
+    pub fn focus_order(&self) -> Vec<AccessId> {
+        let mut nodes = Vec::new();
+        self.collect_focusable(self.root_id, &mut nodes);
+
+        let (mut explicit, rest): (Vec<&AccessNode>, Vec<&AccessNode>) = nodes
+            .into_iter()
+            .partition(|node| node.tab_index.unwrap_or(0) > 0);
+        explicit.sort_by_key(|node| node.tab_index);
+
+        let (mut placed, unplaced): (Vec<&AccessNode>, Vec<&AccessNode>) =
+            rest.into_iter().partition(|node| node.bounds.is_some());
+        placed.sort_by(|a, b| top(a).total_cmp(&top(b)));
+        // Group into rows: a node starts a new row once it's below the
+        // first node of the current one.
+        let mut rows: Vec<Vec<&AccessNode>> = Vec::new();
+        for node in placed {
+            match rows.last_mut() {
+                Some(row) if top(node) < bottom(row[0]) => row.push(node),
+                _ => rows.push(vec![node]),
+            }
+        }
+        for row in &mut rows {
+            row.sort_by(|a, b| left(a).total_cmp(&left(b)));
+        }
+
+        explicit
+            .into_iter()
+            .chain(rows.into_iter().flatten())
+            .chain(unplaced)
+            .map(|node| node.id)
+            .collect()
+    }
+
+    fn collect_focusable<'a>(&'a self, id: AccessId, nodes: &mut Vec<&'a AccessNode>) {
+        let Some(node) = self.nodes.get(&id) else {
+            return;
+        };
+        if node.is_focusable() {
+            nodes.push(node);
+        }
+        for child in &node.children {
+            self.collect_focusable(*child, nodes);
+        }
+    }
+
+    /// Names of the labels under `id`, in order, joined with spaces. Used
+    /// to name containers like clickable divs from their text.
+    pub fn text_content(&self, id: AccessId) -> String {
+        let mut words = Vec::new();
+        self.collect_text(id, &mut words);
+        words.join(" ")
+    }
+
+    fn collect_text<'a>(&'a self, id: AccessId, words: &mut Vec<&'a str>) {
+        let Some(node) = self.nodes.get(&id) else {
+            return;
+        };
+        for child in &node.children {
+            if let Some(child_node) = self.nodes.get(child) {
+                if child_node.role == AccessRole::Label && !child_node.name.is_empty() {
+                    words.push(&child_node.name);
+                }
+            }
+            self.collect_text(*child, words);
+        }
+    }
+
     /// Number of nodes in the tree.
     pub fn node_count(&self) -> usize {
         self.nodes.len()
@@ -131,6 +491,8 @@ impl AccessTree {
     /// Clear all nodes, keeping root ID.
     pub fn clear(&mut self) {
         self.nodes.clear();
+        self.parent_stack.clear();
+        self.next_generated_id = 0;
     }
 
     /// Build an initial TreeUpdate for AccessKit (includes Tree info).
@@ -150,6 +512,18 @@ impl AccessTree {
     }
 }
 
+fn top(node: &AccessNode) -> f32 {
+    node.bounds.map_or(0.0, |b| b.origin.y)
+}
+
+fn bottom(node: &AccessNode) -> f32 {
+    node.bounds.map_or(0.0, |b| b.origin.y + b.size.height)
+}
+
+fn left(node: &AccessNode) -> f32 {
+    node.bounds.map_or(0.0, |b| b.origin.x)
+}
+
 /// Manages keyboard focus for accessible elements.
 #[derive(Debug, Default)]
 pub struct FocusManager {
@@ -184,6 +558,18 @@ impl FocusManager {
         self.focus_order = order;
     }
 
+    /// Take the focus order from `tree`'s focusable nodes (see
+    /// [`AccessTree::focus_order`]). Focus is cleared if the focused node
+    /// is no longer in the order.
+    pub fn update_focus_order(&mut self, tree: &AccessTree) {
+        self.focus_order = tree.focus_order();
+        if let Some(focused) = self.focused {
+            if !self.focus_order.contains(&focused) {
+                self.focused = None;
+            }
+        }
+    }
+
     /// Move focus to the next element in the focus order.
     pub fn focus_next(&mut self) {
         if self.focus_order.is_empty() {
@@ -277,6 +663,118 @@ mod tests {
         assert_eq!(ak_bounds.y1, 70.0); // y + height
     }
 
+    #[test]
+    fn widget_roles_map_to_accesskit_roles() {
+        let pairs = [
+            (AccessRole::CheckBox, accesskit::Role::CheckBox),
+            (AccessRole::RadioButton, accesskit::Role::RadioButton),
+            (AccessRole::Slider, accesskit::Role::Slider),
+            (AccessRole::ListItem, accesskit::Role::ListItem),
+            (AccessRole::MenuBar, accesskit::Role::MenuBar),
+            (AccessRole::Tab, accesskit::Role::Tab),
+            (AccessRole::Table, accesskit::Role::Table),
+            (AccessRole::Heading, accesskit::Role::Heading),
+            (AccessRole::Link, accesskit::Role::Link),
+        ];
+        for (role, expected) in pairs {
+            assert_eq!(accesskit::Role::from(role), expected);
+            let node = AccessNode::new(AccessId(1), role, String::new());
+            assert_eq!(node.to_accesskit_node().role(), expected);
+        }
+    }
+
+    #[test]
+    fn form_control_state_converts_to_accesskit_properties() {
+        let checkbox = AccessNode::new(AccessId(2), AccessRole::CheckBox, "Wrap".into())
+            .with_checked(CheckedState::Mixed)
+            .with_disabled(true)
+            .with_shortcut("Alt+W")
+            .to_accesskit_node();
+        assert_eq!(checkbox.toggled(), Some(accesskit::Toggled::Mixed));
+        assert!(checkbox.is_disabled());
+        assert_eq!(checkbox.keyboard_shortcut(), Some("Alt+W"));
+        assert_eq!(checkbox.is_selected(), None);
+
+        let slider = AccessNode::new(AccessId(3), AccessRole::Slider, "Volume".into())
+            .with_numeric_value(0.4, 0.0..=1.0)
+            .to_accesskit_node();
+        assert_eq!(slider.numeric_value(), Some(0.4));
+        assert_eq!(slider.min_numeric_value(), Some(0.0));
+        assert_eq!(slider.max_numeric_value(), Some(1.0));
+
+        let input = AccessNode::new(AccessId(4), AccessRole::TextInput, "Name".into())
+            .with_value("Ada")
+            .with_selected(false)
+            .to_accesskit_node();
+        assert_eq!(input.value(), Some("Ada"));
+        assert_eq!(input.is_selected(), Some(false));
+        assert!(!input.is_disabled());
+        assert_eq!(input.toggled(), None);
+    }
+
+    #[test]
+    fn interactive_nodes_advertise_actions() {
+        let button = AccessNode::new(AccessId(1), AccessRole::Button, "OK".into());
+        assert!(button
+            .to_accesskit_node()
+            .supports_action(accesskit::Action::Click));
+        assert!(!button
+            .clone()
+            .with_disabled(true)
+            .to_accesskit_node()
+            .supports_action(accesskit::Action::Click));
+
+        let input = AccessNode::new(AccessId(2), AccessRole::TextInput, "Name".into());
+        assert!(input
+            .to_accesskit_node()
+            .supports_action(accesskit::Action::SetValue));
+
+        assert_eq!(AccessId(7).element_id(), Some(ElementId(7)));
+        let mut tree = AccessTree::new(AccessId(0));
+        assert_eq!(tree.generate_id().element_id(), None);
+    }
+
+    #[test]
+    fn focus_order_follows_reading_order_and_tab_index() {
+        let mut tree = AccessTree::new(AccessId(0));
+        tree.push(AccessNode::new(
+            AccessId(0),
+            AccessRole::Window,
+            "App".into(),
+        ));
+        let button = |id: u64, x: f32, y: f32| {
+            AccessNode::new(AccessId(id), AccessRole::Button, String::new())
+                .with_bounds(Rect::new(Point::new(x, y), Size::new(40.0, 20.0)))
+        };
+        // Second row, declared first; slightly misaligned tops in row one.
+        tree.push_child(button(1, 0.0, 50.0));
+        tree.push_child(button(2, 100.0, 2.0));
+        tree.push_child(button(3, 0.0, 0.0));
+        tree.push_child(button(4, 50.0, 50.0).with_tab_index(2));
+        tree.push_child(button(5, 200.0, 0.0).with_tab_index(1));
+        tree.push_child(button(6, 300.0, 0.0).with_tab_index(-1));
+        tree.push_child(button(7, 400.0, 0.0).with_disabled(true));
+        tree.push_child(AccessNode::new(AccessId(8), AccessRole::Label, "x".into()));
+
+        assert_eq!(
+            tree.focus_order(),
+            vec![
+                AccessId(5),
+                AccessId(4),
+                AccessId(3),
+                AccessId(2),
+                AccessId(1)
+            ]
+        );
+
+        let mut focus = FocusManager::new();
+        focus.set_focus(AccessId(6));
+        focus.update_focus_order(&tree);
+        assert_eq!(focus.focused(), None);
+        focus.focus_next();
+        assert_eq!(focus.focused(), Some(AccessId(5)));
+    }
+
     // AccessTree tests
 
     #[test]
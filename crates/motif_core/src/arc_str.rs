@@ -1,20 +1,63 @@
 //! Cheap-to-clone string type for UI text content.
 
-use std::borrow::Borrow;
+use std::borrow::{Borrow, Cow};
+use std::collections::HashSet;
 use std::fmt;
 use std::ops::Deref;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, OnceLock};
 
 /// An immutable string that can be cheaply cloned.
 ///
-/// Either a `&'static str` (zero-cost) or an `Arc<str>` (reference-counted).
-/// Use this for text content in elements to avoid unnecessary allocations.
-#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+/// Either a `&'static str` (zero-cost), a short string stored inline, or an
+/// `Arc<str>` (reference-counted). Use this for text content in elements to
+/// avoid unnecessary allocations: most UI strings ("OK", a formatted count)
+/// fit in [`INLINE_CAPACITY`] bytes and never touch the allocator once
+/// converted.
+///
+/// Equality, ordering and hashing only look at the string, so a static and
+/// an owned `ArcStr` with the same contents are interchangeable (e.g. as map
+/// keys looked up by `&str`).
+#[derive(Clone)]
 pub enum ArcStr {
     /// A static string literal - zero allocation cost.
     Static(&'static str),
     /// A reference-counted string - cheap to clone.
     Owned(Arc<str>),
+    /// A short string copied inline - no allocation.
+    Inline(InlineStr),
+}
+
+/// Longest string, in bytes, that [`ArcStr`] stores inline. Chosen so an
+/// `ArcStr` stays the size of the `&str` and `Arc<str>` it otherwise holds,
+/// plus its tag.
+pub const INLINE_CAPACITY: usize = 22;
+
+/// A string of up to [`INLINE_CAPACITY`] bytes stored by value.
+#[derive(Clone, Copy)]
+pub struct InlineStr {
+    len: u8,
+    bytes: [u8; INLINE_CAPACITY],
+}
+
+impl InlineStr {
+    /// Copy `s` inline, if it's short enough.
+    pub fn new(s: &str) -> Option<Self> {
+        if s.len() > INLINE_CAPACITY {
+            return None;
+        }
+        let mut bytes = [0; INLINE_CAPACITY];
+        bytes[..s.len()].copy_from_slice(s.as_bytes());
+        Some(Self {
+            len: s.len() as u8,
+            bytes,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        // SAFETY: `bytes[..len]` was copied from a `&str` in `new` and never
+        // modified, so it's valid UTF-8.
+        unsafe { std::str::from_utf8_unchecked(&self.bytes[..self.len as usize]) }
+    }
 }
 
 impl ArcStr {
@@ -28,15 +71,77 @@ impl ArcStr {
         Self::Owned(s.into())
     }
 
+    /// Copy a borrowed string, inline if it's short enough and into a new
+    /// `Arc<str>` otherwise.
+    pub fn copy_from(s: &str) -> Self {
+        match InlineStr::new(s) {
+            Some(inline) => Self::Inline(inline),
+            None => Self::Owned(Arc::from(s)),
+        }
+    }
+
+    /// Return the process-wide copy of `s`, allocating it the first time it's
+    /// seen. Use for strings repeated across many elements and frames, like
+    /// element keys and theme names: every call after the first is a lookup,
+    /// and the result clones like a `&'static str`.
+    ///
+    /// Interned strings are never freed, so don't intern unbounded input
+    /// such as user text.
+    pub fn intern(s: &str) -> Self {
+        static INTERNED: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+        let mut interned = INTERNED
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if let Some(existing) = interned.get(s) {
+            return Self::Static(existing);
+        }
+        let leaked: &'static str = Box::leak(s.into());
+        interned.insert(leaked);
+        Self::Static(leaked)
+    }
+
     /// Get the underlying string slice.
     pub fn as_str(&self) -> &str {
         match self {
             Self::Static(s) => s,
             Self::Owned(s) => s,
+            Self::Inline(s) => s.as_str(),
+        }
+    }
+}
+
+impl PartialEq for ArcStr {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            // Interned and cloned strings share storage; skip the byte compare.
+            (Self::Static(a), Self::Static(b)) if std::ptr::eq(*a, *b) => true,
+            (Self::Owned(a), Self::Owned(b)) if Arc::ptr_eq(a, b) => true,
+            _ => self.as_str() == other.as_str(),
         }
     }
 }
 
+impl Eq for ArcStr {}
+
+impl PartialOrd for ArcStr {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ArcStr {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl std::hash::Hash for ArcStr {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
 impl Deref for ArcStr {
     type Target = str;
 
@@ -83,7 +188,25 @@ impl From<&'static str> for ArcStr {
 
 impl From<String> for ArcStr {
     fn from(s: String) -> Self {
-        Self::Owned(Arc::from(s))
+        match InlineStr::new(&s) {
+            Some(inline) => Self::Inline(inline),
+            None => Self::Owned(Arc::from(s)),
+        }
+    }
+}
+
+impl From<&String> for ArcStr {
+    fn from(s: &String) -> Self {
+        Self::copy_from(s)
+    }
+}
+
+impl From<Cow<'static, str>> for ArcStr {
+    fn from(s: Cow<'static, str>) -> Self {
+        match s {
+            Cow::Borrowed(s) => Self::Static(s),
+            Cow::Owned(s) => s.into(),
+        }
     }
 }
 
@@ -93,6 +216,21 @@ impl From<Arc<str>> for ArcStr {
     }
 }
 
+impl From<ArcStr> for String {
+    fn from(s: ArcStr) -> Self {
+        s.as_str().to_owned()
+    }
+}
+
+impl From<ArcStr> for Arc<str> {
+    fn from(s: ArcStr) -> Self {
+        match s {
+            ArcStr::Owned(s) => s,
+            s => Arc::from(s.as_str()),
+        }
+    }
+}
+
 impl PartialEq<str> for ArcStr {
     fn eq(&self, other: &str) -> bool {
         self.as_str() == other
@@ -145,4 +283,53 @@ mod tests {
         let s = ArcStr::default();
         assert_eq!(s.as_str(), "");
     }
+
+    #[test]
+    fn short_strings_are_stored_inline() {
+        assert_eq!(std::mem::size_of::<ArcStr>(), 24);
+
+        let count = ArcStr::from(format!("{} items", 42));
+        assert!(matches!(count, ArcStr::Inline(_)));
+        assert_eq!(count, "42 items");
+
+        let longest = "x".repeat(INLINE_CAPACITY);
+        assert!(matches!(ArcStr::copy_from(&longest), ArcStr::Inline(_)));
+        let long = ArcStr::from(longest + "é");
+        assert!(matches!(long, ArcStr::Owned(_)));
+        assert_eq!(long.len(), INLINE_CAPACITY + 2);
+
+        assert_eq!(ArcStr::copy_from("héllo"), ArcStr::new_static("héllo"));
+    }
+
+    #[test]
+    fn interning_returns_one_shared_copy() {
+        let name = String::from("sidebar-navigation-section");
+        let a = ArcStr::intern(&name);
+        let b = ArcStr::intern("sidebar-navigation-section");
+        let (ArcStr::Static(a_str), ArcStr::Static(b_str)) = (&a, &b) else {
+            panic!("interned strings should be static");
+        };
+        assert!(std::ptr::eq(*a_str, *b_str));
+        assert_eq!(a, ArcStr::from(name));
+        assert_ne!(a, ArcStr::intern("sidebar-navigation-other"));
+    }
+
+    #[test]
+    fn converts_to_and_from_std_strings() {
+        let owned = String::from("label");
+        assert_eq!(ArcStr::from(&owned), "label");
+        assert_eq!(ArcStr::from(Cow::Borrowed("label")), "label");
+        assert_eq!(String::from(ArcStr::from(Cow::Owned(owned))), "label");
+        let arc: Arc<str> = Arc::from("x".repeat(40));
+        let back: Arc<str> = ArcStr::from(arc.clone()).into();
+        assert!(Arc::ptr_eq(&arc, &back));
+    }
+
+    #[test]
+    fn static_and_owned_are_interchangeable_keys() {
+        let mut map = std::collections::HashMap::new();
+        map.insert(ArcStr::new_static("key"), 1);
+        assert_eq!(map.get("key"), Some(&1));
+        assert_eq!(map.get(&ArcStr::from(String::from("key"))), Some(&1));
+    }
 }
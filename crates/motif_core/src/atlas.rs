@@ -0,0 +1,474 @@
+//! Texture atlas bookkeeping for glyph caching.
+//!
+//! Tracks where glyphs live across one or more fixed-size atlas pages without
+//! touching any GPU resources, so renderers only have to create a texture per
+//! page and upload pixels into the regions handed out here.
+//!
+//! Pages are packed row by row. When every page is full and the page limit is
+//! reached, the least-recently-used page is evicted wholesale and reused.
+//! Pages touched during the current frame are never evicted, so regions
+//! returned earlier in a frame stay valid until the frame is submitted. A GPU
+//! renderer that queues frames ahead also sets
+//! [`frames_in_flight`](AtlasAllocator::frames_in_flight), so a page isn't
+//! rewritten while an earlier frame may still be sampling it.
+//!
+//! [`AtlasAllocator::page_stats`] reports how full each row is, and renderers
+//! that keep their page textures readable return an [`AtlasDump`] pairing
+//! those stats with the page pixels, for debugging text corruption.
+
+use std::collections::{BTreeMap, HashMap};
+
+use image::GrayImage;
+
+/// Cache key for a glyph: (font blob id, glyph id, font size bits).
+pub type AtlasKey = (u64, u32, u32);
+
+/// A region in the texture atlas for a cached glyph.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AtlasRegion {
+    /// Index of the atlas page holding the glyph.
+    pub page: usize,
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl AtlasRegion {
+    /// Whether the region covers no pixels (e.g. a space glyph).
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+}
+
+/// Occupancy of one packed row, as reported by [`AtlasAllocator::page_stats`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AtlasRowStats {
+    /// Top of the row in page pixels.
+    pub y: u32,
+    /// Height of the tallest glyph in the row, padding included.
+    pub height: u32,
+    /// Number of glyphs packed into the row.
+    pub glyphs: usize,
+    /// Right edge of the last glyph, padding included.
+    pub used_width: u32,
+    /// Pixels covered by glyphs, padding excluded.
+    pub glyph_pixels: u64,
+}
+
+/// Occupancy of one atlas page.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AtlasPageStats {
+    /// Number of cached glyphs on the page.
+    pub glyphs: usize,
+    /// Rows from top to bottom.
+    pub rows: Vec<AtlasRowStats>,
+    /// Frame in which a glyph on the page was last used.
+    pub last_used: u64,
+}
+
+/// The pixels of every atlas page, read back from the renderer.
+#[derive(Clone, Debug)]
+pub struct AtlasDump {
+    /// Side length of each page in pixels.
+    pub page_size: u32,
+    pub pages: Vec<AtlasPageDump>,
+}
+
+/// One page of an [`AtlasDump`]: its coverage texture and occupancy.
+#[derive(Clone, Debug)]
+pub struct AtlasPageDump {
+    pub pixels: GrayImage,
+    pub stats: AtlasPageStats,
+}
+
+impl AtlasDump {
+    /// All pages stacked top to bottom in one image.
+    pub fn to_image(&self) -> GrayImage {
+        let height = self.page_size * self.pages.len().max(1) as u32;
+        let mut image = GrayImage::new(self.page_size, height);
+        for (index, page) in self.pages.iter().enumerate() {
+            let top = index as u32 * self.page_size;
+            for (x, y, pixel) in page.pixels.enumerate_pixels() {
+                if x < self.page_size && y < self.page_size {
+                    image.put_pixel(x, top + y, *pixel);
+                }
+            }
+        }
+        image
+    }
+}
+
+/// Row packer state for a single atlas page.
+#[derive(Debug, Default)]
+struct AtlasPage {
+    /// Current row Y position
+    row_y: u32,
+    /// Current X position in row
+    row_x: u32,
+    /// Height of current row (max glyph height in row)
+    row_height: u32,
+    /// Frame in which a glyph on this page was last used.
+    last_used: u64,
+}
+
+impl AtlasPage {
+    fn allocate(&mut self, width: u32, height: u32, page_size: u32) -> Option<(u32, u32)> {
+        // Check if fits in current row
+        if self.row_x + width <= page_size && self.row_y + height <= page_size {
+            let origin = (self.row_x, self.row_y);
+            self.row_x += width;
+            self.row_height = self.row_height.max(height);
+            return Some(origin);
+        }
+
+        // Start new row
+        let next_row_y = self.row_y + self.row_height;
+        if next_row_y + height > page_size {
+            return None;
+        }
+
+        self.row_y = next_row_y;
+        self.row_x = width;
+        self.row_height = height;
+        Some((0, next_row_y))
+    }
+}
+
+/// Allocates glyph regions across atlas pages with LRU page eviction.
+#[derive(Debug)]
+pub struct AtlasAllocator {
+    page_size: u32,
+    max_pages: usize,
+    pages: Vec<AtlasPage>,
+    entries: HashMap<AtlasKey, AtlasRegion>,
+    frame: u64,
+    /// Earlier frames whose pages are protected from eviction.
+    frames_in_flight: u64,
+}
+
+impl AtlasAllocator {
+    /// Gap left between glyphs so linear sampling doesn't bleed neighbours.
+    pub const PADDING: u32 = 1;
+
+    /// Create an allocator for square pages of `page_size` pixels, holding at
+    /// most `max_pages` pages before evicting.
+    pub fn new(page_size: u32, max_pages: usize) -> Self {
+        Self {
+            page_size,
+            max_pages: max_pages.max(1),
+            pages: Vec::new(),
+            entries: HashMap::new(),
+            frame: 0,
+            frames_in_flight: 0,
+        }
+    }
+
+    /// Also keep pages used in the last `frames` frames, for renderers whose
+    /// earlier frames may still be executing on the GPU when glyphs for the
+    /// next one are uploaded.
+    pub fn frames_in_flight(mut self, frames: u64) -> Self {
+        self.frames_in_flight = frames;
+        self
+    }
+
+    /// Side length of each page in pixels.
+    pub fn page_size(&self) -> u32 {
+        self.page_size
+    }
+
+    /// Number of pages allocated so far. Renderers should keep one texture
+    /// per page and create new ones when this grows.
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Number of cached glyphs.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if no glyphs are cached.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Mark the start of a new frame. Pages not used since an earlier frame
+    /// become candidates for eviction.
+    pub fn begin_frame(&mut self) {
+        self.frame += 1;
+    }
+
+    /// Look up a cached glyph, marking its page as used this frame.
+    pub fn get(&mut self, key: &AtlasKey) -> Option<AtlasRegion> {
+        let region = *self.entries.get(key)?;
+        if !region.is_empty() {
+            self.pages[region.page].last_used = self.frame;
+        }
+        Some(region)
+    }
+
+    /// Reserve space for a glyph of the given size.
+    ///
+    /// Returns `None` if the glyph is larger than a page, or if every page is
+    /// full and all of them are in use this frame or a frame still in
+    /// flight.
+    pub fn insert(&mut self, key: AtlasKey, width: u32, height: u32) -> Option<AtlasRegion> {
+        if width == 0 || height == 0 {
+            // Empty glyph (e.g., space) - cache a zero-size region
+            let region = AtlasRegion {
+                page: 0,
+                x: 0,
+                y: 0,
+                width: 0,
+                height: 0,
+            };
+            self.entries.insert(key, region);
+            return Some(region);
+        }
+
+        let padded_width = width + Self::PADDING;
+        let padded_height = height + Self::PADDING;
+        if padded_width > self.page_size || padded_height > self.page_size {
+            return None;
+        }
+
+        let (page, (x, y)) = self.allocate(padded_width, padded_height)?;
+        let region = AtlasRegion {
+            page,
+            x,
+            y,
+            width,
+            height,
+        };
+        self.pages[region.page].last_used = self.frame;
+        self.entries.insert(key, region);
+        Some(region)
+    }
+
+    /// Row occupancy of each page, indexed like [`AtlasRegion::page`].
+    ///
+    /// Glyphs in a row share its top edge, so rows are rebuilt by grouping
+    /// cached regions on `y`.
+    pub fn page_stats(&self) -> Vec<AtlasPageStats> {
+        let mut rows: Vec<BTreeMap<u32, AtlasRowStats>> = vec![BTreeMap::new(); self.pages.len()];
+        for region in self.entries.values().filter(|region| !region.is_empty()) {
+            let row = rows[region.page].entry(region.y).or_insert(AtlasRowStats {
+                y: region.y,
+                ..AtlasRowStats::default()
+            });
+            row.height = row.height.max(region.height + Self::PADDING);
+            row.used_width = row.used_width.max(region.x + region.width + Self::PADDING);
+            row.glyphs += 1;
+            row.glyph_pixels += u64::from(region.width) * u64::from(region.height);
+        }
+        self.pages
+            .iter()
+            .zip(rows)
+            .map(|(page, rows)| AtlasPageStats {
+                glyphs: rows.values().map(|row| row.glyphs).sum(),
+                rows: rows.into_values().collect(),
+                last_used: page.last_used,
+            })
+            .collect()
+    }
+
+    /// Drop all cached glyphs and pages.
+    pub fn clear(&mut self) {
+        self.pages.clear();
+        self.entries.clear();
+    }
+
+    fn allocate(&mut self, width: u32, height: u32) -> Option<(usize, (u32, u32))> {
+        let page_size = self.page_size;
+
+        for (index, page) in self.pages.iter_mut().enumerate() {
+            if let Some(origin) = page.allocate(width, height, page_size) {
+                return Some((index, origin));
+            }
+        }
+
+        if self.pages.len() < self.max_pages {
+            let mut page = AtlasPage::default();
+            let origin = page.allocate(width, height, page_size)?;
+            self.pages.push(page);
+            return Some((self.pages.len() - 1, origin));
+        }
+
+        let index = self.evict_least_recently_used()?;
+        let origin = self.pages[index].allocate(width, height, page_size)?;
+        Some((index, origin))
+    }
+
+    /// Reset the least-recently-used page that wasn't touched this frame or
+    /// one still in flight.
+    fn evict_least_recently_used(&mut self) -> Option<usize> {
+        let frame = self.frame;
+        let in_flight = self.frames_in_flight;
+        let (index, _) = self
+            .pages
+            .iter()
+            .enumerate()
+            .filter(|(_, page)| page.last_used + in_flight < frame)
+            .min_by_key(|(_, page)| page.last_used)?;
+
+        self.pages[index] = AtlasPage::default();
+        self.entries
+            .retain(|_, region| region.is_empty() || region.page != index);
+        Some(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(glyph_id: u32) -> AtlasKey {
+        (1, glyph_id, 16.0f32.to_bits())
+    }
+
+    #[test]
+    fn packs_glyphs_in_rows() {
+        let mut atlas = AtlasAllocator::new(32, 1);
+        let a = atlas.insert(key(1), 10, 10).unwrap();
+        let b = atlas.insert(key(2), 10, 10).unwrap();
+        assert_eq!((a.x, a.y), (0, 0));
+        assert_eq!((b.x, b.y), (11, 0));
+
+        // Third glyph doesn't fit in the row, wraps to the next one.
+        let c = atlas.insert(key(3), 15, 10).unwrap();
+        assert_eq!((c.x, c.y), (0, 11));
+    }
+
+    #[test]
+    fn cached_glyphs_are_returned() {
+        let mut atlas = AtlasAllocator::new(64, 1);
+        let region = atlas.insert(key(1), 10, 10).unwrap();
+        assert_eq!(atlas.get(&key(1)), Some(region));
+        assert_eq!(atlas.get(&key(2)), None);
+        assert_eq!(atlas.len(), 1);
+    }
+
+    #[test]
+    fn empty_glyphs_take_no_space() {
+        let mut atlas = AtlasAllocator::new(64, 1);
+        let space = atlas.insert(key(1), 0, 0).unwrap();
+        assert!(space.is_empty());
+        assert_eq!(atlas.page_count(), 0);
+    }
+
+    #[test]
+    fn oversized_glyph_is_rejected() {
+        let mut atlas = AtlasAllocator::new(32, 4);
+        assert!(atlas.insert(key(1), 40, 10).is_none());
+    }
+
+    #[test]
+    fn grows_into_new_pages() {
+        let mut atlas = AtlasAllocator::new(16, 3);
+        let a = atlas.insert(key(1), 14, 14).unwrap();
+        let b = atlas.insert(key(2), 14, 14).unwrap();
+        assert_eq!(a.page, 0);
+        assert_eq!(b.page, 1);
+        assert_eq!(atlas.page_count(), 2);
+    }
+
+    #[test]
+    fn evicts_least_recently_used_page() {
+        let mut atlas = AtlasAllocator::new(16, 2);
+        atlas.insert(key(1), 14, 14).unwrap();
+        atlas.insert(key(2), 14, 14).unwrap();
+
+        // Next frame only glyph 2 is used, so page 0 is the stale one.
+        atlas.begin_frame();
+        atlas.get(&key(2)).unwrap();
+        let c = atlas.insert(key(3), 14, 14).unwrap();
+
+        assert_eq!(c.page, 0);
+        assert_eq!(atlas.get(&key(1)), None, "evicted glyph should be gone");
+        assert!(atlas.get(&key(2)).is_some());
+    }
+
+    #[test]
+    fn never_evicts_pages_used_this_frame() {
+        let mut atlas = AtlasAllocator::new(16, 1);
+        atlas.insert(key(1), 14, 14).unwrap();
+        assert!(atlas.insert(key(2), 14, 14).is_none());
+
+        // After a new frame the page is stale and can be recycled.
+        atlas.begin_frame();
+        assert!(atlas.insert(key(2), 14, 14).is_some());
+    }
+
+    #[test]
+    fn never_evicts_pages_used_by_frames_in_flight() {
+        let mut atlas = AtlasAllocator::new(16, 1).frames_in_flight(2);
+        atlas.insert(key(1), 14, 14).unwrap();
+
+        // The two frames after the page was used may still be sampling it.
+        for _ in 0..2 {
+            atlas.begin_frame();
+            assert!(atlas.insert(key(2), 14, 14).is_none());
+        }
+        atlas.begin_frame();
+        let region = atlas.insert(key(2), 14, 14).unwrap();
+        assert_eq!(region.page, 0);
+        assert_eq!(atlas.get(&key(1)), None);
+    }
+
+    #[test]
+    fn page_stats_report_row_occupancy() {
+        let mut atlas = AtlasAllocator::new(32, 2);
+        atlas.insert(key(1), 10, 10).unwrap();
+        atlas.insert(key(2), 10, 6).unwrap();
+        atlas.insert(key(3), 15, 4).unwrap();
+        atlas.insert(key(4), 0, 0).unwrap();
+
+        let stats = atlas.page_stats();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].glyphs, 3);
+        assert_eq!(
+            stats[0].rows,
+            vec![
+                AtlasRowStats {
+                    y: 0,
+                    height: 11,
+                    glyphs: 2,
+                    used_width: 22,
+                    glyph_pixels: 160,
+                },
+                AtlasRowStats {
+                    y: 11,
+                    height: 5,
+                    glyphs: 1,
+                    used_width: 16,
+                    glyph_pixels: 60,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn dump_stacks_pages_vertically() {
+        let mut first = GrayImage::new(4, 4);
+        first.put_pixel(1, 2, image::Luma([200]));
+        let mut second = GrayImage::new(4, 4);
+        second.put_pixel(3, 0, image::Luma([90]));
+        let dump = AtlasDump {
+            page_size: 4,
+            pages: vec![first, second]
+                .into_iter()
+                .map(|pixels| AtlasPageDump {
+                    pixels,
+                    stats: AtlasPageStats::default(),
+                })
+                .collect(),
+        };
+
+        let image = dump.to_image();
+        assert_eq!(image.dimensions(), (4, 8));
+        assert_eq!(image.get_pixel(1, 2).0, [200]);
+        assert_eq!(image.get_pixel(3, 4).0, [90]);
+        assert_eq!(image.get_pixel(3, 0).0, [0]);
+    }
+}
@@ -0,0 +1,355 @@
+//! Runtime renderer selection.
+//!
+//! A [`Backend`] owns a renderer together with the surface it draws to, behind
+//! one object-safe trait, so apps choose how to render at startup instead of
+//! naming `MetalRenderer` and `MetalSurface` directly. [`select_backend`]
+//! picks one from the [`BACKEND_ENV_VAR`] environment variable, the app's
+//! preference, or the platform default, in that order. The environment wins
+//! so CI and VMs can force the software renderer without code changes.
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use image::RgbaImage;
+use winit::window::Window;
+
+use crate::software::{SoftwareRenderer, SoftwareSurface};
+use crate::{AtlasDump, RenderMode, Renderer, Scene};
+
+/// Environment variable naming the backend to use, e.g. `MOTIF_BACKEND=software`.
+pub const BACKEND_ENV_VAR: &str = "MOTIF_BACKEND";
+
+/// The renderers a [`Backend`] can be built from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BackendKind {
+    /// The Metal GPU renderer (macOS only).
+    Metal,
+    /// The CPU renderer, presenting through softbuffer.
+    Software,
+}
+
+impl BackendKind {
+    pub const ALL: [BackendKind; 2] = [BackendKind::Metal, BackendKind::Software];
+
+    /// The name used in [`BACKEND_ENV_VAR`] and debug output.
+    pub fn name(self) -> &'static str {
+        match self {
+            BackendKind::Metal => "metal",
+            BackendKind::Software => "software",
+        }
+    }
+
+    /// Whether this backend is compiled in on the current platform.
+    pub fn is_available(self) -> bool {
+        match self {
+            BackendKind::Metal => cfg!(target_os = "macos"),
+            BackendKind::Software => true,
+        }
+    }
+
+    /// The GPU backend where there is one, otherwise software.
+    pub fn platform_default() -> Self {
+        if BackendKind::Metal.is_available() {
+            BackendKind::Metal
+        } else {
+            BackendKind::Software
+        }
+    }
+
+    /// Create this backend's renderer and a surface for `window`.
+    pub fn create(self, window: Arc<Window>) -> Result<Box<dyn Backend>, BackendError> {
+        match self {
+            #[cfg(target_os = "macos")]
+            BackendKind::Metal => Ok(Box::new(MetalBackend::new(window))),
+            #[cfg(not(target_os = "macos"))]
+            BackendKind::Metal => Err(BackendError::Unavailable(self)),
+            BackendKind::Software => {
+                let surface = SoftwareSurface::new(window)
+                    .map_err(|e| BackendError::Surface(e.to_string()))?;
+                Ok(Box::new(SoftwareBackend {
+                    renderer: SoftwareRenderer::new(),
+                    surface,
+                }))
+            }
+        }
+    }
+}
+
+impl fmt::Display for BackendKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for BackendKind {
+    type Err = BackendError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        BackendKind::ALL
+            .into_iter()
+            .find(|kind| kind.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| BackendError::Unknown(s.to_string()))
+    }
+}
+
+/// Why a backend couldn't be selected or created.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BackendError {
+    /// The name doesn't match any [`BackendKind`].
+    Unknown(String),
+    /// The backend isn't compiled in on this platform.
+    Unavailable(BackendKind),
+    /// The backend exists but couldn't attach to the window.
+    Surface(String),
+}
+
+impl fmt::Display for BackendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BackendError::Unknown(name) => {
+                let names: Vec<_> = BackendKind::ALL.iter().map(|k| k.name()).collect();
+                write!(
+                    f,
+                    "unknown backend {name:?} (expected one of: {})",
+                    names.join(", ")
+                )
+            }
+            BackendError::Unavailable(kind) => {
+                write!(f, "the {kind} backend is not available on this platform")
+            }
+            BackendError::Surface(message) => write!(f, "failed to create surface: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for BackendError {}
+
+/// A renderer bound to a window surface.
+pub trait Backend {
+    fn kind(&self) -> BackendKind;
+
+    /// The device frames are rendered on, for debug output.
+    fn device_name(&self) -> String;
+
+    /// Update the surface size in device pixels (call on window resize).
+    fn resize(&mut self, width: f32, height: f32);
+
+    fn drawable_size(&self) -> (f32, f32);
+
+    /// Render the scene and present it to the window.
+    fn render(&mut self, scene: &Scene);
+
+    /// Render the scene offscreen at the surface's size and read the pixels
+    /// back as sRGB, premultiplied RGBA8. Unlike capturing the window, this
+    /// needs no screen-recording permission and never includes other windows.
+    fn render_to_image(&mut self, scene: &Scene) -> RgbaImage;
+
+    /// Like [`render_to_image`](Self::render_to_image), for a scene built
+    /// at another size or scale factor: `size` is in logical pixels and the
+    /// image is `size * scale` device pixels.
+    fn render_to_image_at(&mut self, scene: &Scene, size: (f32, f32), scale: f32) -> RgbaImage;
+
+    fn render_mode(&self) -> RenderMode;
+
+    /// Switch between normal output and the overdraw heat map. Applies from
+    /// the next frame.
+    fn set_render_mode(&mut self, mode: RenderMode);
+
+    /// See [`Renderer::last_frame_gpu_time`].
+    fn last_frame_gpu_time(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Read back the glyph atlas, or `None` if the renderer doesn't keep
+    /// one (the software renderer rasterizes glyphs straight into the frame).
+    fn dump_glyph_atlas(&self) -> Option<AtlasDump> {
+        None
+    }
+}
+
+/// Create the backend named by [`BACKEND_ENV_VAR`], else `preferred`, else
+/// the platform default.
+pub fn select_backend(
+    window: Arc<Window>,
+    preferred: Option<BackendKind>,
+) -> Result<Box<dyn Backend>, BackendError> {
+    let env = std::env::var(BACKEND_ENV_VAR).ok();
+    resolve_backend(env.as_deref(), preferred)?.create(window)
+}
+
+/// The selection rule behind [`select_backend`], without touching the
+/// environment or a window. An empty `env` counts as unset.
+pub fn resolve_backend(
+    env: Option<&str>,
+    preferred: Option<BackendKind>,
+) -> Result<BackendKind, BackendError> {
+    let kind = match env.filter(|s| !s.trim().is_empty()) {
+        Some(name) => name.parse()?,
+        None => preferred.unwrap_or_else(BackendKind::platform_default),
+    };
+    if kind.is_available() {
+        Ok(kind)
+    } else {
+        Err(BackendError::Unavailable(kind))
+    }
+}
+
+struct SoftwareBackend {
+    renderer: SoftwareRenderer,
+    surface: SoftwareSurface,
+}
+
+impl Backend for SoftwareBackend {
+    fn kind(&self) -> BackendKind {
+        BackendKind::Software
+    }
+
+    fn device_name(&self) -> String {
+        "cpu".to_string()
+    }
+
+    fn resize(&mut self, width: f32, height: f32) {
+        self.surface.resize(width, height);
+    }
+
+    fn drawable_size(&self) -> (f32, f32) {
+        self.surface.drawable_size()
+    }
+
+    fn render(&mut self, scene: &Scene) {
+        self.renderer.render(scene, &mut self.surface);
+    }
+
+    fn render_to_image(&mut self, scene: &Scene) -> RgbaImage {
+        let size = self.surface.drawable_size();
+        self.renderer.render_to_image(scene, size, 1.0)
+    }
+
+    fn render_to_image_at(&mut self, scene: &Scene, size: (f32, f32), scale: f32) -> RgbaImage {
+        self.renderer.render_to_image(scene, size, scale)
+    }
+
+    fn render_mode(&self) -> RenderMode {
+        self.renderer.render_mode()
+    }
+
+    fn set_render_mode(&mut self, mode: RenderMode) {
+        self.renderer.set_render_mode(mode);
+    }
+}
+
+#[cfg(target_os = "macos")]
+struct MetalBackend {
+    renderer: crate::metal::MetalRenderer,
+    surface: crate::metal::MetalSurface,
+    // The surface's layer is attached to this window's view; holding the
+    // window keeps it alive as long as the surface.
+    _window: Arc<Window>,
+}
+
+#[cfg(target_os = "macos")]
+impl MetalBackend {
+    fn new(window: Arc<Window>) -> Self {
+        let renderer = crate::metal::MetalRenderer::new();
+        // SAFETY: the backend owns the window, so it outlives the surface.
+        let surface = unsafe { crate::metal::MetalSurface::new(&window, renderer.device()) };
+        Self {
+            renderer,
+            surface,
+            _window: window,
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl Backend for MetalBackend {
+    fn kind(&self) -> BackendKind {
+        BackendKind::Metal
+    }
+
+    fn device_name(&self) -> String {
+        self.renderer.device().name().to_string()
+    }
+
+    fn resize(&mut self, width: f32, height: f32) {
+        self.surface.resize(width, height);
+    }
+
+    fn drawable_size(&self) -> (f32, f32) {
+        self.surface.drawable_size()
+    }
+
+    fn render(&mut self, scene: &Scene) {
+        self.renderer.render(scene, &mut self.surface);
+    }
+
+    fn render_to_image(&mut self, scene: &Scene) -> RgbaImage {
+        let size = self.surface.drawable_size();
+        self.renderer.render_to_image(scene, size, 1.0)
+    }
+
+    fn render_to_image_at(&mut self, scene: &Scene, size: (f32, f32), scale: f32) -> RgbaImage {
+        self.renderer.render_to_image(scene, size, scale)
+    }
+
+    fn render_mode(&self) -> RenderMode {
+        self.renderer.render_mode()
+    }
+
+    fn set_render_mode(&mut self, mode: RenderMode) {
+        self.renderer.set_render_mode(mode);
+    }
+
+    fn last_frame_gpu_time(&self) -> Option<Duration> {
+        self.renderer.last_frame_gpu_time()
+    }
+
+    fn dump_glyph_atlas(&self) -> Option<AtlasDump> {
+        Some(self.renderer.dump_glyph_atlas())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_backend_names() {
+        assert_eq!("software".parse(), Ok(BackendKind::Software));
+        assert_eq!(" Metal ".parse(), Ok(BackendKind::Metal));
+        assert_eq!(
+            "wgpu".parse::<BackendKind>(),
+            Err(BackendError::Unknown("wgpu".to_string()))
+        );
+    }
+
+    #[test]
+    fn env_overrides_preference() {
+        assert_eq!(
+            resolve_backend(Some("software"), Some(BackendKind::Metal)),
+            Ok(BackendKind::Software)
+        );
+        assert_eq!(
+            resolve_backend(Some(""), Some(BackendKind::Software)),
+            Ok(BackendKind::Software)
+        );
+        assert_eq!(
+            resolve_backend(None, None),
+            Ok(BackendKind::platform_default())
+        );
+    }
+
+    #[test]
+    fn unavailable_backends_are_rejected() {
+        let result = resolve_backend(Some("metal"), None);
+        if BackendKind::Metal.is_available() {
+            assert_eq!(result, Ok(BackendKind::Metal));
+        } else {
+            assert_eq!(result, Err(BackendError::Unavailable(BackendKind::Metal)));
+        }
+        assert!(resolve_backend(Some("bogus"), None).is_err());
+    }
+}
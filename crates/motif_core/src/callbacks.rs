@@ -2,20 +2,102 @@
 //!
 //! Stores callbacks registered during rendering and allows dispatching
 //! them after input events are processed.
+//!
+//! Mouse and key events propagate in two phases. Capture handlers run first,
+//! from the outermost ancestor down to the target, so containers can
+//! intercept events before their children see them. Then the event bubbles:
+//! the handler on the target element runs, then the handlers on each
+//! ancestor, until one calls [`EventContext::stop_propagation`]. Ancestry
+//! comes from [`push_parent`](CallbackRegistry::push_parent) /
+//! [`pop_parent`](CallbackRegistry::pop_parent) around painting children.
 
-use crate::ElementId;
+use crate::input::{ElementState, InputAction, KeyEvent, MouseButton, MouseEvent, MouseEventKind};
+use crate::{AccessValue, ElementId};
 use std::collections::HashMap;
 
 /// A click callback that can be invoked when an element is clicked.
 pub type ClickCallback = Box<dyn FnMut()>;
 
+/// A mouse callback, given the event and the bubbling state.
+pub type MouseCallback = Box<dyn FnMut(&MouseEvent, &mut EventContext)>;
+
+/// A hover callback, called with `true` when the cursor enters an element
+/// (or any of its descendants) and `false` when it leaves.
+pub type HoverCallback = Box<dyn FnMut(bool)>;
+
+/// Called when assistive technology sets an element's value.
+pub type ValueCallback = Box<dyn FnMut(&AccessValue)>;
+
+/// A key callback, given the event and the propagation state.
+pub type KeyCallback = Box<dyn FnMut(&KeyEvent, &mut EventContext)>;
+
+/// Called with editing actions that don't come from a key press, e.g. the
+/// Edit menu's Copy and Paste.
+pub type InputActionCallback = Box<dyn FnMut(&InputAction, &mut EventContext)>;
+
+type Handlers<E> = HashMap<ElementId, Box<dyn FnMut(&E, &mut EventContext)>>;
+
+/// Which way an event is travelling through the element tree.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventPhase {
+    /// From the outermost ancestor down to the target.
+    Capture,
+    /// From the target up to the outermost ancestor.
+    Bubble,
+}
+
+/// Propagation state handed to mouse and key handlers.
+#[derive(Debug)]
+pub struct EventContext {
+    target: ElementId,
+    current: ElementId,
+    phase: EventPhase,
+    propagation_stopped: bool,
+}
+
+impl EventContext {
+    pub fn phase(&self) -> EventPhase {
+        self.phase
+    }
+
+    /// The element the event was dispatched to.
+    pub fn target(&self) -> ElementId {
+        self.target
+    }
+
+    /// The element whose handler is running.
+    pub fn current(&self) -> ElementId {
+        self.current
+    }
+
+    /// Don't run any further handlers for this event, in either phase.
+    pub fn stop_propagation(&mut self) {
+        self.propagation_stopped = true;
+    }
+}
+
 /// Registry for element callbacks.
 ///
 /// Callbacks are registered during rendering and cleared each frame.
 /// After processing input events, call `dispatch_click` to invoke
 /// the appropriate callback.
 pub struct CallbackRegistry {
-    click_handlers: HashMap<ElementId, ClickCallback>,
+    click_handlers: HashMap<ElementId, MouseCallback>,
+    mouse_down_handlers: HashMap<ElementId, MouseCallback>,
+    mouse_up_handlers: HashMap<ElementId, MouseCallback>,
+    mouse_move_handlers: HashMap<ElementId, MouseCallback>,
+    scroll_handlers: HashMap<ElementId, MouseCallback>,
+    mouse_capture_handlers: HashMap<ElementId, MouseCallback>,
+    key_down_handlers: HashMap<ElementId, KeyCallback>,
+    key_capture_handlers: HashMap<ElementId, KeyCallback>,
+    hover_handlers: HashMap<ElementId, HoverCallback>,
+    value_handlers: HashMap<ElementId, ValueCallback>,
+    input_action_handlers: HashMap<ElementId, InputActionCallback>,
+    parents: HashMap<ElementId, ElementId>,
+    parent_stack: Vec<ElementId>,
+    /// The hovered element and its ancestors, as of the last
+    /// `dispatch_hover`. Kept across frames.
+    hovered: Vec<ElementId>,
 }
 
 impl Default for CallbackRegistry {
@@ -28,29 +110,285 @@ impl CallbackRegistry {
     pub fn new() -> Self {
         Self {
             click_handlers: HashMap::new(),
+            mouse_down_handlers: HashMap::new(),
+            mouse_up_handlers: HashMap::new(),
+            mouse_move_handlers: HashMap::new(),
+            scroll_handlers: HashMap::new(),
+            mouse_capture_handlers: HashMap::new(),
+            key_down_handlers: HashMap::new(),
+            key_capture_handlers: HashMap::new(),
+            hover_handlers: HashMap::new(),
+            value_handlers: HashMap::new(),
+            input_action_handlers: HashMap::new(),
+            parents: HashMap::new(),
+            parent_stack: Vec::new(),
+            hovered: Vec::new(),
         }
     }
 
     /// Register a click handler for an element.
-    pub fn on_click(&mut self, id: ElementId, callback: impl FnMut() + 'static) {
+    pub fn on_click(&mut self, id: ElementId, mut callback: impl FnMut() + 'static) {
+        self.on_click_event(id, move |_, _| callback());
+    }
+
+    /// Register a click handler that receives the event.
+    pub fn on_click_event(
+        &mut self,
+        id: ElementId,
+        callback: impl FnMut(&MouseEvent, &mut EventContext) + 'static,
+    ) {
         self.click_handlers.insert(id, Box::new(callback));
     }
 
+    /// Register a handler for mouse button presses on an element.
+    pub fn on_mouse_down(
+        &mut self,
+        id: ElementId,
+        callback: impl FnMut(&MouseEvent, &mut EventContext) + 'static,
+    ) {
+        self.mouse_down_handlers.insert(id, Box::new(callback));
+    }
+
+    /// Register a handler for mouse button releases on an element.
+    pub fn on_mouse_up(
+        &mut self,
+        id: ElementId,
+        callback: impl FnMut(&MouseEvent, &mut EventContext) + 'static,
+    ) {
+        self.mouse_up_handlers.insert(id, Box::new(callback));
+    }
+
+    /// Register a handler for the cursor moving over an element.
+    pub fn on_mouse_move(
+        &mut self,
+        id: ElementId,
+        callback: impl FnMut(&MouseEvent, &mut EventContext) + 'static,
+    ) {
+        self.mouse_move_handlers.insert(id, Box::new(callback));
+    }
+
+    /// Register a handler for scrolling over an element.
+    pub fn on_scroll(
+        &mut self,
+        id: ElementId,
+        callback: impl FnMut(&MouseEvent, &mut EventContext) + 'static,
+    ) {
+        self.scroll_handlers.insert(id, Box::new(callback));
+    }
+
+    /// Register a capture-phase handler, which sees every mouse event
+    /// dispatched to the element or its descendants before they do.
+    pub fn on_mouse_capture(
+        &mut self,
+        id: ElementId,
+        callback: impl FnMut(&MouseEvent, &mut EventContext) + 'static,
+    ) {
+        self.mouse_capture_handlers.insert(id, Box::new(callback));
+    }
+
+    /// Register a handler for key presses while the element or one of its
+    /// descendants has focus.
+    pub fn on_key_down(
+        &mut self,
+        id: ElementId,
+        callback: impl FnMut(&KeyEvent, &mut EventContext) + 'static,
+    ) {
+        self.key_down_handlers.insert(id, Box::new(callback));
+    }
+
+    /// Register a capture-phase handler for key presses and releases
+    /// dispatched to the element or its descendants.
+    pub fn on_key_capture(
+        &mut self,
+        id: ElementId,
+        callback: impl FnMut(&KeyEvent, &mut EventContext) + 'static,
+    ) {
+        self.key_capture_handlers.insert(id, Box::new(callback));
+    }
+
+    /// Register a handler for assistive technology setting an element's
+    /// value (e.g. dictating into a text input).
+    pub fn on_set_value(&mut self, id: ElementId, callback: impl FnMut(&AccessValue) + 'static) {
+        self.value_handlers.insert(id, Box::new(callback));
+    }
+
+    /// Register a handler for editing actions sent to the element or its
+    /// descendants while focused, e.g. from the Edit menu.
+    pub fn on_input_action(
+        &mut self,
+        id: ElementId,
+        callback: impl FnMut(&InputAction, &mut EventContext) + 'static,
+    ) {
+        self.input_action_handlers.insert(id, Box::new(callback));
+    }
+
+    /// Register a handler for the cursor entering and leaving an element.
+    pub fn on_hover(&mut self, id: ElementId, callback: impl FnMut(bool) + 'static) {
+        self.hover_handlers.insert(id, Box::new(callback));
+    }
+
+    /// Record `id` as a child of the innermost element pushed with
+    /// [`push_parent`](Self::push_parent), if any.
+    pub fn register_element(&mut self, id: ElementId) {
+        if let Some(&parent) = self.parent_stack.last() {
+            if parent != id {
+                self.parents.insert(id, parent);
+            }
+        }
+    }
+
+    /// Make `id` the parent of elements registered until the matching
+    /// [`pop_parent`](Self::pop_parent).
+    pub fn push_parent(&mut self, id: ElementId) {
+        self.parent_stack.push(id);
+    }
+
+    pub fn pop_parent(&mut self) {
+        self.parent_stack.pop();
+    }
+
+    /// `id` followed by each of its registered ancestors.
+    pub fn ancestry(&self, id: ElementId) -> Vec<ElementId> {
+        let mut chain = vec![id];
+        let mut current = id;
+        while let Some(&parent) = self.parents.get(&current) {
+            if chain.contains(&parent) {
+                break;
+            }
+            chain.push(parent);
+            current = parent;
+        }
+        chain
+    }
+
     /// Dispatch a click event to the registered handler.
     /// Returns true if a handler was found and invoked.
     pub fn dispatch_click(&mut self, id: ElementId) -> bool {
-        if let Some(callback) = self.click_handlers.get_mut(&id) {
-            callback();
-            true
-        } else {
-            false
+        let event = MouseEvent {
+            kind: MouseEventKind::Up,
+            position: None,
+            button: Some(MouseButton::Left),
+            modifiers: Default::default(),
+        };
+        self.dispatch_click_event(id, &event)
+    }
+
+    /// Dispatch a click on `id`, bubbling to its ancestors.
+    /// Returns true if any handler was invoked.
+    pub fn dispatch_click_event(&mut self, id: ElementId, event: &MouseEvent) -> bool {
+        let chain = self.ancestry(id);
+        propagate(None, &mut self.click_handlers, &chain, event)
+    }
+
+    /// Dispatch a mouse `Down`, `Up`, `Move` or `Scroll` event on `id`
+    /// through the capture and bubble phases. `Enter` and `Leave` are
+    /// ignored. Returns true if any handler was invoked.
+    pub fn dispatch_mouse(&mut self, id: ElementId, event: &MouseEvent) -> bool {
+        let chain = self.ancestry(id);
+        let handlers = match event.kind {
+            MouseEventKind::Down => &mut self.mouse_down_handlers,
+            MouseEventKind::Up => &mut self.mouse_up_handlers,
+            MouseEventKind::Move => &mut self.mouse_move_handlers,
+            MouseEventKind::Scroll { .. } => &mut self.scroll_handlers,
+            MouseEventKind::Enter | MouseEventKind::Leave => return false,
+        };
+        propagate(
+            Some(&mut self.mouse_capture_handlers),
+            handlers,
+            &chain,
+            event,
+        )
+    }
+
+    /// Dispatch a value set by assistive technology to `id`. Doesn't bubble.
+    /// Returns true if a handler was invoked.
+    pub fn dispatch_set_value(&mut self, id: ElementId, value: &AccessValue) -> bool {
+        match self.value_handlers.get_mut(&id) {
+            Some(callback) => {
+                callback(value);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Dispatch a key event on the focused element `id` through the capture
+    /// and bubble phases. Releases only reach capture handlers. Returns true
+    /// if any handler was invoked.
+    pub fn dispatch_key(&mut self, id: ElementId, event: &KeyEvent) -> bool {
+        let chain = self.ancestry(id);
+        let mut no_handlers = HashMap::new();
+        let handlers = match event.state {
+            ElementState::Pressed => &mut self.key_down_handlers,
+            ElementState::Released => &mut no_handlers,
+        };
+        propagate(
+            Some(&mut self.key_capture_handlers),
+            handlers,
+            &chain,
+            event,
+        )
+    }
+
+    /// Dispatch an editing action on the focused element `id`, bubbling to
+    /// its ancestors. Returns true if any handler was invoked.
+    pub fn dispatch_input_action(&mut self, id: ElementId, action: InputAction) -> bool {
+        let chain = self.ancestry(id);
+        propagate(None, &mut self.input_action_handlers, &chain, &action)
+    }
+
+    /// Notify hover handlers that the hovered element changed from
+    /// `previous` to `current`. Elements stay hovered while the cursor is
+    /// over any of their descendants, so only the parts of the two ancestry
+    /// chains that differ are notified.
+    pub fn dispatch_hover(&mut self, previous: Option<ElementId>, current: Option<ElementId>) {
+        let old = previous.map(|id| self.ancestry(id)).unwrap_or_default();
+        let new = current.map(|id| self.ancestry(id)).unwrap_or_default();
+        for id in old.iter().filter(|id| !new.contains(id)) {
+            if let Some(callback) = self.hover_handlers.get_mut(id) {
+                callback(false);
+            }
+        }
+        for id in new.iter().rev().filter(|id| !old.contains(id)) {
+            if let Some(callback) = self.hover_handlers.get_mut(id) {
+                callback(true);
+            }
         }
+        self.hovered = new;
+    }
+
+    /// Whether `id` or one of its descendants was hovered at the last
+    /// [`dispatch_hover`](Self::dispatch_hover).
+    pub fn is_hovered(&self, id: ElementId) -> bool {
+        self.hovered.contains(&id)
     }
 
     /// Clear all registered callbacks.
     /// Call this at the start of each frame.
     pub fn clear(&mut self) {
         self.click_handlers.clear();
+        self.mouse_down_handlers.clear();
+        self.mouse_up_handlers.clear();
+        self.mouse_move_handlers.clear();
+        self.scroll_handlers.clear();
+        self.mouse_capture_handlers.clear();
+        self.key_down_handlers.clear();
+        self.key_capture_handlers.clear();
+        self.hover_handlers.clear();
+        self.value_handlers.clear();
+        self.input_action_handlers.clear();
+        self.parents.clear();
+        self.parent_stack.clear();
+    }
+
+    /// The element a press on `id` should focus: `id` or its nearest
+    /// ancestor with a key or editing-action handler, which is what key
+    /// events and Edit menu actions are sent to. Capture-phase key handlers
+    /// don't count, since they only watch keys headed for a descendant.
+    pub fn focus_target(&self, id: ElementId) -> Option<ElementId> {
+        self.ancestry(id).into_iter().find(|id| {
+            self.key_down_handlers.contains_key(id) || self.input_action_handlers.contains_key(id)
+        })
     }
 
     /// Check if a click handler is registered for an element.
@@ -59,6 +397,51 @@ impl CallbackRegistry {
     }
 }
 
+/// Run the `capture` handlers down `chain` (target first, outermost
+/// ancestor last) and then the `bubble` handlers back up it, until one
+/// stops propagation.
+fn propagate<E>(
+    capture: Option<&mut Handlers<E>>,
+    bubble: &mut Handlers<E>,
+    chain: &[ElementId],
+    event: &E,
+) -> bool {
+    let Some(&target) = chain.first() else {
+        return false;
+    };
+    let mut cx = EventContext {
+        target,
+        current: target,
+        phase: EventPhase::Capture,
+        propagation_stopped: false,
+    };
+    let mut handled = false;
+    let mut run = |handlers: &mut Handlers<E>, id: ElementId, cx: &mut EventContext| {
+        if let Some(callback) = handlers.get_mut(&id) {
+            cx.current = id;
+            callback(event, cx);
+            handled = true;
+        }
+    };
+
+    if let Some(capture) = capture {
+        for &id in chain.iter().rev() {
+            run(capture, id, &mut cx);
+            if cx.propagation_stopped {
+                return true;
+            }
+        }
+    }
+    cx.phase = EventPhase::Bubble;
+    for &id in chain {
+        run(bubble, id, &mut cx);
+        if cx.propagation_stopped {
+            break;
+        }
+    }
+    handled
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -113,4 +496,103 @@ mod tests {
         registry.dispatch_click(ElementId(2));
         assert_eq!(count.get(), 11);
     }
+
+    fn mouse(kind: MouseEventKind) -> MouseEvent {
+        MouseEvent {
+            kind,
+            position: None,
+            button: Some(MouseButton::Left),
+            modifiers: Default::default(),
+        }
+    }
+
+    /// Registry where 3 is a child of 2, which is a child of 1.
+    fn nested() -> CallbackRegistry {
+        let mut registry = CallbackRegistry::new();
+        for id in 1..=3 {
+            registry.register_element(ElementId(id));
+            registry.push_parent(ElementId(id));
+        }
+        registry
+    }
+
+    #[test]
+    fn clicks_bubble_to_ancestors() {
+        let mut registry = nested();
+        let log = Rc::new(std::cell::RefCell::new(Vec::new()));
+        for id in [1, 3] {
+            let log = log.clone();
+            registry.on_click_event(ElementId(id), move |_, cx| {
+                log.borrow_mut().push((cx.current().0, cx.target().0));
+            });
+        }
+
+        assert!(registry.dispatch_click(ElementId(3)));
+        assert_eq!(*log.borrow(), vec![(3, 3), (1, 3)]);
+    }
+
+    #[test]
+    fn stop_propagation_ends_bubbling() {
+        let mut registry = nested();
+        let outer = Rc::new(Cell::new(false));
+        let outer_clone = outer.clone();
+        registry.on_mouse_down(ElementId(1), move |_, _| outer_clone.set(true));
+        registry.on_mouse_down(ElementId(2), |_, cx| cx.stop_propagation());
+
+        assert!(registry.dispatch_mouse(ElementId(3), &mouse(MouseEventKind::Down)));
+        assert!(!outer.get());
+        assert!(!registry.dispatch_mouse(ElementId(3), &mouse(MouseEventKind::Up)));
+    }
+
+    #[test]
+    fn capture_runs_outside_in_before_bubbling() {
+        let mut registry = nested();
+        let log = Rc::new(std::cell::RefCell::new(Vec::new()));
+        for id in [1, 3] {
+            let capture_log = log.clone();
+            registry.on_mouse_capture(ElementId(id), move |_, cx| {
+                capture_log.borrow_mut().push((cx.current().0, cx.phase()));
+            });
+            let bubble_log = log.clone();
+            registry.on_mouse_move(ElementId(id), move |_, cx| {
+                bubble_log.borrow_mut().push((cx.current().0, cx.phase()));
+            });
+        }
+
+        assert!(registry.dispatch_mouse(ElementId(3), &mouse(MouseEventKind::Move)));
+        assert_eq!(
+            *log.borrow(),
+            vec![
+                (1, EventPhase::Capture),
+                (3, EventPhase::Capture),
+                (3, EventPhase::Bubble),
+                (1, EventPhase::Bubble),
+            ]
+        );
+
+        // Stopping in capture keeps the event from the target entirely.
+        log.borrow_mut().clear();
+        registry.on_mouse_capture(ElementId(1), |_, cx| cx.stop_propagation());
+        assert!(registry.dispatch_mouse(ElementId(3), &mouse(MouseEventKind::Move)));
+        assert!(log.borrow().is_empty());
+    }
+
+    #[test]
+    fn hover_tracks_ancestry_changes() {
+        let mut registry = nested();
+        let log = Rc::new(std::cell::RefCell::new(Vec::new()));
+        for id in 1..=3 {
+            let log = log.clone();
+            registry.on_hover(ElementId(id), move |hovered| {
+                log.borrow_mut().push((id, hovered));
+            });
+        }
+
+        registry.dispatch_hover(None, Some(ElementId(3)));
+        assert_eq!(*log.borrow(), vec![(1, true), (2, true), (3, true)]);
+
+        log.borrow_mut().clear();
+        registry.dispatch_hover(Some(ElementId(3)), Some(ElementId(2)));
+        assert_eq!(*log.borrow(), vec![(3, false)]);
+    }
 }
@@ -0,0 +1,267 @@
+//! The time timers and animations run on.
+//!
+//! A window's [`Clock`] is the system clock in apps. Tests use a
+//! [manual](Clock::manual) one that only moves when
+//! [advanced](Clock::advance), so an animation can be stepped to an exact
+//! point instead of slept through:
+//!
+//! ```ignore
+//! let clock = Clock::manual();
+//! let fade = Animation::new(&clock, Duration::from_millis(200));
+//! clock.advance(Duration::from_millis(100));
+//! assert_eq!(fade.lerp(0.0, 1.0), 0.5);
+//! ```
+//!
+//! Apps run on a [scaled](Clock::scaled) clock instead, which follows the
+//! system clock at an adjustable rate so animations can be slowed down to
+//! watch them.
+
+use std::cell::Cell;
+use std::fmt;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// A source of the current time.
+///
+/// Clones of a manual or scaled clock share its time.
+#[derive(Clone, Default)]
+pub struct Clock(Source);
+
+#[derive(Clone, Default)]
+enum Source {
+    #[default]
+    System,
+    Manual(Rc<Cell<Instant>>),
+    Scaled(Rc<Cell<Scaled>>),
+}
+
+/// A scaled clock read `at` when the system clock read `real`, and has
+/// run at `scale` times its speed since.
+#[derive(Clone, Copy)]
+struct Scaled {
+    real: Instant,
+    at: Instant,
+    scale: f64,
+}
+
+impl Scaled {
+    fn now(&self) -> Instant {
+        self.at + Instant::now().duration_since(self.real).mul_f64(self.scale)
+    }
+}
+
+impl Clock {
+    /// The system's monotonic clock.
+    pub fn system() -> Self {
+        Self(Source::System)
+    }
+
+    /// A clock stopped at the current time, which moves only when
+    /// [advanced](Self::advance).
+    pub fn manual() -> Self {
+        Self(Source::Manual(Rc::new(Cell::new(Instant::now()))))
+    }
+
+    /// A clock running at `scale` times the speed of the system clock,
+    /// adjustable with [`set_time_scale`](Self::set_time_scale).
+    pub fn scaled(scale: f64) -> Self {
+        let now = Instant::now();
+        Self(Source::Scaled(Rc::new(Cell::new(Scaled {
+            real: now,
+            at: now,
+            scale: checked_scale(scale),
+        }))))
+    }
+
+    pub fn is_manual(&self) -> bool {
+        matches!(self.0, Source::Manual(_))
+    }
+
+    pub fn now(&self) -> Instant {
+        match &self.0 {
+            Source::System => Instant::now(),
+            Source::Manual(now) => now.get(),
+            Source::Scaled(scaled) => scaled.get().now(),
+        }
+    }
+
+    /// How fast this clock runs relative to the system clock.
+    pub fn time_scale(&self) -> f64 {
+        match &self.0 {
+            Source::Scaled(scaled) => scaled.get().scale,
+            _ => 1.0,
+        }
+    }
+
+    /// Run a scaled clock at `scale` times the speed of the system clock
+    /// from now on; `0.1` plays animations at 10% speed and `0` pauses them.
+    ///
+    /// # Panics
+    ///
+    /// If this isn't a scaled clock, or `scale` is negative or not finite.
+    #[track_caller]
+    pub fn set_time_scale(&self, scale: f64) {
+        let Source::Scaled(scaled) = &self.0 else {
+            panic!("only scaled clocks can change speed");
+        };
+        let scale = checked_scale(scale);
+        let now = scaled.get().now();
+        scaled.set(Scaled {
+            real: Instant::now(),
+            at: now,
+            scale,
+        });
+    }
+
+    /// The system time at which this clock will read `at`, for sleeping
+    /// until a deadline. `None` if it never will on its own: a manual
+    /// clock, or a paused scaled one.
+    pub fn real_instant(&self, at: Instant) -> Option<Instant> {
+        match &self.0 {
+            Source::System => Some(at),
+            Source::Manual(_) => None,
+            Source::Scaled(scaled) => {
+                let scaled = scaled.get();
+                (scaled.scale > 0.0).then(|| {
+                    scaled.real
+                        + at.saturating_duration_since(scaled.at)
+                            .div_f64(scaled.scale)
+                })
+            }
+        }
+    }
+
+    /// Move a manual clock forward by `by`.
+    ///
+    /// # Panics
+    ///
+    /// If this isn't a manual clock.
+    #[track_caller]
+    pub fn advance(&self, by: Duration) {
+        let Source::Manual(now) = &self.0 else {
+            panic!("only manual clocks can be advanced");
+        };
+        now.set(now.get() + by);
+    }
+}
+
+#[track_caller]
+fn checked_scale(scale: f64) -> f64 {
+    assert!(
+        scale.is_finite() && scale >= 0.0,
+        "time scale must be finite and non-negative, got {scale}"
+    );
+    scale
+}
+
+impl fmt::Debug for Clock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Source::System => f.write_str("Clock::System"),
+            Source::Manual(_) => f.write_str("Clock::Manual"),
+            Source::Scaled(scaled) => write!(f, "Clock::Scaled({})", scaled.get().scale),
+        }
+    }
+}
+
+/// Linear progress over a fixed duration, measured on a [`Clock`].
+#[derive(Clone, Debug)]
+pub struct Animation {
+    clock: Clock,
+    start: Instant,
+    duration: Duration,
+}
+
+impl Animation {
+    /// An animation starting now on `clock`.
+    pub fn new(clock: &Clock, duration: Duration) -> Self {
+        Self {
+            clock: clock.clone(),
+            start: clock.now(),
+            duration,
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.clock.now().saturating_duration_since(self.start)
+    }
+
+    /// How far along the animation is, from 0 to 1.
+    pub fn progress(&self) -> f32 {
+        if self.duration.is_zero() {
+            return 1.0;
+        }
+        (self.elapsed().as_secs_f32() / self.duration.as_secs_f32()).min(1.0)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.elapsed() >= self.duration
+    }
+
+    /// The value between `from` and `to` at the current progress.
+    pub fn lerp(&self, from: f32, to: f32) -> f32 {
+        from + (to - from) * self.progress()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manual_clocks_only_move_when_advanced() {
+        let clock = Clock::manual();
+        let start = clock.now();
+        assert_eq!(clock.now(), start);
+
+        clock.clone().advance(Duration::from_millis(16));
+        assert_eq!(clock.now(), start + Duration::from_millis(16));
+    }
+
+    #[test]
+    fn scaled_clocks_run_slower_and_map_deadlines_back_to_real_time() {
+        let clock = Clock::scaled(0.1);
+        let start = clock.now();
+        let real_start = Instant::now();
+        assert_eq!(clock.time_scale(), 0.1);
+
+        // 100ms on the clock is 1s of real time.
+        let due = clock
+            .real_instant(start + Duration::from_millis(100))
+            .unwrap();
+        let real = due.duration_since(real_start);
+        assert!(real > Duration::from_millis(900) && real <= Duration::from_secs(1));
+
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(clock.now().duration_since(start) < Duration::from_millis(20));
+
+        // Pausing keeps the current time and leaves deadlines unreachable.
+        clock.set_time_scale(0.0);
+        let paused = clock.now();
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(clock.now(), paused);
+        assert_eq!(clock.real_instant(paused + Duration::from_millis(1)), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "only scaled clocks")]
+    fn the_system_clock_cannot_change_speed() {
+        Clock::system().set_time_scale(0.5);
+    }
+
+    #[test]
+    fn animations_interpolate_exactly_on_a_manual_clock() {
+        let clock = Clock::manual();
+        let fade = Animation::new(&clock, Duration::from_millis(200));
+        assert_eq!(fade.lerp(10.0, 20.0), 10.0);
+
+        clock.advance(Duration::from_millis(100));
+        assert_eq!(fade.progress(), 0.5);
+        assert_eq!(fade.lerp(10.0, 20.0), 15.0);
+        assert!(!fade.is_finished());
+
+        clock.advance(Duration::from_millis(500));
+        assert_eq!(fade.lerp(10.0, 20.0), 20.0);
+        assert!(fade.is_finished());
+    }
+}
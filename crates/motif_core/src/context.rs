@@ -176,14 +176,14 @@ impl<'a> DrawContext<'a> {
 
         for run in layout.glyph_runs_with_font() {
             if let Some(font) = run.font_data {
-                let mut text_run = TextRun::new(device_origin, color, run.font_size, font);
+                let mut text_run = TextRun::new(device_origin, color, run.font_size, font)
+                    .with_line_metrics(run.ascent, run.descent);
                 text_run.normalized_coords = run.normalized_coords;
 
-                for glyph in run.glyphs {
-                    text_run.push_glyph(glyph.id, glyph.x, glyph.y);
-                }
-
                 self.scene.push_text_run(text_run);
+                for glyph in &run.glyphs {
+                    self.scene.push_positioned_glyph(glyph);
+                }
             }
         }
     }
@@ -316,7 +316,7 @@ mod tests {
 
         assert!(scene.text_run_count() > 0, "should create text runs");
         let text_run = &scene.text_runs()[0];
-        assert!(!text_run.glyphs.is_empty(), "should have glyphs");
+        assert!(!scene.glyphs(text_run).is_empty(), "should have glyphs");
         // X position should be exact
         assert_eq!(text_run.origin.x, 10.0);
         // Y position is adjusted for baseline - origin is above baseline
@@ -0,0 +1,463 @@
+//! Damage tracking for partial redraws.
+//!
+//! [`DamageTracker`] remembers a fingerprint of each primitive in the last
+//! scene it saw. Comparing the next scene against it yields the region that
+//! actually changed, so renderers can scissor to it and keep the rest of the
+//! previous frame.
+//!
+//! Primitives are compared by position in draw order, which keeps the diff
+//! cheap and catches reordering. Inserting or removing a primitive damages
+//! everything drawn after it, which is conservative but always correct.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use crate::{
+    DevicePoint, DeviceRect, DeviceSize, GlyphInstance, ImageQuad, PathPrimitive, Quad, Scene,
+    TextRun,
+};
+use palette::Srgba;
+
+/// Region of the frame that needs repainting.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Damage {
+    /// Nothing changed; the previous frame can be shown as is.
+    None,
+    /// Only this rect (device pixels) changed.
+    Partial(DeviceRect),
+    /// Repaint everything.
+    Full,
+}
+
+/// Fingerprint and bounds of one primitive from the previous frame.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Entry {
+    hash: u64,
+    bounds: DeviceRect,
+}
+
+/// Computes [`Damage`] between consecutive scenes.
+#[derive(Debug, Default)]
+pub struct DamageTracker {
+    previous: Option<Vec<Entry>>,
+    background: Option<[u32; 4]>,
+    invalidated: Option<DeviceRect>,
+    invalidate_all: bool,
+}
+
+impl DamageTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mark a rect as dirty on the next frame, even if the scene didn't change
+    /// there (e.g. content drawn outside the scene).
+    pub fn invalidate(&mut self, rect: DeviceRect) {
+        self.invalidated = Some(match self.invalidated {
+            Some(existing) => union(existing, rect),
+            None => rect,
+        });
+    }
+
+    /// Force the next frame to repaint everything, e.g. after a resize.
+    pub fn invalidate_all(&mut self) {
+        self.invalidate_all = true;
+    }
+
+    /// Diff `scene` against the previous one and remember it for next time.
+    pub fn compute(&mut self, scene: &Scene) -> Damage {
+        let entries = entries(scene);
+        let background = color_bits(scene.background());
+
+        let damage = match &self.previous {
+            _ if self.invalidate_all => Damage::Full,
+            None => Damage::Full,
+            Some(_) if self.background != Some(background) => Damage::Full,
+            Some(previous) => {
+                let mut dirty = self.invalidated;
+                let mut mark = |rect: DeviceRect| {
+                    dirty = Some(match dirty {
+                        Some(existing) => union(existing, rect),
+                        None => rect,
+                    });
+                };
+
+                for i in 0..previous.len().max(entries.len()) {
+                    match (previous.get(i), entries.get(i)) {
+                        (Some(old), Some(new)) if old == new => {}
+                        (old, new) => {
+                            old.into_iter().chain(new).for_each(|e| mark(e.bounds));
+                        }
+                    }
+                }
+
+                match dirty {
+                    Some(rect) if rect.size.width > 0.0 && rect.size.height > 0.0 => {
+                        Damage::Partial(rect)
+                    }
+                    _ => Damage::None,
+                }
+            }
+        };
+
+        self.previous = Some(entries);
+        self.background = Some(background);
+        self.invalidated = None;
+        self.invalidate_all = false;
+        damage
+    }
+}
+
+/// Fingerprints for every primitive, in the renderer's draw order.
+fn entries(scene: &Scene) -> Vec<Entry> {
+    let quads = scene.quads().iter().map(quad_entry);
+    let paths = scene.paths().iter().filter_map(path_entry);
+    let images = scene.images().iter().map(image_entry);
+    let text = scene
+        .text_runs()
+        .iter()
+        .filter_map(|run| text_entry(run, scene.glyphs(run)));
+    let overlay = scene.overlay().map(entries).unwrap_or_default();
+    quads
+        .chain(paths)
+        .chain(images)
+        .chain(text)
+        .chain(overlay)
+        .collect()
+}
+
+fn quad_entry(quad: &Quad) -> Entry {
+    let mut hasher = DefaultHasher::new();
+    0u8.hash(&mut hasher);
+    hash_rect(&mut hasher, quad.bounds);
+    color_bits(quad.background).hash(&mut hasher);
+    color_bits(quad.border_color).hash(&mut hasher);
+    let widths = &quad.border_widths;
+    hash_f32s(
+        &mut hasher,
+        &[widths.top, widths.right, widths.bottom, widths.left],
+    );
+    let radii = &quad.corner_radii;
+    hash_f32s(
+        &mut hasher,
+        &[
+            radii.top_left,
+            radii.top_right,
+            radii.bottom_right,
+            radii.bottom_left,
+        ],
+    );
+    hash_clip(&mut hasher, quad.clip_bounds);
+    if let Some(gradient) = &quad.gradient {
+        format!("{:?}", gradient.kind).hash(&mut hasher);
+        for stop in &gradient.stops {
+            stop.offset.to_bits().hash(&mut hasher);
+            color_bits(stop.color).hash(&mut hasher);
+        }
+    }
+
+    let mut bounds = quad.bounds;
+    if let Some(shadow) = &quad.shadow {
+        hash_f32s(
+            &mut hasher,
+            &[
+                shadow.offset.0,
+                shadow.offset.1,
+                shadow.blur_radius,
+                shadow.spread,
+            ],
+        );
+        color_bits(shadow.color).hash(&mut hasher);
+
+        // Blur reaches three sigma (1.5 blur radii) past the spread rect.
+        let reach = shadow.spread + shadow.blur_radius * 1.5;
+        let shadow_bounds = DeviceRect::new(
+            DevicePoint::new(
+                quad.bounds.origin.x + shadow.offset.0 - reach,
+                quad.bounds.origin.y + shadow.offset.1 - reach,
+            ),
+            DeviceSize::new(
+                quad.bounds.size.width + 2.0 * reach,
+                quad.bounds.size.height + 2.0 * reach,
+            ),
+        );
+        bounds = union(bounds, shadow_bounds);
+    }
+
+    Entry {
+        hash: hasher.finish(),
+        bounds: clip(bounds, quad.clip_bounds),
+    }
+}
+
+fn path_entry(path: &PathPrimitive) -> Option<Entry> {
+    let mut hasher = DefaultHasher::new();
+    1u8.hash(&mut hasher);
+    path.path.hash_geometry(&mut hasher);
+    path.fill.map(color_bits).hash(&mut hasher);
+    let mut outset = 0.0;
+    if let Some(stroke) = &path.stroke {
+        stroke.width.to_bits().hash(&mut hasher);
+        color_bits(stroke.color).hash(&mut hasher);
+        outset = stroke.width * 0.5;
+    }
+    hash_clip(&mut hasher, path.clip_bounds);
+
+    let bounds = path.path.bounds()?;
+    let bounds = DeviceRect::new(
+        DevicePoint::new(bounds.origin.x - outset, bounds.origin.y - outset),
+        DeviceSize::new(
+            bounds.size.width + 2.0 * outset,
+            bounds.size.height + 2.0 * outset,
+        ),
+    );
+    Some(Entry {
+        hash: hasher.finish(),
+        bounds: clip(bounds, path.clip_bounds),
+    })
+}
+
+fn image_entry(image: &ImageQuad) -> Entry {
+    let mut hasher = DefaultHasher::new();
+    2u8.hash(&mut hasher);
+    hash_rect(&mut hasher, image.bounds);
+    image.image.id().hash(&mut hasher);
+    hash_f32s(&mut hasher, &image.uv);
+    let radii = &image.corner_radii;
+    hash_f32s(
+        &mut hasher,
+        &[
+            radii.top_left,
+            radii.top_right,
+            radii.bottom_right,
+            radii.bottom_left,
+        ],
+    );
+    image.opacity.to_bits().hash(&mut hasher);
+    hash_clip(&mut hasher, image.clip_bounds);
+    Entry {
+        hash: hasher.finish(),
+        bounds: clip(image.bounds, image.clip_bounds),
+    }
+}
+
+fn text_entry(run: &TextRun, glyphs: &[GlyphInstance]) -> Option<Entry> {
+    let mut hasher = DefaultHasher::new();
+    3u8.hash(&mut hasher);
+    hash_f32s(&mut hasher, &[run.origin.x, run.origin.y, run.font_size]);
+    color_bits(run.color).hash(&mut hasher);
+    run.font.data.id().hash(&mut hasher);
+    run.font.index.hash(&mut hasher);
+    run.normalized_coords.hash(&mut hasher);
+
+    let mut min_x = f32::MAX;
+    let mut max_x = f32::MIN;
+    for glyph in glyphs {
+        glyph.glyph_id.hash(&mut hasher);
+        hash_f32s(&mut hasher, &[glyph.x, glyph.y]);
+        min_x = min_x.min(glyph.x);
+        // Advance may be unknown; a font size is a generous upper bound.
+        let advance = if glyph.advance > 0.0 {
+            glyph.advance
+        } else {
+            run.font_size
+        };
+        max_x = max_x.max(glyph.x + advance);
+    }
+    if glyphs.is_empty() {
+        return None;
+    }
+
+    // Fall back to the font size when line metrics are missing, and pad
+    // for glyphs that overhang their advance.
+    let ascent = if run.ascent > 0.0 {
+        run.ascent
+    } else {
+        run.font_size
+    };
+    let descent = if run.descent > 0.0 {
+        run.descent
+    } else {
+        run.font_size * 0.5
+    };
+    let pad = run.font_size * 0.25;
+    let bounds = DeviceRect::new(
+        DevicePoint::new(run.origin.x + min_x - pad, run.origin.y - ascent - pad),
+        DeviceSize::new(max_x - min_x + 2.0 * pad, ascent + descent + 2.0 * pad),
+    );
+    Some(Entry {
+        hash: hasher.finish(),
+        bounds,
+    })
+}
+
+fn color_bits(color: Srgba) -> [u32; 4] {
+    [
+        color.red.to_bits(),
+        color.green.to_bits(),
+        color.blue.to_bits(),
+        color.alpha.to_bits(),
+    ]
+}
+
+fn hash_f32s(hasher: &mut DefaultHasher, values: &[f32]) {
+    for value in values {
+        value.to_bits().hash(hasher);
+    }
+}
+
+fn hash_rect(hasher: &mut DefaultHasher, rect: DeviceRect) {
+    hash_f32s(
+        hasher,
+        &[
+            rect.origin.x,
+            rect.origin.y,
+            rect.size.width,
+            rect.size.height,
+        ],
+    );
+}
+
+fn hash_clip(hasher: &mut DefaultHasher, clip: Option<DeviceRect>) {
+    clip.is_some().hash(hasher);
+    if let Some(rect) = clip {
+        hash_rect(hasher, rect);
+    }
+}
+
+fn union(a: DeviceRect, b: DeviceRect) -> DeviceRect {
+    let min_x = a.origin.x.min(b.origin.x);
+    let min_y = a.origin.y.min(b.origin.y);
+    let max_x = (a.origin.x + a.size.width).max(b.origin.x + b.size.width);
+    let max_y = (a.origin.y + a.size.height).max(b.origin.y + b.size.height);
+    DeviceRect::new(
+        DevicePoint::new(min_x, min_y),
+        DeviceSize::new(max_x - min_x, max_y - min_y),
+    )
+}
+
+/// Restrict `bounds` to `clip`, if any. Disjoint rects give an empty rect.
+fn clip(bounds: DeviceRect, clip: Option<DeviceRect>) -> DeviceRect {
+    let Some(clip) = clip else {
+        return bounds;
+    };
+    let min_x = bounds.origin.x.max(clip.origin.x);
+    let min_y = bounds.origin.y.max(clip.origin.y);
+    let max_x = (bounds.origin.x + bounds.size.width).min(clip.origin.x + clip.size.width);
+    let max_y = (bounds.origin.y + bounds.size.height).min(clip.origin.y + clip.size.height);
+    DeviceRect::new(
+        DevicePoint::new(min_x, min_y),
+        DeviceSize::new((max_x - min_x).max(0.0), (max_y - min_y).max(0.0)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: f32, y: f32, w: f32, h: f32) -> DeviceRect {
+        DeviceRect::new(DevicePoint::new(x, y), DeviceSize::new(w, h))
+    }
+
+    fn scene_with(quads: &[(DeviceRect, Srgba)]) -> Scene {
+        let mut scene = Scene::new();
+        for (bounds, color) in quads {
+            scene.push_quad(Quad::new(*bounds, *color));
+        }
+        scene
+    }
+
+    const RED: Srgba = Srgba::new(1.0, 0.0, 0.0, 1.0);
+    const BLUE: Srgba = Srgba::new(0.0, 0.0, 1.0, 1.0);
+
+    #[test]
+    fn first_frame_is_full() {
+        let mut tracker = DamageTracker::new();
+        assert_eq!(tracker.compute(&Scene::new()), Damage::Full);
+    }
+
+    #[test]
+    fn unchanged_scene_has_no_damage() {
+        let mut tracker = DamageTracker::new();
+        let scene = scene_with(&[(rect(0.0, 0.0, 10.0, 10.0), RED)]);
+        tracker.compute(&scene);
+        assert_eq!(tracker.compute(&scene), Damage::None);
+    }
+
+    #[test]
+    fn changed_quad_damages_its_bounds() {
+        let mut tracker = DamageTracker::new();
+        tracker.compute(&scene_with(&[
+            (rect(0.0, 0.0, 10.0, 10.0), RED),
+            (rect(50.0, 50.0, 10.0, 10.0), RED),
+        ]));
+        let damage = tracker.compute(&scene_with(&[
+            (rect(0.0, 0.0, 10.0, 10.0), RED),
+            (rect(50.0, 50.0, 10.0, 10.0), BLUE),
+        ]));
+        assert_eq!(damage, Damage::Partial(rect(50.0, 50.0, 10.0, 10.0)));
+    }
+
+    #[test]
+    fn moved_quad_damages_old_and_new_position() {
+        let mut tracker = DamageTracker::new();
+        tracker.compute(&scene_with(&[(rect(0.0, 0.0, 10.0, 10.0), RED)]));
+        let damage = tracker.compute(&scene_with(&[(rect(20.0, 0.0, 10.0, 10.0), RED)]));
+        assert_eq!(damage, Damage::Partial(rect(0.0, 0.0, 30.0, 10.0)));
+    }
+
+    #[test]
+    fn removed_quad_damages_where_it_was() {
+        let mut tracker = DamageTracker::new();
+        tracker.compute(&scene_with(&[
+            (rect(0.0, 0.0, 10.0, 10.0), RED),
+            (rect(40.0, 40.0, 5.0, 5.0), RED),
+        ]));
+        let damage = tracker.compute(&scene_with(&[(rect(0.0, 0.0, 10.0, 10.0), RED)]));
+        assert_eq!(damage, Damage::Partial(rect(40.0, 40.0, 5.0, 5.0)));
+    }
+
+    #[test]
+    fn clipped_quad_only_damages_visible_part() {
+        let mut tracker = DamageTracker::new();
+        let mut quad = Quad::new(rect(0.0, 0.0, 100.0, 100.0), RED);
+        quad.clip_bounds = Some(rect(0.0, 0.0, 20.0, 20.0));
+        let mut scene = Scene::new();
+        scene.push_quad(quad.clone());
+        tracker.compute(&scene);
+
+        quad.background = BLUE;
+        let mut scene = Scene::new();
+        scene.push_quad(quad);
+        assert_eq!(
+            tracker.compute(&scene),
+            Damage::Partial(rect(0.0, 0.0, 20.0, 20.0))
+        );
+    }
+
+    #[test]
+    fn background_change_is_full() {
+        let mut tracker = DamageTracker::new();
+        let mut scene = Scene::new();
+        tracker.compute(&scene);
+        scene.set_background(Srgba::new(1.0, 1.0, 1.0, 1.0));
+        assert_eq!(tracker.compute(&scene), Damage::Full);
+    }
+
+    #[test]
+    fn explicit_invalidation_is_included_once() {
+        let mut tracker = DamageTracker::new();
+        let scene = Scene::new();
+        tracker.compute(&scene);
+
+        tracker.invalidate(rect(5.0, 5.0, 10.0, 10.0));
+        assert_eq!(
+            tracker.compute(&scene),
+            Damage::Partial(rect(5.0, 5.0, 10.0, 10.0))
+        );
+        assert_eq!(tracker.compute(&scene), Damage::None);
+
+        tracker.invalidate_all();
+        assert_eq!(tracker.compute(&scene), Damage::Full);
+    }
+}
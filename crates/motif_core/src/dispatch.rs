@@ -0,0 +1,429 @@
+//! Routing window input to element handlers.
+//!
+//! An [`EventDispatcher`] sits between winit and a frame's
+//! [`HitTree`] and [`CallbackRegistry`]:
+//!
+//! ```ignore
+//! WindowEvent::CursorMoved { .. } | WindowEvent::MouseInput { .. } | .. => {
+//!     let scale = window.scale_factor() as f32;
+//!     if dispatcher.handle_window_event(&event, scale, &hit_tree, &mut callbacks) {
+//!         window.request_redraw();
+//!     }
+//! }
+//! ```
+//!
+//! Winit's physical-pixel events become motif [`InputEvent`]s in logical
+//! pixels. Mouse events go to the topmost element under the cursor, key
+//! events to the focused element, and both run through the registry's
+//! capture and bubble phases. Hover changes and clicks (a press and release
+//! on the same element) are derived along the way, and a press moves focus
+//! to the element under it that takes key events or editing actions (see
+//! [`CallbackRegistry::focus_target`]), or clears it.
+//!
+//! Actions from assistive technology (AccessKit [`ActionRequest`]s) go
+//! through [`EventDispatcher::handle_access_action`] to the same handlers:
+//! a screen reader's click runs the element's `on_click`.
+//!
+//! After the frame is painted, [`EventDispatcher::update_cursor`] picks the
+//! cursor for whatever is under the pointer; apply it with
+//! `window.set_cursor(icon)` when it changes.
+
+use accesskit::{Action, ActionData, ActionRequest};
+use winit::event::WindowEvent;
+
+use crate::input::{
+    CursorIcon, ElementState, InputAction, InputEvent, InputState, MouseButton, MouseEvent,
+    MouseEventKind, ScrollDelta,
+};
+use crate::{AccessId, AccessValue, CallbackRegistry, ElementId, HitTree};
+
+/// Converts window events and dispatches them to element handlers.
+#[derive(Debug, Default)]
+pub struct EventDispatcher {
+    input: InputState,
+    focused: Option<ElementId>,
+    cursor: CursorIcon,
+}
+
+impl EventDispatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cursor position, pressed buttons, modifiers and hover state.
+    pub fn input(&self) -> &InputState {
+        &self.input
+    }
+
+    /// Mutable input state, for queueing events that didn't come from winit.
+    pub fn input_mut(&mut self) -> &mut InputState {
+        &mut self.input
+    }
+
+    /// The element key events are dispatched to.
+    pub fn focused(&self) -> Option<ElementId> {
+        self.focused
+    }
+
+    pub fn set_focused(&mut self, id: Option<ElementId>) {
+        self.focused = id;
+    }
+
+    /// The cursor last chosen by [`update_cursor`](Self::update_cursor).
+    pub fn cursor(&self) -> CursorIcon {
+        self.cursor
+    }
+
+    /// Resolve the cursor for the element under the pointer in `hit_tree`.
+    /// Returns the new cursor if it changed since the last call. Call once
+    /// a frame, after paint, and after the pointer moves.
+    pub fn update_cursor(&mut self, hit_tree: &HitTree) -> Option<CursorIcon> {
+        let cursor = self
+            .input
+            .cursor_position
+            .map(|point| hit_tree.cursor_at(point))
+            .unwrap_or_default();
+        (cursor != self.cursor).then(|| {
+            self.cursor = cursor;
+            cursor
+        })
+    }
+
+    /// Convert `event` and dispatch it. `scale` is the window's scale
+    /// factor. Returns true if any handler ran; events motif doesn't handle
+    /// are ignored.
+    pub fn handle_window_event(
+        &mut self,
+        event: &WindowEvent,
+        scale: f32,
+        hit_tree: &HitTree,
+        callbacks: &mut CallbackRegistry,
+    ) -> bool {
+        match event {
+            WindowEvent::CursorMoved { position, .. } => {
+                self.input
+                    .handle_cursor_moved(position.x, position.y, scale);
+            }
+            WindowEvent::CursorEntered { .. } => self.input.handle_cursor_entered(),
+            WindowEvent::CursorLeft { .. } => self.input.handle_cursor_left(),
+            WindowEvent::MouseInput { state, button, .. } => self.input.handle_mouse_button(
+                MouseButton::from_winit(*button),
+                *state == ElementState::Pressed,
+            ),
+            WindowEvent::MouseWheel { delta, .. } => self
+                .input
+                .handle_scroll(ScrollDelta::from_winit(*delta, scale)),
+            WindowEvent::ModifiersChanged(modifiers) => {
+                self.input.handle_modifiers_changed(modifiers.state())
+            }
+            WindowEvent::KeyboardInput { event, .. } => {
+                self.input
+                    .handle_key(event.logical_key.clone(), event.physical_key, event.state)
+            }
+            _ => return false,
+        }
+        self.dispatch_pending(hit_tree, callbacks)
+    }
+
+    /// Route an action from assistive technology to the element its target
+    /// node was created for. Click runs click handlers, Focus and Blur move
+    /// keyboard focus, and SetValue runs
+    /// [`on_set_value`](CallbackRegistry::on_set_value) handlers. Returns
+    /// true if the action was handled.
+    pub fn handle_access_action(
+        &mut self,
+        request: &ActionRequest,
+        callbacks: &mut CallbackRegistry,
+    ) -> bool {
+        let Some(id) = AccessId(request.target_node.0).element_id() else {
+            return false;
+        };
+        match request.action {
+            Action::Click => callbacks.dispatch_click(id),
+            Action::Focus => {
+                self.focused = Some(id);
+                true
+            }
+            Action::Blur if self.focused == Some(id) => {
+                self.focused = None;
+                true
+            }
+            Action::SetValue => {
+                let value = match &request.data {
+                    Some(ActionData::Value(text)) => AccessValue::Text(text.to_string()),
+                    Some(ActionData::NumericValue(value)) => AccessValue::Numeric(*value),
+                    _ => return false,
+                };
+                callbacks.dispatch_set_value(id, &value)
+            }
+            _ => false,
+        }
+    }
+
+    /// Send an editing action that didn't come from a key press (e.g. an
+    /// Edit menu item) to the focused element. Returns true if a handler
+    /// ran.
+    pub fn handle_input_action(
+        &mut self,
+        action: InputAction,
+        callbacks: &mut CallbackRegistry,
+    ) -> bool {
+        match self.focused {
+            Some(id) => callbacks.dispatch_input_action(id, action),
+            None => false,
+        }
+    }
+
+    /// Dispatch every queued input event. Returns true if any handler ran.
+    pub fn dispatch_pending(
+        &mut self,
+        hit_tree: &HitTree,
+        callbacks: &mut CallbackRegistry,
+    ) -> bool {
+        let mut handled = false;
+        for event in self.input.take_events() {
+            handled |= match event {
+                InputEvent::Mouse(event) => self.dispatch_mouse(&event, hit_tree, callbacks),
+                InputEvent::Key(event) => match self.focused {
+                    Some(id) => callbacks.dispatch_key(id, &event),
+                    None => false,
+                },
+                InputEvent::ModifiersChanged(_) => false,
+            };
+        }
+        handled
+    }
+
+    fn dispatch_mouse(
+        &mut self,
+        event: &MouseEvent,
+        hit_tree: &HitTree,
+        callbacks: &mut CallbackRegistry,
+    ) -> bool {
+        let target = event.position.and_then(|point| hit_tree.hit_test(point));
+        match event.kind {
+            MouseEventKind::Move | MouseEventKind::Leave => {
+                let previous = self.input.hovered();
+                if previous != target {
+                    self.input.set_hovered(target);
+                    callbacks.dispatch_hover(previous, target);
+                }
+            }
+            MouseEventKind::Down => {
+                self.input.begin_press();
+                self.focused = target.and_then(|id| callbacks.focus_target(id));
+            }
+            _ => {}
+        }
+
+        let Some(target) = target else {
+            if event.kind == MouseEventKind::Up {
+                self.input.end_press();
+            }
+            return false;
+        };
+        let mut handled = callbacks.dispatch_mouse(target, event);
+        if event.kind == MouseEventKind::Up {
+            if let Some(clicked) = self.input.end_press() {
+                handled |= callbacks.dispatch_click_event(clicked, event);
+            }
+        }
+        handled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::{Key, KeyCode, ModifiersState, PhysicalKey};
+    use crate::{Point, Rect, Size};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// A 100x100 parent (1) containing a 20x20 child (2) at (10, 10).
+    fn frame() -> (HitTree, CallbackRegistry) {
+        let mut hit_tree = HitTree::new();
+        hit_tree.push(
+            ElementId(1),
+            Rect::new(Point::new(0.0, 0.0), Size::new(100.0, 100.0)),
+        );
+        hit_tree.push(
+            ElementId(2),
+            Rect::new(Point::new(10.0, 10.0), Size::new(20.0, 20.0)),
+        );
+        let mut callbacks = CallbackRegistry::new();
+        callbacks.push_parent(ElementId(1));
+        callbacks.register_element(ElementId(2));
+        callbacks.pop_parent();
+        (hit_tree, callbacks)
+    }
+
+    #[test]
+    fn mouse_events_reach_the_element_under_the_cursor() {
+        let (hit_tree, mut callbacks) = frame();
+        let log = Rc::new(RefCell::new(Vec::new()));
+        for id in [1, 2] {
+            let down = log.clone();
+            callbacks.on_mouse_down(ElementId(id), move |_, cx| {
+                down.borrow_mut().push(("down", cx.current().0))
+            });
+            let click = log.clone();
+            callbacks.on_click_event(ElementId(id), move |_, cx| {
+                click.borrow_mut().push(("click", cx.current().0))
+            });
+        }
+
+        let mut dispatcher = EventDispatcher::new();
+        // Physical (30, 30) at 2x is logical (15, 15), inside the child.
+        dispatcher.input_mut().handle_cursor_moved(30.0, 30.0, 2.0);
+        dispatcher
+            .input_mut()
+            .handle_mouse_button(MouseButton::Left, true);
+        dispatcher
+            .input_mut()
+            .handle_mouse_button(MouseButton::Left, false);
+        assert!(dispatcher.dispatch_pending(&hit_tree, &mut callbacks));
+
+        assert_eq!(dispatcher.input().hovered(), Some(ElementId(2)));
+        assert!(callbacks.is_hovered(ElementId(1)));
+        assert_eq!(
+            *log.borrow(),
+            vec![("down", 2), ("down", 1), ("click", 2), ("click", 1)]
+        );
+    }
+
+    #[test]
+    fn access_actions_reach_element_handlers() {
+        let (_, mut callbacks) = frame();
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let clicks = log.clone();
+        callbacks.on_click(ElementId(1), move || {
+            clicks.borrow_mut().push("click".into())
+        });
+        let values = log.clone();
+        callbacks.on_set_value(ElementId(2), move |value| {
+            values.borrow_mut().push(format!("{value:?}"))
+        });
+
+        let request = |action, target: u64, data| ActionRequest {
+            action,
+            target_tree: accesskit::TreeId::ROOT,
+            target_node: accesskit::NodeId(target),
+            data,
+        };
+        let mut dispatcher = EventDispatcher::new();
+
+        // Clicks bubble like mouse clicks.
+        assert!(dispatcher.handle_access_action(&request(Action::Click, 2, None), &mut callbacks));
+        assert!(dispatcher.handle_access_action(
+            &request(Action::SetValue, 2, Some(ActionData::Value("hi".into()))),
+            &mut callbacks,
+        ));
+        assert!(dispatcher.handle_access_action(&request(Action::Focus, 2, None), &mut callbacks));
+        assert_eq!(dispatcher.focused(), Some(ElementId(2)));
+
+        // Generated ids have no element to route to.
+        let generated = AccessId::GENERATED | 1;
+        assert!(!dispatcher
+            .handle_access_action(&request(Action::Click, generated, None), &mut callbacks,));
+        assert_eq!(*log.borrow(), vec!["click", "Text(\"hi\")"]);
+    }
+
+    #[test]
+    fn cursor_updates_only_when_it_changes() {
+        let (mut hit_tree, _) = frame();
+        hit_tree.set_cursor(ElementId(2), CursorIcon::Pointer);
+
+        let mut dispatcher = EventDispatcher::new();
+        dispatcher.input_mut().handle_cursor_moved(15.0, 15.0, 1.0);
+        assert_eq!(
+            dispatcher.update_cursor(&hit_tree),
+            Some(CursorIcon::Pointer)
+        );
+        assert_eq!(dispatcher.update_cursor(&hit_tree), None);
+
+        dispatcher.input_mut().handle_cursor_moved(50.0, 50.0, 1.0);
+        assert_eq!(
+            dispatcher.update_cursor(&hit_tree),
+            Some(CursorIcon::Default)
+        );
+        assert_eq!(dispatcher.cursor(), CursorIcon::Default);
+    }
+
+    #[test]
+    fn key_events_go_to_the_focused_element() {
+        let (hit_tree, mut callbacks) = frame();
+        let keys = Rc::new(RefCell::new(Vec::new()));
+        let captured = keys.clone();
+        callbacks.on_key_capture(ElementId(1), move |event, cx| {
+            if event.key == Key::Character("q".into()) {
+                cx.stop_propagation();
+            }
+        });
+        callbacks.on_key_down(ElementId(2), move |event, _| {
+            captured.borrow_mut().push(event.key.clone());
+        });
+
+        let mut dispatcher = EventDispatcher::new();
+        let press = |dispatcher: &mut EventDispatcher, ch: &str| {
+            dispatcher.input_mut().handle_key(
+                Key::Character(ch.into()),
+                PhysicalKey::Code(KeyCode::KeyA),
+                ElementState::Pressed,
+            );
+        };
+
+        press(&mut dispatcher, "a");
+        assert!(!dispatcher.dispatch_pending(&hit_tree, &mut callbacks));
+
+        dispatcher.set_focused(Some(ElementId(2)));
+        press(&mut dispatcher, "a");
+        press(&mut dispatcher, "q");
+        dispatcher
+            .input_mut()
+            .handle_modifiers_changed(ModifiersState::SHIFT);
+        assert!(dispatcher.dispatch_pending(&hit_tree, &mut callbacks));
+        assert_eq!(*keys.borrow(), vec![Key::Character("a".into())]);
+    }
+
+    #[test]
+    fn input_actions_bubble_from_the_focused_element() {
+        let (_, mut callbacks) = frame();
+        let actions = Rc::new(RefCell::new(Vec::new()));
+        let received = actions.clone();
+        callbacks.on_input_action(ElementId(1), move |action, cx| {
+            received.borrow_mut().push((*action, cx.target()));
+        });
+
+        let mut dispatcher = EventDispatcher::new();
+        assert!(!dispatcher.handle_input_action(InputAction::Copy, &mut callbacks));
+
+        dispatcher.set_focused(Some(ElementId(2)));
+        assert!(dispatcher.handle_input_action(InputAction::Copy, &mut callbacks));
+        assert_eq!(*actions.borrow(), vec![(InputAction::Copy, ElementId(2))]);
+    }
+
+    #[test]
+    fn presses_focus_the_nearest_element_taking_input() {
+        let (hit_tree, mut callbacks) = frame();
+        callbacks.on_input_action(ElementId(1), |_, _| {});
+        callbacks.on_key_capture(ElementId(2), |_, _| {});
+
+        let mut dispatcher = EventDispatcher::new();
+        let press = |dispatcher: &mut EventDispatcher, x: f64, callbacks: &mut _| {
+            dispatcher.input_mut().handle_cursor_moved(x, 15.0, 1.0);
+            let input = dispatcher.input_mut();
+            input.handle_mouse_button(MouseButton::Left, true);
+            input.handle_mouse_button(MouseButton::Left, false);
+            dispatcher.dispatch_pending(&hit_tree, callbacks);
+        };
+
+        // The child only captures keys, so its parent takes focus.
+        press(&mut dispatcher, 15.0, &mut callbacks);
+        assert_eq!(dispatcher.focused(), Some(ElementId(1)));
+
+        // Pressing outside every element clears it.
+        press(&mut dispatcher, 150.0, &mut callbacks);
+        assert_eq!(dispatcher.focused(), None);
+    }
+}
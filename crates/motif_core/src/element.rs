@@ -9,8 +9,18 @@
 //! 2. Layout engine computes bounds
 //! 3. `paint()` - draw at computed bounds
 
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use crate::input::{CursorIcon, InputAction};
+use crate::profiler::{self, FramePhase};
+use crate::style::Style;
+use crate::view::{DirtyFlag, View};
 use crate::{
-    ElementId, HitTree, LayoutEngine, NodeId, Point, Rect, ScaleFactor, Scene, TextContext,
+    child_identities, AccessId, AccessNode, AccessRole, AccessTree, CallbackRegistry,
+    ChildIdentity, Clock, Constraints, ElementId, ElementKey, EventContext, GlobalElementId,
+    HitEntry, HitTree, LayoutEngine, NodeId, Point, Reconciler, Rect, ScaleFactor, Scene, Size,
+    TaskId, Tasks, TextContext, Theme, TimerId, Timers,
 };
 
 /// Views are stateful components that persist across frames.
@@ -37,7 +47,7 @@ pub trait Render: 'static + Sized {
 /// Takes `self` by value (consumes) because elements are ephemeral.
 ///
 /// ```ignore
-/// struct Button { label: SharedString }
+/// struct Button { label: ArcStr }
 ///
 /// impl RenderOnce for Button {
 ///     fn render(self, cx: &mut WindowContext) -> impl IntoElement {
@@ -56,6 +66,15 @@ pub trait RenderOnce: 'static + Sized {
 pub trait IntoElement: Sized {
     type Element: Element;
     fn into_element(self) -> Self::Element;
+
+    /// Identify this element among its siblings by `key`, so it keeps its
+    /// [`GlobalElementId`] when siblings are inserted, removed or reordered.
+    fn key(self, key: impl Into<ElementKey>) -> Keyed<Self::Element> {
+        Keyed {
+            element: self.into_element(),
+            key: key.into(),
+        }
+    }
 }
 
 /// Low-level element trait with layout and paint phases.
@@ -76,6 +95,57 @@ pub trait Element: 'static {
     /// Called after layout has been computed. The bounds are the
     /// computed position and size from the layout engine.
     fn paint(&mut self, bounds: Rect, cx: &mut PaintContext);
+
+    /// The size this element wants within `constraints`, without placing it.
+    ///
+    /// Lets containers and virtualized lists size themselves to content
+    /// before layout. The default lays the element out in a scratch tree;
+    /// leaves that know their size (like text) override it to skip that.
+    /// Call it before `request_layout` for the frame, since the scratch
+    /// layout replaces the node ids recorded on children.
+    fn measure(&mut self, constraints: Constraints, cx: &mut LayoutContext) -> Size {
+        let mut engine = LayoutEngine::new();
+        let mut measure_cx = LayoutContext::new(&mut engine, cx.text_ctx, cx.scale_factor);
+        measure_cx.text_style = cx.text_style.clone();
+        let root = self.request_layout(&mut measure_cx);
+        engine.compute_layout_within(root, constraints, cx.text_ctx)
+    }
+
+    /// The key set with [`IntoElement::key`], if any.
+    fn element_key(&self) -> Option<&ElementKey> {
+        None
+    }
+}
+
+/// An element with an explicit key. Created by [`IntoElement::key`].
+pub struct Keyed<E> {
+    element: E,
+    key: ElementKey,
+}
+
+impl<E: Element> Element for Keyed<E> {
+    fn request_layout(&mut self, cx: &mut LayoutContext) -> NodeId {
+        self.element.request_layout(cx)
+    }
+
+    fn paint(&mut self, bounds: Rect, cx: &mut PaintContext) {
+        self.element.paint(bounds, cx)
+    }
+
+    fn measure(&mut self, constraints: Constraints, cx: &mut LayoutContext) -> Size {
+        self.element.measure(constraints, cx)
+    }
+
+    fn element_key(&self) -> Option<&ElementKey> {
+        Some(&self.key)
+    }
+}
+
+impl<E: Element> IntoElement for Keyed<E> {
+    type Element = Self;
+    fn into_element(self) -> Self::Element {
+        self
+    }
 }
 
 /// Trait for elements that can accept children.
@@ -95,6 +165,36 @@ pub trait ParentElement: Sized {
         }
         self
     }
+
+    /// Add the child built by `build`, only if `condition` holds.
+    ///
+    /// `build` isn't called otherwise, so the child can be expensive or
+    /// depend on data that only exists when `condition` is true.
+    fn when<E: IntoElement>(self, condition: bool, build: impl FnOnce() -> E) -> Self {
+        if condition {
+            self.child(build())
+        } else {
+            self
+        }
+    }
+
+    /// Add `child` only if `condition` holds.
+    fn child_if(self, condition: bool, child: impl IntoElement) -> Self {
+        if condition {
+            self.child(child)
+        } else {
+            self
+        }
+    }
+
+    /// Add a child for each item of `items`, built by `render`.
+    fn children_iter<T, E: IntoElement>(
+        self,
+        items: impl IntoIterator<Item = T>,
+        render: impl FnMut(T) -> E,
+    ) -> Self {
+        self.children(items.into_iter().map(render))
+    }
 }
 
 /// Type-erased element wrapper.
@@ -103,6 +203,7 @@ pub trait ParentElement: Sized {
 pub struct AnyElement {
     element: Box<dyn Element>,
     node_id: Option<NodeId>,
+    global_id: Option<GlobalElementId>,
 }
 
 impl AnyElement {
@@ -110,22 +211,65 @@ impl AnyElement {
         Self {
             element: Box::new(element),
             node_id: None,
+            global_id: None,
         }
     }
 
     /// Request layout for this element.
     pub fn request_layout(&mut self, cx: &mut LayoutContext) -> NodeId {
+        self.global_id = Some(cx.global_id());
         let node_id = self.element.request_layout(cx);
         self.node_id = Some(node_id);
         node_id
     }
 
+    /// The element's identity from the last `request_layout`, if any.
+    pub fn global_id(&self) -> Option<GlobalElementId> {
+        self.global_id
+    }
+
+    /// The layout node from the last `request_layout`, if any.
+    pub fn node_id(&self) -> Option<NodeId> {
+        self.node_id
+    }
+
     /// Paint this element. Must call request_layout first.
     pub fn paint(&mut self, cx: &mut PaintContext) {
         let node_id = self.node_id.expect("must call request_layout before paint");
         let bounds = cx.layout_bounds(node_id);
         self.element.paint(bounds, cx);
     }
+
+    /// Paint this element at explicit bounds instead of its layout bounds.
+    /// Children still use the layout. Must call request_layout first.
+    pub fn paint_at(&mut self, bounds: Rect, cx: &mut PaintContext) {
+        self.element.paint(bounds, cx);
+    }
+}
+
+impl Element for AnyElement {
+    fn request_layout(&mut self, cx: &mut LayoutContext) -> NodeId {
+        AnyElement::request_layout(self, cx)
+    }
+
+    fn paint(&mut self, bounds: Rect, cx: &mut PaintContext) {
+        self.paint_at(bounds, cx);
+    }
+
+    fn measure(&mut self, constraints: Constraints, cx: &mut LayoutContext) -> Size {
+        self.element.measure(constraints, cx)
+    }
+
+    fn element_key(&self) -> Option<&ElementKey> {
+        self.element.element_key()
+    }
+}
+
+impl IntoElement for AnyElement {
+    type Element = AnyElement;
+    fn into_element(self) -> Self::Element {
+        self
+    }
 }
 
 /// Context for rendering views (stateful).
@@ -170,6 +314,11 @@ pub struct LayoutContext<'a> {
     pub(crate) layout_engine: &'a mut LayoutEngine,
     pub(crate) text_ctx: &'a mut TextContext,
     pub(crate) scale_factor: ScaleFactor,
+    /// Ids of the element being laid out and its ancestors.
+    pub(crate) id_stack: Vec<GlobalElementId>,
+    pub(crate) reconciler: Option<&'a mut Reconciler>,
+    /// Text properties inherited from ancestors.
+    pub(crate) text_style: Style,
 }
 
 impl<'a> LayoutContext<'a> {
@@ -182,7 +331,65 @@ impl<'a> LayoutContext<'a> {
             layout_engine,
             text_ctx,
             scale_factor,
+            id_stack: vec![GlobalElementId::ROOT],
+            reconciler: None,
+            text_style: Style::default(),
+        }
+    }
+
+    /// Text properties inherited from ancestors. Only `text_color` and
+    /// `font_size` are meaningful.
+    pub fn text_style(&self) -> &Style {
+        &self.text_style
+    }
+
+    /// Run `f` with the text properties `style` sets layered over the
+    /// inherited ones.
+    pub fn with_text_style<R>(&mut self, style: &Style, f: impl FnOnce(&mut Self) -> R) -> R {
+        let refined = self.text_style.refine(style);
+        let inherited = std::mem::replace(&mut self.text_style, refined);
+        let result = f(self);
+        self.text_style = inherited;
+        result
+    }
+
+    /// Record every element laid out in `reconciler`, for diffing frames.
+    pub fn with_reconciler(mut self, reconciler: &'a mut Reconciler) -> Self {
+        reconciler.record(GlobalElementId::ROOT);
+        self.reconciler = Some(reconciler);
+        self
+    }
+
+    /// Identity of the element currently being laid out.
+    pub fn global_id(&self) -> GlobalElementId {
+        *self
+            .id_stack
+            .last()
+            .expect("id stack always holds the root")
+    }
+
+    /// Run `f` as the child of the current element identified by `identity`.
+    pub fn with_child<R>(&mut self, identity: &ChildIdentity, f: impl FnOnce(&mut Self) -> R) -> R {
+        let id = self.global_id().child(identity);
+        if let Some(reconciler) = &mut self.reconciler {
+            reconciler.record(id);
         }
+        self.id_stack.push(id);
+        let result = f(self);
+        self.id_stack.pop();
+        result
+    }
+
+    /// Request layout for `children`, giving each its identity among its
+    /// siblings. Containers should use this rather than laying out children
+    /// directly.
+    pub fn request_children_layout(&mut self, children: &mut [AnyElement]) -> Vec<NodeId> {
+        let identities = child_identities(children.iter().map(Element::element_key));
+        children
+            .iter_mut()
+            .zip(&identities)
+            .map(|(child, identity)| self.with_child(identity, |cx| child.request_layout(cx)))
+            .collect()
     }
 
     pub fn layout_engine(&mut self) -> &mut LayoutEngine {
@@ -203,6 +410,12 @@ pub struct WindowContext<'a> {
     pub(crate) scene: &'a mut Scene,
     pub(crate) text_ctx: &'a mut TextContext,
     pub(crate) scale_factor: ScaleFactor,
+    pub(crate) theme: Theme,
+    pub(crate) dirty: DirtyFlag,
+    pub(crate) timers: Timers,
+    pub(crate) tasks: Tasks,
+    /// Where painted elements register interaction callbacks.
+    pub(crate) callbacks: Option<&'a mut CallbackRegistry>,
 }
 
 impl<'a> WindowContext<'a> {
@@ -215,9 +428,146 @@ impl<'a> WindowContext<'a> {
             scene,
             text_ctx,
             scale_factor,
+            theme: Theme::default(),
+            dirty: DirtyFlag::default(),
+            timers: Timers::default(),
+            tasks: Tasks::default(),
+            callbacks: None,
+        }
+    }
+
+    /// Share `dirty` with views created in this window, so the app can
+    /// tell when one of them changed.
+    pub fn with_dirty_flag(mut self, dirty: DirtyFlag) -> Self {
+        self.dirty = dirty;
+        self
+    }
+
+    /// Schedule timers and animation frames on the window's queue, which
+    /// the app runs.
+    pub fn with_timers(mut self, timers: Timers) -> Self {
+        self.timers = timers;
+        self
+    }
+
+    /// Spawn background work on the window's tasks, whose completions the
+    /// app runs.
+    pub fn with_tasks(mut self, tasks: Tasks) -> Self {
+        self.tasks = tasks;
+        self
+    }
+
+    /// Have elements painted by [`render_view`] register their handlers
+    /// with `callbacks`.
+    pub fn with_callbacks(mut self, callbacks: &'a mut CallbackRegistry) -> Self {
+        self.callbacks = Some(callbacks);
+        self
+    }
+
+    /// The window's "needs redraw" flag.
+    pub fn dirty_flag(&self) -> &DirtyFlag {
+        &self.dirty
+    }
+
+    /// Mark the window as needing a redraw.
+    pub fn notify(&self) {
+        self.dirty.mark();
+    }
+
+    /// The window's timers, for handlers that schedule work later.
+    pub fn timers(&self) -> &Timers {
+        &self.timers
+    }
+
+    /// Call `f` right before the next frame, and draw one as soon as this
+    /// one is done. Animations request a frame from each frame they want to
+    /// continue.
+    pub fn request_animation_frame(&self, f: impl FnOnce() + 'static) {
+        self.timers.request_animation_frame(f);
+    }
+
+    /// Call `f` once, after `delay`.
+    pub fn set_timeout(&self, delay: Duration, f: impl FnOnce() + 'static) -> TimerId {
+        self.timers.set_timeout(delay, f)
+    }
+
+    /// Call `f` every `period` until [cancelled](Timers::cancel).
+    pub fn set_interval(&self, period: Duration, f: impl FnMut() + 'static) -> TimerId {
+        self.timers.set_interval(period, f)
+    }
+
+    /// The window's background tasks, for handlers that spawn work later.
+    pub fn tasks(&self) -> &Tasks {
+        &self.tasks
+    }
+
+    /// Run `future` on a background thread, then call `on_done` with its
+    /// output on the UI thread, e.g. to update a view.
+    pub fn spawn<T: Send + 'static>(
+        &self,
+        future: impl Future<Output = T> + Send + 'static,
+        on_done: impl FnOnce(T) + 'static,
+    ) -> TaskId {
+        self.tasks.spawn(future, on_done)
+    }
+
+    /// Redraw once `delay` has passed, e.g. to blink a caret.
+    pub fn request_frame_after(&self, delay: Duration) {
+        self.dirty.request_frame_at(self.now() + delay);
+    }
+
+    /// The window's clock, which timers and animations should measure time
+    /// on so tests can control it.
+    pub fn clock(&self) -> Clock {
+        self.timers.clock()
+    }
+
+    /// The current time on the window's [clock](Self::clock).
+    pub fn now(&self) -> Instant {
+        self.timers.clock().now()
+    }
+
+    /// Create a view owned by the caller, built by `build`.
+    pub fn new_view<V: 'static>(
+        &mut self,
+        build: impl FnOnce(&mut ViewContext<V>) -> V,
+    ) -> View<V> {
+        let state = build(&mut ViewContext::new(self.reborrow()));
+        View::new(state, self.dirty.clone())
+    }
+
+    /// A context borrowing from this one.
+    pub(crate) fn reborrow(&mut self) -> WindowContext<'_> {
+        WindowContext {
+            scene: &mut *self.scene,
+            text_ctx: &mut *self.text_ctx,
+            scale_factor: self.scale_factor,
+            theme: self.theme.clone(),
+            dirty: self.dirty.clone(),
+            timers: self.timers.clone(),
+            tasks: self.tasks.clone(),
+            callbacks: self.callbacks.as_deref_mut(),
         }
     }
 
+    /// Render with `theme`, e.g. `registry.active().clone()`. Defaults to
+    /// [`Theme::dark`].
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// The theme views should draw with.
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// `value` [`rem`](crate::rem)s in logical pixels, at the theme's root
+    /// font size.
+    pub fn rem(&self, value: f32) -> f32 {
+        self.theme.rem(value)
+    }
+
     pub fn scene(&mut self) -> &mut Scene {
         self.scene
     }
@@ -241,6 +591,14 @@ pub struct PaintContext<'a> {
     /// Offset between layout position and actual paint position.
     /// Applied to all layout_bounds results.
     pub(crate) offset: Point,
+    /// Where elements register interaction callbacks, if the app dispatches them.
+    pub(crate) callbacks: Option<&'a mut CallbackRegistry>,
+    /// Text properties inherited from ancestors.
+    pub(crate) text_style: Style,
+    /// Size of the window being painted, for keeping overlays on screen.
+    pub(crate) viewport_size: Option<Size>,
+    /// Where elements add accessibility nodes, if the app exposes them.
+    pub(crate) access_tree: Option<&'a mut AccessTree>,
 }
 
 impl<'a> PaintContext<'a> {
@@ -258,7 +616,138 @@ impl<'a> PaintContext<'a> {
             layout_engine,
             scale_factor,
             offset: Point::new(0.0, 0.0),
+            callbacks: None,
+            text_style: Style::default(),
+            viewport_size: None,
+            access_tree: None,
+        }
+    }
+
+    /// Text properties inherited from ancestors. Only `text_color` and
+    /// `font_size` are meaningful.
+    pub fn text_style(&self) -> &Style {
+        &self.text_style
+    }
+
+    /// Run `f` with the text properties `style` sets layered over the
+    /// inherited ones.
+    pub fn with_text_style<R>(&mut self, style: &Style, f: impl FnOnce(&mut Self) -> R) -> R {
+        let refined = self.text_style.refine(style);
+        let inherited = std::mem::replace(&mut self.text_style, refined);
+        let result = f(self);
+        self.text_style = inherited;
+        result
+    }
+
+    /// Have elements add accessibility nodes to `tree` as they paint, under
+    /// its root node (which the app adds).
+    pub fn with_access_tree(mut self, tree: &'a mut AccessTree) -> Self {
+        self.access_tree = Some(tree);
+        self
+    }
+
+    /// The accessibility tree being built, if any.
+    pub fn access_tree(&mut self) -> Option<&mut AccessTree> {
+        self.access_tree.as_deref_mut()
+    }
+
+    /// Add an accessibility node for an element painted at `bounds`, as a
+    /// child of the innermost [`with_access_parent`](Self::with_access_parent).
+    /// Elements with an [`ElementId`] should pass it so the node keeps its
+    /// id across frames. Returns the node to fill in its state, or `None`
+    /// if accessibility is off.
+    pub fn push_access_node(
+        &mut self,
+        id: Option<ElementId>,
+        role: AccessRole,
+        name: impl Into<String>,
+        bounds: Rect,
+    ) -> Option<&mut AccessNode> {
+        // AccessKit (via winit) expects window-relative physical pixels.
+        let device = self.scale_factor.scale_rect(bounds);
+        let bounds = Rect::new(
+            Point::new(device.origin.x, device.origin.y),
+            Size::new(device.size.width, device.size.height),
+        );
+        let tree = self.access_tree.as_deref_mut()?;
+        let id = id.map(AccessId::from).unwrap_or_else(|| tree.generate_id());
+        Some(tree.push_child(AccessNode::new(id, role, name.into()).with_bounds(bounds)))
+    }
+
+    /// Run `f` with nodes it adds attached to `id`.
+    pub fn with_access_parent<R>(&mut self, id: AccessId, f: impl FnOnce(&mut Self) -> R) -> R {
+        if let Some(tree) = self.access_tree.as_deref_mut() {
+            tree.push_parent(id);
+        }
+        let result = f(self);
+        if let Some(tree) = self.access_tree.as_deref_mut() {
+            tree.pop_parent();
         }
+        result
+    }
+
+    /// Run `f` without adding accessibility nodes, for content hidden from
+    /// assistive technology.
+    pub fn without_accessibility<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        let tree = self.access_tree.take();
+        let result = f(self);
+        self.access_tree = tree;
+        result
+    }
+
+    /// Keep overlays within a window of `size` logical pixels. Without it
+    /// overlays are placed as if the window were unbounded.
+    pub fn with_viewport_size(mut self, size: Size) -> Self {
+        self.viewport_size = Some(size);
+        self
+    }
+
+    /// Whether `id` or one of its descendants is under the cursor, as last
+    /// reported to the callback registry. Always false without one.
+    pub fn is_hovered(&self, id: ElementId) -> bool {
+        self.callbacks
+            .as_deref()
+            .is_some_and(|callbacks| callbacks.is_hovered(id))
+    }
+
+    /// Paint `element` into the scene's overlay, above everything else,
+    /// next to `anchor`.
+    ///
+    /// The element is sized to its content and placed below the anchor,
+    /// flipping above it if it would leave the viewport. Returns where it
+    /// was painted.
+    pub fn paint_overlay(&mut self, anchor: Rect, element: impl IntoElement) -> Rect {
+        let mut element = AnyElement::new(element.into_element());
+        let mut engine = LayoutEngine::new();
+        let mut layout_cx = LayoutContext::new(&mut engine, self.text_ctx, self.scale_factor);
+        layout_cx.text_style = self.text_style.clone();
+        let root = element.request_layout(&mut layout_cx);
+        let size = engine.compute_layout_within(root, Constraints::unbounded(), self.text_ctx);
+
+        let origin = crate::anchored_origin(anchor, size, self.viewport_size);
+        let bounds = Rect::new(origin, size);
+        let mut overlay_cx = PaintContext {
+            scene: self.scene.overlay_mut(),
+            text_ctx: &mut *self.text_ctx,
+            hit_tree: &mut *self.hit_tree,
+            layout_engine: &engine,
+            scale_factor: self.scale_factor,
+            offset: origin,
+            callbacks: self.callbacks.as_deref_mut(),
+            text_style: self.text_style.clone(),
+            viewport_size: self.viewport_size,
+            access_tree: self.access_tree.as_deref_mut(),
+        };
+        element.paint_at(bounds, &mut overlay_cx);
+        bounds
+    }
+
+    /// Collect element callbacks (e.g. button clicks) into `callbacks`.
+    /// Without a registry, elements paint normally but their callbacks are
+    /// dropped.
+    pub fn with_callbacks(mut self, callbacks: &'a mut CallbackRegistry) -> Self {
+        self.callbacks = Some(callbacks);
+        self
     }
 
     pub fn scene(&mut self) -> &mut Scene {
@@ -299,6 +788,53 @@ impl<'a> PaintContext<'a> {
     /// Register an element for hit testing.
     pub fn register_hit(&mut self, id: ElementId, bounds: Rect) {
         self.hit_tree.push(id, bounds);
+        if let Some(callbacks) = &mut self.callbacks {
+            callbacks.register_element(id);
+        }
+    }
+
+    /// Show `cursor` while the pointer is over `id`. The element must also
+    /// be [registered](Self::register_hit).
+    pub fn set_cursor(&mut self, id: ElementId, cursor: CursorIcon) {
+        self.hit_tree.set_cursor(id, cursor);
+    }
+
+    /// Elements painted so far that contain `point`, topmost first.
+    pub fn hit_test(&self, point: Point) -> Vec<HitEntry> {
+        self.hit_tree.query(point)
+    }
+
+    /// The callback registry, if the app dispatches callbacks.
+    pub fn callbacks(&mut self) -> Option<&mut CallbackRegistry> {
+        self.callbacks.as_deref_mut()
+    }
+
+    /// Register a click handler for an element. Returns false if this
+    /// context has no callback registry.
+    pub fn on_click(&mut self, id: ElementId, callback: impl FnMut() + 'static) -> bool {
+        match &mut self.callbacks {
+            Some(callbacks) => {
+                callbacks.on_click(id, callback);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Register an editing-action handler for an element, if the context
+    /// has a callback registry. Returns whether it was registered.
+    pub fn on_input_action(
+        &mut self,
+        id: ElementId,
+        callback: impl FnMut(&InputAction, &mut EventContext) + 'static,
+    ) -> bool {
+        match &mut self.callbacks {
+            Some(callbacks) => {
+                callbacks.on_input_action(id, callback);
+                true
+            }
+            None => false,
+        }
     }
 
     /// Paint a child element.
@@ -319,27 +855,25 @@ pub fn render_view<V: Render>(
     cx: &mut WindowContext,
     layout_engine: &mut LayoutEngine,
     hit_tree: &mut HitTree,
-    window_size: crate::Size,
+    window_size: Size,
 ) {
+    #[cfg(feature = "tracing")]
+    let _span = tracing::info_span!("render_view").entered();
+
     // Clear layout for fresh computation
     layout_engine.clear();
 
     // Render phase: build element tree
-    let mut view_cx = ViewContext::<V>::new(WindowContext {
-        scene: cx.scene,
-        text_ctx: cx.text_ctx,
-        scale_factor: cx.scale_factor,
-    });
+    let render_scope = profiler::scope(FramePhase::Render);
+    let mut view_cx = ViewContext::<V>::new(cx.reborrow());
     let element = view.render(&mut view_cx);
     let mut element = element.into_element();
+    drop(render_scope);
 
     // Layout phase: request layout for all elements
     {
-        let mut layout_cx = LayoutContext {
-            layout_engine: &mut *layout_engine,
-            text_ctx: cx.text_ctx,
-            scale_factor: cx.scale_factor,
-        };
+        let layout_scope = profiler::scope(FramePhase::Layout);
+        let mut layout_cx = LayoutContext::new(&mut *layout_engine, cx.text_ctx, cx.scale_factor);
         let root_node = element.request_layout(&mut layout_cx);
 
         // Compute layout
@@ -349,8 +883,10 @@ pub fn render_view<V: Render>(
             window_size.height * cx.scale_factor.0,
             cx.text_ctx,
         );
+        drop(layout_scope);
 
         // Paint phase: paint at computed bounds
+        let _paint_scope = profiler::scope(FramePhase::Paint);
         let root_bounds = layout_engine.layout_bounds(root_node);
         let mut paint_cx = PaintContext {
             scene: cx.scene,
@@ -359,6 +895,10 @@ pub fn render_view<V: Render>(
             layout_engine,
             scale_factor: cx.scale_factor,
             offset: Point::new(0.0, 0.0),
+            callbacks: cx.callbacks.as_deref_mut(),
+            text_style: Style::default(),
+            viewport_size: Some(window_size),
+            access_tree: None,
         };
         element.paint(root_bounds, &mut paint_cx);
     }
@@ -386,7 +926,7 @@ impl IntoElement for Empty {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{ElementId, HitTree, LayoutEngine, Point, Rect, Size};
+    use crate::{ElementId, FrameDiff, HitTree, LayoutEngine, Point, Rect, Size};
 
     #[test]
     fn empty_element_paints_nothing() {
@@ -513,4 +1053,58 @@ mod tests {
             Some(ElementId(1))
         );
     }
+
+    /// Lay out a div with children keyed by `keys`, returning the children's
+    /// global ids and the reconciler's diff for the frame.
+    fn keyed_frame(
+        reconciler: &mut Reconciler,
+        keys: &[&str],
+    ) -> (Vec<GlobalElementId>, FrameDiff) {
+        let mut root = crate::div().children(keys.iter().map(|&key| Empty.key(key)));
+        let mut text_ctx = TextContext::new();
+        let mut layout_engine = LayoutEngine::new();
+        let mut cx = LayoutContext::new(&mut layout_engine, &mut text_ctx, ScaleFactor(1.0))
+            .with_reconciler(reconciler);
+        root.request_layout(&mut cx);
+        drop(cx);
+        let ids = root
+            .children_mut()
+            .iter()
+            .map(|child| child.global_id().unwrap())
+            .collect();
+        (ids, reconciler.finish_frame())
+    }
+
+    #[test]
+    fn keyed_children_keep_identity_across_frames() {
+        let mut reconciler = Reconciler::new();
+        let (first, _) = keyed_frame(&mut reconciler, &["a", "b"]);
+        let (second, diff) = keyed_frame(&mut reconciler, &["new", "a", "b"]);
+
+        assert_eq!(&second[1..], &first[..]);
+        assert_eq!(diff.added, vec![second[0]]);
+        assert!(diff.removed.is_empty());
+
+        let (_, diff) = keyed_frame(&mut reconciler, &["b"]);
+        assert_eq!(diff.removed.len(), 2);
+    }
+
+    #[test]
+    fn conditional_children_are_added_only_when_true() {
+        let mut built = 0;
+        let mut root = crate::div()
+            .when(true, || Empty)
+            .when(false, || {
+                built += 1;
+                Empty
+            })
+            .child_if(true, Empty)
+            .child_if(false, Empty)
+            .children_iter(1..=3, |n| Empty.key(n as u64));
+
+        assert_eq!(built, 0);
+        let children = root.children_mut();
+        assert_eq!(children.len(), 5);
+        assert_eq!(children[4].element_key(), Some(&ElementKey::Number(3)));
+    }
 }
@@ -1,18 +1,34 @@
 //! Interactive button element.
 //!
 //! ```ignore
-//! let button_id = ElementId(1);
-//! button("Click me", button_id)
+//! button("Click me", ElementId(1))
+//!     .hovered(input.hovered() == Some(ElementId(1)))
+//!     .pressed(input.pressed() == Some(ElementId(1)))
 //!     .on_click(|| println!("Clicked!"))
-//!     .render(cx);
 //! ```
+//!
+//! Click handlers are registered with the paint context's
+//! [`CallbackRegistry`](crate::CallbackRegistry); dispatch them with
+//! `registry.dispatch_click(id)` when `InputState::end_press` returns the
+//! button's id.
 
 use crate::{
     element::{Element, IntoElement, LayoutContext, PaintContext},
+    input::CursorIcon,
     layout::{MeasureContext, NodeId},
-    ArcStr, ElementId, Rect, Srgba, TextRun,
+    AccessRole, ArcStr, ClickCallback, ElementId, Rect, Srgba, TextRun,
 };
 
+/// Which look a button paints with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ButtonState {
+    Normal,
+    Hovered,
+    Pressed,
+    /// Ignores input: no hit region and no click handler.
+    Disabled,
+}
+
 /// Interactive button element.
 pub struct Button {
     label: ArcStr,
@@ -21,13 +37,21 @@ pub struct Button {
     background: Srgba,
     hover_background: Srgba,
     press_background: Srgba,
+    disabled_background: Srgba,
     text_color: Srgba,
+    disabled_text_color: Srgba,
     font_size: f32,
     corner_radius: f32,
     padding: f32,
     // State (set externally before paint)
     is_hovered: bool,
     is_pressed: bool,
+    is_disabled: bool,
+    cursor: CursorIcon,
+    accessibility_label: Option<ArcStr>,
+    accessibility_hidden: bool,
+    tab_index: Option<i32>,
+    on_click: Option<ClickCallback>,
 }
 
 impl Button {
@@ -38,12 +62,20 @@ impl Button {
             background: Srgba::new(0.2, 0.4, 0.8, 1.0),
             hover_background: Srgba::new(0.3, 0.5, 0.9, 1.0),
             press_background: Srgba::new(0.15, 0.3, 0.6, 1.0),
+            disabled_background: Srgba::new(0.25, 0.25, 0.28, 1.0),
             text_color: Srgba::new(1.0, 1.0, 1.0, 1.0),
+            disabled_text_color: Srgba::new(0.55, 0.55, 0.58, 1.0),
             font_size: 14.0,
             corner_radius: 6.0,
             padding: 12.0,
             is_hovered: false,
             is_pressed: false,
+            is_disabled: false,
+            cursor: CursorIcon::Pointer,
+            accessibility_label: None,
+            accessibility_hidden: false,
+            tab_index: None,
+            on_click: None,
         }
     }
 
@@ -59,6 +91,60 @@ impl Button {
         self
     }
 
+    /// Set whether the button is disabled. Disabled buttons ignore hover and
+    /// press state and never invoke their click handler.
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.is_disabled = disabled;
+        self
+    }
+
+    /// Set the cursor shown over the button. Defaults to
+    /// [`CursorIcon::Pointer`].
+    pub fn cursor(mut self, cursor: CursorIcon) -> Self {
+        self.cursor = cursor;
+        self
+    }
+
+    /// What screen readers announce instead of the button's label, e.g. for
+    /// icon buttons.
+    pub fn accessibility_label(mut self, label: impl Into<ArcStr>) -> Self {
+        self.accessibility_label = Some(label.into());
+        self
+    }
+
+    /// Hide the button from screen readers.
+    pub fn accessibility_hidden(mut self, hidden: bool) -> Self {
+        self.accessibility_hidden = hidden;
+        self
+    }
+
+    /// Set this element's place in the tab order: positive values come
+    /// first, in ascending order, and negative ones are skipped. Without it,
+    /// buttons are tabbed to in reading order.
+    pub fn tab_index(mut self, index: i32) -> Self {
+        self.tab_index = Some(index);
+        self
+    }
+
+    /// Set the handler invoked when the button is clicked.
+    pub fn on_click(mut self, handler: impl FnMut() + 'static) -> Self {
+        self.on_click = Some(Box::new(handler));
+        self
+    }
+
+    /// The state the button will paint with.
+    pub fn state(&self) -> ButtonState {
+        if self.is_disabled {
+            ButtonState::Disabled
+        } else if self.is_pressed {
+            ButtonState::Pressed
+        } else if self.is_hovered {
+            ButtonState::Hovered
+        } else {
+            ButtonState::Normal
+        }
+    }
+
     /// Set the background color.
     pub fn background(mut self, color: Srgba) -> Self {
         self.background = color;
@@ -77,12 +163,24 @@ impl Button {
         self
     }
 
+    /// Set the disabled background color.
+    pub fn disabled_background(mut self, color: Srgba) -> Self {
+        self.disabled_background = color;
+        self
+    }
+
     /// Set the text color.
     pub fn text_color(mut self, color: Srgba) -> Self {
         self.text_color = color;
         self
     }
 
+    /// Set the disabled text color.
+    pub fn disabled_text_color(mut self, color: Srgba) -> Self {
+        self.disabled_text_color = color;
+        self
+    }
+
     /// Set the font size.
     pub fn font_size(mut self, size: f32) -> Self {
         self.font_size = size;
@@ -129,13 +227,18 @@ impl Element for Button {
     }
 
     fn paint(&mut self, bounds: Rect, cx: &mut PaintContext) {
-        // Determine background color based on state
-        let bg_color = if self.is_pressed {
-            self.press_background
-        } else if self.is_hovered {
-            self.hover_background
+        // Determine colors based on state
+        let state = self.state();
+        let bg_color = match state {
+            ButtonState::Normal => self.background,
+            ButtonState::Hovered => self.hover_background,
+            ButtonState::Pressed => self.press_background,
+            ButtonState::Disabled => self.disabled_background,
+        };
+        let text_color = if state == ButtonState::Disabled {
+            self.disabled_text_color
         } else {
-            self.background
+            self.text_color
         };
 
         // Paint background quad
@@ -166,20 +269,38 @@ impl Element for Button {
 
         for run in layout.glyph_runs_with_font() {
             if let Some(font) = run.font_data {
-                let mut text_run =
-                    TextRun::new(device_origin, self.text_color, run.font_size, font);
+                let mut text_run = TextRun::new(device_origin, text_color, run.font_size, font)
+                    .with_line_metrics(run.ascent, run.descent);
                 text_run.normalized_coords = run.normalized_coords;
 
-                for glyph in run.glyphs {
-                    text_run.push_glyph(glyph.id, glyph.x, glyph.y);
+                let scene = cx.scene();
+                scene.push_text_run(text_run);
+                for glyph in &run.glyphs {
+                    scene.push_positioned_glyph(glyph);
                 }
+            }
+        }
 
-                cx.scene().push_text_run(text_run);
+        if !self.accessibility_hidden {
+            let name = self.accessibility_label.as_ref().unwrap_or(&self.label);
+            if let Some(node) =
+                cx.push_access_node(Some(self.id), AccessRole::Button, name.as_str(), bounds)
+            {
+                node.disabled = state == ButtonState::Disabled;
+                node.tab_index = self.tab_index;
             }
         }
 
-        // Register for hit testing
+        if state == ButtonState::Disabled {
+            return;
+        }
+
+        // Register for hit testing and click dispatch
         cx.register_hit(self.id, bounds);
+        cx.set_cursor(self.id, self.cursor);
+        if let Some(on_click) = self.on_click.take() {
+            cx.on_click(self.id, on_click);
+        }
     }
 }
 
@@ -202,7 +323,31 @@ pub fn button(label: impl Into<ArcStr>, id: ElementId) -> Button {
 mod tests {
     use super::*;
     use crate::element::LayoutContext;
-    use crate::{HitTree, LayoutEngine, Point, ScaleFactor, Scene, TextContext};
+    use crate::{CallbackRegistry, HitTree, LayoutEngine, Point, ScaleFactor, Scene, TextContext};
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    /// Lay out and paint `btn`, collecting callbacks into `callbacks`.
+    fn paint_button(btn: &mut Button, callbacks: &mut CallbackRegistry) -> (Scene, HitTree) {
+        let mut scene = Scene::new();
+        let mut text_ctx = TextContext::new();
+        let mut hit_tree = HitTree::new();
+        let mut layout_engine = LayoutEngine::new();
+        let mut layout_cx = LayoutContext::new(&mut layout_engine, &mut text_ctx, ScaleFactor(1.0));
+        let node_id = btn.request_layout(&mut layout_cx);
+        layout_engine.compute_layout(node_id, 800.0, 600.0, &mut text_ctx);
+        let bounds = layout_engine.layout_bounds(node_id);
+        let mut cx = PaintContext::new(
+            &mut scene,
+            &mut text_ctx,
+            &mut hit_tree,
+            &layout_engine,
+            ScaleFactor(1.0),
+        )
+        .with_callbacks(callbacks);
+        btn.paint(bounds, &mut cx);
+        (scene, hit_tree)
+    }
 
     #[test]
     fn button_registers_hit() {
@@ -281,4 +426,32 @@ mod tests {
         assert!(hovered.is_hovered);
         assert!(pressed.is_pressed);
     }
+
+    #[test]
+    fn button_registers_click_handler() {
+        let clicks = Rc::new(Cell::new(0));
+        let counter = clicks.clone();
+        let mut btn = button("Test", ElementId(7)).on_click(move || counter.set(counter.get() + 1));
+        let mut callbacks = CallbackRegistry::new();
+        paint_button(&mut btn, &mut callbacks);
+
+        assert!(callbacks.dispatch_click(ElementId(7)));
+        assert_eq!(clicks.get(), 1);
+    }
+
+    #[test]
+    fn disabled_button_ignores_input() {
+        let mut btn = button("Test", ElementId(7))
+            .hovered(true)
+            .pressed(true)
+            .disabled(true)
+            .on_click(|| panic!("disabled button clicked"));
+        assert_eq!(btn.state(), ButtonState::Disabled);
+
+        let mut callbacks = CallbackRegistry::new();
+        let (scene, hit_tree) = paint_button(&mut btn, &mut callbacks);
+        assert!(!callbacks.has_click_handler(ElementId(7)));
+        assert!(hit_tree.is_empty());
+        assert_eq!(scene.quads()[0].background, btn.disabled_background);
+    }
 }
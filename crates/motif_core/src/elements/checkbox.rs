@@ -9,8 +9,9 @@
 
 use crate::{
     element::{Element, IntoElement, LayoutContext, PaintContext},
+    input::CursorIcon,
     layout::NodeId,
-    Corners, DevicePoint, DeviceRect, DeviceSize, Edges, ElementId, Quad, Rect, Srgba,
+    AccessRole, Corners, DevicePoint, DeviceRect, DeviceSize, Edges, ElementId, Quad, Rect, Srgba,
 };
 
 /// Checkbox element with checked/unchecked visual state.
@@ -29,6 +30,7 @@ pub struct Checkbox {
     border_color: Srgba,
     border_width: f32,
     check_color: Srgba,
+    tab_index: Option<i32>,
     // Interactive state (set by caller before paint)
     is_hovered: bool,
     is_pressed: bool,
@@ -45,6 +47,7 @@ impl Checkbox {
             border_color: Srgba::new(0.4, 0.4, 0.4, 1.0),
             border_width: 1.5,
             check_color: Srgba::new(0.2, 0.4, 0.8, 1.0),
+            tab_index: None,
             is_hovered: false,
             is_pressed: false,
         }
@@ -92,6 +95,14 @@ impl Checkbox {
         self
     }
 
+    /// Set this element's place in the tab order: positive values come
+    /// first, in ascending order, and negative ones are skipped. Without it,
+    /// checkboxes are tabbed to in reading order.
+    pub fn tab_index(mut self, index: i32) -> Self {
+        self.tab_index = Some(index);
+        self
+    }
+
     /// Set whether the checkbox is currently hovered.
     pub fn hovered(mut self, hovered: bool) -> Self {
         self.is_hovered = hovered;
@@ -133,13 +144,10 @@ impl Element for Checkbox {
         };
 
         // Paint outer box.
-        let device_bounds = DeviceRect::new(
-            DevicePoint::new(bounds.origin.x * scale, bounds.origin.y * scale),
-            DeviceSize::new(bounds.size.width * scale, bounds.size.height * scale),
-        );
+        let device_bounds = cx.scale_factor().snap_rect(bounds);
         let mut outer_quad = Quad::new(device_bounds, self.background);
         outer_quad.border_color = border_color;
-        outer_quad.border_widths = Edges::all(self.border_width * scale);
+        outer_quad.border_widths = Edges::all(cx.scale_factor().snap_length(self.border_width));
         outer_quad.corner_radii = Corners::all(self.corner_radius * scale);
         cx.scene().push_quad(outer_quad);
 
@@ -159,8 +167,14 @@ impl Element for Checkbox {
             cx.scene().push_quad(check_quad);
         }
 
+        if let Some(node) = cx.push_access_node(Some(self.id), AccessRole::CheckBox, "", bounds) {
+            node.checked = Some(self.checked.into());
+            node.tab_index = self.tab_index;
+        }
+
         // Register the full box for hit testing.
         cx.register_hit(self.id, bounds);
+        cx.set_cursor(self.id, CursorIcon::Pointer);
     }
 }
 
@@ -3,23 +3,38 @@
 use crate::element::{
     AnyElement, Element, IntoElement, LayoutContext, PaintContext, ParentElement,
 };
+use crate::input::{CursorIcon, MouseEvent};
 use crate::layout::{self, NodeId};
-use crate::{Corners, DeviceRect, Edges, Quad, Rect, Size};
+use crate::style;
+use crate::{
+    AccessRole, ArcStr, Corners, DeviceRect, Edges, ElementId, EventContext, HoverCallback,
+    MouseCallback, Quad, Rect, Size,
+};
 use palette::Srgba;
 use smallvec::SmallVec;
 
 /// A container element, analogous to an HTML div.
 ///
 /// Supports background color, borders, rounded corners, and children.
-/// Uses builder pattern for configuration.
+/// Children are positioned by flexbox layout (direction, wrap, grow/shrink,
+/// gap and alignment), so only the root needs a size. Uses builder pattern
+/// for configuration.
 ///
 /// ```ignore
 /// div()
+///     .flex_row()
 ///     .size(Size::new(200.0, 100.0))
+///     .gap(8.0)
+///     .align_items(AlignItems::Center)
 ///     .background(Srgba::new(0.1, 0.1, 0.15, 1.0))
 ///     .corner_radius(8.0)
 ///     .child(text("Hello"))
+///     .child(div().flex_grow(1.0))
 /// ```
+///
+/// A Div with an [`id`](Div::id) registers a hit region and any mouse
+/// handlers while painting. Events on children bubble up to it; see
+/// [`CallbackRegistry`](crate::CallbackRegistry).
 pub struct Div {
     // Layout style
     style: layout::Style,
@@ -28,6 +43,20 @@ pub struct Div {
     border_color: Option<Srgba>,
     border_widths: Edges<f32>,
     corner_radii: Corners<f32>,
+    // Text properties inherited by descendants
+    text_style: style::Style,
+    // Interaction
+    id: Option<ElementId>,
+    on_click: Option<MouseCallback>,
+    on_mouse_down: Option<MouseCallback>,
+    on_mouse_up: Option<MouseCallback>,
+    on_hover: Option<HoverCallback>,
+    tooltip: Option<ArcStr>,
+    cursor: Option<CursorIcon>,
+    // Accessibility
+    accessibility_label: Option<ArcStr>,
+    accessibility_hidden: bool,
+    tab_index: Option<i32>,
     // Children
     children: SmallVec<[AnyElement; 2]>,
 }
@@ -40,10 +69,99 @@ impl Div {
             border_color: None,
             border_widths: Edges::default(),
             corner_radii: Corners::default(),
+            text_style: style::Style::default(),
+            id: None,
+            on_click: None,
+            on_mouse_down: None,
+            on_mouse_up: None,
+            on_hover: None,
+            tooltip: None,
+            cursor: None,
+            accessibility_label: None,
+            accessibility_hidden: false,
+            tab_index: None,
             children: SmallVec::new(),
         }
     }
 
+    /// Make this div hit-testable as `id`. Needed for mouse handlers, and
+    /// for children's events to bubble through it.
+    pub fn id(mut self, id: ElementId) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// Handle clicks on this div or its children.
+    pub fn on_click(
+        mut self,
+        handler: impl FnMut(&MouseEvent, &mut EventContext) + 'static,
+    ) -> Self {
+        self.on_click = Some(Box::new(handler));
+        self
+    }
+
+    /// Handle mouse button presses on this div or its children.
+    pub fn on_mouse_down(
+        mut self,
+        handler: impl FnMut(&MouseEvent, &mut EventContext) + 'static,
+    ) -> Self {
+        self.on_mouse_down = Some(Box::new(handler));
+        self
+    }
+
+    /// Handle mouse button releases on this div or its children.
+    pub fn on_mouse_up(
+        mut self,
+        handler: impl FnMut(&MouseEvent, &mut EventContext) + 'static,
+    ) -> Self {
+        self.on_mouse_up = Some(Box::new(handler));
+        self
+    }
+
+    /// Called with `true` when the cursor enters this div or its children
+    /// and `false` when it leaves.
+    pub fn on_hover(mut self, handler: impl FnMut(bool) + 'static) -> Self {
+        self.on_hover = Some(Box::new(handler));
+        self
+    }
+
+    /// Show `label` in a [`tooltip`](super::tooltip()) while this div or
+    /// its children are hovered.
+    pub fn tooltip(mut self, label: impl Into<ArcStr>) -> Self {
+        self.tooltip = Some(label.into());
+        self
+    }
+
+    /// Show `cursor` while the pointer is over this div, e.g.
+    /// [`CursorIcon::ColResize`] on a split handle. Children that set their
+    /// own cursor take precedence.
+    pub fn cursor(mut self, cursor: CursorIcon) -> Self {
+        self.cursor = Some(cursor);
+        self
+    }
+
+    /// What screen readers announce for this div. Clickable divs are
+    /// otherwise named after the text inside them; giving a plain div a
+    /// label exposes it as a group.
+    pub fn accessibility_label(mut self, label: impl Into<ArcStr>) -> Self {
+        self.accessibility_label = Some(label.into());
+        self
+    }
+
+    /// Hide this div and everything in it from screen readers.
+    pub fn accessibility_hidden(mut self, hidden: bool) -> Self {
+        self.accessibility_hidden = hidden;
+        self
+    }
+
+    /// Set this element's place in the tab order: positive values come
+    /// first, in ascending order, and negative ones are skipped. Without it,
+    /// clickable divs are tabbed to in reading order.
+    pub fn tab_index(mut self, index: i32) -> Self {
+        self.tab_index = Some(index);
+        self
+    }
+
     /// Set fixed size in logical pixels.
     pub fn size(mut self, size: Size) -> Self {
         self.style.size = taffy::Size {
@@ -100,13 +218,40 @@ impl Div {
         self
     }
 
-    /// Set padding on all sides.
-    pub fn padding(mut self, padding: f32) -> Self {
+    /// Set gap between columns (horizontal).
+    pub fn column_gap(mut self, gap: f32) -> Self {
+        self.style.gap.width = taffy::style::LengthPercentage::length(gap);
+        self
+    }
+
+    /// Set gap between rows (vertical).
+    pub fn row_gap(mut self, gap: f32) -> Self {
+        self.style.gap.height = taffy::style::LengthPercentage::length(gap);
+        self
+    }
+
+    /// Set padding, inset between the border and the children. Accepts a
+    /// single value for all sides or per-side [`Edges`].
+    pub fn padding(mut self, padding: impl Into<Edges<f32>>) -> Self {
+        let padding = padding.into();
         self.style.padding = taffy::Rect {
-            left: taffy::style::LengthPercentage::length(padding),
-            right: taffy::style::LengthPercentage::length(padding),
-            top: taffy::style::LengthPercentage::length(padding),
-            bottom: taffy::style::LengthPercentage::length(padding),
+            left: taffy::style::LengthPercentage::length(padding.left),
+            right: taffy::style::LengthPercentage::length(padding.right),
+            top: taffy::style::LengthPercentage::length(padding.top),
+            bottom: taffy::style::LengthPercentage::length(padding.bottom),
+        };
+        self
+    }
+
+    /// Set margin, space kept clear around this element within its parent.
+    /// Accepts a single value for all sides or per-side [`Edges`].
+    pub fn margin(mut self, margin: impl Into<Edges<f32>>) -> Self {
+        let margin = margin.into();
+        self.style.margin = taffy::Rect {
+            left: taffy::style::LengthPercentageAuto::length(margin.left),
+            right: taffy::style::LengthPercentageAuto::length(margin.right),
+            top: taffy::style::LengthPercentageAuto::length(margin.top),
+            bottom: taffy::style::LengthPercentageAuto::length(margin.bottom),
         };
         self
     }
@@ -123,6 +268,30 @@ impl Div {
         self
     }
 
+    /// Override the parent's `align_items` for this element.
+    pub fn align_self(mut self, align: layout::AlignSelf) -> Self {
+        self.style.align_self = Some(align);
+        self
+    }
+
+    /// Set align content (cross axis alignment of wrapped lines).
+    pub fn align_content(mut self, align: layout::AlignContent) -> Self {
+        self.style.align_content = Some(align);
+        self
+    }
+
+    /// Set whether children wrap onto multiple lines.
+    pub fn flex_wrap(mut self, wrap: layout::FlexWrap) -> Self {
+        self.style.flex_wrap = wrap;
+        self
+    }
+
+    /// Set the main axis size before growing or shrinking, in logical pixels.
+    pub fn flex_basis(mut self, basis: f32) -> Self {
+        self.style.flex_basis = taffy::style::Dimension::length(basis);
+        self
+    }
+
     /// Set flex grow.
     pub fn flex_grow(mut self, grow: f32) -> Self {
         self.style.flex_grow = grow;
@@ -165,6 +334,100 @@ impl Div {
         self.corner_radii = radii;
         self
     }
+
+    /// Default text color for descendant text that doesn't set its own.
+    pub fn text_color(mut self, color: impl Into<Srgba>) -> Self {
+        self.text_style.text_color = Some(color.into());
+        self
+    }
+
+    /// Default font size for descendant text that doesn't set its own.
+    pub fn font_size(mut self, size: f32) -> Self {
+        self.text_style.font_size = Some(size);
+        self
+    }
+
+    /// Apply the visual properties `style` sets. Text properties become
+    /// defaults inherited by descendant text.
+    pub fn style(mut self, style: style::Style) -> Self {
+        if let Some(color) = style.text_color {
+            self = self.text_color(color);
+        }
+        if let Some(size) = style.font_size {
+            self = self.font_size(size);
+        }
+        if let Some(color) = style.background {
+            self = self.background(color);
+        }
+        if let Some(color) = style.border_color {
+            self = self.border_color(color);
+        }
+        if let Some(widths) = style.border_widths {
+            self.style.border = taffy::Rect {
+                left: taffy::style::LengthPercentage::length(widths.left),
+                right: taffy::style::LengthPercentage::length(widths.right),
+                top: taffy::style::LengthPercentage::length(widths.top),
+                bottom: taffy::style::LengthPercentage::length(widths.bottom),
+            };
+            self.border_widths = widths;
+        }
+        if let Some(radii) = style.corner_radii {
+            self.corner_radii = radii;
+        }
+        self
+    }
+}
+
+impl Div {
+    /// The role this div reports to screen readers. Layout-only divs don't
+    /// get a node; their children attach to the nearest ancestor that does.
+    fn access_role(&self) -> Option<AccessRole> {
+        if self.on_click.is_some() {
+            Some(AccessRole::Button)
+        } else if self.id.is_some()
+            || self.accessibility_label.is_some()
+            || self.tab_index.is_some()
+        {
+            Some(AccessRole::Group)
+        } else {
+            None
+        }
+    }
+
+    fn paint_children(&mut self, bounds: Rect, role: Option<AccessRole>, cx: &mut PaintContext) {
+        // Children get their bounds from the layout engine
+        let children = &mut self.children;
+        let text_style = &self.text_style;
+        let paint = |cx: &mut PaintContext| {
+            cx.with_text_style(text_style, |cx| {
+                for child in children {
+                    cx.paint_child(child);
+                }
+            })
+        };
+
+        if self.accessibility_hidden {
+            return cx.without_accessibility(paint);
+        }
+        let label = self.accessibility_label.as_ref().map_or("", ArcStr::as_str);
+        let node = role.and_then(|role| cx.push_access_node(self.id, role, label, bounds));
+        let Some(access_id) = node.map(|node| {
+            node.tab_index = self.tab_index;
+            node.id
+        }) else {
+            return paint(cx);
+        };
+        cx.with_access_parent(access_id, paint);
+
+        if role == Some(AccessRole::Button) && self.accessibility_label.is_none() {
+            if let Some(tree) = cx.access_tree() {
+                let name = tree.text_content(access_id);
+                if let Some(node) = tree.get_mut(access_id) {
+                    node.name = name;
+                }
+            }
+        }
+    }
 }
 
 // Re-export taffy types for convenience
@@ -185,11 +448,9 @@ impl ParentElement for Div {
 impl Element for Div {
     fn request_layout(&mut self, cx: &mut LayoutContext) -> NodeId {
         // Request layout for all children first
-        let child_ids: Vec<NodeId> = self
-            .children
-            .iter_mut()
-            .map(|child| child.request_layout(cx))
-            .collect();
+        let children = &mut self.children;
+        let child_ids =
+            cx.with_text_style(&self.text_style, |cx| cx.request_children_layout(children));
 
         // Create our layout node with children
         cx.layout_engine()
@@ -197,13 +458,20 @@ impl Element for Div {
     }
 
     fn paint(&mut self, bounds: Rect, cx: &mut PaintContext) {
+        let access_role = self.access_role();
+
         // Paint self as a quad if it has any visual properties
         if self.background.is_some() || self.border_color.is_some() {
             let scale = cx.scale_factor();
-            let device_bounds = DeviceRect::new(
-                scale.scale_point(bounds.origin),
-                scale.scale_size(bounds.size),
-            );
+            // Borders straddling device pixels blur into two faint lines.
+            let device_bounds = if self.border_color.is_some() {
+                scale.snap_rect(bounds)
+            } else {
+                DeviceRect::new(
+                    scale.scale_point(bounds.origin),
+                    scale.scale_size(bounds.size),
+                )
+            };
 
             let mut quad = Quad::new(
                 device_bounds,
@@ -219,9 +487,48 @@ impl Element for Div {
             cx.scene().push_quad(quad);
         }
 
-        // Paint children (they get their bounds from layout engine)
-        for child in &mut self.children {
-            cx.paint_child(child);
+        let Some(id) = self.id else {
+            debug_assert!(
+                self.on_click.is_none()
+                    && self.on_mouse_down.is_none()
+                    && self.on_mouse_up.is_none()
+                    && self.on_hover.is_none()
+                    && self.tooltip.is_none()
+                    && self.cursor.is_none(),
+                "div mouse handlers, tooltips and cursors need an id"
+            );
+            self.paint_children(bounds, access_role, cx);
+            return;
+        };
+
+        cx.register_hit(id, bounds);
+        if let Some(cursor) = self.cursor {
+            cx.set_cursor(id, cursor);
+        }
+        if let Some(callbacks) = cx.callbacks() {
+            if let Some(handler) = self.on_click.take() {
+                callbacks.on_click_event(id, handler);
+            }
+            if let Some(handler) = self.on_mouse_down.take() {
+                callbacks.on_mouse_down(id, handler);
+            }
+            if let Some(handler) = self.on_mouse_up.take() {
+                callbacks.on_mouse_up(id, handler);
+            }
+            if let Some(handler) = self.on_hover.take() {
+                callbacks.on_hover(id, handler);
+            }
+            callbacks.push_parent(id);
+        }
+        self.paint_children(bounds, access_role, cx);
+        if let Some(callbacks) = cx.callbacks() {
+            callbacks.pop_parent();
+        }
+
+        if let Some(label) = &self.tooltip {
+            if cx.is_hovered(id) {
+                cx.paint_overlay(bounds, super::tooltip(label.clone()));
+            }
         }
     }
 }
@@ -242,7 +549,9 @@ pub fn div() -> Div {
 mod tests {
     use super::*;
     use crate::element::LayoutContext;
-    use crate::{HitTree, LayoutEngine, ScaleFactor, Scene, TextContext};
+    use crate::{
+        DevicePoint, DeviceSize, HitTree, LayoutEngine, Point, ScaleFactor, Scene, TextContext,
+    };
 
     #[test]
     fn div_builder_sets_background() {
@@ -250,6 +559,56 @@ mod tests {
         assert_eq!(d.background, Some(Srgba::new(1.0, 0.0, 0.0, 1.0)));
     }
 
+    #[test]
+    fn div_style_applies_theme_panel() {
+        let theme = crate::Theme::dark();
+        let d = div().style(theme.panel());
+        assert_eq!(d.background, Some(theme.surface));
+        assert_eq!(d.border_color, Some(theme.border));
+        assert_eq!(d.border_widths, Edges::all(1.0));
+        assert_eq!(d.corner_radii, Corners::all(theme.radius));
+    }
+
+    #[test]
+    fn text_inherits_nearest_ancestor_style() {
+        use crate::text;
+
+        let red = Srgba::new(1.0, 0.0, 0.0, 1.0);
+        let blue = Srgba::new(0.0, 0.0, 1.0, 1.0);
+        let mut root = div()
+            .text_color(red)
+            .font_size(30.0)
+            .child(text("inherits"))
+            .child(div().text_color(blue).child(text("nested")))
+            .child(text("inherits").color(blue).font_size(10.0));
+
+        let mut scene = Scene::new();
+        let mut text_ctx = TextContext::new();
+        let mut hit_tree = HitTree::new();
+        let mut layout_engine = LayoutEngine::new();
+        let mut layout_cx = LayoutContext::new(&mut layout_engine, &mut text_ctx, ScaleFactor(1.0));
+        let node_id = root.request_layout(&mut layout_cx);
+        layout_engine.compute_layout(node_id, 800.0, 600.0, &mut text_ctx);
+        let bounds = layout_engine.layout_bounds(node_id);
+        let mut cx = PaintContext::new(
+            &mut scene,
+            &mut text_ctx,
+            &mut hit_tree,
+            &layout_engine,
+            ScaleFactor(1.0),
+        );
+        root.paint(bounds, &mut cx);
+
+        let runs = scene.text_runs();
+        assert_eq!((runs[0].color, runs[0].font_size), (red, 30.0));
+        assert_eq!((runs[1].color, runs[1].font_size), (blue, 30.0));
+        assert_eq!((runs[2].color, runs[2].font_size), (blue, 10.0));
+
+        // Layout measured the inherited size too.
+        let sizes = layout_children(&mut root);
+        assert!(sizes[0].size.width > sizes[2].size.width * 2.0);
+    }
+
     #[test]
     fn div_builder_sets_size() {
         let d = div().size(Size::new(100.0, 50.0));
@@ -290,6 +649,35 @@ mod tests {
         assert_eq!(scene.quad_count(), 1);
     }
 
+    #[test]
+    fn bordered_div_snaps_to_device_pixels() {
+        let paint = |mut d: Div| {
+            let mut scene = Scene::new();
+            let mut text_ctx = TextContext::new();
+            let mut hit_tree = HitTree::new();
+            let layout_engine = LayoutEngine::new();
+            let mut cx = PaintContext::new(
+                &mut scene,
+                &mut text_ctx,
+                &mut hit_tree,
+                &layout_engine,
+                ScaleFactor(1.5),
+            );
+            let bounds = Rect::new(Point::new(10.3, 4.5), Size::new(20.0, 7.1));
+            d.paint(bounds, &mut cx);
+            scene.quads()[0].bounds
+        };
+
+        let red = Srgba::new(1.0, 0.0, 0.0, 1.0);
+        let bordered = paint(div().background(red).border_color(red));
+        assert_eq!(bordered.origin, DevicePoint::new(15.0, 7.0));
+        assert_eq!(bordered.size, DeviceSize::new(30.0, 10.0));
+
+        // Plain fills keep their exact bounds.
+        let filled = paint(div().background(red));
+        assert_eq!(filled.origin.x, 10.3 * 1.5);
+    }
+
     #[test]
     fn div_skips_paint_when_no_visual() {
         let mut d = div().size(Size::new(100.0, 50.0));
@@ -320,6 +708,83 @@ mod tests {
         assert_eq!(scene.quad_count(), 0);
     }
 
+    fn layout_children(root: &mut Div) -> Vec<Rect> {
+        let mut text_ctx = TextContext::new();
+        let mut layout_engine = LayoutEngine::new();
+        let mut layout_cx = LayoutContext::new(&mut layout_engine, &mut text_ctx, ScaleFactor(1.0));
+        let node_id = root.request_layout(&mut layout_cx);
+        layout_engine.compute_layout(node_id, 800.0, 600.0, &mut text_ctx);
+        root.children
+            .iter()
+            .map(|child| layout_engine.layout_bounds(child.node_id().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn flex_row_places_children_with_gap_and_grow() {
+        let mut root = div()
+            .flex_row()
+            .size(Size::new(300.0, 100.0))
+            .gap(10.0)
+            .align_items(layout::AlignItems::Center)
+            .child(div().size(Size::new(50.0, 20.0)))
+            .child(div().height(40.0).flex_grow(1.0));
+
+        let bounds = layout_children(&mut root);
+        assert_eq!(
+            bounds[0],
+            Rect::new(Point::new(0.0, 40.0), Size::new(50.0, 20.0))
+        );
+        assert_eq!(
+            bounds[1],
+            Rect::new(Point::new(60.0, 30.0), Size::new(240.0, 40.0))
+        );
+    }
+
+    #[test]
+    fn flex_wrap_moves_overflow_to_next_line() {
+        let mut root = div()
+            .flex_row()
+            .flex_wrap(layout::FlexWrap::Wrap)
+            .align_content(layout::AlignContent::FlexStart)
+            .width(100.0)
+            .row_gap(5.0)
+            .child(div().size(Size::new(60.0, 10.0)))
+            .child(
+                div()
+                    .size(Size::new(60.0, 10.0))
+                    .align_self(layout::AlignSelf::FlexStart),
+            );
+
+        let bounds = layout_children(&mut root);
+        assert_eq!(bounds[1].origin, Point::new(0.0, 15.0));
+    }
+
+    #[test]
+    fn padding_and_margin_offset_children() {
+        let mut root = div()
+            .flex_col()
+            .size(Size::new(200.0, 200.0))
+            .padding(Edges {
+                top: 10.0,
+                right: 0.0,
+                bottom: 0.0,
+                left: 20.0,
+            })
+            .child(div().height(30.0).margin(Edges::symmetric(5.0, 8.0)))
+            .child(div().height(30.0));
+
+        let bounds = layout_children(&mut root);
+        assert_eq!(
+            bounds[0],
+            Rect::new(Point::new(28.0, 15.0), Size::new(164.0, 30.0))
+        );
+        assert_eq!(
+            bounds[1],
+            Rect::new(Point::new(20.0, 50.0), Size::new(180.0, 30.0))
+        );
+    }
+
     #[test]
     fn div_accepts_children() {
         let d = div()
@@ -327,4 +792,166 @@ mod tests {
             .child(crate::element::Empty);
         assert_eq!(d.children.len(), 2);
     }
+
+    #[test]
+    fn div_handlers_receive_bubbled_events() {
+        use crate::{button, CallbackRegistry};
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let (click_log, hover_log) = (log.clone(), log.clone());
+        let mut root = div()
+            .id(ElementId(1))
+            .size(Size::new(200.0, 100.0))
+            .on_click(move |_, cx| {
+                click_log
+                    .borrow_mut()
+                    .push(format!("div {:?}", cx.target()))
+            })
+            .on_hover(move |hovered| hover_log.borrow_mut().push(format!("hover {hovered}")))
+            .child(button("Ok", ElementId(2)));
+
+        let mut scene = Scene::new();
+        let mut text_ctx = TextContext::new();
+        let mut hit_tree = HitTree::new();
+        let mut callbacks = CallbackRegistry::new();
+        let mut layout_engine = LayoutEngine::new();
+        let mut layout_cx = LayoutContext::new(&mut layout_engine, &mut text_ctx, ScaleFactor(1.0));
+        let node_id = root.request_layout(&mut layout_cx);
+        layout_engine.compute_layout(node_id, 800.0, 600.0, &mut text_ctx);
+        let bounds = layout_engine.layout_bounds(node_id);
+        let mut cx = PaintContext::new(
+            &mut scene,
+            &mut text_ctx,
+            &mut hit_tree,
+            &layout_engine,
+            ScaleFactor(1.0),
+        )
+        .with_callbacks(&mut callbacks);
+        root.paint(bounds, &mut cx);
+
+        // The button is painted last, so it's the topmost hit.
+        let target = hit_tree.hit_test(Point::new(5.0, 5.0));
+        assert_eq!(target, Some(ElementId(2)));
+        assert_eq!(
+            hit_tree.hit_test(Point::new(150.0, 80.0)),
+            Some(ElementId(1))
+        );
+
+        callbacks.dispatch_hover(None, target);
+        assert!(callbacks.dispatch_click(ElementId(2)));
+        assert_eq!(*log.borrow(), vec!["hover true", "div ElementId(2)"]);
+    }
+
+    #[test]
+    fn tooltip_paints_into_overlay_while_hovered() {
+        use crate::{text, CallbackRegistry};
+
+        let mut scene = Scene::new();
+        let mut text_ctx = TextContext::new();
+        let mut hit_tree = HitTree::new();
+        let mut callbacks = CallbackRegistry::new();
+        let mut layout_engine = LayoutEngine::new();
+        let mut paint_frame = |callbacks: &mut CallbackRegistry| {
+            scene.clear();
+            let mut root = div()
+                .id(ElementId(1))
+                .size(Size::new(100.0, 20.0))
+                .child(text("Save"))
+                .tooltip("Save the file");
+            layout_engine.clear();
+            let mut layout_cx =
+                LayoutContext::new(&mut layout_engine, &mut text_ctx, ScaleFactor(1.0));
+            let node_id = root.request_layout(&mut layout_cx);
+            layout_engine.compute_layout(node_id, 800.0, 600.0, &mut text_ctx);
+            let bounds = layout_engine.layout_bounds(node_id);
+            let mut cx = PaintContext::new(
+                &mut scene,
+                &mut text_ctx,
+                &mut hit_tree,
+                &layout_engine,
+                ScaleFactor(1.0),
+            )
+            .with_callbacks(callbacks)
+            .with_viewport_size(Size::new(800.0, 600.0));
+            root.paint(bounds, &mut cx);
+            scene
+                .overlay()
+                .map(|overlay| (overlay.quads()[0].bounds.origin.y, overlay.text_run_count()))
+        };
+
+        assert_eq!(paint_frame(&mut callbacks), None);
+        callbacks.dispatch_hover(None, Some(ElementId(1)));
+        assert_eq!(paint_frame(&mut callbacks), Some((24.0, 1)));
+        callbacks.dispatch_hover(Some(ElementId(1)), None);
+        assert_eq!(paint_frame(&mut callbacks), None);
+    }
+
+    #[test]
+    fn paint_builds_access_nodes_from_the_element_tree() {
+        use crate::{text, AccessId, AccessNode, AccessTree, Button};
+
+        let mut root = div()
+            .child(text("Title"))
+            .child(
+                div()
+                    .id(ElementId(5))
+                    .on_click(|_, _| {})
+                    .child(div().child(text("Save")).child(text("draft"))),
+            )
+            .child(Button::new("X", ElementId(6)).accessibility_label("Close"))
+            .child(div().accessibility_hidden(true).child(text("decoration")));
+
+        let mut scene = Scene::new();
+        let mut text_ctx = TextContext::new();
+        let mut hit_tree = HitTree::new();
+        let mut access_tree = AccessTree::new(AccessId(0));
+        access_tree.push(AccessNode::new(
+            AccessId(0),
+            crate::AccessRole::Window,
+            "App".into(),
+        ));
+        let mut layout_engine = LayoutEngine::new();
+        let mut layout_cx = LayoutContext::new(&mut layout_engine, &mut text_ctx, ScaleFactor(2.0));
+        let node_id = root.request_layout(&mut layout_cx);
+        layout_engine.compute_layout(node_id, 800.0, 600.0, &mut text_ctx);
+        let bounds = layout_engine.layout_bounds(node_id);
+        let mut cx = PaintContext::new(
+            &mut scene,
+            &mut text_ctx,
+            &mut hit_tree,
+            &layout_engine,
+            ScaleFactor(2.0),
+        )
+        .with_access_tree(&mut access_tree);
+        root.paint(bounds, &mut cx);
+
+        let window = access_tree.get(AccessId(0)).unwrap();
+        assert_eq!(window.children.len(), 3);
+        let title = access_tree.get(window.children[0]).unwrap();
+        assert_eq!(
+            (title.role, title.name.as_str()),
+            (AccessRole::Label, "Title")
+        );
+
+        let save = access_tree.get(AccessId(5)).unwrap();
+        assert_eq!(window.children[1], AccessId(5));
+        assert_eq!(
+            (save.role, save.name.as_str()),
+            (AccessRole::Button, "Save draft")
+        );
+        let layout_bounds = layout_engine.layout_bounds(layout_engine.children(node_id)[1]);
+        assert_eq!(
+            save.bounds.unwrap().size.width,
+            layout_bounds.size.width * 2.0
+        );
+
+        let close = access_tree.get(AccessId(6)).unwrap();
+        assert_eq!(
+            (close.role, close.name.as_str()),
+            (AccessRole::Button, "Close")
+        );
+        assert_eq!(access_tree.node_count(), 6);
+    }
 }
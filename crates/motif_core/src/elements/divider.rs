@@ -0,0 +1,129 @@
+//! Hairline separators.
+//!
+//! ```ignore
+//! v_stack()
+//!     .child(text("Account"))
+//!     .child(divider().color(cx.theme().border))
+//!     .child(text("Sign out"))
+//! ```
+//!
+//! A divider is one device pixel thick at any scale factor and stretches
+//! across its container: horizontal dividers separate the children of a
+//! [`v_stack`](super::v_stack), vertical ones those of an
+//! [`h_stack`](super::h_stack).
+
+use crate::element::{Element, IntoElement, LayoutContext, PaintContext};
+use crate::layout::{self, AlignSelf, NodeId};
+use crate::{Axis, DeviceRect, DeviceSize, Quad, Rect, Theme};
+use palette::Srgba;
+
+/// A one-device-pixel line across its container.
+pub struct Divider {
+    /// The direction the line runs in.
+    axis: Axis,
+    color: Srgba,
+}
+
+impl Divider {
+    pub fn new(axis: Axis) -> Self {
+        Self {
+            axis,
+            color: Theme::default().border,
+        }
+    }
+
+    /// Line color. Defaults to the default theme's border color.
+    pub fn color(mut self, color: impl Into<Srgba>) -> Self {
+        self.color = color.into();
+        self
+    }
+}
+
+impl Element for Divider {
+    fn request_layout(&mut self, cx: &mut LayoutContext) -> NodeId {
+        let thickness = taffy::style::Dimension::length(1.0 / cx.scale_factor.0);
+        let mut style = layout::Style {
+            align_self: Some(AlignSelf::Stretch),
+            flex_shrink: 0.0,
+            ..Default::default()
+        };
+        match self.axis {
+            Axis::Horizontal => style.size.height = thickness,
+            Axis::Vertical => style.size.width = thickness,
+        }
+        cx.layout_engine().new_leaf(style)
+    }
+
+    fn paint(&mut self, bounds: Rect, cx: &mut PaintContext) {
+        // Snap to the pixel grid so the line is one crisp pixel rather than
+        // two half-covered ones.
+        let device = cx.scale_factor().snap_rect(bounds);
+        let size = match self.axis {
+            Axis::Horizontal => DeviceSize::new(device.size.width, 1.0),
+            Axis::Vertical => DeviceSize::new(1.0, device.size.height),
+        };
+        let quad = Quad::new(DeviceRect::new(device.origin, size), self.color);
+        cx.scene().push_quad(quad);
+    }
+}
+
+impl IntoElement for Divider {
+    type Element = Divider;
+    fn into_element(self) -> Self::Element {
+        self
+    }
+}
+
+/// A horizontal divider, for separating the children of a vertical stack.
+pub fn divider() -> Divider {
+    Divider::new(Axis::Horizontal)
+}
+
+/// A vertical divider, for separating the children of a horizontal stack.
+pub fn vertical_divider() -> Divider {
+    Divider::new(Axis::Vertical)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::element::ParentElement;
+    use crate::{div, v_stack, HitTree, LayoutEngine, ScaleFactor, Scene, Size, TextContext};
+
+    fn paint_at_scale(scale: f32) -> Scene {
+        let mut root = v_stack()
+            .width(100.0)
+            .padding(10.0)
+            .child(div().size(Size::new(20.0, 15.3)))
+            .child(divider());
+
+        let mut scene = Scene::new();
+        let mut text_ctx = TextContext::new();
+        let mut hit_tree = HitTree::new();
+        let mut engine = LayoutEngine::new();
+        let mut cx = LayoutContext::new(&mut engine, &mut text_ctx, ScaleFactor(scale));
+        let node_id = root.request_layout(&mut cx);
+        engine.compute_layout(node_id, 800.0, 600.0, &mut text_ctx);
+        let bounds = engine.layout_bounds(node_id);
+        let mut cx = PaintContext::new(
+            &mut scene,
+            &mut text_ctx,
+            &mut hit_tree,
+            &engine,
+            ScaleFactor(scale),
+        );
+        root.paint(bounds, &mut cx);
+        scene
+    }
+
+    #[test]
+    fn divider_is_one_device_pixel_at_any_scale() {
+        for scale in [1.0, 2.0, 3.0] {
+            let scene = paint_at_scale(scale);
+            let line = scene.quads()[0].bounds;
+            assert_eq!(line.size.height, 1.0, "scale {scale}");
+            assert_eq!(line.size.width, 80.0 * scale, "scale {scale}");
+            assert_eq!(line.origin.y, line.origin.y.round());
+        }
+    }
+}
@@ -0,0 +1,488 @@
+//! Image element and decoded-image cache.
+//!
+//! ```ignore
+//! img("assets/avatar.png")
+//!     .size(Size::new(64.0, 64.0))
+//!     .fit(ImageFit::Cover)
+//!     .corner_radius(32.0)
+//! ```
+//!
+//! Sources are decoded on a background thread by an [`ImageCache`]. Until the
+//! pixels are ready the element lays out at its requested size and paints
+//! nothing; register [`ImageCache::on_load`] to redraw once they arrive.
+
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use crate::element::{Element, IntoElement, LayoutContext, PaintContext};
+use crate::layout::{self, NodeId};
+use crate::{Corners, DeviceRect, ImageData, ImageQuad, Point, Rect, Size};
+
+/// Where an image's pixels come from. Also the key for [`ImageCache`].
+#[derive(Clone, Debug)]
+pub enum ImageSource {
+    /// An encoded image file on disk.
+    Path(Arc<Path>),
+    /// Encoded image bytes, e.g. from `include_bytes!`. Keyed by address.
+    Static(&'static [u8]),
+    /// Pixels that are already decoded. Keyed by [`ImageData::id`].
+    Data(ImageData),
+}
+
+impl ImageSource {
+    fn decode(&self) -> Result<ImageData, String> {
+        let bytes = match self {
+            ImageSource::Path(path) => {
+                std::fs::read(path).map_err(|e| format!("{}: {e}", path.display()))?
+            }
+            ImageSource::Static(bytes) => bytes.to_vec(),
+            ImageSource::Data(data) => return Ok(data.clone()),
+        };
+        let rgba = image::load_from_memory(&bytes)
+            .map_err(|e| e.to_string())?
+            .to_rgba8();
+        let (width, height) = rgba.dimensions();
+        ImageData::new(width, height, rgba.into_raw()).ok_or_else(|| "bad pixel buffer".into())
+    }
+}
+
+impl PartialEq for ImageSource {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (ImageSource::Path(a), ImageSource::Path(b)) => a == b,
+            (ImageSource::Static(a), ImageSource::Static(b)) => std::ptr::eq(*a, *b),
+            (ImageSource::Data(a), ImageSource::Data(b)) => a.id() == b.id(),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for ImageSource {}
+
+impl Hash for ImageSource {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            ImageSource::Path(path) => path.hash(state),
+            ImageSource::Static(bytes) => {
+                bytes.as_ptr().hash(state);
+                bytes.len().hash(state);
+            }
+            ImageSource::Data(data) => data.id().hash(state),
+        }
+    }
+}
+
+impl From<&str> for ImageSource {
+    fn from(path: &str) -> Self {
+        ImageSource::Path(Path::new(path).into())
+    }
+}
+
+impl From<PathBuf> for ImageSource {
+    fn from(path: PathBuf) -> Self {
+        ImageSource::Path(path.into())
+    }
+}
+
+impl From<&'static [u8]> for ImageSource {
+    fn from(bytes: &'static [u8]) -> Self {
+        ImageSource::Static(bytes)
+    }
+}
+
+impl From<ImageData> for ImageSource {
+    fn from(data: ImageData) -> Self {
+        ImageSource::Data(data)
+    }
+}
+
+/// Progress of a source through an [`ImageCache`].
+#[derive(Clone, Debug)]
+pub enum ImageState {
+    /// Decoding on a background thread.
+    Loading,
+    Ready(ImageData),
+    /// The source couldn't be read or decoded.
+    Failed(String),
+}
+
+type LoadCallback = Arc<dyn Fn() + Send + Sync>;
+
+#[derive(Default)]
+struct CacheInner {
+    entries: Mutex<HashMap<ImageSource, ImageState>>,
+    on_load: Mutex<Option<LoadCallback>>,
+}
+
+/// Decoded images keyed by [`ImageSource`], shared between clones.
+///
+/// [`get`](Self::get) never blocks: the first request for a source starts a
+/// decode thread and reports [`ImageState::Loading`] until it finishes.
+#[derive(Clone, Default)]
+pub struct ImageCache {
+    inner: Arc<CacheInner>,
+}
+
+impl ImageCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The process-wide cache used by [`img`] unless another is given.
+    pub fn global() -> &'static ImageCache {
+        static GLOBAL: OnceLock<ImageCache> = OnceLock::new();
+        GLOBAL.get_or_init(ImageCache::new)
+    }
+
+    /// Call `f` from the decode thread whenever a load finishes, successfully
+    /// or not. Typically requests a redraw.
+    pub fn on_load(&self, f: impl Fn() + Send + Sync + 'static) {
+        *self.inner.on_load.lock().unwrap() = Some(Arc::new(f));
+    }
+
+    /// Current state of `source`, starting a decode if it hasn't been seen.
+    pub fn get(&self, source: &ImageSource) -> ImageState {
+        let mut entries = self.inner.entries.lock().unwrap();
+        if let Some(state) = entries.get(source) {
+            return state.clone();
+        }
+
+        // Already-decoded pixels don't need a thread.
+        if let ImageSource::Data(data) = source {
+            let state = ImageState::Ready(data.clone());
+            entries.insert(source.clone(), state.clone());
+            return state;
+        }
+
+        entries.insert(source.clone(), ImageState::Loading);
+        let inner = Arc::clone(&self.inner);
+        let source = source.clone();
+        std::thread::spawn(move || {
+            let state = match source.decode() {
+                Ok(data) => ImageState::Ready(data),
+                Err(message) => ImageState::Failed(message),
+            };
+            inner.entries.lock().unwrap().insert(source, state);
+            let on_load = inner.on_load.lock().unwrap().clone();
+            if let Some(on_load) = on_load {
+                on_load();
+            }
+        });
+        ImageState::Loading
+    }
+
+    /// Decoded pixels for `source`, if ready. Starts a decode like
+    /// [`get`](Self::get).
+    pub fn image(&self, source: &ImageSource) -> Option<ImageData> {
+        match self.get(source) {
+            ImageState::Ready(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Forget `source` so the next request decodes it again.
+    pub fn remove(&self, source: &ImageSource) {
+        self.inner.entries.lock().unwrap().remove(source);
+    }
+
+    pub fn clear(&self) {
+        self.inner.entries.lock().unwrap().clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// How an image is scaled into its element's bounds.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ImageFit {
+    /// Scale to fit entirely inside the bounds, centered, keeping aspect ratio.
+    #[default]
+    Contain,
+    /// Scale to cover the bounds, centered, cropping whatever overflows.
+    Cover,
+    /// Stretch to the bounds exactly.
+    Fill,
+}
+
+impl ImageFit {
+    /// Where an image of `image_size` lands inside `bounds`, and the part of
+    /// it shown there in normalized texture coordinates.
+    pub fn place(self, image_size: Size, bounds: Rect) -> (Rect, [f32; 4]) {
+        let full = [0.0, 0.0, 1.0, 1.0];
+        if image_size.width <= 0.0 || image_size.height <= 0.0 {
+            return (bounds, full);
+        }
+        let scale_x = bounds.size.width / image_size.width;
+        let scale_y = bounds.size.height / image_size.height;
+
+        match self {
+            ImageFit::Fill => (bounds, full),
+            ImageFit::Contain => {
+                let scale = scale_x.min(scale_y);
+                let size = Size::new(image_size.width * scale, image_size.height * scale);
+                let origin = Point::new(
+                    bounds.origin.x + (bounds.size.width - size.width) * 0.5,
+                    bounds.origin.y + (bounds.size.height - size.height) * 0.5,
+                );
+                (Rect::new(origin, size), full)
+            }
+            ImageFit::Cover => {
+                let scale = scale_x.max(scale_y);
+                let u = bounds.size.width / (image_size.width * scale);
+                let v = bounds.size.height / (image_size.height * scale);
+                let (u0, v0) = ((1.0 - u) * 0.5, (1.0 - v) * 0.5);
+                (bounds, [u0, v0, u0 + u, v0 + v])
+            }
+        }
+    }
+}
+
+/// An image drawn from an [`ImageSource`].
+///
+/// Without an explicit size the element takes the image's pixel size once
+/// it has been decoded.
+pub struct Img {
+    source: ImageSource,
+    cache: ImageCache,
+    style: layout::Style,
+    fit: ImageFit,
+    corner_radii: Corners<f32>,
+    opacity: f32,
+}
+
+impl Img {
+    pub fn new(source: impl Into<ImageSource>) -> Self {
+        Self {
+            source: source.into(),
+            cache: ImageCache::global().clone(),
+            style: layout::Style::default(),
+            fit: ImageFit::default(),
+            corner_radii: Corners::default(),
+            opacity: 1.0,
+        }
+    }
+
+    /// Load through `cache` instead of the global one.
+    pub fn cache(mut self, cache: &ImageCache) -> Self {
+        self.cache = cache.clone();
+        self
+    }
+
+    /// Set fixed size in logical pixels.
+    pub fn size(mut self, size: Size) -> Self {
+        self.style.size = taffy::Size {
+            width: taffy::style::Dimension::length(size.width),
+            height: taffy::style::Dimension::length(size.height),
+        };
+        self
+    }
+
+    /// Set width in logical pixels.
+    pub fn width(mut self, width: f32) -> Self {
+        self.style.size.width = taffy::style::Dimension::length(width);
+        self
+    }
+
+    /// Set height in logical pixels.
+    pub fn height(mut self, height: f32) -> Self {
+        self.style.size.height = taffy::style::Dimension::length(height);
+        self
+    }
+
+    pub fn fit(mut self, fit: ImageFit) -> Self {
+        self.fit = fit;
+        self
+    }
+
+    /// Set uniform corner radius in logical pixels.
+    pub fn corner_radius(mut self, radius: f32) -> Self {
+        self.corner_radii = Corners::all(radius);
+        self
+    }
+
+    /// Set per-corner radii in logical pixels.
+    pub fn corner_radii(mut self, radii: Corners<f32>) -> Self {
+        self.corner_radii = radii;
+        self
+    }
+
+    pub fn opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity;
+        self
+    }
+}
+
+impl Element for Img {
+    fn request_layout(&mut self, cx: &mut LayoutContext) -> NodeId {
+        let mut style = self.style.clone();
+        if let Some(image) = self.cache.image(&self.source) {
+            let auto = taffy::style::Dimension::auto();
+            if style.size.width == auto && style.size.height == auto {
+                style.size = taffy::Size {
+                    width: taffy::style::Dimension::length(image.width() as f32),
+                    height: taffy::style::Dimension::length(image.height() as f32),
+                };
+            }
+        }
+        cx.layout_engine().new_leaf(style)
+    }
+
+    fn paint(&mut self, bounds: Rect, cx: &mut PaintContext) {
+        let Some(image) = self.cache.image(&self.source) else {
+            return;
+        };
+        let image_size = Size::new(image.width() as f32, image.height() as f32);
+        let (dest, uv) = self.fit.place(image_size, bounds);
+
+        let scale = cx.scale_factor();
+        let mut quad = ImageQuad::new(
+            DeviceRect::new(scale.scale_point(dest.origin), scale.scale_size(dest.size)),
+            image,
+        );
+        quad.uv = uv;
+        quad.opacity = self.opacity;
+        let radii = &self.corner_radii;
+        quad.corner_radii = Corners {
+            top_left: radii.top_left * scale.0,
+            top_right: radii.top_right * scale.0,
+            bottom_right: radii.bottom_right * scale.0,
+            bottom_left: radii.bottom_left * scale.0,
+        };
+        cx.scene().push_image(quad);
+    }
+}
+
+impl IntoElement for Img {
+    type Element = Img;
+    fn into_element(self) -> Self::Element {
+        self
+    }
+}
+
+/// Create an image element.
+pub fn img(source: impl Into<ImageSource>) -> Img {
+    Img::new(source)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{HitTree, LayoutEngine, ScaleFactor, Scene, TextContext};
+    use std::time::{Duration, Instant};
+
+    fn solid(width: u32, height: u32) -> ImageData {
+        ImageData::new(width, height, vec![255u8; (width * height * 4) as usize]).unwrap()
+    }
+
+    fn paint(element: &mut Img, scale: f32) -> Scene {
+        let mut scene = Scene::new();
+        let mut text_ctx = TextContext::new();
+        let mut hit_tree = HitTree::new();
+        let mut engine = LayoutEngine::new();
+        let mut cx = LayoutContext::new(&mut engine, &mut text_ctx, ScaleFactor(scale));
+        let root = element.request_layout(&mut cx);
+        engine.compute_layout(root, 800.0, 600.0, &mut text_ctx);
+        let bounds = engine.layout_bounds(root);
+        let mut cx = PaintContext::new(
+            &mut scene,
+            &mut text_ctx,
+            &mut hit_tree,
+            &engine,
+            ScaleFactor(scale),
+        );
+        element.paint(bounds, &mut cx);
+        scene
+    }
+
+    #[test]
+    fn fit_modes_place_image() {
+        let bounds = Rect::new(Point::new(0.0, 0.0), Size::new(100.0, 50.0));
+        let image = Size::new(20.0, 20.0);
+
+        let (dest, uv) = ImageFit::Fill.place(image, bounds);
+        assert_eq!((dest, uv), (bounds, [0.0, 0.0, 1.0, 1.0]));
+
+        let (dest, uv) = ImageFit::Contain.place(image, bounds);
+        assert_eq!(
+            dest,
+            Rect::new(Point::new(25.0, 0.0), Size::new(50.0, 50.0))
+        );
+        assert_eq!(uv, [0.0, 0.0, 1.0, 1.0]);
+
+        let (dest, uv) = ImageFit::Cover.place(image, bounds);
+        assert_eq!(dest, bounds);
+        assert_eq!(uv, [0.0, 0.25, 1.0, 0.75]);
+    }
+
+    #[test]
+    fn img_paints_scaled_rounded_quad() {
+        let mut element = img(solid(10, 10))
+            .size(Size::new(40.0, 20.0))
+            .fit(ImageFit::Cover)
+            .corner_radius(4.0)
+            .cache(&ImageCache::new());
+        let scene = paint(&mut element, 2.0);
+
+        let quad = &scene.images()[0];
+        assert_eq!(quad.bounds.size.width, 80.0);
+        assert_eq!(quad.bounds.size.height, 40.0);
+        assert_eq!(quad.uv, [0.0, 0.25, 1.0, 0.75]);
+        assert_eq!(quad.corner_radii, Corners::all(8.0));
+    }
+
+    #[test]
+    fn img_defaults_to_pixel_size() {
+        let mut element = img(solid(30, 12)).cache(&ImageCache::new());
+        let scene = paint(&mut element, 1.0);
+        assert_eq!(scene.images()[0].bounds.size.width, 30.0);
+        assert_eq!(scene.images()[0].bounds.size.height, 12.0);
+    }
+
+    #[test]
+    fn cache_decodes_in_background() {
+        let mut png = Vec::new();
+        image::RgbaImage::from_pixel(3, 2, image::Rgba([1, 2, 3, 255]))
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        let path = std::env::temp_dir().join(format!("motif-img-{}.png", std::process::id()));
+        std::fs::write(&path, &png).unwrap();
+
+        let cache = ImageCache::new();
+        let source = ImageSource::from(path.clone());
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let image = loop {
+            match cache.get(&source) {
+                ImageState::Ready(image) => break image,
+                ImageState::Loading if Instant::now() < deadline => {
+                    std::thread::sleep(Duration::from_millis(5))
+                }
+                state => panic!("unexpected {state:?}"),
+            }
+        };
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!((image.width(), image.height()), (3, 2));
+        assert_eq!(&image.pixels()[..4], &[1, 2, 3, 255]);
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn cache_reports_failures() {
+        let cache = ImageCache::new();
+        let source = ImageSource::from("/nonexistent/motif.png");
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while matches!(cache.get(&source), ImageState::Loading) && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert!(matches!(cache.get(&source), ImageState::Failed(_)));
+    }
+}
@@ -3,11 +3,21 @@
 mod button;
 mod checkbox;
 mod div;
+mod divider;
+mod img;
+mod stack;
 mod text;
 mod text_input;
+mod tooltip;
+mod uniform_list;
 
 pub use button::*;
 pub use checkbox::*;
 pub use div::*;
+pub use divider::*;
+pub use img::*;
+pub use stack::*;
 pub use text::*;
 pub use text_input::*;
+pub use tooltip::*;
+pub use uniform_list::*;
@@ -0,0 +1,112 @@
+//! Vertical and horizontal stacks.
+//!
+//! Stacks are [`Div`]s preset for the common case: children placed one after
+//! another at their intrinsic size, aligned to the leading edge. Spacing,
+//! alignment and every other container option come from the usual `Div`
+//! builders.
+//!
+//! ```ignore
+//! v_stack()
+//!     .gap(8.0)
+//!     .child(text("Title").font_size(20.0))
+//!     .child(h_stack().gap(4.0).child(button_a).child(spacer()).child(button_b))
+//! ```
+
+use super::{div, Div};
+use crate::layout::AlignItems;
+
+/// A container that places children top to bottom.
+pub fn v_stack() -> Div {
+    div().flex_col().align_items(AlignItems::FlexStart)
+}
+
+/// A container that places children left to right.
+pub fn h_stack() -> Div {
+    div().flex_row().align_items(AlignItems::FlexStart)
+}
+
+/// Empty space that grows to fill what its stack has left over, pushing
+/// the children after it to the far end.
+pub fn spacer() -> Div {
+    div().flex_grow(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::element::{Element, LayoutContext, ParentElement};
+    use crate::{text, LayoutEngine, Point, Rect, ScaleFactor, Size, TextContext};
+
+    fn child_bounds(stack: &mut Div, count: usize) -> (Vec<Rect>, Rect) {
+        let mut text_ctx = TextContext::new();
+        let mut engine = LayoutEngine::new();
+        let mut cx = LayoutContext::new(&mut engine, &mut text_ctx, ScaleFactor(1.0));
+        let root = stack.request_layout(&mut cx);
+        engine.compute_layout(root, 800.0, 600.0, &mut text_ctx);
+        let children = engine.children(root);
+        assert_eq!(children.len(), count);
+        (
+            children
+                .iter()
+                .map(|&id| engine.layout_bounds(id))
+                .collect(),
+            engine.layout_bounds(root),
+        )
+    }
+
+    #[test]
+    fn v_stack_places_children_below_each_other() {
+        let mut stack = v_stack()
+            .gap(5.0)
+            .child(div().size(Size::new(40.0, 10.0)))
+            .child(div().size(Size::new(20.0, 30.0)));
+
+        let (bounds, root) = child_bounds(&mut stack, 2);
+        assert_eq!(
+            bounds[0],
+            Rect::new(Point::new(0.0, 0.0), Size::new(40.0, 10.0))
+        );
+        assert_eq!(
+            bounds[1],
+            Rect::new(Point::new(0.0, 15.0), Size::new(20.0, 30.0))
+        );
+        assert_eq!(root.size.height, 45.0);
+    }
+
+    #[test]
+    fn h_stack_uses_intrinsic_text_width() {
+        let mut stack = h_stack()
+            .gap(4.0)
+            .child(text("Hello").font_size(16.0))
+            .child(div().size(Size::new(10.0, 10.0)));
+
+        let (bounds, _) = child_bounds(&mut stack, 2);
+        assert!(bounds[0].size.width > 0.0);
+        assert_eq!(bounds[1].origin.x, bounds[0].size.width + 4.0);
+        assert_eq!(bounds[1].origin.y, 0.0);
+    }
+
+    #[test]
+    fn spacer_pushes_trailing_children_to_the_end() {
+        let mut stack = h_stack()
+            .width(200.0)
+            .child(div().size(Size::new(30.0, 10.0)))
+            .child(spacer())
+            .child(div().size(Size::new(40.0, 10.0)));
+
+        let (bounds, _) = child_bounds(&mut stack, 3);
+        assert_eq!(bounds[1].size.width, 130.0);
+        assert_eq!(bounds[2].origin.x, 160.0);
+    }
+
+    #[test]
+    fn stacks_accept_alignment() {
+        let mut stack = v_stack()
+            .width(100.0)
+            .align_items(AlignItems::Center)
+            .child(div().size(Size::new(40.0, 10.0)));
+
+        let (bounds, _) = child_bounds(&mut stack, 1);
+        assert_eq!(bounds[0].origin.x, 30.0);
+    }
+}
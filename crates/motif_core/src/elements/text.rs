@@ -2,7 +2,8 @@
 
 use crate::element::{Element, IntoElement, LayoutContext, PaintContext};
 use crate::layout::{MeasureContext, NodeId};
-use crate::{ArcStr, Rect, TextRun};
+use crate::style::Style;
+use crate::{AccessRole, ArcStr, Constraints, Rect, Size, TextRun, TextStyle};
 use palette::Srgba;
 
 /// A text element that renders a string.
@@ -16,49 +17,107 @@ use palette::Srgba;
 /// ```
 pub struct Text {
     content: ArcStr,
-    font_size: f32,
-    color: Srgba,
+    /// Unset properties are inherited from the nearest ancestor that sets
+    /// them, falling back to the defaults.
+    font_size: Option<f32>,
+    color: Option<Srgba>,
+    accessibility_label: Option<ArcStr>,
+    accessibility_hidden: bool,
 }
 
 impl Text {
+    /// Font size used when neither the text nor an ancestor sets one.
+    pub const DEFAULT_FONT_SIZE: f32 = 16.0;
+    /// Color used when neither the text nor an ancestor sets one.
+    pub const DEFAULT_COLOR: Srgba = Srgba::new(1.0, 1.0, 1.0, 1.0);
+
     pub fn new(content: impl Into<ArcStr>) -> Self {
         Self {
             content: content.into(),
-            font_size: 16.0,
-            color: Srgba::new(1.0, 1.0, 1.0, 1.0),
+            font_size: None,
+            color: None,
+            accessibility_label: None,
+            accessibility_hidden: false,
         }
     }
 
     pub fn font_size(mut self, size: f32) -> Self {
-        self.font_size = size;
+        self.font_size = Some(size);
         self
     }
 
     pub fn color(mut self, color: impl Into<Srgba>) -> Self {
-        self.color = color.into();
+        self.color = Some(color.into());
+        self
+    }
+
+    /// What screen readers announce instead of the text itself.
+    pub fn accessibility_label(mut self, label: impl Into<ArcStr>) -> Self {
+        self.accessibility_label = Some(label.into());
+        self
+    }
+
+    /// Hide the text from screen readers, e.g. for decorative glyphs.
+    pub fn accessibility_hidden(mut self, hidden: bool) -> Self {
+        self.accessibility_hidden = hidden;
+        self
+    }
+
+    /// Apply the text properties `style` sets.
+    pub fn style(mut self, style: Style) -> Self {
+        if let Some(color) = style.text_color {
+            self.color = Some(color);
+        }
+        if let Some(size) = style.font_size {
+            self.font_size = Some(size);
+        }
         self
     }
+
+    fn resolved_font_size(&self, inherited: &Style) -> f32 {
+        self.font_size
+            .or(inherited.font_size)
+            .unwrap_or(Self::DEFAULT_FONT_SIZE)
+    }
 }
 
 impl Element for Text {
     fn request_layout(&mut self, cx: &mut LayoutContext) -> NodeId {
         // Text uses MeasureContext to size itself based on content
+        let font_size = self.resolved_font_size(cx.text_style());
         cx.layout_engine().new_leaf_with_context(
             crate::layout::Style::default(),
             MeasureContext::Text {
                 content: self.content.to_string(),
-                font_size: self.font_size,
+                font_size,
             },
         )
     }
 
+    fn measure(&mut self, constraints: Constraints, cx: &mut LayoutContext) -> Size {
+        let font_size = self.resolved_font_size(cx.text_style());
+        let metrics = cx
+            .text_ctx
+            .measure(&self.content, &TextStyle::new(font_size));
+        constraints.constrain(Size::new(metrics.width, metrics.height))
+    }
+
     fn paint(&mut self, bounds: Rect, cx: &mut PaintContext) {
         if self.content.is_empty() {
             return;
         }
+        if !self.accessibility_hidden {
+            let name = self.accessibility_label.as_ref().unwrap_or(&self.content);
+            cx.push_access_node(None, AccessRole::Label, name.as_str(), bounds);
+        }
 
         let scale = cx.scale_factor();
-        let scaled_font_size = self.font_size * scale.0;
+        let font_size = self.resolved_font_size(cx.text_style());
+        let color = self
+            .color
+            .or(cx.text_style().text_color)
+            .unwrap_or(Self::DEFAULT_COLOR);
+        let scaled_font_size = font_size * scale.0;
         let layout = cx.text_ctx().layout_text(&self.content, scaled_font_size);
 
         // Use bounds from layout, not manually specified position
@@ -73,14 +132,15 @@ impl Element for Text {
 
         for run in layout.glyph_runs_with_font() {
             if let Some(font) = run.font_data {
-                let mut text_run = TextRun::new(device_origin, self.color, run.font_size, font);
+                let mut text_run = TextRun::new(device_origin, color, run.font_size, font)
+                    .with_line_metrics(run.ascent, run.descent);
                 text_run.normalized_coords = run.normalized_coords;
 
-                for glyph in run.glyphs {
-                    text_run.push_glyph(glyph.id, glyph.x, glyph.y);
+                let scene = cx.scene();
+                scene.push_text_run(text_run);
+                for glyph in &run.glyphs {
+                    scene.push_positioned_glyph(glyph);
                 }
-
-                cx.scene().push_text_run(text_run);
             }
         }
     }
@@ -128,7 +188,8 @@ mod tests {
     fn text_builder_defaults() {
         let t = text("hello");
         assert_eq!(t.content, "hello");
-        assert_eq!(t.font_size, 16.0);
+        assert_eq!(t.font_size, None);
+        assert_eq!(t.resolved_font_size(&Style::default()), 16.0);
     }
 
     #[test]
@@ -137,7 +198,7 @@ mod tests {
             .font_size(24.0)
             .color(Srgba::new(1.0, 0.0, 0.0, 1.0));
 
-        assert_eq!(t.font_size, 24.0);
+        assert_eq!(t.font_size, Some(24.0));
     }
 
     #[test]
@@ -151,4 +212,36 @@ mod tests {
         let t: Text = String::from("hello").into_element();
         assert_eq!(t.content, "hello");
     }
+
+    #[test]
+    fn measure_matches_layout_in_a_container() {
+        use crate::element::ParentElement;
+        use crate::{div, LayoutEngine, ScaleFactor, TextContext};
+
+        let mut text_ctx = TextContext::new();
+        let mut engine = LayoutEngine::new();
+        let mut cx = LayoutContext::new(&mut engine, &mut text_ctx, ScaleFactor(1.0));
+
+        let own = text("Measure me")
+            .font_size(20.0)
+            .measure(Constraints::unbounded(), &mut cx);
+        assert!(own.width > 0.0 && own.height > 0.0);
+
+        let mut container = div().padding(5.0).child(text("Measure me").font_size(20.0));
+        let padded = container.measure(Constraints::unbounded(), &mut cx);
+        assert_eq!(padded.width, own.width.round() + 10.0);
+
+        let capped = text("Measure me")
+            .font_size(20.0)
+            .measure(Constraints::loose(Size::new(30.0, 100.0)), &mut cx);
+        assert_eq!(capped.width, 30.0);
+    }
+
+    #[test]
+    fn style_sets_text_properties() {
+        let theme = crate::Theme::light();
+        let t = text("hello").style(theme.muted_text());
+        assert_eq!(t.color, Some(theme.text_muted));
+        assert_eq!(t.font_size, Some(theme.font_size));
+    }
 }
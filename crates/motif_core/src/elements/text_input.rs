@@ -12,8 +12,9 @@
 
 use crate::{
     element::{Element, IntoElement, LayoutContext, PaintContext},
+    input::{CursorIcon, InputAction},
     layout::NodeId,
-    ArcStr, ElementId, Point, Rect, Size, Srgba, TextRun,
+    AccessRole, ArcStr, ElementId, Point, Rect, Size, Srgba, TextRun,
 };
 
 /// Single-line text input element.
@@ -36,6 +37,9 @@ pub struct TextInput {
     padding: f32,
     corner_radius: f32,
     border_width: f32,
+    cursor: CursorIcon,
+    tab_index: Option<i32>,
+    on_input_action: Option<Box<dyn FnMut(InputAction)>>,
     // State (set externally before paint)
     is_focused: bool,
     /// Byte offset into `value` at which to draw the cursor.
@@ -61,12 +65,38 @@ impl TextInput {
             padding: 8.0,
             corner_radius: 4.0,
             border_width: 1.5,
+            cursor: CursorIcon::Text,
+            tab_index: None,
+            on_input_action: None,
             is_focused: false,
             cursor_pos: 0,
             selection: 0..0,
         }
     }
 
+    /// Set the mouse cursor shown over the input. Defaults to
+    /// [`CursorIcon::Text`].
+    pub fn cursor(mut self, cursor: CursorIcon) -> Self {
+        self.cursor = cursor;
+        self
+    }
+
+    /// Set this element's place in the tab order: positive values come
+    /// first, in ascending order, and negative ones are skipped. Without it,
+    /// inputs are tabbed to in reading order.
+    pub fn tab_index(mut self, index: i32) -> Self {
+        self.tab_index = Some(index);
+        self
+    }
+
+    /// Handle editing actions sent to the input while it's focused, such as
+    /// the Edit menu's Copy and Paste. Typically passes them to
+    /// [`TextEditState::apply_action`](crate::TextEditState::apply_action).
+    pub fn on_input_action(mut self, handler: impl FnMut(InputAction) + 'static) -> Self {
+        self.on_input_action = Some(Box::new(handler));
+        self
+    }
+
     /// Set the placeholder text shown when the value is empty.
     pub fn placeholder(mut self, placeholder: impl Into<ArcStr>) -> Self {
         self.placeholder = placeholder.into();
@@ -216,16 +246,10 @@ impl Element for TextInput {
             self.border_color
         };
 
-        let mut quad = crate::Quad::new(
-            crate::DeviceRect::new(
-                crate::DevicePoint::new(bounds.origin.x * scale, bounds.origin.y * scale),
-                crate::DeviceSize::new(bounds.size.width * scale, bounds.size.height * scale),
-            ),
-            self.background,
-        );
+        let mut quad = crate::Quad::new(cx.scale_factor().snap_rect(bounds), self.background);
         quad.corner_radii = crate::Corners::all(self.corner_radius * scale);
         quad.border_color = active_border;
-        quad.border_widths = crate::Edges::all(self.border_width * scale);
+        quad.border_widths = crate::Edges::all(cx.scale_factor().snap_length(self.border_width));
         cx.scene().push_quad(quad);
 
         // 2. Selection highlight (painted before text so it appears behind)
@@ -286,14 +310,15 @@ impl Element for TextInput {
 
             for run in layout.glyph_runs_with_font() {
                 if let Some(font) = run.font_data {
-                    let mut text_run = TextRun::new(device_origin, text_color, run.font_size, font);
+                    let mut text_run = TextRun::new(device_origin, text_color, run.font_size, font)
+                        .with_line_metrics(run.ascent, run.descent);
                     text_run.normalized_coords = run.normalized_coords;
 
-                    for glyph in run.glyphs {
-                        text_run.push_glyph(glyph.id, glyph.x, glyph.y);
+                    let scene = cx.scene();
+                    scene.push_text_run(text_run);
+                    for glyph in &run.glyphs {
+                        scene.push_positioned_glyph(glyph);
                     }
-
-                    cx.scene().push_text_run(text_run);
                 }
             }
         }
@@ -332,8 +357,24 @@ impl Element for TextInput {
             cx.scene().push_quad(cursor_quad);
         }
 
-        // 5. Hit-test registration
+        // 5. Accessibility and hit-test registration
+        if let Some(node) = cx.push_access_node(
+            Some(self.id),
+            AccessRole::TextInput,
+            self.placeholder.as_str(),
+            bounds,
+        ) {
+            node.value = Some(self.value.to_string());
+            node.tab_index = self.tab_index;
+        }
         cx.register_hit(self.id, bounds);
+        cx.set_cursor(self.id, self.cursor);
+        if let Some(mut handler) = self.on_input_action.take() {
+            cx.on_input_action(self.id, move |action, cx| {
+                handler(*action);
+                cx.stop_propagation();
+            });
+        }
     }
 }
 
@@ -351,7 +392,7 @@ impl IntoElement for TextInput {
 ///
 /// Takes `&str` directly since input values are typically dynamic content.
 pub fn text_input(value: &str, id: ElementId) -> TextInput {
-    TextInput::new(ArcStr::new(value), id)
+    TextInput::new(ArcStr::copy_from(value), id)
 }
 
 #[cfg(test)]
@@ -0,0 +1,85 @@
+//! Tooltips and anchored overlay placement.
+//!
+//! A div with an [`id`](super::Div::id) and a [`tooltip`](super::Div::tooltip)
+//! paints the tooltip into the scene's overlay while it's hovered, so it
+//! covers everything else in the window:
+//!
+//! ```ignore
+//! div().id(ElementId(7)).child(icon).tooltip("Delete file")
+//! ```
+//!
+//! Hover comes from [`CallbackRegistry::dispatch_hover`](crate::CallbackRegistry::dispatch_hover),
+//! so the paint context needs the app's registry.
+
+use super::{div, text, Div};
+use crate::element::ParentElement;
+use crate::{ArcStr, Edges, Point, Rect, Size, Srgba};
+
+/// Space between an anchor and the overlay placed next to it.
+pub const OVERLAY_GAP: f32 = 4.0;
+
+/// Where to put an overlay of `size` next to `anchor`.
+///
+/// Prefers just below the anchor, left-aligned with it. Flips above the
+/// anchor if it would run off the bottom of `viewport` (when there's room
+/// above), and shifts left to stay inside the viewport's right edge.
+pub fn anchored_origin(anchor: Rect, size: Size, viewport: Option<Size>) -> Point {
+    let below = anchor.origin.y + anchor.size.height + OVERLAY_GAP;
+    let Some(viewport) = viewport else {
+        return Point::new(anchor.origin.x, below);
+    };
+
+    let above = anchor.origin.y - OVERLAY_GAP - size.height;
+    let y = if below + size.height > viewport.height && above >= 0.0 {
+        above
+    } else {
+        below
+    };
+    let x = anchor.origin.x.min(viewport.width - size.width).max(0.0);
+    Point::new(x, y)
+}
+
+/// The default tooltip look: a small dark label.
+pub fn tooltip(label: impl Into<ArcStr>) -> Div {
+    div()
+        .padding(Edges::symmetric(4.0, 8.0))
+        .background(Srgba::new(0.1, 0.1, 0.12, 0.95))
+        .border_color(Srgba::new(0.3, 0.3, 0.35, 1.0))
+        .border_width(1.0)
+        .corner_radius(4.0)
+        .child(
+            text(label)
+                .font_size(12.0)
+                .color(Srgba::new(0.92, 0.92, 0.95, 1.0)),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn anchor() -> Rect {
+        Rect::new(Point::new(50.0, 50.0), Size::new(40.0, 20.0))
+    }
+
+    #[test]
+    fn overlay_goes_below_the_anchor() {
+        let origin = anchored_origin(
+            anchor(),
+            Size::new(60.0, 20.0),
+            Some(Size::new(400.0, 400.0)),
+        );
+        assert_eq!(origin, Point::new(50.0, 74.0));
+    }
+
+    #[test]
+    fn overlay_flips_and_shifts_to_stay_on_screen() {
+        let viewport = Some(Size::new(100.0, 90.0));
+        let origin = anchored_origin(anchor(), Size::new(60.0, 20.0), viewport);
+        assert_eq!(origin, Point::new(40.0, 26.0));
+
+        // No room above either: stay below.
+        let tall = anchored_origin(anchor(), Size::new(60.0, 60.0), viewport);
+        assert_eq!(tall.y, 74.0);
+    }
+}
@@ -0,0 +1,274 @@
+//! Virtualized list of equal-height rows.
+//!
+//! Only rows intersecting the list's bounds are built, laid out and painted,
+//! so a list of 10k rows costs about as much as the handful on screen.
+//!
+//! ```ignore
+//! uniform_list(items.len(), move |ix| text(items[ix].clone()))
+//!     .size(Size::new(300.0, 400.0))
+//!     .row_height(24.0)
+//!     .scroll_offset(scroll_y)
+//! ```
+//!
+//! Without [`row_height`](UniformList::row_height) the first row is measured
+//! and its height reused for every row. Scrolling is driven by the app: feed
+//! scroll events into an offset and clamp it to
+//! [`max_scroll_offset`](UniformList::max_scroll_offset).
+
+use std::ops::Range;
+
+use crate::element::{AnyElement, Element, IntoElement, LayoutContext, PaintContext};
+use crate::layout::{self, NodeId};
+use crate::{Constraints, LayoutEngine, Point, Rect, Size};
+
+type RenderRow = Box<dyn Fn(usize) -> AnyElement>;
+
+/// A vertically scrolling list that renders only its visible rows.
+pub struct UniformList {
+    count: usize,
+    render_row: RenderRow,
+    style: layout::Style,
+    row_height: Option<f32>,
+    scroll_offset: f32,
+}
+
+impl UniformList {
+    pub fn new<E: IntoElement>(count: usize, render_row: impl Fn(usize) -> E + 'static) -> Self {
+        Self {
+            count,
+            render_row: Box::new(move |ix| AnyElement::new(render_row(ix).into_element())),
+            style: layout::Style::default(),
+            row_height: None,
+            scroll_offset: 0.0,
+        }
+    }
+
+    /// Set fixed size in logical pixels.
+    pub fn size(mut self, size: Size) -> Self {
+        self.style.size = taffy::Size {
+            width: taffy::style::Dimension::length(size.width),
+            height: taffy::style::Dimension::length(size.height),
+        };
+        self
+    }
+
+    /// Set width in logical pixels.
+    pub fn width(mut self, width: f32) -> Self {
+        self.style.size.width = taffy::style::Dimension::length(width);
+        self
+    }
+
+    /// Set height in logical pixels.
+    pub fn height(mut self, height: f32) -> Self {
+        self.style.size.height = taffy::style::Dimension::length(height);
+        self
+    }
+
+    /// Set flex grow factor, to fill the remaining space in a container.
+    pub fn flex_grow(mut self, grow: f32) -> Self {
+        self.style.flex_grow = grow;
+        self
+    }
+
+    /// Use a known row height instead of measuring the first row.
+    pub fn row_height(mut self, height: f32) -> Self {
+        self.row_height = Some(height);
+        self
+    }
+
+    /// How far the list is scrolled, in logical pixels from the top.
+    pub fn scroll_offset(mut self, offset: f32) -> Self {
+        self.scroll_offset = offset.max(0.0);
+        self
+    }
+
+    /// The furthest the list can scroll when `viewport_height` tall.
+    pub fn max_scroll_offset(count: usize, row_height: f32, viewport_height: f32) -> f32 {
+        (count as f32 * row_height - viewport_height).max(0.0)
+    }
+
+    /// Lay out `row` in `engine` at `width`, returning its root node.
+    fn layout_row(
+        row: &mut AnyElement,
+        engine: &mut LayoutEngine,
+        cx: &mut PaintContext,
+        width: f32,
+        height: f32,
+    ) -> NodeId {
+        engine.clear();
+        let mut layout_cx = LayoutContext::new(engine, cx.text_ctx, cx.scale_factor);
+        layout_cx.text_style = cx.text_style.clone();
+        let node_id = row.request_layout(&mut layout_cx);
+        engine.compute_layout(node_id, width, height, cx.text_ctx);
+        node_id
+    }
+}
+
+/// Indices of the rows that intersect a viewport `viewport_height` tall,
+/// scrolled `scroll_offset` from the top.
+pub fn visible_rows(
+    count: usize,
+    row_height: f32,
+    scroll_offset: f32,
+    viewport_height: f32,
+) -> Range<usize> {
+    if count == 0 || row_height <= 0.0 || viewport_height <= 0.0 {
+        return 0..0;
+    }
+    let first = (scroll_offset.max(0.0) / row_height).floor() as usize;
+    let last = ((scroll_offset.max(0.0) + viewport_height) / row_height).ceil() as usize;
+    first.min(count)..last.min(count)
+}
+
+impl Element for UniformList {
+    fn request_layout(&mut self, cx: &mut LayoutContext) -> NodeId {
+        // Rows are laid out separately at paint time, once the viewport is
+        // known, so the list itself is a plain leaf.
+        cx.layout_engine().new_leaf(self.style.clone())
+    }
+
+    fn paint(&mut self, bounds: Rect, cx: &mut PaintContext) {
+        if self.count == 0 {
+            return;
+        }
+
+        let mut engine = LayoutEngine::new();
+        let width = bounds.size.width;
+        let row_height = match self.row_height {
+            Some(height) => height,
+            None => {
+                let mut first = (self.render_row)(0);
+                let mut measure_cx = LayoutContext::new(&mut engine, cx.text_ctx, cx.scale_factor);
+                measure_cx.text_style = cx.text_style.clone();
+                let constraints = Constraints::loose(Size::new(width, f32::INFINITY));
+                first.measure(constraints, &mut measure_cx).height
+            }
+        };
+
+        let rows = visible_rows(
+            self.count,
+            row_height,
+            self.scroll_offset,
+            bounds.size.height,
+        );
+        for ix in rows {
+            let mut row = (self.render_row)(ix);
+            Self::layout_row(&mut row, &mut engine, cx, width, row_height);
+
+            let origin = Point::new(
+                bounds.origin.x,
+                bounds.origin.y + ix as f32 * row_height - self.scroll_offset,
+            );
+            let mut row_cx = PaintContext {
+                scene: &mut *cx.scene,
+                text_ctx: &mut *cx.text_ctx,
+                hit_tree: &mut *cx.hit_tree,
+                layout_engine: &engine,
+                scale_factor: cx.scale_factor,
+                offset: origin,
+                callbacks: cx.callbacks.as_deref_mut(),
+                text_style: cx.text_style.clone(),
+                viewport_size: cx.viewport_size,
+                access_tree: cx.access_tree.as_deref_mut(),
+            };
+            row.paint_at(Rect::new(origin, Size::new(width, row_height)), &mut row_cx);
+        }
+    }
+}
+
+impl IntoElement for UniformList {
+    type Element = UniformList;
+    fn into_element(self) -> Self::Element {
+        self
+    }
+}
+
+/// Create a virtualized list of `count` rows built by `render_row`.
+pub fn uniform_list<E: IntoElement>(
+    count: usize,
+    render_row: impl Fn(usize) -> E + 'static,
+) -> UniformList {
+    UniformList::new(count, render_row)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::element::ParentElement;
+    use crate::{div, text, HitTree, ScaleFactor, Scene, Srgba, TextContext};
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    fn paint(list: &mut UniformList) -> Scene {
+        let mut scene = Scene::new();
+        let mut text_ctx = TextContext::new();
+        let mut hit_tree = HitTree::new();
+        let mut engine = LayoutEngine::new();
+        let mut cx = LayoutContext::new(&mut engine, &mut text_ctx, ScaleFactor(1.0));
+        let root = list.request_layout(&mut cx);
+        engine.compute_layout(root, 800.0, 600.0, &mut text_ctx);
+        let bounds = engine.layout_bounds(root);
+        let mut cx = PaintContext::new(
+            &mut scene,
+            &mut text_ctx,
+            &mut hit_tree,
+            &engine,
+            ScaleFactor(1.0),
+        );
+        list.paint(bounds, &mut cx);
+        scene
+    }
+
+    fn row(_: usize) -> crate::Div {
+        div()
+            .height(20.0)
+            .background(Srgba::new(1.0, 1.0, 1.0, 1.0))
+    }
+
+    #[test]
+    fn visible_rows_cover_partial_rows() {
+        assert_eq!(visible_rows(100, 20.0, 0.0, 100.0), 0..5);
+        assert_eq!(visible_rows(100, 20.0, 10.0, 100.0), 0..6);
+        assert_eq!(visible_rows(100, 20.0, 1990.0, 100.0), 99..100);
+        assert_eq!(visible_rows(0, 20.0, 0.0, 100.0), 0..0);
+        assert_eq!(UniformList::max_scroll_offset(100, 20.0, 100.0), 1900.0);
+    }
+
+    #[test]
+    fn only_visible_rows_are_built() {
+        let built = Rc::new(Cell::new(0));
+        let counter = built.clone();
+        let mut list = uniform_list(10_000, move |ix| {
+            counter.set(counter.get() + 1);
+            row(ix)
+        })
+        .size(Size::new(200.0, 100.0))
+        .row_height(20.0)
+        .scroll_offset(500.0);
+
+        let scene = paint(&mut list);
+        assert_eq!(built.get(), 5);
+        assert_eq!(scene.quad_count(), 5);
+        assert_eq!(scene.quads()[0].bounds.origin.y, 0.0);
+        assert_eq!(scene.quads()[0].bounds.size.width, 200.0);
+    }
+
+    #[test]
+    fn row_height_is_measured_from_first_row() {
+        let mut list = uniform_list(50, row).size(Size::new(200.0, 50.0));
+        let scene = paint(&mut list);
+
+        // 20px rows: three intersect a 50px viewport.
+        assert_eq!(scene.quad_count(), 3);
+        assert_eq!(scene.quads()[2].bounds.origin.y, 40.0);
+    }
+
+    #[test]
+    fn rows_lay_out_their_children() {
+        let mut list = uniform_list(3, |ix| div().child(text(format!("Row {ix}"))))
+            .size(Size::new(200.0, 100.0));
+        let scene = paint(&mut list);
+        assert_eq!(scene.text_run_count(), 3);
+        assert!(scene.text_runs()[1].origin.y > scene.text_runs()[0].origin.y);
+    }
+}
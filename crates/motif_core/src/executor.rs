@@ -0,0 +1,285 @@
+//! Running futures off the UI thread.
+//!
+//! ```ignore
+//! let readme = cx.new_view(|_| Readme { text: None });
+//! let handle = readme.clone();
+//! cx.spawn(
+//!     async move { std::fs::read_to_string("README.md") },
+//!     move |text| handle.update(|readme| readme.text = text.ok()),
+//! );
+//! ```
+//!
+//! Futures run on the worker threads of [`Executor::global`]. Their results
+//! are handed back to the window's [`Tasks`], which runs each completion
+//! callback on the UI thread, where it can update views. The app gives
+//! [`Tasks`] a [waker](Tasks::set_waker) so a finished future wakes the
+//! event loop instead of waiting for the next input event.
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::task::{Context, Wake, Waker};
+
+type BoxFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// A spawned future, re-queued on its executor whenever it's woken.
+struct Job {
+    future: Mutex<Option<BoxFuture>>,
+    queue: Sender<Arc<Job>>,
+}
+
+impl Job {
+    fn poll(self: Arc<Self>) {
+        let mut slot = self.future.lock().unwrap();
+        // Finished, or woken again after it finished.
+        let Some(mut future) = slot.take() else {
+            return;
+        };
+        let waker = Waker::from(self.clone());
+        if future
+            .as_mut()
+            .poll(&mut Context::from_waker(&waker))
+            .is_pending()
+        {
+            *slot = Some(future);
+        }
+    }
+}
+
+impl Wake for Job {
+    fn wake(self: Arc<Self>) {
+        let queue = self.queue.clone();
+        // Only fails once the executor is gone, and then nothing can run it.
+        let _ = queue.send(self);
+    }
+}
+
+/// A pool of worker threads polling futures.
+pub struct Executor {
+    queue: Sender<Arc<Job>>,
+}
+
+impl Executor {
+    /// Start `threads` workers (at least one).
+    pub fn new(threads: usize) -> Self {
+        let (queue, jobs) = mpsc::channel::<Arc<Job>>();
+        let jobs = Arc::new(Mutex::new(jobs));
+        for index in 0..threads.max(1) {
+            let jobs = Arc::clone(&jobs);
+            std::thread::Builder::new()
+                .name(format!("motif-executor-{index}"))
+                .spawn(move || loop {
+                    let job = jobs.lock().unwrap().recv();
+                    match job {
+                        Ok(job) => job.poll(),
+                        Err(_) => return,
+                    }
+                })
+                .expect("failed to start executor thread");
+        }
+        Self { queue }
+    }
+
+    /// The process-wide executor [`Tasks`] spawns on.
+    pub fn global() -> &'static Executor {
+        static GLOBAL: OnceLock<Executor> = OnceLock::new();
+        GLOBAL.get_or_init(|| {
+            let threads = std::thread::available_parallelism().map_or(2, |n| n.get().min(4));
+            Executor::new(threads)
+        })
+    }
+
+    /// Run `future` to completion on a worker thread.
+    pub fn spawn(&self, future: impl Future<Output = ()> + Send + 'static) {
+        let job = Arc::new(Job {
+            future: Mutex::new(Some(Box::pin(future))),
+            queue: self.queue.clone(),
+        });
+        let _ = self.queue.send(job);
+    }
+}
+
+impl fmt::Debug for Executor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Executor").finish_non_exhaustive()
+    }
+}
+
+/// Identifies a task spawned with [`Tasks::spawn`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TaskId(u64);
+
+type Output = Box<dyn Any + Send>;
+type Completion = Box<dyn FnOnce(Output)>;
+type WakeFn = Arc<dyn Fn() + Send + Sync>;
+
+/// Results sent back from worker threads.
+#[derive(Default)]
+struct Finished {
+    results: Mutex<Vec<(TaskId, Output)>>,
+    wake: Mutex<Option<WakeFn>>,
+}
+
+#[derive(Default)]
+struct TaskQueue {
+    next_id: u64,
+    completions: HashMap<TaskId, Completion>,
+}
+
+/// A window's background tasks and the callbacks waiting on them.
+///
+/// Clones share the same tasks, so handlers can keep one to spawn work
+/// later.
+#[derive(Clone, Default)]
+pub struct Tasks {
+    queue: Rc<RefCell<TaskQueue>>,
+    finished: Arc<Finished>,
+}
+
+impl Tasks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Call `wake` from the worker thread whenever a task finishes,
+    /// typically to wake the event loop so it calls
+    /// [`run_completed`](Self::run_completed).
+    pub fn set_waker(&self, wake: impl Fn() + Send + Sync + 'static) {
+        *self.finished.wake.lock().unwrap() = Some(Arc::new(wake));
+    }
+
+    /// Run `future` on the [global executor](Executor::global), then call
+    /// `on_done` with its output on the UI thread.
+    pub fn spawn<T: Send + 'static>(
+        &self,
+        future: impl Future<Output = T> + Send + 'static,
+        on_done: impl FnOnce(T) + 'static,
+    ) -> TaskId {
+        let id = {
+            let mut queue = self.queue.borrow_mut();
+            let id = TaskId(queue.next_id);
+            queue.next_id += 1;
+            queue.completions.insert(
+                id,
+                Box::new(move |output: Output| {
+                    on_done(*output.downcast::<T>().expect("task output type"))
+                }),
+            );
+            id
+        };
+        let finished = Arc::clone(&self.finished);
+        Executor::global().spawn(async move {
+            let output = future.await;
+            finished
+                .results
+                .lock()
+                .unwrap()
+                .push((id, Box::new(output)));
+            let wake = finished.wake.lock().unwrap().clone();
+            if let Some(wake) = wake {
+                wake();
+            }
+        });
+        id
+    }
+
+    /// Tasks whose completion callbacks haven't run yet.
+    pub fn pending(&self) -> usize {
+        self.queue.borrow().completions.len()
+    }
+
+    /// Call the completion callbacks of tasks that finished since the last
+    /// call. Returns whether any ran.
+    pub fn run_completed(&self) -> bool {
+        let results = std::mem::take(&mut *self.finished.results.lock().unwrap());
+        let ran = !results.is_empty();
+        for (id, output) in results {
+            let completion = self.queue.borrow_mut().completions.remove(&id);
+            if let Some(completion) = completion {
+                completion(output);
+            }
+        }
+        ran
+    }
+}
+
+impl fmt::Debug for Tasks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Tasks")
+            .field("pending", &self.pending())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::{Duration, Instant};
+
+    /// Run completions until none are pending, or fail after a while.
+    fn wait_for(tasks: &Tasks) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while tasks.pending() > 0 {
+            assert!(Instant::now() < deadline, "tasks didn't finish");
+            tasks.run_completed();
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    /// Pending once, waking itself, so the executor has to poll it again.
+    struct YieldOnce(bool);
+
+    impl Future for YieldOnce {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> std::task::Poll<()> {
+            if self.0 {
+                return std::task::Poll::Ready(());
+            }
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            std::task::Poll::Pending
+        }
+    }
+
+    #[test]
+    fn results_come_back_on_the_ui_thread() {
+        let tasks = Tasks::new();
+        let wakes = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&wakes);
+        tasks.set_waker(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+
+        let total = Rc::new(Cell::new(0));
+        for n in 1..=3 {
+            let total = total.clone();
+            tasks.spawn(
+                async move {
+                    YieldOnce(false).await;
+                    n * 10
+                },
+                move |value| total.set(total.get() + value),
+            );
+        }
+        assert_eq!(tasks.pending(), 3);
+
+        wait_for(&tasks);
+        assert_eq!(total.get(), 60);
+        // The waker runs just after each result is queued.
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while wakes.load(Ordering::SeqCst) < 3 {
+            assert!(Instant::now() < deadline, "waker wasn't called");
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert!(!tasks.run_completed());
+    }
+}
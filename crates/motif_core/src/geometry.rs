@@ -56,6 +56,195 @@ impl ScaleFactor {
     pub fn unscale_rect(&self, r: DeviceRect) -> Rect {
         Rect::new(self.unscale_point(r.origin), self.unscale_size(r.size))
     }
+
+    /// Scale a logical point and round it to the nearest device pixel.
+    pub fn snap_point(&self, p: Point) -> DevicePoint {
+        let p = self.scale_point(p);
+        DevicePoint::new(p.x.round(), p.y.round())
+    }
+
+    /// Scale a logical rect and round each edge to the device pixel grid.
+    ///
+    /// Edges are rounded rather than the origin and size, so rects that
+    /// share an edge still do after snapping.
+    pub fn snap_rect(&self, r: Rect) -> DeviceRect {
+        let min = self.snap_point(r.origin);
+        let max = self.snap_point(Point::new(
+            r.origin.x + r.size.width,
+            r.origin.y + r.size.height,
+        ));
+        DeviceRect::new(min, DeviceSize::new(max.x - min.x, max.y - min.y))
+    }
+
+    /// Scale a logical length to whole device pixels. Positive lengths are
+    /// at least one pixel, so hairlines never vanish.
+    pub fn snap_length(&self, length: f32) -> f32 {
+        if length > 0.0 {
+            (length * self.0).round().max(1.0)
+        } else {
+            0.0
+        }
+    }
+
+    /// The logical-to-device scale as a transform.
+    pub fn to_affine(&self) -> Affine {
+        Affine::scale(self.0, self.0)
+    }
+}
+
+/// A 2D affine transform, in CSS `matrix(a, b, c, d, e, f)` order:
+///
+/// ```text
+/// x' = a * x + c * y + e
+/// y' = b * x + d * y + f
+/// ```
+///
+/// Works in any coordinate space; points and rects keep their units.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Affine {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub e: f32,
+    pub f: f32,
+}
+
+impl Default for Affine {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Affine {
+    pub const IDENTITY: Self = Self::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0);
+
+    pub const fn new(a: f32, b: f32, c: f32, d: f32, e: f32, f: f32) -> Self {
+        Self { a, b, c, d, e, f }
+    }
+
+    pub const fn translate(x: f32, y: f32) -> Self {
+        Self::new(1.0, 0.0, 0.0, 1.0, x, y)
+    }
+
+    pub const fn scale(x: f32, y: f32) -> Self {
+        Self::new(x, 0.0, 0.0, y, 0.0, 0.0)
+    }
+
+    /// Rotate by `radians`, clockwise on screen (y points down).
+    pub fn rotate(radians: f32) -> Self {
+        let (sin, cos) = radians.sin_cos();
+        Self::new(cos, sin, -sin, cos, 0.0, 0.0)
+    }
+
+    /// Skew by `x` radians along the x axis and `y` along the y axis, like
+    /// CSS `skew(x, y)`.
+    pub fn skew(x: f32, y: f32) -> Self {
+        Self::new(1.0, y.tan(), x.tan(), 1.0, 0.0, 0.0)
+    }
+
+    /// Apply `self`, then `next`.
+    pub fn then(&self, next: &Affine) -> Self {
+        Self::new(
+            next.a * self.a + next.c * self.b,
+            next.b * self.a + next.d * self.b,
+            next.a * self.c + next.c * self.d,
+            next.b * self.c + next.d * self.d,
+            next.a * self.e + next.c * self.f + next.e,
+            next.b * self.e + next.d * self.f + next.f,
+        )
+    }
+
+    pub fn then_translate(&self, x: f32, y: f32) -> Self {
+        self.then(&Self::translate(x, y))
+    }
+
+    pub fn then_scale(&self, x: f32, y: f32) -> Self {
+        self.then(&Self::scale(x, y))
+    }
+
+    pub fn then_rotate(&self, radians: f32) -> Self {
+        self.then(&Self::rotate(radians))
+    }
+
+    pub fn determinant(&self) -> f32 {
+        self.a * self.d - self.b * self.c
+    }
+
+    /// The transform that undoes this one, or `None` if it collapses the
+    /// plane onto a line or point.
+    pub fn invert(&self) -> Option<Self> {
+        let det = self.determinant();
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+        let inv = 1.0 / det;
+        Some(Self::new(
+            self.d * inv,
+            -self.b * inv,
+            -self.c * inv,
+            self.a * inv,
+            (self.c * self.f - self.d * self.e) * inv,
+            (self.b * self.e - self.a * self.f) * inv,
+        ))
+    }
+
+    pub fn is_identity(&self) -> bool {
+        *self == Self::IDENTITY
+    }
+
+    /// Whether the transform only moves things, so rects stay rects of the
+    /// same size.
+    pub fn is_translation(&self) -> bool {
+        self.a == 1.0 && self.b == 0.0 && self.c == 0.0 && self.d == 1.0
+    }
+
+    pub fn translation(&self) -> (f32, f32) {
+        (self.e, self.f)
+    }
+
+    pub fn transform_point<U: Unit<Scalar = f32>>(
+        &self,
+        p: glamour::Point2<U>,
+    ) -> glamour::Point2<U> {
+        glamour::Point2::new(
+            self.a * p.x + self.c * p.y + self.e,
+            self.b * p.x + self.d * p.y + self.f,
+        )
+    }
+
+    /// Transform a vector, ignoring the translation.
+    pub fn transform_vector<U: Unit<Scalar = f32>>(
+        &self,
+        v: glamour::Vector2<U>,
+    ) -> glamour::Vector2<U> {
+        glamour::Vector2::new(self.a * v.x + self.c * v.y, self.b * v.x + self.d * v.y)
+    }
+
+    /// The axis-aligned bounds of the transformed rect.
+    pub fn transform_rect<U: Unit<Scalar = f32>>(&self, r: glamour::Rect<U>) -> glamour::Rect<U> {
+        let (x0, y0) = (r.origin.x, r.origin.y);
+        let (x1, y1) = (x0 + r.size.width, y0 + r.size.height);
+        let corners = [(x0, y0), (x1, y0), (x0, y1), (x1, y1)]
+            .map(|(x, y)| self.transform_point(glamour::Point2::<U>::new(x, y)));
+        let (mut min_x, mut min_y) = (f32::INFINITY, f32::INFINITY);
+        let (mut max_x, mut max_y) = (f32::NEG_INFINITY, f32::NEG_INFINITY);
+        for p in corners {
+            min_x = min_x.min(p.x);
+            min_y = min_y.min(p.y);
+            max_x = max_x.max(p.x);
+            max_y = max_y.max(p.y);
+        }
+        glamour::Rect::new(
+            glamour::Point2::new(min_x, min_y),
+            glamour::Size2::new(max_x - min_x, max_y - min_y),
+        )
+    }
+
+    /// The six coefficients in `a..f` order, as a renderer uploads them.
+    pub fn to_array(&self) -> [f32; 6] {
+        [self.a, self.b, self.c, self.d, self.e, self.f]
+    }
 }
 
 /// Edge values for padding, margin, border widths.
@@ -98,6 +287,12 @@ impl<T: Copy + std::ops::Add<Output = T>> Edges<T> {
     }
 }
 
+impl<T: Copy> From<T> for Edges<T> {
+    fn from(value: T) -> Self {
+        Self::all(value)
+    }
+}
+
 /// Corner values for border radii.
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct Corners<T> {
@@ -169,6 +364,86 @@ mod tests {
         assert_eq!(original.size.height, back.size.height);
     }
 
+    #[test]
+    fn snapped_rects_land_on_device_pixels() {
+        let scale = ScaleFactor(1.5);
+        let left = Rect::new(Point::new(0.3, 10.1), Size::new(10.1, 1.0));
+        let right = Rect::new(Point::new(10.4, 10.1), Size::new(5.0, 1.0));
+        let (left, right) = (scale.snap_rect(left), scale.snap_rect(right));
+        assert_eq!(
+            left,
+            DeviceRect::new(DevicePoint::new(0.0, 15.0), DeviceSize::new(16.0, 2.0))
+        );
+        // Adjacent rects stay adjacent.
+        assert_eq!(right.origin.x, left.origin.x + left.size.width);
+
+        assert_eq!(
+            scale.snap_point(Point::new(0.9, 1.1)),
+            DevicePoint::new(1.0, 2.0)
+        );
+        assert_eq!(ScaleFactor(1.5).snap_length(1.0), 2.0);
+        assert_eq!(ScaleFactor(2.0).snap_length(0.25), 1.0);
+        assert_eq!(ScaleFactor(2.0).snap_length(0.0), 0.0);
+    }
+
+    fn assert_point_near(p: Point, x: f32, y: f32) {
+        assert!(
+            (p.x - x).abs() < 1e-4 && (p.y - y).abs() < 1e-4,
+            "expected ({x}, {y}), got ({}, {})",
+            p.x,
+            p.y
+        );
+    }
+
+    #[test]
+    fn affine_composes_in_application_order() {
+        let p = Point::new(1.0, 0.0);
+        let rotated = Affine::rotate(std::f32::consts::FRAC_PI_2);
+        assert_point_near(rotated.transform_point(p), 0.0, 1.0);
+
+        let t = Affine::scale(2.0, 3.0).then_translate(10.0, 20.0);
+        assert_point_near(t.transform_point(Point::new(1.0, 1.0)), 12.0, 23.0);
+        let t = Affine::translate(10.0, 20.0).then_scale(2.0, 3.0);
+        assert_point_near(t.transform_point(Point::new(1.0, 1.0)), 22.0, 63.0);
+
+        let skewed = Affine::skew(std::f32::consts::FRAC_PI_4, 0.0);
+        assert_point_near(skewed.transform_point(Point::new(0.0, 2.0)), 2.0, 2.0);
+        assert!(Affine::default().is_identity());
+        assert!(Affine::translate(3.0, 4.0).is_translation());
+        assert!(!rotated.is_translation());
+    }
+
+    #[test]
+    fn affine_inverts() {
+        let t = Affine::rotate(0.3)
+            .then(&Affine::skew(0.2, -0.1))
+            .then_scale(2.0, 0.5)
+            .then_translate(-7.0, 4.0);
+        let inverse = t.invert().expect("transform is invertible");
+        let p = Point::new(13.0, -5.0);
+        let back = inverse.transform_point(t.transform_point(p));
+        assert_point_near(back, 13.0, -5.0);
+        assert!(Affine::scale(0.0, 1.0).invert().is_none());
+    }
+
+    #[test]
+    fn affine_maps_rects_to_their_bounds() {
+        let rect = Rect::new(Point::new(0.0, 0.0), Size::new(4.0, 2.0));
+        let bounds = Affine::rotate(std::f32::consts::FRAC_PI_2).transform_rect(rect);
+        assert_point_near(bounds.origin, -2.0, 0.0);
+        assert!((bounds.size.width - 2.0).abs() < 1e-4);
+        assert!((bounds.size.height - 4.0).abs() < 1e-4);
+
+        let device = ScaleFactor(2.0).to_affine().transform_rect(DeviceRect::new(
+            DevicePoint::new(1.0, 1.0),
+            DeviceSize::new(3.0, 3.0),
+        ));
+        assert_eq!(
+            device,
+            DeviceRect::new(DevicePoint::new(2.0, 2.0), DeviceSize::new(6.0, 6.0))
+        );
+    }
+
     #[test]
     fn edges_sums() {
         let edges = Edges {
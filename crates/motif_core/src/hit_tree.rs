@@ -1,8 +1,12 @@
 //! Hit testing data structures.
 //!
 //! Collects element bounds during paint and provides hit testing queries.
+//! [`HitTree::query`] is the one query the event system, the debug server
+//! and tests share; the other methods are shorthands for it.
 
+use crate::input::CursorIcon;
 use crate::{Point, Rect};
+use std::collections::HashMap;
 
 /// Unique identifier for an element within a frame.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -18,7 +22,7 @@ impl ElementId {
 }
 
 /// Entry in the hit tree: element bounds with z-order.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct HitEntry {
     pub id: ElementId,
     pub bounds: Rect,
@@ -33,6 +37,7 @@ pub struct HitEntry {
 pub struct HitTree {
     entries: Vec<HitEntry>,
     next_z: u32,
+    cursors: HashMap<ElementId, CursorIcon>,
 }
 
 impl HitTree {
@@ -51,23 +56,40 @@ impl HitTree {
         self.next_z += 1;
     }
 
+    /// Show `cursor` while the pointer is over `id`, unless an element
+    /// painted above it sets its own. Called during paint.
+    pub fn set_cursor(&mut self, id: ElementId, cursor: CursorIcon) {
+        self.cursors.insert(id, cursor);
+    }
+
+    /// The cursor to show at `point`: that of the topmost element there
+    /// that sets one, or the default arrow.
+    pub fn cursor_at(&self, point: Point) -> CursorIcon {
+        self.hits(point)
+            .find_map(|e| self.cursors.get(&e.id).copied())
+            .unwrap_or_default()
+    }
+
+    /// Every entry containing point, topmost (highest z-index) first.
+    pub fn query(&self, point: Point) -> Vec<HitEntry> {
+        self.hits(point).cloned().collect()
+    }
+
     /// Hit test: returns topmost element containing point.
     pub fn hit_test(&self, point: Point) -> Option<ElementId> {
-        self.entries
-            .iter()
-            .rev()
-            .find(|e| rect_contains(&e.bounds, point))
-            .map(|e| e.id)
+        self.hits(point).next().map(|e| e.id)
     }
 
     /// Hit test: returns all elements containing point, topmost first.
     pub fn hit_test_all(&self, point: Point) -> Vec<ElementId> {
+        self.hits(point).map(|e| e.id).collect()
+    }
+
+    fn hits(&self, point: Point) -> impl Iterator<Item = &HitEntry> {
         self.entries
             .iter()
             .rev()
-            .filter(|e| rect_contains(&e.bounds, point))
-            .map(|e| e.id)
-            .collect()
+            .filter(move |e| rect_contains(&e.bounds, point))
     }
 
     /// Number of registered elements.
@@ -84,6 +106,7 @@ impl HitTree {
     pub fn clear(&mut self) {
         self.entries.clear();
         self.next_z = 0;
+        self.cursors.clear();
     }
 
     /// Get all entries (for debugging/visualization).
@@ -264,6 +287,50 @@ mod tests {
         assert!(hits.is_empty());
     }
 
+    #[test]
+    fn query_returns_bounds_and_z_order() {
+        let mut tree = HitTree::new();
+        tree.push(ElementId(1), rect(0.0, 0.0, 200.0, 200.0));
+        tree.push(ElementId(2), rect(50.0, 50.0, 100.0, 100.0));
+        tree.push(ElementId(3), rect(300.0, 0.0, 10.0, 10.0));
+
+        let hits = tree.query(pt(75.0, 75.0));
+        assert_eq!(
+            hits,
+            vec![
+                HitEntry {
+                    id: ElementId(2),
+                    bounds: rect(50.0, 50.0, 100.0, 100.0),
+                    z_index: 1,
+                },
+                HitEntry {
+                    id: ElementId(1),
+                    bounds: rect(0.0, 0.0, 200.0, 200.0),
+                    z_index: 0,
+                },
+            ]
+        );
+        assert!(tree.query(pt(250.0, 250.0)).is_empty());
+    }
+
+    #[test]
+    fn cursor_comes_from_topmost_element_that_sets_one() {
+        let mut tree = HitTree::new();
+        tree.push(ElementId(1), rect(0.0, 0.0, 200.0, 200.0));
+        tree.push(ElementId(2), rect(50.0, 50.0, 100.0, 100.0));
+        tree.push(ElementId(3), rect(60.0, 60.0, 10.0, 10.0));
+        tree.set_cursor(ElementId(1), CursorIcon::ColResize);
+        tree.set_cursor(ElementId(2), CursorIcon::Text);
+
+        assert_eq!(tree.cursor_at(pt(65.0, 65.0)), CursorIcon::Text);
+        assert_eq!(tree.cursor_at(pt(10.0, 10.0)), CursorIcon::ColResize);
+        assert_eq!(tree.cursor_at(pt(250.0, 250.0)), CursorIcon::Default);
+
+        tree.clear();
+        tree.push(ElementId(1), rect(0.0, 0.0, 200.0, 200.0));
+        assert_eq!(tree.cursor_at(pt(10.0, 10.0)), CursorIcon::Default);
+    }
+
     // --- Z-index tracking ---
 
     #[test]
@@ -15,6 +15,7 @@ use std::collections::HashSet;
 // Re-export winit keyboard types (well-designed, handles international layouts)
 pub use winit::event::ElementState;
 pub use winit::keyboard::{Key, KeyCode, ModifiersState, NamedKey, PhysicalKey};
+pub use winit::window::CursorIcon;
 
 /// Mouse button identifier.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -655,6 +655,19 @@ impl TextEditState {
                 }
                 HandleKeyResult::Handled
             }
+            InputAction::InsertTab if shift && !self.multiline => HandleKeyResult::FocusPrev,
+            action => self.apply_action(action),
+        }
+    }
+
+    /// Performs an input action that didn't come from a key event, e.g. an
+    /// Edit menu item. Returns what happened like
+    /// [`handle_key_event`](Self::handle_key_event);
+    /// [`InsertCharacter`](InputAction::InsertCharacter) needs a key and
+    /// isn't handled.
+    pub fn apply_action(&mut self, action: InputAction) -> HandleKeyResult {
+        match action {
+            InputAction::InsertCharacter => HandleKeyResult::NotHandled,
             InputAction::InsertNewline => {
                 if self.multiline {
                     self.insert_newline();
@@ -667,8 +680,6 @@ impl TextEditState {
                 if self.multiline {
                     self.insert_tab();
                     HandleKeyResult::Handled
-                } else if shift {
-                    HandleKeyResult::FocusPrev
                 } else {
                     HandleKeyResult::FocusNext
                 }
@@ -2271,6 +2282,31 @@ mod tests {
         assert_eq!(state.content(), "hello\t");
     }
 
+    #[test]
+    fn apply_action_edits_without_a_key_event() {
+        let mut state = TextEditState::new();
+        state.set_content("hello");
+
+        assert_eq!(
+            state.apply_action(InputAction::SelectAll),
+            HandleKeyResult::Handled
+        );
+        assert_eq!(
+            state.apply_action(InputAction::Cut),
+            HandleKeyResult::Cut("hello".into())
+        );
+        assert_eq!(state.content(), "");
+        assert_eq!(
+            state.apply_action(InputAction::Undo),
+            HandleKeyResult::Handled
+        );
+        assert_eq!(state.content(), "hello");
+        assert_eq!(
+            state.apply_action(InputAction::InsertCharacter),
+            HandleKeyResult::NotHandled
+        );
+    }
+
     #[test]
     fn new_creates_single_line_state() {
         let state = TextEditState::new();
@@ -1,6 +1,6 @@
 //! Layout engine using Taffy for flexbox layout.
 
-use crate::{Point, Rect, Size, TextContext};
+use crate::{Point, Rect, Size, TextContext, TextStyle};
 use taffy::prelude::*;
 
 /// Context attached to layout nodes that need measurement (e.g., text).
@@ -10,6 +10,44 @@ pub enum MeasureContext {
     Text { content: String, font_size: f32 },
 }
 
+/// Bounds on the size an element may take, in logical pixels.
+///
+/// `max` may be infinite on either axis, meaning "as large as the content".
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Constraints {
+    pub min: Size,
+    pub max: Size,
+}
+
+impl Constraints {
+    pub fn new(min: Size, max: Size) -> Self {
+        Self { min, max }
+    }
+
+    /// Any size up to `max`.
+    pub fn loose(max: Size) -> Self {
+        Self::new(Size::new(0.0, 0.0), max)
+    }
+
+    /// Exactly `size`.
+    pub fn tight(size: Size) -> Self {
+        Self::new(size, size)
+    }
+
+    /// Any size at all.
+    pub fn unbounded() -> Self {
+        Self::loose(Size::new(f32::INFINITY, f32::INFINITY))
+    }
+
+    /// `size` clamped to these constraints.
+    pub fn constrain(&self, size: Size) -> Size {
+        Size::new(
+            size.width.max(self.min.width).min(self.max.width),
+            size.height.max(self.min.height).min(self.max.height),
+        )
+    }
+}
+
 /// Layout engine wrapping Taffy.
 pub struct LayoutEngine {
     taffy: TaffyTree<MeasureContext>,
@@ -58,7 +96,38 @@ impl LayoutEngine {
             width: AvailableSpace::Definite(available_width),
             height: AvailableSpace::Definite(available_height),
         };
+        self.compute(root, available_space, text_context);
+    }
 
+    /// Compute layout for the tree rooted at `root` within `constraints`,
+    /// returning the root's size. Unbounded axes size to content.
+    pub fn compute_layout_within(
+        &mut self,
+        root: NodeId,
+        constraints: Constraints,
+        text_context: &mut TextContext,
+    ) -> Size {
+        let available = |max: f32| {
+            if max.is_finite() {
+                AvailableSpace::Definite(max)
+            } else {
+                AvailableSpace::MaxContent
+            }
+        };
+        let available_space = taffy::Size {
+            width: available(constraints.max.width),
+            height: available(constraints.max.height),
+        };
+        self.compute(root, available_space, text_context);
+        constraints.constrain(self.layout_bounds(root).size)
+    }
+
+    fn compute(
+        &mut self,
+        root: NodeId,
+        available_space: taffy::Size<AvailableSpace>,
+        text_context: &mut TextContext,
+    ) {
         self.taffy
             .compute_layout_with_measure(
                 root,
@@ -98,6 +167,11 @@ impl LayoutEngine {
         bounds
     }
 
+    /// Child nodes of a node, in order.
+    pub fn children(&self, id: NodeId) -> Vec<NodeId> {
+        self.taffy.children(id).expect("node not found")
+    }
+
     /// Get raw layout (relative position, device pixels).
     pub fn layout(&self, id: NodeId) -> &taffy::Layout {
         self.taffy.layout(id).expect("node not found")
@@ -160,11 +234,11 @@ fn measure_text(
     // Layout the text
     // TODO: Support max_width for wrapping (parley's break_all_lines takes Option<f32>)
     let _ = max_width; // Suppress unused warning for now
-    let layout = text_context.layout_text(content, font_size);
+    let metrics = text_context.measure(content, &TextStyle::new(font_size));
 
     taffy::Size {
-        width: known_dimensions.width.unwrap_or(layout.width()),
-        height: known_dimensions.height.unwrap_or(layout.height()),
+        width: known_dimensions.width.unwrap_or(metrics.width),
+        height: known_dimensions.height.unwrap_or(metrics.height),
     }
 }
 
@@ -183,6 +257,23 @@ mod tests {
         let _engine = LayoutEngine::new();
     }
 
+    #[test]
+    fn constraints_clamp_sizes() {
+        let constraints = Constraints::new(Size::new(10.0, 10.0), Size::new(100.0, f32::INFINITY));
+        assert_eq!(
+            constraints.constrain(Size::new(5.0, 500.0)),
+            Size::new(10.0, 500.0)
+        );
+        assert_eq!(
+            constraints.constrain(Size::new(150.0, 20.0)),
+            Size::new(100.0, 20.0)
+        );
+        assert_eq!(
+            Constraints::tight(Size::new(3.0, 4.0)).constrain(Size::new(0.0, 0.0)),
+            Size::new(3.0, 4.0)
+        );
+    }
+
     #[test]
     fn simple_fixed_layout() {
         let mut engine = LayoutEngine::new();
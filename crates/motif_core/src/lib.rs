@@ -1,35 +1,62 @@
 pub mod accessibility;
 pub mod arc_str;
+pub mod atlas;
+pub mod backend;
 pub mod callbacks;
+pub mod clock;
 pub mod context;
+pub mod damage;
+pub mod dispatch;
 pub mod element;
 pub mod elements;
+pub mod executor;
 pub mod focus;
 pub mod geometry;
 pub mod hit_tree;
 pub mod input;
 pub mod layout;
+pub mod path;
+pub mod profiler;
+pub mod reconcile;
 pub mod renderer;
 pub mod scene;
+pub mod software;
+pub mod style;
 pub mod text;
+pub mod theme_file;
+pub mod timer;
+pub mod view;
 
 #[cfg(target_os = "macos")]
 pub mod metal;
 
 pub use accessibility::*;
 pub use arc_str::*;
+pub use atlas::*;
+pub use backend::*;
 pub use callbacks::*;
+pub use clock::*;
 pub use context::*;
+pub use damage::*;
+pub use dispatch::*;
 pub use element::*;
 pub use elements::*;
+pub use executor::*;
 pub use focus::*;
 pub use geometry::*;
 pub use hit_tree::*;
 pub use input::*;
 pub use layout::*;
+pub use path::*;
+pub use profiler::{FramePhase, FrameProfile, FrameProfiler};
+pub use reconcile::*;
 pub use renderer::*;
 pub use scene::*;
+pub use style::{rem, Rems, Theme, ThemeRegistry};
 pub use text::*;
+pub use theme_file::*;
+pub use timer::*;
+pub use view::*;
 
 // Re-export commonly used palette types
 pub use palette::{Hsla, LinSrgba, Srgba};
@@ -27,36 +27,114 @@
 //! Never use `float3` in a buffer-bound struct.
 //!
 //! The shaders in `shaders.metal` follow the same convention: the `has_clip`
-//! slot is padded with `_pad1`, `_pad2`, `_pad3` to keep the struct at 112
-//! bytes.  Compile-time assertions below verify that both sides agree.
+//! slot is followed by `gradient_index`, `shadow_sigma`, `depth` to keep the
+//! struct at 112 bytes.  Compile-time assertions below verify that both sides agree.
+//!
+//! ## Color pipeline
+//!
+//! Scene colors are sRGB-encoded `Srgba` values and are uploaded as-is.  The
+//! vertex shaders decode them to linear light, fragment shaders work and
+//! output in linear, and every render target uses [`COLOR_FORMAT`]
+//! (`BGRA8Unorm_sRGB`), so the GPU blends in linear space and re-encodes to
+//! sRGB on store.  Image textures are `RGBA8Unorm_sRGB` so sampling decodes
+//! them too.  Gradient stops are converted to linear on the CPU.  Glyph
+//! atlas coverage is not a color and stays linear `R8Unorm`.
+//!
+//! Surfaces with extended dynamic range enabled use [`EDR_COLOR_FORMAT`]
+//! (`RGBA16Float`) in the extended linear sRGB color space instead.  The
+//! shaders are unchanged: linear output is stored as is, and since nothing
+//! clamps, scene colors above 1.0 come out brighter than SDR white on
+//! displays with EDR headroom.
+//!
+//! ## Overdraw
+//!
+//! In [`RenderMode::Overdraw`] the same pipelines draw with color writes
+//! masked off, and every fragment that isn't discarded increments a
+//! [`OVERDRAW_FORMAT`] stencil buffer. A final full-screen pass maps the
+//! counts to the heat colors of [`overdraw_color`](crate::overdraw_color).
+//! MSAA, depth testing and partial redraw are bypassed meanwhile, so the
+//! counts reflect every primitive in painter's order.
+//!
+//! ## Frames in flight
+//!
+//! Instance data lives in shared-memory buffers the GPU reads asynchronously,
+//! so the renderer keeps `MAX_FRAMES_IN_FLIGHT` sets of them and rotates
+//! through them. Before a set is rewritten, the command buffer that last used
+//! it is waited on; with three sets the CPU can run up to two frames ahead
+//! before it blocks.
 
-/// Metal shader source, compiled at runtime.
+/// Metal shader source, compiled at runtime if there is no precompiled library.
 const SHADER_SOURCE: &str = include_str!("shaders.metal");
 
-use crate::{FontData, GlyphCache, Quad, RasterizedGlyph, Renderer, Scene, TextRun};
+/// Shader library precompiled by the build script, when the Metal toolchain
+/// was available at build time.
+#[cfg(motif_metallib)]
+const SHADER_LIBRARY: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/shaders.metallib"));
+
+use crate::profiler::{self, FramePhase};
+use crate::{
+    tessellate_fill, tessellate_stroke, AtlasAllocator, AtlasDump, AtlasPageDump, AtlasRegion,
+    Damage, DamageTracker, DevicePoint, DeviceRect, DeviceSize, FontData, GlyphCache, Gradient,
+    GradientKind, ImageData, ImageQuad, PathPrimitive, Quad, RasterizedGlyph, RenderMode, Renderer,
+    Scene, Shadow, MAX_GRADIENT_STOPS, PATH_TOLERANCE,
+};
 use core_graphics_types::geometry::CGSize;
 use foreign_types::ForeignType;
+pub use image::RgbaImage;
 use metal::{
-    Buffer, CommandQueue, CompileOptions, Device, MTLPixelFormat, MTLResourceOptions,
-    MTLTextureUsage, MetalLayer, RenderPipelineDescriptor, RenderPipelineState, Texture,
-    TextureDescriptor,
+    Buffer, CommandBuffer, CommandBufferRef, CommandQueue, CompileOptions, DepthStencilDescriptor,
+    DepthStencilState, Device, Library, MTLColorWriteMask, MTLPixelFormat, MTLResourceOptions,
+    MTLStorageMode, MTLTextureType, MTLTextureUsage, MetalLayer,
+    RenderPipelineColorAttachmentDescriptorRef, RenderPipelineDescriptor, RenderPipelineState,
+    StencilDescriptor, Texture, TextureDescriptor, TextureRef,
 };
 use objc2::msg_send;
 use objc2::runtime::AnyObject;
 use objc2_app_kit::NSView;
 use std::collections::HashMap;
 use std::mem;
+use std::time::Duration;
 use winit::raw_window_handle::{HasWindowHandle, RawWindowHandle};
 
 /// Unit quad vertices for triangle strip: [0,0], [1,0], [0,1], [1,1]
 const UNIT_QUAD_VERTICES: [[f32; 2]; 4] = [[0.0, 0.0], [1.0, 0.0], [0.0, 1.0], [1.0, 1.0]];
 
+/// Pixel format of every color render target. See "Color pipeline" above.
+pub const COLOR_FORMAT: MTLPixelFormat = MTLPixelFormat::BGRA8Unorm_sRGB;
+
+/// Pixel format of surfaces with extended dynamic range enabled.
+pub const EDR_COLOR_FORMAT: MTLPixelFormat = MTLPixelFormat::RGBA16Float;
+
+/// Pixel format of the depth buffer used when depth testing is on.
+const DEPTH_FORMAT: MTLPixelFormat = MTLPixelFormat::Depth32Float;
+
+/// Pixel format of the per-pixel draw counts in overdraw mode.
+const OVERDRAW_FORMAT: MTLPixelFormat = MTLPixelFormat::Stencil8;
+
 const INITIAL_INSTANCE_CAPACITY: usize = 1024;
+const INITIAL_GRADIENT_CAPACITY: usize = 64;
+const INITIAL_IMAGE_CAPACITY: usize = 64;
+const INITIAL_PATH_VERTEX_CAPACITY: usize = 4096;
+/// Frames the CPU may encode ahead of the GPU. Each has its own instance
+/// buffers, so writing one frame's instances never touches a queued frame's.
+/// Textures are shared between frames; the glyph atlas keeps pages that
+/// queued frames may sample out of eviction instead.
+const MAX_FRAMES_IN_FLIGHT: usize = 3;
+/// Shortest run of consecutive quads sharing a clip rect that is drawn with a
+/// scissor instead of per-fragment clipping. Shorter runs aren't worth the
+/// extra draw call.
+const MIN_SCISSOR_RUN: usize = 4;
+/// How many earlier clip groups a quad may be moved back past to join one
+/// with its clip rect. Keeps [`group_quads_by_clip`] linear.
+const CLIP_GROUP_LOOKBACK: usize = 8;
+/// Submitted command buffers kept around for GPU timing before the oldest are
+/// dropped unread.
+const MAX_TIMED_FRAMES: usize = 8;
 
 /// GPU-side quad instance data.
 ///
 /// Tightly packed for Metal buffer: **112 bytes** per quad instance
-/// (6 × `float4` = 96 B, plus one `float` + 3-float pad = 16 B).
+/// (6 × `float4` = 96 B, plus three `float`s + 1-float pad = 16 B).
 ///
 /// ## Adding fields
 ///
@@ -80,13 +158,17 @@ pub struct QuadInstance {
     pub clip_bounds: [f32; 4],
     /// 1.0 if clip is active, 0.0 otherwise
     pub has_clip: f32,
-    /// Explicit padding to reach the next 16-byte boundary.
+    /// Index into the gradient buffer, or -1.0 for a solid background.
+    pub gradient_index: f32,
+    /// Gaussian sigma when this instance is a shadow, 0.0 for a regular quad.
+    pub shadow_sigma: f32,
+    /// Clip-space depth in painter's order, used when depth testing is on.
     ///
-    /// Metal requires buffer structs to be 16-byte aligned.  `has_clip` is a
-    /// single `f32` (4 bytes), so three padding floats bring the trailing slot
-    /// to 16 bytes.  See the module-level doc for the full alignment story.
-    /// The matching `_pad1`/`_pad2`/`_pad3` fields live in `shaders.metal`.
-    pub _padding: [f32; 3],
+    /// Also completes the trailing 16-byte slot: Metal requires buffer
+    /// structs to be 16-byte aligned, and `has_clip`, `gradient_index` and
+    /// `shadow_sigma` only fill 12 bytes.  See the module-level doc for the
+    /// full alignment story.
+    pub depth: f32,
 }
 
 impl QuadInstance {
@@ -126,7 +208,97 @@ impl QuadInstance {
                 [r.origin.x, r.origin.y, r.size.width, r.size.height]
             }),
             has_clip: if quad.clip_bounds.is_some() { 1.0 } else { 0.0 },
-            _padding: [0.0; 3],
+            gradient_index: -1.0,
+            shadow_sigma: 0.0,
+            depth: 0.0,
+        }
+    }
+
+    /// Build the instance for a quad's drop shadow.
+    ///
+    /// The instance bounds cover the shadow rect plus three sigma of blur on
+    /// every side; the shader recovers the shadow rect by insetting them again.
+    /// Returns `None` if a negative spread collapses the shadow.
+    pub fn from_shadow(quad: &Quad, shadow: &Shadow) -> Option<Self> {
+        // A hard shadow still gets half a pixel of blur for antialiasing.
+        let sigma = (shadow.blur_radius * 0.5).max(0.5);
+        let margin = 3.0 * sigma;
+
+        let x = quad.bounds.origin.x + shadow.offset.0 - shadow.spread;
+        let y = quad.bounds.origin.y + shadow.offset.1 - shadow.spread;
+        let width = quad.bounds.size.width + 2.0 * shadow.spread;
+        let height = quad.bounds.size.height + 2.0 * shadow.spread;
+        if width <= 0.0 || height <= 0.0 {
+            return None;
+        }
+
+        let radius = |r: f32| (r + shadow.spread).max(0.0);
+        let mut instance = Self::from_quad(quad);
+        instance.bounds = [
+            x - margin,
+            y - margin,
+            width + 2.0 * margin,
+            height + 2.0 * margin,
+        ];
+        instance.color = [
+            shadow.color.red,
+            shadow.color.green,
+            shadow.color.blue,
+            shadow.color.alpha,
+        ];
+        instance.border_color = [0.0; 4];
+        instance.border_widths = [0.0; 4];
+        instance.corner_radii = [
+            radius(quad.corner_radii.top_left),
+            radius(quad.corner_radii.top_right),
+            radius(quad.corner_radii.bottom_right),
+            radius(quad.corner_radii.bottom_left),
+        ];
+        instance.shadow_sigma = sigma;
+        Some(instance)
+    }
+}
+
+/// GPU-side gradient data, referenced from `QuadInstance::gradient_index`.
+///
+/// **96 bytes** (6 × `float4`).  Stop colors are stored in linear space so the
+/// shader can interpolate them directly.  Unused stop slots repeat the last
+/// stop, which makes them no-ops during evaluation.  Mirror any changes in
+/// `shaders.metal`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GradientInstance {
+    /// Linear: (0, cos angle, sin angle, 0). Radial: (1, center x, center y, radius).
+    pub params: [f32; 4],
+    /// Stop offsets, 0.0 to 1.0.
+    pub offsets: [f32; 4],
+    /// Linear-space r, g, b, a for each stop.
+    pub colors: [[f32; 4]; MAX_GRADIENT_STOPS],
+}
+
+impl GradientInstance {
+    pub fn from_gradient(gradient: &Gradient) -> Self {
+        let params = match gradient.kind {
+            GradientKind::Linear { angle } => [0.0, angle.cos(), angle.sin(), 0.0],
+            GradientKind::Radial { center, radius } => [1.0, center.0, center.1, radius],
+        };
+
+        let mut offsets = [0.0; MAX_GRADIENT_STOPS];
+        let mut colors = [[0.0; 4]; MAX_GRADIENT_STOPS];
+        let stops = &gradient.stops[..gradient.stops.len().min(MAX_GRADIENT_STOPS)];
+        for i in 0..MAX_GRADIENT_STOPS {
+            let Some(stop) = stops.get(i).or(stops.last()) else {
+                break;
+            };
+            let linear = stop.color.into_linear();
+            offsets[i] = stop.offset;
+            colors[i] = [linear.red, linear.green, linear.blue, linear.alpha];
+        }
+
+        Self {
+            params,
+            offsets,
+            colors,
         }
     }
 }
@@ -152,59 +324,154 @@ const _QUAD_INSTANCE_SIZE_CHECK: () = assert!(
     std::mem::size_of::<QuadInstance>() == 112,
     "QuadInstance must be 112 bytes to match shaders.metal QuadInstance"
 );
+/// GPU-side image instance data.
+///
+/// **80 bytes** (4 × `float4` + one 16-byte slot of scalars and padding).
+/// Mirror any changes in the `ImageInstance` struct in `shaders.metal`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ImageInstance {
+    /// x, y, width, height in device pixels
+    pub bounds: [f32; 4],
+    /// u_min, v_min, u_max, v_max
+    pub uv: [f32; 4],
+    /// x, y, width, height of clip region
+    pub clip_bounds: [f32; 4],
+    /// top_left, top_right, bottom_right, bottom_left
+    pub corner_radii: [f32; 4],
+    /// 1.0 if clip is active, 0.0 otherwise
+    pub has_clip: f32,
+    /// Alpha multiplier
+    pub opacity: f32,
+    pub _padding: [f32; 2],
+}
+
+impl ImageInstance {
+    pub fn from_image(image: &ImageQuad) -> Self {
+        Self {
+            bounds: [
+                image.bounds.origin.x,
+                image.bounds.origin.y,
+                image.bounds.size.width,
+                image.bounds.size.height,
+            ],
+            uv: image.uv,
+            clip_bounds: image.clip_bounds.map_or([0.0, 0.0, 0.0, 0.0], |r| {
+                [r.origin.x, r.origin.y, r.size.width, r.size.height]
+            }),
+            corner_radii: [
+                image.corner_radii.top_left,
+                image.corner_radii.top_right,
+                image.corner_radii.bottom_right,
+                image.corner_radii.bottom_left,
+            ],
+            has_clip: if image.clip_bounds.is_some() {
+                1.0
+            } else {
+                0.0
+            },
+            opacity: image.opacity,
+            _padding: [0.0; 2],
+        }
+    }
+}
+
+/// GPU-side vertex for tessellated paths, drawn as a triangle list.
+///
+/// **48 bytes**: position and clip flag share the first 16-byte slot.
+/// Mirror any changes in the `PathVertex` struct in `shaders.metal`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PathVertex {
+    /// x, y in device pixels
+    pub position: [f32; 2],
+    /// 1.0 if clip is active, 0.0 otherwise
+    pub has_clip: f32,
+    pub _padding: f32,
+    /// r, g, b, a
+    pub color: [f32; 4],
+    /// x, y, width, height of clip region
+    pub clip_bounds: [f32; 4],
+}
+
+impl PathVertex {
+    /// Tessellate a path's fill and stroke, appending vertices to `out`.
+    pub fn tessellate(path: &PathPrimitive, out: &mut Vec<PathVertex>) {
+        let clip_bounds = path.clip_bounds.map_or([0.0, 0.0, 0.0, 0.0], |r| {
+            [r.origin.x, r.origin.y, r.size.width, r.size.height]
+        });
+        let has_clip = if path.clip_bounds.is_some() { 1.0 } else { 0.0 };
+
+        let mut push = |points: Vec<crate::DevicePoint>, color: palette::Srgba| {
+            out.extend(points.into_iter().map(|p| PathVertex {
+                position: [p.x, p.y],
+                has_clip,
+                _padding: 0.0,
+                color: [color.red, color.green, color.blue, color.alpha],
+                clip_bounds,
+            }));
+        };
+
+        if let Some(color) = path.fill {
+            push(tessellate_fill(&path.path, PATH_TOLERANCE), color);
+        }
+        if let Some(stroke) = path.stroke {
+            push(
+                tessellate_stroke(&path.path, stroke.width, PATH_TOLERANCE),
+                stroke.color,
+            );
+        }
+    }
+}
+
+const _PATH_VERTEX_SIZE_CHECK: () = assert!(
+    std::mem::size_of::<PathVertex>() == 48,
+    "PathVertex must be 48 bytes to match shaders.metal PathVertex"
+);
+const _IMAGE_INSTANCE_SIZE_CHECK: () = assert!(
+    std::mem::size_of::<ImageInstance>() == 80,
+    "ImageInstance must be 80 bytes to match shaders.metal ImageInstance"
+);
+const _GRADIENT_INSTANCE_SIZE_CHECK: () = assert!(
+    std::mem::size_of::<GradientInstance>() == 96,
+    "GradientInstance must be 96 bytes to match shaders.metal GradientInstance"
+);
 const _GLYPH_INSTANCE_SIZE_CHECK: () = assert!(
     std::mem::size_of::<GlyphInstance>() == 48,
     "GlyphInstance must be 48 bytes to match shaders.metal GlyphInstance"
 );
 
-/// A region in the texture atlas for a cached glyph.
-#[derive(Clone, Copy, Debug)]
-pub struct AtlasRegion {
-    pub x: u32,
-    pub y: u32,
-    pub width: u32,
-    pub height: u32,
-}
-
-/// Simple row-based texture atlas for glyph caching.
+/// Glyph atlas backed by one R8 texture per [`AtlasAllocator`] page.
+///
+/// New pages are created on demand up to [`GlyphAtlas::MAX_PAGES`]; after
+/// that the least-recently-used page is recycled, once no frame that could
+/// still be on the GPU used it. Glyphs are uploaded while building a frame,
+/// before waiting on the slot it reuses, so all `MAX_FRAMES_IN_FLIGHT`
+/// earlier frames count as in flight.
 pub struct GlyphAtlas {
-    texture: Texture,
-    width: u32,
-    height: u32,
-    /// Current row Y position
-    row_y: u32,
-    /// Current X position in row
-    row_x: u32,
-    /// Height of current row (max glyph height in row)
-    row_height: u32,
-    /// Cached glyph locations: (font_id, glyph_id, size_bits) -> region
-    cache: HashMap<(u64, u32, u32), AtlasRegion>,
+    device: Device,
+    pages: Vec<Texture>,
+    allocator: AtlasAllocator,
 }
 
 impl GlyphAtlas {
-    const ATLAS_SIZE: u32 = 1024;
-    const PADDING: u32 = 1;
+    const PAGE_SIZE: u32 = 1024;
+    pub const MAX_PAGES: usize = 4;
 
     pub fn new(device: &Device) -> Self {
-        let descriptor = TextureDescriptor::new();
-        descriptor.set_width(Self::ATLAS_SIZE as u64);
-        descriptor.set_height(Self::ATLAS_SIZE as u64);
-        descriptor.set_pixel_format(MTLPixelFormat::R8Unorm);
-        descriptor.set_usage(MTLTextureUsage::ShaderRead);
-
-        let texture = device.new_texture(&descriptor);
-
         Self {
-            texture,
-            width: Self::ATLAS_SIZE,
-            height: Self::ATLAS_SIZE,
-            row_y: 0,
-            row_x: 0,
-            row_height: 0,
-            cache: HashMap::new(),
+            device: device.clone(),
+            pages: Vec::new(),
+            allocator: AtlasAllocator::new(Self::PAGE_SIZE, Self::MAX_PAGES)
+                .frames_in_flight(MAX_FRAMES_IN_FLIGHT as u64),
         }
     }
 
+    /// Mark the start of a frame for LRU bookkeeping.
+    pub fn begin_frame(&mut self) {
+        self.allocator.begin_frame();
+    }
+
     /// Get or insert a glyph into the atlas.
     /// Returns the atlas region for the glyph.
     pub fn get_or_insert(
@@ -218,76 +485,39 @@ impl GlyphAtlas {
         let key = (font.data.id(), glyph_id, font_size.to_bits());
 
         // Check if already in atlas
-        if let Some(&region) = self.cache.get(&key) {
+        if let Some(region) = self.allocator.get(&key) {
             return Some(region);
         }
 
         // Rasterize the glyph
         let rasterized = glyph_cache.rasterize(font, normalized_coords, glyph_id, font_size)?;
 
-        if rasterized.width == 0 || rasterized.height == 0 {
-            // Empty glyph (e.g., space) - return zero-size region
-            let region = AtlasRegion {
-                x: 0,
-                y: 0,
-                width: 0,
-                height: 0,
-            };
-            self.cache.insert(key, region);
-            return Some(region);
-        }
-
-        // Find space in atlas
-        let region = self.allocate(rasterized.width, rasterized.height)?;
-
-        // Upload to texture
-        self.upload_glyph(&region, rasterized);
-
-        self.cache.insert(key, region);
-        Some(region)
-    }
-
-    /// Allocate space for a glyph in the atlas.
-    fn allocate(&mut self, width: u32, height: u32) -> Option<AtlasRegion> {
-        let padded_width = width + Self::PADDING;
-        let padded_height = height + Self::PADDING;
+        // Find space in atlas (empty glyphs get a zero-size region)
+        let region = self
+            .allocator
+            .insert(key, rasterized.width, rasterized.height)?;
 
-        // Check if fits in current row
-        if self.row_x + padded_width <= self.width {
-            let region = AtlasRegion {
-                x: self.row_x,
-                y: self.row_y,
-                width,
-                height,
-            };
-            self.row_x += padded_width;
-            self.row_height = self.row_height.max(padded_height);
-            return Some(region);
+        while self.pages.len() < self.allocator.page_count() {
+            self.pages.push(Self::new_page_texture(&self.device));
         }
 
-        // Start new row
-        self.row_y += self.row_height;
-        self.row_x = 0;
-        self.row_height = 0;
-
-        // Check if fits in atlas
-        if self.row_y + padded_height > self.height {
-            // Atlas full - would need to implement atlas growth or eviction
-            return None;
+        if !region.is_empty() {
+            self.upload_glyph(&region, rasterized);
         }
 
-        let region = AtlasRegion {
-            x: self.row_x,
-            y: self.row_y,
-            width,
-            height,
-        };
-        self.row_x += padded_width;
-        self.row_height = padded_height;
         Some(region)
     }
 
-    /// Upload glyph data to the texture.
+    fn new_page_texture(device: &Device) -> Texture {
+        let descriptor = TextureDescriptor::new();
+        descriptor.set_width(Self::PAGE_SIZE as u64);
+        descriptor.set_height(Self::PAGE_SIZE as u64);
+        descriptor.set_pixel_format(MTLPixelFormat::R8Unorm);
+        descriptor.set_usage(MTLTextureUsage::ShaderRead);
+        device.new_texture(&descriptor)
+    }
+
+    /// Upload glyph data to the region's page texture.
     fn upload_glyph(&self, region: &AtlasRegion, glyph: &RasterizedGlyph) {
         let mtl_region = metal::MTLRegion {
             origin: metal::MTLOrigin {
@@ -302,7 +532,7 @@ impl GlyphAtlas {
             },
         };
 
-        self.texture.replace_region(
+        self.pages[region.page].replace_region(
             mtl_region,
             0,
             glyph.data.as_ptr() as *const _,
@@ -310,28 +540,69 @@ impl GlyphAtlas {
         );
     }
 
-    pub fn texture(&self) -> &Texture {
-        &self.texture
+    /// Texture for an atlas page.
+    pub fn texture(&self, page: usize) -> Option<&TextureRef> {
+        self.pages.get(page).map(|texture| &**texture)
+    }
+
+    /// Number of atlas pages currently allocated.
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
     }
 
     /// Get UV coordinates for a region (0.0 to 1.0 range).
     pub fn uv_for_region(&self, region: &AtlasRegion) -> [f32; 4] {
-        let w = self.width as f32;
-        let h = self.height as f32;
+        let size = self.allocator.page_size() as f32;
         [
-            region.x as f32 / w,
-            region.y as f32 / h,
-            (region.x + region.width) as f32 / w,
-            (region.y + region.height) as f32 / h,
+            region.x as f32 / size,
+            region.y as f32 / size,
+            (region.x + region.width) as f32 / size,
+            (region.y + region.height) as f32 / size,
         ]
     }
 
-    /// Clear the atlas (for when it fills up).
+    /// Drop all cached glyphs. Page textures are kept and reused.
     pub fn clear(&mut self) {
-        self.row_y = 0;
-        self.row_x = 0;
-        self.row_height = 0;
-        self.cache.clear();
+        self.allocator.clear();
+    }
+
+    /// Read every page texture back with its row occupancy.
+    ///
+    /// Pages are only ever written from the CPU with `replace_region`, so
+    /// their contents can be read without synchronizing with the GPU.
+    pub fn dump(&self) -> AtlasDump {
+        let size = Self::PAGE_SIZE;
+        let mut stats = self.allocator.page_stats().into_iter();
+        let pages = self
+            .pages
+            .iter()
+            .map(|texture| {
+                let mut pixels = vec![0u8; (size * size) as usize];
+                texture.get_bytes(
+                    pixels.as_mut_ptr() as *mut _,
+                    size as u64,
+                    metal::MTLRegion {
+                        origin: metal::MTLOrigin { x: 0, y: 0, z: 0 },
+                        size: metal::MTLSize {
+                            width: size as u64,
+                            height: size as u64,
+                            depth: 1,
+                        },
+                    },
+                    0,
+                );
+                AtlasPageDump {
+                    pixels: image::GrayImage::from_raw(size, size, pixels)
+                        .expect("pixel buffer matches page dimensions"),
+                    // After `clear` the textures outlive the allocator's pages.
+                    stats: stats.next().unwrap_or_default(),
+                }
+            })
+            .collect();
+        AtlasDump {
+            page_size: size,
+            pages,
+        }
     }
 }
 
@@ -352,6 +623,7 @@ pub fn window_id(window: &impl HasWindowHandle) -> Option<u32> {
 pub struct MetalSurface {
     layer: MetalLayer,
     drawable_size: (f32, f32),
+    extended_dynamic_range: bool,
 }
 
 impl MetalSurface {
@@ -370,7 +642,7 @@ impl MetalSurface {
 
         let layer = MetalLayer::new();
         layer.set_device(device);
-        layer.set_pixel_format(metal::MTLPixelFormat::BGRA8Unorm);
+        layer.set_pixel_format(COLOR_FORMAT);
         layer.set_presents_with_transaction(false);
 
         // Set layer on view
@@ -390,6 +662,7 @@ impl MetalSurface {
         Self {
             layer,
             drawable_size,
+            extended_dynamic_range: false,
         }
     }
 
@@ -412,22 +685,386 @@ impl MetalSurface {
     pub fn set_vsync(&self, enabled: bool) {
         self.layer.set_display_sync_enabled(enabled);
     }
+
+    /// Whether extended dynamic range output is enabled.
+    pub fn extended_dynamic_range(&self) -> bool {
+        self.extended_dynamic_range
+    }
+
+    /// Opt into extended dynamic range (HDR) output.
+    ///
+    /// Switches the layer to a half-float drawable in the extended linear
+    /// sRGB color space, so colors brighter than 1.0 are shown above SDR
+    /// white on XDR and other EDR-capable displays. Off by default; it
+    /// doubles drawable memory and costs the system compositor more.
+    pub fn set_extended_dynamic_range(&mut self, enabled: bool) {
+        self.extended_dynamic_range = enabled;
+        self.layer.set_pixel_format(self.pixel_format());
+        self.layer.set_wants_extended_dynamic_range_content(enabled);
+
+        let name = if enabled {
+            unsafe { kCGColorSpaceExtendedLinearSRGB }
+        } else {
+            unsafe { kCGColorSpaceSRGB }
+        };
+        unsafe {
+            let space = CGColorSpaceCreateWithName(name);
+            let layer_ptr = self.layer.as_ptr() as *mut AnyObject;
+            let _: () = msg_send![layer_ptr, setColorspace: space];
+            CGColorSpaceRelease(space);
+        }
+    }
+
+    /// Pixel format of the drawables this surface hands out.
+    pub fn pixel_format(&self) -> MTLPixelFormat {
+        if self.extended_dynamic_range {
+            EDR_COLOR_FORMAT
+        } else {
+            COLOR_FORMAT
+        }
+    }
+}
+
+/// Opaque `CGColorSpace`, encoded so objc2 accepts it as a `CGColorSpaceRef`
+/// message argument.
+#[repr(C)]
+struct CGColorSpace {
+    _private: [u8; 0],
+}
+
+unsafe impl objc2::RefEncode for CGColorSpace {
+    const ENCODING_REF: objc2::Encoding =
+        objc2::Encoding::Pointer(&objc2::Encoding::Struct("CGColorSpace", &[]));
+}
+
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    static kCGColorSpaceSRGB: *const std::ffi::c_void;
+    static kCGColorSpaceExtendedLinearSRGB: *const std::ffi::c_void;
+    fn CGColorSpaceCreateWithName(name: *const std::ffi::c_void) -> *mut CGColorSpace;
+    fn CGColorSpaceRelease(space: *mut CGColorSpace);
+}
+
+/// A shared-memory Metal buffer holding `T` instances, grown on demand.
+struct InstanceBuffer<T> {
+    buffer: Buffer,
+    capacity: usize,
+    _marker: std::marker::PhantomData<T>,
+}
+
+impl<T: Copy> InstanceBuffer<T> {
+    fn new(device: &Device, capacity: usize) -> Self {
+        Self {
+            buffer: device.new_buffer(
+                (capacity * mem::size_of::<T>()) as u64,
+                MTLResourceOptions::StorageModeShared,
+            ),
+            capacity,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    /// Reallocate, dropping the contents, if `len` instances don't fit.
+    fn reserve(&mut self, device: &Device, len: usize) {
+        if len > self.capacity {
+            *self = Self::new(device, len.next_power_of_two());
+        }
+    }
+
+    /// Copy `data` to the start of the buffer, reallocating if it doesn't fit.
+    fn write(&mut self, device: &Device, data: &[T]) {
+        self.reserve(device, data.len());
+        if !data.is_empty() {
+            unsafe {
+                std::ptr::copy_nonoverlapping(
+                    data.as_ptr(),
+                    self.buffer.contents() as *mut T,
+                    data.len(),
+                );
+            }
+        }
+    }
+}
+
+/// Writes instances straight into an [`InstanceBuffer`]'s shared memory,
+/// skipping the intermediate `Vec` and copy of [`InstanceBuffer::write`].
+///
+/// The buffer is grown before writing starts, so pushes never reallocate;
+/// pushing more than the reserved length panics.
+struct InstanceWriter<'a, T> {
+    contents: *mut T,
+    len: usize,
+    capacity: usize,
+    _buffer: std::marker::PhantomData<&'a mut InstanceBuffer<T>>,
+}
+
+impl<'a, T: Copy> InstanceWriter<'a, T> {
+    /// Start writing at the beginning of `buffer`, which is first grown to
+    /// hold at least `max_len` instances.
+    fn new(buffer: &'a mut InstanceBuffer<T>, device: &Device, max_len: usize) -> Self {
+        buffer.reserve(device, max_len);
+        Self {
+            contents: buffer.buffer.contents() as *mut T,
+            len: 0,
+            capacity: buffer.capacity,
+            _buffer: std::marker::PhantomData,
+        }
+    }
+
+    fn push(&mut self, instance: T) {
+        assert!(self.len < self.capacity, "instance buffer overflow");
+        // SAFETY: `len < capacity`, and the buffer holds `capacity` `T`s and
+        // is borrowed mutably for `'a`.
+        unsafe { self.contents.add(self.len).write(instance) };
+        self.len += 1;
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    /// The instances written so far.
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        // SAFETY: the first `len` slots were initialized by `push`.
+        unsafe { std::slice::from_raw_parts_mut(self.contents, self.len) }
+    }
+
+    fn as_slice(&self) -> &[T] {
+        // SAFETY: as for `as_mut_slice`.
+        unsafe { std::slice::from_raw_parts(self.contents, self.len) }
+    }
+}
+
+/// Per-frame instance data for every primitive type.
+struct FrameBuffers {
+    quads: InstanceBuffer<QuadInstance>,
+    gradients: InstanceBuffer<GradientInstance>,
+    path_vertices: InstanceBuffer<PathVertex>,
+    images: InstanceBuffer<ImageInstance>,
+    glyphs: InstanceBuffer<GlyphInstance>,
+}
+
+impl FrameBuffers {
+    fn new(device: &Device) -> Self {
+        Self {
+            quads: InstanceBuffer::new(device, INITIAL_INSTANCE_CAPACITY),
+            gradients: InstanceBuffer::new(device, INITIAL_GRADIENT_CAPACITY),
+            path_vertices: InstanceBuffer::new(device, INITIAL_PATH_VERTEX_CAPACITY),
+            images: InstanceBuffer::new(device, INITIAL_IMAGE_CAPACITY),
+            glyphs: InstanceBuffer::new(device, INITIAL_INSTANCE_CAPACITY),
+        }
+    }
+}
+
+/// GPU textures for [`ImageData`], uploaded on first use.
+///
+/// The renderer owns the textures; callers own the pixels. A texture is
+/// dropped once its image hasn't been drawn for [`Self::MAX_IDLE_FRAMES`]
+/// frames, and re-uploaded if the image comes back.
+struct ImageTextures {
+    device: Device,
+    textures: HashMap<u64, ImageTexture>,
+    frame: u64,
+}
+
+struct ImageTexture {
+    texture: Texture,
+    last_used: u64,
+}
+
+impl ImageTextures {
+    const MAX_IDLE_FRAMES: u64 = 120;
+
+    fn new(device: &Device) -> Self {
+        Self {
+            device: device.clone(),
+            textures: HashMap::new(),
+            frame: 0,
+        }
+    }
+
+    /// Start a frame, dropping textures that have been idle too long.
+    fn begin_frame(&mut self) {
+        self.frame += 1;
+        let frame = self.frame;
+        self.textures
+            .retain(|_, entry| frame - entry.last_used <= Self::MAX_IDLE_FRAMES);
+    }
+
+    /// Texture for an image, uploading it if needed. `None` for empty images.
+    fn get_or_upload(&mut self, image: &ImageData) -> Option<Texture> {
+        if image.width() == 0 || image.height() == 0 {
+            return None;
+        }
+
+        let frame = self.frame;
+        let device = &self.device;
+        let entry = self
+            .textures
+            .entry(image.id())
+            .or_insert_with(|| ImageTexture {
+                texture: Self::upload(device, image),
+                last_used: frame,
+            });
+        entry.last_used = frame;
+        Some(entry.texture.clone())
+    }
+
+    fn upload(device: &Device, image: &ImageData) -> Texture {
+        let descriptor = TextureDescriptor::new();
+        descriptor.set_width(image.width() as u64);
+        descriptor.set_height(image.height() as u64);
+        descriptor.set_pixel_format(MTLPixelFormat::RGBA8Unorm_sRGB);
+        descriptor.set_usage(MTLTextureUsage::ShaderRead);
+        let texture = device.new_texture(&descriptor);
+
+        let region = metal::MTLRegion {
+            origin: metal::MTLOrigin { x: 0, y: 0, z: 0 },
+            size: metal::MTLSize {
+                width: image.width() as u64,
+                height: image.height() as u64,
+                depth: 1,
+            },
+        };
+        texture.replace_region(
+            region,
+            0,
+            image.pixels().as_ptr() as *const _,
+            image.width() as u64 * 4, // bytes per row
+        );
+        texture
+    }
+
+    /// Drop a texture now instead of waiting for it to go idle.
+    fn remove(&mut self, id: u64) {
+        self.textures.remove(&id);
+    }
+
+    fn len(&self) -> usize {
+        self.textures.len()
+    }
+}
+
+/// Render pipelines for each primitive type, built once per [`PipelineKey`].
+#[derive(Clone)]
+struct Pipelines {
+    quad: RenderPipelineState,
+    path: RenderPipelineState,
+    image: RenderPipelineState,
+    text: RenderPipelineState,
+    /// Maps draw counts to heat colors; only built for overdraw.
+    overdraw: Option<RenderPipelineState>,
+}
+
+/// Render target configuration a set of [`Pipelines`] is built for.
+#[derive(Clone, Copy, Debug)]
+struct PipelineKey {
+    sample_count: u64,
+    depth: bool,
+    color_format: MTLPixelFormat,
+    overdraw: bool,
+}
+
+impl PipelineKey {
+    fn fields(&self) -> (u64, bool, u64, bool) {
+        (
+            self.sample_count,
+            self.depth,
+            self.color_format as u64,
+            self.overdraw,
+        )
+    }
+}
+
+impl PartialEq for PipelineKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.fields() == other.fields()
+    }
+}
+
+impl Eq for PipelineKey {}
+
+impl std::hash::Hash for PipelineKey {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.fields().hash(state);
+    }
+}
+
+/// Depth-stencil states for the two passes of depth-tested rendering, and
+/// for counting overdraw.
+struct DepthStates {
+    /// Opaque quads, front to back: test and write depth.
+    opaque: DepthStencilState,
+    /// Everything else, back to front: test against opaque quads only.
+    transparent: DepthStencilState,
+    /// Overdraw mode: no depth, and every fragment increments the stencil.
+    count: DepthStencilState,
+}
+
+impl DepthStates {
+    fn new(device: &Device) -> Self {
+        let state = |write: bool| {
+            let descriptor = DepthStencilDescriptor::new();
+            descriptor.set_depth_compare_function(metal::MTLCompareFunction::Less);
+            descriptor.set_depth_write_enabled(write);
+            device.new_depth_stencil_state(&descriptor)
+        };
+
+        let stencil = StencilDescriptor::new();
+        stencil.set_stencil_compare_function(metal::MTLCompareFunction::Always);
+        stencil.set_depth_stencil_pass_operation(metal::MTLStencilOperation::IncrementClamp);
+        stencil.set_read_mask(0xff);
+        stencil.set_write_mask(0xff);
+        let count = DepthStencilDescriptor::new();
+        count.set_front_face_stencil(Some(&stencil));
+        count.set_back_face_stencil(Some(&stencil));
+
+        Self {
+            opaque: state(true),
+            transparent: state(false),
+            count: device.new_depth_stencil_state(&count),
+        }
+    }
 }
 
 pub struct MetalRenderer {
     device: Device,
     command_queue: CommandQueue,
-    // Quad rendering
-    quad_pipeline: RenderPipelineState,
+    library: Library,
+    // Multisampling
+    sample_count: u64,
+    msaa_texture: Option<Texture>,
+    pipelines: Pipelines,
+    /// Every pipeline set built so far, so toggling MSAA, depth testing or
+    /// EDR back and forth doesn't recompile.
+    pipeline_cache: HashMap<PipelineKey, Pipelines>,
+    /// Pixel format the pipelines and intermediate targets are built for.
+    color_format: MTLPixelFormat,
+    // Depth testing
+    depth_test: bool,
+    depth_texture: Option<Texture>,
+    depth_states: DepthStates,
+    // Overdraw visualization
+    render_mode: RenderMode,
+    overdraw_texture: Option<Texture>,
+    // Instance data, one set per frame in flight
     unit_quad_buffer: Buffer,
-    instance_buffer: Buffer,
-    instance_capacity: usize,
+    frame_buffers: [FrameBuffers; MAX_FRAMES_IN_FLIGHT],
+    /// Command buffer that last read each slot of `frame_buffers`.
+    in_flight: [Option<CommandBuffer>; MAX_FRAMES_IN_FLIGHT],
+    next_slot: usize,
+    // Image rendering
+    image_textures: ImageTextures,
     // Text rendering
-    text_pipeline: RenderPipelineState,
-    glyph_instance_buffer: Buffer,
-    glyph_instance_capacity: usize,
     glyph_atlas: GlyphAtlas,
     glyph_cache: GlyphCache,
+    // Partial redraw
+    partial_redraw: bool,
+    damage: DamageTracker,
+    retained_texture: Option<Texture>,
+    // GPU timing
+    timed_frames: Vec<CommandBuffer>,
+    last_gpu_time: Option<Duration>,
 }
 
 impl MetalRenderer {
@@ -435,46 +1072,17 @@ impl MetalRenderer {
         let device = Device::system_default().expect("No Metal device found");
         let command_queue = device.new_command_queue();
 
-        // Compile shader
-        let library = device
-            .new_library_with_source(SHADER_SOURCE, &CompileOptions::new())
-            .expect("Failed to compile shader");
-
-        // Quad pipeline
-        let vertex_fn = library.get_function("vertex_main", None).unwrap();
-        let fragment_fn = library.get_function("fragment_main", None).unwrap();
-
-        let pipeline_desc = RenderPipelineDescriptor::new();
-        pipeline_desc.set_vertex_function(Some(&vertex_fn));
-        pipeline_desc.set_fragment_function(Some(&fragment_fn));
-        let color_attachment = pipeline_desc.color_attachments().object_at(0).unwrap();
-        color_attachment.set_pixel_format(metal::MTLPixelFormat::BGRA8Unorm);
-
-        let quad_pipeline = device
-            .new_render_pipeline_state(&pipeline_desc)
-            .expect("Failed to create quad pipeline");
-
-        // Text pipeline
-        let text_vertex_fn = library.get_function("text_vertex_main", None).unwrap();
-        let text_fragment_fn = library.get_function("text_fragment_main", None).unwrap();
-
-        let text_pipeline_desc = RenderPipelineDescriptor::new();
-        text_pipeline_desc.set_vertex_function(Some(&text_vertex_fn));
-        text_pipeline_desc.set_fragment_function(Some(&text_fragment_fn));
-        let text_color_attachment = text_pipeline_desc.color_attachments().object_at(0).unwrap();
-        text_color_attachment.set_pixel_format(metal::MTLPixelFormat::BGRA8Unorm);
-        // Enable alpha blending for text
-        text_color_attachment.set_blending_enabled(true);
-        text_color_attachment.set_source_rgb_blend_factor(metal::MTLBlendFactor::SourceAlpha);
-        text_color_attachment
-            .set_destination_rgb_blend_factor(metal::MTLBlendFactor::OneMinusSourceAlpha);
-        text_color_attachment.set_source_alpha_blend_factor(metal::MTLBlendFactor::One);
-        text_color_attachment
-            .set_destination_alpha_blend_factor(metal::MTLBlendFactor::OneMinusSourceAlpha);
-
-        let text_pipeline = device
-            .new_render_pipeline_state(&text_pipeline_desc)
-            .expect("Failed to create text pipeline");
+        let library = Self::load_library(&device);
+
+        let key = PipelineKey {
+            sample_count: 1,
+            depth: false,
+            color_format: COLOR_FORMAT,
+            overdraw: false,
+        };
+        let pipelines = Self::build_pipelines(&device, &library, key);
+        let pipeline_cache = HashMap::from([(key, pipelines.clone())]);
+        let depth_states = DepthStates::new(&device);
 
         // Create unit quad buffer
         let unit_quad_buffer = device.new_buffer_with_data(
@@ -484,15 +1092,8 @@ impl MetalRenderer {
         );
 
         // Create instance buffers
-        let instance_buffer = device.new_buffer(
-            (INITIAL_INSTANCE_CAPACITY * mem::size_of::<QuadInstance>()) as u64,
-            MTLResourceOptions::StorageModeShared,
-        );
-
-        let glyph_instance_buffer = device.new_buffer(
-            (INITIAL_INSTANCE_CAPACITY * mem::size_of::<GlyphInstance>()) as u64,
-            MTLResourceOptions::StorageModeShared,
-        );
+        let frame_buffers = std::array::from_fn(|_| FrameBuffers::new(&device));
+        let image_textures = ImageTextures::new(&device);
 
         // Create glyph atlas
         let glyph_atlas = GlyphAtlas::new(&device);
@@ -501,157 +1102,1218 @@ impl MetalRenderer {
         Self {
             device,
             command_queue,
-            quad_pipeline,
+            library,
+            sample_count: 1,
+            msaa_texture: None,
+            pipelines,
+            pipeline_cache,
+            color_format: COLOR_FORMAT,
+            depth_test: false,
+            depth_texture: None,
+            depth_states,
+            render_mode: RenderMode::Normal,
+            overdraw_texture: None,
             unit_quad_buffer,
-            instance_buffer,
-            instance_capacity: INITIAL_INSTANCE_CAPACITY,
-            text_pipeline,
-            glyph_instance_buffer,
-            glyph_instance_capacity: INITIAL_INSTANCE_CAPACITY,
+            frame_buffers,
+            in_flight: Default::default(),
+            next_slot: 0,
+            image_textures,
             glyph_atlas,
             glyph_cache,
+            partial_redraw: false,
+            damage: DamageTracker::new(),
+            retained_texture: None,
+            timed_frames: Vec::new(),
+            last_gpu_time: None,
         }
     }
 
     pub fn device(&self) -> &Device {
         &self.device
     }
-}
 
-impl Default for MetalRenderer {
-    fn default() -> Self {
-        Self::new()
+    /// Current MSAA sample count (1 means multisampling is off).
+    pub fn sample_count(&self) -> u64 {
+        self.sample_count
     }
-}
 
-impl Renderer for MetalRenderer {
-    type Surface = MetalSurface;
+    /// Set the MSAA sample count: 1 (off), 2 or 4.
+    ///
+    /// When enabled, frames render into a multisampled target that is
+    /// resolved into the drawable. Counts the device doesn't support fall
+    /// back to 1. Returns the sample count actually in use.
+    pub fn set_sample_count(&mut self, count: u64) -> u64 {
+        let count = match count {
+            2 | 4 if self.device.supports_texture_sample_count(count) => count,
+            _ => 1,
+        };
+        if count != self.sample_count {
+            self.sample_count = count;
+            self.update_pipelines();
+            self.msaa_texture = None;
+            self.depth_texture = None;
+        }
+        self.sample_count
+    }
 
-    fn render(&mut self, scene: &Scene, surface: &mut MetalSurface) {
-        let quads = scene.quads();
-        let text_runs = scene.text_runs();
+    /// Whether depth testing is enabled.
+    pub fn depth_test(&self) -> bool {
+        self.depth_test
+    }
 
-        if quads.is_empty() && text_runs.is_empty() {
-            return;
+    /// Draw opaque quads front to back against a depth buffer.
+    ///
+    /// Opaque quads go first, nearest first, writing depth so that anything
+    /// they cover is rejected before shading. Translucent quads and all other
+    /// primitives follow in painter's order, tested against that depth but
+    /// not writing it. Output is identical; stacked opaque panels just stop
+    /// shading every covered pixel.
+    pub fn set_depth_test(&mut self, enabled: bool) {
+        if enabled != self.depth_test {
+            self.depth_test = enabled;
+            self.update_pipelines();
+            self.depth_texture = None;
         }
+    }
 
-        // Prepare quad instances
-        let quad_instances: Vec<QuadInstance> = quads.iter().map(QuadInstance::from_quad).collect();
+    pub fn render_mode(&self) -> RenderMode {
+        self.render_mode
+    }
 
-        // Prepare glyph instances (must be done before command buffer due to &mut self)
-        let glyph_instances = self.build_glyph_instances(text_runs);
+    /// Draw scenes normally or as an overdraw heat map. See "Overdraw"
+    /// above.
+    pub fn set_render_mode(&mut self, mode: RenderMode) {
+        if mode != self.render_mode {
+            self.render_mode = mode;
+            self.update_pipelines();
+            self.overdraw_texture = None;
+            // The retained frame is from the other mode.
+            self.damage.invalidate_all();
+        }
+    }
 
-        // Grow instance buffers if needed
-        if quad_instances.len() > self.instance_capacity {
-            self.instance_capacity = quad_instances.len().next_power_of_two();
-            self.instance_buffer = self.device.new_buffer(
-                (self.instance_capacity * mem::size_of::<QuadInstance>()) as u64,
-                MTLResourceOptions::StorageModeShared,
-            );
+    /// Release the GPU texture for an image immediately.
+    ///
+    /// Textures are otherwise dropped automatically once an image hasn't been
+    /// drawn for a couple of seconds' worth of frames.
+    pub fn release_image(&mut self, image: &ImageData) {
+        self.image_textures.remove(image.id());
+    }
+
+    /// Number of image textures currently resident on the GPU.
+    pub fn image_texture_count(&self) -> usize {
+        self.image_textures.len()
+    }
+
+    /// Whether partial redraw is enabled.
+    pub fn partial_redraw(&self) -> bool {
+        self.partial_redraw
+    }
+
+    /// Only repaint the parts of the frame that changed since the last one.
+    ///
+    /// Frames are drawn into a retained texture: a [`DamageTracker`] diffs
+    /// each scene against the previous one, the render pass loads the old
+    /// contents and is scissored to the damaged rect, and the result is
+    /// blitted to the drawable. Unchanged frames skip the GPU entirely.
+    ///
+    /// Has no effect while MSAA is on or the scene background is translucent;
+    /// those frames are always repainted in full.
+    pub fn set_partial_redraw(&mut self, enabled: bool) {
+        self.partial_redraw = enabled;
+        self.damage.invalidate_all();
+        if !enabled {
+            self.retained_texture = None;
         }
+    }
 
-        if glyph_instances.len() > self.glyph_instance_capacity {
-            self.glyph_instance_capacity = glyph_instances.len().next_power_of_two();
-            self.glyph_instance_buffer = self.device.new_buffer(
-                (self.glyph_instance_capacity * mem::size_of::<GlyphInstance>()) as u64,
-                MTLResourceOptions::StorageModeShared,
-            );
+    /// Mark a rect (device pixels) for repainting on the next partial redraw,
+    /// for changes the scene diff can't see.
+    pub fn invalidate(&mut self, rect: DeviceRect) {
+        self.damage.invalidate(rect);
+    }
+
+    /// Builder-style [`set_sample_count`](Self::set_sample_count).
+    pub fn with_sample_count(mut self, count: u64) -> Self {
+        self.set_sample_count(count);
+        self
+    }
+
+    /// Switch the pipelines and intermediate targets to a new output pixel
+    /// format, e.g. when a surface turns extended dynamic range on.
+    fn set_color_format(&mut self, format: MTLPixelFormat) {
+        if format != self.color_format {
+            self.color_format = format;
+            self.update_pipelines();
+            self.msaa_texture = None;
+            self.retained_texture = None;
         }
+    }
 
-        // Copy data to GPU buffers
-        if !quad_instances.is_empty() {
-            unsafe {
-                std::ptr::copy_nonoverlapping(
-                    quad_instances.as_ptr(),
-                    self.instance_buffer.contents() as *mut QuadInstance,
-                    quad_instances.len(),
-                );
+    /// Load the shader library: the `.metallib` compiled by the build script
+    /// if there is one, otherwise `SHADER_SOURCE` compiled at runtime.
+    fn load_library(device: &Device) -> Library {
+        #[cfg(motif_metallib)]
+        if let Ok(library) = device.new_library_with_data(SHADER_LIBRARY) {
+            return library;
+        }
+
+        device
+            .new_library_with_source(SHADER_SOURCE, &CompileOptions::new())
+            .expect("Failed to compile shader")
+    }
+
+    /// The render target configuration frames are drawn with. Overdraw
+    /// counting turns MSAA and depth testing off.
+    fn pipeline_key(&self) -> PipelineKey {
+        let overdraw = self.render_mode == RenderMode::Overdraw;
+        PipelineKey {
+            sample_count: if overdraw { 1 } else { self.sample_count },
+            depth: self.depth_test && !overdraw,
+            color_format: self.color_format,
+            overdraw,
+        }
+    }
+
+    /// Switch to the pipelines for the current sample count, depth, color
+    /// format and render mode, building them on first use.
+    fn update_pipelines(&mut self) {
+        let key = self.pipeline_key();
+        self.pipelines = self
+            .pipeline_cache
+            .entry(key)
+            .or_insert_with(|| Self::build_pipelines(&self.device, &self.library, key))
+            .clone();
+    }
+
+    /// Create the quad, path, image and text pipelines for a render target
+    /// configuration.
+    fn build_pipelines(device: &Device, library: &Library, key: PipelineKey) -> Pipelines {
+        let build = |vertex: &str, fragment: &str, label: &str| {
+            let vertex_fn = library.get_function(vertex, None).unwrap();
+            let fragment_fn = library.get_function(fragment, None).unwrap();
+
+            let pipeline_desc = RenderPipelineDescriptor::new();
+            pipeline_desc.set_vertex_function(Some(&vertex_fn));
+            pipeline_desc.set_fragment_function(Some(&fragment_fn));
+            pipeline_desc.set_sample_count(key.sample_count);
+            let color_attachment = pipeline_desc.color_attachments().object_at(0).unwrap();
+            color_attachment.set_pixel_format(key.color_format);
+            enable_alpha_blending(color_attachment);
+            if key.depth {
+                pipeline_desc.set_depth_attachment_pixel_format(DEPTH_FORMAT);
+            }
+            if key.overdraw {
+                // Only the stencil counts are kept.
+                color_attachment.set_write_mask(MTLColorWriteMask::empty());
+                pipeline_desc.set_stencil_attachment_pixel_format(OVERDRAW_FORMAT);
             }
+
+            device
+                .new_render_pipeline_state(&pipeline_desc)
+                .unwrap_or_else(|e| panic!("Failed to create {label} pipeline: {e}"))
+        };
+
+        let overdraw = key.overdraw.then(|| {
+            let vertex_fn = library.get_function("overdraw_vertex_main", None).unwrap();
+            let fragment_fn = library
+                .get_function("overdraw_fragment_main", None)
+                .unwrap();
+
+            let pipeline_desc = RenderPipelineDescriptor::new();
+            pipeline_desc.set_vertex_function(Some(&vertex_fn));
+            pipeline_desc.set_fragment_function(Some(&fragment_fn));
+            let color_attachment = pipeline_desc.color_attachments().object_at(0).unwrap();
+            color_attachment.set_pixel_format(key.color_format);
+
+            device
+                .new_render_pipeline_state(&pipeline_desc)
+                .unwrap_or_else(|e| panic!("Failed to create overdraw pipeline: {e}"))
+        });
+
+        Pipelines {
+            quad: build("vertex_main", "fragment_main", "quad"),
+            path: build("path_vertex_main", "path_fragment_main", "path"),
+            image: build("image_vertex_main", "image_fragment_main", "image"),
+            text: build("text_vertex_main", "text_fragment_main", "text"),
+            overdraw,
         }
+    }
 
-        if !glyph_instances.is_empty() {
-            unsafe {
-                std::ptr::copy_nonoverlapping(
-                    glyph_instances.as_ptr(),
-                    self.glyph_instance_buffer.contents() as *mut GlyphInstance,
-                    glyph_instances.len(),
-                );
+    /// Return the multisampled color target, recreating it if the drawable
+    /// size changed.
+    fn msaa_target(&mut self, width: u64, height: u64) -> Texture {
+        if let Some(texture) = &self.msaa_texture {
+            if texture.width() == width && texture.height() == height {
+                return texture.clone();
             }
         }
 
+        let descriptor = TextureDescriptor::new();
+        descriptor.set_texture_type(MTLTextureType::D2Multisample);
+        descriptor.set_width(width);
+        descriptor.set_height(height);
+        descriptor.set_sample_count(self.sample_count);
+        descriptor.set_pixel_format(self.color_format);
+        descriptor.set_usage(MTLTextureUsage::RenderTarget);
+        descriptor.set_storage_mode(MTLStorageMode::Private);
+
+        let texture = self.device.new_texture(&descriptor);
+        self.msaa_texture = Some(texture.clone());
+        texture
+    }
+
+    /// Return the depth buffer, recreating it if the target size changed.
+    fn depth_target(&mut self, width: u64, height: u64) -> Texture {
+        if let Some(texture) = &self.depth_texture {
+            if texture.width() == width && texture.height() == height {
+                return texture.clone();
+            }
+        }
+
+        let descriptor = TextureDescriptor::new();
+        if self.sample_count > 1 {
+            descriptor.set_texture_type(MTLTextureType::D2Multisample);
+            descriptor.set_sample_count(self.sample_count);
+        }
+        descriptor.set_width(width);
+        descriptor.set_height(height);
+        descriptor.set_pixel_format(DEPTH_FORMAT);
+        descriptor.set_usage(MTLTextureUsage::RenderTarget);
+        descriptor.set_storage_mode(MTLStorageMode::Private);
+
+        let texture = self.device.new_texture(&descriptor);
+        self.depth_texture = Some(texture.clone());
+        texture
+    }
+
+    /// Return the overdraw count buffer, recreating it if the target size
+    /// changed.
+    fn overdraw_target(&mut self, width: u64, height: u64) -> Texture {
+        if let Some(texture) = &self.overdraw_texture {
+            if texture.width() == width && texture.height() == height {
+                return texture.clone();
+            }
+        }
+
+        let descriptor = TextureDescriptor::new();
+        descriptor.set_width(width);
+        descriptor.set_height(height);
+        descriptor.set_pixel_format(OVERDRAW_FORMAT);
+        descriptor.set_usage(MTLTextureUsage::RenderTarget | MTLTextureUsage::ShaderRead);
+        descriptor.set_storage_mode(MTLStorageMode::Private);
+
+        let texture = self.device.new_texture(&descriptor);
+        self.overdraw_texture = Some(texture.clone());
+        texture
+    }
+
+    /// Return the texture partial redraws accumulate into and whether it was
+    /// just (re)created, in which case its contents are undefined.
+    fn retained_target(&mut self, width: u64, height: u64) -> (Texture, bool) {
+        if let Some(texture) = &self.retained_texture {
+            if texture.width() == width && texture.height() == height {
+                return (texture.clone(), false);
+            }
+        }
+
+        let descriptor = TextureDescriptor::new();
+        descriptor.set_width(width);
+        descriptor.set_height(height);
+        descriptor.set_pixel_format(self.color_format);
+        descriptor.set_usage(MTLTextureUsage::RenderTarget | MTLTextureUsage::ShaderRead);
+        descriptor.set_storage_mode(MTLStorageMode::Private);
+
+        let texture = self.device.new_texture(&descriptor);
+        self.retained_texture = Some(texture.clone());
+        (texture, true)
+    }
+
+    /// Render through the retained texture, repainting only damaged pixels.
+    fn render_partial(&mut self, scene: &Scene, surface: &mut MetalSurface) {
+        // The drawable is a blit destination, not just a render target.
+        surface.layer().set_framebuffer_only(false);
+
+        let damage = self.damage.compute(scene);
+
+        // Nothing changed and the layer still shows the retained frame, so
+        // leave it on screen instead of acquiring, blitting and presenting.
+        let (width, height) = surface.drawable_size();
+        let retained_fits = self.retained_texture.as_ref().is_some_and(|texture| {
+            texture.width() == width as u64 && texture.height() == height as u64
+        });
+        if damage == Damage::None && retained_fits && scene.background().alpha >= 1.0 {
+            return;
+        }
+
+        let Some(drawable) = surface.layer().next_drawable() else {
+            // This frame's damage is lost; make sure the next one catches up.
+            self.damage.invalidate_all();
+            return;
+        };
+        let drawable_texture = drawable.texture();
+        let (width, height) = (drawable_texture.width(), drawable_texture.height());
+
+        let (retained, fresh) = self.retained_target(width, height);
+        let damage = if fresh || scene.background().alpha < 1.0 {
+            Damage::Full
+        } else {
+            damage
+        };
+        let scissor = match damage {
+            Damage::None => None,
+            Damage::Full => Some(None),
+            Damage::Partial(rect) => scissor_rect(rect, width, height).map(Some),
+        };
+
+        let command_buffer = self.command_queue.new_command_buffer().to_owned();
+        if let Some(scissor) = scissor {
+            // Loaded pixels inside the scissor still hold the old frame, so
+            // repaint the background over them first.
+            let backdrop = scissor.map(|_| {
+                DeviceRect::new(
+                    DevicePoint::new(0.0, 0.0),
+                    DeviceSize::new(width as f32, height as f32),
+                )
+            });
+            let viewport_size: [f32; 2] = [width as f32, height as f32];
+            self.encode_scene(
+                scene,
+                backdrop,
+                &command_buffer,
+                &retained,
+                viewport_size,
+                scissor,
+            );
+        }
+
+        // Drawables rotate, so always copy the full retained frame across.
+        let origin = metal::MTLOrigin { x: 0, y: 0, z: 0 };
+        let blit = command_buffer.new_blit_command_encoder();
+        blit.copy_from_texture(
+            &retained,
+            0,
+            0,
+            origin,
+            metal::MTLSize {
+                width,
+                height,
+                depth: 1,
+            },
+            drawable_texture,
+            0,
+            0,
+            origin,
+        );
+        blit.end_encoding();
+
+        command_buffer.present_drawable(drawable);
+        {
+            let _scope = profiler::scope(FramePhase::Submit);
+            command_buffer.commit();
+        }
+        self.track_gpu_time(command_buffer);
+    }
+
+    /// Keep a submitted command buffer so its GPU time can be read once it
+    /// completes.
+    fn track_gpu_time(&mut self, command_buffer: CommandBuffer) {
+        if self.timed_frames.len() >= MAX_TIMED_FRAMES {
+            self.timed_frames.remove(0);
+        }
+        self.timed_frames.push(command_buffer);
+    }
+
+    /// Record the GPU time of the newest completed frame and drop every
+    /// finished command buffer.
+    fn collect_gpu_times(&mut self) {
+        let mut latest = None;
+        self.timed_frames
+            .retain(|command_buffer| match command_buffer.status() {
+                metal::MTLCommandBufferStatus::Completed => {
+                    latest = gpu_duration(command_buffer).or(latest);
+                    false
+                }
+                metal::MTLCommandBufferStatus::Error => false,
+                _ => true,
+            });
+        if latest.is_some() {
+            self.last_gpu_time = latest;
+        }
+    }
+}
+
+impl Default for MetalRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Renderer for MetalRenderer {
+    type Surface = MetalSurface;
+
+    fn render(&mut self, scene: &Scene, surface: &mut MetalSurface) {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("render", backend = "metal").entered();
+        // Let the compositor show through translucent backgrounds.
+        surface.layer().set_opaque(scene.background().alpha >= 1.0);
+        self.set_color_format(surface.pixel_format());
+        self.collect_gpu_times();
+
+        if self.partial_redraw && self.sample_count == 1 && self.render_mode == RenderMode::Normal {
+            self.render_partial(scene, surface);
+            return;
+        }
+
         // Get drawable
         let drawable = match surface.layer().next_drawable() {
             Some(d) => d,
             None => return,
         };
 
-        // Create command buffer and encoder
-        let command_buffer = self.command_queue.new_command_buffer();
+        let command_buffer = self.command_queue.new_command_buffer().to_owned();
+        let viewport_size: [f32; 2] = [surface.drawable_size().0, surface.drawable_size().1];
+        self.encode_scene(
+            scene,
+            None,
+            &command_buffer,
+            drawable.texture(),
+            viewport_size,
+            None,
+        );
+
+        command_buffer.present_drawable(drawable);
+        {
+            let _scope = profiler::scope(FramePhase::Submit);
+            command_buffer.commit();
+        }
+        self.track_gpu_time(command_buffer);
+    }
+
+    fn last_frame_gpu_time(&self) -> Option<Duration> {
+        self.last_gpu_time
+    }
+}
+
+/// Draw counts and bindings for one frame, produced by
+/// [`MetalRenderer::prepare_frame`] once the instance buffers are filled.
+struct FrameData {
+    /// Index into `frame_buffers` holding this frame's instance data.
+    slot: usize,
+    clear_color: metal::MTLClearColor,
+    quad_count: usize,
+    /// Opaque quads stored front to back at the end of the quad buffer when
+    /// depth testing; `quad_batches` covers the rest.
+    opaque_quad_count: usize,
+    quad_batches: Vec<QuadBatch>,
+    path_vertex_count: usize,
+    /// One texture per image instance, in instance order.
+    image_textures: Vec<Texture>,
+    glyph_batches: Vec<GlyphBatch>,
+}
+
+impl MetalRenderer {
+    /// Render a scene offscreen and read the pixels back.
+    ///
+    /// `size` is in logical pixels and `scale` is the scale factor the scene
+    /// was built with, so the image is `size * scale` device pixels. No window
+    /// or `CAMetalLayer` is needed. Blocks until the GPU has finished.
+    pub fn render_to_image(&mut self, scene: &Scene, size: (f32, f32), scale: f32) -> RgbaImage {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("render_to_image", backend = "metal").entered();
+        let width = ((size.0 * scale).round() as u64).max(1);
+        let height = ((size.1 * scale).round() as u64).max(1);
+        // Readback is always 8-bit sRGB, even if a surface is rendering EDR.
+        self.set_color_format(COLOR_FORMAT);
+
+        let descriptor = TextureDescriptor::new();
+        descriptor.set_width(width);
+        descriptor.set_height(height);
+        descriptor.set_pixel_format(COLOR_FORMAT);
+        descriptor.set_usage(MTLTextureUsage::RenderTarget | MTLTextureUsage::ShaderRead);
+        descriptor.set_storage_mode(MTLStorageMode::Managed);
+        let target = self.device.new_texture(&descriptor);
+
+        let command_buffer = self.command_queue.new_command_buffer().to_owned();
+        self.encode_scene(
+            scene,
+            None,
+            &command_buffer,
+            &target,
+            [width as f32, height as f32],
+            None,
+        );
+
+        // Make the GPU copy visible to the CPU before reading it.
+        let blit = command_buffer.new_blit_command_encoder();
+        blit.synchronize_resource(&target);
+        blit.end_encoding();
+        command_buffer.commit();
+        command_buffer.wait_until_completed();
+        self.last_gpu_time = gpu_duration(&command_buffer).or(self.last_gpu_time);
+
+        let mut pixels = vec![0u8; (width * height * 4) as usize];
+        target.get_bytes(
+            pixels.as_mut_ptr() as *mut _,
+            width * 4,
+            metal::MTLRegion {
+                origin: metal::MTLOrigin { x: 0, y: 0, z: 0 },
+                size: metal::MTLSize {
+                    width,
+                    height,
+                    depth: 1,
+                },
+            },
+            0,
+        );
+        bgra_to_rgba(&mut pixels);
+
+        RgbaImage::from_raw(width as u32, height as u32, pixels)
+            .expect("pixel buffer matches image dimensions")
+    }
+
+    /// Read back the glyph atlas pages and their row occupancy.
+    pub fn dump_glyph_atlas(&self) -> AtlasDump {
+        self.glyph_atlas.dump()
+    }
+
+    /// Build instance data for a scene and copy it into the GPU buffers.
+    ///
+    /// A `backdrop` rect is filled with the scene background before any other
+    /// quad, for passes that load rather than clear the target.
+    fn prepare_frame(&mut self, scene: &Scene, backdrop: Option<DeviceRect>) -> FrameData {
+        self.image_textures.begin_frame();
+        self.glyph_atlas.begin_frame();
+        self.prepare_layer(scene, backdrop)
+    }
+
+    /// [`prepare_frame`](Self::prepare_frame) for one layer of a frame that
+    /// has already started, so the overlay can't evict the textures or
+    /// glyphs the layers below it use.
+    fn prepare_layer(&mut self, scene: &Scene, backdrop: Option<DeviceRect>) -> FrameData {
+        let _scope = profiler::scope(FramePhase::InstanceBuild);
+        let quads = scene.quads();
+        let paths = scene.paths();
+        let images = scene.images();
+
+        // Tessellate paths into one triangle list
+        let mut path_vertices: Vec<PathVertex> = Vec::new();
+        for path in paths {
+            PathVertex::tessellate(path, &mut path_vertices);
+        }
+
+        // Prepare image instances, uploading textures for new images
+        let mut image_instances: Vec<ImageInstance> = Vec::with_capacity(images.len());
+        let mut image_textures: Vec<Texture> = Vec::with_capacity(images.len());
+        for image in images {
+            if let Some(texture) = self.image_textures.get_or_upload(&image.image) {
+                image_instances.push(ImageInstance::from_image(image));
+                image_textures.push(texture);
+            }
+        }
+
+        // Prepare glyph instances (must be done before command buffer due to &mut self)
+        let (glyph_instances, glyph_batches) = self.build_glyph_instances(scene);
+        let depth = self.pipeline_key().depth;
+
+        // Reuse the oldest slot, waiting if the GPU is still reading it.
+        let slot = self.next_slot;
+        self.next_slot = (slot + 1) % MAX_FRAMES_IN_FLIGHT;
+        if let Some(previous) = self.in_flight[slot].take() {
+            previous.wait_until_completed();
+        }
+
+        // Copy data to GPU buffers
+        let buffers = &mut self.frame_buffers[slot];
+        buffers.path_vertices.write(&self.device, &path_vertices);
+        buffers.images.write(&self.device, &image_instances);
+        buffers.glyphs.write(&self.device, &glyph_instances);
+
+        // Quads can run into the hundreds of thousands, so their instances
+        // and gradients are built in place in the slot's buffers, sized up
+        // front for the most each quad can emit. Shadows are emitted just
+        // before their quad so painter's order holds.
+        let max_quads = usize::from(backdrop.is_some())
+            + quads.len()
+            + quads.iter().filter(|quad| quad.shadow.is_some()).count();
+        let max_gradients = quads.iter().filter(|quad| quad.gradient.is_some()).count();
+        let mut quad_instances = InstanceWriter::new(&mut buffers.quads, &self.device, max_quads);
+        let mut gradient_instances =
+            InstanceWriter::new(&mut buffers.gradients, &self.device, max_gradients);
+        if let Some(rect) = backdrop {
+            quad_instances.push(QuadInstance::from_quad(&Quad::new(
+                rect,
+                scene.background(),
+            )));
+        }
+        for quad in quads {
+            if let Some(shadow) = &quad.shadow {
+                if let Some(instance) = QuadInstance::from_shadow(quad, shadow) {
+                    quad_instances.push(instance);
+                }
+            }
+            let mut instance = QuadInstance::from_quad(quad);
+            if let Some(gradient) = &quad.gradient {
+                instance.gradient_index = gradient_instances.len() as f32;
+                gradient_instances.push(GradientInstance::from_gradient(gradient));
+            }
+            quad_instances.push(instance);
+        }
+
+        let quad_count = quad_instances.len();
+        let instances = quad_instances.as_mut_slice();
+        let opaque_quad_count = if depth {
+            split_opaque_quads(instances, gradient_instances.as_slice())
+        } else {
+            0
+        };
+        let translucent = &mut instances[..quad_count - opaque_quad_count];
+        group_quads_by_clip(translucent);
+        let quad_batches = batch_quads_by_clip(translucent);
+
+        FrameData {
+            slot,
+            clear_color: clear_color(scene.background()),
+            quad_count,
+            opaque_quad_count,
+            quad_batches,
+            path_vertex_count: path_vertices.len(),
+            image_textures,
+            glyph_batches,
+        }
+    }
+
+    /// Prepare and encode `scene` into `target`, then its overlay on top.
+    ///
+    /// Arguments are as for [`prepare_frame`](Self::prepare_frame) and
+    /// [`encode_frame`](Self::encode_frame). The overlay is drawn in a second
+    /// pass that loads the first, so it covers every primitive below it
+    /// regardless of kind.
+    fn encode_scene(
+        &mut self,
+        scene: &Scene,
+        backdrop: Option<DeviceRect>,
+        command_buffer: &CommandBufferRef,
+        target: &TextureRef,
+        viewport_size: [f32; 2],
+        scissor: Option<metal::MTLScissorRect>,
+    ) {
+        let frame = self.prepare_frame(scene, backdrop);
+        let overlay = scene.overlay();
+        self.encode_frame(
+            &frame,
+            command_buffer,
+            target,
+            viewport_size,
+            scissor,
+            overlay.is_some(),
+        );
+
+        if let Some(overlay) = overlay {
+            let frame = self.prepare_layer(overlay, None);
+            let scissor = scissor.unwrap_or(metal::MTLScissorRect {
+                x: 0,
+                y: 0,
+                width: target.width(),
+                height: target.height(),
+            });
+            self.encode_frame(
+                &frame,
+                command_buffer,
+                target,
+                viewport_size,
+                Some(scissor),
+                false,
+            );
+        }
+
+        if self.render_mode == RenderMode::Overdraw {
+            self.encode_overdraw(command_buffer, target);
+        }
+    }
+
+    /// Encode the pass replacing `target` with the heat colors of the draw
+    /// counts left by [`encode_frame`](Self::encode_frame).
+    fn encode_overdraw(&mut self, command_buffer: &CommandBufferRef, target: &TextureRef) {
+        let Some(pipeline) = self.pipelines.overdraw.clone() else {
+            return;
+        };
+        let counts = self.overdraw_target(target.width(), target.height());
 
         let render_pass_desc = metal::RenderPassDescriptor::new();
         let color_attachment = render_pass_desc.color_attachments().object_at(0).unwrap();
-        color_attachment.set_texture(Some(drawable.texture()));
-        color_attachment.set_load_action(metal::MTLLoadAction::Clear);
-        color_attachment.set_clear_color(metal::MTLClearColor::new(0.0, 0.0, 0.0, 1.0));
+        color_attachment.set_texture(Some(target));
+        color_attachment.set_load_action(metal::MTLLoadAction::DontCare);
         color_attachment.set_store_action(metal::MTLStoreAction::Store);
 
         let encoder = command_buffer.new_render_command_encoder(render_pass_desc);
+        encoder.set_render_pipeline_state(&pipeline);
+        encoder.set_fragment_texture(0, Some(&counts));
+        encoder.draw_primitives(metal::MTLPrimitiveType::Triangle, 0, 3);
+        encoder.end_encoding();
+    }
 
-        let viewport_size: [f32; 2] = [surface.drawable_size().0, surface.drawable_size().1];
+    /// Encode a render pass drawing `frame` into `target`.
+    ///
+    /// With a `scissor`, the target's existing contents are loaded instead of
+    /// cleared and drawing is limited to that rect. `more_passes` keeps the
+    /// multisampled target around for a following pass to load.
+    fn encode_frame(
+        &mut self,
+        frame: &FrameData,
+        command_buffer: &CommandBufferRef,
+        target: &TextureRef,
+        viewport_size: [f32; 2],
+        scissor: Option<metal::MTLScissorRect>,
+        more_passes: bool,
+    ) {
+        let _scope = profiler::scope(FramePhase::Submit);
+        let key = self.pipeline_key();
+        let render_pass_desc = metal::RenderPassDescriptor::new();
+        let color_attachment = render_pass_desc.color_attachments().object_at(0).unwrap();
+        if scissor.is_some() {
+            color_attachment.set_load_action(metal::MTLLoadAction::Load);
+        } else {
+            color_attachment.set_load_action(metal::MTLLoadAction::Clear);
+            color_attachment.set_clear_color(frame.clear_color);
+        }
+        if key.sample_count > 1 {
+            // Render into the multisampled target and resolve into the target.
+            let msaa = self.msaa_target(target.width(), target.height());
+            color_attachment.set_texture(Some(&msaa));
+            color_attachment.set_resolve_texture(Some(target));
+            color_attachment.set_store_action(if more_passes {
+                metal::MTLStoreAction::StoreAndMultisampleResolve
+            } else {
+                metal::MTLStoreAction::MultisampleResolve
+            });
+        } else {
+            color_attachment.set_texture(Some(target));
+            color_attachment.set_store_action(metal::MTLStoreAction::Store);
+        }
+        if key.depth {
+            let depth = self.depth_target(target.width(), target.height());
+            let depth_attachment = render_pass_desc.depth_attachment().unwrap();
+            depth_attachment.set_texture(Some(&depth));
+            depth_attachment.set_load_action(metal::MTLLoadAction::Clear);
+            depth_attachment.set_clear_depth(1.0);
+            depth_attachment.set_store_action(metal::MTLStoreAction::DontCare);
+        }
+        if key.overdraw {
+            // Counts carry over into the overlay pass and on to the heat map.
+            let counts = self.overdraw_target(target.width(), target.height());
+            let stencil_attachment = render_pass_desc.stencil_attachment().unwrap();
+            stencil_attachment.set_texture(Some(&counts));
+            if scissor.is_some() {
+                stencil_attachment.set_load_action(metal::MTLLoadAction::Load);
+            } else {
+                stencil_attachment.set_load_action(metal::MTLLoadAction::Clear);
+                stencil_attachment.set_clear_stencil(0);
+            }
+            stencil_attachment.set_store_action(metal::MTLStoreAction::Store);
+        }
+
+        // The slot can't be rewritten until this command buffer completes.
+        // Every caller commits it right after encoding.
+        self.in_flight[frame.slot] = Some(command_buffer.to_owned());
+
+        let buffers = &self.frame_buffers[frame.slot];
+        let encoder = command_buffer.new_render_command_encoder(render_pass_desc);
+        let pass_scissor = scissor.unwrap_or(metal::MTLScissorRect {
+            x: 0,
+            y: 0,
+            width: target.width(),
+            height: target.height(),
+        });
+        if scissor.is_some() {
+            encoder.set_scissor_rect(pass_scissor);
+        }
+        if key.overdraw {
+            encoder.set_depth_stencil_state(&self.depth_states.count);
+        }
 
         // Render quads with instancing
-        if !quad_instances.is_empty() {
-            encoder.set_render_pipeline_state(&self.quad_pipeline);
+        if frame.quad_count > 0 {
+            encoder.set_render_pipeline_state(&self.pipelines.quad);
             encoder.set_vertex_buffer(0, Some(&self.unit_quad_buffer), 0);
-            encoder.set_vertex_buffer(1, Some(&self.instance_buffer), 0);
+            encoder.set_vertex_buffer(1, Some(&buffers.quads.buffer), 0);
             encoder.set_vertex_bytes(
                 2,
                 mem::size_of::<[f32; 2]>() as u64,
                 viewport_size.as_ptr() as *const _,
             );
+            encoder.set_fragment_buffer(0, Some(&buffers.gradients.buffer), 0);
+
+            // With depth testing, opaque quads go first, nearest first.
+            if frame.opaque_quad_count > 0 {
+                encoder.set_depth_stencil_state(&self.depth_states.opaque);
+                encoder.draw_primitives_instanced_base_instance(
+                    metal::MTLPrimitiveType::TriangleStrip,
+                    0,
+                    4,
+                    frame.opaque_quad_count as u64,
+                    (frame.quad_count - frame.opaque_quad_count) as u64,
+                );
+            }
+            if key.depth {
+                // The rest, and paths, images and text (all at depth 0), only
+                // test against the opaque quads.
+                encoder.set_depth_stencil_state(&self.depth_states.transparent);
+            }
+
+            for batch in &frame.quad_batches {
+                if let Some(clip) = batch.scissor {
+                    // Empty scissors are invalid; the batch is fully clipped.
+                    let Some(rect) = clip_scissor(clip, pass_scissor) else {
+                        continue;
+                    };
+                    encoder.set_scissor_rect(rect);
+                }
+                encoder.draw_primitives_instanced_base_instance(
+                    metal::MTLPrimitiveType::TriangleStrip,
+                    0,
+                    4,
+                    batch.count as u64,
+                    batch.start as u64,
+                );
+                if batch.scissor.is_some() {
+                    encoder.set_scissor_rect(pass_scissor);
+                }
+            }
+        }
 
-            encoder.draw_primitives_instanced(
-                metal::MTLPrimitiveType::TriangleStrip,
+        // Render tessellated paths
+        if frame.path_vertex_count > 0 {
+            encoder.set_render_pipeline_state(&self.pipelines.path);
+            encoder.set_vertex_buffer(0, Some(&buffers.path_vertices.buffer), 0);
+            encoder.set_vertex_bytes(
+                1,
+                mem::size_of::<[f32; 2]>() as u64,
+                viewport_size.as_ptr() as *const _,
+            );
+            encoder.draw_primitives(
+                metal::MTLPrimitiveType::Triangle,
                 0,
-                4,
-                quad_instances.len() as u64,
+                frame.path_vertex_count as u64,
             );
         }
 
-        // Render text
-        if !glyph_instances.is_empty() {
-            encoder.set_render_pipeline_state(&self.text_pipeline);
+        // Render images, one draw per texture
+        if !frame.image_textures.is_empty() {
+            encoder.set_render_pipeline_state(&self.pipelines.image);
             encoder.set_vertex_buffer(0, Some(&self.unit_quad_buffer), 0);
-            encoder.set_vertex_buffer(1, Some(&self.glyph_instance_buffer), 0);
+            encoder.set_vertex_buffer(1, Some(&buffers.images.buffer), 0);
             encoder.set_vertex_bytes(
                 2,
                 mem::size_of::<[f32; 2]>() as u64,
                 viewport_size.as_ptr() as *const _,
             );
-            encoder.set_fragment_texture(0, Some(self.glyph_atlas.texture()));
 
-            encoder.draw_primitives_instanced(
-                metal::MTLPrimitiveType::TriangleStrip,
-                0,
-                4,
-                glyph_instances.len() as u64,
+            for (index, texture) in frame.image_textures.iter().enumerate() {
+                encoder.set_fragment_texture(0, Some(texture));
+                encoder.draw_primitives_instanced_base_instance(
+                    metal::MTLPrimitiveType::TriangleStrip,
+                    0,
+                    4,
+                    1,
+                    index as u64,
+                );
+            }
+        }
+
+        // Render text, one draw per atlas page
+        if !frame.glyph_batches.is_empty() {
+            encoder.set_render_pipeline_state(&self.pipelines.text);
+            encoder.set_vertex_buffer(0, Some(&self.unit_quad_buffer), 0);
+            encoder.set_vertex_buffer(1, Some(&buffers.glyphs.buffer), 0);
+            encoder.set_vertex_bytes(
+                2,
+                mem::size_of::<[f32; 2]>() as u64,
+                viewport_size.as_ptr() as *const _,
             );
+
+            for batch in &frame.glyph_batches {
+                encoder.set_fragment_texture(0, self.glyph_atlas.texture(batch.page));
+                encoder.draw_primitives_instanced_base_instance(
+                    metal::MTLPrimitiveType::TriangleStrip,
+                    0,
+                    4,
+                    batch.count as u64,
+                    batch.start as u64,
+                );
+            }
         }
 
         encoder.end_encoding();
+    }
+}
 
-        command_buffer.present_drawable(drawable);
-        command_buffer.commit();
+/// Time the GPU spent executing a completed command buffer.
+fn gpu_duration(command_buffer: &CommandBufferRef) -> Option<Duration> {
+    // metal-rs doesn't wrap GPUStartTime/GPUEndTime (CFTimeInterval seconds).
+    let ptr = command_buffer.as_ptr() as *mut AnyObject;
+    let start: f64 = unsafe { msg_send![ptr, GPUStartTime] };
+    let end: f64 = unsafe { msg_send![ptr, GPUEndTime] };
+    (end > start).then(|| Duration::from_secs_f64(end - start))
+}
+
+/// Round a damage rect out to whole pixels within a `width` x `height`
+/// target. Returns `None` if nothing visible is left.
+fn scissor_rect(rect: DeviceRect, width: u64, height: u64) -> Option<metal::MTLScissorRect> {
+    let x0 = rect.origin.x.floor().max(0.0) as u64;
+    let y0 = rect.origin.y.floor().max(0.0) as u64;
+    let x1 = ((rect.origin.x + rect.size.width).ceil().max(0.0) as u64).min(width);
+    let y1 = ((rect.origin.y + rect.size.height).ceil().max(0.0) as u64).min(height);
+    if x1 <= x0 || y1 <= y0 {
+        return None;
+    }
+    Some(metal::MTLScissorRect {
+        x: x0,
+        y: y0,
+        width: x1 - x0,
+        height: y1 - y0,
+    })
+}
+
+/// Clear color for a scene background.
+///
+/// Render targets are sRGB, so the clear color is given in linear light, and
+/// premultiplied because that is what the compositor expects.
+fn clear_color(background: palette::Srgba) -> metal::MTLClearColor {
+    let linear = background.into_linear();
+    let alpha = linear.alpha as f64;
+    metal::MTLClearColor::new(
+        linear.red as f64 * alpha,
+        linear.green as f64 * alpha,
+        linear.blue as f64 * alpha,
+        alpha,
+    )
+}
+
+/// Swap the red and blue channels of BGRA8 pixels in place.
+fn bgra_to_rgba(pixels: &mut [u8]) {
+    for pixel in pixels.chunks_exact_mut(4) {
+        pixel.swap(0, 2);
+    }
+}
+
+/// Straight-alpha "over" blending shared by every pipeline.
+fn enable_alpha_blending(attachment: &RenderPipelineColorAttachmentDescriptorRef) {
+    attachment.set_blending_enabled(true);
+    attachment.set_source_rgb_blend_factor(metal::MTLBlendFactor::SourceAlpha);
+    attachment.set_destination_rgb_blend_factor(metal::MTLBlendFactor::OneMinusSourceAlpha);
+    attachment.set_source_alpha_blend_factor(metal::MTLBlendFactor::One);
+    attachment.set_destination_alpha_blend_factor(metal::MTLBlendFactor::OneMinusSourceAlpha);
+}
+
+/// Whether a quad instance fully covers every pixel it draws.
+///
+/// The quad shader discards outside the shape rather than antialiasing, so a
+/// quad is opaque when everything it can output is: the fill (or every
+/// gradient stop) and the border, if it draws one. Shadows never are.
+fn is_opaque(instance: &QuadInstance, gradients: &[GradientInstance]) -> bool {
+    if instance.shadow_sigma > 0.0 {
+        return false;
+    }
+    let fill_opaque = match usize::try_from(instance.gradient_index as i64) {
+        Ok(index) => gradients[index].colors.iter().all(|c| c[3] >= 1.0),
+        Err(_) => instance.color[3] >= 1.0,
+    };
+    let draws_border =
+        instance.border_widths.iter().any(|w| *w > 0.0) && instance.border_color[3] > 0.0;
+    fill_opaque && (!draws_border || instance.border_color[3] >= 1.0)
+}
+
+/// Assign painter's-order depths and move opaque instances to the end of the
+/// list, nearest first. Returns how many instances are opaque.
+///
+/// Later instances get smaller depths, so with a `Less` test an opaque quad
+/// hides whatever was painted before it. Depths stay strictly between 0 and
+/// 1 so primitives drawn at depth 0 land in front of every quad.
+fn split_opaque_quads(instances: &mut [QuadInstance], gradients: &[GradientInstance]) -> usize {
+    let step = 1.0 / (instances.len() + 1) as f32;
+    for (i, instance) in instances.iter_mut().enumerate() {
+        instance.depth = 1.0 - (i + 1) as f32 * step;
+    }
+
+    // Stable, so both halves keep painter's order until the opaque half is
+    // flipped.
+    instances.sort_by_key(|instance| is_opaque(instance, gradients));
+    let count = instances
+        .iter()
+        .rev()
+        .take_while(|instance| is_opaque(instance, gradients))
+        .count();
+    let translucent = instances.len() - count;
+    instances[translucent..].reverse();
+    count
+}
+
+/// Reorder quad instances so quads sharing a clip rect sit next to each
+/// other, letting [`batch_quads_by_clip`] scissor them as one run.
+///
+/// A quad only moves back to the latest earlier group with its clip rect
+/// when it overlaps none of the groups in between, so quads that could
+/// blend with each other keep their painter's order.
+fn group_quads_by_clip(instances: &mut [QuadInstance]) {
+    struct Group {
+        clip: Option<[f32; 4]>,
+        /// Union of the visible bounds of the group's quads.
+        bounds: [f32; 4],
+    }
+
+    let mut groups: Vec<Group> = Vec::new();
+    let mut keyed: Vec<(usize, QuadInstance)> = Vec::with_capacity(instances.len());
+    for instance in instances.iter() {
+        let clip = (instance.has_clip > 0.5).then_some(instance.clip_bounds);
+        let bounds = clip.map_or(instance.bounds, |clip| intersect(instance.bounds, clip));
+
+        let mut target = None;
+        for (index, group) in groups.iter().enumerate().rev().take(CLIP_GROUP_LOOKBACK) {
+            if group.clip == clip {
+                target = Some(index);
+                break;
+            }
+            if overlaps(group.bounds, bounds) {
+                break;
+            }
+        }
+        let index = match target {
+            Some(index) => {
+                groups[index].bounds = union(groups[index].bounds, bounds);
+                index
+            }
+            None => {
+                groups.push(Group { clip, bounds });
+                groups.len() - 1
+            }
+        };
+        keyed.push((index, *instance));
+    }
+
+    // Stable, so quads within a group keep their order.
+    keyed.sort_by_key(|(group, _)| *group);
+    for (slot, (_, instance)) in instances.iter_mut().zip(keyed) {
+        *slot = instance;
+    }
+}
+
+/// Overlap of two (x, y, width, height) rects, possibly empty.
+fn intersect(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+    let x = a[0].max(b[0]);
+    let y = a[1].max(b[1]);
+    let right = (a[0] + a[2]).min(b[0] + b[2]);
+    let bottom = (a[1] + a[3]).min(b[1] + b[3]);
+    [x, y, (right - x).max(0.0), (bottom - y).max(0.0)]
+}
+
+fn union(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+    let x = a[0].min(b[0]);
+    let y = a[1].min(b[1]);
+    let right = (a[0] + a[2]).max(b[0] + b[2]);
+    let bottom = (a[1] + a[3]).max(b[1] + b[3]);
+    [x, y, right - x, bottom - y]
+}
+
+fn overlaps(a: [f32; 4], b: [f32; 4]) -> bool {
+    a[0] < b[0] + b[2] && b[0] < a[0] + a[2] && a[1] < b[1] + b[3] && b[1] < a[1] + a[3]
+}
+
+/// A contiguous range of quad instances, optionally drawn under a scissor.
+#[derive(Debug, PartialEq)]
+struct QuadBatch {
+    start: usize,
+    count: usize,
+    /// Clip rect (x, y, width, height) applied by scissor instead of in the
+    /// shader.
+    scissor: Option<[f32; 4]>,
+}
+
+/// Split quad instances into draw batches, moving clipping to the scissor
+/// for runs of at least [`MIN_SCISSOR_RUN`] quads with the same clip rect.
+///
+/// Instances in scissored runs have `has_clip` cleared so the fragment
+/// shader skips its clip test. Everything else keeps shader clipping and is
+/// merged into as few batches as possible.
+fn batch_quads_by_clip(instances: &mut [QuadInstance]) -> Vec<QuadBatch> {
+    let mut batches: Vec<QuadBatch> = Vec::new();
+    let mut start = 0;
+    while start < instances.len() {
+        let clip = (instances[start].has_clip > 0.5).then_some(instances[start].clip_bounds);
+        let mut end = start + 1;
+        while end < instances.len()
+            && (instances[end].has_clip > 0.5).then_some(instances[end].clip_bounds) == clip
+        {
+            end += 1;
+        }
+
+        let scissor = clip.filter(|_| end - start >= MIN_SCISSOR_RUN);
+        if scissor.is_some() {
+            for instance in &mut instances[start..end] {
+                instance.has_clip = 0.0;
+            }
+        }
+
+        match batches.last_mut() {
+            Some(last) if scissor.is_none() && last.scissor.is_none() => {
+                last.count += end - start;
+            }
+            _ => batches.push(QuadBatch {
+                start,
+                count: end - start,
+                scissor,
+            }),
+        }
+        start = end;
     }
+    batches
+}
+
+/// Scissor rect matching the shader's clip test for `clip` (x, y, width,
+/// height), limited to `bounds`. Returns `None` if nothing is left.
+///
+/// The shader keeps a pixel when its center lies inside the clip rect
+/// (edges inclusive), so pixel `x` survives when
+/// `ceil(min - 0.5) <= x <= floor(max - 0.5)`.
+fn clip_scissor(clip: [f32; 4], bounds: metal::MTLScissorRect) -> Option<metal::MTLScissorRect> {
+    let first = |min: f32| (min - 0.5).ceil().max(0.0) as u64;
+    let end = |max: f32| ((max - 0.5).floor() + 1.0).max(0.0) as u64;
+
+    let x0 = first(clip[0]).max(bounds.x);
+    let y0 = first(clip[1]).max(bounds.y);
+    let x1 = end(clip[0] + clip[2]).min(bounds.x + bounds.width);
+    let y1 = end(clip[1] + clip[3]).min(bounds.y + bounds.height);
+    if x1 <= x0 || y1 <= y0 {
+        return None;
+    }
+    Some(metal::MTLScissorRect {
+        x: x0,
+        y: y0,
+        width: x1 - x0,
+        height: y1 - y0,
+    })
+}
+
+/// A contiguous range of glyph instances sampling the same atlas page.
+struct GlyphBatch {
+    page: usize,
+    start: usize,
+    count: usize,
 }
 
 impl MetalRenderer {
     /// Build glyph instances from text runs, uploading glyphs to atlas as needed.
-    fn build_glyph_instances(&mut self, text_runs: &[TextRun]) -> Vec<GlyphInstance> {
-        let mut instances = Vec::new();
-
-        for run in text_runs {
-            for glyph in &run.glyphs {
+    ///
+    /// Instances are grouped by atlas page so each page can be drawn with a
+    /// single instanced call.
+    fn build_glyph_instances(&mut self, scene: &Scene) -> (Vec<GlyphInstance>, Vec<GlyphBatch>) {
+        #[cfg(feature = "tracing")]
+        let _span =
+            tracing::debug_span!("build_glyph_instances", runs = scene.text_run_count()).entered();
+        let mut pages: Vec<Vec<GlyphInstance>> = Vec::new();
+
+        for run in scene.text_runs() {
+            for glyph in scene.glyphs(run) {
                 // Get or rasterize glyph and add to atlas
                 let region = match self.glyph_atlas.get_or_insert(
                     &run.font,
@@ -665,7 +2327,7 @@ impl MetalRenderer {
                 };
 
                 // Skip empty glyphs (spaces)
-                if region.width == 0 || region.height == 0 {
+                if region.is_empty() {
                     continue;
                 }
 
@@ -686,7 +2348,10 @@ impl MetalRenderer {
 
                 let uv = self.glyph_atlas.uv_for_region(&region);
 
-                instances.push(GlyphInstance {
+                if pages.len() <= region.page {
+                    pages.resize_with(region.page + 1, Vec::new);
+                }
+                pages[region.page].push(GlyphInstance {
                     bounds: [x, y, region.width as f32, region.height as f32],
                     uv,
                     color: [
@@ -699,7 +2364,21 @@ impl MetalRenderer {
             }
         }
 
-        instances
+        let mut instances = Vec::new();
+        let mut batches = Vec::new();
+        for (page, page_instances) in pages.into_iter().enumerate() {
+            if page_instances.is_empty() {
+                continue;
+            }
+            batches.push(GlyphBatch {
+                page,
+                start: instances.len(),
+                count: page_instances.len(),
+            });
+            instances.extend(page_instances);
+        }
+
+        (instances, batches)
     }
 }
 
@@ -761,6 +2440,344 @@ mod tests {
         assert_eq!(instance.has_clip, 1.0); // Flag indicating clip is active
     }
 
+    #[test]
+    fn quad_instance_defaults_to_solid_fill() {
+        let quad = Quad::new(
+            DeviceRect::new(Point2::new(0.0, 0.0), Size2::new(10.0, 10.0)),
+            Srgba::new(1.0, 0.0, 0.0, 1.0),
+        );
+        assert_eq!(QuadInstance::from_quad(&quad).gradient_index, -1.0);
+    }
+
+    #[test]
+    fn shadow_instance_covers_blur_extent() {
+        use crate::Corners;
+
+        let mut quad = Quad::new(
+            DeviceRect::new(Point2::new(10.0, 20.0), Size2::new(100.0, 50.0)),
+            Srgba::new(1.0, 1.0, 1.0, 1.0),
+        );
+        quad.corner_radii = Corners::all(8.0);
+        let shadow = Shadow::new((0.0, 4.0), 8.0, Srgba::new(0.0, 0.0, 0.0, 0.5)).with_spread(2.0);
+
+        let instance = QuadInstance::from_shadow(&quad, &shadow).unwrap();
+        // sigma = 4, so the bounds grow by spread + 3 * sigma = 14 on each side.
+        assert_eq!(instance.shadow_sigma, 4.0);
+        assert_eq!(instance.bounds, [-4.0, 10.0, 128.0, 78.0]);
+        assert_eq!(instance.corner_radii, [10.0; 4]);
+        assert_eq!(instance.color, [0.0, 0.0, 0.0, 0.5]);
+        assert_eq!(QuadInstance::from_quad(&quad).shadow_sigma, 0.0);
+    }
+
+    #[test]
+    fn shadow_collapsed_by_negative_spread_is_skipped() {
+        let quad = Quad::new(
+            DeviceRect::new(Point2::new(0.0, 0.0), Size2::new(10.0, 10.0)),
+            Srgba::new(1.0, 1.0, 1.0, 1.0),
+        );
+        let shadow = Shadow::new((0.0, 0.0), 4.0, Srgba::new(0.0, 0.0, 0.0, 1.0)).with_spread(-6.0);
+        assert!(QuadInstance::from_shadow(&quad, &shadow).is_none());
+    }
+
+    #[test]
+    fn clear_color_is_linear_and_premultiplied() {
+        let opaque = clear_color(Srgba::new(1.0, 0.5, 0.0, 1.0));
+        assert_eq!((opaque.red, opaque.blue, opaque.alpha), (1.0, 0.0, 1.0));
+        // sRGB 0.5 is roughly 0.214 in linear space.
+        assert!((opaque.green - 0.214).abs() < 0.001);
+
+        let transparent = clear_color(Srgba::new(1.0, 1.0, 1.0, 0.0));
+        assert_eq!(
+            (transparent.red, transparent.green, transparent.alpha),
+            (0.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn scissor_rect_rounds_out_and_clamps() {
+        let rect = DeviceRect::new(DevicePoint::new(-4.5, 10.2), DeviceSize::new(20.0, 5.5));
+        let scissor = scissor_rect(rect, 100, 100).unwrap();
+        assert_eq!((scissor.x, scissor.y), (0, 10));
+        assert_eq!((scissor.width, scissor.height), (16, 6));
+
+        let offscreen = DeviceRect::new(DevicePoint::new(120.0, 0.0), DeviceSize::new(10.0, 10.0));
+        assert!(scissor_rect(offscreen, 100, 100).is_none());
+    }
+
+    fn clipped_instance(clip: Option<[f32; 4]>) -> QuadInstance {
+        let mut quad = Quad::new(
+            DeviceRect::new(Point2::new(0.0, 0.0), Size2::new(10.0, 10.0)),
+            Srgba::new(1.0, 0.0, 0.0, 1.0),
+        );
+        quad.clip_bounds =
+            clip.map(|c| DeviceRect::new(Point2::new(c[0], c[1]), Size2::new(c[2], c[3])));
+        QuadInstance::from_quad(&quad)
+    }
+
+    #[test]
+    fn long_clip_runs_are_scissored() {
+        let clip = [0.0, 0.0, 50.0, 50.0];
+        let mut instances = vec![clipped_instance(None)];
+        instances.extend((0..MIN_SCISSOR_RUN).map(|_| clipped_instance(Some(clip))));
+        instances.push(clipped_instance(None));
+
+        let batches = batch_quads_by_clip(&mut instances);
+        assert_eq!(
+            batches,
+            vec![
+                QuadBatch {
+                    start: 0,
+                    count: 1,
+                    scissor: None
+                },
+                QuadBatch {
+                    start: 1,
+                    count: MIN_SCISSOR_RUN,
+                    scissor: Some(clip)
+                },
+                QuadBatch {
+                    start: MIN_SCISSOR_RUN + 1,
+                    count: 1,
+                    scissor: None
+                },
+            ]
+        );
+        assert!(instances[1..=MIN_SCISSOR_RUN]
+            .iter()
+            .all(|i| i.has_clip == 0.0));
+    }
+
+    #[test]
+    fn short_and_mixed_clip_runs_keep_shader_clipping() {
+        let mut instances = vec![
+            clipped_instance(Some([0.0, 0.0, 10.0, 10.0])),
+            clipped_instance(Some([5.0, 5.0, 10.0, 10.0])),
+            clipped_instance(None),
+            clipped_instance(Some([0.0, 0.0, 10.0, 10.0])),
+        ];
+        let batches = batch_quads_by_clip(&mut instances);
+        assert_eq!(
+            batches,
+            vec![QuadBatch {
+                start: 0,
+                count: 4,
+                scissor: None
+            }]
+        );
+        assert_eq!(instances[0].has_clip, 1.0);
+    }
+
+    fn instance_at(x: f32, clip: Option<[f32; 4]>) -> QuadInstance {
+        let mut instance = clipped_instance(clip);
+        instance.bounds[0] = x;
+        instance
+    }
+
+    #[test]
+    fn grouping_gathers_separate_clip_runs() {
+        // Two panels whose rows interleave in paint order but never overlap.
+        let left = [0.0, 0.0, 100.0, 100.0];
+        let right = [200.0, 0.0, 100.0, 100.0];
+        let mut instances: Vec<QuadInstance> = (0..MIN_SCISSOR_RUN)
+            .flat_map(|_| {
+                [
+                    instance_at(10.0, Some(left)),
+                    instance_at(210.0, Some(right)),
+                ]
+            })
+            .collect();
+
+        group_quads_by_clip(&mut instances);
+        let clips: Vec<[f32; 4]> = instances.iter().map(|i| i.clip_bounds).collect();
+        let expected: Vec<[f32; 4]> = [left; MIN_SCISSOR_RUN]
+            .into_iter()
+            .chain([right; MIN_SCISSOR_RUN])
+            .collect();
+        assert_eq!(clips, expected);
+
+        let batches = batch_quads_by_clip(&mut instances);
+        assert_eq!(batches.len(), 2);
+        assert!(batches.iter().all(|b| b.scissor.is_some()));
+    }
+
+    #[test]
+    fn grouping_keeps_overlapping_quads_in_order() {
+        let clip = [0.0, 0.0, 100.0, 100.0];
+        // The unclipped quad covers the first one, so the last can't move
+        // back past it.
+        let mut instances = vec![
+            instance_at(0.0, Some(clip)),
+            instance_at(5.0, None),
+            instance_at(0.0, Some(clip)),
+        ];
+        let before = instances.clone();
+        group_quads_by_clip(&mut instances);
+        assert_eq!(instances, before);
+
+        // Moved clear of the others, it joins the first quad's group.
+        instances[1].bounds[0] = 500.0;
+        group_quads_by_clip(&mut instances);
+        let xs: Vec<f32> = instances.iter().map(|i| i.bounds[0]).collect();
+        assert_eq!(xs, [0.0, 0.0, 500.0]);
+    }
+
+    #[test]
+    fn clip_scissor_matches_pixel_center_test() {
+        let bounds = metal::MTLScissorRect {
+            x: 0,
+            y: 0,
+            width: 100,
+            height: 100,
+        };
+        // Centers 10.5..=19.5 lie in [10.4, 20.2]; 20.5 does not.
+        let rect = clip_scissor([10.4, 10.4, 9.8, 9.8], bounds).unwrap();
+        assert_eq!((rect.x, rect.width), (10, 10));
+        // Center 10.5 lies outside [10.6, ..].
+        let rect = clip_scissor([10.6, 0.0, 5.0, 5.0], bounds).unwrap();
+        assert_eq!(rect.x, 11);
+        // Clamped to the pass bounds, and empty when outside them.
+        let rect = clip_scissor([-20.0, 90.0, 200.0, 50.0], bounds).unwrap();
+        assert_eq!((rect.x, rect.width, rect.y, rect.height), (0, 100, 90, 10));
+        assert!(clip_scissor([150.0, 0.0, 10.0, 10.0], bounds).is_none());
+    }
+
+    #[test]
+    fn opacity_considers_fill_border_gradient_and_shadow() {
+        let opaque = clipped_instance(None);
+        assert!(is_opaque(&opaque, &[]));
+
+        let mut translucent = opaque;
+        translucent.color[3] = 0.5;
+        assert!(!is_opaque(&translucent, &[]));
+
+        let mut bordered = opaque;
+        bordered.border_widths = [1.0; 4];
+        bordered.border_color = [0.0, 0.0, 0.0, 0.5];
+        assert!(!is_opaque(&bordered, &[]));
+        bordered.border_color[3] = 0.0;
+        assert!(is_opaque(&bordered, &[]));
+
+        let mut shadow = opaque;
+        shadow.shadow_sigma = 2.0;
+        assert!(!is_opaque(&shadow, &[]));
+
+        let gradient = Gradient::linear(
+            0.0,
+            Srgba::new(1.0, 0.0, 0.0, 1.0),
+            Srgba::new(0.0, 0.0, 1.0, 0.0),
+        );
+        let mut graded = opaque;
+        graded.gradient_index = 0.0;
+        assert!(!is_opaque(
+            &graded,
+            &[GradientInstance::from_gradient(&gradient)]
+        ));
+    }
+
+    #[test]
+    fn opaque_quads_move_to_the_end_nearest_first() {
+        let mut translucent = clipped_instance(None);
+        translucent.color[3] = 0.5;
+        let mut instances = vec![clipped_instance(None), translucent, clipped_instance(None)];
+        instances[0].color[0] = 0.1;
+        instances[2].color[0] = 0.3;
+
+        let opaque = split_opaque_quads(&mut instances, &[]);
+        assert_eq!(opaque, 2);
+        // Translucent first, then opaque in reverse painter's order.
+        assert_eq!(instances[0].color[3], 0.5);
+        assert_eq!(instances[1].color[0], 0.3);
+        assert_eq!(instances[2].color[0], 0.1);
+        // Later quads are nearer, and all depths are strictly inside (0, 1).
+        assert!(instances[1].depth < instances[0].depth);
+        assert!(instances[0].depth < instances[2].depth);
+        assert!(instances.iter().all(|i| i.depth > 0.0 && i.depth < 1.0));
+    }
+
+    #[test]
+    fn bgra_pixels_are_swizzled_to_rgba() {
+        let mut pixels = vec![1, 2, 3, 4, 5, 6, 7, 8];
+        bgra_to_rgba(&mut pixels);
+        assert_eq!(pixels, vec![3, 2, 1, 4, 7, 6, 5, 8]);
+    }
+
+    #[test]
+    fn path_vertices_cover_fill_then_stroke() {
+        use crate::{DevicePoint, Path, Stroke};
+
+        let triangle = Path::new()
+            .move_to(DevicePoint::new(0.0, 0.0))
+            .line_to(DevicePoint::new(10.0, 0.0))
+            .line_to(DevicePoint::new(0.0, 10.0))
+            .close();
+        let path = PathPrimitive::fill(triangle, Srgba::new(1.0, 0.0, 0.0, 1.0))
+            .with_stroke(Stroke::new(1.0, Srgba::new(0.0, 0.0, 1.0, 1.0)));
+
+        let mut vertices = Vec::new();
+        PathVertex::tessellate(&path, &mut vertices);
+
+        // One fill triangle, then three stroke segments and three joins.
+        assert_eq!(vertices.len(), 3 + 3 * 6 + 3 * 6);
+        assert_eq!(vertices[0].color, [1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(vertices[3].color, [0.0, 0.0, 1.0, 1.0]);
+        assert!(vertices.iter().all(|v| v.has_clip == 0.0));
+    }
+
+    #[test]
+    fn image_instance_packs_uv_and_clip() {
+        let pixels = vec![255u8; 4 * 4 * 4];
+        let mut image = ImageQuad::new(
+            DeviceRect::new(Point2::new(5.0, 6.0), Size2::new(40.0, 30.0)),
+            ImageData::new(4, 4, pixels).unwrap(),
+        );
+        image.uv = [0.0, 0.0, 0.5, 0.5];
+        image.opacity = 0.25;
+
+        let instance = ImageInstance::from_image(&image);
+        assert_eq!(instance.bounds, [5.0, 6.0, 40.0, 30.0]);
+        assert_eq!(instance.uv, [0.0, 0.0, 0.5, 0.5]);
+        assert_eq!(instance.has_clip, 0.0);
+        assert_eq!(instance.opacity, 0.25);
+
+        image.clip_bounds = Some(DeviceRect::new(
+            Point2::new(0.0, 0.0),
+            Size2::new(10.0, 10.0),
+        ));
+        let instance = ImageInstance::from_image(&image);
+        assert_eq!(instance.has_clip, 1.0);
+        assert_eq!(instance.clip_bounds, [0.0, 0.0, 10.0, 10.0]);
+        assert_eq!(instance.corner_radii, [0.0; 4]);
+    }
+
+    #[test]
+    fn gradient_instance_packs_linear_stops() {
+        let gradient = Gradient::linear(
+            0.0,
+            Srgba::new(0.0, 0.0, 0.0, 1.0),
+            Srgba::new(1.0, 1.0, 1.0, 1.0),
+        );
+        let instance = GradientInstance::from_gradient(&gradient);
+
+        assert_eq!(instance.params, [0.0, 1.0, 0.0, 0.0]);
+        assert_eq!(instance.offsets, [0.0, 1.0, 1.0, 1.0]);
+        // Unused slots repeat the last stop.
+        assert_eq!(instance.colors[3], instance.colors[1]);
+    }
+
+    #[test]
+    fn gradient_instance_stores_linear_colors() {
+        let gradient = Gradient::radial(
+            Srgba::new(0.5, 0.5, 0.5, 1.0),
+            Srgba::new(0.0, 0.0, 0.0, 0.0),
+        );
+        let instance = GradientInstance::from_gradient(&gradient);
+
+        assert_eq!(instance.params, [1.0, 0.5, 0.5, 0.5]);
+        // sRGB 0.5 is roughly 0.214 in linear space.
+        assert!((instance.colors[0][0] - 0.214).abs() < 0.001);
+        assert_eq!(instance.colors[0][3], 1.0);
+    }
+
     #[test]
     fn quad_instance_no_clip() {
         let quad = Quad::new(
@@ -1,6 +1,20 @@
 #include <metal_stdlib>
 using namespace metal;
 
+// Color pipeline: instance colors arrive sRGB-encoded and are decoded to
+// linear in the vertex stage. Render targets are *_sRGB formats, so blending
+// happens in linear light and the hardware re-encodes on store.
+
+float3 srgb_to_linear(float3 c) {
+    float3 lo = c / 12.92;
+    float3 hi = pow((c + 0.055) / 1.055, 2.4);
+    return select(hi, lo, c <= 0.04045);
+}
+
+float4 srgba_to_linear(float4 c) {
+    return float4(srgb_to_linear(c.rgb), c.a);
+}
+
 struct QuadInstance {
     float4 bounds;        // x, y, width, height
     float4 color;         // r, g, b, a (background)
@@ -9,11 +23,17 @@ struct QuadInstance {
     float4 corner_radii;  // top_left, top_right, bottom_right, bottom_left
     float4 clip_bounds;   // x, y, width, height of clip region
     float has_clip;       // 1.0 if clip active
-    // NOTE: Using 3 separate floats instead of float3 because float3 has 16-byte
+    float gradient_index; // index into gradients buffer, -1.0 for solid fill
+    float shadow_sigma;   // Gaussian sigma for shadow instances, 0.0 otherwise
+    // NOTE: Using separate floats instead of float3 because float3 has 16-byte
     // alignment in Metal, which would cause struct size mismatch with Rust's [f32; 3]
-    float _pad1;
-    float _pad2;
-    float _pad3;
+    float depth;          // painter's-order depth, used when depth testing
+};
+
+struct GradientInstance {
+    float4 params;        // linear: (0, cos, sin, 0); radial: (1, cx, cy, radius)
+    float4 offsets;       // stop offsets
+    float4 colors[4];     // stop colors in linear space
 };
 
 struct VertexOut {
@@ -24,6 +44,8 @@ struct VertexOut {
     float4 corner_radii;
     float4 clip_bounds;   // in device pixels
     float has_clip;
+    float gradient_index [[flat]];
+    float shadow_sigma [[flat]];
     float2 quad_size;     // width, height in pixels
     float2 local_pos;     // position within quad in pixels
     float2 device_pos;    // absolute position in device pixels
@@ -47,13 +69,15 @@ vertex VertexOut vertex_main(
     clip.y = -clip.y;  // Flip Y for Metal's coordinate system
 
     VertexOut out;
-    out.position = float4(clip, 0.0, 1.0);
-    out.color = inst.color;
-    out.border_color = inst.border_color;
+    out.position = float4(clip, inst.depth, 1.0);
+    out.color = srgba_to_linear(inst.color);
+    out.border_color = srgba_to_linear(inst.border_color);
     out.border_widths = inst.border_widths;
     out.corner_radii = inst.corner_radii;
     out.clip_bounds = inst.clip_bounds;
     out.has_clip = inst.has_clip;
+    out.gradient_index = inst.gradient_index;
+    out.shadow_sigma = inst.shadow_sigma;
     out.quad_size = inst.bounds.zw;
     out.local_pos = unit_pos * inst.bounds.zw;
     out.device_pos = pos; // Absolute position in device pixels
@@ -82,7 +106,88 @@ float rounded_rect_sdf(float2 pos, float2 size, float4 radii) {
     return min(max(q.x, q.y), 0.0) + length(max(q, 0.0)) - r;
 }
 
-fragment float4 fragment_main(VertexOut in [[stage_in]]) {
+// Evaluate a gradient at a position within the quad. Stops are already
+// linear, so the result can be output directly.
+float4 evaluate_gradient(GradientInstance g, float2 pos, float2 size) {
+    float t;
+    if (g.params.x < 0.5) {
+        // Linear: project onto the direction, spanning the quad's extent along it
+        float2 dir = g.params.yz;
+        float extent = abs(size.x * dir.x) + abs(size.y * dir.y);
+        t = dot(pos - size * 0.5, dir) / max(extent, 1e-5) + 0.5;
+    } else {
+        // Radial: distance from center in unit coordinates
+        float2 uv = pos / max(size, float2(1e-5));
+        t = length(uv - g.params.yz) / max(g.params.w, 1e-5);
+    }
+    t = saturate(t);
+
+    float4 color = g.colors[0];
+    for (int i = 1; i < 4; i++) {
+        float span = g.offsets[i] - g.offsets[i - 1];
+        float f = span > 0.0 ? saturate((t - g.offsets[i - 1]) / span)
+                             : (t >= g.offsets[i] ? 1.0 : 0.0);
+        color = mix(color, g.colors[i], f);
+    }
+
+    return color;
+}
+
+// Shadows use the analytic approach from Evan Wallace's "Fast Rounded
+// Rectangle Shadows": the blur is separable, so integrate exactly along x with
+// erf and sample a few points along y.
+float gaussian(float x, float sigma) {
+    return exp(-(x * x) / (2.0 * sigma * sigma)) / (sqrt(2.0 * M_PI_F) * sigma);
+}
+
+float2 erf(float2 x) {
+    float2 s = sign(x);
+    float2 a = abs(x);
+    x = 1.0 + (0.278393 + (0.230389 + 0.078108 * (a * a)) * a) * a;
+    x *= x;
+    return s - s / (x * x);
+}
+
+float blur_along_x(float x, float y, float sigma, float corner, float2 half_size) {
+    float delta = min(half_size.y - corner - abs(y), 0.0);
+    float curved = half_size.x - corner + sqrt(max(0.0, corner * corner - delta * delta));
+    float2 integral = 0.5 + 0.5 * erf((x + float2(-curved, curved)) * (sqrt(0.5) / sigma));
+    return integral.y - integral.x;
+}
+
+// Coverage of a Gaussian-blurred rounded rect. The instance bounds include
+// 3 sigma of blur on every side, so the shadow rect shares their center.
+float shadow_alpha(float2 pos, float2 size, float4 radii, float sigma) {
+    float2 half_size = size * 0.5 - 3.0 * sigma;
+    float2 p = pos - size * 0.5;
+
+    float corner;
+    if (p.x < 0.0) {
+        corner = (p.y < 0.0) ? radii.x : radii.w;
+    } else {
+        corner = (p.y < 0.0) ? radii.y : radii.z;
+    }
+    corner = min(corner, min(half_size.x, half_size.y));
+
+    float low = p.y - half_size.y;
+    float high = p.y + half_size.y;
+    float start = clamp(-3.0 * sigma, low, high);
+    float end = clamp(3.0 * sigma, low, high);
+
+    float step = (end - start) / 4.0;
+    float y = start + step * 0.5;
+    float alpha = 0.0;
+    for (int i = 0; i < 4; i++) {
+        alpha += blur_along_x(p.x, p.y - y, sigma, corner, half_size) * gaussian(y, sigma) * step;
+        y += step;
+    }
+    return alpha;
+}
+
+fragment float4 fragment_main(
+    VertexOut in [[stage_in]],
+    constant GradientInstance *gradients [[buffer(0)]]
+) {
     // Apply clip bounds first
     if (in.has_clip > 0.5) {
         float2 clip_min = in.clip_bounds.xy;
@@ -96,6 +201,11 @@ fragment float4 fragment_main(VertexOut in [[stage_in]]) {
     float2 pos = in.local_pos;
     float2 size = in.quad_size;
 
+    if (in.shadow_sigma > 0.0) {
+        float alpha = shadow_alpha(pos, size, in.corner_radii, in.shadow_sigma);
+        return float4(in.color.rgb, in.color.a * alpha);
+    }
+
     // Compute SDF distance (negative = inside)
     float dist = rounded_rect_sdf(pos, size, in.corner_radii);
 
@@ -114,9 +224,141 @@ fragment float4 fragment_main(VertexOut in [[stage_in]]) {
         return in.border_color;
     }
 
+    if (in.gradient_index >= 0.0) {
+        return evaluate_gradient(gradients[int(in.gradient_index)], pos, size);
+    }
+
     return in.color;
 }
 
+// ============================================================================
+// Path rendering shaders
+// ============================================================================
+
+struct PathVertex {
+    float2 position;     // device pixels
+    float has_clip;      // 1.0 if clip active
+    float _pad0;
+    float4 color;        // r, g, b, a
+    float4 clip_bounds;  // x, y, width, height of clip region
+};
+
+struct PathVertexOut {
+    float4 position [[position]];
+    float4 color;
+    float4 clip_bounds;
+    float has_clip;
+    float2 device_pos;
+};
+
+vertex PathVertexOut path_vertex_main(
+    uint vertex_id [[vertex_id]],
+    constant PathVertex *vertices [[buffer(0)]],
+    constant float2 &viewport_size [[buffer(1)]]
+) {
+    PathVertex v = vertices[vertex_id];
+
+    float2 clip = (v.position / viewport_size) * 2.0 - 1.0;
+    clip.y = -clip.y;
+
+    PathVertexOut out;
+    out.position = float4(clip, 0.0, 1.0);
+    out.color = srgba_to_linear(v.color);
+    out.clip_bounds = v.clip_bounds;
+    out.has_clip = v.has_clip;
+    out.device_pos = v.position;
+    return out;
+}
+
+fragment float4 path_fragment_main(PathVertexOut in [[stage_in]]) {
+    if (in.has_clip > 0.5) {
+        float2 clip_min = in.clip_bounds.xy;
+        float2 clip_max = clip_min + in.clip_bounds.zw;
+        if (in.device_pos.x < clip_min.x || in.device_pos.x > clip_max.x ||
+            in.device_pos.y < clip_min.y || in.device_pos.y > clip_max.y) {
+            discard_fragment();
+        }
+    }
+    return in.color;
+}
+
+// ============================================================================
+// Image rendering shaders
+// ============================================================================
+
+struct ImageInstance {
+    float4 bounds;       // x, y, width, height in device pixels
+    float4 uv;           // u_min, v_min, u_max, v_max
+    float4 clip_bounds;  // x, y, width, height of clip region
+    float4 corner_radii; // top_left, top_right, bottom_right, bottom_left
+    float has_clip;      // 1.0 if clip active
+    float opacity;
+    float _pad0;
+    float _pad1;
+};
+
+struct ImageVertexOut {
+    float4 position [[position]];
+    float2 uv;
+    float4 clip_bounds;
+    float has_clip;
+    float opacity;
+    float2 device_pos;
+    float2 local_pos;
+    float2 size;
+    float4 corner_radii;
+};
+
+vertex ImageVertexOut image_vertex_main(
+    uint vertex_id [[vertex_id]],
+    uint instance_id [[instance_id]],
+    constant float2 *vertices [[buffer(0)]],
+    constant ImageInstance *instances [[buffer(1)]],
+    constant float2 &viewport_size [[buffer(2)]]
+) {
+    float2 unit_pos = vertices[vertex_id];
+    ImageInstance inst = instances[instance_id];
+
+    float2 pos = inst.bounds.xy + unit_pos * inst.bounds.zw;
+
+    float2 clip = (pos / viewport_size) * 2.0 - 1.0;
+    clip.y = -clip.y;
+
+    ImageVertexOut out;
+    out.position = float4(clip, 0.0, 1.0);
+    out.uv = inst.uv.xy + unit_pos * (inst.uv.zw - inst.uv.xy);
+    out.clip_bounds = inst.clip_bounds;
+    out.has_clip = inst.has_clip;
+    out.opacity = inst.opacity;
+    out.device_pos = pos;
+    out.local_pos = unit_pos * inst.bounds.zw;
+    out.size = inst.bounds.zw;
+    out.corner_radii = inst.corner_radii;
+    return out;
+}
+
+fragment float4 image_fragment_main(
+    ImageVertexOut in [[stage_in]],
+    texture2d<float> image [[texture(0)]]
+) {
+    if (in.has_clip > 0.5) {
+        float2 clip_min = in.clip_bounds.xy;
+        float2 clip_max = clip_min + in.clip_bounds.zw;
+        if (in.device_pos.x < clip_min.x || in.device_pos.x > clip_max.x ||
+            in.device_pos.y < clip_min.y || in.device_pos.y > clip_max.y) {
+            discard_fragment();
+        }
+    }
+
+    if (rounded_rect_sdf(in.local_pos, in.size, in.corner_radii) > 0.0) {
+        discard_fragment();
+    }
+
+    constexpr sampler image_sampler(filter::linear, address::clamp_to_edge);
+    float4 color = image.sample(image_sampler, in.uv);
+    return float4(color.rgb, color.a * in.opacity);
+}
+
 // ============================================================================
 // Text rendering shaders
 // ============================================================================
@@ -155,7 +397,7 @@ vertex TextVertexOut text_vertex_main(
 
     TextVertexOut out;
     out.position = float4(clip, 0.0, 1.0);
-    out.color = inst.color;
+    out.color = srgba_to_linear(inst.color);
     out.uv = uv;
     return out;
 }
@@ -172,3 +414,38 @@ fragment float4 text_fragment_main(
     // Apply alpha to text color
     return float4(in.color.rgb, in.color.a * alpha);
 }
+
+// Overdraw mode. Primitives are drawn with color writes off, incrementing
+// the stencil buffer for every fragment they don't discard; this pass then
+// replaces the frame with the counts as heat colors.
+
+struct OverdrawVertexOut {
+    float4 position [[position]];
+};
+
+vertex OverdrawVertexOut overdraw_vertex_main(uint vertex_id [[vertex_id]]) {
+    // One triangle covering the whole viewport.
+    float2 uv = float2((vertex_id << 1) & 2, vertex_id & 2);
+
+    OverdrawVertexOut out;
+    out.position = float4(uv * 2.0 - 1.0, 0.0, 1.0);
+    return out;
+}
+
+// Mirrors `overdraw_color` in renderer.rs: none, then 1 to 5+ draws.
+constant float3 OVERDRAW_COLORS[6] = {
+    float3(0.0, 0.0, 0.0),
+    float3(0.1, 0.2, 0.8),
+    float3(0.1, 0.7, 0.2),
+    float3(0.95, 0.85, 0.1),
+    float3(1.0, 0.5, 0.0),
+    float3(0.9, 0.1, 0.1),
+};
+
+fragment float4 overdraw_fragment_main(
+    OverdrawVertexOut in [[stage_in]],
+    texture2d<uint> counts [[texture(0)]]
+) {
+    uint count = counts.read(uint2(in.position.xy)).r;
+    return float4(srgb_to_linear(OVERDRAW_COLORS[min(count, 5u)]), 1.0);
+}
@@ -0,0 +1,570 @@
+//! Vector paths and CPU tessellation into triangles.
+//!
+//! Paths are built from move/line/curve commands in device pixels. Renderers
+//! turn them into triangle lists with [`tessellate_fill`] and
+//! [`tessellate_stroke`], which flatten curves to within a tolerance first.
+//!
+//! Fills use ear clipping on each subpath independently, so each subpath
+//! should be a simple (non-self-intersecting) polygon. Holes are not cut out;
+//! draw them as separate shapes on top instead.
+
+use crate::{DevicePoint, DeviceRect, DeviceSize};
+use std::hash::{Hash, Hasher};
+
+/// Default flattening tolerance in device pixels.
+pub const PATH_TOLERANCE: f32 = 0.25;
+
+/// Upper bound on segments per curve, regardless of tolerance.
+const MAX_CURVE_SEGMENTS: usize = 64;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum PathCommand {
+    MoveTo(DevicePoint),
+    LineTo(DevicePoint),
+    QuadTo(DevicePoint, DevicePoint),
+    CubicTo(DevicePoint, DevicePoint, DevicePoint),
+    Close,
+}
+
+/// A sequence of subpaths made of lines and Bézier curves.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Path {
+    commands: Vec<PathCommand>,
+}
+
+impl Path {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a new subpath at `point`.
+    pub fn move_to(mut self, point: DevicePoint) -> Self {
+        self.commands.push(PathCommand::MoveTo(point));
+        self
+    }
+
+    pub fn line_to(mut self, point: DevicePoint) -> Self {
+        self.commands.push(PathCommand::LineTo(point));
+        self
+    }
+
+    /// Quadratic Bézier with one control point.
+    pub fn quad_to(mut self, control: DevicePoint, to: DevicePoint) -> Self {
+        self.commands.push(PathCommand::QuadTo(control, to));
+        self
+    }
+
+    /// Cubic Bézier with two control points.
+    pub fn cubic_to(
+        mut self,
+        control1: DevicePoint,
+        control2: DevicePoint,
+        to: DevicePoint,
+    ) -> Self {
+        self.commands
+            .push(PathCommand::CubicTo(control1, control2, to));
+        self
+    }
+
+    /// Close the current subpath back to its start.
+    pub fn close(mut self) -> Self {
+        self.commands.push(PathCommand::Close);
+        self
+    }
+
+    /// Open polyline through `points`.
+    pub fn polyline(points: impl IntoIterator<Item = DevicePoint>) -> Self {
+        let mut path = Self::new();
+        for (i, point) in points.into_iter().enumerate() {
+            path = if i == 0 {
+                path.move_to(point)
+            } else {
+                path.line_to(point)
+            };
+        }
+        path
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Bounding box of every point, including curve control points.
+    ///
+    /// Control points bound the curve, so this may be slightly larger than
+    /// the flattened shape.
+    pub fn bounds(&self) -> Option<DeviceRect> {
+        let mut points = self.points();
+        let first = points.next()?;
+        let (min, max) = points.fold((first, first), |(min, max), p| {
+            (
+                DevicePoint::new(min.x.min(p.x), min.y.min(p.y)),
+                DevicePoint::new(max.x.max(p.x), max.y.max(p.y)),
+            )
+        });
+        Some(DeviceRect::new(
+            min,
+            DeviceSize::new(max.x - min.x, max.y - min.y),
+        ))
+    }
+
+    /// Feed the path's commands and points into a hasher.
+    pub(crate) fn hash_geometry<H: Hasher>(&self, state: &mut H) {
+        for command in &self.commands {
+            let tag: u8 = match command {
+                PathCommand::MoveTo(_) => 0,
+                PathCommand::LineTo(_) => 1,
+                PathCommand::QuadTo(..) => 2,
+                PathCommand::CubicTo(..) => 3,
+                PathCommand::Close => 4,
+            };
+            tag.hash(state);
+        }
+        for point in self.points() {
+            point.x.to_bits().hash(state);
+            point.y.to_bits().hash(state);
+        }
+    }
+
+    fn points(&self) -> impl Iterator<Item = DevicePoint> + '_ {
+        self.commands.iter().flat_map(|command| {
+            let (points, len) = match *command {
+                PathCommand::MoveTo(p) | PathCommand::LineTo(p) => ([p, p, p], 1),
+                PathCommand::QuadTo(c, p) => ([c, p, p], 2),
+                PathCommand::CubicTo(c1, c2, p) => ([c1, c2, p], 3),
+                PathCommand::Close => ([DevicePoint::new(0.0, 0.0); 3], 0),
+            };
+            points.into_iter().take(len)
+        })
+    }
+
+    /// Flatten curves into polylines, one per subpath.
+    pub fn flatten(&self, tolerance: f32) -> Vec<Polyline> {
+        let tolerance = tolerance.max(0.001);
+        let mut polylines = Vec::new();
+        let mut current = Polyline::default();
+        let mut pen = DevicePoint::new(0.0, 0.0);
+
+        fn finish(current: &mut Polyline, polylines: &mut Vec<Polyline>) {
+            if current.points.len() > 1 {
+                polylines.push(std::mem::take(current));
+            } else {
+                current.points.clear();
+                current.closed = false;
+            }
+        }
+
+        for command in &self.commands {
+            match *command {
+                PathCommand::MoveTo(point) => {
+                    finish(&mut current, &mut polylines);
+                    current.points.push(point);
+                    pen = point;
+                }
+                PathCommand::LineTo(point) => {
+                    if current.points.is_empty() {
+                        current.points.push(pen);
+                    }
+                    current.points.push(point);
+                    pen = point;
+                }
+                PathCommand::QuadTo(control, to) => {
+                    if current.points.is_empty() {
+                        current.points.push(pen);
+                    }
+                    let dd = length(sub(add(pen, to), scale(control, 2.0)));
+                    let segments = segment_count(dd / (8.0 * tolerance));
+                    for i in 1..=segments {
+                        let t = i as f32 / segments as f32;
+                        current.points.push(quad_point(pen, control, to, t));
+                    }
+                    pen = to;
+                }
+                PathCommand::CubicTo(c1, c2, to) => {
+                    if current.points.is_empty() {
+                        current.points.push(pen);
+                    }
+                    let dd1 = length(sub(add(pen, c2), scale(c1, 2.0)));
+                    let dd2 = length(sub(add(c1, to), scale(c2, 2.0)));
+                    let segments = segment_count(0.75 * dd1.max(dd2) / tolerance);
+                    for i in 1..=segments {
+                        let t = i as f32 / segments as f32;
+                        current.points.push(cubic_point(pen, c1, c2, to, t));
+                    }
+                    pen = to;
+                }
+                PathCommand::Close => {
+                    if let Some(&start) = current.points.first() {
+                        current.closed = true;
+                        pen = start;
+                    }
+                    finish(&mut current, &mut polylines);
+                }
+            }
+        }
+        finish(&mut current, &mut polylines);
+        polylines
+    }
+}
+
+/// A flattened subpath.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Polyline {
+    pub points: Vec<DevicePoint>,
+    pub closed: bool,
+}
+
+/// Triangulate the interior of every subpath. Open subpaths are treated as
+/// closed. Returns vertices, three per triangle.
+pub fn tessellate_fill(path: &Path, tolerance: f32) -> Vec<DevicePoint> {
+    let mut triangles = Vec::new();
+    for polyline in path.flatten(tolerance) {
+        let mut points = polyline.points;
+        if points.len() > 1 && points.first() == points.last() {
+            points.pop();
+        }
+        ear_clip(&points, &mut triangles);
+    }
+    triangles
+}
+
+/// Triangulate a stroke of `width` along every subpath, with bevel joins and
+/// butt caps. Returns vertices, three per triangle.
+pub fn tessellate_stroke(path: &Path, width: f32, tolerance: f32) -> Vec<DevicePoint> {
+    let mut triangles = Vec::new();
+    if width <= 0.0 {
+        return triangles;
+    }
+    let half = width * 0.5;
+
+    for polyline in path.flatten(tolerance) {
+        let mut points = polyline.points;
+        points.dedup();
+        if polyline.closed && points.len() > 2 && points.first() == points.last() {
+            points.pop();
+        }
+        if points.len() < 2 {
+            continue;
+        }
+
+        let segment_count = if polyline.closed {
+            points.len()
+        } else {
+            points.len() - 1
+        };
+        let normals: Vec<DevicePoint> = (0..segment_count)
+            .map(|i| {
+                let a = points[i];
+                let b = points[(i + 1) % points.len()];
+                normal(a, b)
+            })
+            .collect();
+
+        for (i, n) in normals.iter().enumerate() {
+            let a = points[i];
+            let b = points[(i + 1) % points.len()];
+            let offset = scale(*n, half);
+            push_quad(
+                &mut triangles,
+                add(a, offset),
+                add(b, offset),
+                sub(b, offset),
+                sub(a, offset),
+            );
+        }
+
+        // Bevel joins fill the wedge between consecutive segments.
+        let join_count = if polyline.closed {
+            segment_count
+        } else {
+            segment_count - 1
+        };
+        for i in 0..join_count {
+            let next = (i + 1) % segment_count;
+            let p = points[next];
+            let n0 = scale(normals[i], half);
+            let n1 = scale(normals[next], half);
+            triangles.extend_from_slice(&[p, add(p, n0), add(p, n1)]);
+            triangles.extend_from_slice(&[p, sub(p, n0), sub(p, n1)]);
+        }
+    }
+    triangles
+}
+
+fn push_quad(
+    out: &mut Vec<DevicePoint>,
+    a: DevicePoint,
+    b: DevicePoint,
+    c: DevicePoint,
+    d: DevicePoint,
+) {
+    out.extend_from_slice(&[a, b, c, a, c, d]);
+}
+
+/// Ear-clipping triangulation of a simple polygon.
+fn ear_clip(points: &[DevicePoint], out: &mut Vec<DevicePoint>) {
+    if points.len() < 3 {
+        return;
+    }
+
+    // Work in counter-clockwise order (positive signed area, y down).
+    let mut indices: Vec<usize> = (0..points.len()).collect();
+    if signed_area(points) < 0.0 {
+        indices.reverse();
+    }
+
+    let mut guard = 0;
+    while indices.len() > 3 {
+        let n = indices.len();
+        let mut clipped = false;
+        for i in 0..n {
+            let prev = points[indices[(i + n - 1) % n]];
+            let curr = points[indices[i]];
+            let next = points[indices[(i + 1) % n]];
+
+            if cross(sub(curr, prev), sub(next, curr)) <= 0.0 {
+                continue; // Reflex or degenerate vertex
+            }
+            let contains_other = indices.iter().any(|&j| {
+                let p = points[j];
+                p != prev && p != curr && p != next && point_in_triangle(p, prev, curr, next)
+            });
+            if contains_other {
+                continue;
+            }
+
+            out.extend_from_slice(&[prev, curr, next]);
+            indices.remove(i);
+            clipped = true;
+            break;
+        }
+
+        if !clipped {
+            // Degenerate or self-intersecting input: drop a vertex and carry on
+            // so we still terminate.
+            indices.remove(0);
+            guard += 1;
+            if guard > points.len() {
+                return;
+            }
+        }
+    }
+
+    let [a, b, c] = [points[indices[0]], points[indices[1]], points[indices[2]]];
+    if cross(sub(b, a), sub(c, b)).abs() > f32::EPSILON {
+        out.extend_from_slice(&[a, b, c]);
+    }
+}
+
+fn segment_count(estimate: f32) -> usize {
+    (estimate.sqrt().ceil() as usize).clamp(1, MAX_CURVE_SEGMENTS)
+}
+
+fn quad_point(p0: DevicePoint, p1: DevicePoint, p2: DevicePoint, t: f32) -> DevicePoint {
+    let mt = 1.0 - t;
+    add(
+        add(scale(p0, mt * mt), scale(p1, 2.0 * mt * t)),
+        scale(p2, t * t),
+    )
+}
+
+fn cubic_point(
+    p0: DevicePoint,
+    p1: DevicePoint,
+    p2: DevicePoint,
+    p3: DevicePoint,
+    t: f32,
+) -> DevicePoint {
+    let mt = 1.0 - t;
+    add(
+        add(scale(p0, mt * mt * mt), scale(p1, 3.0 * mt * mt * t)),
+        add(scale(p2, 3.0 * mt * t * t), scale(p3, t * t * t)),
+    )
+}
+
+fn signed_area(points: &[DevicePoint]) -> f32 {
+    let n = points.len();
+    (0..n)
+        .map(|i| cross(points[i], points[(i + 1) % n]))
+        .sum::<f32>()
+        * 0.5
+}
+
+fn point_in_triangle(p: DevicePoint, a: DevicePoint, b: DevicePoint, c: DevicePoint) -> bool {
+    let d1 = cross(sub(b, a), sub(p, a));
+    let d2 = cross(sub(c, b), sub(p, b));
+    let d3 = cross(sub(a, c), sub(p, c));
+    d1 >= 0.0 && d2 >= 0.0 && d3 >= 0.0
+}
+
+/// Unit normal to the left of the direction a → b.
+fn normal(a: DevicePoint, b: DevicePoint) -> DevicePoint {
+    let d = sub(b, a);
+    let len = length(d);
+    if len <= f32::EPSILON {
+        return DevicePoint::new(0.0, 0.0);
+    }
+    DevicePoint::new(-d.y / len, d.x / len)
+}
+
+fn add(a: DevicePoint, b: DevicePoint) -> DevicePoint {
+    DevicePoint::new(a.x + b.x, a.y + b.y)
+}
+
+fn sub(a: DevicePoint, b: DevicePoint) -> DevicePoint {
+    DevicePoint::new(a.x - b.x, a.y - b.y)
+}
+
+fn scale(a: DevicePoint, s: f32) -> DevicePoint {
+    DevicePoint::new(a.x * s, a.y * s)
+}
+
+fn cross(a: DevicePoint, b: DevicePoint) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+fn length(a: DevicePoint) -> f32 {
+    (a.x * a.x + a.y * a.y).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pt(x: f32, y: f32) -> DevicePoint {
+        DevicePoint::new(x, y)
+    }
+
+    fn triangle_area(tris: &[DevicePoint]) -> f32 {
+        tris.chunks(3)
+            .map(|t| cross(sub(t[1], t[0]), sub(t[2], t[0])).abs() * 0.5)
+            .sum()
+    }
+
+    #[test]
+    fn fills_square_with_two_triangles() {
+        let path = Path::new()
+            .move_to(pt(0.0, 0.0))
+            .line_to(pt(10.0, 0.0))
+            .line_to(pt(10.0, 10.0))
+            .line_to(pt(0.0, 10.0))
+            .close();
+        let tris = tessellate_fill(&path, PATH_TOLERANCE);
+        assert_eq!(tris.len(), 6);
+        assert!((triangle_area(&tris) - 100.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn fills_concave_polygon_without_overlap() {
+        // L-shape, area 75
+        let path = Path::new()
+            .move_to(pt(0.0, 0.0))
+            .line_to(pt(10.0, 0.0))
+            .line_to(pt(10.0, 5.0))
+            .line_to(pt(5.0, 5.0))
+            .line_to(pt(5.0, 10.0))
+            .line_to(pt(0.0, 10.0))
+            .close();
+        let tris = tessellate_fill(&path, PATH_TOLERANCE);
+        assert_eq!(tris.len(), 4 * 3);
+        assert!((triangle_area(&tris) - 75.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn winding_direction_does_not_matter() {
+        let path = Path::new()
+            .move_to(pt(0.0, 0.0))
+            .line_to(pt(0.0, 10.0))
+            .line_to(pt(10.0, 10.0))
+            .line_to(pt(10.0, 0.0))
+            .close();
+        let tris = tessellate_fill(&path, PATH_TOLERANCE);
+        assert!((triangle_area(&tris) - 100.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn curves_flatten_within_tolerance() {
+        let path = Path::new()
+            .move_to(pt(0.0, 0.0))
+            .quad_to(pt(50.0, 100.0), pt(100.0, 0.0));
+        let polylines = path.flatten(PATH_TOLERANCE);
+        assert_eq!(polylines.len(), 1);
+        let points = &polylines[0].points;
+        assert!(points.len() > 10, "curve should be subdivided");
+        assert_eq!(*points.last().unwrap(), pt(100.0, 0.0));
+
+        // Apex of the curve is at t = 0.5, y = 50.
+        let max_y = points.iter().map(|p| p.y).fold(0.0, f32::max);
+        assert!((max_y - 50.0).abs() < PATH_TOLERANCE);
+    }
+
+    #[test]
+    fn cubic_ends_at_target() {
+        let path = Path::new().move_to(pt(0.0, 0.0)).cubic_to(
+            pt(0.0, 50.0),
+            pt(100.0, 50.0),
+            pt(100.0, 0.0),
+        );
+        let points = &path.flatten(PATH_TOLERANCE)[0].points;
+        assert_eq!(*points.last().unwrap(), pt(100.0, 0.0));
+    }
+
+    #[test]
+    fn strokes_line_as_rectangle() {
+        let path = Path::polyline([pt(0.0, 0.0), pt(10.0, 0.0)]);
+        let tris = tessellate_stroke(&path, 2.0, PATH_TOLERANCE);
+        assert_eq!(tris.len(), 6);
+        assert!((triangle_area(&tris) - 20.0).abs() < 0.01);
+        assert!(tris.iter().all(|p| p.y.abs() <= 1.0));
+    }
+
+    #[test]
+    fn stroke_adds_joins_between_segments() {
+        let open = Path::polyline([pt(0.0, 0.0), pt(10.0, 0.0), pt(10.0, 10.0)]);
+        // Two segments plus one bevel join (two triangles).
+        assert_eq!(
+            tessellate_stroke(&open, 2.0, PATH_TOLERANCE).len(),
+            2 * 6 + 6
+        );
+
+        let closed = Path::polyline([pt(0.0, 0.0), pt(10.0, 0.0), pt(10.0, 10.0)]).close();
+        // Three segments and three joins.
+        assert_eq!(
+            tessellate_stroke(&closed, 2.0, PATH_TOLERANCE).len(),
+            3 * 6 + 3 * 6
+        );
+    }
+
+    #[test]
+    fn bounds_include_control_points() {
+        let path = Path::new()
+            .move_to(pt(0.0, 0.0))
+            .quad_to(pt(5.0, 20.0), pt(10.0, 0.0));
+        let bounds = path.bounds().unwrap();
+        assert_eq!((bounds.origin.x, bounds.origin.y), (0.0, 0.0));
+        assert_eq!((bounds.size.width, bounds.size.height), (10.0, 20.0));
+        assert!(Path::new().bounds().is_none());
+    }
+
+    #[test]
+    fn zero_width_stroke_is_empty() {
+        let path = Path::polyline([pt(0.0, 0.0), pt(10.0, 0.0)]);
+        assert!(tessellate_stroke(&path, 0.0, PATH_TOLERANCE).is_empty());
+    }
+
+    #[test]
+    fn separate_subpaths_fill_independently() {
+        let path = Path::new()
+            .move_to(pt(0.0, 0.0))
+            .line_to(pt(4.0, 0.0))
+            .line_to(pt(0.0, 4.0))
+            .close()
+            .move_to(pt(10.0, 10.0))
+            .line_to(pt(14.0, 10.0))
+            .line_to(pt(10.0, 14.0))
+            .close();
+        assert_eq!(path.flatten(PATH_TOLERANCE).len(), 2);
+        let tris = tessellate_fill(&path, PATH_TOLERANCE);
+        assert!((triangle_area(&tris) - 16.0).abs() < 0.01);
+    }
+}
@@ -0,0 +1,342 @@
+//! Per-phase frame timing.
+//!
+//! The frame pipeline times its phases with [`scope`] guards, which add to
+//! the frame started by [`begin_frame`] on the current thread:
+//!
+//! ```ignore
+//! profiler::begin_frame();
+//! render_view(&mut root, &mut cx, &mut layout_engine, &mut hit_tree, size);
+//! renderer.render(&scene, &mut surface);
+//! let profile = profiler::end_frame().unwrap();
+//! println!("layout took {:?}", profile.phase(FramePhase::Layout));
+//! ```
+//!
+//! Outside a frame, scopes cost a thread-local lookup and record nothing.
+//! [`FrameProfiler`] keeps recent frames for averaging.
+//!
+//! With the `profile` feature, frames and phases go straight to Tracy through
+//! `tracy-client`: a `frame` zone from [`begin_frame`] to [`end_frame`] with a
+//! zone per phase scope inside it, and a frame mark from [`end_frame`], so the
+//! Tracy profiler shows a frame timeline with no subscriber or app
+//! instrumentation. The client starts with the first frame. The same frames
+//! and phases also open `tracing` spans, for whichever subscriber the app
+//! installs.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// A stage of producing a frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FramePhase {
+    /// Views building their element trees.
+    Render,
+    /// Requesting and computing layout.
+    Layout,
+    /// Elements painting into the scene.
+    Paint,
+    /// Laying out text. Happens during layout and paint, so it's counted
+    /// in those phases too.
+    TextShaping,
+    /// Turning the scene into GPU instance data.
+    InstanceBuild,
+    /// Encoding and submitting GPU work, or rasterizing on the CPU for the
+    /// software renderer.
+    Submit,
+}
+
+impl FramePhase {
+    pub const ALL: [FramePhase; 6] = [
+        FramePhase::Render,
+        FramePhase::Layout,
+        FramePhase::Paint,
+        FramePhase::TextShaping,
+        FramePhase::InstanceBuild,
+        FramePhase::Submit,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            FramePhase::Render => "render",
+            FramePhase::Layout => "layout",
+            FramePhase::Paint => "paint",
+            FramePhase::TextShaping => "text_shaping",
+            FramePhase::InstanceBuild => "instance_build",
+            FramePhase::Submit => "submit",
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    #[cfg(feature = "profile")]
+    fn span(self) -> tracing::Span {
+        // Span names must be literals, and profilers key zones by name.
+        match self {
+            FramePhase::Render => tracing::info_span!("render"),
+            FramePhase::Layout => tracing::info_span!("layout"),
+            FramePhase::Paint => tracing::info_span!("paint"),
+            FramePhase::TextShaping => tracing::info_span!("text_shaping"),
+            FramePhase::InstanceBuild => tracing::info_span!("instance_build"),
+            FramePhase::Submit => tracing::info_span!("submit"),
+        }
+    }
+}
+
+/// Open a Tracy zone named `name` until the returned span is dropped.
+#[cfg(feature = "profile")]
+fn tracy_zone(name: &str) -> tracy_client::Span {
+    tracy_client::Client::start().span_alloc(Some(name), "", file!(), line!(), 0)
+}
+
+/// Time spent in each phase of one frame.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FrameProfile {
+    /// From [`begin_frame`] to [`end_frame`].
+    pub total: Duration,
+    phases: [Duration; FramePhase::ALL.len()],
+}
+
+impl FrameProfile {
+    pub fn phase(&self, phase: FramePhase) -> Duration {
+        self.phases[phase.index()]
+    }
+
+    /// Every phase and its time, in pipeline order.
+    pub fn phases(&self) -> impl Iterator<Item = (FramePhase, Duration)> + '_ {
+        FramePhase::ALL
+            .iter()
+            .map(|&phase| (phase, self.phase(phase)))
+    }
+
+    pub fn record(&mut self, phase: FramePhase, time: Duration) {
+        self.phases[phase.index()] += time;
+    }
+
+    /// The mean of `profiles`, phase by phase.
+    pub fn average<'a>(profiles: impl IntoIterator<Item = &'a FrameProfile>) -> FrameProfile {
+        let mut sum = FrameProfile::default();
+        let mut count = 0u32;
+        for profile in profiles {
+            sum.total += profile.total;
+            for (phase, time) in profile.phases() {
+                sum.record(phase, time);
+            }
+            count += 1;
+        }
+        if count > 0 {
+            sum.total /= count;
+            for time in &mut sum.phases {
+                *time /= count;
+            }
+        }
+        sum
+    }
+}
+
+struct ActiveFrame {
+    start: Instant,
+    profile: FrameProfile,
+    #[cfg(feature = "profile")]
+    span: tracing::span::EnteredSpan,
+    #[cfg(feature = "profile")]
+    zone: tracy_client::Span,
+}
+
+thread_local! {
+    static FRAME: RefCell<Option<ActiveFrame>> = const { RefCell::new(None) };
+}
+
+/// Start timing a frame on this thread, discarding any unfinished one.
+pub fn begin_frame() {
+    FRAME.with(|frame| {
+        let mut frame = frame.borrow_mut();
+        // Close the abandoned frame's span before opening the next one.
+        frame.take();
+        *frame = Some(ActiveFrame {
+            start: Instant::now(),
+            profile: FrameProfile::default(),
+            #[cfg(feature = "profile")]
+            span: tracing::info_span!("frame").entered(),
+            #[cfg(feature = "profile")]
+            zone: tracy_zone("frame"),
+        })
+    });
+}
+
+/// Finish the frame started by [`begin_frame`] and return its profile.
+pub fn end_frame() -> Option<FrameProfile> {
+    FRAME.with(|frame| {
+        frame.borrow_mut().take().map(|active| {
+            let profile = FrameProfile {
+                total: active.start.elapsed(),
+                ..active.profile
+            };
+            #[cfg(feature = "profile")]
+            {
+                tracing::info!(
+                    parent: active.span.id(),
+                    total_us = profile.total.as_micros() as u64,
+                    "frame end"
+                );
+                drop(active.zone);
+                tracy_client::Client::start().frame_mark();
+            }
+            profile
+        })
+    })
+}
+
+/// Add `time` to `phase` of the current frame, if one is being timed.
+pub fn record(phase: FramePhase, time: Duration) {
+    FRAME.with(|frame| {
+        if let Some(active) = frame.borrow_mut().as_mut() {
+            active.profile.record(phase, time);
+        }
+    });
+}
+
+/// Time `phase` until the returned guard is dropped.
+pub fn scope(phase: FramePhase) -> PhaseScope {
+    PhaseScope {
+        phase,
+        start: Instant::now(),
+        #[cfg(feature = "profile")]
+        _zone: phase.span().entered(),
+        #[cfg(feature = "profile")]
+        _tracy_zone: tracy_zone(phase.name()),
+    }
+}
+
+/// Records its phase's time when dropped; see [`scope`].
+#[must_use = "the phase is timed until the scope is dropped"]
+pub struct PhaseScope {
+    phase: FramePhase,
+    start: Instant,
+    #[cfg(feature = "profile")]
+    _zone: tracing::span::EnteredSpan,
+    #[cfg(feature = "profile")]
+    _tracy_zone: tracy_client::Span,
+}
+
+impl Drop for PhaseScope {
+    fn drop(&mut self) {
+        record(self.phase, self.start.elapsed());
+    }
+}
+
+/// How many frames [`FrameProfiler`] keeps by default, about two seconds'
+/// worth at 60 fps.
+pub const DEFAULT_PROFILE_HISTORY: usize = 120;
+
+/// Times frames and keeps the most recent profiles.
+#[derive(Clone, Debug)]
+pub struct FrameProfiler {
+    recent: VecDeque<FrameProfile>,
+    capacity: usize,
+}
+
+impl Default for FrameProfiler {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_PROFILE_HISTORY)
+    }
+}
+
+impl FrameProfiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A profiler keeping the last `capacity` frames.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            recent: VecDeque::with_capacity(capacity),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Start timing a frame; see [`begin_frame`].
+    pub fn begin_frame(&mut self) {
+        begin_frame();
+    }
+
+    /// Finish timing the frame and keep its profile.
+    pub fn end_frame(&mut self) -> Option<FrameProfile> {
+        let profile = end_frame()?;
+        self.push(profile);
+        Some(profile)
+    }
+
+    /// Keep a profile timed elsewhere.
+    pub fn push(&mut self, profile: FrameProfile) {
+        if self.recent.len() == self.capacity {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(profile);
+    }
+
+    /// The most recent frame.
+    pub fn last(&self) -> Option<&FrameProfile> {
+        self.recent.back()
+    }
+
+    /// Kept frames, oldest first.
+    pub fn recent(&self) -> impl DoubleEndedIterator<Item = &FrameProfile> {
+        self.recent.iter()
+    }
+
+    /// The mean of the last `frames` frames.
+    pub fn average(&self, frames: usize) -> FrameProfile {
+        FrameProfile::average(self.recent.iter().rev().take(frames))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scopes_add_to_the_current_frame() {
+        begin_frame();
+        {
+            let _layout = scope(FramePhase::Layout);
+            record(FramePhase::TextShaping, Duration::from_millis(2));
+        }
+        record(FramePhase::TextShaping, Duration::from_millis(3));
+        let profile = end_frame().unwrap();
+
+        assert_eq!(
+            profile.phase(FramePhase::TextShaping),
+            Duration::from_millis(5)
+        );
+        assert!(profile.phase(FramePhase::Layout) > Duration::ZERO);
+        assert_eq!(profile.phase(FramePhase::Submit), Duration::ZERO);
+        assert!(profile.total >= profile.phase(FramePhase::Layout));
+    }
+
+    #[test]
+    fn nothing_is_recorded_outside_a_frame() {
+        record(FramePhase::Paint, Duration::from_millis(1));
+        assert_eq!(end_frame(), None);
+    }
+
+    #[test]
+    fn profiler_averages_recent_frames() {
+        let mut profiler = FrameProfiler::with_capacity(2);
+        for ms in [100, 2, 4] {
+            let mut profile = FrameProfile {
+                total: Duration::from_millis(ms),
+                ..Default::default()
+            };
+            profile.record(FramePhase::Paint, Duration::from_millis(ms));
+            profiler.push(profile);
+        }
+
+        assert_eq!(profiler.recent().count(), 2);
+        let average = profiler.average(10);
+        assert_eq!(average.total, Duration::from_millis(3));
+        assert_eq!(average.phase(FramePhase::Paint), Duration::from_millis(3));
+        assert_eq!(profiler.average(1).total, Duration::from_millis(4));
+    }
+}
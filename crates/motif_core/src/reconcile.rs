@@ -0,0 +1,212 @@
+//! Stable element identity across frames.
+//!
+//! Element trees are rebuilt every frame, so anything that outlives a frame
+//! (element state, focus, animations) needs a way to find "the same" element
+//! again. Each element gets a [`GlobalElementId`] derived from its parent's id
+//! and its [`ChildIdentity`] among its siblings:
+//!
+//! - children given a [`key`](crate::IntoElement::key) are identified by it,
+//!   so they keep their id when siblings are inserted, removed or reordered;
+//! - unkeyed children are identified by their position among the *unkeyed*
+//!   siblings, which is stable as long as only keyed siblings change.
+//!
+//! A [`Reconciler`] records the ids seen each frame and reports which ones
+//! appeared and disappeared, so retained state can be created and dropped.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+use crate::ArcStr;
+
+/// A caller-chosen identity for a child element, unique among its siblings.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ElementKey {
+    Name(ArcStr),
+    Number(u64),
+}
+
+// Keys are rebuilt with the same names every frame, so intern them.
+impl From<&str> for ElementKey {
+    fn from(name: &str) -> Self {
+        ElementKey::Name(ArcStr::intern(name))
+    }
+}
+
+impl From<String> for ElementKey {
+    fn from(name: String) -> Self {
+        ElementKey::Name(name.into())
+    }
+}
+
+impl From<ArcStr> for ElementKey {
+    fn from(name: ArcStr) -> Self {
+        ElementKey::Name(name)
+    }
+}
+
+impl From<u64> for ElementKey {
+    fn from(number: u64) -> Self {
+        ElementKey::Number(number)
+    }
+}
+
+impl From<usize> for ElementKey {
+    fn from(number: usize) -> Self {
+        ElementKey::Number(number as u64)
+    }
+}
+
+/// How a child is told apart from its siblings.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ChildIdentity {
+    /// An explicit key. `occurrence` is non-zero only for duplicate keys.
+    Key { key: ElementKey, occurrence: usize },
+    /// Position among the unkeyed siblings.
+    Index(usize),
+}
+
+/// Identities for a list of children, given each child's key.
+pub fn child_identities<'a>(
+    keys: impl IntoIterator<Item = Option<&'a ElementKey>>,
+) -> Vec<ChildIdentity> {
+    let mut seen: HashMap<&ElementKey, usize> = HashMap::new();
+    let mut unkeyed = 0;
+    keys.into_iter()
+        .map(|key| match key {
+            Some(key) => {
+                let count = seen.entry(key).or_insert(0);
+                let occurrence = *count;
+                *count += 1;
+                ChildIdentity::Key {
+                    key: key.clone(),
+                    occurrence,
+                }
+            }
+            None => {
+                unkeyed += 1;
+                ChildIdentity::Index(unkeyed - 1)
+            }
+        })
+        .collect()
+}
+
+/// An element's identity within the whole tree, stable across frames.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct GlobalElementId(u64);
+
+impl GlobalElementId {
+    /// The id of the root of a tree.
+    pub const ROOT: GlobalElementId = GlobalElementId(0);
+
+    /// The id of the child of `self` identified by `identity`.
+    pub fn child(self, identity: &ChildIdentity) -> Self {
+        let mut hasher = DefaultHasher::new();
+        self.0.hash(&mut hasher);
+        identity.hash(&mut hasher);
+        GlobalElementId(hasher.finish())
+    }
+}
+
+/// What changed between two frames.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FrameDiff {
+    /// Ids seen this frame but not the last.
+    pub added: Vec<GlobalElementId>,
+    /// Ids seen last frame but not this one.
+    pub removed: Vec<GlobalElementId>,
+}
+
+/// Tracks which elements exist from one frame to the next.
+///
+/// Pass it to [`LayoutContext::with_reconciler`](crate::LayoutContext::with_reconciler)
+/// for the layout pass, then call [`finish_frame`](Self::finish_frame).
+#[derive(Debug, Default)]
+pub struct Reconciler {
+    previous: HashSet<GlobalElementId>,
+    current: HashSet<GlobalElementId>,
+    /// Current ids in first-seen order, for a deterministic diff.
+    order: Vec<GlobalElementId>,
+}
+
+impl Reconciler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record that `id` is part of the frame being built.
+    pub fn record(&mut self, id: GlobalElementId) {
+        if self.current.insert(id) {
+            self.order.push(id);
+        }
+    }
+
+    /// Whether `id` existed in the last finished frame.
+    pub fn existed(&self, id: GlobalElementId) -> bool {
+        self.previous.contains(&id)
+    }
+
+    /// End the frame, returning what changed since the last one.
+    pub fn finish_frame(&mut self) -> FrameDiff {
+        let added = self
+            .order
+            .iter()
+            .copied()
+            .filter(|id| !self.previous.contains(id))
+            .collect();
+        let mut removed: Vec<_> = self
+            .previous
+            .iter()
+            .copied()
+            .filter(|id| !self.current.contains(id))
+            .collect();
+        removed.sort_by_key(|id| id.0);
+
+        self.previous = std::mem::take(&mut self.current);
+        self.order.clear();
+        FrameDiff { added, removed }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(keys: &[Option<&str>]) -> Vec<GlobalElementId> {
+        let keys: Vec<Option<ElementKey>> = keys.iter().map(|k| k.map(ElementKey::from)).collect();
+        child_identities(keys.iter().map(Option::as_ref))
+            .iter()
+            .map(|identity| GlobalElementId::ROOT.child(identity))
+            .collect()
+    }
+
+    #[test]
+    fn keyed_children_survive_insertion() {
+        let before = ids(&[Some("a"), Some("b")]);
+        let after = ids(&[Some("new"), Some("a"), Some("b")]);
+        assert_eq!(after[1], before[0]);
+        assert_eq!(after[2], before[1]);
+    }
+
+    #[test]
+    fn unkeyed_children_ignore_keyed_siblings() {
+        let before = ids(&[None, None]);
+        let after = ids(&[Some("header"), None, None]);
+        assert_eq!(&after[1..], &before[..]);
+    }
+
+    #[test]
+    fn reconciler_reports_added_and_removed() {
+        let mut reconciler = Reconciler::new();
+        let first = ids(&[Some("a"), Some("b")]);
+        first.iter().for_each(|&id| reconciler.record(id));
+        assert_eq!(reconciler.finish_frame().added, first);
+
+        let second = ids(&[Some("b"), Some("c")]);
+        second.iter().for_each(|&id| reconciler.record(id));
+        let diff = reconciler.finish_frame();
+        assert_eq!(diff.added, vec![second[1]]);
+        assert_eq!(diff.removed, vec![first[0]]);
+        assert!(reconciler.existed(second[0]));
+    }
+}
@@ -1,6 +1,35 @@
 //! Renderer trait for backend abstraction.
 
 use crate::Scene;
+use palette::Srgba;
+use std::time::Duration;
+
+/// What a renderer draws for a scene.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum RenderMode {
+    /// The scene as it should look.
+    #[default]
+    Normal,
+    /// Each pixel colored by how many primitives were drawn over it, from
+    /// black for none through blue, green, yellow and orange to red for
+    /// five or more. See [`overdraw_color`].
+    Overdraw,
+}
+
+/// Heat color for a pixel drawn `count` times in [`RenderMode::Overdraw`].
+///
+/// Mirrored by `overdraw_fragment_main` in the Metal shaders.
+pub fn overdraw_color(count: u32) -> Srgba {
+    let [r, g, b] = match count {
+        0 => [0.0, 0.0, 0.0],
+        1 => [0.1, 0.2, 0.8],
+        2 => [0.1, 0.7, 0.2],
+        3 => [0.95, 0.85, 0.1],
+        4 => [1.0, 0.5, 0.0],
+        _ => [0.9, 0.1, 0.1],
+    };
+    Srgba::new(r, g, b, 1.0)
+}
 
 /// Backend-agnostic renderer.
 pub trait Renderer {
@@ -9,6 +38,16 @@ pub trait Renderer {
 
     /// Render the scene to the surface.
     fn render(&mut self, scene: &Scene, surface: &mut Self::Surface);
+
+    /// GPU execution time of the most recently completed frame.
+    ///
+    /// Unlike timing `render` on the CPU, this excludes encoding and submit
+    /// cost. Frames complete asynchronously, so the value usually trails the
+    /// last `render` call by a frame or two. `None` if the backend can't
+    /// measure it or no frame has completed yet.
+    fn last_frame_gpu_time(&self) -> Option<Duration> {
+        None
+    }
 }
 
 /// Debug renderer that counts primitives without GPU.
@@ -1,7 +1,10 @@
 //! Scene holds primitives for rendering.
 
-use crate::{Corners, DevicePoint, DeviceRect, Edges, FontData};
+use crate::{Corners, DevicePoint, DeviceRect, Edges, FontData, Path, PositionedGlyph};
 use palette::Srgba;
+use std::ops::Range;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 
 /// A filled/stroked rectangle with optional rounded corners.
 #[derive(Clone, Debug)]
@@ -13,6 +16,10 @@ pub struct Quad {
     pub corner_radii: Corners<f32>,
     /// Optional clip bounds in device pixels. Fragments outside are discarded.
     pub clip_bounds: Option<DeviceRect>,
+    /// Optional gradient fill. When set it replaces `background`.
+    pub gradient: Option<Gradient>,
+    /// Optional drop shadow, drawn immediately beneath this quad.
+    pub shadow: Option<Shadow>,
 }
 
 impl Quad {
@@ -24,12 +31,244 @@ impl Quad {
             border_widths: Edges::default(),
             corner_radii: Corners::default(),
             clip_bounds: None,
+            gradient: None,
+            shadow: None,
         }
     }
 }
 
-/// A positioned glyph within a text run.
+/// A blurred drop shadow following a quad's rounded-rect outline.
+///
+/// Values are in device pixels, like the rest of the scene.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Shadow {
+    /// Offset of the shadow from the quad.
+    pub offset: (f32, f32),
+    /// Blur radius. Matches CSS `box-shadow`: the Gaussian sigma is half of it.
+    pub blur_radius: f32,
+    /// Amount the shadow grows (or shrinks, if negative) before blurring.
+    pub spread: f32,
+    pub color: Srgba,
+}
+
+impl Shadow {
+    pub fn new(offset: (f32, f32), blur_radius: f32, color: impl Into<Srgba>) -> Self {
+        Self {
+            offset,
+            blur_radius,
+            spread: 0.0,
+            color: color.into(),
+        }
+    }
+
+    pub fn with_spread(mut self, spread: f32) -> Self {
+        self.spread = spread;
+        self
+    }
+}
+
+/// Maximum number of color stops a gradient can have on the GPU.
+pub const MAX_GRADIENT_STOPS: usize = 4;
+
+/// A color at a position along a gradient.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GradientStop {
+    /// Position along the gradient, 0.0 to 1.0.
+    pub offset: f32,
+    pub color: Srgba,
+}
+
+impl GradientStop {
+    pub fn new(offset: f32, color: impl Into<Srgba>) -> Self {
+        Self {
+            offset,
+            color: color.into(),
+        }
+    }
+}
+
+/// Shape of a gradient fill.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GradientKind {
+    /// Linear gradient along `angle` radians. 0 runs left to right, π/2 top
+    /// to bottom. The gradient line spans the quad's extent in that direction.
+    Linear { angle: f32 },
+    /// Elliptical radial gradient. `center` and `radius` are in unit quad
+    /// coordinates, so a radius of 0.5 from the center reaches the edges.
+    Radial { center: (f32, f32), radius: f32 },
+}
+
+/// A gradient fill for a quad.
+///
+/// Colors are interpolated in linear space. Stops beyond
+/// [`MAX_GRADIENT_STOPS`] are ignored by the renderer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gradient {
+    pub kind: GradientKind,
+    pub stops: Vec<GradientStop>,
+}
+
+impl Gradient {
+    /// Linear gradient from `from` to `to` along `angle` radians.
+    pub fn linear(angle: f32, from: impl Into<Srgba>, to: impl Into<Srgba>) -> Self {
+        Self {
+            kind: GradientKind::Linear { angle },
+            stops: vec![GradientStop::new(0.0, from), GradientStop::new(1.0, to)],
+        }
+    }
+
+    /// Radial gradient from `inner` at the quad's center to `outer` at its edges.
+    pub fn radial(inner: impl Into<Srgba>, outer: impl Into<Srgba>) -> Self {
+        Self {
+            kind: GradientKind::Radial {
+                center: (0.5, 0.5),
+                radius: 0.5,
+            },
+            stops: vec![GradientStop::new(0.0, inner), GradientStop::new(1.0, outer)],
+        }
+    }
+
+    /// Replace the color stops. Offsets should be ascending.
+    pub fn with_stops(mut self, stops: impl IntoIterator<Item = GradientStop>) -> Self {
+        self.stops = stops.into_iter().collect();
+        self
+    }
+}
+
+/// Straight-alpha RGBA8 pixels for an image, cheap to clone between scenes.
+///
+/// Every `ImageData` gets a unique id when created. Renderers upload the
+/// pixels once per id and drop the GPU copy after the image stops appearing
+/// in scenes, so callers just keep the `ImageData` around while it's in use.
 #[derive(Clone, Debug)]
+pub struct ImageData {
+    id: u64,
+    width: u32,
+    height: u32,
+    pixels: Arc<[u8]>,
+}
+
+impl ImageData {
+    /// Wrap RGBA8 pixels, row-major with no row padding.
+    ///
+    /// Returns `None` if `pixels` isn't exactly `width * height * 4` bytes.
+    pub fn new(width: u32, height: u32, pixels: impl Into<Arc<[u8]>>) -> Option<Self> {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+        let pixels = pixels.into();
+        if pixels.len() != width as usize * height as usize * 4 {
+            return None;
+        }
+        Some(Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    /// Unique id, stable across clones.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+}
+
+/// An image drawn into a rectangle.
+#[derive(Clone, Debug)]
+pub struct ImageQuad {
+    pub bounds: DeviceRect,
+    pub image: ImageData,
+    /// Source rect in normalized texture coordinates: u_min, v_min, u_max, v_max.
+    pub uv: [f32; 4],
+    /// Corner radii in device pixels. Pixels outside the rounded rect are
+    /// discarded.
+    pub corner_radii: Corners<f32>,
+    /// Multiplier applied to the image's alpha.
+    pub opacity: f32,
+    /// Optional clip bounds in device pixels. Fragments outside are discarded.
+    pub clip_bounds: Option<DeviceRect>,
+}
+
+impl ImageQuad {
+    pub fn new(bounds: DeviceRect, image: ImageData) -> Self {
+        Self {
+            bounds,
+            image,
+            uv: [0.0, 0.0, 1.0, 1.0],
+            corner_radii: Corners::default(),
+            opacity: 1.0,
+            clip_bounds: None,
+        }
+    }
+}
+
+/// Stroke style for a path.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Stroke {
+    /// Line width in device pixels.
+    pub width: f32,
+    pub color: Srgba,
+}
+
+impl Stroke {
+    pub fn new(width: f32, color: impl Into<Srgba>) -> Self {
+        Self {
+            width,
+            color: color.into(),
+        }
+    }
+}
+
+/// A vector path drawn with a fill, a stroke, or both (fill first).
+#[derive(Clone, Debug)]
+pub struct PathPrimitive {
+    pub path: Path,
+    pub fill: Option<Srgba>,
+    pub stroke: Option<Stroke>,
+    /// Optional clip bounds in device pixels. Fragments outside are discarded.
+    pub clip_bounds: Option<DeviceRect>,
+}
+
+impl PathPrimitive {
+    pub fn fill(path: Path, color: impl Into<Srgba>) -> Self {
+        Self {
+            path,
+            fill: Some(color.into()),
+            stroke: None,
+            clip_bounds: None,
+        }
+    }
+
+    pub fn stroke(path: Path, stroke: Stroke) -> Self {
+        Self {
+            path,
+            fill: None,
+            stroke: Some(stroke),
+            clip_bounds: None,
+        }
+    }
+
+    /// Add a stroke on top of the fill.
+    pub fn with_stroke(mut self, stroke: Stroke) -> Self {
+        self.stroke = Some(stroke);
+        self
+    }
+}
+
+/// A positioned glyph within a text run.
+#[derive(Clone, Copy, Debug)]
 pub struct GlyphInstance {
     /// Glyph ID in the font.
     pub glyph_id: u32,
@@ -37,9 +276,26 @@ pub struct GlyphInstance {
     pub x: f32,
     /// Y offset from run baseline.
     pub y: f32,
+    /// Horizontal advance from the layout, or 0.0 if unknown.
+    pub advance: f32,
+}
+
+impl From<&PositionedGlyph> for GlyphInstance {
+    fn from(glyph: &PositionedGlyph) -> Self {
+        Self {
+            glyph_id: glyph.id,
+            x: glyph.x,
+            y: glyph.y,
+            advance: glyph.advance,
+        }
+    }
 }
 
 /// A run of glyphs to render as text.
+///
+/// The glyphs themselves live in the [`Scene`]'s shared glyph buffer, so a
+/// text-heavy frame fills one growing list instead of allocating per run.
+/// Read them with [`Scene::glyphs`].
 #[derive(Clone, Debug)]
 pub struct TextRun {
     /// Origin point (baseline start) in device pixels.
@@ -52,8 +308,12 @@ pub struct TextRun {
     pub font: FontData,
     /// Normalized coordinates for variable fonts.
     pub normalized_coords: Vec<i16>,
-    /// Glyphs to render.
-    pub glyphs: Vec<GlyphInstance>,
+    /// This run's glyphs, as a range of its scene's glyph buffer.
+    pub glyphs: Range<usize>,
+    /// Ascent of the line this run was laid out on, in device pixels.
+    pub ascent: f32,
+    /// Descent of the line this run was laid out on, in device pixels.
+    pub descent: f32,
 }
 
 impl TextRun {
@@ -69,25 +329,50 @@ impl TextRun {
             font_size,
             font,
             normalized_coords: Vec::new(),
-            glyphs: Vec::new(),
+            glyphs: 0..0,
+            ascent: 0.0,
+            descent: 0.0,
         }
     }
 
-    pub fn with_normalized_coords(mut self, coords: Vec<i16>) -> Self {
-        self.normalized_coords = coords;
+    /// Record the line metrics the run was laid out with, for debug tooling.
+    pub fn with_line_metrics(mut self, ascent: f32, descent: f32) -> Self {
+        self.ascent = ascent;
+        self.descent = descent;
         self
     }
 
-    pub fn push_glyph(&mut self, glyph_id: u32, x: f32, y: f32) {
-        self.glyphs.push(GlyphInstance { glyph_id, x, y });
+    pub fn with_normalized_coords(mut self, coords: Vec<i16>) -> Self {
+        self.normalized_coords = coords;
+        self
     }
 }
 
 /// Holds all primitives for a frame, ready for rendering.
-#[derive(Default)]
 pub struct Scene {
+    background: Srgba,
     quads: Vec<Quad>,
+    paths: Vec<PathPrimitive>,
+    images: Vec<ImageQuad>,
     text_runs: Vec<TextRun>,
+    /// Primitives drawn above everything else, like tooltips and menus.
+    overlay: Option<Box<Scene>>,
+    /// Glyphs for every text run, each run owning a contiguous range.
+    glyphs: Vec<GlyphInstance>,
+}
+
+impl Default for Scene {
+    fn default() -> Self {
+        Self {
+            background: Srgba::new(0.0, 0.0, 0.0, 1.0),
+            quads: Vec::new(),
+            paths: Vec::new(),
+            images: Vec::new(),
+            text_runs: Vec::new(),
+            overlay: None,
+            glyphs: Vec::new(),
+        }
+    }
 }
 
 impl Scene {
@@ -95,10 +380,54 @@ impl Scene {
         Self::default()
     }
 
-    /// Clear all primitives, reusing allocations.
+    /// Color the frame is cleared to before drawing. Defaults to opaque black.
+    ///
+    /// A translucent background leaves the window see-through, for
+    /// overlay-style windows. Kept across [`clear`](Self::clear).
+    pub fn set_background(&mut self, color: impl Into<Srgba>) {
+        self.background = color.into();
+    }
+
+    pub fn background(&self) -> Srgba {
+        self.background
+    }
+
+    /// Clear all primitives, reusing allocations. The background is kept.
     pub fn clear(&mut self) {
         self.quads.clear();
+        self.paths.clear();
+        self.images.clear();
         self.text_runs.clear();
+        self.glyphs.clear();
+        if let Some(overlay) = &mut self.overlay {
+            overlay.clear();
+        }
+    }
+
+    /// Whether the scene (including its overlay) has nothing to draw.
+    pub fn is_empty(&self) -> bool {
+        self.quads.is_empty()
+            && self.paths.is_empty()
+            && self.images.is_empty()
+            && self.text_runs.is_empty()
+            && self.overlay().is_none()
+    }
+
+    /// The layer drawn above every other primitive in this scene, if
+    /// anything was painted into it.
+    ///
+    /// Primitives are otherwise drawn grouped by kind (all quads, then all
+    /// text, ...), so content that must cover the whole scene, like a
+    /// tooltip, goes here instead.
+    pub fn overlay(&self) -> Option<&Scene> {
+        self.overlay
+            .as_deref()
+            .filter(|overlay| !overlay.is_empty())
+    }
+
+    /// The overlay layer, for painting into.
+    pub fn overlay_mut(&mut self) -> &mut Scene {
+        self.overlay.get_or_insert_with(Box::default)
     }
 
     pub fn push_quad(&mut self, quad: Quad) {
@@ -109,14 +438,80 @@ impl Scene {
         &self.quads
     }
 
+    /// Quads in paint order, for tools that adjust a frame after it is
+    /// built, such as the debug server's `scene.patch`.
+    pub fn quads_mut(&mut self) -> &mut [Quad] {
+        &mut self.quads
+    }
+
     pub fn quad_count(&self) -> usize {
         self.quads.len()
     }
 
-    pub fn push_text_run(&mut self, text_run: TextRun) {
+    /// Add a path. Paths draw above quads and below images and text.
+    pub fn push_path(&mut self, path: PathPrimitive) {
+        self.paths.push(path);
+    }
+
+    pub fn paths(&self) -> &[PathPrimitive] {
+        &self.paths
+    }
+
+    pub fn path_count(&self) -> usize {
+        self.paths.len()
+    }
+
+    /// Add an image. Images draw above quads and paths, and below text.
+    pub fn push_image(&mut self, image: ImageQuad) {
+        self.images.push(image);
+    }
+
+    pub fn images(&self) -> &[ImageQuad] {
+        &self.images
+    }
+
+    pub fn image_count(&self) -> usize {
+        self.images.len()
+    }
+
+    /// Add a text run. Its glyphs follow with [`push_glyph`](Self::push_glyph)
+    /// or [`push_positioned_glyph`](Self::push_positioned_glyph); any range
+    /// the run already had is replaced.
+    pub fn push_text_run(&mut self, mut text_run: TextRun) {
+        text_run.glyphs = self.glyphs.len()..self.glyphs.len();
         self.text_runs.push(text_run);
     }
 
+    /// Add a glyph to the most recently pushed text run.
+    pub fn push_glyph(&mut self, glyph_id: u32, x: f32, y: f32) {
+        self.push_glyph_instance(GlyphInstance {
+            glyph_id,
+            x,
+            y,
+            advance: 0.0,
+        });
+    }
+
+    /// Add a glyph from a text layout to the most recently pushed text run,
+    /// keeping its advance.
+    pub fn push_positioned_glyph(&mut self, glyph: &PositionedGlyph) {
+        self.push_glyph_instance(glyph.into());
+    }
+
+    fn push_glyph_instance(&mut self, glyph: GlyphInstance) {
+        let Some(run) = self.text_runs.last_mut() else {
+            debug_assert!(false, "glyph pushed before any text run");
+            return;
+        };
+        self.glyphs.push(glyph);
+        run.glyphs.end = self.glyphs.len();
+    }
+
+    /// The glyphs of `run`, which must be one of this scene's text runs.
+    pub fn glyphs(&self, run: &TextRun) -> &[GlyphInstance] {
+        &self.glyphs[run.glyphs.clone()]
+    }
+
     pub fn text_runs(&self) -> &[TextRun] {
         &self.text_runs
     }
@@ -124,4 +519,200 @@ impl Scene {
     pub fn text_run_count(&self) -> usize {
         self.text_runs.len()
     }
+
+    /// Move every primitive from `other` to the end of this scene, overlay
+    /// included, leaving `other` empty but keeping its allocations.
+    pub fn append(&mut self, other: &mut Scene) {
+        self.quads.append(&mut other.quads);
+        self.paths.append(&mut other.paths);
+        self.images.append(&mut other.images);
+        let offset = self.glyphs.len();
+        self.text_runs
+            .extend(other.text_runs.drain(..).map(|mut run| {
+                run.glyphs = run.glyphs.start + offset..run.glyphs.end + offset;
+                run
+            }));
+        self.glyphs.append(&mut other.glyphs);
+        if let Some(overlay) = other.overlay.as_deref_mut() {
+            if !overlay.is_empty() {
+                self.overlay_mut().append(overlay);
+            }
+        }
+    }
+}
+
+/// Builds a scene from independent regions painted on separate threads.
+///
+/// Each region gets its own [`Scene`] to paint into, so threads never share
+/// a primitive list; the regions are then appended to the target scene in
+/// index order, making the result the same as painting them one after
+/// another. The builder keeps the region scenes between frames to reuse
+/// their allocations.
+///
+/// ```ignore
+/// let mut builder = SceneBuilder::new();
+/// builder.build(&mut scene, panels.len(), |index, region| {
+///     let mut cx = DrawContext::new(region, scale);
+///     panels[index].paint(&mut cx);
+/// });
+/// ```
+///
+/// Spawning threads costs tens of microseconds, so this only pays off when
+/// regions hold thousands of primitives.
+#[derive(Default)]
+pub struct SceneBuilder {
+    regions: Vec<Scene>,
+}
+
+impl SceneBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Paint `count` regions with `paint(index, region)`, in parallel, and
+    /// append them to `scene` in index order.
+    pub fn build<F>(&mut self, scene: &mut Scene, count: usize, paint: F)
+    where
+        F: Fn(usize, &mut Scene) + Sync,
+    {
+        if self.regions.len() < count {
+            self.regions.resize_with(count, Scene::default);
+        }
+        let regions = &mut self.regions[..count];
+        let paint = &paint;
+        std::thread::scope(|scope| {
+            let mut regions = regions.iter_mut().enumerate();
+            // The calling thread paints the first region instead of idling.
+            let first = regions.next();
+            for (index, region) in regions {
+                scope.spawn(move || paint(index, region));
+            }
+            if let Some((index, region)) = first {
+                paint(index, region);
+            }
+        });
+        for region in regions {
+            scene.append(region);
+            region.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn background_defaults_to_black_and_survives_clear() {
+        let mut scene = Scene::new();
+        assert_eq!(scene.background(), Srgba::new(0.0, 0.0, 0.0, 1.0));
+
+        scene.set_background(Srgba::new(1.0, 1.0, 1.0, 0.0));
+        scene.clear();
+        assert_eq!(scene.background(), Srgba::new(1.0, 1.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn overlay_is_cleared_with_the_scene() {
+        let mut scene = Scene::new();
+        assert!(scene.overlay().is_none());
+
+        let bounds = DeviceRect::new(DevicePoint::new(0.0, 0.0), crate::DeviceSize::new(4.0, 4.0));
+        scene
+            .overlay_mut()
+            .push_quad(Quad::new(bounds, Srgba::new(1.0, 1.0, 1.0, 1.0)));
+        assert_eq!(scene.overlay().unwrap().quad_count(), 1);
+        assert!(!scene.is_empty());
+
+        scene.clear();
+        assert!(scene.overlay().is_none());
+        assert!(scene.is_empty());
+    }
+
+    #[test]
+    fn text_runs_share_the_scene_glyph_buffer() {
+        let font = FontData::new(parley::fontique::Blob::from(vec![0u8; 4]), 0);
+        let run = || {
+            TextRun::new(
+                DevicePoint::new(0.0, 0.0),
+                Srgba::new(1.0, 1.0, 1.0, 1.0),
+                16.0,
+                font.clone(),
+            )
+        };
+        let ids = |scene: &Scene, run: &TextRun| -> Vec<u32> {
+            scene.glyphs(run).iter().map(|g| g.glyph_id).collect()
+        };
+
+        let mut scene = Scene::new();
+        scene.push_text_run(run());
+        scene.push_glyph(1, 0.0, 0.0);
+        scene.push_glyph(2, 8.0, 0.0);
+        scene.push_text_run(run());
+        scene.push_text_run(run());
+        scene.push_glyph(3, 0.0, 0.0);
+
+        let runs = scene.text_runs();
+        assert_eq!(ids(&scene, &runs[0]), [1, 2]);
+        assert!(scene.glyphs(&runs[1]).is_empty());
+        assert_eq!(ids(&scene, &runs[2]), [3]);
+
+        // Appending rebases the other scene's ranges onto this buffer.
+        let mut other = Scene::new();
+        other.push_text_run(run());
+        other.push_glyph(4, 0.0, 0.0);
+        scene.append(&mut other);
+        assert_eq!(ids(&scene, &scene.text_runs()[3]), [4]);
+
+        let capacity = scene.glyphs.capacity();
+        scene.clear();
+        assert!(scene.glyphs.is_empty());
+        assert_eq!(scene.glyphs.capacity(), capacity);
+    }
+
+    #[test]
+    fn scene_builder_appends_regions_in_order() {
+        let quad = |x: f32| {
+            let bounds =
+                DeviceRect::new(DevicePoint::new(x, 0.0), crate::DeviceSize::new(1.0, 1.0));
+            Quad::new(bounds, Srgba::new(1.0, 1.0, 1.0, 1.0))
+        };
+        let mut builder = SceneBuilder::new();
+        let mut scene = Scene::new();
+        scene.push_quad(quad(-1.0));
+
+        for _ in 0..2 {
+            builder.build(&mut scene, 4, |index, region| {
+                for i in 0..3 {
+                    region.push_quad(quad((index * 3 + i) as f32));
+                }
+                if index == 2 {
+                    region.overlay_mut().push_quad(quad(100.0));
+                }
+            });
+        }
+
+        let xs: Vec<f32> = scene.quads().iter().map(|q| q.bounds.origin.x).collect();
+        let expected: Vec<f32> = std::iter::once(-1.0)
+            .chain((0..2).flat_map(|_| (0..12).map(|x| x as f32)))
+            .collect();
+        assert_eq!(xs, expected);
+        assert_eq!(scene.overlay().unwrap().quad_count(), 2);
+        // Regions are emptied for the next frame.
+        assert!(builder.regions.iter().all(Scene::is_empty));
+    }
+
+    #[test]
+    fn image_data_checks_pixel_length() {
+        assert!(ImageData::new(2, 2, vec![0u8; 16]).is_some());
+        assert!(ImageData::new(2, 2, vec![0u8; 15]).is_none());
+    }
+
+    #[test]
+    fn image_data_ids_are_unique_and_survive_clones() {
+        let a = ImageData::new(1, 1, vec![0u8; 4]).unwrap();
+        let b = ImageData::new(1, 1, vec![0u8; 4]).unwrap();
+        assert_ne!(a.id(), b.id());
+        assert_eq!(a.clone().id(), a.id());
+    }
 }
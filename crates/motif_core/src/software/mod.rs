@@ -0,0 +1,946 @@
+//! CPU software renderer.
+//!
+//! [`SoftwareRenderer`] rasterizes a [`Scene`] without a GPU, for CI, VMs and
+//! platforms Metal doesn't cover. It follows the Metal shaders closely:
+//! quads use the same rounded-rect SDF, border and gradient rules and the
+//! same analytic shadow blur, paths are filled from the same tessellation,
+//! glyphs come from the same [`GlyphCache`], and blending happens in linear
+//! light with the same blend equation. Coverage is sampled once per pixel
+//! center, like the GPU without MSAA.
+//!
+//! Frames render into a [`Framebuffer`], which can be read back as RGBA8 or
+//! shown in a window through a [`SoftwareSurface`].
+//!
+//! In [`RenderMode::Overdraw`] every blend into a pixel is counted, and the
+//! finished frame is replaced by the counts as heat colors.
+
+mod surface;
+
+pub use image::RgbaImage;
+pub use surface::SoftwareSurface;
+
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+use palette::Srgba;
+
+use crate::profiler::{self, FramePhase};
+use crate::{
+    overdraw_color, tessellate_fill, tessellate_stroke, DevicePoint, DeviceRect, GlyphCache,
+    GlyphInstance, Gradient, GradientKind, ImageData, ImageQuad, PathPrimitive, Quad, RenderMode,
+    Renderer, Scene, Shadow, TextRun, MAX_GRADIENT_STOPS, PATH_TOLERANCE,
+};
+
+/// Linear-light RGBA. Straight alpha for source colors, premultiplied in the
+/// framebuffer.
+type Rgba = [f32; 4];
+
+/// A frame in linear light with premultiplied alpha: the CPU counterpart of
+/// the GPU's sRGB render target.
+#[derive(Clone, Debug)]
+pub struct Framebuffer {
+    width: u32,
+    height: u32,
+    pixels: Vec<Rgba>,
+    /// Blends into each pixel, while rendering in [`RenderMode::Overdraw`].
+    overdraw: Option<Vec<u32>>,
+}
+
+impl Framebuffer {
+    /// A transparent black framebuffer.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![[0.0; 4]; width as usize * height as usize],
+            overdraw: None,
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Change the size, discarding the contents.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        *self = Self::new(width, height);
+    }
+
+    /// A pixel as sRGB-encoded, premultiplied RGBA8, the bytes a GPU readback
+    /// of the same frame would produce.
+    pub fn rgba8_at(&self, x: u32, y: u32) -> [u8; 4] {
+        let [r, g, b, a] = self.pixels[(y * self.width + x) as usize];
+        [
+            encode_srgb(r),
+            encode_srgb(g),
+            encode_srgb(b),
+            (a.clamp(0.0, 1.0) * 255.0).round() as u8,
+        ]
+    }
+
+    /// The whole frame as sRGB-encoded, premultiplied RGBA8, row-major with
+    /// no padding.
+    pub fn to_rgba8(&self) -> Vec<u8> {
+        (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+            .flat_map(|(x, y)| self.rgba8_at(x, y))
+            .collect()
+    }
+
+    fn clear(&mut self, color: Rgba) {
+        self.pixels.fill(color);
+    }
+
+    /// Blend a straight-alpha color over a pixel with the GPU pipelines'
+    /// blend state (source alpha / one minus source alpha).
+    fn blend(&mut self, x: usize, y: usize, [r, g, b, a]: Rgba) {
+        let index = y * self.width as usize + x;
+        if let Some(counts) = &mut self.overdraw {
+            counts[index] += 1;
+        }
+        let dst = &mut self.pixels[index];
+        let keep = 1.0 - a;
+        *dst = [
+            r * a + dst[0] * keep,
+            g * a + dst[1] * keep,
+            b * a + dst[2] * keep,
+            a + dst[3] * keep,
+        ];
+    }
+
+    /// Start counting blends per pixel.
+    fn begin_overdraw(&mut self) {
+        self.overdraw = Some(vec![0; self.pixels.len()]);
+    }
+
+    /// Stop counting and replace the frame with the counts as heat colors.
+    fn show_overdraw(&mut self) {
+        let Some(counts) = self.overdraw.take() else {
+            return;
+        };
+        for (pixel, count) in self.pixels.iter_mut().zip(counts) {
+            *pixel = premultiply(to_linear(overdraw_color(count)));
+        }
+    }
+
+    /// Pixels whose centers lie inside the rect, clamped to the framebuffer.
+    fn covered(&self, x: f32, y: f32, width: f32, height: f32) -> (Range<usize>, Range<usize>) {
+        let span = |start: f32, end: f32, limit: u32| {
+            let first = (start - 0.5).ceil().max(0.0) as usize;
+            let last = ((end - 0.5).ceil().max(0.0) as usize).min(limit as usize);
+            first..last.max(first)
+        };
+        (
+            span(x, x + width, self.width),
+            span(y, y + height, self.height),
+        )
+    }
+}
+
+/// Renders scenes on the CPU.
+pub struct SoftwareRenderer {
+    glyph_cache: GlyphCache,
+    /// Image pixels decoded to linear light, by image id.
+    images: HashMap<u64, LinearImage>,
+    render_mode: RenderMode,
+}
+
+impl SoftwareRenderer {
+    pub fn new() -> Self {
+        Self {
+            glyph_cache: GlyphCache::new(),
+            images: HashMap::new(),
+            render_mode: RenderMode::Normal,
+        }
+    }
+
+    pub fn render_mode(&self) -> RenderMode {
+        self.render_mode
+    }
+
+    /// Draw scenes normally or as an overdraw heat map.
+    pub fn set_render_mode(&mut self, mode: RenderMode) {
+        self.render_mode = mode;
+    }
+
+    /// Rasterize a scene into `framebuffer`, replacing its contents.
+    pub fn render_to_framebuffer(&mut self, scene: &Scene, framebuffer: &mut Framebuffer) {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("render", backend = "software").entered();
+        let _scope = profiler::scope(FramePhase::Submit);
+        framebuffer.clear(premultiply(to_linear(scene.background())));
+        if self.render_mode == RenderMode::Overdraw {
+            framebuffer.begin_overdraw();
+        }
+
+        let mut used = HashSet::new();
+        self.draw_scene(scene, framebuffer, &mut used);
+        framebuffer.show_overdraw();
+
+        // Images that weren't drawn this frame are decoded again if they
+        // come back.
+        self.images.retain(|id, _| used.contains(id));
+    }
+
+    /// Draw a scene's primitives, then its overlay on top.
+    fn draw_scene(
+        &mut self,
+        scene: &Scene,
+        framebuffer: &mut Framebuffer,
+        used: &mut HashSet<u64>,
+    ) {
+        // Same order as the GPU: quads (each after its shadow), paths,
+        // images, text.
+        for quad in scene.quads() {
+            if let Some(shadow) = &quad.shadow {
+                draw_shadow(framebuffer, quad, shadow);
+            }
+            draw_quad(framebuffer, quad);
+        }
+        for path in scene.paths() {
+            draw_path(framebuffer, path);
+        }
+        for image in scene.images() {
+            used.insert(image.image.id());
+            let texels = self
+                .images
+                .entry(image.image.id())
+                .or_insert_with(|| LinearImage::new(&image.image));
+            draw_image(framebuffer, image, texels);
+        }
+        for run in scene.text_runs() {
+            draw_text(framebuffer, &mut self.glyph_cache, run, scene.glyphs(run));
+        }
+        if let Some(overlay) = scene.overlay() {
+            self.draw_scene(overlay, framebuffer, used);
+        }
+    }
+
+    /// Render a scene offscreen and return the pixels, like
+    /// `MetalRenderer::render_to_image`.
+    ///
+    /// `size` is in logical pixels and `scale` is the scale factor the scene
+    /// was built with, so the image is `size * scale` device pixels.
+    pub fn render_to_image(&mut self, scene: &Scene, size: (f32, f32), scale: f32) -> RgbaImage {
+        let width = ((size.0 * scale).round() as u32).max(1);
+        let height = ((size.1 * scale).round() as u32).max(1);
+        let mut framebuffer = Framebuffer::new(width, height);
+        self.render_to_framebuffer(scene, &mut framebuffer);
+        RgbaImage::from_raw(width, height, framebuffer.to_rgba8())
+            .expect("pixel buffer matches image dimensions")
+    }
+}
+
+impl Default for SoftwareRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Renderer for SoftwareRenderer {
+    type Surface = SoftwareSurface;
+
+    fn render(&mut self, scene: &Scene, surface: &mut SoftwareSurface) {
+        self.render_to_framebuffer(scene, surface.framebuffer_mut());
+        // Like a missing drawable on the GPU, a failed present drops the frame.
+        let _ = surface.present();
+    }
+}
+
+fn draw_quad(framebuffer: &mut Framebuffer, quad: &Quad) {
+    let bounds = quad.bounds;
+    let size = (bounds.size.width, bounds.size.height);
+    let radii = corner_radii(quad);
+    let background = to_linear(quad.background);
+    let border = to_linear(quad.border_color);
+    let widths = &quad.border_widths;
+    let max_border = widths
+        .top
+        .max(widths.right)
+        .max(widths.bottom)
+        .max(widths.left);
+    let gradient = quad.gradient.as_ref().map(LinearGradient::new);
+
+    let (columns, rows) = framebuffer.covered(bounds.origin.x, bounds.origin.y, size.0, size.1);
+    for y in rows {
+        for x in columns.clone() {
+            let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+            if clipped(quad.clip_bounds, px, py) {
+                continue;
+            }
+            let pos = (px - bounds.origin.x, py - bounds.origin.y);
+            let dist = rounded_rect_sdf(pos, size, radii);
+            if dist > 0.0 {
+                continue;
+            }
+            let color = if dist > -max_border && border[3] > 0.0 {
+                border
+            } else if let Some(gradient) = &gradient {
+                gradient.evaluate(pos, size)
+            } else {
+                background
+            };
+            framebuffer.blend(x, y, color);
+        }
+    }
+}
+
+/// Mirrors `QuadInstance::from_shadow` and the shader's `shadow_alpha`.
+fn draw_shadow(framebuffer: &mut Framebuffer, quad: &Quad, shadow: &Shadow) {
+    // A hard shadow still gets half a pixel of blur for antialiasing.
+    let sigma = (shadow.blur_radius * 0.5).max(0.5);
+    let margin = 3.0 * sigma;
+
+    let width = quad.bounds.size.width + 2.0 * shadow.spread;
+    let height = quad.bounds.size.height + 2.0 * shadow.spread;
+    if width <= 0.0 || height <= 0.0 {
+        return;
+    }
+    let x = quad.bounds.origin.x + shadow.offset.0 - shadow.spread - margin;
+    let y = quad.bounds.origin.y + shadow.offset.1 - shadow.spread - margin;
+    let size = (width + 2.0 * margin, height + 2.0 * margin);
+    let radii = corner_radii(quad).map(|r| (r + shadow.spread).max(0.0));
+    let color = to_linear(shadow.color);
+
+    let (columns, rows) = framebuffer.covered(x, y, size.0, size.1);
+    for row in rows {
+        for column in columns.clone() {
+            let (px, py) = (column as f32 + 0.5, row as f32 + 0.5);
+            if clipped(quad.clip_bounds, px, py) {
+                continue;
+            }
+            let alpha = shadow_alpha((px - x, py - y), size, radii, sigma);
+            framebuffer.blend(
+                column,
+                row,
+                [color[0], color[1], color[2], color[3] * alpha],
+            );
+        }
+    }
+}
+
+fn draw_path(framebuffer: &mut Framebuffer, path: &PathPrimitive) {
+    if let Some(color) = path.fill {
+        let triangles = tessellate_fill(&path.path, PATH_TOLERANCE);
+        fill_triangles(framebuffer, &triangles, to_linear(color), path.clip_bounds);
+    }
+    if let Some(stroke) = path.stroke {
+        let triangles = tessellate_stroke(&path.path, stroke.width, PATH_TOLERANCE);
+        fill_triangles(
+            framebuffer,
+            &triangles,
+            to_linear(stroke.color),
+            path.clip_bounds,
+        );
+    }
+}
+
+/// Fill a triangle list, one triangle at a time like the GPU, so overlapping
+/// triangles blend twice there too.
+fn fill_triangles(
+    framebuffer: &mut Framebuffer,
+    points: &[DevicePoint],
+    color: Rgba,
+    clip: Option<DeviceRect>,
+) {
+    for triangle in points.chunks_exact(3) {
+        let (a, mut b, mut c) = (triangle[0], triangle[1], triangle[2]);
+        if edge(a, b, c) == 0.0 {
+            continue;
+        }
+        if edge(a, b, c) < 0.0 {
+            std::mem::swap(&mut b, &mut c);
+        }
+
+        let min_x = a.x.min(b.x).min(c.x);
+        let min_y = a.y.min(b.y).min(c.y);
+        let max_x = a.x.max(b.x).max(c.x);
+        let max_y = a.y.max(b.y).max(c.y);
+        let (columns, rows) = framebuffer.covered(min_x, min_y, max_x - min_x, max_y - min_y);
+        for y in rows {
+            for x in columns.clone() {
+                let p = DevicePoint::new(x as f32 + 0.5, y as f32 + 0.5);
+                let inside = [(b, c), (c, a), (a, b)]
+                    .iter()
+                    .all(|&(from, to)| covers_edge(from, to, p));
+                if inside && !clipped(clip, p.x, p.y) {
+                    framebuffer.blend(x, y, color);
+                }
+            }
+        }
+    }
+}
+
+/// Twice the signed area of `a`, `b`, `p`.
+fn edge(a: DevicePoint, b: DevicePoint, p: DevicePoint) -> f32 {
+    (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+}
+
+/// Whether `p` is on the inner side of the edge. Points exactly on an edge
+/// count for only one of the two triangles sharing it, so shared edges
+/// aren't blended twice.
+fn covers_edge(from: DevicePoint, to: DevicePoint, p: DevicePoint) -> bool {
+    let w = edge(from, to, p);
+    let (dx, dy) = (to.x - from.x, to.y - from.y);
+    w > 0.0 || (w == 0.0 && (dy > 0.0 || (dy == 0.0 && dx > 0.0)))
+}
+
+fn draw_image(framebuffer: &mut Framebuffer, image: &ImageQuad, texels: &LinearImage) {
+    let bounds = image.bounds;
+    let size = (bounds.size.width, bounds.size.height);
+    let radii = &image.corner_radii;
+    let radii = [
+        radii.top_left,
+        radii.top_right,
+        radii.bottom_right,
+        radii.bottom_left,
+    ];
+    let [u0, v0, u1, v1] = image.uv;
+    let (columns, rows) = framebuffer.covered(
+        bounds.origin.x,
+        bounds.origin.y,
+        bounds.size.width,
+        bounds.size.height,
+    );
+    for y in rows {
+        for x in columns.clone() {
+            let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+            if clipped(image.clip_bounds, px, py) {
+                continue;
+            }
+            let pos = (px - bounds.origin.x, py - bounds.origin.y);
+            if rounded_rect_sdf(pos, size, radii) > 0.0 {
+                continue;
+            }
+            let tx = (px - bounds.origin.x) / bounds.size.width;
+            let ty = (py - bounds.origin.y) / bounds.size.height;
+            let mut color = texels.sample(u0 + tx * (u1 - u0), v0 + ty * (v1 - v0));
+            color[3] *= image.opacity;
+            framebuffer.blend(x, y, color);
+        }
+    }
+}
+
+/// Mirrors the glyph placement in the Metal renderer's text pass.
+fn draw_text(
+    framebuffer: &mut Framebuffer,
+    glyph_cache: &mut GlyphCache,
+    run: &TextRun,
+    glyphs: &[GlyphInstance],
+) {
+    let color = to_linear(run.color);
+    for glyph in glyphs {
+        let Some(mask) = glyph_cache.rasterize(
+            &run.font,
+            &run.normalized_coords,
+            glyph.glyph_id,
+            run.font_size,
+        ) else {
+            continue;
+        };
+        if mask.width == 0 || mask.height == 0 {
+            continue;
+        }
+
+        let left = run.origin.x + glyph.x + mask.bearing_x as f32;
+        let top = run.origin.y + glyph.y - mask.bearing_y as f32;
+        let (columns, rows) = framebuffer.covered(left, top, mask.width as f32, mask.height as f32);
+        for y in rows {
+            for x in columns.clone() {
+                // Bilinear, like the atlas sampler.
+                let mx = x as f32 + 0.5 - left - 0.5;
+                let my = y as f32 + 0.5 - top - 0.5;
+                let coverage = bilinear(mx, my, |tx, ty| {
+                    if tx < 0 || ty < 0 || tx >= mask.width as i32 || ty >= mask.height as i32 {
+                        0.0
+                    } else {
+                        mask.data[(ty as u32 * mask.width + tx as u32) as usize] as f32 / 255.0
+                    }
+                });
+                if coverage > 0.0 {
+                    framebuffer.blend(x, y, [color[0], color[1], color[2], color[3] * coverage]);
+                }
+            }
+        }
+    }
+}
+
+/// Image pixels decoded to linear light, like an `RGBA8Unorm_sRGB` texture.
+struct LinearImage {
+    width: u32,
+    height: u32,
+    texels: Vec<Rgba>,
+}
+
+impl LinearImage {
+    fn new(image: &ImageData) -> Self {
+        let texels = image
+            .pixels()
+            .chunks_exact(4)
+            .map(|p| {
+                [
+                    decode_srgb(p[0]),
+                    decode_srgb(p[1]),
+                    decode_srgb(p[2]),
+                    p[3] as f32 / 255.0,
+                ]
+            })
+            .collect();
+        Self {
+            width: image.width(),
+            height: image.height(),
+            texels,
+        }
+    }
+
+    /// Bilinear sample with clamp-to-edge addressing.
+    fn sample(&self, u: f32, v: f32) -> Rgba {
+        if self.texels.is_empty() {
+            return [0.0; 4];
+        }
+        let texel = |x: i32, y: i32| {
+            let x = x.clamp(0, self.width as i32 - 1) as u32;
+            let y = y.clamp(0, self.height as i32 - 1) as u32;
+            self.texels[(y * self.width + x) as usize]
+        };
+        let (x, y) = (u * self.width as f32 - 0.5, v * self.height as f32 - 0.5);
+        std::array::from_fn(|channel| bilinear(x, y, |tx, ty| texel(tx, ty)[channel]))
+    }
+}
+
+/// Bilinear interpolation of `texel` around texel-space position `(x, y)`.
+fn bilinear(x: f32, y: f32, texel: impl Fn(i32, i32) -> f32) -> f32 {
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+    let (x0, y0) = (x0 as i32, y0 as i32);
+    let top = texel(x0, y0) * (1.0 - fx) + texel(x0 + 1, y0) * fx;
+    let bottom = texel(x0, y0 + 1) * (1.0 - fx) + texel(x0 + 1, y0 + 1) * fx;
+    top * (1.0 - fy) + bottom * fy
+}
+
+/// Gradient stops in linear light, laid out like `GradientInstance`.
+struct LinearGradient {
+    kind: GradientKind,
+    offsets: [f32; MAX_GRADIENT_STOPS],
+    colors: [Rgba; MAX_GRADIENT_STOPS],
+}
+
+impl LinearGradient {
+    fn new(gradient: &Gradient) -> Self {
+        let mut offsets = [0.0; MAX_GRADIENT_STOPS];
+        let mut colors = [[0.0; 4]; MAX_GRADIENT_STOPS];
+        let stops = &gradient.stops[..gradient.stops.len().min(MAX_GRADIENT_STOPS)];
+        for i in 0..MAX_GRADIENT_STOPS {
+            // Unused slots repeat the last stop.
+            let Some(stop) = stops.get(i).or(stops.last()) else {
+                break;
+            };
+            offsets[i] = stop.offset;
+            colors[i] = to_linear(stop.color);
+        }
+        Self {
+            kind: gradient.kind,
+            offsets,
+            colors,
+        }
+    }
+
+    /// Port of the shader's `evaluate_gradient`.
+    fn evaluate(&self, pos: (f32, f32), size: (f32, f32)) -> Rgba {
+        let t = match self.kind {
+            GradientKind::Linear { angle } => {
+                let (dx, dy) = (angle.cos(), angle.sin());
+                let extent = (size.0 * dx).abs() + (size.1 * dy).abs();
+                ((pos.0 - size.0 * 0.5) * dx + (pos.1 - size.1 * 0.5) * dy) / extent.max(1e-5) + 0.5
+            }
+            GradientKind::Radial { center, radius } => {
+                let u = pos.0 / size.0.max(1e-5) - center.0;
+                let v = pos.1 / size.1.max(1e-5) - center.1;
+                (u * u + v * v).sqrt() / radius.max(1e-5)
+            }
+        }
+        .clamp(0.0, 1.0);
+
+        let mut color = self.colors[0];
+        for i in 1..MAX_GRADIENT_STOPS {
+            let span = self.offsets[i] - self.offsets[i - 1];
+            let f = if span > 0.0 {
+                ((t - self.offsets[i - 1]) / span).clamp(0.0, 1.0)
+            } else if t >= self.offsets[i] {
+                1.0
+            } else {
+                0.0
+            };
+            color = std::array::from_fn(|c| color[c] + (self.colors[i][c] - color[c]) * f);
+        }
+        color
+    }
+}
+
+fn corner_radii(quad: &Quad) -> [f32; 4] {
+    let radii = &quad.corner_radii;
+    [
+        radii.top_left,
+        radii.top_right,
+        radii.bottom_right,
+        radii.bottom_left,
+    ]
+}
+
+/// Whether a pixel center falls outside the clip rect (edges inclusive).
+fn clipped(clip: Option<DeviceRect>, x: f32, y: f32) -> bool {
+    clip.is_some_and(|clip| {
+        x < clip.origin.x
+            || x > clip.origin.x + clip.size.width
+            || y < clip.origin.y
+            || y > clip.origin.y + clip.size.height
+    })
+}
+
+/// Port of the shader's `rounded_rect_sdf`: negative inside.
+fn rounded_rect_sdf(pos: (f32, f32), size: (f32, f32), radii: [f32; 4]) -> f32 {
+    let center = (size.0 * 0.5, size.1 * 0.5);
+    let r = match (pos.0 < center.0, pos.1 < center.1) {
+        (true, true) => radii[0],
+        (false, true) => radii[1],
+        (false, false) => radii[2],
+        (true, false) => radii[3],
+    };
+    let r = r.min(size.0.min(size.1) * 0.5);
+
+    let qx = (pos.0 - center.0).abs() - (center.0 - r);
+    let qy = (pos.1 - center.1).abs() - (center.1 - r);
+    let outside = (qx.max(0.0).powi(2) + qy.max(0.0).powi(2)).sqrt();
+    qx.max(qy).min(0.0) + outside - r
+}
+
+/// Port of the shader's `shadow_alpha`: coverage of a Gaussian-blurred
+/// rounded rect whose bounds include 3 sigma of blur on every side.
+fn shadow_alpha(pos: (f32, f32), size: (f32, f32), radii: [f32; 4], sigma: f32) -> f32 {
+    let half = (size.0 * 0.5 - 3.0 * sigma, size.1 * 0.5 - 3.0 * sigma);
+    let p = (pos.0 - size.0 * 0.5, pos.1 - size.1 * 0.5);
+
+    let corner = match (p.0 < 0.0, p.1 < 0.0) {
+        (true, true) => radii[0],
+        (false, true) => radii[1],
+        (false, false) => radii[2],
+        (true, false) => radii[3],
+    };
+    let corner = corner.min(half.0.min(half.1));
+
+    // Metal's clamp(), which doesn't require low <= high.
+    let clamp = |v: f32, low: f32, high: f32| v.max(low).min(high);
+    let low = p.1 - half.1;
+    let high = p.1 + half.1;
+    let start = clamp(-3.0 * sigma, low, high);
+    let end = clamp(3.0 * sigma, low, high);
+
+    let step = (end - start) / 4.0;
+    let mut y = start + step * 0.5;
+    let mut alpha = 0.0;
+    for _ in 0..4 {
+        alpha += blur_along_x(p.0, p.1 - y, sigma, corner, half) * gaussian(y, sigma) * step;
+        y += step;
+    }
+    alpha
+}
+
+fn blur_along_x(x: f32, y: f32, sigma: f32, corner: f32, half: (f32, f32)) -> f32 {
+    let delta = (half.1 - corner - y.abs()).min(0.0);
+    let curved = half.0 - corner + (corner * corner - delta * delta).max(0.0).sqrt();
+    let scale = 0.5f32.sqrt() / sigma;
+    let low = 0.5 + 0.5 * erf((x - curved) * scale);
+    let high = 0.5 + 0.5 * erf((x + curved) * scale);
+    high - low
+}
+
+fn gaussian(x: f32, sigma: f32) -> f32 {
+    (-(x * x) / (2.0 * sigma * sigma)).exp() / ((2.0 * std::f32::consts::PI).sqrt() * sigma)
+}
+
+/// The shader's polynomial approximation of erf.
+fn erf(x: f32) -> f32 {
+    let s = if x == 0.0 { 0.0 } else { x.signum() };
+    let a = x.abs();
+    let t = 1.0 + (0.278393 + (0.230389 + 0.078108 * (a * a)) * a) * a;
+    let t = t * t;
+    s - s / (t * t)
+}
+
+/// Decode a scene color to linear light, as the vertex shaders do.
+fn to_linear(color: Srgba) -> Rgba {
+    let linear = color.into_linear();
+    [linear.red, linear.green, linear.blue, linear.alpha]
+}
+
+fn premultiply([r, g, b, a]: Rgba) -> Rgba {
+    [r * a, g * a, b * a, a]
+}
+
+fn decode_srgb(value: u8) -> f32 {
+    let c = value as f32 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn encode_srgb(linear: f32) -> u8 {
+    let c = linear.clamp(0.0, 1.0);
+    let encoded = if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    };
+    (encoded * 255.0).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Corners, DeviceSize, DrawContext, Edges, Path, Point, ScaleFactor, TextContext};
+
+    fn rect(x: f32, y: f32, w: f32, h: f32) -> DeviceRect {
+        DeviceRect::new(DevicePoint::new(x, y), DeviceSize::new(w, h))
+    }
+
+    const RED: Srgba = Srgba::new(1.0, 0.0, 0.0, 1.0);
+    const WHITE: Srgba = Srgba::new(1.0, 1.0, 1.0, 1.0);
+    const BLACK: [u8; 4] = [0, 0, 0, 255];
+
+    fn render(scene: &Scene, width: u32, height: u32) -> Framebuffer {
+        let mut framebuffer = Framebuffer::new(width, height);
+        SoftwareRenderer::new().render_to_framebuffer(scene, &mut framebuffer);
+        framebuffer
+    }
+
+    #[test]
+    fn clears_to_scene_background() {
+        let mut scene = Scene::new();
+        scene.set_background(WHITE);
+        let frame = render(&scene, 4, 4);
+        assert_eq!(frame.rgba8_at(3, 3), [255, 255, 255, 255]);
+
+        let image = SoftwareRenderer::new().render_to_image(&scene, (2.0, 2.0), 2.0);
+        assert_eq!(image.dimensions(), (4, 4));
+        assert_eq!(image.get_pixel(3, 3).0, [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn overdraw_mode_shows_how_often_pixels_were_drawn() {
+        let mut scene = Scene::new();
+        scene.set_background(WHITE);
+        scene.push_quad(Quad::new(rect(0.0, 0.0, 4.0, 4.0), RED));
+        scene.push_quad(Quad::new(
+            rect(2.0, 0.0, 4.0, 4.0),
+            Srgba::new(1.0, 0.0, 0.0, 0.5),
+        ));
+
+        let mut renderer = SoftwareRenderer::new();
+        renderer.set_render_mode(RenderMode::Overdraw);
+        let mut frame = Framebuffer::new(8, 4);
+        renderer.render_to_framebuffer(&scene, &mut frame);
+
+        let heat = |count| {
+            let mut framebuffer = Framebuffer::new(1, 1);
+            framebuffer.clear(premultiply(to_linear(overdraw_color(count))));
+            framebuffer.rgba8_at(0, 0)
+        };
+        assert_eq!(frame.rgba8_at(0, 0), heat(1));
+        assert_eq!(frame.rgba8_at(3, 0), heat(2));
+        assert_eq!(frame.rgba8_at(7, 0), heat(0));
+
+        renderer.set_render_mode(RenderMode::Normal);
+        renderer.render_to_framebuffer(&scene, &mut frame);
+        assert_eq!(frame.rgba8_at(7, 0), [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn quad_covers_pixels_whose_centers_are_inside() {
+        let mut scene = Scene::new();
+        scene.push_quad(Quad::new(rect(2.0, 2.0, 4.0, 4.0), RED));
+        let frame = render(&scene, 8, 8);
+        assert_eq!(frame.rgba8_at(2, 2), [255, 0, 0, 255]);
+        assert_eq!(frame.rgba8_at(5, 5), [255, 0, 0, 255]);
+        assert_eq!(frame.rgba8_at(1, 2), BLACK);
+        assert_eq!(frame.rgba8_at(6, 5), BLACK);
+    }
+
+    #[test]
+    fn rounded_corners_and_borders() {
+        let mut quad = Quad::new(rect(0.0, 0.0, 20.0, 20.0), RED);
+        quad.corner_radii = Corners::all(8.0);
+        quad.border_widths = Edges::all(2.0);
+        quad.border_color = WHITE;
+        let mut scene = Scene::new();
+        scene.push_quad(quad);
+        let frame = render(&scene, 20, 20);
+
+        assert_eq!(frame.rgba8_at(0, 0), BLACK, "outside the rounded corner");
+        assert_eq!(frame.rgba8_at(10, 0), [255, 255, 255, 255], "border");
+        assert_eq!(frame.rgba8_at(10, 10), [255, 0, 0, 255], "fill");
+    }
+
+    #[test]
+    fn clip_bounds_discard_outside_pixels() {
+        let mut quad = Quad::new(rect(0.0, 0.0, 10.0, 10.0), RED);
+        quad.clip_bounds = Some(rect(0.0, 0.0, 5.0, 10.0));
+        let mut scene = Scene::new();
+        scene.push_quad(quad);
+        let frame = render(&scene, 10, 10);
+        assert_eq!(frame.rgba8_at(4, 4), [255, 0, 0, 255]);
+        assert_eq!(frame.rgba8_at(5, 4), BLACK);
+    }
+
+    #[test]
+    fn clip_cuts_through_rounded_borders() {
+        let mut quad = Quad::new(rect(0.0, 0.0, 20.0, 20.0), RED);
+        quad.corner_radii = Corners::all(8.0);
+        quad.border_widths = Edges::all(2.0);
+        quad.border_color = WHITE;
+        quad.clip_bounds = Some(rect(0.0, 0.0, 20.0, 10.0));
+        let mut scene = Scene::new();
+        scene.push_quad(quad);
+        let frame = render(&scene, 20, 20);
+
+        assert_eq!(frame.rgba8_at(0, 0), BLACK, "outside the rounded corner");
+        assert_eq!(frame.rgba8_at(10, 0), [255, 255, 255, 255], "top border");
+        assert_eq!(frame.rgba8_at(0, 9), [255, 255, 255, 255], "side border");
+        assert_eq!(frame.rgba8_at(10, 9), [255, 0, 0, 255], "fill");
+        assert_eq!(frame.rgba8_at(10, 10), BLACK, "fill below the clip");
+        assert_eq!(frame.rgba8_at(10, 19), BLACK, "border below the clip");
+    }
+
+    #[test]
+    fn blending_happens_in_linear_light() {
+        let mut scene = Scene::new();
+        scene.push_quad(Quad::new(
+            rect(0.0, 0.0, 1.0, 1.0),
+            Srgba::new(1.0, 1.0, 1.0, 0.5),
+        ));
+        let frame = render(&scene, 1, 1);
+        // 50% linear white encodes to ~188, not 128.
+        assert_eq!(frame.rgba8_at(0, 0), [188, 188, 188, 255]);
+    }
+
+    #[test]
+    fn linear_gradient_runs_between_stops() {
+        let mut quad = Quad::new(rect(0.0, 0.0, 100.0, 1.0), RED);
+        quad.gradient = Some(Gradient::linear(0.0, RED, Srgba::new(0.0, 0.0, 1.0, 1.0)));
+        let mut scene = Scene::new();
+        scene.push_quad(quad);
+        let frame = render(&scene, 100, 1);
+        let left = frame.rgba8_at(0, 0);
+        let right = frame.rgba8_at(99, 0);
+        assert!(left[0] > 250 && left[2] < 30, "{left:?}");
+        assert!(right[2] > 250 && right[0] < 30, "{right:?}");
+    }
+
+    #[test]
+    fn shadow_darkens_around_the_quad() {
+        let mut scene = Scene::new();
+        scene.set_background(WHITE);
+        let mut quad = Quad::new(rect(20.0, 20.0, 20.0, 20.0), WHITE);
+        quad.shadow = Some(Shadow::new((0.0, 0.0), 8.0, Srgba::new(0.0, 0.0, 0.0, 1.0)));
+        scene.push_quad(quad);
+        let frame = render(&scene, 60, 60);
+
+        let near = frame.rgba8_at(18, 30)[0];
+        let far = frame.rgba8_at(2, 30)[0];
+        assert!(near < 240, "shadow next to the quad: {near}");
+        assert!(far > near, "shadow fades with distance");
+        assert_eq!(frame.rgba8_at(30, 30), [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn paths_fill_their_triangles_once() {
+        let mut scene = Scene::new();
+        let path = Path::new()
+            .move_to(DevicePoint::new(0.0, 0.0))
+            .line_to(DevicePoint::new(10.0, 0.0))
+            .line_to(DevicePoint::new(10.0, 10.0))
+            .line_to(DevicePoint::new(0.0, 10.0))
+            .close();
+        scene.push_path(PathPrimitive::fill(path, Srgba::new(1.0, 1.0, 1.0, 0.5)));
+        let frame = render(&scene, 12, 12);
+
+        // The square is two triangles; the shared diagonal must not blend twice.
+        let expected = frame.rgba8_at(2, 7);
+        assert_eq!(frame.rgba8_at(5, 5), expected);
+        assert_eq!(frame.rgba8_at(7, 2), expected);
+        assert_eq!(frame.rgba8_at(11, 5), BLACK);
+    }
+
+    #[test]
+    fn images_are_sampled_with_opacity() {
+        let image = ImageData::new(1, 1, vec![255u8, 0, 0, 255]).unwrap();
+        let mut quad = ImageQuad::new(rect(0.0, 0.0, 4.0, 4.0), image);
+        let mut scene = Scene::new();
+        scene.push_image(quad.clone());
+        assert_eq!(render(&scene, 4, 4).rgba8_at(1, 1), [255, 0, 0, 255]);
+
+        quad.opacity = 0.0;
+        let mut scene = Scene::new();
+        scene.push_image(quad);
+        assert_eq!(render(&scene, 4, 4).rgba8_at(1, 1), BLACK);
+    }
+
+    #[test]
+    fn image_corners_are_rounded() {
+        let image = ImageData::new(1, 1, vec![255u8, 255, 255, 255]).unwrap();
+        let mut quad = ImageQuad::new(rect(0.0, 0.0, 20.0, 20.0), image);
+        quad.corner_radii = Corners::all(10.0);
+        let mut scene = Scene::new();
+        scene.push_image(quad);
+        let frame = render(&scene, 20, 20);
+
+        assert_eq!(frame.rgba8_at(0, 0), BLACK);
+        assert_eq!(frame.rgba8_at(10, 10), [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn text_draws_glyph_coverage() {
+        let mut scene = Scene::new();
+        let mut text_ctx = TextContext::new();
+        DrawContext::new(&mut scene, ScaleFactor(1.0)).paint_text(
+            "Hello",
+            Point::new(4.0, 24.0),
+            20.0,
+            WHITE,
+            &mut text_ctx,
+        );
+        let frame = render(&scene, 80, 32);
+        let lit = frame
+            .to_rgba8()
+            .chunks_exact(4)
+            .filter(|p| p[0] > 128)
+            .count();
+        assert!(lit > 20, "expected glyph pixels, got {lit}");
+    }
+
+    #[test]
+    fn overlay_covers_text_below_it() {
+        let mut scene = Scene::new();
+        let mut text_ctx = TextContext::new();
+        DrawContext::new(&mut scene, ScaleFactor(1.0)).paint_text(
+            "Hello",
+            Point::new(4.0, 24.0),
+            20.0,
+            WHITE,
+            &mut text_ctx,
+        );
+        scene
+            .overlay_mut()
+            .push_quad(Quad::new(rect(0.0, 0.0, 80.0, 32.0), RED));
+        let frame = render(&scene, 80, 32);
+        assert!(frame
+            .to_rgba8()
+            .chunks_exact(4)
+            .all(|p| p == [255, 0, 0, 255]));
+    }
+}
@@ -0,0 +1,73 @@
+use std::num::NonZeroU32;
+use std::sync::Arc;
+
+use softbuffer::{Context, SoftBufferError, Surface};
+use winit::window::Window;
+
+use super::Framebuffer;
+
+/// Presents software-rendered frames to a window through softbuffer.
+pub struct SoftwareSurface {
+    surface: Surface<Arc<Window>, Arc<Window>>,
+    framebuffer: Framebuffer,
+}
+
+impl SoftwareSurface {
+    /// Create a surface for the window, sized to its current inner size.
+    pub fn new(window: Arc<Window>) -> Result<Self, SoftBufferError> {
+        let context = Context::new(window.clone())?;
+        let size = window.inner_size();
+        let surface = Surface::new(&context, window)?;
+        let mut this = Self {
+            surface,
+            framebuffer: Framebuffer::new(0, 0),
+        };
+        this.resize(size.width as f32, size.height as f32);
+        Ok(this)
+    }
+
+    /// Update the framebuffer size (call on window resize).
+    pub fn resize(&mut self, width: f32, height: f32) {
+        let (width, height) = (width.max(0.0) as u32, height.max(0.0) as u32);
+        if (width, height) == (self.framebuffer.width(), self.framebuffer.height()) {
+            return;
+        }
+        self.framebuffer.resize(width, height);
+        if let (Some(w), Some(h)) = (NonZeroU32::new(width), NonZeroU32::new(height)) {
+            let _ = self.surface.resize(w, h);
+        }
+    }
+
+    pub fn drawable_size(&self) -> (f32, f32) {
+        (
+            self.framebuffer.width() as f32,
+            self.framebuffer.height() as f32,
+        )
+    }
+
+    /// The most recently rendered frame.
+    pub fn framebuffer(&self) -> &Framebuffer {
+        &self.framebuffer
+    }
+
+    pub(super) fn framebuffer_mut(&mut self) -> &mut Framebuffer {
+        &mut self.framebuffer
+    }
+
+    /// Copy the framebuffer to the window. Windows are opaque, so the
+    /// premultiplied frame is shown as if composited over black.
+    pub(super) fn present(&mut self) -> Result<(), SoftBufferError> {
+        if self.framebuffer.width() == 0 || self.framebuffer.height() == 0 {
+            return Ok(());
+        }
+        let mut buffer = self.surface.buffer_mut()?;
+        let (width, height) = (self.framebuffer.width(), self.framebuffer.height());
+        let len = (width * height) as usize;
+        for (i, pixel) in buffer.iter_mut().take(len).enumerate() {
+            let (x, y) = (i as u32 % width, i as u32 / width);
+            let [r, g, b, _] = self.framebuffer.rgba8_at(x, y);
+            *pixel = (r as u32) << 16 | (g as u32) << 8 | b as u32;
+        }
+        buffer.present()
+    }
+}
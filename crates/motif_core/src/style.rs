@@ -0,0 +1,308 @@
+//! Reusable visual styles and themes.
+//!
+//! A [`Style`] bundles the visual properties elements share (colors, radii,
+//! borders, text) so they can be defined once and applied with
+//! `.style(..)`. A [`Theme`] names the colors and metrics an app is built
+//! from, and views read the active one from
+//! [`WindowContext::theme`](crate::WindowContext::theme):
+//!
+//! ```ignore
+//! let theme = cx.theme();
+//! div()
+//!     .style(theme.panel())
+//!     .child(text("Settings").color(theme.text))
+//!     .child(text("Changes apply on restart").color(theme.text_muted))
+//! ```
+//!
+//! Sizes given in [`rem`]s scale with the theme's root font size, so a
+//! larger-text preference can grow a whole UI at once:
+//!
+//! ```ignore
+//! div().padding(cx.rem(1.0)).child(text("Title").font_size(cx.rem(1.5)))
+//! ```
+//!
+//! Not to be confused with the layout [`Style`](crate::layout::Style)
+//! re-exported from taffy; this one lives at `motif_core::style::Style`.
+
+use std::collections::HashMap;
+
+use palette::Srgba;
+
+use crate::{ArcStr, Corners, Edges};
+
+/// A length in multiples of the root font size, like CSS `rem`.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct Rems(pub f32);
+
+impl Rems {
+    /// The length in logical pixels for a root font size.
+    pub fn to_pixels(self, root_font_size: f32) -> f32 {
+        self.0 * root_font_size
+    }
+}
+
+/// A length of `value` root font sizes.
+pub fn rem(value: f32) -> Rems {
+    Rems(value)
+}
+
+/// Visual properties that can be applied to elements together.
+///
+/// Every field is optional: applying a style only overrides what it sets.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Style {
+    pub background: Option<Srgba>,
+    pub border_color: Option<Srgba>,
+    /// Border widths in logical pixels.
+    pub border_widths: Option<Edges<f32>>,
+    /// Corner radii in logical pixels.
+    pub corner_radii: Option<Corners<f32>>,
+    pub text_color: Option<Srgba>,
+    pub font_size: Option<f32>,
+}
+
+impl Style {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn background(mut self, color: impl Into<Srgba>) -> Self {
+        self.background = Some(color.into());
+        self
+    }
+
+    pub fn border_color(mut self, color: impl Into<Srgba>) -> Self {
+        self.border_color = Some(color.into());
+        self
+    }
+
+    pub fn border_width(mut self, widths: impl Into<Edges<f32>>) -> Self {
+        self.border_widths = Some(widths.into());
+        self
+    }
+
+    pub fn corner_radius(mut self, radius: f32) -> Self {
+        self.corner_radii = Some(Corners::all(radius));
+        self
+    }
+
+    pub fn corner_radii(mut self, radii: Corners<f32>) -> Self {
+        self.corner_radii = Some(radii);
+        self
+    }
+
+    pub fn text_color(mut self, color: impl Into<Srgba>) -> Self {
+        self.text_color = Some(color.into());
+        self
+    }
+
+    pub fn font_size(mut self, size: f32) -> Self {
+        self.font_size = Some(size);
+        self
+    }
+
+    /// This style with every property `other` sets taking precedence.
+    pub fn refine(&self, other: &Style) -> Style {
+        Style {
+            background: other.background.or(self.background),
+            border_color: other.border_color.or(self.border_color),
+            border_widths: other.border_widths.or(self.border_widths),
+            corner_radii: other.corner_radii.or(self.corner_radii),
+            text_color: other.text_color.or(self.text_color),
+            font_size: other.font_size.or(self.font_size),
+        }
+    }
+}
+
+/// Named colors and metrics for an app's look.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Theme {
+    pub name: ArcStr,
+    /// Window background.
+    pub background: Srgba,
+    /// Panels, cards and other raised areas.
+    pub surface: Srgba,
+    /// Surfaces under the cursor.
+    pub surface_hover: Srgba,
+    pub border: Srgba,
+    pub text: Srgba,
+    /// Secondary text: captions, hints, placeholders.
+    pub text_muted: Srgba,
+    /// Primary actions and selection.
+    pub accent: Srgba,
+    /// Text drawn on `accent`.
+    pub accent_text: Srgba,
+    /// Default corner radius in logical pixels.
+    pub radius: f32,
+    /// Default font size in logical pixels.
+    pub font_size: f32,
+    /// Size of one [`rem`] in logical pixels. Raise it to scale everything
+    /// sized in rems, e.g. for a larger-text preference.
+    pub root_font_size: f32,
+}
+
+impl Theme {
+    /// The root font size of the built-in themes, matching browsers.
+    pub const DEFAULT_ROOT_FONT_SIZE: f32 = 16.0;
+
+    pub fn dark() -> Self {
+        Self {
+            name: "dark".into(),
+            background: Srgba::new(0.06, 0.06, 0.08, 1.0),
+            surface: Srgba::new(0.12, 0.12, 0.15, 1.0),
+            surface_hover: Srgba::new(0.17, 0.17, 0.21, 1.0),
+            border: Srgba::new(0.25, 0.25, 0.3, 1.0),
+            text: Srgba::new(0.92, 0.92, 0.95, 1.0),
+            text_muted: Srgba::new(0.55, 0.55, 0.6, 1.0),
+            accent: Srgba::new(0.2, 0.4, 0.8, 1.0),
+            accent_text: Srgba::new(1.0, 1.0, 1.0, 1.0),
+            radius: 6.0,
+            font_size: 14.0,
+            root_font_size: Self::DEFAULT_ROOT_FONT_SIZE,
+        }
+    }
+
+    pub fn light() -> Self {
+        Self {
+            name: "light".into(),
+            background: Srgba::new(0.97, 0.97, 0.98, 1.0),
+            surface: Srgba::new(1.0, 1.0, 1.0, 1.0),
+            surface_hover: Srgba::new(0.93, 0.93, 0.95, 1.0),
+            border: Srgba::new(0.82, 0.82, 0.86, 1.0),
+            text: Srgba::new(0.1, 0.1, 0.12, 1.0),
+            text_muted: Srgba::new(0.42, 0.42, 0.48, 1.0),
+            accent: Srgba::new(0.15, 0.4, 0.85, 1.0),
+            accent_text: Srgba::new(1.0, 1.0, 1.0, 1.0),
+            radius: 6.0,
+            font_size: 14.0,
+            root_font_size: Self::DEFAULT_ROOT_FONT_SIZE,
+        }
+    }
+
+    /// A raised panel: surface background, border and the theme's radius.
+    pub fn panel(&self) -> Style {
+        Style::new()
+            .background(self.surface)
+            .border_color(self.border)
+            .border_width(1.0)
+            .corner_radius(self.radius)
+    }
+
+    /// Body text in the theme's color and size.
+    pub fn body_text(&self) -> Style {
+        Style::new().text_color(self.text).font_size(self.font_size)
+    }
+
+    /// Secondary text.
+    pub fn muted_text(&self) -> Style {
+        self.body_text().text_color(self.text_muted)
+    }
+
+    /// `value` [`rem`]s in logical pixels.
+    pub fn rem(&self, value: f32) -> f32 {
+        rem(value).to_pixels(self.root_font_size)
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+/// Themes available to an app, one of which is active.
+#[derive(Clone, Debug)]
+pub struct ThemeRegistry {
+    themes: HashMap<ArcStr, Theme>,
+    active: ArcStr,
+}
+
+impl Default for ThemeRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ThemeRegistry {
+    /// A registry holding the built-in `dark` (active) and `light` themes.
+    pub fn new() -> Self {
+        let mut registry = Self {
+            themes: HashMap::new(),
+            active: "dark".into(),
+        };
+        registry.register(Theme::dark());
+        registry.register(Theme::light());
+        registry
+    }
+
+    /// Add `theme`, replacing any theme with the same name.
+    pub fn register(&mut self, theme: Theme) {
+        self.themes.insert(theme.name.clone(), theme);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Theme> {
+        self.themes.get(name)
+    }
+
+    /// Make the theme called `name` active. Returns false if there is none.
+    pub fn set_active(&mut self, name: &str) -> bool {
+        match self.themes.get_key_value(name) {
+            Some((key, _)) => {
+                self.active = key.clone();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn active(&self) -> &Theme {
+        &self.themes[&self.active]
+    }
+
+    /// Names of every registered theme, sorted.
+    pub fn names(&self) -> Vec<ArcStr> {
+        let mut names: Vec<_> = self.themes.keys().cloned().collect();
+        names.sort();
+        names
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refine_prefers_set_properties() {
+        let base = Style::new()
+            .background(Srgba::new(1.0, 0.0, 0.0, 1.0))
+            .font_size(12.0);
+        let over = Style::new().font_size(20.0);
+        let refined = base.refine(&over);
+        assert_eq!(refined.background, base.background);
+        assert_eq!(refined.font_size, Some(20.0));
+    }
+
+    #[test]
+    fn rems_scale_with_the_root_font_size() {
+        let mut theme = Theme::default();
+        assert_eq!(theme.rem(1.5), 24.0);
+        theme.root_font_size = 20.0;
+        assert_eq!(theme.rem(1.5), 30.0);
+        assert_eq!(rem(0.5).to_pixels(20.0), 10.0);
+    }
+
+    #[test]
+    fn registry_switches_active_theme() {
+        let mut registry = ThemeRegistry::new();
+        assert_eq!(registry.active().name, "dark");
+        assert!(registry.set_active("light"));
+        assert_eq!(registry.active(), &Theme::light());
+        assert!(!registry.set_active("missing"));
+        assert_eq!(registry.active().name, "light");
+
+        let mut custom = Theme::dark();
+        custom.name = "custom".into();
+        registry.register(custom);
+        assert_eq!(registry.names().len(), 3);
+    }
+}
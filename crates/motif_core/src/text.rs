@@ -1,5 +1,6 @@
 //! Text layout and rendering using parley.
 
+use crate::profiler::{self, FramePhase};
 use parley::{FontContext, LayoutContext};
 use std::collections::HashMap;
 use swash::scale::{Render, ScaleContext, Source, StrikeWith};
@@ -22,21 +23,92 @@ impl TextContext {
 
     /// Layout text with given font size, using system default font.
     pub fn layout_text(&mut self, text: &str, font_size: f32) -> TextLayout {
+        TextLayout {
+            layout: self.build_layout(text, &TextStyle::new(font_size)),
+        }
+    }
+
+    /// Measure text without producing a [`TextLayout`].
+    ///
+    /// Use this when only the size is needed, e.g. during layout before
+    /// anything is painted.
+    pub fn measure(&mut self, text: &str, style: &TextStyle) -> TextMetrics {
+        let layout = self.build_layout(text, style);
+        let (ascent, descent) = layout
+            .lines()
+            .next()
+            .map(|line| (line.metrics().ascent, line.metrics().descent))
+            .unwrap_or((0.0, 0.0));
+        TextMetrics {
+            width: layout.width(),
+            height: layout.height(),
+            ascent,
+            descent,
+        }
+    }
+
+    fn build_layout(&mut self, text: &str, style: &TextStyle) -> parley::Layout<()> {
+        let _scope = profiler::scope(FramePhase::TextShaping);
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("layout_text", len = text.len()).entered();
         let mut builder = self
             .layout_cx
             .ranged_builder(&mut self.font_cx, text, 1.0, false);
-        builder.push_default(parley::style::StyleProperty::FontSize(font_size));
+        builder.push_default(parley::style::StyleProperty::FontSize(style.font_size));
         let mut layout = builder.build(text);
-        layout.break_all_lines(None);
+        layout.break_all_lines(style.max_width);
         layout.align(
-            None,
+            style.max_width,
             parley::layout::Alignment::Start,
             parley::layout::AlignmentOptions::default(),
         );
-        TextLayout { layout }
+        layout
     }
 }
 
+/// Style parameters for measuring text.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TextStyle {
+    /// Font size in pixels.
+    pub font_size: f32,
+    /// Wrap lines that exceed this width. `None` lays out on a single line
+    /// unless the text contains explicit newlines.
+    pub max_width: Option<f32>,
+}
+
+impl TextStyle {
+    pub fn new(font_size: f32) -> Self {
+        Self {
+            font_size,
+            max_width: None,
+        }
+    }
+
+    pub fn max_width(mut self, max_width: f32) -> Self {
+        self.max_width = Some(max_width);
+        self
+    }
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        Self::new(16.0)
+    }
+}
+
+/// Size of a piece of text as returned by [`TextContext::measure`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TextMetrics {
+    /// Width of the widest line.
+    pub width: f32,
+    /// Total height of all lines.
+    pub height: f32,
+    /// Distance from the top of the first line to its baseline.
+    pub ascent: f32,
+    /// Distance from the first baseline to the bottom of the first line.
+    pub descent: f32,
+}
+
 impl Default for TextContext {
     fn default() -> Self {
         Self::new()
@@ -228,6 +300,10 @@ pub struct GlyphRunWithFont {
     pub font_size: f32,
     pub font_data: Option<FontData>,
     pub normalized_coords: Vec<i16>,
+    /// Ascent of the line containing this run.
+    pub ascent: f32,
+    /// Descent of the line containing this run.
+    pub descent: f32,
 }
 
 /// Key for caching rasterized glyphs.
@@ -315,6 +391,9 @@ impl GlyphCache {
             return self.cache.get(&key);
         }
 
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!("rasterize", glyph_id, font_size).entered();
+
         // Rasterize the glyph
         let font_ref = FontRef::from_index(font.data.as_ref(), font.index as usize)?;
 
@@ -357,7 +436,9 @@ impl TextLayout {
     /// Iterate over glyph runs with font data for rasterization.
     pub fn glyph_runs_with_font(&self) -> impl Iterator<Item = GlyphRunWithFont> + '_ {
         self.layout.lines().flat_map(|line| {
-            line.items().filter_map(|item| {
+            let metrics = line.metrics();
+            let (ascent, descent) = (metrics.ascent, metrics.descent);
+            line.items().filter_map(move |item| {
                 match item {
                     parley::layout::PositionedLayoutItem::GlyphRun(run) => {
                         // Use positioned_glyphs() which handles advance accumulation
@@ -382,6 +463,8 @@ impl TextLayout {
                             font_size: inner_run.font_size(),
                             font_data: Some(font.clone()),
                             normalized_coords,
+                            ascent,
+                            descent,
                         })
                     }
                     _ => None,
@@ -446,6 +529,29 @@ mod tests {
         }
     }
 
+    #[test]
+    fn measure_matches_layout_size() {
+        let mut ctx = TextContext::new();
+        let metrics = ctx.measure("Hello", &TextStyle::new(16.0));
+        let layout = ctx.layout_text("Hello", 16.0);
+
+        assert_eq!(metrics.width, layout.width());
+        assert_eq!(metrics.height, layout.height());
+        assert!(metrics.ascent > 0.0);
+        assert!(metrics.descent > 0.0);
+    }
+
+    #[test]
+    fn measure_wraps_at_max_width() {
+        let mut ctx = TextContext::new();
+        let text = "the quick brown fox jumps over the lazy dog";
+        let single = ctx.measure(text, &TextStyle::new(16.0));
+        let wrapped = ctx.measure(text, &TextStyle::new(16.0).max_width(single.width / 2.0));
+
+        assert!(wrapped.width <= single.width / 2.0);
+        assert!(wrapped.height > single.height);
+    }
+
     // GlyphCache tests
 
     #[test]
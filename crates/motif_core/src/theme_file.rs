@@ -0,0 +1,295 @@
+//! Themes defined in files, reloaded while the app runs.
+//!
+//! A theme file lists `key = value` pairs over a built-in base theme, so
+//! colors and metrics can be tweaked without recompiling:
+//!
+//! ```text
+//! # nord.theme
+//! base = light
+//! name = nord
+//! background = #eceff4
+//! accent = #5e81acff
+//! radius = 4
+//! ```
+//!
+//! Keys are the [`Theme`] fields; colors are `#rrggbb` or `#rrggbbaa`. A
+//! [`ThemeWatcher`] reports each new version of the file:
+//!
+//! ```ignore
+//! let mut watcher = ThemeWatcher::new("assets/app.theme");
+//! // Once in a while, e.g. between frames:
+//! if let Some(Ok(theme)) = watcher.poll() {
+//!     app_theme = theme;
+//!     window.request_redraw();
+//! }
+//! ```
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::{ArcStr, Srgba, Theme};
+
+/// Why a theme file couldn't be loaded.
+#[derive(Debug)]
+pub enum ThemeFileError {
+    Io(std::io::Error),
+    /// A line that isn't a known `key = value` pair. Lines count from 1.
+    Parse {
+        line: usize,
+        message: String,
+    },
+}
+
+impl fmt::Display for ThemeFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThemeFileError::Io(err) => write!(f, "failed to read theme: {err}"),
+            ThemeFileError::Parse { line, message } => write!(f, "line {line}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for ThemeFileError {}
+
+impl From<std::io::Error> for ThemeFileError {
+    fn from(err: std::io::Error) -> Self {
+        ThemeFileError::Io(err)
+    }
+}
+
+impl Theme {
+    /// Parse a theme file. Fields it doesn't set come from its `base`
+    /// (`dark` or `light`, default `dark`), which must come first if given.
+    pub fn parse(source: &str) -> Result<Theme, ThemeFileError> {
+        let mut theme = Theme::dark();
+        for (index, line) in source.lines().enumerate() {
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+            let error = |message: String| ThemeFileError::Parse {
+                line: index + 1,
+                message,
+            };
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(error(format!("expected `key = value`, found `{line}`")));
+            };
+            let (key, value) = (key.trim(), value.trim().trim_matches('"'));
+            match key {
+                "base" => {
+                    theme = match value {
+                        "dark" => Theme::dark(),
+                        "light" => Theme::light(),
+                        _ => return Err(error(format!("unknown base theme `{value}`"))),
+                    };
+                }
+                "name" => theme.name = ArcStr::intern(value),
+                "radius" => theme.radius = parse_number(value).map_err(error)?,
+                "font_size" => theme.font_size = parse_number(value).map_err(error)?,
+                "root_font_size" => {
+                    theme.root_font_size = parse_number(value).map_err(error)?;
+                }
+                _ => {
+                    let color = theme
+                        .color_mut(key)
+                        .ok_or_else(|| error(format!("unknown key `{key}`")))?;
+                    *color = parse_color(value).map_err(error)?;
+                }
+            }
+        }
+        Ok(theme)
+    }
+
+    /// Read and parse the theme file at `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Theme, ThemeFileError> {
+        Theme::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// The theme as a file [`parse`](Self::parse) reads back, e.g. to start
+    /// a new theme from an existing one.
+    pub fn to_theme_file(&self) -> String {
+        let mut out = format!("name = {}\n", self.name);
+        for (key, color) in self.colors() {
+            let [r, g, b, a]: [u8; 4] = color.into_format::<u8, u8>().into();
+            out.push_str(&format!("{key} = #{r:02x}{g:02x}{b:02x}{a:02x}\n"));
+        }
+        out.push_str(&format!("radius = {}\n", self.radius));
+        out.push_str(&format!("font_size = {}\n", self.font_size));
+        out.push_str(&format!("root_font_size = {}\n", self.root_font_size));
+        out
+    }
+
+    fn colors(&self) -> [(&'static str, Srgba); 8] {
+        [
+            ("background", self.background),
+            ("surface", self.surface),
+            ("surface_hover", self.surface_hover),
+            ("border", self.border),
+            ("text", self.text),
+            ("text_muted", self.text_muted),
+            ("accent", self.accent),
+            ("accent_text", self.accent_text),
+        ]
+    }
+
+    fn color_mut(&mut self, key: &str) -> Option<&mut Srgba> {
+        Some(match key {
+            "background" => &mut self.background,
+            "surface" => &mut self.surface,
+            "surface_hover" => &mut self.surface_hover,
+            "border" => &mut self.border,
+            "text" => &mut self.text,
+            "text_muted" => &mut self.text_muted,
+            "accent" => &mut self.accent,
+            "accent_text" => &mut self.accent_text,
+            _ => return None,
+        })
+    }
+}
+
+/// `line` without a trailing `# comment`. A `#` straight after `=` starts
+/// a color instead.
+fn strip_comment(line: &str) -> &str {
+    let mut search_from = 0;
+    while let Some(offset) = line[search_from..].find('#') {
+        let at = search_from + offset;
+        if !line[..at].trim_end().ends_with('=') {
+            return &line[..at];
+        }
+        search_from = at + 1;
+    }
+    line
+}
+
+fn parse_number(value: &str) -> Result<f32, String> {
+    value
+        .parse()
+        .map_err(|_| format!("expected a number, found `{value}`"))
+}
+
+fn parse_color(value: &str) -> Result<Srgba, String> {
+    let invalid = || format!("expected `#rrggbb` or `#rrggbbaa`, found `{value}`");
+    let hex = value.strip_prefix('#').ok_or_else(invalid)?;
+    if !matches!(hex.len(), 6 | 8) || !hex.is_ascii() {
+        return Err(invalid());
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| invalid());
+    let alpha = if hex.len() == 8 { channel(6)? } else { 255 };
+    Ok(Srgba::new(channel(0)?, channel(2)?, channel(4)?, alpha).into_format())
+}
+
+/// Reloads a theme file whenever it changes on disk.
+///
+/// Checks the file's modification time rather than subscribing to file
+/// system events, so call [`poll`](Self::poll) periodically.
+#[derive(Debug)]
+pub struct ThemeWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+impl ThemeWatcher {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            modified: None,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The file's theme if it changed since the last poll (or this is the
+    /// first), otherwise `None`. A file that fails to parse is reported once
+    /// per change, so fixing it reloads it.
+    pub fn poll(&mut self) -> Option<Result<Theme, ThemeFileError>> {
+        let modified = match std::fs::metadata(&self.path).and_then(|meta| meta.modified()) {
+            Ok(modified) => modified,
+            Err(err) => {
+                // Report a missing file once, then wait for it to appear.
+                return self.modified.take().map(|_| Err(err.into()));
+            }
+        };
+        if self.modified == Some(modified) {
+            return None;
+        }
+        self.modified = Some(modified);
+        Some(Theme::load(&self.path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_overrides_the_base_theme() {
+        let theme = Theme::parse(
+            "# A warm light theme\n\
+             base = light\n\
+             name = \"warm\"\n\
+             accent = #ff8000 # orange\n\
+             text_muted = #00000080\n\
+             radius = 2.5\n\
+             root_font_size = 18\n",
+        )
+        .unwrap();
+        assert_eq!(theme.name.as_str(), "warm");
+        assert_eq!(theme.accent, Srgba::new(1.0, 128.0 / 255.0, 0.0, 1.0));
+        assert_eq!(theme.text_muted.alpha, 128.0 / 255.0);
+        assert_eq!(theme.radius, 2.5);
+        assert_eq!(theme.root_font_size, 18.0);
+        assert_eq!(theme.background, Theme::light().background);
+    }
+
+    #[test]
+    fn parse_errors_name_the_line() {
+        let err = Theme::parse("radius = 4\ncolour = #ffffff").unwrap_err();
+        assert!(
+            matches!(err, ThemeFileError::Parse { line: 2, .. }),
+            "{err}"
+        );
+
+        let err = Theme::parse("accent = red").unwrap_err();
+        assert!(err.to_string().contains("#rrggbb"), "{err}");
+    }
+
+    #[test]
+    fn theme_files_round_trip() {
+        let theme = Theme::light();
+        let parsed = Theme::parse(&theme.to_theme_file()).unwrap();
+        assert_eq!(parsed.name, theme.name);
+        assert_eq!(parsed.radius, theme.radius);
+        for ((key, a), (_, b)) in parsed.colors().into_iter().zip(theme.colors()) {
+            assert!((a.red - b.red).abs() < 0.003, "{key}");
+            assert!((a.alpha - b.alpha).abs() < 0.003, "{key}");
+        }
+    }
+
+    #[test]
+    fn watcher_reloads_when_the_file_changes() {
+        let path = std::env::temp_dir().join(format!("motif-theme-{}.theme", std::process::id()));
+        std::fs::write(&path, "radius = 3").unwrap();
+        let mut watcher = ThemeWatcher::new(&path);
+
+        assert_eq!(watcher.poll().unwrap().unwrap().radius, 3.0);
+        assert!(watcher.poll().is_none());
+
+        std::fs::write(&path, "radius = 9").unwrap();
+        // Make sure the modification time moves even on coarse clocks.
+        let later = SystemTime::now() + std::time::Duration::from_secs(2);
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        assert_eq!(watcher.poll().unwrap().unwrap().radius, 9.0);
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(watcher.poll().unwrap().is_err());
+        assert!(watcher.poll().is_none());
+    }
+}
@@ -0,0 +1,303 @@
+//! Timers and animation-frame callbacks.
+//!
+//! ```ignore
+//! let caret = cx.new_view(|_| Caret { visible: true });
+//! let handle = caret.clone();
+//! cx.set_interval(Duration::from_millis(530), move || {
+//!     handle.update(|caret| caret.visible = !caret.visible)
+//! });
+//! ```
+//!
+//! Each window has a [`Timers`] queue. The app runs due timers between
+//! events and sleeps until the next deadline instead of redrawing
+//! continuously; animation-frame callbacks run just before the next frame
+//! is drawn, and requesting one is what schedules that frame.
+//!
+//! Deadlines are measured on the queue's [`Clock`], so tests can drive
+//! timers with a manual one.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use crate::Clock;
+
+/// Identifies a timeout or interval, for [`Timers::cancel`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TimerId(u64);
+
+struct Timer {
+    id: TimerId,
+    due: Instant,
+    /// Repeat period for intervals.
+    interval: Option<Duration>,
+    callback: Box<dyn FnMut()>,
+}
+
+type FrameCallback = Box<dyn FnOnce()>;
+
+#[derive(Default)]
+struct TimerQueue {
+    next_id: u64,
+    timers: Vec<Timer>,
+    frame_callbacks: Vec<FrameCallback>,
+    /// Timers cancelled while their callbacks were running.
+    cancelled: Vec<TimerId>,
+    clock: Clock,
+}
+
+/// A window's pending timers and animation-frame callbacks.
+///
+/// Clones share the same queue, so handlers can keep one to schedule work
+/// later. Callbacks may schedule or cancel timers themselves.
+#[derive(Clone, Default)]
+pub struct Timers(Rc<RefCell<TimerQueue>>);
+
+impl Timers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A queue whose deadlines are measured on `clock`.
+    pub fn with_clock(clock: Clock) -> Self {
+        let timers = Self::default();
+        timers.0.borrow_mut().clock = clock;
+        timers
+    }
+
+    /// The clock deadlines are measured on.
+    pub fn clock(&self) -> Clock {
+        self.0.borrow().clock.clone()
+    }
+
+    /// Call `f` once, after `delay`.
+    pub fn set_timeout(&self, delay: Duration, f: impl FnOnce() + 'static) -> TimerId {
+        let mut f = Some(f);
+        self.schedule(delay, None, move || {
+            if let Some(f) = f.take() {
+                f()
+            }
+        })
+    }
+
+    /// Call `f` every `period`, until cancelled.
+    pub fn set_interval(&self, period: Duration, f: impl FnMut() + 'static) -> TimerId {
+        self.schedule(period, Some(period), f)
+    }
+
+    fn schedule(
+        &self,
+        delay: Duration,
+        interval: Option<Duration>,
+        f: impl FnMut() + 'static,
+    ) -> TimerId {
+        let mut queue = self.0.borrow_mut();
+        let id = TimerId(queue.next_id);
+        queue.next_id += 1;
+        let due = queue.clock.now() + delay;
+        queue.timers.push(Timer {
+            id,
+            due,
+            interval,
+            callback: Box::new(f),
+        });
+        id
+    }
+
+    /// Stop a timeout or interval. Does nothing if it already finished.
+    pub fn cancel(&self, id: TimerId) {
+        let mut queue = self.0.borrow_mut();
+        queue.timers.retain(|timer| timer.id != id);
+        queue.cancelled.push(id);
+    }
+
+    /// Call `f` right before the next frame is drawn, and draw one soon.
+    /// Animations request a frame from each frame they want to continue.
+    pub fn request_animation_frame(&self, f: impl FnOnce() + 'static) {
+        self.0.borrow_mut().frame_callbacks.push(Box::new(f));
+    }
+
+    /// Whether a frame was requested with
+    /// [`request_animation_frame`](Self::request_animation_frame).
+    pub fn has_frame_callbacks(&self) -> bool {
+        !self.0.borrow().frame_callbacks.is_empty()
+    }
+
+    /// When the earliest timer is due.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.0.borrow().timers.iter().map(|timer| timer.due).min()
+    }
+
+    /// Run every timer due at `now`, in deadline order. Returns whether any
+    /// ran.
+    pub fn run_due(&self, now: Instant) -> bool {
+        let mut due: Vec<Timer> = {
+            let mut queue = self.0.borrow_mut();
+            queue.cancelled.clear();
+            let (due, pending) = std::mem::take(&mut queue.timers)
+                .into_iter()
+                .partition(|timer| timer.due <= now);
+            queue.timers = pending;
+            due
+        };
+        due.sort_by_key(|timer| timer.due);
+        let mut ran = false;
+
+        for mut timer in due {
+            // An earlier callback in this batch may have cancelled it.
+            if self.0.borrow().cancelled.contains(&timer.id) {
+                continue;
+            }
+            (timer.callback)();
+            ran = true;
+            let Some(period) = timer.interval else {
+                continue;
+            };
+            let mut queue = self.0.borrow_mut();
+            if queue.cancelled.contains(&timer.id) {
+                continue;
+            }
+            // Skip ticks that were missed rather than running them back to back.
+            timer.due += period;
+            if timer.due <= now {
+                timer.due = now + period;
+            }
+            queue.timers.push(timer);
+        }
+        ran
+    }
+
+    /// Run the callbacks requested for this frame. Ones they request run
+    /// before the next frame. Returns whether any ran.
+    pub fn run_frame_callbacks(&self) -> bool {
+        let callbacks = std::mem::take(&mut self.0.borrow_mut().frame_callbacks);
+        let ran = !callbacks.is_empty();
+        for callback in callbacks {
+            callback();
+        }
+        ran
+    }
+}
+
+impl fmt::Debug for Timers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let queue = self.0.borrow();
+        f.debug_struct("Timers")
+            .field("timers", &queue.timers.len())
+            .field("frame_callbacks", &queue.frame_callbacks.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn counter() -> (Rc<Cell<u32>>, impl FnMut() + 'static) {
+        let count = Rc::new(Cell::new(0));
+        let handle = count.clone();
+        (count, move || handle.set(handle.get() + 1))
+    }
+
+    #[test]
+    fn timeouts_run_once_when_due() {
+        let timers = Timers::new();
+        let (count, tick) = counter();
+        timers.set_timeout(Duration::from_millis(100), tick);
+        let now = Instant::now();
+
+        assert!(!timers.run_due(now));
+        assert!(timers.next_deadline().unwrap() > now);
+        assert!(timers.run_due(now + Duration::from_millis(100)));
+        assert!(!timers.run_due(now + Duration::from_secs(1)));
+        assert_eq!(count.get(), 1);
+        assert_eq!(timers.next_deadline(), None);
+    }
+
+    #[test]
+    fn intervals_repeat_until_cancelled() {
+        let timers = Timers::new();
+        let (count, tick) = counter();
+        let id = timers.set_interval(Duration::from_millis(10), tick);
+        let start = Instant::now();
+
+        timers.run_due(start + Duration::from_millis(10));
+        timers.run_due(start + Duration::from_millis(20));
+        // Missed ticks run once, not back to back.
+        timers.run_due(start + Duration::from_millis(75));
+        assert_eq!(count.get(), 3);
+        assert!(timers.next_deadline().unwrap() > start + Duration::from_millis(75));
+
+        timers.cancel(id);
+        timers.run_due(start + Duration::from_secs(1));
+        assert_eq!(count.get(), 3);
+    }
+
+    #[test]
+    fn callbacks_can_cancel_their_own_interval() {
+        let timers = Timers::new();
+        let id = Rc::new(Cell::new(None));
+        let (handle, cancel_id) = (timers.clone(), id.clone());
+        id.set(Some(timers.set_interval(Duration::ZERO, move || {
+            handle.cancel(cancel_id.get().unwrap());
+        })));
+
+        assert!(timers.run_due(Instant::now() + Duration::from_millis(1)));
+        assert_eq!(timers.next_deadline(), None);
+    }
+
+    #[test]
+    fn callbacks_can_cancel_timers_due_in_the_same_batch() {
+        let timers = Timers::new();
+        let (count, tick) = counter();
+        let b = Rc::new(Cell::new(None));
+        let (handle, cancel_b) = (timers.clone(), b.clone());
+        timers.set_timeout(Duration::from_millis(10), move || {
+            handle.cancel(cancel_b.get().unwrap());
+        });
+        b.set(Some(timers.set_timeout(Duration::from_millis(20), tick)));
+        let (interval_count, interval_tick) = counter();
+        let interval = timers.set_interval(Duration::from_millis(30), interval_tick);
+        let handle = timers.clone();
+        timers.set_timeout(Duration::from_millis(25), move || handle.cancel(interval));
+
+        assert!(timers.run_due(Instant::now() + Duration::from_millis(50)));
+        assert_eq!(count.get(), 0);
+        assert_eq!(interval_count.get(), 0);
+        assert_eq!(timers.next_deadline(), None);
+    }
+
+    #[test]
+    fn deadlines_follow_the_queue_clock() {
+        let clock = Clock::manual();
+        let timers = Timers::with_clock(clock.clone());
+        let (count, tick) = counter();
+        timers.set_timeout(Duration::from_millis(50), tick);
+
+        clock.advance(Duration::from_millis(49));
+        assert!(!timers.run_due(clock.now()));
+        clock.advance(Duration::from_millis(1));
+        assert!(timers.run_due(clock.now()));
+        assert_eq!(count.get(), 1);
+    }
+
+    #[test]
+    fn frame_callbacks_requested_during_a_frame_wait_for_the_next() {
+        let timers = Timers::new();
+        let (count, mut tick) = counter();
+        let handle = timers.clone();
+        timers.request_animation_frame(move || {
+            tick();
+            handle.request_animation_frame(|| {});
+        });
+
+        assert!(timers.has_frame_callbacks());
+        assert!(timers.run_frame_callbacks());
+        assert_eq!(count.get(), 1);
+        assert!(timers.has_frame_callbacks());
+        assert!(timers.run_frame_callbacks());
+        assert!(!timers.has_frame_callbacks());
+    }
+}
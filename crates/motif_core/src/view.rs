@@ -0,0 +1,250 @@
+//! Shared handles to views, so one view can own and update another.
+//!
+//! ```ignore
+//! struct Sidebar { counter: View<Counter> }
+//!
+//! impl Sidebar {
+//!     fn new(cx: &mut WindowContext) -> Self {
+//!         Self { counter: cx.new_view(|_| Counter { count: 0 }) }
+//!     }
+//! }
+//!
+//! impl Render for Sidebar {
+//!     fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+//!         let counter = self.counter.clone();
+//!         div()
+//!             .child(self.counter.render(cx))
+//!             .child(button("+1", ElementId(1)).on_click(move || counter.update(|c| c.count += 1)))
+//!     }
+//! }
+//! ```
+//!
+//! Updating a view marks its window dirty; the app checks the window's
+//! [`DirtyFlag`] to decide whether to redraw. Nothing redraws a window
+//! otherwise, so animations ask for their next frame with
+//! [`WindowContext::request_animation_frame`] or a
+//! [timer](crate::timer).
+
+use std::cell::{Cell, Ref, RefCell};
+use std::rc::Rc;
+use std::time::Instant;
+
+use crate::element::{AnyElement, IntoElement, Render, ViewContext, WindowContext};
+
+#[derive(Debug, Default)]
+struct DirtyState {
+    dirty: Cell<bool>,
+    /// When a scheduled frame is due.
+    next_frame: Cell<Option<Instant>>,
+}
+
+/// Shared "needs redraw" flag for a window, plus the time of the next
+/// frame an animation asked for.
+///
+/// Clones share the same flag.
+#[derive(Clone, Debug, Default)]
+pub struct DirtyFlag(Rc<DirtyState>);
+
+impl DirtyFlag {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request a redraw.
+    pub fn mark(&self) {
+        self.0.dirty.set(true);
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.0.dirty.get()
+    }
+
+    /// Clear the flag, returning whether it was set.
+    pub fn take(&self) -> bool {
+        self.0.dirty.replace(false)
+    }
+
+    /// Request a redraw at `at`. The earliest request wins.
+    pub fn request_frame_at(&self, at: Instant) {
+        let next = match self.0.next_frame.get() {
+            Some(next) => next.min(at),
+            None => at,
+        };
+        self.0.next_frame.set(Some(next));
+    }
+
+    /// When the next scheduled frame is due, if one is.
+    pub fn next_frame(&self) -> Option<Instant> {
+        self.0.next_frame.get()
+    }
+
+    /// Whether the window should redraw at `now`: it's dirty, or a scheduled
+    /// frame is due. A due frame marks the flag dirty and is cleared.
+    pub fn needs_redraw(&self, now: Instant) -> bool {
+        if self.0.next_frame.get().is_some_and(|at| at <= now) {
+            self.0.next_frame.set(None);
+            self.mark();
+        }
+        self.is_dirty()
+    }
+}
+
+/// A handle to a view's state, shared between its owner and anything that
+/// needs to update it (e.g. click handlers).
+///
+/// Clones refer to the same view.
+pub struct View<V> {
+    state: Rc<RefCell<V>>,
+    dirty: DirtyFlag,
+}
+
+impl<V: 'static> View<V> {
+    pub(crate) fn new(state: V, dirty: DirtyFlag) -> Self {
+        Self {
+            state: Rc::new(RefCell::new(state)),
+            dirty,
+        }
+    }
+
+    /// Borrow the view's state.
+    ///
+    /// Panics if the view is being updated or rendered.
+    pub fn read(&self) -> Ref<'_, V> {
+        self.state.borrow()
+    }
+
+    /// Mutate the view's state and mark its window dirty.
+    ///
+    /// Panics if the view is already borrowed.
+    pub fn update<R>(&self, f: impl FnOnce(&mut V) -> R) -> R {
+        let result = f(&mut self.state.borrow_mut());
+        self.dirty.mark();
+        result
+    }
+
+    /// Mark the view's window dirty without changing its state.
+    pub fn notify(&self) {
+        self.dirty.mark();
+    }
+
+    /// Render the view into an element that can be added as a child.
+    pub fn render(&self, cx: &mut WindowContext) -> AnyElement
+    where
+        V: Render,
+    {
+        let mut view_cx = ViewContext::<V>::new(cx.reborrow());
+        let element = self.state.borrow_mut().render(&mut view_cx).into_element();
+        AnyElement::new(element)
+    }
+
+    /// Whether both handles refer to the same view.
+    pub fn same_view(&self, other: &View<V>) -> bool {
+        Rc::ptr_eq(&self.state, &other.state)
+    }
+}
+
+impl<V> Clone for View<V> {
+    fn clone(&self) -> Self {
+        Self {
+            state: Rc::clone(&self.state),
+            dirty: self.dirty.clone(),
+        }
+    }
+}
+
+impl<V: std::fmt::Debug> std::fmt::Debug for View<V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("View").field(&self.state).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    use crate::element::ParentElement;
+    use crate::{
+        div, render_view, text, HitTree, LayoutEngine, ScaleFactor, Scene, Size, TextContext,
+    };
+
+    struct Counter {
+        count: u32,
+    }
+
+    impl Render for Counter {
+        fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+            text(format!("{}", self.count))
+        }
+    }
+
+    struct Parent {
+        counters: Vec<View<Counter>>,
+    }
+
+    impl Render for Parent {
+        fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+            let children: Vec<_> = self.counters.iter().map(|c| c.render(cx)).collect();
+            div().children(children)
+        }
+    }
+
+    #[test]
+    fn updating_a_view_marks_the_window_dirty() {
+        let mut scene = Scene::new();
+        let mut text_ctx = TextContext::new();
+        let dirty = DirtyFlag::new();
+        let mut cx = WindowContext::new(&mut scene, &mut text_ctx, ScaleFactor(1.0))
+            .with_dirty_flag(dirty.clone());
+
+        let counter = cx.new_view(|_| Counter { count: 0 });
+        let handle = counter.clone();
+        assert!(!dirty.is_dirty());
+
+        handle.update(|c| c.count += 2);
+        assert_eq!(counter.read().count, 2);
+        assert!(dirty.take());
+        assert!(!dirty.is_dirty());
+        assert!(handle.same_view(&counter));
+    }
+
+    #[test]
+    fn scheduled_frames_mark_the_flag_once_due() {
+        let dirty = DirtyFlag::new();
+        let now = Instant::now();
+        dirty.request_frame_at(now + Duration::from_millis(50));
+        dirty.request_frame_at(now + Duration::from_millis(16));
+        assert_eq!(dirty.next_frame(), Some(now + Duration::from_millis(16)));
+
+        assert!(!dirty.needs_redraw(now));
+        assert!(dirty.needs_redraw(now + Duration::from_millis(16)));
+        assert_eq!(dirty.next_frame(), None);
+        assert!(dirty.take());
+        assert!(!dirty.needs_redraw(now + Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn child_views_render_inside_their_owner() {
+        let mut scene = Scene::new();
+        let mut text_ctx = TextContext::new();
+        let mut layout_engine = LayoutEngine::new();
+        let mut hit_tree = HitTree::new();
+        let mut cx = WindowContext::new(&mut scene, &mut text_ctx, ScaleFactor(1.0));
+
+        let mut parent = Parent {
+            counters: vec![
+                cx.new_view(|_| Counter { count: 1 }),
+                cx.new_view(|_| Counter { count: 2 }),
+            ],
+        };
+        render_view(
+            &mut parent,
+            &mut cx,
+            &mut layout_engine,
+            &mut hit_tree,
+            Size::new(200.0, 100.0),
+        );
+        drop(cx);
+        assert_eq!(scene.text_run_count(), 2);
+    }
+}
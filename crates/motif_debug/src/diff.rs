@@ -0,0 +1,548 @@
+//! Comparing screenshots and scene snapshots.
+//!
+//! [`compare_images`] finds the pixels that changed between two captures and
+//! groups them into regions; [`compare_snapshots`] does the same for the
+//! quads and text runs of two [`SceneSnapshot`]s. Both describe the changes
+//! with a [`DiffReport`], whose image highlights them, so the CLI and test
+//! harnesses report differences the same way.
+
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+
+use image::RgbaImage;
+use serde::Serialize;
+
+use crate::snapshot::{QuadInfo, SceneSnapshot, TextRunInfo};
+
+/// Highlight for changed pixels in image diffs and removed primitives in
+/// snapshot diffs.
+const CHANGED: [u8; 4] = [255, 0, 0, 255];
+
+/// Highlight for primitives added in snapshot diffs.
+const ADDED: [u8; 4] = [0, 160, 0, 255];
+
+/// Fill for primitives present in both snapshots.
+const UNCHANGED: [u8; 4] = [224, 224, 224, 255];
+
+/// A rectangle of pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct DiffRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl DiffRect {
+    fn right(&self) -> u32 {
+        self.x + self.width
+    }
+
+    fn bottom(&self) -> u32 {
+        self.y + self.height
+    }
+
+    /// The smallest rectangle containing both.
+    pub fn union(&self, other: &DiffRect) -> DiffRect {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        DiffRect {
+            x,
+            y,
+            width: self.right().max(other.right()) - x,
+            height: self.bottom().max(other.bottom()) - y,
+        }
+    }
+
+    /// Whether the rectangles overlap or share an edge.
+    fn touches(&self, other: &DiffRect) -> bool {
+        self.x <= other.right()
+            && other.x <= self.right()
+            && self.y <= other.bottom()
+            && other.y <= self.bottom()
+    }
+}
+
+/// How two images or snapshots differ.
+#[derive(Debug, Clone)]
+pub struct DiffReport {
+    /// Pixels that changed.
+    pub changed_pixels: usize,
+    /// Largest channel difference anywhere. Pixels outside one of the
+    /// images, and pixels under changed primitives, count as 255.
+    pub max_delta: u8,
+    /// Bounds of all the changes, or `None` if nothing changed.
+    pub bounds: Option<DiffRect>,
+    /// Bounds of each separate area of change, top to bottom.
+    pub regions: Vec<DiffRect>,
+    /// The second image faded, or the second snapshot's primitives in grey,
+    /// with the changes highlighted.
+    pub image: RgbaImage,
+}
+
+impl DiffReport {
+    /// Whether nothing changed.
+    pub fn is_identical(&self) -> bool {
+        self.changed_pixels == 0
+    }
+
+    /// Save the diff image as a PNG.
+    pub fn save_image(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.image
+            .save(path)
+            .map_err(|e| io::Error::other(e.to_string()))
+    }
+
+    /// The report without its image, for JSON output.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "width": self.image.width(),
+            "height": self.image.height(),
+            "changed_pixels": self.changed_pixels,
+            "max_delta": self.max_delta,
+            "bounds": self.bounds,
+            "regions": self.regions,
+        })
+    }
+
+    /// Build a report from a mask of changed pixels, `width` wide.
+    fn from_mask(mask: &[bool], max_delta: u8, image: RgbaImage) -> Self {
+        let regions = regions(mask, image.width() as usize);
+        DiffReport {
+            changed_pixels: mask.iter().filter(|&&changed| changed).count(),
+            max_delta,
+            bounds: regions.iter().copied().reduce(|a, b| a.union(&b)),
+            regions,
+            image,
+        }
+    }
+}
+
+/// Compare two images pixel for pixel. See [`compare_images_within`].
+pub fn compare_images(a: &RgbaImage, b: &RgbaImage) -> DiffReport {
+    compare_images_within(a, b, 0)
+}
+
+/// Compare two images, counting pixels with any channel differing by more
+/// than `tolerance`.
+///
+/// Images of different sizes are compared over the larger width and height;
+/// pixels outside either image have changed.
+pub fn compare_images_within(a: &RgbaImage, b: &RgbaImage, tolerance: u8) -> DiffReport {
+    let width = a.width().max(b.width());
+    let height = a.height().max(b.height());
+    let mut image = RgbaImage::new(width, height);
+    let mut mask = vec![false; width as usize * height as usize];
+    let mut max_delta = 0;
+
+    for (x, y, out) in image.enumerate_pixels_mut() {
+        let (delta, after) = match (a.get_pixel_checked(x, y), b.get_pixel_checked(x, y)) {
+            (Some(before), Some(after)) => {
+                let delta = (0..4)
+                    .map(|c| before.0[c].abs_diff(after.0[c]))
+                    .max()
+                    .unwrap_or(0);
+                (delta, Some(after.0))
+            }
+            (_, after) => (u8::MAX, after.map(|p| p.0)),
+        };
+        max_delta = max_delta.max(delta);
+        out.0 = match after {
+            Some([r, g, b, _]) if delta <= tolerance => {
+                let luma = (r as u32 * 3 + g as u32 * 6 + b as u32) / 10;
+                let faded = (luma / 4 + 191) as u8;
+                [faded, faded, faded, 255]
+            }
+            _ => {
+                mask[(y * width + x) as usize] = true;
+                CHANGED
+            }
+        };
+    }
+
+    DiffReport::from_mask(&mask, max_delta, image)
+}
+
+/// Compare two PNG files. See [`compare_images`].
+pub fn compare_png_files(a: impl AsRef<Path>, b: impl AsRef<Path>) -> io::Result<DiffReport> {
+    let open = |path: &Path| {
+        image::open(path)
+            .map(|image| image.to_rgba8())
+            .map_err(|e| io::Error::other(format!("{}: {e}", path.display())))
+    };
+    Ok(compare_images(&open(a.as_ref())?, &open(b.as_ref())?))
+}
+
+/// The primitives that differ between two snapshots.
+#[derive(Debug, Clone)]
+pub struct SnapshotDiff {
+    /// Quads in the second snapshot but not the first.
+    pub added_quads: Vec<QuadInfo>,
+    /// Quads in the first snapshot but not the second.
+    pub removed_quads: Vec<QuadInfo>,
+    /// Text runs in the second snapshot but not the first.
+    pub added_text_runs: Vec<TextRunInfo>,
+    /// Text runs in the first snapshot but not the second.
+    pub removed_text_runs: Vec<TextRunInfo>,
+    /// The pixels covered by those primitives, in device pixels.
+    pub report: DiffReport,
+}
+
+impl SnapshotDiff {
+    /// Whether both snapshots hold the same primitives.
+    pub fn is_identical(&self) -> bool {
+        self.added_quads.is_empty()
+            && self.removed_quads.is_empty()
+            && self.added_text_runs.is_empty()
+            && self.removed_text_runs.is_empty()
+    }
+}
+
+/// Compare the quads and text runs of two snapshots.
+///
+/// Primitives are matched by value regardless of paint order, so a moved
+/// quad shows up as one removed and one added. The report covers the larger
+/// of the two viewports; changed pixels are those under any removed or added
+/// primitive's bounds, with quads cut to their clip.
+pub fn compare_snapshots(a: &SceneSnapshot, b: &SceneSnapshot) -> SnapshotDiff {
+    let (removed_quads, unchanged_quads, added_quads) = match_primitives(&a.quads, &b.quads);
+    let (removed_text_runs, unchanged_text_runs, added_text_runs) =
+        match_primitives(&a.text_runs, &b.text_runs);
+
+    let width = a.viewport_size.0.max(b.viewport_size.0).ceil().max(0.0) as u32;
+    let height = a.viewport_size.1.max(b.viewport_size.1).ceil().max(0.0) as u32;
+    let mut image = RgbaImage::from_pixel(width, height, image::Rgba([255; 4]));
+    let mut mask = vec![false; width as usize * height as usize];
+
+    let mut paint = |rect: Option<DiffRect>, color: [u8; 4], changed: bool| {
+        let Some(rect) = rect else { return };
+        for y in rect.y..rect.bottom() {
+            for x in rect.x..rect.right() {
+                let index = (y * width + x) as usize;
+                // Changes stay visible under unchanged primitives.
+                if changed || !mask[index] {
+                    image.put_pixel(x, y, image::Rgba(color));
+                }
+                mask[index] |= changed;
+            }
+        }
+    };
+    for quad in &unchanged_quads {
+        paint(quad_rect(quad, width, height), UNCHANGED, false);
+    }
+    for run in &unchanged_text_runs {
+        paint(text_run_rect(run, width, height), UNCHANGED, false);
+    }
+    for quad in &removed_quads {
+        paint(quad_rect(quad, width, height), CHANGED, true);
+    }
+    for run in &removed_text_runs {
+        paint(text_run_rect(run, width, height), CHANGED, true);
+    }
+    for quad in &added_quads {
+        paint(quad_rect(quad, width, height), ADDED, true);
+    }
+    for run in &added_text_runs {
+        paint(text_run_rect(run, width, height), ADDED, true);
+    }
+
+    let max_delta = if mask.contains(&true) { u8::MAX } else { 0 };
+    SnapshotDiff {
+        added_quads,
+        removed_quads,
+        added_text_runs,
+        removed_text_runs,
+        report: DiffReport::from_mask(&mask, max_delta, image),
+    }
+}
+
+/// Split two lists of primitives into those only in `a`, those in both
+/// (from `b`), and those only in `b`, matching equal values one to one.
+fn match_primitives<T: Clone + Serialize>(a: &[T], b: &[T]) -> (Vec<T>, Vec<T>, Vec<T>) {
+    // Floats aren't `Hash`; their serialized form is, and is exact.
+    let key = |item: &T| serde_json::to_string(item).unwrap_or_default();
+    let mut unmatched: HashMap<String, usize> = HashMap::new();
+    for item in a {
+        *unmatched.entry(key(item)).or_default() += 1;
+    }
+
+    let mut unchanged = Vec::new();
+    let mut added = Vec::new();
+    for item in b {
+        match unmatched.get_mut(&key(item)) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                unchanged.push(item.clone());
+            }
+            _ => added.push(item.clone()),
+        }
+    }
+
+    let mut removed = Vec::new();
+    for item in a {
+        if let Some(count) = unmatched.get_mut(&key(item)) {
+            if *count > 0 {
+                *count -= 1;
+                removed.push(item.clone());
+            }
+        }
+    }
+    (removed, unchanged, added)
+}
+
+/// The pixels a quad covers, cut to its clip and the image.
+fn quad_rect(quad: &QuadInfo, width: u32, height: u32) -> Option<DiffRect> {
+    let b = &quad.bounds;
+    let (mut left, mut top, mut right, mut bottom) = (b.x, b.y, b.x + b.w, b.y + b.h);
+    if let Some(clip) = &quad.clip_bounds {
+        left = left.max(clip.x);
+        top = top.max(clip.y);
+        right = right.min(clip.x + clip.w);
+        bottom = bottom.min(clip.y + clip.h);
+    }
+    pixel_rect(left, top, right, bottom, width, height)
+}
+
+/// The pixels covered by a text run's line boxes.
+fn text_run_rect(run: &TextRunInfo, width: u32, height: u32) -> Option<DiffRect> {
+    run.lines
+        .iter()
+        .filter_map(|line| {
+            pixel_rect(
+                line.x,
+                line.baseline - run.ascent,
+                line.x + line.width,
+                line.baseline + run.descent,
+                width,
+                height,
+            )
+        })
+        .reduce(|a, b| a.union(&b))
+}
+
+/// Whole pixels touched by a device-pixel rect, within the image.
+fn pixel_rect(
+    left: f32,
+    top: f32,
+    right: f32,
+    bottom: f32,
+    width: u32,
+    height: u32,
+) -> Option<DiffRect> {
+    let x = left.floor().clamp(0.0, width as f32) as u32;
+    let y = top.floor().clamp(0.0, height as f32) as u32;
+    let right = right.ceil().clamp(0.0, width as f32) as u32;
+    let bottom = bottom.ceil().clamp(0.0, height as f32) as u32;
+    (right > x && bottom > y).then(|| DiffRect {
+        x,
+        y,
+        width: right - x,
+        height: bottom - y,
+    })
+}
+
+/// Bounding boxes of the connected areas of `mask`, merged where they
+/// overlap or touch, sorted top to bottom then left to right.
+fn regions(mask: &[bool], width: usize) -> Vec<DiffRect> {
+    let mut seen = vec![false; mask.len()];
+    let mut regions: Vec<DiffRect> = Vec::new();
+    let mut stack = Vec::new();
+    let height = mask.len().checked_div(width).unwrap_or(0);
+
+    for start in 0..mask.len() {
+        if !mask[start] || seen[start] {
+            continue;
+        }
+        seen[start] = true;
+        stack.push(start);
+        let (mut left, mut top, mut right, mut bottom) =
+            (start % width, start / width, start % width, start / width);
+        while let Some(index) = stack.pop() {
+            let (x, y) = (index % width, index / width);
+            left = left.min(x);
+            right = right.max(x);
+            top = top.min(y);
+            bottom = bottom.max(y);
+            for ny in y.saturating_sub(1)..=(y + 1).min(height - 1) {
+                for nx in x.saturating_sub(1)..=(x + 1).min(width - 1) {
+                    let neighbor = ny * width + nx;
+                    if mask[neighbor] && !seen[neighbor] {
+                        seen[neighbor] = true;
+                        stack.push(neighbor);
+                    }
+                }
+            }
+        }
+        regions.push(DiffRect {
+            x: left as u32,
+            y: top as u32,
+            width: (right - left + 1) as u32,
+            height: (bottom - top + 1) as u32,
+        });
+    }
+
+    // A region inside another's box (the hole of a ring, say) reads as one
+    // change; merge until no boxes touch.
+    let mut merged = true;
+    while merged {
+        merged = false;
+        let mut i = 0;
+        while i < regions.len() {
+            let mut j = i + 1;
+            while j < regions.len() {
+                if regions[i].touches(&regions[j]) {
+                    let other = regions.swap_remove(j);
+                    regions[i] = regions[i].union(&other);
+                    merged = true;
+                } else {
+                    j += 1;
+                }
+            }
+            i += 1;
+        }
+    }
+    regions.sort_by_key(|r| (r.y, r.x));
+    regions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::{BoundsInfo, ColorInfo, CornersInfo, EdgesInfo};
+    use image::Rgba;
+
+    fn quad(x: f32, y: f32, w: f32, h: f32) -> QuadInfo {
+        let color = ColorInfo {
+            r: 1.0,
+            g: 0.0,
+            b: 0.0,
+            a: 1.0,
+        };
+        QuadInfo {
+            bounds: BoundsInfo { x, y, w, h },
+            color: color.clone(),
+            border_color: color,
+            border_widths: EdgesInfo {
+                top: 0.0,
+                right: 0.0,
+                bottom: 0.0,
+                left: 0.0,
+            },
+            corner_radii: CornersInfo {
+                top_left: 0.0,
+                top_right: 0.0,
+                bottom_right: 0.0,
+                bottom_left: 0.0,
+            },
+            has_clip: false,
+            clip_bounds: None,
+        }
+    }
+
+    fn snapshot(quads: Vec<QuadInfo>) -> SceneSnapshot {
+        SceneSnapshot {
+            quad_count: quads.len(),
+            quads,
+            text_runs: Vec::new(),
+            text_run_count: 0,
+            viewport_size: (20.0, 10.0),
+            scale_factor: 1.0,
+            text_glyphs: None,
+        }
+    }
+
+    #[test]
+    fn image_diff_groups_changed_pixels_into_regions() {
+        let before = RgbaImage::from_pixel(10, 10, Rgba([10, 20, 30, 255]));
+        let mut after = before.clone();
+        for (x, y) in [(1, 1), (2, 2), (7, 6), (8, 7)] {
+            after.put_pixel(x, y, Rgba([200, 20, 30, 255]));
+        }
+        after.put_pixel(5, 5, Rgba([12, 20, 30, 255]));
+
+        let report = compare_images(&before, &after);
+        assert_eq!(report.changed_pixels, 5);
+        assert_eq!(report.max_delta, 190);
+        assert_eq!(
+            report.regions,
+            [
+                DiffRect {
+                    x: 1,
+                    y: 1,
+                    width: 2,
+                    height: 2
+                },
+                DiffRect {
+                    x: 5,
+                    y: 5,
+                    width: 1,
+                    height: 1
+                },
+                DiffRect {
+                    x: 7,
+                    y: 6,
+                    width: 2,
+                    height: 2
+                },
+            ]
+        );
+        assert_eq!(
+            report.bounds,
+            Some(DiffRect {
+                x: 1,
+                y: 1,
+                width: 8,
+                height: 7
+            })
+        );
+        assert_eq!(report.image.get_pixel(1, 1).0, CHANGED);
+        assert_ne!(report.image.get_pixel(0, 0).0, CHANGED);
+
+        let within = compare_images_within(&before, &after, 2);
+        assert_eq!(within.changed_pixels, 4);
+        assert!(compare_images(&before, &before).is_identical());
+    }
+
+    #[test]
+    fn image_diff_counts_pixels_outside_the_smaller_image() {
+        let small = RgbaImage::from_pixel(2, 2, Rgba([0, 0, 0, 255]));
+        let large = RgbaImage::from_pixel(3, 2, Rgba([0, 0, 0, 255]));
+        let report = compare_images(&small, &large);
+        assert_eq!(report.image.dimensions(), (3, 2));
+        assert_eq!(report.changed_pixels, 2);
+        assert_eq!(report.max_delta, 255);
+        assert_eq!(
+            report.regions,
+            [DiffRect {
+                x: 2,
+                y: 0,
+                width: 1,
+                height: 2
+            }]
+        );
+    }
+
+    #[test]
+    fn snapshot_diff_reports_added_and_removed_primitives() {
+        let kept = quad(0.0, 0.0, 20.0, 10.0);
+        let before = snapshot(vec![kept.clone(), quad(2.0, 2.0, 2.0, 2.0)]);
+        let after = snapshot(vec![quad(12.5, 2.0, 2.0, 2.0), kept.clone()]);
+
+        let diff = compare_snapshots(&before, &after);
+        assert_eq!(diff.removed_quads, [quad(2.0, 2.0, 2.0, 2.0)]);
+        assert_eq!(diff.added_quads, [quad(12.5, 2.0, 2.0, 2.0)]);
+        let report = &diff.report;
+        assert_eq!(report.image.dimensions(), (20, 10));
+        // 2x2 removed, plus 3x2 pixels touched by the half-pixel offset quad.
+        assert_eq!(report.changed_pixels, 10);
+        assert_eq!(report.regions.len(), 2);
+        assert_eq!(report.image.get_pixel(2, 2).0, CHANGED);
+        assert_eq!(report.image.get_pixel(13, 3).0, ADDED);
+        assert_eq!(report.image.get_pixel(0, 0).0, UNCHANGED);
+
+        let same = compare_snapshots(&before, &before);
+        assert!(same.is_identical());
+        assert!(same.report.is_identical());
+    }
+}
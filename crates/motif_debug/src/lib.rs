@@ -14,18 +14,39 @@
 //!
 //! // Each frame, after rendering:
 //! let scene = Scene::new();
-//! let snapshot = SceneSnapshot::from_scene(&scene, (800.0, 600.0), 2.0);
+//! let mut snapshot = SceneSnapshot::from_scene(&scene, (800.0, 600.0), 2.0);
+//! if let Some(index) = server.inspected_text_run() {
+//!     snapshot.capture_text_glyphs(&scene, index);
+//! }
 //! server.update_scene(snapshot);
 //! ```
 
+pub mod diff;
 pub mod input_sim;
+pub mod log_filter;
+pub mod methods;
 pub mod protocol;
+pub mod query;
+pub mod registry;
 pub mod screenshot;
 pub mod server;
 pub mod snapshot;
 
+pub use diff::{compare_images, compare_snapshots, DiffRect, DiffReport, SnapshotDiff};
 pub use input_sim::{SimResult, WindowPosition};
-pub use protocol::{DebugError, DebugRequest, DebugResponse};
-pub use screenshot::capture_window_to_png;
-pub use server::DebugServer;
-pub use snapshot::{InputStateSnapshot, OverlayQuad, SceneSnapshot};
+pub use log_filter::LogFilter;
+pub use methods::{MethodSpec, METHODS};
+pub use protocol::{
+    decode_base64, encode_base64, DebugError, DebugNotification, DebugRequest, DebugResponse,
+    Encoding,
+};
+pub use query::Query;
+pub use registry::{list_entries, RegistryEntry, REGISTRY_DIR};
+pub use screenshot::{
+    capture_window_to_png, save_rendered_apng, save_rendered_png, screen_capture_permitted,
+};
+pub use server::{CaptureOptions, DebugServer, PROTOCOL_VERSION};
+pub use snapshot::{
+    AccessNodeInfo, DensityCell, InputStateSnapshot, OverlayArrow, OverlayLabel, OverlayQuad,
+    Primitive, QuadPatch, SceneBounds, SceneSnapshot,
+};
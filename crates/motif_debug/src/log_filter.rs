@@ -0,0 +1,150 @@
+//! Log filters for `log.set_level`.
+//!
+//! The server never talks to a logging backend itself. The app hands it the
+//! filter it started with, in `EnvFilter` directive syntax, and a callback
+//! that installs a new one. [`LogFilter`] tracks the overrides made over the
+//! socket and renders the combined directive string to install:
+//!
+//! ```text
+//! base:       info,wgpu=warn
+//! overrides:  wgpu=trace, (global) debug
+//! installed:  debug,wgpu=trace
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Level names `log.set_level` accepts, quietest first.
+pub const LEVELS: [&str; 6] = ["off", "error", "warn", "info", "debug", "trace"];
+
+/// The app's base filter plus the per-target overrides on top of it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LogFilter {
+    base: String,
+    /// Level and generation by target; `""` overrides the global level.
+    overrides: BTreeMap<String, (String, u64)>,
+    generation: u64,
+}
+
+impl LogFilter {
+    /// Start from `base`, the filter the app installed itself.
+    pub fn new(base: impl Into<String>) -> Self {
+        Self {
+            base: base.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Override the level of `target`, or the global level for `None`.
+    /// Returns a generation to pass to [`expire`](Self::expire), or the
+    /// problem with `level`.
+    pub fn set(&mut self, target: Option<&str>, level: &str) -> Result<u64, String> {
+        let level = level.to_ascii_lowercase();
+        if !LEVELS.contains(&level.as_str()) {
+            return Err(format!(
+                "unknown level {level:?}, expected one of {}",
+                LEVELS.join(", ")
+            ));
+        }
+        if target.is_some_and(|t| t.is_empty() || t.contains([',', '=', ' '])) {
+            return Err(format!("invalid target {:?}", target.unwrap_or("")));
+        }
+        self.generation += 1;
+        self.overrides
+            .insert(target.unwrap_or("").to_string(), (level, self.generation));
+        Ok(self.generation)
+    }
+
+    /// Drop the override of `target` made at `generation`, unless a later
+    /// `set` replaced it. Returns whether the filter changed.
+    pub fn expire(&mut self, target: Option<&str>, generation: u64) -> bool {
+        let key = target.unwrap_or("");
+        if self
+            .overrides
+            .get(key)
+            .is_some_and(|(_, g)| *g == generation)
+        {
+            self.overrides.remove(key);
+            return true;
+        }
+        false
+    }
+
+    /// Drop every override, back to the base filter.
+    pub fn reset(&mut self) {
+        self.overrides.clear();
+    }
+}
+
+/// The directive string to install: the base directives, each replaced by
+/// an override for the same target, followed by overrides for new targets.
+impl fmt::Display for LogFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let target_of = |directive: &str| match directive.split_once('=') {
+            Some((target, _)) => target.trim().to_string(),
+            None => String::new(),
+        };
+        let mut directives: Vec<(String, String)> = self
+            .base
+            .split(',')
+            .map(str::trim)
+            .filter(|d| !d.is_empty())
+            .map(|d| (target_of(d), d.to_string()))
+            .collect();
+        for (target, (level, _)) in &self.overrides {
+            let directive = if target.is_empty() {
+                level.clone()
+            } else {
+                format!("{target}={level}")
+            };
+            match directives.iter_mut().find(|(t, _)| t == target) {
+                Some(existing) => existing.1 = directive,
+                None => directives.push((target.clone(), directive)),
+            }
+        }
+        let directives: Vec<&str> = directives.iter().map(|(_, d)| d.as_str()).collect();
+        f.write_str(&directives.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overrides_replace_matching_base_directives() {
+        let mut filter = LogFilter::new("info,wgpu=warn");
+        assert_eq!(filter.to_string(), "info,wgpu=warn");
+
+        filter.set(Some("wgpu"), "trace").unwrap();
+        filter.set(None, "DEBUG").unwrap();
+        filter.set(Some("motif_core::text"), "trace").unwrap();
+        assert_eq!(
+            filter.to_string(),
+            "debug,wgpu=trace,motif_core::text=trace"
+        );
+
+        filter.reset();
+        assert_eq!(filter.to_string(), "info,wgpu=warn");
+    }
+
+    #[test]
+    fn expiry_ignores_replaced_overrides() {
+        let mut filter = LogFilter::new("warn");
+        let first = filter.set(Some("wgpu"), "trace").unwrap();
+        let second = filter.set(Some("wgpu"), "debug").unwrap();
+        assert!(!filter.expire(Some("wgpu"), first));
+        assert_eq!(filter.to_string(), "warn,wgpu=debug");
+        assert!(filter.expire(Some("wgpu"), second));
+        assert_eq!(filter.to_string(), "warn");
+    }
+
+    #[test]
+    fn rejects_bad_levels_and_targets() {
+        let mut filter = LogFilter::new("");
+        assert!(filter.set(None, "verbose").is_err());
+        assert!(filter.set(Some("a=b"), "info").is_err());
+        assert!(filter.set(Some(""), "info").is_err());
+        assert_eq!(filter.to_string(), "");
+    }
+}
@@ -0,0 +1,756 @@
+//! The debug server's method table.
+//!
+//! Every method the server dispatches is listed here with its parameters and
+//! result shape. `rpc.describe` serves the table as JSON Schema-style
+//! objects so tools can build help and completion from the running server
+//! instead of hard-coding it.
+
+/// A named value in a method's params or result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Field {
+    pub name: &'static str,
+    /// JSON Schema type name. Alternatives are separated by `|`, as in
+    /// `"number|array"`. Empty for any value.
+    pub ty: &'static str,
+    /// Whether a request must include it, or a result always carries it.
+    pub required: bool,
+    pub description: &'static str,
+}
+
+/// What a method returns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shape {
+    /// A single object with these fields.
+    Object(&'static [Field]),
+    /// An array of objects with these fields.
+    Array(&'static [Field]),
+}
+
+/// One method the debug server answers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MethodSpec {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub params: &'static [Field],
+    pub result: Shape,
+}
+
+const fn req(name: &'static str, ty: &'static str, description: &'static str) -> Field {
+    Field {
+        name,
+        ty,
+        required: true,
+        description,
+    }
+}
+
+const fn opt(name: &'static str, ty: &'static str, description: &'static str) -> Field {
+    Field {
+        name,
+        ty,
+        required: false,
+        description,
+    }
+}
+
+const POINT: &[Field] = &[
+    req("x", "number", "Window-local x in logical pixels"),
+    req("y", "number", "Window-local y in logical pixels"),
+];
+
+const COLOR: &str = "RGBA components in 0..1, as [r, g, b, a]";
+const PERSIST: &str = "Keep the overlay after this connection closes (default false)";
+
+/// Every method the debug server dispatches, in dispatch order.
+pub const METHODS: &[MethodSpec] = &[
+    MethodSpec {
+        name: "server.info",
+        description: "Server version, frame counter, renderer and capture support",
+        params: &[],
+        result: Shape::Object(&[
+            req("protocol_version", "integer", "Debug protocol version"),
+            req("version", "string", "motif_debug crate version"),
+            req("pid", "integer", "Process id of the app"),
+            req("platform", "string", "Operating system"),
+            req("frame", "integer", "Scene snapshots received so far"),
+            req(
+                "has_snapshot",
+                "boolean",
+                "Whether a scene snapshot is available",
+            ),
+            req("renderer", "object|null", "Renderer backend and version"),
+            req("gpu_time_ms", "number|null", "GPU time of the last frame"),
+            req("encodings", "array", "Wire encodings rpc.handshake accepts"),
+            req("screenshot", "object", "Screenshot support and permissions"),
+        ]),
+    },
+    MethodSpec {
+        name: "server.stats",
+        description: "Connections, requests served per method and snapshot freshness",
+        params: &[],
+        result: Shape::Object(&[
+            req("uptime_ms", "number", "Time since the server started"),
+            req("connections", "integer", "Connections open now"),
+            req(
+                "total_connections",
+                "integer",
+                "Connections accepted so far",
+            ),
+            req("requests", "object", "Requests served, by method"),
+            req("total_requests", "integer", "Requests served in total"),
+            req(
+                "malformed_requests",
+                "integer",
+                "Frames that didn't decode as a request",
+            ),
+            req("frame", "integer", "Scene snapshots received so far"),
+            req(
+                "snapshot_age_ms",
+                "number|null",
+                "Time since the last scene snapshot",
+            ),
+            req(
+                "snapshot_size",
+                "object|null",
+                "quads, text_runs and json_bytes of the last snapshot",
+            ),
+        ]),
+    },
+    MethodSpec {
+        name: "ping",
+        description: "Check the connection is alive and reset its idle timer",
+        params: &[opt("data", "", "Echoed back unchanged")],
+        result: Shape::Object(&[
+            req("pong", "boolean", "Always true"),
+            req("data", "", "The request's data, or null"),
+        ]),
+    },
+    MethodSpec {
+        name: "rpc.handshake",
+        description: "Switch the connection's wire encoding after this response",
+        params: &[req("encoding", "string", "\"json\" or \"cbor\"")],
+        result: Shape::Object(&[req("encoding", "string", "The encoding now in use")]),
+    },
+    MethodSpec {
+        name: "rpc.describe",
+        description: "Every supported method with its params and result shape",
+        params: &[],
+        result: Shape::Object(&[
+            req("protocol_version", "integer", "Debug protocol version"),
+            req("methods", "array", "One entry per method"),
+        ]),
+    },
+    MethodSpec {
+        name: "scene.stats",
+        description: "Primitive counts, viewport size and scale factor",
+        params: &[],
+        result: Shape::Object(&[
+            req("quad_count", "integer", "Quads in the scene"),
+            req("text_run_count", "integer", "Text runs in the scene"),
+            req(
+                "viewport_size",
+                "array",
+                "[width, height] in logical pixels",
+            ),
+            req("scale_factor", "number", "Device pixels per logical pixel"),
+        ]),
+    },
+    MethodSpec {
+        name: "scene.quads",
+        description: "Every quad in the scene",
+        params: &[],
+        result: Shape::Array(&[
+            req("bounds", "object", "x, y, w, h in logical pixels"),
+            req("color", "object", "Fill color as r, g, b, a"),
+        ]),
+    },
+    MethodSpec {
+        name: "scene.text_runs",
+        description: "Every text run in the scene",
+        params: &[],
+        result: Shape::Array(&[
+            req("origin", "object", "x, y of the run's origin"),
+            req("font_size", "number", "Font size in logical pixels"),
+            req("glyph_count", "integer", "Glyphs in the run"),
+            req("color", "object", "Text color as r, g, b, a"),
+        ]),
+    },
+    MethodSpec {
+        name: "scene.bounds",
+        description: "Union bounds of all primitives and how much of the viewport they cover",
+        params: &[],
+        result: Shape::Object(&[
+            req("bounds", "object|null", "x, y, w, h in device pixels"),
+            req(
+                "coverage_percent",
+                "number",
+                "Viewport area covered by primitives",
+            ),
+            req("extent_ratio", "number", "Bounds area over viewport area"),
+            req(
+                "outside_quads",
+                "integer",
+                "Quads entirely outside the viewport",
+            ),
+            req(
+                "outside_text_runs",
+                "integer",
+                "Text runs entirely outside the viewport",
+            ),
+        ]),
+    },
+    MethodSpec {
+        name: "scene.query",
+        description: "Quads and text runs matching an expression",
+        params: &[
+            req("expr", "string", "e.g. \"bounds.w > 500 && color.a < 1.0\""),
+            opt(
+                "from",
+                "string",
+                "\"quads\" or \"text_runs\" (default both)",
+            ),
+        ],
+        result: Shape::Object(&[
+            req("count", "integer", "Total matches"),
+            req("quads", "array", "Matching quads, each with its index"),
+            req(
+                "text_runs",
+                "array",
+                "Matching text runs, each with its index",
+            ),
+        ]),
+    },
+    MethodSpec {
+        name: "scene.patch",
+        description: "Override a quad in the frames the app builds next",
+        params: &[
+            opt("quad", "integer", "Quad index from scene.quads"),
+            opt("dx", "number", "Move right by this many device pixels"),
+            opt("dy", "number", "Move down by this many device pixels"),
+            opt("color", "array", "New background [r, g, b, a]"),
+            opt("hidden", "boolean", "Draw the quad transparent"),
+            opt("reset", "boolean", "Drop this quad's override"),
+            opt("clear", "boolean", "Drop every override"),
+        ],
+        result: Shape::Object(&[req(
+            "patches",
+            "array",
+            "Every override now queued: quad, dx, dy, color, hidden",
+        )]),
+    },
+    MethodSpec {
+        name: "scene.profile",
+        description: "Time spent in each frame phase, last frame and average",
+        params: &[opt(
+            "frames",
+            "integer",
+            "Frames to average over (default 60)",
+        )],
+        result: Shape::Object(&[
+            req("frames", "integer", "Frames actually averaged"),
+            req("last", "object", "Phase timings of the last frame in ms"),
+            req("average", "object", "Average phase timings in ms"),
+        ]),
+    },
+    MethodSpec {
+        name: "perf.set_budget",
+        description: "Notify this connection of frames slower than a budget",
+        params: &[req("ms", "number", "Frame budget in ms (0 clears it)")],
+        result: Shape::Object(&[req("budget_ms", "number|null", "The budget now set")]),
+    },
+    MethodSpec {
+        name: "fps",
+        description: "Frame rate over the last 120 frames",
+        params: &[],
+        result: Shape::Object(&[
+            req("current", "number", "Rate implied by the last frame"),
+            req("average", "number", "Average rate"),
+            req("min", "number", "Rate implied by the slowest frame"),
+            req("max", "number", "Rate implied by the fastest frame"),
+            req("frame_time_ms", "number", "Average frame interval"),
+            req("samples", "integer", "Frame intervals measured"),
+            req("frame", "integer", "Scene snapshots received"),
+            req("since_last_frame_ms", "number", "Time since the last frame"),
+        ]),
+    },
+    MethodSpec {
+        name: "log.set_level",
+        description: "Override the app's log level, globally or for one target",
+        params: &[
+            opt(
+                "level",
+                "string",
+                "off, error, warn, info, debug or trace (required unless reset)",
+            ),
+            opt("target", "string", "Module path to override; omit for all"),
+            opt(
+                "duration_ms",
+                "integer",
+                "Revert the override after this long",
+            ),
+            opt("reset", "boolean", "Drop every override instead"),
+        ],
+        result: Shape::Object(&[
+            req("filter", "string", "The filter directives now installed"),
+            req("expires_ms", "integer|null", "When the override reverts"),
+        ]),
+    },
+    MethodSpec {
+        name: "input.state",
+        description: "Cursor position, mouse buttons, modifiers and hovered element",
+        params: &[],
+        result: Shape::Object(&[
+            req("cursor_position", "object|null", "x, y of the cursor"),
+            req("mouse_buttons", "array", "Buttons currently held"),
+            req("modifiers", "object", "shift, control, alt and super flags"),
+            req(
+                "hovered_element",
+                "integer|null",
+                "Element under the cursor",
+            ),
+            req("pressed_element", "integer|null", "Element being pressed"),
+        ]),
+    },
+    MethodSpec {
+        name: "input.activate",
+        description: "Bring the app window to the front",
+        params: &[],
+        result: Shape::Object(&[
+            req("activated", "boolean", "Always true on success"),
+            req("message", "string", "Platform detail"),
+        ]),
+    },
+    MethodSpec {
+        name: "input.move_to",
+        description: "Move the mouse to window-local coordinates",
+        params: POINT,
+        result: Shape::Object(&[
+            req("moved_to", "object", "Window-local x, y"),
+            req("screen", "object", "Screen x, y"),
+        ]),
+    },
+    MethodSpec {
+        name: "input.click",
+        description: "Click at window-local coordinates",
+        params: POINT,
+        result: Shape::Object(&[
+            req("clicked_at", "object", "Window-local x, y"),
+            req("screen", "object", "Screen x, y"),
+        ]),
+    },
+    MethodSpec {
+        name: "input.mouse_down",
+        description: "Press the mouse button at window-local coordinates",
+        params: POINT,
+        result: Shape::Object(&[
+            req("mouse_down_at", "object", "Window-local x, y"),
+            req("screen", "object", "Screen x, y"),
+        ]),
+    },
+    MethodSpec {
+        name: "input.mouse_up",
+        description: "Release the mouse button at window-local coordinates",
+        params: POINT,
+        result: Shape::Object(&[
+            req("mouse_up_at", "object", "Window-local x, y"),
+            req("screen", "object", "Screen x, y"),
+        ]),
+    },
+    MethodSpec {
+        name: "input.drag",
+        description: "Drag from one point to another",
+        params: &[
+            req("from_x", "number", "Start x"),
+            req("from_y", "number", "Start y"),
+            req("to_x", "number", "End x"),
+            req("to_y", "number", "End y"),
+        ],
+        result: Shape::Object(&[req("dragged", "object", "from and to points")]),
+    },
+    MethodSpec {
+        name: "screenshot",
+        description: "Capture the window or the rendered frame to a PNG",
+        params: &[
+            req("path", "string", "Output PNG path"),
+            opt("source", "string", "\"renderer\" or \"window\""),
+            opt("scale", "number", "Render scale for renderer captures"),
+            opt("width", "number", "Logical width; requires height"),
+            opt("height", "number", "Logical height; requires width"),
+            opt(
+                "include_overlays",
+                "boolean",
+                "Draw debug overlays into the capture",
+            ),
+        ],
+        result: Shape::Object(&[
+            req("path", "string", "Where the PNG was written"),
+            req("source", "string", "\"renderer\" or \"window\""),
+            opt("width", "integer", "Image width in pixels"),
+            opt("height", "integer", "Image height in pixels"),
+        ]),
+    },
+    MethodSpec {
+        name: "framebuffer.get",
+        description: "The current frame's RGBA pixels",
+        params: &[opt(
+            "encoding",
+            "string",
+            "\"base64\" (default) or \"binary\"",
+        )],
+        result: Shape::Object(&[
+            req("width", "integer", "Width in pixels"),
+            req("height", "integer", "Height in pixels"),
+            req("format", "string", "Always \"rgba8\""),
+            req("encoding", "string", "How the pixels are sent"),
+            req("bytes", "integer", "Pixel data length"),
+            opt("data", "string", "Base64 pixels, unless sent as binary"),
+        ]),
+    },
+    MethodSpec {
+        name: "pixel.color",
+        description: "The color at a window point and the topmost primitive there",
+        params: &[
+            req("x", "number", "Window x in logical pixels"),
+            req("y", "number", "Window y in logical pixels"),
+        ],
+        result: Shape::Object(&[
+            req("x", "number", "The x asked for"),
+            req("y", "number", "The y asked for"),
+            req("device", "array", "The device pixel sampled, [x, y]"),
+            req("color", "object", "r, g, b, a from 0 to 1"),
+            req("hex", "string", "#rrggbbaa"),
+            req(
+                "source",
+                "string",
+                "\"renderer\" readback or a \"snapshot\" approximation",
+            ),
+            req(
+                "primitive",
+                "object|null",
+                "kind, index and info of the topmost quad or text run",
+            ),
+        ]),
+    },
+    MethodSpec {
+        name: "atlas.dump",
+        description: "Save the glyph atlas pages to a PNG with per-row occupancy",
+        params: &[req("path", "string", "Output PNG path")],
+        result: Shape::Object(&[
+            req("path", "string", "Where the PNG was written"),
+            req("width", "integer", "Width in pixels, one page"),
+            req("height", "integer", "Height in pixels, pages stacked top to bottom"),
+            req("page_size", "integer", "Side length of each page"),
+            req(
+                "pages",
+                "array",
+                "index, glyphs, last_used, used_height, rows[{y, height, glyphs, used_width, fill}]",
+            ),
+        ]),
+    },
+    MethodSpec {
+        name: "capture.start",
+        description: "Record frames to an animated PNG",
+        params: &[
+            req("path", "string", "Output APNG path"),
+            req("frames", "integer", "Frames to record"),
+            opt("every", "integer", "Keep one frame in every N (default 1)"),
+        ],
+        result: Shape::Object(&[
+            req("path", "string", "Where the APNG was written"),
+            req("format", "string", "Always \"apng\""),
+            req("frames", "integer", "Frames recorded"),
+            req("width", "integer", "Width in pixels"),
+            req("height", "integer", "Height in pixels"),
+            req("duration_ms", "number", "Recording length"),
+            req("stopped", "boolean", "Whether capture.stop ended it early"),
+        ]),
+    },
+    MethodSpec {
+        name: "capture.stop",
+        description: "End a recording early, keeping its frames",
+        params: &[],
+        result: Shape::Object(&[req("stopped", "boolean", "Whether a recording was running")]),
+    },
+    MethodSpec {
+        name: "debug.draw_quad",
+        description: "Draw a debug overlay quad",
+        params: &[
+            opt("x", "number", "Left edge (default 0)"),
+            opt("y", "number", "Top edge (default 0)"),
+            opt("w", "number", "Width (default 100)"),
+            opt("h", "number", "Height (default 100)"),
+            opt("color", "array", COLOR),
+            opt("border_color", "array", COLOR),
+            opt("border_width", "number", "Border width"),
+            opt("corner_radius", "number", "Corner radius"),
+            opt("persist", "boolean", PERSIST),
+        ],
+        result: Shape::Object(&[req("id", "integer", "Overlay id for debug.remove")]),
+    },
+    MethodSpec {
+        name: "debug.draw_arrow",
+        description: "Draw an arrow overlay, optionally labelled at its tail",
+        params: &[
+            req("from", "array", "Tail as [x, y] in logical pixels"),
+            req("to", "array", "Head as [x, y] in logical pixels"),
+            opt("color", "array", COLOR),
+            opt("label", "string", "Text drawn by the tail"),
+            opt("width", "number", "Stroke width (default 2)"),
+            opt("persist", "boolean", PERSIST),
+        ],
+        result: Shape::Object(&[req("id", "integer", "Overlay id for debug.remove")]),
+    },
+    MethodSpec {
+        name: "debug.clear",
+        description: "Remove all debug overlays",
+        params: &[],
+        result: Shape::Object(&[req("cleared", "integer", "Overlays removed")]),
+    },
+    MethodSpec {
+        name: "debug.remove",
+        description: "Remove one debug overlay",
+        params: &[req("id", "integer", "Overlay id from debug.draw_quad")],
+        result: Shape::Object(&[req("removed", "boolean", "Whether the overlay existed")]),
+    },
+    MethodSpec {
+        name: "debug.list",
+        description: "Every debug overlay quad, then every arrow (with from, to, label)",
+        params: &[],
+        result: Shape::Array(&[
+            req("id", "integer", "Overlay id"),
+            req("x", "number", "Left edge"),
+            req("y", "number", "Top edge"),
+            req("w", "number", "Width"),
+            req("h", "number", "Height"),
+            req("color", "object", "Fill color as r, g, b, a"),
+            req("border_color", "object", "Border color as r, g, b, a"),
+            req("border_width", "number", "Border width"),
+            req("corner_radius", "number", "Corner radius"),
+            req(
+                "connection",
+                "integer|null",
+                "Connection the overlay goes away with, null if it persists",
+            ),
+        ]),
+    },
+    MethodSpec {
+        name: "debug.show_guides",
+        description: "Draw margin guides inset from a window's edges",
+        params: &[
+            req(
+                "insets",
+                "number|array",
+                "One inset or [top, right, bottom, left]; 0 hides",
+            ),
+            opt("window", "integer", "Window id (default: the app's window)"),
+            opt("color", "array", COLOR),
+        ],
+        result: Shape::Object(&[
+            req("enabled", "boolean", "Whether guides are shown"),
+            req("window", "integer|null", "Window the guides belong to"),
+            req("insets", "object", "top, right, bottom, left"),
+        ]),
+    },
+    MethodSpec {
+        name: "debug.inspect_text",
+        description: "Overlay a text run's line boxes, baseline and advances",
+        params: &[req(
+            "index",
+            "integer|null",
+            "Text run index, or null to stop",
+        )],
+        result: Shape::Object(&[
+            req("inspecting", "integer|null", "Run being inspected"),
+            opt("origin", "object", "x, y of the run's origin"),
+            opt("font_size", "number", "Font size"),
+            opt("ascent", "number", "Ascent"),
+            opt("descent", "number", "Descent"),
+            opt("lines", "array", "Line boxes"),
+            opt("glyphs", "array", "Glyph positions"),
+        ]),
+    },
+    MethodSpec {
+        name: "debug.overdraw",
+        description: "Shade pixels by how often they're drawn",
+        params: &[opt("enabled", "boolean", "Omit to toggle")],
+        result: Shape::Object(&[req("enabled", "boolean", "Whether the heat map is on")]),
+    },
+    MethodSpec {
+        name: "debug.time_scale",
+        description: "Run timers and animations faster or slower",
+        params: &[req("scale", "number", "Speed multiplier (0 pauses)")],
+        result: Shape::Object(&[req("scale", "number", "The scale now set")]),
+    },
+    MethodSpec {
+        name: "debug.crosshair",
+        description: "Draw hairlines and a coordinate readout at the pointer",
+        params: &[opt("on", "boolean", "Omit to toggle")],
+        result: Shape::Object(&[
+            req("on", "boolean", "Whether the crosshair is shown"),
+            req("pointer", "object|null", "Last reported pointer x, y"),
+        ]),
+    },
+    MethodSpec {
+        name: "debug.highlight_access",
+        description: "Flash an outline over an accessibility node's bounds",
+        params: &[
+            req("id", "integer", "Access node id"),
+            opt(
+                "duration_ms",
+                "integer",
+                "How long to show it (default 1500)",
+            ),
+        ],
+        result: Shape::Object(&[
+            req("id", "integer", "Access node id"),
+            req("role", "string", "Node role"),
+            req("name", "string", "Accessible name"),
+            req("bounds", "object", "x, y, w, h in logical pixels"),
+            req("duration_ms", "integer", "How long the outline shows"),
+        ]),
+    },
+    MethodSpec {
+        name: "debug.fps_overlay",
+        description: "Show a live frame-rate widget in the top-right corner",
+        params: &[opt("on", "boolean", "Omit to toggle")],
+        result: Shape::Object(&[req("on", "boolean", "Whether the widget is shown")]),
+    },
+    MethodSpec {
+        name: "debug.show_density",
+        description: "Shade a grid by primitive count",
+        params: &[req(
+            "cell",
+            "number",
+            "Cell size in logical pixels (0 hides)",
+        )],
+        result: Shape::Object(&[
+            req("enabled", "boolean", "Whether the grid is shown"),
+            opt("cell", "number", "Cell size"),
+            opt("busiest", "array", "Cells with the most primitives"),
+        ]),
+    },
+    MethodSpec {
+        name: "debug.show_baseline_grid",
+        description: "Draw baseline rules every line height",
+        params: &[
+            req(
+                "line_height",
+                "number",
+                "Rule spacing in logical pixels (0 hides)",
+            ),
+            opt("color", "array", COLOR),
+        ],
+        result: Shape::Object(&[
+            req("enabled", "boolean", "Whether the grid is shown"),
+            req("line_height", "number", "Rule spacing"),
+        ]),
+    },
+    MethodSpec {
+        name: "debug.show_spacing",
+        description: "Draw spacing rules on a fixed scale",
+        params: &[
+            req(
+                "scale",
+                "number",
+                "Rule spacing in logical pixels (0 hides)",
+            ),
+            opt("color", "array", COLOR),
+        ],
+        result: Shape::Object(&[
+            req("enabled", "boolean", "Whether the rules are shown"),
+            req("scale", "number", "Rule spacing"),
+        ]),
+    },
+];
+
+/// Look up a method by name.
+pub fn find(name: &str) -> Option<&'static MethodSpec> {
+    METHODS.iter().find(|method| method.name == name)
+}
+
+impl Field {
+    fn schema(&self) -> serde_json::Value {
+        let mut schema = serde_json::json!({ "description": self.description });
+        let types: Vec<&str> = self.ty.split('|').collect();
+        match types.as_slice() {
+            [""] => {}
+            [ty] => schema["type"] = serde_json::json!(ty),
+            types => schema["type"] = serde_json::json!(types),
+        }
+        schema
+    }
+}
+
+/// A JSON Schema-style object schema for `fields`.
+fn object_schema(fields: &[Field]) -> serde_json::Value {
+    let properties: serde_json::Map<String, serde_json::Value> = fields
+        .iter()
+        .map(|field| (field.name.to_string(), field.schema()))
+        .collect();
+    let required: Vec<&str> = fields
+        .iter()
+        .filter(|field| field.required)
+        .map(|field| field.name)
+        .collect();
+    serde_json::json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    })
+}
+
+impl Shape {
+    fn schema(&self) -> serde_json::Value {
+        match self {
+            Shape::Object(fields) => object_schema(fields),
+            Shape::Array(fields) => serde_json::json!({
+                "type": "array",
+                "items": object_schema(fields),
+            }),
+        }
+    }
+}
+
+impl MethodSpec {
+    /// This method as a `rpc.describe` entry.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "name": self.name,
+            "description": self.description,
+            "params": object_schema(self.params),
+            "result": self.result.schema(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn method_names_are_unique() {
+        let mut seen = HashSet::new();
+        for method in METHODS {
+            assert!(seen.insert(method.name), "{} listed twice", method.name);
+        }
+    }
+
+    #[test]
+    fn schema_lists_required_params_and_union_types() {
+        let guides = find("debug.show_guides").unwrap().to_json();
+        assert_eq!(guides["params"]["required"], serde_json::json!(["insets"]));
+        assert_eq!(
+            guides["params"]["properties"]["insets"]["type"],
+            serde_json::json!(["number", "array"])
+        );
+        assert_eq!(
+            guides["params"]["properties"]["color"]["type"],
+            serde_json::json!("array")
+        );
+
+        let quads = find("scene.quads").unwrap().to_json();
+        assert_eq!(quads["result"]["type"], "array");
+        assert_eq!(quads["result"]["items"]["type"], "object");
+    }
+}
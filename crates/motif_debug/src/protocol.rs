@@ -1,8 +1,11 @@
 //! Wire protocol for the motif debug server.
 //!
-//! Uses JSON-RPC 2.0 style messages over newline-delimited JSON.
+//! Uses JSON-RPC 2.0 style messages over newline-delimited JSON. A client
+//! can switch its connection to CBOR with `rpc.handshake`; see [`Encoding`].
 
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead};
 
 /// A debug request from a client.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -50,6 +53,166 @@ impl DebugResponse {
     }
 }
 
+/// A message the server pushes without a request, such as a
+/// `perf.budget_exceeded` alert. Unlike a response it carries no `id`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DebugNotification {
+    pub method: String,
+    pub params: serde_json::Value,
+}
+
+impl DebugNotification {
+    pub fn new(method: impl Into<String>, params: serde_json::Value) -> Self {
+        Self {
+            method: method.into(),
+            params,
+        }
+    }
+}
+
+/// How messages on a connection are encoded.
+///
+/// Connections start as newline-delimited JSON, which stays readable for
+/// tools like `nc`. A client that moves large snapshots can send
+/// `rpc.handshake` with `{"encoding": "cbor"}`: the response still comes as
+/// JSON, and every message after it in either direction is CBOR, framed by
+/// a 4-byte big-endian length instead of a newline.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Encoding {
+    #[default]
+    Json,
+    Cbor,
+}
+
+/// Largest CBOR frame [`Encoding::read_frame`] accepts, to keep a corrupt
+/// length prefix from allocating gigabytes.
+const MAX_FRAME_LEN: usize = 256 << 20;
+
+impl Encoding {
+    /// Every encoding, in the order `server.info` lists them.
+    pub const ALL: [Encoding; 2] = [Encoding::Json, Encoding::Cbor];
+
+    /// The name used in `rpc.handshake`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Encoding::Json => "json",
+            Encoding::Cbor => "cbor",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|encoding| encoding.name() == name)
+    }
+
+    /// Encode `message` as one frame, delimiter included.
+    pub fn encode<T: Serialize>(self, message: &T) -> io::Result<Vec<u8>> {
+        match self {
+            Encoding::Json => {
+                let mut frame = serde_json::to_vec(message)?;
+                frame.push(b'\n');
+                Ok(frame)
+            }
+            Encoding::Cbor => {
+                let mut frame = vec![0; 4];
+                ciborium::into_writer(message, &mut frame)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+                let len = u32::try_from(frame.len() - 4)
+                    .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "message too large"))?;
+                frame[..4].copy_from_slice(&len.to_be_bytes());
+                Ok(frame)
+            }
+        }
+    }
+
+    /// Read the next frame, without its delimiter. `Ok(None)` once the
+    /// stream has ended.
+    pub fn read_frame(self, reader: &mut impl BufRead) -> io::Result<Option<Vec<u8>>> {
+        match self {
+            Encoding::Json => {
+                let mut frame = Vec::new();
+                if reader.read_until(b'\n', &mut frame)? == 0 {
+                    return Ok(None);
+                }
+                if frame.last() == Some(&b'\n') {
+                    frame.pop();
+                }
+                Ok(Some(frame))
+            }
+            Encoding::Cbor => {
+                let mut len = [0; 4];
+                match reader.read_exact(&mut len) {
+                    Ok(()) => {}
+                    Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+                    Err(e) => return Err(e),
+                }
+                let len = u32::from_be_bytes(len) as usize;
+                if len > MAX_FRAME_LEN {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("frame of {len} bytes is too large"),
+                    ));
+                }
+                let mut frame = vec![0; len];
+                reader.read_exact(&mut frame)?;
+                Ok(Some(frame))
+            }
+        }
+    }
+
+    /// Decode a frame returned by [`read_frame`](Self::read_frame).
+    pub fn decode<T: DeserializeOwned>(self, frame: &[u8]) -> io::Result<T> {
+        match self {
+            Encoding::Json => Ok(serde_json::from_slice(frame)?),
+            Encoding::Cbor => ciborium::from_reader(frame)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string())),
+        }
+    }
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Standard, padded base64, for binary data in JSON results.
+pub fn encode_base64(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64_ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Decode [`encode_base64`] output. `None` if `text` isn't valid base64.
+pub fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let text = text.trim_end_matches('=');
+    let mut out = Vec::with_capacity(text.len() * 3 / 4);
+    let mut bits = 0u32;
+    let mut count = 0;
+    for c in text.bytes() {
+        let value = BASE64_ALPHABET.iter().position(|&a| a == c)? as u32;
+        bits = bits << 6 | value;
+        count += 6;
+        if count >= 8 {
+            count -= 8;
+            out.push((bits >> count) as u8);
+        }
+    }
+    Some(out)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -101,6 +264,47 @@ mod tests {
         assert_eq!(err.message, "Method not found");
     }
 
+    #[test]
+    fn notification_is_not_mistaken_for_a_response() {
+        let note = DebugNotification::new("perf.budget_exceeded", json!({"total_ms": 20.0}));
+        let json = serde_json::to_string(&note).unwrap();
+        assert!(serde_json::from_str::<DebugResponse>(&json).is_err());
+        let deserialized: DebugNotification = serde_json::from_str(&json).unwrap();
+        assert_eq!(note, deserialized);
+    }
+
+    #[test]
+    fn messages_round_trip_in_every_encoding() {
+        let resp = DebugResponse::ok(3, json!({"quads": [{"x": 1.5, "label": "a"}], "n": -2}));
+        let note = DebugNotification::new("perf.budget_exceeded", json!({"total_ms": 20.0}));
+        for encoding in Encoding::ALL {
+            let mut stream = encoding.encode(&resp).unwrap();
+            stream.extend(encoding.encode(&note).unwrap());
+            let mut reader = io::BufReader::new(stream.as_slice());
+
+            let frame = encoding.read_frame(&mut reader).unwrap().unwrap();
+            assert_eq!(encoding.decode::<DebugResponse>(&frame).unwrap(), resp);
+            let frame = encoding.read_frame(&mut reader).unwrap().unwrap();
+            assert!(encoding.decode::<DebugResponse>(&frame).is_err());
+            assert_eq!(encoding.decode::<DebugNotification>(&frame).unwrap(), note);
+            assert!(encoding.read_frame(&mut reader).unwrap().is_none());
+        }
+        assert_eq!(Encoding::from_name("cbor"), Some(Encoding::Cbor));
+        assert_eq!(Encoding::from_name("msgpack"), None);
+    }
+
+    #[test]
+    fn base64_round_trips_every_padding_length() {
+        assert_eq!(encode_base64(b""), "");
+        assert_eq!(encode_base64(b"f"), "Zg==");
+        assert_eq!(encode_base64(b"fo"), "Zm8=");
+        assert_eq!(encode_base64(b"foo"), "Zm9v");
+        assert_eq!(encode_base64(b"foobar"), "Zm9vYmFy");
+        let bytes: Vec<u8> = (0..=255).collect();
+        assert_eq!(decode_base64(&encode_base64(&bytes)), Some(bytes));
+        assert_eq!(decode_base64("Zm9v!"), None);
+    }
+
     #[test]
     fn request_deserializes_from_raw_json() {
         let raw = r#"{"method":"scene.stats","params":null,"id":7}"#;
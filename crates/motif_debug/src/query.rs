@@ -0,0 +1,325 @@
+//! Filter expressions for `scene.query`.
+//!
+//! A query is a small boolean expression evaluated against each JSON object
+//! `scene.quads` or `scene.text_runs` would return:
+//!
+//! ```text
+//! bounds.w > 500 && color.a < 1.0
+//! !has_clip || (font_size >= 14 && glyph_count == 0)
+//! ```
+//!
+//! Operands are dotted field paths, numbers, `'strings'` or `"strings"`,
+//! `true`, `false` and `null`. Operators are `==`, `!=`, `<`, `<=`, `>`,
+//! `>=`, `&&`, `||` and `!`, with parentheses for grouping. A missing field
+//! is `null`, and ordering comparisons are false unless both sides are
+//! numbers or both are strings.
+
+use serde_json::Value;
+
+/// A parsed `scene.query` expression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    expr: Expr,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Literal(Value),
+    Field(Vec<String>),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Compare(Box<Expr>, CompareOp, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Str(String),
+    Ident(String),
+    Compare(CompareOp),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+impl Query {
+    /// Parse an expression, describing the first problem on failure.
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.or()?;
+        if let Some(token) = parser.tokens.get(parser.pos) {
+            return Err(format!("unexpected {} after expression", describe(token)));
+        }
+        Ok(Self { expr })
+    }
+
+    /// Whether `item` satisfies the expression.
+    pub fn matches(&self, item: &Value) -> bool {
+        truthy(&self.expr.eval(item))
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        let two = source.get(start..start + 2).unwrap_or("");
+        let (token, len) = match (c, two) {
+            (c, _) if c.is_whitespace() => {
+                chars.next();
+                continue;
+            }
+            (_, "&&") => (Token::And, 2),
+            (_, "||") => (Token::Or, 2),
+            (_, "==") => (Token::Compare(CompareOp::Eq), 2),
+            (_, "!=") => (Token::Compare(CompareOp::Ne), 2),
+            (_, "<=") => (Token::Compare(CompareOp::Le), 2),
+            (_, ">=") => (Token::Compare(CompareOp::Ge), 2),
+            ('<', _) => (Token::Compare(CompareOp::Lt), 1),
+            ('>', _) => (Token::Compare(CompareOp::Gt), 1),
+            ('!', _) => (Token::Not, 1),
+            ('(', _) => (Token::Open, 1),
+            (')', _) => (Token::Close, 1),
+            ('\'' | '"', _) => {
+                let end = source[start + 1..]
+                    .find(c)
+                    .ok_or_else(|| format!("unterminated string at {start}"))?;
+                let text = &source[start + 1..start + 1 + end];
+                (Token::Str(text.to_string()), end + 2)
+            }
+            (c, _) if c.is_ascii_digit() || c == '-' || c == '.' => {
+                let len = source[start + 1..]
+                    .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == 'e'))
+                    .map_or(source.len() - start, |end| end + 1);
+                let text = &source[start..start + len];
+                let number = text
+                    .parse()
+                    .map_err(|_| format!("invalid number {text:?} at {start}"))?;
+                (Token::Number(number), len)
+            }
+            (c, _) if c.is_alphabetic() || c == '_' => {
+                let len = source[start..]
+                    .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.'))
+                    .unwrap_or(source.len() - start);
+                (Token::Ident(source[start..start + len].to_string()), len)
+            }
+            (c, _) => return Err(format!("unexpected {c:?} at {start}")),
+        };
+        tokens.push(token);
+        while chars.next_if(|&(i, _)| i < start + len).is_some() {}
+    }
+    Ok(tokens)
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Number(n) => format!("number {n}"),
+        Token::Str(s) => format!("string {s:?}"),
+        Token::Ident(name) => format!("{name:?}"),
+        Token::Compare(_) => "comparison".to_string(),
+        Token::And => "'&&'".to_string(),
+        Token::Or => "'||'".to_string(),
+        Token::Not => "'!'".to_string(),
+        Token::Open => "'('".to_string(),
+        Token::Close => "')'".to_string(),
+    }
+}
+
+/// Recursive descent over the tokens, loosest binding first:
+/// `||`, then `&&`, then `!`, then comparisons.
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn eat(&mut self, token: &Token) -> bool {
+        let matched = self.tokens.get(self.pos) == Some(token);
+        if matched {
+            self.pos += 1;
+        }
+        matched
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut expr = self.and()?;
+        while self.eat(&Token::Or) {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut expr = self.unary()?;
+        while self.eat(&Token::And) {
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat(&Token::Not) {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        let left = self.operand()?;
+        match self.tokens.get(self.pos) {
+            Some(&Token::Compare(op)) => {
+                self.pos += 1;
+                let right = self.operand()?;
+                Ok(Expr::Compare(Box::new(left), op, Box::new(right)))
+            }
+            _ => Ok(left),
+        }
+    }
+
+    fn operand(&mut self) -> Result<Expr, String> {
+        let Some(token) = self.tokens.get(self.pos).cloned() else {
+            return Err("expression ended early".to_string());
+        };
+        self.pos += 1;
+        match token {
+            Token::Number(n) => Ok(Expr::Literal(n.into())),
+            Token::Str(s) => Ok(Expr::Literal(s.into())),
+            Token::Ident(name) => Ok(match name.as_str() {
+                "true" => Expr::Literal(true.into()),
+                "false" => Expr::Literal(false.into()),
+                "null" => Expr::Literal(Value::Null),
+                _ => Expr::Field(name.split('.').map(str::to_string).collect()),
+            }),
+            Token::Open => {
+                let expr = self.or()?;
+                if !self.eat(&Token::Close) {
+                    return Err("missing ')'".to_string());
+                }
+                Ok(expr)
+            }
+            token => Err(format!("expected a value, found {}", describe(&token))),
+        }
+    }
+}
+
+impl Expr {
+    fn eval(&self, item: &Value) -> Value {
+        match self {
+            Expr::Literal(value) => value.clone(),
+            Expr::Field(path) => path
+                .iter()
+                .try_fold(item, |value, key| value.get(key))
+                .cloned()
+                .unwrap_or(Value::Null),
+            Expr::Not(expr) => (!truthy(&expr.eval(item))).into(),
+            Expr::And(a, b) => (truthy(&a.eval(item)) && truthy(&b.eval(item))).into(),
+            Expr::Or(a, b) => (truthy(&a.eval(item)) || truthy(&b.eval(item))).into(),
+            Expr::Compare(a, op, b) => compare(&a.eval(item), *op, &b.eval(item)).into(),
+        }
+    }
+}
+
+fn compare(a: &Value, op: CompareOp, b: &Value) -> bool {
+    let ordering = match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a
+            .as_f64()
+            .unwrap_or(f64::NAN)
+            .partial_cmp(&b.as_f64().unwrap_or(f64::NAN)),
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        _ => None,
+    };
+    match op {
+        CompareOp::Eq => ordering.map_or(a == b, |o| o.is_eq()),
+        CompareOp::Ne => ordering.map_or(a != b, |o| o.is_ne()),
+        CompareOp::Lt => ordering.is_some_and(|o| o.is_lt()),
+        CompareOp::Le => ordering.is_some_and(|o| o.is_le()),
+        CompareOp::Gt => ordering.is_some_and(|o| o.is_gt()),
+        CompareOp::Ge => ordering.is_some_and(|o| o.is_ge()),
+    }
+}
+
+fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64().is_some_and(|n| n != 0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(_) | Value::Object(_) => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn quad(w: f64, a: f64) -> Value {
+        json!({
+            "bounds": { "x": 0.0, "y": 0.0, "w": w, "h": 10.0 },
+            "color": { "r": 1.0, "g": 0.0, "b": 0.0, "a": a },
+            "has_clip": false,
+            "clip_bounds": null,
+        })
+    }
+
+    #[test]
+    fn compares_nested_fields() {
+        let query = Query::parse("bounds.w > 500 && color.a < 1.0").unwrap();
+        assert!(query.matches(&quad(600.0, 0.5)));
+        assert!(!query.matches(&quad(600.0, 1.0)));
+        assert!(!query.matches(&quad(400.0, 0.5)));
+    }
+
+    #[test]
+    fn precedence_and_grouping() {
+        // && binds tighter than ||.
+        let query = Query::parse("bounds.w == 1 || bounds.w == 2 && color.a == 0").unwrap();
+        assert!(query.matches(&quad(1.0, 1.0)));
+        assert!(!query.matches(&quad(2.0, 1.0)));
+
+        let query = Query::parse("(bounds.w == 1 || bounds.w == 2) && color.a == 0").unwrap();
+        assert!(!query.matches(&quad(1.0, 1.0)));
+        assert!(query.matches(&quad(2.0, 0.0)));
+    }
+
+    #[test]
+    fn missing_fields_are_null_and_never_ordered() {
+        let item = quad(10.0, 1.0);
+        assert!(Query::parse("font_size == null").unwrap().matches(&item));
+        assert!(!Query::parse("font_size < 12").unwrap().matches(&item));
+        assert!(!Query::parse("font_size >= 12").unwrap().matches(&item));
+        assert!(Query::parse("!has_clip && clip_bounds == null")
+            .unwrap()
+            .matches(&item));
+        assert!(Query::parse("bounds").unwrap().matches(&item));
+    }
+
+    #[test]
+    fn literals() {
+        let item = json!({ "name": "button", "size": -2.5 });
+        assert!(Query::parse("name == 'button'").unwrap().matches(&item));
+        assert!(Query::parse("name != \"label\"").unwrap().matches(&item));
+        assert!(Query::parse("size == -2.5").unwrap().matches(&item));
+        assert!(Query::parse("true && !false").unwrap().matches(&item));
+    }
+
+    #[test]
+    fn reports_syntax_errors() {
+        assert!(Query::parse("").is_err());
+        assert!(Query::parse("bounds.w >").is_err());
+        assert!(Query::parse("(bounds.w > 1").is_err());
+        assert!(Query::parse("bounds.w > 1 )").is_err());
+        assert!(Query::parse("name == 'open").is_err());
+        assert!(Query::parse("bounds.w # 1").is_err());
+    }
+}
@@ -0,0 +1,145 @@
+//! Registry of running debug servers.
+//!
+//! Each [`DebugServer`](crate::DebugServer) writes a small JSON entry to
+//! [`REGISTRY_DIR`] describing the app behind its socket, and removes it on
+//! shutdown. Tools read the registry to pick the intended app by name instead
+//! of connecting to whichever socket they find first.
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+/// Directory holding one entry per running debug server.
+pub const REGISTRY_DIR: &str = "/tmp/motif-debug";
+
+/// A running debug server, as recorded in the registry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegistryEntry {
+    /// Human-readable app name. Defaults to the executable's file name.
+    pub app_name: String,
+    pub pid: u32,
+    /// When the server started, in seconds since the Unix epoch.
+    pub started_at: u64,
+    pub socket_path: PathBuf,
+}
+
+impl RegistryEntry {
+    /// An entry for a server in this process listening on `socket_path`.
+    pub fn for_current_process(socket_path: &Path) -> Self {
+        let app_name = std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.file_stem().map(|s| s.to_string_lossy().into_owned()))
+            .unwrap_or_else(|| "motif".to_string());
+        let started_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        Self {
+            app_name,
+            pid: std::process::id(),
+            started_at,
+            socket_path: socket_path.to_path_buf(),
+        }
+    }
+
+    /// Where this entry is stored in `dir`, named after its socket so every
+    /// server gets its own file.
+    pub fn file_path(&self, dir: &Path) -> PathBuf {
+        let stem = self.socket_path.file_stem().map_or_else(
+            || self.pid.to_string(),
+            |s| s.to_string_lossy().into_owned(),
+        );
+        dir.join(format!("{stem}.json"))
+    }
+
+    /// Write the entry into `dir`, creating it if needed. Returns the file
+    /// written.
+    pub fn write(&self, dir: &Path) -> io::Result<PathBuf> {
+        std::fs::create_dir_all(dir)?;
+        let path = self.file_path(dir);
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        std::fs::write(&path, json)?;
+        Ok(path)
+    }
+}
+
+/// Read every live entry in `dir`, most recently started first.
+///
+/// Entries whose socket no longer exists were left behind by a crashed app
+/// and are deleted. Unreadable entries are skipped.
+pub fn list_entries(dir: &Path) -> Vec<RegistryEntry> {
+    let Ok(read_dir) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut entries = Vec::new();
+    for file in read_dir.flatten() {
+        let path = file.path();
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        let Some(entry) = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|json| serde_json::from_str::<RegistryEntry>(&json).ok())
+        else {
+            continue;
+        };
+        if entry.socket_path.exists() {
+            entries.push(entry);
+        } else {
+            let _ = std::fs::remove_file(&path);
+        }
+    }
+
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.started_at));
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("motif-registry-test-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn entries_round_trip_newest_first() {
+        let dir = temp_dir("round-trip");
+        let sockets = [dir.join("old.sock"), dir.join("new.sock")];
+        std::fs::create_dir_all(&dir).unwrap();
+        for (i, socket) in sockets.iter().enumerate() {
+            std::fs::write(socket, "").unwrap();
+            let mut entry = RegistryEntry::for_current_process(socket);
+            entry.started_at = i as u64;
+            entry.write(&dir).unwrap();
+        }
+
+        let entries = list_entries(&dir);
+        let _ = std::fs::remove_dir_all(&dir);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].socket_path, sockets[1]);
+        assert_eq!(entries[0].pid, std::process::id());
+        assert!(!entries[0].app_name.is_empty());
+    }
+
+    #[test]
+    fn stale_entries_are_removed() {
+        let dir = temp_dir("stale");
+        let entry = RegistryEntry::for_current_process(&dir.join("gone.sock"));
+        let file = entry.write(&dir).unwrap();
+
+        assert!(list_entries(&dir).is_empty());
+        assert!(!file.exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn missing_directory_lists_nothing() {
+        assert!(list_entries(&temp_dir("missing")).is_empty());
+    }
+}
@@ -1,9 +1,35 @@
-//! Window screenshot capture using macOS native APIs.
+//! Screenshot capture.
 //!
-//! Uses `CGWindowListCreateImage` to capture the actual rendered window
-//! pixels — exactly what's on screen, including Metal rendering, text, etc.
+//! Window capture uses `CGWindowListCreateImage` to grab the actual window
+//! pixels as composited on screen. Apps that hand frames to the debug server
+//! with `DebugServer::provide_capture` are captured by renderer readback
+//! instead, which needs no permission and is exact; [`save_rendered_png`]
+//! writes those frames out, and [`save_rendered_apng`] animates a sequence
+//! of them. Renderer captures show only what the app painted unless debug
+//! overlays are added with [`composite_overlays`].
 
 use std::io;
+use std::time::Duration;
+
+use image::RgbaImage;
+use motif_core::Scene;
+
+use crate::snapshot::OverlayQuad;
+
+/// Paint debug overlay quads into `scene`'s overlay layer, above everything
+/// the app drew, at `scale` device pixels per logical pixel.
+///
+/// Use on a scene rendered for a capture that should include the
+/// annotations added with `debug.draw_quad` and the guide commands.
+pub fn composite_overlays(scene: &mut Scene, overlays: &[OverlayQuad], scale: f32) {
+    if overlays.is_empty() {
+        return;
+    }
+    let layer = scene.overlay_mut();
+    for overlay in overlays {
+        layer.push_quad(overlay.to_quad(scale));
+    }
+}
 
 /// Capture a window to a PNG file using macOS screen capture.
 ///
@@ -13,11 +39,32 @@ pub fn capture_window_to_png(window_id: u32, path: &str) -> io::Result<()> {
     capture_window_to_png_impl(window_id, path)
 }
 
+/// Whether this process is allowed to capture screen contents.
+///
+/// On macOS this checks the Screen Recording permission without prompting.
+/// Always `false` on other platforms.
+pub fn screen_capture_permitted() -> bool {
+    screen_capture_permitted_impl()
+}
+
+#[cfg(target_os = "macos")]
+fn screen_capture_permitted_impl() -> bool {
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGPreflightScreenCaptureAccess() -> bool;
+    }
+    unsafe { CGPreflightScreenCaptureAccess() }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn screen_capture_permitted_impl() -> bool {
+    false
+}
+
 #[cfg(target_os = "macos")]
 fn capture_window_to_png_impl(window_id: u32, path: &str) -> io::Result<()> {
     use core_graphics::display::*;
     use core_graphics::geometry::{CGPoint, CGRect, CGSize};
-    use image::RgbaImage;
     use std::path::Path;
 
     // Capture the specific window
@@ -61,6 +108,73 @@ fn capture_window_to_png_impl(window_id: u32, path: &str) -> io::Result<()> {
     Ok(())
 }
 
+/// Save a frame read back from the renderer as a PNG.
+///
+/// Renderer readback is premultiplied, so colors are divided back out by
+/// alpha before encoding.
+pub fn save_rendered_png(image: &RgbaImage, path: &str) -> io::Result<()> {
+    let mut image = image.clone();
+    unpremultiply(&mut image);
+    image
+        .save(std::path::Path::new(path))
+        .map_err(io::Error::other)
+}
+
+/// Save frames read back from the renderer as an animated PNG that loops
+/// forever, each shown for its duration (to the millisecond). Every frame
+/// must be the size of the first.
+pub fn save_rendered_apng(frames: &[(RgbaImage, Duration)], path: &str) -> io::Result<()> {
+    let Some((first, _)) = frames.first() else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "no frames to save",
+        ));
+    };
+    let (width, height) = first.dimensions();
+    if frames
+        .iter()
+        .any(|(frame, _)| frame.dimensions() != (width, height))
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "animation frames differ in size",
+        ));
+    }
+
+    let file = io::BufWriter::new(std::fs::File::create(path)?);
+    let mut encoder = png::Encoder::new(file, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .set_animated(frames.len() as u32, 0)
+        .map_err(io::Error::other)?;
+    let mut writer = encoder.write_header().map_err(io::Error::other)?;
+    for (frame, delay) in frames {
+        let mut frame = frame.clone();
+        unpremultiply(&mut frame);
+        let millis = delay.as_millis().min(u16::MAX as u128) as u16;
+        writer
+            .set_frame_delay(millis, 1000)
+            .map_err(io::Error::other)?;
+        writer
+            .write_image_data(frame.as_raw())
+            .map_err(io::Error::other)?;
+    }
+    writer.finish().map_err(io::Error::other)
+}
+
+/// Divide colors by alpha, turning renderer readback into straight-alpha
+/// RGBA as image files and most tools expect.
+pub fn unpremultiply(image: &mut RgbaImage) {
+    for pixel in image.pixels_mut() {
+        let [r, g, b, a] = pixel.0;
+        if a > 0 && a < 255 {
+            let unpremultiply = |c: u8| ((c as u32 * 255 + a as u32 / 2) / a as u32).min(255) as u8;
+            pixel.0 = [unpremultiply(r), unpremultiply(g), unpremultiply(b), a];
+        }
+    }
+}
+
 #[cfg(not(target_os = "macos"))]
 fn capture_window_to_png_impl(_window_id: u32, _path: &str) -> io::Result<()> {
     Err(io::Error::new(
@@ -78,4 +192,102 @@ mod tests {
         let result = capture_window_to_png(999999, "/tmp/motif-test-bad-window.png");
         assert!(result.is_err());
     }
+
+    #[test]
+    fn rendered_png_is_unpremultiplied() {
+        let path = std::env::temp_dir().join(format!("motif-rendered-{}.png", std::process::id()));
+        let path = path.to_str().unwrap();
+        let image = RgbaImage::from_raw(1, 1, vec![64, 0, 128, 128]).unwrap();
+        save_rendered_png(&image, path).unwrap();
+
+        let saved = image::open(path).unwrap().to_rgba8();
+        let _ = std::fs::remove_file(path);
+        assert_eq!(saved.get_pixel(0, 0).0, [128, 0, 255, 128]);
+    }
+
+    #[test]
+    fn rendered_apng_holds_every_frame_with_its_delay() {
+        let path = std::env::temp_dir().join(format!("motif-rendered-{}.apng", std::process::id()));
+        let path = path.to_str().unwrap();
+        let red = RgbaImage::from_pixel(2, 2, image::Rgba([255, 0, 0, 255]));
+        let blue = RgbaImage::from_pixel(2, 2, image::Rgba([0, 0, 255, 255]));
+        save_rendered_apng(
+            &[
+                (red.clone(), Duration::from_millis(16)),
+                (blue, Duration::from_millis(33)),
+            ],
+            path,
+        )
+        .unwrap();
+
+        let decoder = png::Decoder::new(io::BufReader::new(std::fs::File::open(path).unwrap()));
+        let mut reader = decoder.read_info().unwrap();
+        let control = reader.info().animation_control.unwrap();
+        assert_eq!((control.num_frames, control.num_plays), (2, 0));
+        let mut pixels = vec![0; reader.output_buffer_size().unwrap()];
+        reader.next_frame(&mut pixels).unwrap();
+        assert_eq!(&pixels[..4], &[255, 0, 0, 255]);
+        reader.next_frame(&mut pixels).unwrap();
+        assert_eq!(&pixels[..4], &[0, 0, 255, 255]);
+        let delay = reader.info().frame_control.unwrap();
+        assert_eq!((delay.delay_num, delay.delay_den), (33, 1000));
+        let _ = std::fs::remove_file(path);
+
+        let small = RgbaImage::new(1, 1);
+        let mismatched = [(red, Duration::ZERO), (small, Duration::ZERO)];
+        assert!(save_rendered_apng(&mismatched, path).is_err());
+    }
+
+    #[test]
+    fn composited_overlays_draw_above_the_scene() {
+        use crate::snapshot::ColorInfo;
+        use motif_core::software::SoftwareRenderer;
+        use motif_core::{DevicePoint, DeviceRect, DeviceSize, Quad, Srgba};
+
+        let mut scene = Scene::new();
+        scene.set_background(Srgba::new(1.0, 1.0, 1.0, 1.0));
+        scene.push_quad(Quad::new(
+            DeviceRect::new(DevicePoint::new(0.0, 0.0), DeviceSize::new(12.0, 12.0)),
+            Srgba::new(0.0, 0.0, 1.0, 1.0),
+        ));
+        let clear = ColorInfo {
+            r: 0.0,
+            g: 0.0,
+            b: 0.0,
+            a: 0.0,
+        };
+        let overlay = OverlayQuad {
+            id: 1,
+            x: 1.0,
+            y: 1.0,
+            w: 4.0,
+            h: 4.0,
+            color: ColorInfo {
+                r: 1.0,
+                g: 0.0,
+                b: 0.0,
+                a: 1.0,
+            },
+            border_color: clear,
+            border_width: 0.0,
+            corner_radius: 0.0,
+        };
+
+        let mut renderer = SoftwareRenderer::new();
+        let plain = renderer.render_to_image(&scene, (20.0, 20.0), 1.0);
+        composite_overlays(&mut scene, &[overlay], 2.0);
+        let annotated = renderer.render_to_image(&scene, (20.0, 20.0), 1.0);
+
+        assert_eq!(plain.get_pixel(5, 5).0, [0, 0, 255, 255]);
+        // The overlay covers device pixels 2..10 at scale 2.
+        assert_eq!(annotated.get_pixel(5, 5).0, [255, 0, 0, 255]);
+        assert_eq!(annotated.get_pixel(11, 11).0, [0, 0, 255, 255]);
+        assert_eq!(annotated.get_pixel(15, 15).0, [255, 255, 255, 255]);
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    fn screen_capture_not_permitted_off_macos() {
+        assert!(!screen_capture_permitted());
+    }
 }
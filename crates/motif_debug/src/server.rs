@@ -3,23 +3,89 @@
 //! The server runs on a background thread and does NOT block the render loop.
 //! Scene state is shared via an `Arc<Mutex<Option<SceneSnapshot>>>`.
 
-use std::io::{BufRead, BufReader, Write};
-use std::os::unix::net::UnixListener;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::io::{BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, Mutex};
+use std::sync::{mpsc, Arc, Mutex};
 use std::thread;
+use std::time::{Duration, Instant};
+
+use image::RgbaImage;
+use motif_core::{AccessTree, AtlasDump, FramePhase, FrameProfile, FrameProfiler, RenderMode};
 
 use crate::input_sim::{self, WindowPosition};
-use crate::protocol::{DebugRequest, DebugResponse};
+use crate::log_filter::LogFilter;
+use crate::methods::{MethodSpec, METHODS};
+use crate::protocol::{encode_base64, DebugNotification, DebugRequest, DebugResponse, Encoding};
+use crate::query::Query;
+use crate::registry::{RegistryEntry, REGISTRY_DIR};
 use crate::screenshot;
-use crate::snapshot::{ColorInfo, InputStateSnapshot, OverlayQuad, SceneSnapshot};
+use crate::snapshot::{
+    AccessNodeInfo, BoundsInfo, ColorInfo, DensityCell, EdgesInfo, GlyphInfo, InputStateSnapshot,
+    OverlayArrow, OverlayLabel, OverlayQuad, PointInfo, Primitive, QuadPatch, SceneSnapshot,
+    TextRunInfo,
+};
+
+/// ID reported for overlay quads generated from guides rather than added
+/// with `debug.draw_quad`. Guide quads cannot be removed individually.
+pub const GUIDE_OVERLAY_ID: u64 = u64::MAX;
+
+/// Version of the debug protocol reported by `server.info`. Bump when
+/// existing methods change shape so tools can detect mismatches.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Evenly spaced guide lines, repeated every `step` logical pixels.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GuideLines {
+    pub step: f32,
+    pub color: ColorInfo,
+}
+
+/// Margin or safe-area guide rules inset from each window edge.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarginGuides {
+    pub insets: EdgesInfo,
+    pub color: ColorInfo,
+}
 
 /// Shared state for debug overlays injected via the debug CLI.
 ///
-/// Overlays persist across frames until explicitly cleared.
+/// Overlays persist across frames until explicitly cleared. Quads and arrows
+/// drawn without `persist` also go away when the connection that drew them
+/// closes, so a script that dies mid-run doesn't leave them on screen.
 #[derive(Debug, Default)]
 pub struct DebugOverlays {
     pub quads: Vec<OverlayQuad>,
+    /// Annotation arrows (`debug.draw_arrow`), sharing IDs with `quads`.
+    pub arrows: Vec<OverlayArrow>,
+    /// Horizontal baseline rules (`debug.show_baseline_grid`).
+    pub baseline_grid: Option<GuideLines>,
+    /// Vertical spacing-scale rules (`debug.show_spacing`).
+    pub spacing: Option<GuideLines>,
+    /// Margin guides (`debug.show_guides`), keyed by window ID. `None` is
+    /// used before the app has reported a window ID.
+    pub margins: HashMap<Option<u32>, MarginGuides>,
+    /// Index of the text run whose layout boxes are shown (`debug.inspect_text`).
+    pub inspected_text_run: Option<usize>,
+    /// Cell size of the primitive-density heatmap (`debug.show_density`).
+    pub density_cell: Option<f32>,
+    /// How the app should render (`debug.overdraw`).
+    pub render_mode: RenderMode,
+    /// A time scale requested with `debug.time_scale`, until the app takes it.
+    pub time_scale: Option<f64>,
+    /// Whether hairlines follow the pointer (`debug.crosshair`).
+    pub crosshair: bool,
+    /// The pointer in logical pixels, as last reported by the app.
+    pub pointer: Option<PointInfo>,
+    /// The outline flashed by `debug.highlight_access`, until it expires.
+    pub highlight: Option<(OverlayQuad, Instant)>,
+    /// Whether the live frame-rate widget is shown (`debug.fps_overlay`).
+    pub fps_overlay: bool,
+    /// Quad overrides queued with `scene.patch`, ordered by quad index.
+    pub patches: Vec<QuadPatch>,
+    /// The connection each scoped quad or arrow belongs to, by overlay ID.
+    owners: HashMap<u64, u64>,
     next_id: u64,
 }
 
@@ -53,19 +119,571 @@ impl DebugOverlays {
         id
     }
 
+    /// Add an arrow from `from` to `to`. Returns the assigned ID.
+    pub fn add_arrow(
+        &mut self,
+        from: PointInfo,
+        to: PointInfo,
+        color: ColorInfo,
+        width: f32,
+        label: Option<String>,
+    ) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.arrows.push(OverlayArrow {
+            id,
+            from,
+            to,
+            color,
+            width,
+            label,
+        });
+        id
+    }
+
     /// Remove a specific overlay by ID. Returns true if it was found and removed.
     pub fn remove(&mut self, id: u64) -> bool {
-        let len_before = self.quads.len();
+        let len_before = self.quads.len() + self.arrows.len();
         self.quads.retain(|q| q.id != id);
-        self.quads.len() < len_before
+        self.arrows.retain(|a| a.id != id);
+        self.owners.remove(&id);
+        self.quads.len() + self.arrows.len() < len_before
+    }
+
+    /// Tie overlay `id` to `connection`, so it's removed when that
+    /// connection closes.
+    pub fn scope_to(&mut self, id: u64, connection: u64) {
+        self.owners.insert(id, connection);
+    }
+
+    /// The connection overlay `id` is scoped to, or `None` if it persists.
+    pub fn owner(&self, id: u64) -> Option<u64> {
+        self.owners.get(&id).copied()
+    }
+
+    /// Remove the quads and arrows scoped to `connection`. Returns how many
+    /// were removed.
+    pub fn remove_connection(&mut self, connection: u64) -> usize {
+        let owned: Vec<u64> = self
+            .owners
+            .iter()
+            .filter(|(_, owner)| **owner == connection)
+            .map(|(id, _)| *id)
+            .collect();
+        owned.into_iter().filter(|id| self.remove(*id)).count()
     }
 
-    /// Clear all overlays. Returns the number removed.
+    /// Clear all overlays, including guides. Returns the number of quads and
+    /// arrows removed.
     pub fn clear(&mut self) -> usize {
-        let count = self.quads.len();
+        let count = self.quads.len() + self.arrows.len();
         self.quads.clear();
+        self.arrows.clear();
+        self.owners.clear();
+        self.baseline_grid = None;
+        self.spacing = None;
+        self.margins.clear();
+        self.inspected_text_run = None;
+        self.density_cell = None;
+        self.crosshair = false;
+        self.highlight = None;
+        self.fps_overlay = false;
         count
     }
+
+    /// Generate 1px guide line quads covering a viewport of `width` x `height`
+    /// logical pixels in the given window.
+    ///
+    /// Baseline rules sit at every multiple of the line height, spacing rules
+    /// at every multiple of the spacing scale, and margin rules at each
+    /// window's configured insets.
+    pub fn guide_quads(&self, window_id: Option<u32>, width: f32, height: f32) -> Vec<OverlayQuad> {
+        let mut quads = Vec::new();
+
+        if let Some(margins) = self.margins.get(&window_id) {
+            let EdgesInfo {
+                top,
+                right,
+                bottom,
+                left,
+            } = margins.insets;
+            let color = &margins.color;
+            quads.push(guide_quad(0.0, top, width, 1.0, color));
+            quads.push(guide_quad(0.0, height - bottom - 1.0, width, 1.0, color));
+            quads.push(guide_quad(left, 0.0, 1.0, height, color));
+            quads.push(guide_quad(width - right - 1.0, 0.0, 1.0, height, color));
+        }
+
+        if let Some(grid) = &self.baseline_grid {
+            let mut y = grid.step;
+            while y < height {
+                quads.push(guide_quad(0.0, y, width, 1.0, &grid.color));
+                y += grid.step;
+            }
+        }
+
+        if let Some(spacing) = &self.spacing {
+            let mut x = spacing.step;
+            while x < width {
+                quads.push(guide_quad(x, 0.0, 1.0, height, &spacing.color));
+                x += spacing.step;
+            }
+        }
+
+        quads
+    }
+}
+
+/// Overlay quads visualising a text run's layout, in logical pixels.
+///
+/// Draws an outlined box per line (ascent to descent), the baseline, and a
+/// tick at each glyph's pen position plus one at the end of the last advance.
+/// Ticks are left out until the run's `glyphs` have been captured.
+fn text_inspection_quads(run: &TextRunInfo, glyphs: &[GlyphInfo], scale: f32) -> Vec<OverlayQuad> {
+    let line_box = ColorInfo {
+        r: 0.2,
+        g: 0.9,
+        b: 0.4,
+        a: 0.9,
+    };
+    let baseline = ColorInfo {
+        r: 1.0,
+        g: 0.2,
+        b: 0.2,
+        a: 0.9,
+    };
+    let tick = ColorInfo {
+        r: 1.0,
+        g: 0.85,
+        b: 0.1,
+        a: 0.9,
+    };
+    let transparent = ColorInfo {
+        r: 0.0,
+        g: 0.0,
+        b: 0.0,
+        a: 0.0,
+    };
+
+    let ascent = run.ascent / scale;
+    let descent = run.descent / scale;
+    let mut quads = Vec::new();
+
+    for line in &run.lines {
+        let x = line.x / scale;
+        let width = line.width / scale;
+        let baseline_y = line.baseline / scale;
+
+        let mut outline = guide_quad(
+            x,
+            baseline_y - ascent,
+            width,
+            ascent + descent,
+            &transparent,
+        );
+        outline.border_color = line_box.clone();
+        outline.border_width = 1.0;
+        quads.push(outline);
+
+        quads.push(guide_quad(x, baseline_y, width, 1.0, &baseline));
+
+        let glyphs = glyphs
+            .get(line.glyph_start..line.glyph_end)
+            .unwrap_or_default();
+        for glyph in glyphs {
+            let pen_x = (run.origin_x + glyph.x) / scale;
+            quads.push(guide_quad(pen_x, baseline_y - ascent, 1.0, ascent, &tick));
+        }
+        if let Some(last) = glyphs.last() {
+            let end_x = (run.origin_x + last.x + last.advance) / scale;
+            quads.push(guide_quad(end_x, baseline_y - ascent, 1.0, ascent, &tick));
+        }
+    }
+
+    quads
+}
+
+/// Overlay quads shading density grid cells from green, for the emptiest
+/// non-empty cells, to red for the busiest, in logical pixels.
+fn density_quads(cells: &[DensityCell], cell: f32) -> Vec<OverlayQuad> {
+    let max = cells
+        .iter()
+        .map(DensityCell::count)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    cells
+        .iter()
+        .map(|c| {
+            let t = c.count() as f32 / max as f32;
+            let color = ColorInfo {
+                r: (2.0 * t).min(1.0),
+                g: (2.0 - 2.0 * t).min(1.0) * 0.8,
+                b: 0.1,
+                a: 0.15 + 0.4 * t,
+            };
+            guide_quad(c.x, c.y, cell, cell, &color)
+        })
+        .collect()
+}
+
+/// A frame profile as `{ "total_ms": .., "phases": { "<phase>": ms, .. } }`.
+fn profile_json(profile: &FrameProfile) -> serde_json::Value {
+    let ms = |time: Duration| time.as_secs_f64() * 1000.0;
+    let phases: serde_json::Map<String, serde_json::Value> = FramePhase::ALL
+        .iter()
+        .map(|&phase| (phase.name().to_string(), ms(profile.phase(phase)).into()))
+        .collect();
+    serde_json::json!({
+        "total_ms": ms(profile.total),
+        "phases": phases,
+    })
+}
+
+/// The `encoding` a `framebuffer.get` asked for, defaulting to base64.
+/// `None` if it isn't one we support.
+fn framebuffer_encoding(request: &DebugRequest) -> Option<&str> {
+    match request.params.as_ref().and_then(|p| p.get("encoding")) {
+        None | Some(serde_json::Value::Null) => Some("base64"),
+        Some(serde_json::Value::String(encoding))
+            if encoding == "base64" || encoding == "binary" =>
+        {
+            Some(encoding)
+        }
+        Some(_) => None,
+    }
+}
+
+/// The `framebuffer.get` result: dimensions, format and byte count, plus
+/// the base64 `data` unless the bytes follow the response.
+fn framebuffer_json(image: &RgbaImage, encoding: &str, data: Option<String>) -> serde_json::Value {
+    let mut result = serde_json::json!({
+        "width": image.width(),
+        "height": image.height(),
+        "format": "rgba8",
+        "encoding": encoding,
+        "bytes": image.as_raw().len(),
+    });
+    if let Some(data) = data {
+        result["data"] = data.into();
+    }
+    result
+}
+
+/// Read `scale`, `width`/`height` and `include_overlays` from `screenshot`
+/// params.
+fn capture_options(params: &serde_json::Value) -> Result<CaptureOptions, String> {
+    let number = |name: &str| -> Result<Option<f32>, String> {
+        match params.get(name) {
+            None | Some(serde_json::Value::Null) => Ok(None),
+            Some(value) => value
+                .as_f64()
+                .map(|v| v as f32)
+                .filter(|v| v.is_finite() && *v > 0.0)
+                .map(Some)
+                .ok_or_else(|| format!("screenshot {name} must be a positive number")),
+        }
+    };
+
+    let scale = number("scale")?;
+    if scale.is_some_and(|scale| scale > MAX_CAPTURE_SCALE) {
+        return Err(format!(
+            "screenshot scale must be at most {MAX_CAPTURE_SCALE}"
+        ));
+    }
+    let size = match (number("width")?, number("height")?) {
+        (Some(width), Some(height)) => Some((width, height)),
+        (None, None) => None,
+        _ => return Err("screenshot width and height must be given together".to_string()),
+    };
+    let include_overlays = match params.get("include_overlays") {
+        None | Some(serde_json::Value::Null) => false,
+        Some(value) => value
+            .as_bool()
+            .ok_or("screenshot include_overlays must be a boolean")?,
+    };
+    Ok(CaptureOptions {
+        scale,
+        size,
+        include_overlays,
+    })
+}
+
+/// Whether a draw request asked to outlive its connection with
+/// `persist: true`.
+fn persists(request: &DebugRequest) -> bool {
+    request
+        .params
+        .as_ref()
+        .and_then(|p| p.get("persist"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+}
+
+/// Ask the app for a frame, if it enabled frame capture, so changes made
+/// from the debug server show up even when nothing else is redrawing.
+fn wake_app(capture: &Arc<Mutex<FrameCapture>>) {
+    let request_redraw = capture
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .request_redraw
+        .clone();
+    if let Some(request_redraw) = request_redraw {
+        request_redraw();
+    }
+}
+
+fn guide_quad(x: f32, y: f32, w: f32, h: f32, color: &ColorInfo) -> OverlayQuad {
+    OverlayQuad {
+        id: GUIDE_OVERLAY_ID,
+        x,
+        y,
+        w,
+        h,
+        color: color.clone(),
+        border_color: ColorInfo {
+            r: 0.0,
+            g: 0.0,
+            b: 0.0,
+            a: 0.0,
+        },
+        border_width: 0.0,
+        corner_radius: 0.0,
+    }
+}
+
+/// Process details reported by `server.info`.
+#[derive(Debug, Clone, Default)]
+struct ServerInfo {
+    /// Number of scene snapshots received so far.
+    frame: u64,
+    /// Renderer backend name and version, if the app reported one.
+    renderer: Option<(String, String)>,
+    /// GPU time of the last completed frame, if the app reported one.
+    gpu_time: Option<std::time::Duration>,
+    /// Recent per-phase frame timings (`scene.profile`).
+    profiles: FrameProfiler,
+    /// Frames slower than this raise `perf.budget_exceeded` (`perf.set_budget`).
+    budget: Option<Duration>,
+    /// Connections that set a budget, and so receive its alerts.
+    budget_subscribers: Vec<Subscriber>,
+    /// Connections that send nothing for this long are closed.
+    idle_timeout: Option<Duration>,
+    /// When the most recent scene snapshots arrived (`fps`).
+    frame_times: VecDeque<Instant>,
+    /// When the server started listening.
+    started: Option<Instant>,
+    /// Connections open right now, and accepted in total.
+    connections: usize,
+    total_connections: u64,
+    /// Requests served, by method name (`server.stats`).
+    requests: BTreeMap<String, u64>,
+    /// Frames that couldn't be decoded as a request.
+    malformed_requests: u64,
+    /// The app's log filter, once it enables `log.set_level`.
+    log: Option<LogControl>,
+}
+
+/// Installs a log filter given in `EnvFilter` directive syntax.
+type ApplyLogFilter = Arc<dyn Fn(&str) -> Result<(), String> + Send + Sync>;
+
+/// The app's log filter and how to install a new one (`log.set_level`).
+#[derive(Clone)]
+struct LogControl {
+    filter: LogFilter,
+    apply: ApplyLogFilter,
+}
+
+impl std::fmt::Debug for LogControl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LogControl")
+            .field("filter", &self.filter)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Frame rates over the recent frames, measured between scene snapshots.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+struct FpsStats {
+    /// Rate implied by the last frame interval.
+    current: f64,
+    average: f64,
+    /// Rate implied by the slowest frame.
+    min: f64,
+    /// Rate implied by the fastest frame.
+    max: f64,
+    /// Average frame interval.
+    frame_time_ms: f64,
+    /// Number of frame intervals measured.
+    samples: usize,
+}
+
+impl FpsStats {
+    /// Stats for the intervals between `times`, or `None` with fewer than
+    /// two frames.
+    fn measure(times: &VecDeque<Instant>) -> Option<Self> {
+        let intervals: Vec<f64> = times
+            .iter()
+            .zip(times.iter().skip(1))
+            .map(|(a, b)| b.duration_since(*a).as_secs_f64())
+            .filter(|&secs| secs > 0.0)
+            .collect();
+        let last = *intervals.last()?;
+        let total: f64 = intervals.iter().sum();
+        let slowest = intervals.iter().copied().fold(0.0, f64::max);
+        let fastest = intervals.iter().copied().fold(f64::INFINITY, f64::min);
+        Some(Self {
+            current: 1.0 / last,
+            average: intervals.len() as f64 / total,
+            min: 1.0 / slowest,
+            max: 1.0 / fastest,
+            frame_time_ms: total * 1000.0 / intervals.len() as f64,
+            samples: intervals.len(),
+        })
+    }
+}
+
+/// Top-left corner of the `debug.fps_overlay` widget in a window
+/// `width` logical pixels wide.
+fn fps_widget_origin(width: f32) -> (f32, f32) {
+    ((width - FPS_WIDGET_SIZE.0 - 8.0).max(0.0), 8.0)
+}
+
+/// The write half of a client connection, shared between the thread
+/// answering its requests and the one pushing notifications to it.
+type Subscriber = Arc<Mutex<Outgoing>>;
+
+/// A connection's stream and the encoding its client asked for.
+#[derive(Debug)]
+struct Outgoing {
+    stream: UnixStream,
+    encoding: Encoding,
+}
+
+impl Outgoing {
+    fn send<T: serde::Serialize>(&mut self, message: &T) -> std::io::Result<()> {
+        let frame = self.encoding.encode(message)?;
+        self.stream.write_all(&frame)
+    }
+}
+
+/// Hairline and readout color for `debug.crosshair`.
+const CROSSHAIR_COLOR: ColorInfo = ColorInfo {
+    r: 1.0,
+    g: 0.0,
+    b: 1.0,
+    a: 0.8,
+};
+
+/// Frames `fps` measures over.
+const FPS_SAMPLES: usize = 120;
+
+/// Size and colors of the `debug.fps_overlay` widget, in logical pixels.
+const FPS_WIDGET_SIZE: (f32, f32) = (150.0, 50.0);
+const FPS_WIDGET_BACKGROUND: ColorInfo = ColorInfo {
+    r: 0.0,
+    g: 0.0,
+    b: 0.0,
+    a: 0.7,
+};
+const FPS_WIDGET_TEXT: ColorInfo = ColorInfo {
+    r: 0.4,
+    g: 1.0,
+    b: 0.4,
+    a: 1.0,
+};
+
+/// How long `debug.highlight_access` shows its outline by default, and at
+/// most, in milliseconds.
+const DEFAULT_HIGHLIGHT_MS: u64 = 1500;
+const MAX_HIGHLIGHT_MS: u64 = 10_000;
+
+/// How long a renderer `screenshot` waits for the app to provide a frame.
+const CAPTURE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Largest `scale` a `screenshot` may ask for, to keep captures a sane size.
+const MAX_CAPTURE_SCALE: f32 = 8.0;
+
+/// Most frames one `capture.start` may record. Frames are held in memory
+/// until encoding, so longer spans should use `every` instead.
+const MAX_RECORDING_FRAMES: u64 = 120;
+
+/// Delay given to the last frame of a recording that has no earlier gap
+/// to copy.
+const DEFAULT_FRAME_DELAY: Duration = Duration::from_millis(16);
+
+/// How a renderer `screenshot` should be rendered. Unset fields follow the
+/// window.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CaptureOptions {
+    /// Device pixels per logical pixel, instead of the window's backing scale.
+    pub scale: Option<f32>,
+    /// Logical width and height to lay out and render at.
+    pub size: Option<(f32, f32)>,
+    /// Draw the debug overlays above the scene, as with
+    /// `screenshot::composite_overlays`. Captures show only what the app
+    /// painted otherwise.
+    pub include_overlays: bool,
+}
+
+impl CaptureOptions {
+    /// Whether the window's own frame satisfies these options.
+    pub fn is_window_frame(&self) -> bool {
+        self.scale.is_none() && self.size.is_none() && !self.include_overlays
+    }
+}
+
+/// Screenshot requests served by renderer readback rather than window capture.
+#[derive(Default)]
+struct FrameCapture {
+    /// Asks the app to render a frame. `None` until the app enables capture.
+    request_redraw: Option<Arc<dyn Fn() + Send + Sync>>,
+    /// Requests waiting for the next provided frame.
+    waiting: Vec<(CaptureOptions, mpsc::Sender<RgbaImage>)>,
+    /// `atlas.dump` requests waiting for the renderer's glyph atlas.
+    atlas_waiting: Vec<mpsc::Sender<Option<AtlasDump>>>,
+    /// `debug.inspect_text` requests waiting for a snapshot holding their
+    /// run's glyphs, by run index.
+    text_waiting: Vec<(usize, mpsc::Sender<()>)>,
+    /// The `capture.start` in progress, if any.
+    recording: Option<Recording>,
+    /// Identifies the next recording, so a finished one never clears a
+    /// recording started after it was stopped.
+    next_recording: u64,
+}
+
+/// A `capture.start` collecting window frames.
+struct Recording {
+    id: u64,
+    /// Keep one frame in every `every`.
+    every: u64,
+    /// Window frames provided since the recording started.
+    seen: u64,
+    /// Frames still to keep.
+    remaining: u64,
+    /// Kept frames and when they arrived.
+    frames: mpsc::Sender<(RgbaImage, Instant)>,
+}
+
+impl FrameCapture {
+    /// Pass a window frame to the recording, if any. Returns whether the
+    /// recording wants another frame.
+    fn record(&mut self, image: &RgbaImage) -> bool {
+        let Some(recording) = &mut self.recording else {
+            return false;
+        };
+        let keep = recording.seen % recording.every == 0;
+        recording.seen += 1;
+        if keep {
+            recording.remaining -= 1;
+            let sent = recording.frames.send((image.clone(), Instant::now()));
+            if sent.is_err() || recording.remaining == 0 {
+                self.recording = None;
+                return false;
+            }
+        }
+        true
+    }
 }
 
 /// A debug server that embeds in a running motif app.
@@ -75,9 +693,16 @@ pub struct DebugServer {
     socket_path: PathBuf,
     snapshot: Arc<Mutex<Option<SceneSnapshot>>>,
     input_state: Arc<Mutex<Option<InputStateSnapshot>>>,
+    access_nodes: Arc<Mutex<Vec<AccessNodeInfo>>>,
     window_id: Arc<Mutex<Option<u32>>>,
     window_position: Arc<Mutex<WindowPosition>>,
     overlays: Arc<Mutex<DebugOverlays>>,
+    info: Arc<Mutex<ServerInfo>>,
+    capture: Arc<Mutex<FrameCapture>>,
+    /// This server's entry in the registry, if it could be written.
+    registry_entry: Mutex<Option<RegistryEntry>>,
+    /// Queues notifications for the thread that writes them to subscribers.
+    notifications: mpsc::Sender<DebugNotification>,
     _shutdown: Arc<Mutex<bool>>,
 }
 
@@ -105,17 +730,26 @@ impl DebugServer {
 
         let snapshot: Arc<Mutex<Option<SceneSnapshot>>> = Arc::new(Mutex::new(None));
         let input_state: Arc<Mutex<Option<InputStateSnapshot>>> = Arc::new(Mutex::new(None));
+        let access_nodes: Arc<Mutex<Vec<AccessNodeInfo>>> = Arc::new(Mutex::new(Vec::new()));
         let window_id: Arc<Mutex<Option<u32>>> = Arc::new(Mutex::new(None));
         let window_position: Arc<Mutex<WindowPosition>> =
             Arc::new(Mutex::new(WindowPosition::default()));
         let overlays: Arc<Mutex<DebugOverlays>> = Arc::new(Mutex::new(DebugOverlays::default()));
+        let info: Arc<Mutex<ServerInfo>> = Arc::new(Mutex::new(ServerInfo {
+            started: Some(Instant::now()),
+            ..ServerInfo::default()
+        }));
+        let capture: Arc<Mutex<FrameCapture>> = Arc::new(Mutex::new(FrameCapture::default()));
         let shutdown = Arc::new(Mutex::new(false));
 
         let server_snapshot = Arc::clone(&snapshot);
         let server_input_state = Arc::clone(&input_state);
+        let server_access_nodes = Arc::clone(&access_nodes);
         let server_window_id = Arc::clone(&window_id);
         let server_window_position = Arc::clone(&window_position);
         let server_overlays = Arc::clone(&overlays);
+        let server_info = Arc::clone(&info);
+        let server_capture = Arc::clone(&capture);
         let server_shutdown = Arc::clone(&shutdown);
 
         thread::spawn(move || {
@@ -123,31 +757,81 @@ impl DebugServer {
                 listener,
                 server_snapshot,
                 server_input_state,
+                server_access_nodes,
                 server_window_id,
                 server_window_position,
                 server_overlays,
+                server_info,
+                server_capture,
                 server_shutdown,
             );
         });
 
+        // Notifications are written from their own thread so a slow client
+        // never stalls the render loop that raised them.
+        let (notifications, pending) = mpsc::channel();
+        let notify_info = Arc::clone(&info);
+        thread::spawn(move || Self::notify_loop(pending, notify_info));
+
         eprintln!("[motif-debug] listening on {}", socket_path.display());
 
+        // Discovery falls back to scanning for sockets, so a registry that
+        // can't be written isn't fatal.
+        let entry = RegistryEntry::for_current_process(&socket_path);
+        let registry_entry = entry.write(Path::new(REGISTRY_DIR)).ok().map(|_| entry);
+
         Ok(Self {
             socket_path,
             snapshot,
             input_state,
+            access_nodes,
             window_id,
             window_position,
             overlays,
+            info,
+            capture,
+            registry_entry: Mutex::new(registry_entry),
+            notifications,
             _shutdown: shutdown,
         })
     }
 
+    /// Close connections that send nothing for `timeout`, so clients that
+    /// vanish without closing their socket don't keep a thread alive. Off by
+    /// default; clients that only wait for notifications should `ping` to
+    /// stay connected. Applies from each connection's next read.
+    pub fn set_idle_timeout(&self, timeout: Option<Duration>) {
+        let mut info = self.info.lock().unwrap_or_else(|e| e.into_inner());
+        info.idle_timeout = timeout.filter(|t| !t.is_zero());
+    }
+
     /// Update the shared scene snapshot. Called from the render loop each frame.
     pub fn update_scene(&self, snapshot: SceneSnapshot) {
+        let captured = snapshot.text_glyphs.as_ref().map(|glyphs| glyphs.index);
         if let Ok(mut guard) = self.snapshot.lock() {
             *guard = Some(snapshot);
         }
+        if let (Some(index), Ok(mut guard)) = (captured, self.capture.lock()) {
+            let (ready, rest): (Vec<_>, Vec<_>) = std::mem::take(&mut guard.text_waiting)
+                .into_iter()
+                .partition(|(waiting, _)| *waiting == index);
+            guard.text_waiting = rest;
+            for (_, sender) in ready {
+                let _ = sender.send(());
+            }
+        }
+        if let Ok(mut guard) = self.info.lock() {
+            guard.frame += 1;
+            if guard.frame_times.len() > FPS_SAMPLES {
+                guard.frame_times.pop_front();
+            }
+            guard.frame_times.push_back(Instant::now());
+        }
+    }
+
+    /// How many scene snapshots the app has reported, i.e. frames drawn.
+    pub fn frame_count(&self) -> u64 {
+        self.info.lock().unwrap_or_else(|e| e.into_inner()).frame
     }
 
     /// Update the shared input state snapshot. Called from the event loop.
@@ -157,6 +841,14 @@ impl DebugServer {
         }
     }
 
+    /// Update the accessibility nodes `debug.highlight_access` looks up.
+    /// Call after building each frame's tree, alongside
+    /// `AccessibilityHost::set_tree`.
+    pub fn update_access_tree(&self, tree: &AccessTree) {
+        let nodes = AccessNodeInfo::collect(tree);
+        *self.access_nodes.lock().unwrap_or_else(|e| e.into_inner()) = nodes;
+    }
+
     /// Set the window ID for native screenshot capture.
     /// Call this once after creating the window.
     pub fn set_window_id(&self, id: u32) {
@@ -165,6 +857,186 @@ impl DebugServer {
         }
     }
 
+    /// Set the app name tools show for this server. Defaults to the
+    /// executable's file name.
+    pub fn set_app_name(&self, name: impl Into<String>) {
+        let mut guard = self
+            .registry_entry
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if let Some(entry) = guard.as_mut() {
+            entry.app_name = name.into();
+            let _ = entry.write(Path::new(REGISTRY_DIR));
+        }
+    }
+
+    /// Report the renderer backend and its version (e.g. `"metal"`, a GPU
+    /// family or driver string). Shown by `server.info` and `motif-debug doctor`.
+    pub fn set_renderer_info(&self, backend: impl Into<String>, version: impl Into<String>) {
+        if let Ok(mut guard) = self.info.lock() {
+            guard.renderer = Some((backend.into(), version.into()));
+        }
+    }
+
+    /// Report the GPU time of the last completed frame, typically
+    /// `Renderer::last_frame_gpu_time()`. Shown by `server.info`.
+    pub fn set_gpu_time(&self, gpu_time: Option<std::time::Duration>) {
+        if let Ok(mut guard) = self.info.lock() {
+            guard.gpu_time = gpu_time;
+        }
+    }
+
+    /// Report a frame's per-phase timings, from
+    /// [`FrameProfiler`](motif_core::FrameProfiler). Served by `scene.profile`.
+    ///
+    /// Frames over the budget set with `perf.set_budget` are also pushed to
+    /// the clients that set it as `perf.budget_exceeded` notifications.
+    pub fn push_frame_profile(&self, profile: FrameProfile) {
+        let Ok(mut guard) = self.info.lock() else {
+            return;
+        };
+        guard.profiles.push(profile);
+        let Some(budget) = guard.budget.filter(|&budget| profile.total > budget) else {
+            return;
+        };
+        let mut params = profile_json(&profile);
+        params["frame"] = guard.frame.into();
+        params["budget_ms"] = (budget.as_secs_f64() * 1000.0).into();
+        let _ = self
+            .notifications
+            .send(DebugNotification::new("perf.budget_exceeded", params));
+    }
+
+    /// Let `log.set_level` change the app's log filter while it runs.
+    ///
+    /// `filter` is the filter the app installed, in `EnvFilter` directive
+    /// syntax; overrides are layered on top of it. `apply` installs a new
+    /// directive string, typically through a `tracing_subscriber` reload
+    /// handle:
+    ///
+    /// ```ignore
+    /// let (filter, handle) = reload::Layer::new(EnvFilter::new("info"));
+    /// tracing_subscriber::registry().with(filter).with(fmt::layer()).init();
+    /// debug_server.enable_log_control("info", move |directives| {
+    ///     let filter = EnvFilter::try_new(directives).map_err(|e| e.to_string())?;
+    ///     handle.reload(filter).map_err(|e| e.to_string())
+    /// });
+    /// ```
+    pub fn enable_log_control(
+        &self,
+        filter: impl Into<String>,
+        apply: impl Fn(&str) -> Result<(), String> + Send + Sync + 'static,
+    ) {
+        self.info.lock().unwrap_or_else(|e| e.into_inner()).log = Some(LogControl {
+            filter: LogFilter::new(filter),
+            apply: Arc::new(apply),
+        });
+    }
+
+    /// Serve `screenshot` by reading frames back from the renderer instead of
+    /// capturing the window, which needs Screen Recording permission and
+    /// picks up anything overlapping the window.
+    ///
+    /// `request_redraw` is called from the server thread when a screenshot is
+    /// pending, typically `move || window.request_redraw()`. The app answers
+    /// from its render loop with [`provide_capture`](Self::provide_capture).
+    pub fn enable_frame_capture(&self, request_redraw: impl Fn() + Send + Sync + 'static) {
+        if let Ok(mut guard) = self.capture.lock() {
+            guard.request_redraw = Some(Arc::new(request_redraw));
+        }
+    }
+
+    /// Whether a `screenshot` is waiting for a frame. Check after building
+    /// the scene each frame.
+    pub fn capture_requested(&self) -> bool {
+        self.capture
+            .lock()
+            .is_ok_and(|guard| !guard.waiting.is_empty() || guard.recording.is_some())
+    }
+
+    /// The distinct options of the waiting `screenshot` requests. Answer
+    /// each with [`provide_capture_for`](Self::provide_capture_for).
+    pub fn capture_requests(&self) -> Vec<CaptureOptions> {
+        let Ok(guard) = self.capture.lock() else {
+            return Vec::new();
+        };
+        let mut requests: Vec<CaptureOptions> = Vec::new();
+        if guard.recording.is_some() {
+            requests.push(CaptureOptions::default());
+        }
+        for (options, _) in &guard.waiting {
+            if !requests.contains(options) {
+                requests.push(*options);
+            }
+        }
+        requests
+    }
+
+    /// Hand a rendered frame to every waiting `screenshot` request, whatever
+    /// scale or size it asked for. Pixels are sRGB, premultiplied RGBA8, as
+    /// returned by `Backend::render_to_image`.
+    pub fn provide_capture(&self, image: RgbaImage) {
+        let (waiting, recording) = match self.capture.lock() {
+            Ok(mut guard) => (std::mem::take(&mut guard.waiting), guard.record(&image)),
+            Err(_) => return,
+        };
+        for (_, sender) in waiting {
+            let _ = sender.send(image.clone());
+        }
+        if recording {
+            self.request_next_recorded_frame();
+        }
+    }
+
+    /// Hand a frame rendered with `options` to the `screenshot` requests
+    /// that asked for them.
+    pub fn provide_capture_for(&self, options: CaptureOptions, image: RgbaImage) {
+        let (answered, recording) = match self.capture.lock() {
+            Ok(mut guard) => {
+                let (answered, rest): (Vec<_>, Vec<_>) = std::mem::take(&mut guard.waiting)
+                    .into_iter()
+                    .partition(|(waiting, _)| *waiting == options);
+                guard.waiting = rest;
+                let recording = options.is_window_frame() && guard.record(&image);
+                (answered, recording)
+            }
+            Err(_) => return,
+        };
+        for (_, sender) in answered {
+            let _ = sender.send(image.clone());
+        }
+        if recording {
+            self.request_next_recorded_frame();
+        }
+    }
+
+    /// Whether an `atlas.dump` is waiting for the glyph atlas. Check after
+    /// rendering each frame.
+    pub fn atlas_requested(&self) -> bool {
+        self.capture
+            .lock()
+            .is_ok_and(|guard| !guard.atlas_waiting.is_empty())
+    }
+
+    /// Hand the glyph atlas to every waiting `atlas.dump`, as returned by
+    /// `Backend::dump_glyph_atlas`. `None` tells them the renderer has no
+    /// atlas.
+    pub fn provide_atlas(&self, dump: Option<AtlasDump>) {
+        let waiting = match self.capture.lock() {
+            Ok(mut guard) => std::mem::take(&mut guard.atlas_waiting),
+            Err(_) => return,
+        };
+        for sender in waiting {
+            let _ = sender.send(dump.clone());
+        }
+    }
+
+    /// Keep frames coming while `capture.start` records, even when nothing
+    /// in the app is changing.
+    fn request_next_recorded_frame(&self) {
+        wake_app(&self.capture);
+    }
+
     /// Set the window position for input coordinate translation.
     /// Call this each frame or when the window moves.
     pub fn set_window_position(&self, x: f32, y: f32, scale: f32) {
@@ -178,24 +1050,195 @@ impl DebugServer {
         &self.socket_path
     }
 
+    /// The render mode requested with `debug.overdraw`. Apply it to the
+    /// backend before drawing each frame.
+    pub fn render_mode(&self) -> RenderMode {
+        self.overlays
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .render_mode
+    }
+
+    /// Take the time scale requested with `debug.time_scale`, if there's a
+    /// new one. Apply it to the app clock with `Clock::set_time_scale`.
+    pub fn take_time_scale(&self) -> Option<f64> {
+        self.overlays
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .time_scale
+            .take()
+    }
+
     /// Return a clone of the current debug overlays.
     ///
     /// Call this each frame to paint overlay quads on top of the scene.
+    /// Enabled guides are expanded into line quads sized to the viewport of
+    /// the most recent scene snapshot.
     pub fn overlays(&self) -> Vec<OverlayQuad> {
+        let overlays = self.overlays.lock().unwrap_or_else(|e| e.into_inner());
+        let mut quads = overlays.quads.clone();
+        quads.extend(overlays.arrows.iter().flat_map(OverlayArrow::to_quads));
+        if let Some((outline, until)) = &overlays.highlight {
+            if Instant::now() < *until {
+                quads.push(outline.clone());
+            }
+        }
+
+        let window_id = *self.window_id.lock().unwrap_or_else(|e| e.into_inner());
+        let snapshot = self.snapshot.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(snap) = snapshot.as_ref() {
+            let scale = if snap.scale_factor > 0.0 {
+                snap.scale_factor
+            } else {
+                1.0
+            };
+            quads.extend(overlays.guide_quads(
+                window_id,
+                snap.viewport_size.0 / scale,
+                snap.viewport_size.1 / scale,
+            ));
+            if let Some(index) = overlays.inspected_text_run {
+                let glyphs = snap
+                    .text_glyphs
+                    .as_ref()
+                    .filter(|glyphs| glyphs.index == index)
+                    .map_or(&[][..], |glyphs| &glyphs.glyphs);
+                if let Some(run) = snap.text_runs.get(index) {
+                    quads.extend(text_inspection_quads(run, glyphs, scale));
+                }
+            }
+            if let Some(cell) = overlays.density_cell {
+                quads.extend(density_quads(&snap.density(cell), cell));
+            }
+            if let (true, Some(pointer)) = (overlays.crosshair, &overlays.pointer) {
+                let (width, height) = (snap.viewport_size.0 / scale, snap.viewport_size.1 / scale);
+                quads.push(guide_quad(0.0, pointer.y, width, 1.0, &CROSSHAIR_COLOR));
+                quads.push(guide_quad(pointer.x, 0.0, 1.0, height, &CROSSHAIR_COLOR));
+            }
+            if overlays.fps_overlay {
+                let (x, y) = fps_widget_origin(snap.viewport_size.0 / scale);
+                let (w, h) = FPS_WIDGET_SIZE;
+                quads.push(guide_quad(x, y, w, h, &FPS_WIDGET_BACKGROUND));
+            }
+        }
+
+        quads
+    }
+
+    /// Report where the pointer is, in logical pixels, or `None` once it
+    /// leaves the window. Feeds `debug.crosshair`.
+    ///
+    /// Returns whether the crosshair moved, in which case the app should
+    /// redraw to show it.
+    pub fn set_mouse_position(&self, position: Option<(f32, f32)>) -> bool {
+        let pointer = position.map(|(x, y)| PointInfo { x, y });
+        let mut overlays = self.overlays.lock().unwrap_or_else(|e| e.into_inner());
+        let moved = overlays.pointer != pointer;
+        overlays.pointer = pointer;
+        moved && overlays.crosshair
+    }
+
+    /// Text the current overlays ask to be drawn, such as arrow labels.
+    ///
+    /// Overlay quads can't carry text, so apps that want labels paint these
+    /// themselves after the quads from [`overlays`](Self::overlays).
+    pub fn overlay_labels(&self) -> Vec<OverlayLabel> {
+        let overlays = self.overlays.lock().unwrap_or_else(|e| e.into_inner());
+        let mut labels: Vec<OverlayLabel> = overlays
+            .arrows
+            .iter()
+            .filter_map(OverlayArrow::label)
+            .collect();
+        if let (true, Some(pointer)) = (overlays.crosshair, &overlays.pointer) {
+            labels.push(OverlayLabel {
+                x: pointer.x + 6.0,
+                y: pointer.y + 6.0,
+                text: format!("{:.0}, {:.0}", pointer.x, pointer.y),
+                color: CROSSHAIR_COLOR,
+            });
+        }
+        if !overlays.fps_overlay {
+            return labels;
+        }
+        drop(overlays);
+
+        let width = {
+            let snapshot = self.snapshot.lock().unwrap_or_else(|e| e.into_inner());
+            match snapshot.as_ref() {
+                Some(snap) if snap.scale_factor > 0.0 => snap.viewport_size.0 / snap.scale_factor,
+                Some(snap) => snap.viewport_size.0,
+                None => return labels,
+            }
+        };
+        let stats = {
+            let info = self.info.lock().unwrap_or_else(|e| e.into_inner());
+            FpsStats::measure(&info.frame_times)
+        };
+        let lines = match stats {
+            Some(stats) => [
+                format!("{:.0} fps", stats.current),
+                format!("avg {:.1}  min {:.1}", stats.average, stats.min),
+                format!("{:.2} ms/frame", stats.frame_time_ms),
+            ],
+            None => ["-- fps".to_string(), String::new(), String::new()],
+        };
+        let (x, y) = fps_widget_origin(width);
+        labels.extend(
+            lines
+                .into_iter()
+                .enumerate()
+                .filter(|(_, text)| !text.is_empty())
+                .map(|(row, text)| OverlayLabel {
+                    x: x + 8.0,
+                    y: y + 14.0 * (row as f32 + 1.0),
+                    text,
+                    color: FPS_WIDGET_TEXT,
+                }),
+        );
+        labels
+    }
+
+    /// Quad overrides queued with `scene.patch`. Apply them with
+    /// [`QuadPatch::apply_all`] after building each frame's scene and before
+    /// rendering it; they stay in effect until a client clears them.
+    pub fn patches(&self) -> Vec<QuadPatch> {
         self.overlays
             .lock()
             .unwrap_or_else(|e| e.into_inner())
-            .quads
+            .patches
             .clone()
     }
 
+    /// Whether the frame-rate widget is on (`debug.fps_overlay`). Apps
+    /// should keep requesting redraws while it is, so the readout stays live.
+    pub fn fps_overlay_enabled(&self) -> bool {
+        self.overlays
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .fps_overlay
+    }
+
+    /// The text run `debug.inspect_text` is looking at. Pass it to
+    /// [`SceneSnapshot::capture_text_glyphs`] when snapshotting each frame,
+    /// so only that run's glyph positions are copied.
+    pub fn inspected_text_run(&self) -> Option<usize> {
+        self.overlays
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .inspected_text_run
+    }
+
+    #[allow(clippy::too_many_arguments)]
     fn accept_loop(
         listener: UnixListener,
         snapshot: Arc<Mutex<Option<SceneSnapshot>>>,
         input_state: Arc<Mutex<Option<InputStateSnapshot>>>,
+        access_nodes: Arc<Mutex<Vec<AccessNodeInfo>>>,
         window_id: Arc<Mutex<Option<u32>>>,
         window_position: Arc<Mutex<WindowPosition>>,
         overlays: Arc<Mutex<DebugOverlays>>,
+        info: Arc<Mutex<ServerInfo>>,
+        capture: Arc<Mutex<FrameCapture>>,
         shutdown: Arc<Mutex<bool>>,
     ) {
         loop {
@@ -212,11 +1255,14 @@ impl DebugServer {
 
                     let snap = Arc::clone(&snapshot);
                     let inp = Arc::clone(&input_state);
+                    let acc = Arc::clone(&access_nodes);
                     let wid = Arc::clone(&window_id);
                     let wpos = Arc::clone(&window_position);
                     let ovl = Arc::clone(&overlays);
+                    let inf = Arc::clone(&info);
+                    let cap = Arc::clone(&capture);
                     thread::spawn(move || {
-                        Self::handle_connection(stream, snap, inp, wid, wpos, ovl);
+                        Self::handle_connection(stream, snap, inp, acc, wid, wpos, ovl, inf, cap);
                     });
                 }
                 Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
@@ -231,63 +1277,211 @@ impl DebugServer {
         }
     }
 
+    /// Write each queued notification to every subscriber, dropping those
+    /// whose connection has closed. Ends when the server is dropped.
+    fn notify_loop(pending: mpsc::Receiver<DebugNotification>, info: Arc<Mutex<ServerInfo>>) {
+        for notification in pending {
+            let subscribers = info
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .budget_subscribers
+                .clone();
+            let closed: Vec<Subscriber> = subscribers
+                .into_iter()
+                .filter(|subscriber| {
+                    let mut outgoing = subscriber.lock().unwrap_or_else(|e| e.into_inner());
+                    outgoing.send(&notification).is_err()
+                })
+                .collect();
+            if !closed.is_empty() {
+                info.lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .budget_subscribers
+                    .retain(|s| !closed.iter().any(|c| Arc::ptr_eq(s, c)));
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
     fn handle_connection(
-        stream: std::os::unix::net::UnixStream,
+        stream: UnixStream,
         snapshot: Arc<Mutex<Option<SceneSnapshot>>>,
         input_state: Arc<Mutex<Option<InputStateSnapshot>>>,
+        access_nodes: Arc<Mutex<Vec<AccessNodeInfo>>>,
         window_id: Arc<Mutex<Option<u32>>>,
         window_position: Arc<Mutex<WindowPosition>>,
         overlays: Arc<Mutex<DebugOverlays>>,
+        info: Arc<Mutex<ServerInfo>>,
+        capture: Arc<Mutex<FrameCapture>>,
     ) {
-        let reader = BufReader::new(match stream.try_clone() {
+        let mut reader = BufReader::new(match stream.try_clone() {
             Ok(s) => s,
             Err(e) => {
                 eprintln!("[motif-debug] failed to clone stream: {e}");
                 return;
             }
         });
-        let mut writer = stream;
+        let writer: Subscriber = Arc::new(Mutex::new(Outgoing {
+            stream,
+            encoding: Encoding::Json,
+        }));
+        let respond = |response: &DebugResponse| {
+            let mut outgoing = writer.lock().unwrap_or_else(|e| e.into_inner());
+            let _ = outgoing.send(response);
+        };
+        let mut encoding = Encoding::Json;
+        // Numbered from 1 in accept order, to scope overlays to it.
+        let connection = {
+            let mut info = info.lock().unwrap_or_else(|e| e.into_inner());
+            info.connections += 1;
+            info.total_connections += 1;
+            info.total_connections
+        };
 
-        for line in reader.lines() {
-            let line = match line {
-                Ok(l) => l,
-                Err(_) => break,
+        loop {
+            let idle_timeout = info.lock().unwrap_or_else(|e| e.into_inner()).idle_timeout;
+            let _ = reader.get_ref().set_read_timeout(idle_timeout);
+            let frame = match encoding.read_frame(&mut reader) {
+                Ok(Some(frame)) => frame,
+                Ok(None) => break,
+                Err(e) => {
+                    if matches!(
+                        e.kind(),
+                        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                    ) {
+                        eprintln!("[motif-debug] closing idle connection");
+                    }
+                    break;
+                }
             };
-
-            if line.is_empty() {
+            if frame.is_empty() {
                 continue;
             }
 
-            let request: DebugRequest = match serde_json::from_str(&line) {
+            let request: DebugRequest = match encoding.decode(&frame) {
                 Ok(r) => r,
                 Err(e) => {
-                    let resp = DebugResponse::err(0, -32700, format!("Parse error: {e}"));
-                    let _ = writeln!(writer, "{}", serde_json::to_string(&resp).unwrap());
+                    info.lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .malformed_requests += 1;
+                    respond(&DebugResponse::err(0, -32700, format!("Parse error: {e}")));
                     continue;
                 }
             };
+            *info
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .requests
+                .entry(request.method.clone())
+                .or_default() += 1;
+
+            // Raw pixels follow the response line instead of going into it.
+            if request.method == "framebuffer.get"
+                && framebuffer_encoding(&request) == Some("binary")
+            {
+                match Self::read_framebuffer(&request, &capture) {
+                    Ok(image) => {
+                        let header =
+                            DebugResponse::ok(request.id, framebuffer_json(&image, "binary", None));
+                        let mut outgoing = writer.lock().unwrap_or_else(|e| e.into_inner());
+                        let _ = outgoing.send(&header);
+                        let _ = outgoing.stream.write_all(image.as_raw());
+                    }
+                    Err(response) => respond(&response),
+                }
+                continue;
+            }
 
             let response = Self::dispatch(
                 &request,
+                connection,
                 &snapshot,
                 &input_state,
+                &access_nodes,
                 &window_id,
                 &window_position,
                 &overlays,
+                &info,
+                &capture,
             );
-            let _ = writeln!(writer, "{}", serde_json::to_string(&response).unwrap());
+            if request.method == "perf.set_budget" && response.error.is_none() {
+                let mut info = info.lock().unwrap_or_else(|e| e.into_inner());
+                if !info
+                    .budget_subscribers
+                    .iter()
+                    .any(|s| Arc::ptr_eq(s, &writer))
+                {
+                    info.budget_subscribers.push(Arc::clone(&writer));
+                }
+            }
+            respond(&response);
+            // The handshake response goes out in the old encoding; everything
+            // after it uses the new one.
+            if request.method == "rpc.handshake" {
+                if let Some(new) = response
+                    .result
+                    .as_ref()
+                    .and_then(|result| result["encoding"].as_str())
+                    .and_then(Encoding::from_name)
+                {
+                    encoding = new;
+                    writer.lock().unwrap_or_else(|e| e.into_inner()).encoding = new;
+                }
+            }
+        }
+
+        // Stop alerting a connection that's gone, and close it even while a
+        // pending notification still holds the writer.
+        {
+            let mut info = info.lock().unwrap_or_else(|e| e.into_inner());
+            info.budget_subscribers.retain(|s| !Arc::ptr_eq(s, &writer));
+            info.connections -= 1;
+        }
+        let removed = overlays
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove_connection(connection);
+        if removed > 0 {
+            wake_app(&capture);
         }
+        let outgoing = writer.lock().unwrap_or_else(|e| e.into_inner());
+        let _ = outgoing.stream.shutdown(std::net::Shutdown::Both);
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn dispatch(
         request: &DebugRequest,
+        connection: u64,
         snapshot: &Arc<Mutex<Option<SceneSnapshot>>>,
         input_state: &Arc<Mutex<Option<InputStateSnapshot>>>,
+        access_nodes: &Arc<Mutex<Vec<AccessNodeInfo>>>,
         window_id: &Arc<Mutex<Option<u32>>>,
         window_position: &Arc<Mutex<WindowPosition>>,
         overlays: &Arc<Mutex<DebugOverlays>>,
+        info: &Arc<Mutex<ServerInfo>>,
+        capture: &Arc<Mutex<FrameCapture>>,
     ) -> DebugResponse {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("debug_request", method = %request.method, id = request.id)
+            .entered();
         match request.method.as_str() {
+            "server.info" => Self::handle_server_info(request, snapshot, window_id, info, capture),
+            "server.stats" => Self::handle_server_stats(request, snapshot, info),
+            "ping" => DebugResponse::ok(
+                request.id,
+                serde_json::json!({
+                    "pong": true,
+                    "data": request.params.as_ref().and_then(|p| p.get("data")),
+                }),
+            ),
+            "rpc.handshake" => Self::handle_handshake(request),
+            "rpc.describe" => DebugResponse::ok(
+                request.id,
+                serde_json::json!({
+                    "protocol_version": PROTOCOL_VERSION,
+                    "methods": METHODS.iter().map(MethodSpec::to_json).collect::<Vec<_>>(),
+                }),
+            ),
             "scene.stats" => {
                 let guard = snapshot.lock().unwrap_or_else(|e| e.into_inner());
                 match guard.as_ref() {
@@ -315,8 +1509,26 @@ impl DebugServer {
                     }
                 }
             }
-            "input.state" => {
-                let guard = input_state.lock().unwrap_or_else(|e| e.into_inner());
+            "scene.bounds" => {
+                let guard = snapshot.lock().unwrap_or_else(|e| e.into_inner());
+                match guard.as_ref() {
+                    Some(snap) => DebugResponse::ok(
+                        request.id,
+                        serde_json::to_value(snap.bounds()).unwrap_or_default(),
+                    ),
+                    None => {
+                        DebugResponse::err(request.id, -32000, "No scene snapshot available yet")
+                    }
+                }
+            }
+            "scene.query" => Self::handle_query(request, snapshot),
+            "scene.patch" => Self::handle_scene_patch(request, snapshot, overlays, capture),
+            "scene.profile" => Self::handle_profile(request, info),
+            "perf.set_budget" => Self::handle_set_budget(request, info),
+            "fps" => Self::handle_fps(request, info),
+            "log.set_level" => Self::handle_log_set_level(request, info),
+            "input.state" => {
+                let guard = input_state.lock().unwrap_or_else(|e| e.into_inner());
                 match guard.as_ref() {
                     Some(snap) => DebugResponse::ok(request.id, snap.to_json()),
                     None => DebugResponse::err(request.id, -32000, "No input state available yet"),
@@ -328,11 +1540,51 @@ impl DebugServer {
             "input.mouse_down" => Self::handle_input_mouse_down(request, window_position),
             "input.mouse_up" => Self::handle_input_mouse_up(request, window_position),
             "input.drag" => Self::handle_input_drag(request, window_position),
-            "screenshot" => Self::handle_screenshot(request, window_id),
-            "debug.draw_quad" => Self::handle_draw_quad(request, overlays),
-            "debug.clear" => Self::handle_clear(request, overlays),
-            "debug.remove" => Self::handle_remove(request, overlays),
+            "screenshot" => Self::handle_screenshot(request, window_id, capture),
+            "framebuffer.get" => Self::handle_framebuffer_get(request, capture),
+            "pixel.color" => Self::handle_pixel_color(request, snapshot, capture),
+            "atlas.dump" => Self::handle_atlas_dump(request, capture),
+            "capture.start" => Self::handle_capture_start(request, capture),
+            "capture.stop" => Self::handle_capture_stop(request, capture),
+            "debug.draw_quad" => Self::handle_draw_quad(request, connection, overlays, capture),
+            "debug.draw_arrow" => Self::handle_draw_arrow(request, connection, overlays, capture),
+            "debug.clear" => Self::handle_clear(request, overlays, capture),
+            "debug.remove" => Self::handle_remove(request, overlays, capture),
             "debug.list" => Self::handle_list(request, overlays),
+            "debug.show_guides" => Self::handle_show_guides(request, window_id, overlays, capture),
+            "debug.inspect_text" => Self::handle_inspect_text(request, snapshot, overlays, capture),
+            "debug.overdraw" => Self::handle_overdraw(request, overlays, capture),
+            "debug.time_scale" => Self::handle_time_scale(request, overlays, capture),
+            "debug.crosshair" => Self::handle_crosshair(request, overlays, capture),
+            "debug.highlight_access" => {
+                Self::handle_highlight_access(request, access_nodes, snapshot, overlays, capture)
+            }
+            "debug.fps_overlay" => Self::handle_fps_overlay(request, overlays, capture),
+            "debug.show_density" => Self::handle_show_density(request, snapshot, overlays, capture),
+            "debug.show_baseline_grid" => Self::handle_show_guide_lines(
+                request,
+                overlays,
+                capture,
+                "line_height",
+                ColorInfo {
+                    r: 0.0,
+                    g: 0.8,
+                    b: 1.0,
+                    a: 0.35,
+                },
+            ),
+            "debug.show_spacing" => Self::handle_show_guide_lines(
+                request,
+                overlays,
+                capture,
+                "scale",
+                ColorInfo {
+                    r: 1.0,
+                    g: 0.2,
+                    b: 0.6,
+                    a: 0.3,
+                },
+            ),
             _ => DebugResponse::err(
                 request.id,
                 -32601,
@@ -341,9 +1593,412 @@ impl DebugServer {
         }
     }
 
+    fn handle_server_info(
+        request: &DebugRequest,
+        snapshot: &Arc<Mutex<Option<SceneSnapshot>>>,
+        window_id: &Arc<Mutex<Option<u32>>>,
+        info: &Arc<Mutex<ServerInfo>>,
+        capture: &Arc<Mutex<FrameCapture>>,
+    ) -> DebugResponse {
+        let has_snapshot = snapshot.lock().unwrap_or_else(|e| e.into_inner()).is_some();
+        let window_id = *window_id.lock().unwrap_or_else(|e| e.into_inner());
+        let info = info.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        let renderer_capture = capture
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .request_redraw
+            .is_some();
+
+        let renderer = info.renderer.map(|(backend, version)| {
+            serde_json::json!({
+                "backend": backend,
+                "version": version,
+            })
+        });
+
+        DebugResponse::ok(
+            request.id,
+            serde_json::json!({
+                "protocol_version": PROTOCOL_VERSION,
+                "version": env!("CARGO_PKG_VERSION"),
+                "pid": std::process::id(),
+                "platform": std::env::consts::OS,
+                "frame": info.frame,
+                "has_snapshot": has_snapshot,
+                "renderer": renderer,
+                "gpu_time_ms": info.gpu_time.map(|t| t.as_secs_f64() * 1000.0),
+                "encodings": Encoding::ALL.map(Encoding::name),
+                "screenshot": {
+                    "supported": cfg!(target_os = "macos"),
+                    "permitted": screenshot::screen_capture_permitted(),
+                    "window_id": window_id,
+                    "renderer_capture": renderer_capture,
+                },
+            }),
+        )
+    }
+
+    /// Handle `server.stats`: what the server itself has been doing, to tell
+    /// a misbehaving client from a server that never heard from it.
+    fn handle_server_stats(
+        request: &DebugRequest,
+        snapshot: &Arc<Mutex<Option<SceneSnapshot>>>,
+        info: &Arc<Mutex<ServerInfo>>,
+    ) -> DebugResponse {
+        let snapshot_size = snapshot
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .map(|snap| {
+                serde_json::json!({
+                    "quads": snap.quad_count,
+                    "text_runs": snap.text_run_count,
+                    "json_bytes": serde_json::to_vec(snap).map_or(0, |json| json.len()),
+                })
+            });
+        let info = info.lock().unwrap_or_else(|e| e.into_inner());
+        let ms = |since: Instant| since.elapsed().as_secs_f64() * 1000.0;
+
+        DebugResponse::ok(
+            request.id,
+            serde_json::json!({
+                "uptime_ms": info.started.map_or(0.0, ms),
+                "connections": info.connections,
+                "total_connections": info.total_connections,
+                "requests": info.requests,
+                "total_requests": info.requests.values().sum::<u64>(),
+                "malformed_requests": info.malformed_requests,
+                "frame": info.frame,
+                "snapshot_age_ms": info.frame_times.back().copied().map(ms),
+                "snapshot_size": snapshot_size,
+            }),
+        )
+    }
+
+    /// Handle `log.set_level`: override the app's log level for `target`
+    /// (or globally), optionally only for `duration_ms`. `reset` drops every
+    /// override instead.
+    fn handle_log_set_level(
+        request: &DebugRequest,
+        info: &Arc<Mutex<ServerInfo>>,
+    ) -> DebugResponse {
+        let params = request.params.as_ref();
+        let param = |name| params.and_then(|p| p.get(name)).filter(|v| !v.is_null());
+        let reset = param("reset").and_then(|v| v.as_bool()) == Some(true);
+        let level = param("level").and_then(|v| v.as_str());
+        let target = param("target").and_then(|v| v.as_str());
+        let duration_ms = param("duration_ms").map(|v| v.as_u64().filter(|&ms| ms > 0));
+        if (level.is_none() && !reset) || duration_ms == Some(None) {
+            return DebugResponse::err(
+                request.id,
+                -32602,
+                "log.set_level requires params: { level, target?, duration_ms? } or { reset: true }",
+            );
+        }
+
+        let mut guard = info.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(log) = guard.log.as_mut() else {
+            return DebugResponse::err(
+                request.id,
+                -32000,
+                "Log control not enabled — the app must call enable_log_control()",
+            );
+        };
+        let previous = log.filter.clone();
+        let generation = match level.filter(|_| !reset) {
+            Some(level) => match log.filter.set(target, level) {
+                Ok(generation) => Some(generation),
+                Err(e) => return DebugResponse::err(request.id, -32602, e),
+            },
+            None => {
+                log.filter.reset();
+                None
+            }
+        };
+        let directives = log.filter.to_string();
+        if let Err(e) = (log.apply)(&directives) {
+            log.filter = previous;
+            return DebugResponse::err(
+                request.id,
+                -32000,
+                format!("App rejected log filter {directives:?}: {e}"),
+            );
+        }
+        drop(guard);
+
+        let duration_ms = duration_ms.flatten().filter(|_| !reset);
+        if let (Some(generation), Some(ms)) = (generation, duration_ms) {
+            let info = Arc::clone(info);
+            let target = target.map(str::to_string);
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(ms));
+                let mut guard = info.lock().unwrap_or_else(|e| e.into_inner());
+                let Some(log) = guard.log.as_mut() else {
+                    return;
+                };
+                if log.filter.expire(target.as_deref(), generation) {
+                    if let Err(e) = (log.apply)(&log.filter.to_string()) {
+                        eprintln!("[motif-debug] failed to restore log filter: {e}");
+                    }
+                }
+            });
+        }
+
+        DebugResponse::ok(
+            request.id,
+            serde_json::json!({ "filter": directives, "expires_ms": duration_ms }),
+        )
+    }
+
+    /// Pick the connection's wire encoding (`encoding`: "json" or "cbor").
+    /// The connection switches once this response has been sent.
+    fn handle_handshake(request: &DebugRequest) -> DebugResponse {
+        let name = request
+            .params
+            .as_ref()
+            .and_then(|p| p.get("encoding"))
+            .and_then(|v| v.as_str());
+        let Some(name) = name else {
+            return DebugResponse::err(request.id, -32602, "Missing string param: encoding");
+        };
+        match Encoding::from_name(name) {
+            Some(encoding) => DebugResponse::ok(
+                request.id,
+                serde_json::json!({ "encoding": encoding.name() }),
+            ),
+            None => DebugResponse::err(
+                request.id,
+                -32602,
+                format!(
+                    "Unknown encoding {name:?} (expected one of: {})",
+                    Encoding::ALL.map(Encoding::name).join(", ")
+                ),
+            ),
+        }
+    }
+
+    /// `scene.patch { quad, dx?, dy?, color?, hidden?, reset? }`: queue an
+    /// override for quad `quad` of the frames the app builds next. Moves add
+    /// to earlier ones; `reset` drops the quad's override, and
+    /// `{ clear: true }` drops them all.
+    fn handle_scene_patch(
+        request: &DebugRequest,
+        snapshot: &Arc<Mutex<Option<SceneSnapshot>>>,
+        overlays: &Arc<Mutex<DebugOverlays>>,
+        capture: &Arc<Mutex<FrameCapture>>,
+    ) -> DebugResponse {
+        let params = request.params.as_ref();
+        let param = |name| params.and_then(|p| p.get(name)).filter(|v| !v.is_null());
+        let usage = "scene.patch requires params: { quad, dx?, dy?, color?: [r,g,b,a], hidden?, reset? } or { clear: true }";
+        let flag = |name| match param(name) {
+            None => Ok(None),
+            Some(v) => v.as_bool().map(Some).ok_or(()),
+        };
+
+        let clear = param("clear").and_then(|v| v.as_bool()) == Some(true);
+        let patches = if clear {
+            let mut guard = overlays.lock().unwrap_or_else(|e| e.into_inner());
+            guard.patches.clear();
+            Vec::new()
+        } else {
+            let Some(quad) = param("quad").and_then(|v| v.as_u64()) else {
+                return DebugResponse::err(request.id, -32602, usage);
+            };
+            let quad = quad as usize;
+            let offset = |name| match param(name) {
+                None => Ok(0.0),
+                Some(v) => v.as_f64().map(|d| d as f32).ok_or(()),
+            };
+            let color = match param("color").and_then(|v| v.as_array()) {
+                None if param("color").is_none() => Ok(None),
+                Some(c) if c.len() == 4 && c.iter().all(|v| v.is_number()) => Ok(Some(ColorInfo {
+                    r: c[0].as_f64().unwrap_or(0.0) as f32,
+                    g: c[1].as_f64().unwrap_or(0.0) as f32,
+                    b: c[2].as_f64().unwrap_or(0.0) as f32,
+                    a: c[3].as_f64().unwrap_or(0.0) as f32,
+                })),
+                _ => Err(()),
+            };
+            let (Ok(dx), Ok(dy), Ok(color), Ok(hidden), Ok(reset)) = (
+                offset("dx"),
+                offset("dy"),
+                color,
+                flag("hidden"),
+                flag("reset"),
+            ) else {
+                return DebugResponse::err(request.id, -32602, usage);
+            };
+
+            let quad_count = snapshot
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .as_ref()
+                .map(|snap| snap.quad_count);
+            if let Some(count) = quad_count.filter(|&count| quad >= count) {
+                return DebugResponse::err(
+                    request.id,
+                    -32602,
+                    format!("Quad index {quad} out of range ({count} quads)"),
+                );
+            }
+
+            let mut guard = overlays.lock().unwrap_or_else(|e| e.into_inner());
+            let position = guard.patches.partition_point(|p| p.quad < quad);
+            if reset == Some(true) {
+                if guard.patches.get(position).is_some_and(|p| p.quad == quad) {
+                    guard.patches.remove(position);
+                }
+            } else {
+                if guard.patches.get(position).is_none_or(|p| p.quad != quad) {
+                    guard.patches.insert(
+                        position,
+                        QuadPatch {
+                            quad,
+                            ..QuadPatch::default()
+                        },
+                    );
+                }
+                let patch = &mut guard.patches[position];
+                patch.dx += dx;
+                patch.dy += dy;
+                if color.is_some() {
+                    patch.color = color;
+                }
+                if let Some(hidden) = hidden {
+                    patch.hidden = hidden;
+                }
+            }
+            guard.patches.clone()
+        };
+        wake_app(capture);
+
+        DebugResponse::ok(request.id, serde_json::json!({ "patches": patches }))
+    }
+
+    /// `scene.query { expr, from? }`: the quads and text runs matching
+    /// `expr`, each with its `index`. `from` limits the search to "quads" or
+    /// "text_runs".
+    fn handle_query(
+        request: &DebugRequest,
+        snapshot: &Arc<Mutex<Option<SceneSnapshot>>>,
+    ) -> DebugResponse {
+        let params = request.params.as_ref();
+        let Some(expr) = params.and_then(|p| p.get("expr")).and_then(|v| v.as_str()) else {
+            return DebugResponse::err(request.id, -32602, "Missing string param: expr");
+        };
+        let query = match Query::parse(expr) {
+            Ok(query) => query,
+            Err(e) => return DebugResponse::err(request.id, -32602, format!("Bad query: {e}")),
+        };
+        let from = params.and_then(|p| p.get("from")).and_then(|v| v.as_str());
+        let (quads, text_runs) = match from {
+            None => (true, true),
+            Some("quads") => (true, false),
+            Some("text_runs") => (false, true),
+            Some(other) => {
+                return DebugResponse::err(
+                    request.id,
+                    -32602,
+                    format!("Unknown from {other:?} (expected quads or text_runs)"),
+                )
+            }
+        };
+
+        let guard = snapshot.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(snap) = guard.as_ref() else {
+            return DebugResponse::err(request.id, -32000, "No scene snapshot available yet");
+        };
+        let matching = |items: serde_json::Value| -> Vec<serde_json::Value> {
+            let serde_json::Value::Array(items) = items else {
+                return Vec::new();
+            };
+            items
+                .into_iter()
+                .enumerate()
+                .filter(|(_, item)| query.matches(item))
+                .map(|(index, mut item)| {
+                    item["index"] = index.into();
+                    item
+                })
+                .collect()
+        };
+        let quads = if quads {
+            matching(snap.quads_json())
+        } else {
+            Vec::new()
+        };
+        let text_runs = if text_runs {
+            matching(snap.text_runs_json())
+        } else {
+            Vec::new()
+        };
+
+        DebugResponse::ok(
+            request.id,
+            serde_json::json!({
+                "count": quads.len() + text_runs.len(),
+                "quads": quads,
+                "text_runs": text_runs,
+            }),
+        )
+    }
+
+    /// The last frame's phase timings and their average over recent frames
+    /// (`frames`, default 60), in milliseconds.
+    fn handle_profile(request: &DebugRequest, info: &Arc<Mutex<ServerInfo>>) -> DebugResponse {
+        let frames = request
+            .params
+            .as_ref()
+            .and_then(|p| p.get("frames"))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(60)
+            .max(1) as usize;
+        let info = info.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(last) = info.profiles.last() else {
+            return DebugResponse::err(request.id, -32000, "No frame profile available yet");
+        };
+        let averaged = info.profiles.recent().count().min(frames);
+
+        DebugResponse::ok(
+            request.id,
+            serde_json::json!({
+                "frames": averaged,
+                "last": profile_json(last),
+                "average": profile_json(&info.profiles.average(frames)),
+            }),
+        )
+    }
+
+    /// `perf.set_budget { ms }`: alert the calling connection about frames
+    /// slower than `ms`. The budget is shared by all clients; `0` clears it.
+    fn handle_set_budget(request: &DebugRequest, info: &Arc<Mutex<ServerInfo>>) -> DebugResponse {
+        let Some(ms) = request
+            .params
+            .as_ref()
+            .and_then(|p| p.get("ms"))
+            .and_then(|v| v.as_f64())
+            .filter(|ms| ms.is_finite() && *ms >= 0.0)
+        else {
+            return DebugResponse::err(
+                request.id,
+                -32602,
+                "perf.set_budget requires params: { ms } (0 clears the budget)",
+            );
+        };
+        let budget = (ms > 0.0).then(|| Duration::from_secs_f64(ms / 1000.0));
+        info.lock().unwrap_or_else(|e| e.into_inner()).budget = budget;
+
+        DebugResponse::ok(
+            request.id,
+            serde_json::json!({ "budget_ms": budget.map(|_| ms) }),
+        )
+    }
+
     fn handle_draw_quad(
         request: &DebugRequest,
+        connection: u64,
         overlays: &Arc<Mutex<DebugOverlays>>,
+        capture: &Arc<Mutex<FrameCapture>>,
     ) -> DebugResponse {
         let params = match &request.params {
             Some(p) => p,
@@ -402,19 +2057,29 @@ impl DebugServer {
 
         let mut guard = overlays.lock().unwrap_or_else(|e| e.into_inner());
         let id = guard.add_quad(x, y, w, h, color, border_color, border_width, corner_radius);
+        if !persists(request) {
+            guard.scope_to(id, connection);
+        }
+        drop(guard);
+        wake_app(capture);
 
         DebugResponse::ok(request.id, serde_json::json!({ "id": id }))
     }
 
-    fn handle_clear(request: &DebugRequest, overlays: &Arc<Mutex<DebugOverlays>>) -> DebugResponse {
-        let mut guard = overlays.lock().unwrap_or_else(|e| e.into_inner());
-        let count = guard.clear();
+    fn handle_clear(
+        request: &DebugRequest,
+        overlays: &Arc<Mutex<DebugOverlays>>,
+        capture: &Arc<Mutex<FrameCapture>>,
+    ) -> DebugResponse {
+        let count = overlays.lock().unwrap_or_else(|e| e.into_inner()).clear();
+        wake_app(capture);
         DebugResponse::ok(request.id, serde_json::json!({ "cleared": count }))
     }
 
     fn handle_remove(
         request: &DebugRequest,
         overlays: &Arc<Mutex<DebugOverlays>>,
+        capture: &Arc<Mutex<FrameCapture>>,
     ) -> DebugResponse {
         let params = match &request.params {
             Some(p) => p,
@@ -438,156 +2103,539 @@ impl DebugServer {
             }
         };
 
-        let mut guard = overlays.lock().unwrap_or_else(|e| e.into_inner());
-        let removed = guard.remove(id);
+        let removed = overlays
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(id);
+        if removed {
+            wake_app(capture);
+        }
         DebugResponse::ok(request.id, serde_json::json!({ "removed": removed }))
     }
 
+    /// Handle `debug.draw_arrow`: point from `from` to `to` (each `[x, y]`
+    /// in logical pixels), with an optional `label` by the tail.
+    fn handle_draw_arrow(
+        request: &DebugRequest,
+        connection: u64,
+        overlays: &Arc<Mutex<DebugOverlays>>,
+        capture: &Arc<Mutex<FrameCapture>>,
+    ) -> DebugResponse {
+        let params = request.params.as_ref();
+        let point = |name: &str| {
+            let arr = params?.get(name)?.as_array()?;
+            match arr.as_slice() {
+                [x, y] => Some(PointInfo {
+                    x: x.as_f64()? as f32,
+                    y: y.as_f64()? as f32,
+                }),
+                _ => None,
+            }
+        };
+        let (Some(from), Some(to)) = (point("from"), point("to")) else {
+            return DebugResponse::err(
+                request.id,
+                -32602,
+                "debug.draw_arrow requires params: { from: [x, y], to: [x, y], color?, label?, width? }",
+            );
+        };
+
+        let color = match params
+            .and_then(|p| p.get("color"))
+            .and_then(|v| v.as_array())
+        {
+            Some(arr) if arr.len() >= 4 => ColorInfo {
+                r: arr[0].as_f64().unwrap_or(0.0) as f32,
+                g: arr[1].as_f64().unwrap_or(0.0) as f32,
+                b: arr[2].as_f64().unwrap_or(0.0) as f32,
+                a: arr[3].as_f64().unwrap_or(1.0) as f32,
+            },
+            _ => ColorInfo {
+                r: 1.0,
+                g: 0.2,
+                b: 0.2,
+                a: 1.0,
+            },
+        };
+        let width = params
+            .and_then(|p| p.get("width"))
+            .and_then(|v| v.as_f64())
+            .filter(|w| *w > 0.0)
+            .unwrap_or(2.0) as f32;
+        let label = params
+            .and_then(|p| p.get("label"))
+            .and_then(|v| v.as_str())
+            .map(str::to_string);
+
+        let mut guard = overlays.lock().unwrap_or_else(|e| e.into_inner());
+        let id = guard.add_arrow(from, to, color, width, label);
+        if !persists(request) {
+            guard.scope_to(id, connection);
+        }
+        drop(guard);
+        wake_app(capture);
+        DebugResponse::ok(request.id, serde_json::json!({ "id": id }))
+    }
+
+    /// Handle `debug.list`: overlay quads, then arrows.
     fn handle_list(request: &DebugRequest, overlays: &Arc<Mutex<DebugOverlays>>) -> DebugResponse {
         let guard = overlays.lock().unwrap_or_else(|e| e.into_inner());
-        let json = serde_json::to_value(&guard.quads).unwrap_or(serde_json::Value::Array(vec![]));
-        DebugResponse::ok(request.id, json)
+        let quads = guard.quads.iter().map(|q| (q.id, serde_json::to_value(q)));
+        let arrows = guard.arrows.iter().map(|a| (a.id, serde_json::to_value(a)));
+        let json = quads
+            .chain(arrows)
+            .map(|(id, value)| {
+                let mut value = value?;
+                value["connection"] = guard.owner(id).into();
+                Ok(value)
+            })
+            .collect::<Result<Vec<_>, serde_json::Error>>()
+            .unwrap_or_default();
+        DebugResponse::ok(request.id, json.into())
     }
 
-    fn handle_screenshot(
+    /// Handle `debug.show_density`: shade a grid of `cell`-sized squares by
+    /// how many quads and text runs overlap each, and report the busiest.
+    /// A `cell` of 0 hides the heatmap.
+    fn handle_show_density(
         request: &DebugRequest,
-        window_id: &Arc<Mutex<Option<u32>>>,
+        snapshot: &Arc<Mutex<Option<SceneSnapshot>>>,
+        overlays: &Arc<Mutex<DebugOverlays>>,
+        capture: &Arc<Mutex<FrameCapture>>,
     ) -> DebugResponse {
-        let params = match &request.params {
-            Some(p) => p,
-            None => {
-                return DebugResponse::err(
+        let cell =
+            match request
+                .params
+                .as_ref()
+                .and_then(|p| p.get("cell"))
+                .and_then(|v| v.as_f64())
+            {
+                Some(cell) if cell == 0.0 || cell >= 1.0 => cell as f32,
+                _ => return DebugResponse::err(
                     request.id,
                     -32602,
-                    "screenshot requires params: { \"path\": \"/path/to/output.png\" }",
-                )
-            }
-        };
+                    "debug.show_density requires params: { cell: <px, at least 1, or 0 to hide> }",
+                ),
+            };
 
-        let path = match params.get("path").and_then(|v| v.as_str()) {
-            Some(p) => p,
-            None => {
+        let enabled = cell > 0.0;
+        overlays
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .density_cell = enabled.then_some(cell);
+        wake_app(capture);
+        if !enabled {
+            return DebugResponse::ok(request.id, serde_json::json!({ "enabled": false }));
+        }
+
+        let mut busiest = snapshot
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .map(|snap| snap.density(cell))
+            .unwrap_or_default();
+        busiest.sort_by_key(|c| std::cmp::Reverse(c.count()));
+        busiest.truncate(5);
+
+        DebugResponse::ok(
+            request.id,
+            serde_json::json!({
+                "enabled": true,
+                "cell": cell,
+                "busiest": busiest,
+            }),
+        )
+    }
+
+    /// Handle `debug.overdraw`: switch the renderer to the overdraw heat map
+    /// and back. Pass `enabled` to set it; omit it to toggle.
+    fn handle_overdraw(
+        request: &DebugRequest,
+        overlays: &Arc<Mutex<DebugOverlays>>,
+        capture: &Arc<Mutex<FrameCapture>>,
+    ) -> DebugResponse {
+        let enabled = match request.params.as_ref().and_then(|p| p.get("enabled")) {
+            None | Some(serde_json::Value::Null) => None,
+            Some(serde_json::Value::Bool(enabled)) => Some(*enabled),
+            Some(_) => {
                 return DebugResponse::err(
                     request.id,
                     -32602,
-                    "screenshot requires a \"path\" parameter",
+                    "debug.overdraw takes params: { enabled: <bool> }",
                 )
             }
         };
 
-        let wid = window_id.lock().unwrap_or_else(|e| e.into_inner());
-        let wid = match *wid {
-            Some(id) => id,
-            None => {
+        let enabled = {
+            let mut guard = overlays.lock().unwrap_or_else(|e| e.into_inner());
+            let enabled = enabled.unwrap_or(guard.render_mode != RenderMode::Overdraw);
+            guard.render_mode = if enabled {
+                RenderMode::Overdraw
+            } else {
+                RenderMode::Normal
+            };
+            enabled
+        };
+
+        // Show the change even if nothing else is redrawing.
+        wake_app(capture);
+
+        DebugResponse::ok(request.id, serde_json::json!({ "enabled": enabled }))
+    }
+
+    /// Handle `debug.highlight_access`: outline the accessibility node `id`
+    /// for `duration_ms` (default 1500), to check its bounds on screen.
+    fn handle_highlight_access(
+        request: &DebugRequest,
+        access_nodes: &Arc<Mutex<Vec<AccessNodeInfo>>>,
+        snapshot: &Arc<Mutex<Option<SceneSnapshot>>>,
+        overlays: &Arc<Mutex<DebugOverlays>>,
+        capture: &Arc<Mutex<FrameCapture>>,
+    ) -> DebugResponse {
+        let params = request.params.as_ref();
+        let Some(id) = params.and_then(|p| p.get("id")).and_then(|v| v.as_u64()) else {
+            return DebugResponse::err(
+                request.id,
+                -32602,
+                "debug.highlight_access requires params: { id, duration_ms? }",
+            );
+        };
+        let duration_ms = match params.and_then(|p| p.get("duration_ms")) {
+            None | Some(serde_json::Value::Null) => Some(DEFAULT_HIGHLIGHT_MS),
+            Some(value) => value
+                .as_u64()
+                .filter(|ms| (1..=MAX_HIGHLIGHT_MS).contains(ms)),
+        };
+        let Some(duration_ms) = duration_ms else {
+            return DebugResponse::err(
+                request.id,
+                -32602,
+                format!(
+                    "debug.highlight_access duration_ms must be between 1 and {MAX_HIGHLIGHT_MS}"
+                ),
+            );
+        };
+
+        let node = {
+            let nodes = access_nodes.lock().unwrap_or_else(|e| e.into_inner());
+            if nodes.is_empty() {
                 return DebugResponse::err(
                     request.id,
                     -32000,
-                    "No window ID set — call set_window_id() on the debug server",
-                )
+                    "No accessibility tree available — call update_access_tree() on the debug server",
+                );
+            }
+            match nodes.iter().find(|node| node.id == id) {
+                Some(node) => node.clone(),
+                None => {
+                    return DebugResponse::err(
+                        request.id,
+                        -32000,
+                        format!("No accessibility node with id {id}"),
+                    )
+                }
             }
         };
-
-        match screenshot::capture_window_to_png(wid, path) {
-            Ok(()) => DebugResponse::ok(request.id, serde_json::json!({ "path": path })),
-            Err(e) => DebugResponse::err(
+        let Some(device) = &node.bounds else {
+            return DebugResponse::err(
                 request.id,
                 -32000,
-                format!("Failed to capture screenshot: {e}"),
-            ),
-        }
-    }
+                format!("Accessibility node {id} has no bounds"),
+            );
+        };
 
-    // --- Input simulation handlers ---
+        // Node bounds are device pixels; overlays are logical.
+        let scale = snapshot
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .map(|snap| snap.scale_factor)
+            .filter(|scale| *scale > 0.0)
+            .unwrap_or(1.0);
+        let bounds = BoundsInfo {
+            x: device.x / scale,
+            y: device.y / scale,
+            w: device.w / scale,
+            h: device.h / scale,
+        };
+        let outline = OverlayQuad {
+            id: GUIDE_OVERLAY_ID,
+            x: bounds.x,
+            y: bounds.y,
+            w: bounds.w,
+            h: bounds.h,
+            color: ColorInfo {
+                r: 1.0,
+                g: 0.8,
+                b: 0.0,
+                a: 0.15,
+            },
+            border_color: ColorInfo {
+                r: 1.0,
+                g: 0.8,
+                b: 0.0,
+                a: 1.0,
+            },
+            border_width: 2.0,
+            corner_radius: 0.0,
+        };
+        let duration = Duration::from_millis(duration_ms);
+        overlays.lock().unwrap_or_else(|e| e.into_inner()).highlight =
+            Some((outline, Instant::now() + duration));
 
-    fn handle_input_activate(
-        request: &DebugRequest,
-        window_position: &Arc<Mutex<WindowPosition>>,
-    ) -> DebugResponse {
-        let pos = window_position.lock().unwrap_or_else(|e| e.into_inner());
-        let result = input_sim::activate_window(pos.x, pos.y);
-        if result.success {
-            DebugResponse::ok(
-                request.id,
-                serde_json::json!({ "activated": true, "message": result.message }),
-            )
-        } else {
-            DebugResponse::err(request.id, -32000, result.message)
-        }
+        // Draw it now, and again once it has expired so it disappears.
+        wake_app(capture);
+        let capture = Arc::clone(capture);
+        thread::spawn(move || {
+            thread::sleep(duration);
+            wake_app(&capture);
+        });
+
+        DebugResponse::ok(
+            request.id,
+            serde_json::json!({
+                "id": node.id,
+                "role": node.role,
+                "name": node.name,
+                "bounds": bounds,
+                "duration_ms": duration_ms,
+            }),
+        )
     }
 
-    fn handle_input_move(
+    /// Handle `debug.crosshair`: draw hairlines and a coordinate readout at
+    /// the pointer. Pass `on` to set it; omit it to toggle.
+    fn handle_crosshair(
         request: &DebugRequest,
-        window_position: &Arc<Mutex<WindowPosition>>,
+        overlays: &Arc<Mutex<DebugOverlays>>,
+        capture: &Arc<Mutex<FrameCapture>>,
     ) -> DebugResponse {
-        let params = match &request.params {
-            Some(p) => p,
-            None => {
+        let on = match request.params.as_ref().and_then(|p| p.get("on")) {
+            None | Some(serde_json::Value::Null) => None,
+            Some(serde_json::Value::Bool(on)) => Some(*on),
+            Some(_) => {
                 return DebugResponse::err(
                     request.id,
                     -32602,
-                    "input.move_to requires params: { x, y }",
+                    "debug.crosshair takes params: { on: <bool> }",
                 )
             }
         };
 
-        let x = params.get("x").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
-        let y = params.get("y").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+        let (on, pointer) = {
+            let mut guard = overlays.lock().unwrap_or_else(|e| e.into_inner());
+            guard.crosshair = on.unwrap_or(!guard.crosshair);
+            (guard.crosshair, guard.pointer.clone())
+        };
+        wake_app(capture);
 
-        let pos = window_position.lock().unwrap_or_else(|e| e.into_inner());
-        let (screen_x, screen_y) = pos.to_screen(x, y);
+        DebugResponse::ok(
+            request.id,
+            serde_json::json!({ "on": on, "pointer": pointer }),
+        )
+    }
 
-        let result = input_sim::move_mouse_to(screen_x, screen_y);
-        if result.success {
-            DebugResponse::ok(
+    /// Handle `fps`: frame rates measured between the scene snapshots the
+    /// app has pushed.
+    fn handle_fps(request: &DebugRequest, info: &Arc<Mutex<ServerInfo>>) -> DebugResponse {
+        let info = info.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(stats) = FpsStats::measure(&info.frame_times) else {
+            return DebugResponse::err(
                 request.id,
-                serde_json::json!({
-                    "moved_to": { "x": x, "y": y },
-                    "screen": { "x": screen_x, "y": screen_y }
-                }),
-            )
-        } else {
-            DebugResponse::err(request.id, -32000, result.message)
-        }
+                -32000,
+                "Not enough frames yet — the app must push at least two scenes",
+            );
+        };
+        let mut result = serde_json::to_value(stats).unwrap_or_default();
+        result["frame"] = info.frame.into();
+        result["since_last_frame_ms"] = info
+            .frame_times
+            .back()
+            .map_or(0.0, |last| last.elapsed().as_secs_f64() * 1000.0)
+            .into();
+        DebugResponse::ok(request.id, result)
     }
 
-    fn handle_input_click(
+    /// Handle `debug.fps_overlay`: show or hide (or, without `on`, toggle)
+    /// the frame-rate widget in the window's top-right corner.
+    fn handle_fps_overlay(
         request: &DebugRequest,
-        window_position: &Arc<Mutex<WindowPosition>>,
+        overlays: &Arc<Mutex<DebugOverlays>>,
+        capture: &Arc<Mutex<FrameCapture>>,
     ) -> DebugResponse {
-        let params = match &request.params {
-            Some(p) => p,
-            None => {
+        let on = match request.params.as_ref().and_then(|p| p.get("on")) {
+            None | Some(serde_json::Value::Null) => None,
+            Some(serde_json::Value::Bool(on)) => Some(*on),
+            Some(_) => {
                 return DebugResponse::err(
                     request.id,
                     -32602,
-                    "input.click requires params: { x, y }",
+                    "debug.fps_overlay takes params: { on: <bool> }",
                 )
             }
         };
 
-        let x = params.get("x").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
-        let y = params.get("y").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+        let on = {
+            let mut guard = overlays.lock().unwrap_or_else(|e| e.into_inner());
+            guard.fps_overlay = on.unwrap_or(!guard.fps_overlay);
+            guard.fps_overlay
+        };
+        wake_app(capture);
 
-        let pos = window_position.lock().unwrap_or_else(|e| e.into_inner());
-        let (screen_x, screen_y) = pos.to_screen(x, y);
+        DebugResponse::ok(request.id, serde_json::json!({ "on": on }))
+    }
 
-        let result = input_sim::click_at(screen_x, screen_y);
-        if result.success {
-            DebugResponse::ok(
+    fn handle_time_scale(
+        request: &DebugRequest,
+        overlays: &Arc<Mutex<DebugOverlays>>,
+        capture: &Arc<Mutex<FrameCapture>>,
+    ) -> DebugResponse {
+        let Some(scale) = request
+            .params
+            .as_ref()
+            .and_then(|p| p.get("scale"))
+            .and_then(|v| v.as_f64())
+            .filter(|scale| scale.is_finite() && *scale >= 0.0)
+        else {
+            return DebugResponse::err(
                 request.id,
-                serde_json::json!({
-                    "clicked_at": { "x": x, "y": y },
-                    "screen": { "x": screen_x, "y": screen_y }
-                }),
-            )
+                -32602,
+                "debug.time_scale requires params: { scale } (1 is normal speed, 0 pauses)",
+            );
+        };
+        overlays
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .time_scale = Some(scale);
+        // The app applies it the next time its event loop wakes.
+        wake_app(capture);
+
+        DebugResponse::ok(request.id, serde_json::json!({ "scale": scale }))
+    }
+
+    /// Handle `debug.inspect_text`: overlay a text run's line boxes, baseline
+    /// and advance ticks. Pass `index: null` to stop inspecting.
+    ///
+    /// Snapshots only carry glyph positions for the inspected run, so this
+    /// waits for the app to render a frame with them when switching runs.
+    fn handle_inspect_text(
+        request: &DebugRequest,
+        snapshot: &Arc<Mutex<Option<SceneSnapshot>>>,
+        overlays: &Arc<Mutex<DebugOverlays>>,
+        capture: &Arc<Mutex<FrameCapture>>,
+    ) -> DebugResponse {
+        let index = match request.params.as_ref().and_then(|p| p.get("index")) {
+            Some(serde_json::Value::Null) => {
+                overlays
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .inspected_text_run = None;
+                wake_app(capture);
+                return DebugResponse::ok(request.id, serde_json::json!({ "inspecting": null }));
+            }
+            Some(v) if v.is_u64() => v.as_u64().unwrap() as usize,
+            _ => {
+                return DebugResponse::err(
+                    request.id,
+                    -32602,
+                    "debug.inspect_text requires params: { index: <text run index> | null }",
+                )
+            }
+        };
+
+        let captured = {
+            let guard = snapshot.lock().unwrap_or_else(|e| e.into_inner());
+            let snap = match guard.as_ref() {
+                Some(snap) => snap,
+                None => {
+                    return DebugResponse::err(
+                        request.id,
+                        -32000,
+                        "No scene snapshot available yet",
+                    )
+                }
+            };
+            if index >= snap.text_runs.len() {
+                return DebugResponse::err(
+                    request.id,
+                    -32602,
+                    format!(
+                        "Text run index {index} out of range ({} runs)",
+                        snap.text_runs.len()
+                    ),
+                );
+            }
+            snap.text_glyphs
+                .as_ref()
+                .is_some_and(|glyphs| glyphs.index == index)
+        };
+
+        overlays
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .inspected_text_run = Some(index);
+        if captured {
+            wake_app(capture);
         } else {
-            DebugResponse::err(request.id, -32000, result.message)
+            let (sender, receiver) = mpsc::channel();
+            let request_redraw = {
+                let mut guard = capture.lock().unwrap_or_else(|e| e.into_inner());
+                let Some(request_redraw) = guard.request_redraw.clone() else {
+                    return DebugResponse::err(
+                        request.id,
+                        -32000,
+                        "Renderer capture not enabled — call enable_frame_capture() on the debug server",
+                    );
+                };
+                guard.text_waiting.push((index, sender));
+                request_redraw
+            };
+            request_redraw();
+            if receiver.recv_timeout(CAPTURE_TIMEOUT).is_err() {
+                return DebugResponse::err(
+                    request.id,
+                    -32000,
+                    "Timed out waiting for the app to render a frame",
+                );
+            }
         }
+
+        let guard = snapshot.lock().unwrap_or_else(|e| e.into_inner());
+        let found = guard.as_ref().and_then(|snap| {
+            let glyphs = snap.text_glyphs.as_ref().filter(|g| g.index == index)?;
+            Some((snap.text_runs.get(index)?, &glyphs.glyphs))
+        });
+        let Some((run, glyphs)) = found else {
+            return DebugResponse::err(
+                request.id,
+                -32602,
+                format!("Text run {index} is no longer in the scene"),
+            );
+        };
+        DebugResponse::ok(
+            request.id,
+            serde_json::json!({
+                "inspecting": index,
+                "origin": { "x": run.origin_x, "y": run.origin_y },
+                "font_size": run.font_size,
+                "ascent": run.ascent,
+                "descent": run.descent,
+                "lines": run.lines,
+                "glyphs": glyphs,
+            }),
+        )
     }
 
-    fn handle_input_mouse_down(
+    /// Handle `debug.show_guides`: set margin guides for a window.
+    ///
+    /// `insets` is either a single number applied to every edge or
+    /// `[top, right, bottom, left]`. All-zero insets hide the guides. The
+    /// guides apply to `window` if given, otherwise the current window.
+    fn handle_show_guides(
         request: &DebugRequest,
-        window_position: &Arc<Mutex<WindowPosition>>,
+        window_id: &Arc<Mutex<Option<u32>>>,
+        overlays: &Arc<Mutex<DebugOverlays>>,
+        capture: &Arc<Mutex<FrameCapture>>,
     ) -> DebugResponse {
         let params = match &request.params {
             Some(p) => p,
@@ -595,69 +2643,150 @@ impl DebugServer {
                 return DebugResponse::err(
                     request.id,
                     -32602,
-                    "input.mouse_down requires params: { x, y }",
+                    "debug.show_guides requires params: { insets: n | [top,right,bottom,left], window?, color? }",
                 )
             }
         };
 
-        let x = params.get("x").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
-        let y = params.get("y").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+        let insets = match params.get("insets") {
+            Some(v) if v.is_number() => {
+                let n = v.as_f64().unwrap_or(0.0) as f32;
+                EdgesInfo {
+                    top: n,
+                    right: n,
+                    bottom: n,
+                    left: n,
+                }
+            }
+            Some(v) if v.as_array().is_some_and(|arr| arr.len() >= 4) => {
+                let arr = v.as_array().unwrap();
+                EdgesInfo {
+                    top: arr[0].as_f64().unwrap_or(0.0) as f32,
+                    right: arr[1].as_f64().unwrap_or(0.0) as f32,
+                    bottom: arr[2].as_f64().unwrap_or(0.0) as f32,
+                    left: arr[3].as_f64().unwrap_or(0.0) as f32,
+                }
+            }
+            _ => return DebugResponse::err(
+                request.id,
+                -32602,
+                "debug.show_guides requires \"insets\" as a number or [top, right, bottom, left]",
+            ),
+        };
 
-        let pos = window_position.lock().unwrap_or_else(|e| e.into_inner());
-        let (screen_x, screen_y) = pos.to_screen(x, y);
+        let color = match params.get("color").and_then(|v| v.as_array()) {
+            Some(arr) if arr.len() >= 4 => ColorInfo {
+                r: arr[0].as_f64().unwrap_or(0.0) as f32,
+                g: arr[1].as_f64().unwrap_or(0.0) as f32,
+                b: arr[2].as_f64().unwrap_or(0.0) as f32,
+                a: arr[3].as_f64().unwrap_or(1.0) as f32,
+            },
+            _ => ColorInfo {
+                r: 0.2,
+                g: 1.0,
+                b: 0.4,
+                a: 0.6,
+            },
+        };
 
-        let result = input_sim::mouse_down_at(screen_x, screen_y);
-        if result.success {
-            DebugResponse::ok(
-                request.id,
-                serde_json::json!({
-                    "mouse_down_at": { "x": x, "y": y },
-                    "screen": { "x": screen_x, "y": screen_y }
-                }),
-            )
+        let window = match params.get("window").and_then(|v| v.as_u64()) {
+            Some(id) => Some(id as u32),
+            None => *window_id.lock().unwrap_or_else(|e| e.into_inner()),
+        };
+
+        let enabled = [insets.top, insets.right, insets.bottom, insets.left]
+            .iter()
+            .any(|&v| v != 0.0);
+        let result = serde_json::json!({
+            "enabled": enabled,
+            "window": window,
+            "insets": insets,
+        });
+
+        let mut guard = overlays.lock().unwrap_or_else(|e| e.into_inner());
+        if enabled {
+            guard.margins.insert(window, MarginGuides { insets, color });
         } else {
-            DebugResponse::err(request.id, -32000, result.message)
+            guard.margins.remove(&window);
         }
+        drop(guard);
+        wake_app(capture);
+
+        DebugResponse::ok(request.id, result)
     }
 
-    fn handle_input_mouse_up(
+    /// Shared handler for `debug.show_baseline_grid` and `debug.show_spacing`.
+    ///
+    /// `step_param` names the spacing parameter. A step of 0 hides the guide.
+    fn handle_show_guide_lines(
         request: &DebugRequest,
-        window_position: &Arc<Mutex<WindowPosition>>,
+        overlays: &Arc<Mutex<DebugOverlays>>,
+        capture: &Arc<Mutex<FrameCapture>>,
+        step_param: &str,
+        default_color: ColorInfo,
     ) -> DebugResponse {
-        let params = match &request.params {
-            Some(p) => p,
+        let step = match request
+            .params
+            .as_ref()
+            .and_then(|p| p.get(step_param))
+            .and_then(|v| v.as_f64())
+        {
+            Some(step) => step as f32,
             None => {
                 return DebugResponse::err(
                     request.id,
                     -32602,
-                    "input.mouse_up requires params: { x, y }",
+                    format!(
+                        "{} requires params: {{ {step_param}, color?: [r,g,b,a] }}",
+                        request.method
+                    ),
                 )
             }
         };
 
-        let x = params.get("x").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
-        let y = params.get("y").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+        if step != 0.0 && step < 1.0 {
+            return DebugResponse::err(
+                request.id,
+                -32602,
+                format!("{step_param} must be at least 1 (or 0 to hide)"),
+            );
+        }
 
-        let pos = window_position.lock().unwrap_or_else(|e| e.into_inner());
-        let (screen_x, screen_y) = pos.to_screen(x, y);
+        let color = match request
+            .params
+            .as_ref()
+            .and_then(|p| p.get("color"))
+            .and_then(|v| v.as_array())
+        {
+            Some(arr) if arr.len() >= 4 => ColorInfo {
+                r: arr[0].as_f64().unwrap_or(0.0) as f32,
+                g: arr[1].as_f64().unwrap_or(0.0) as f32,
+                b: arr[2].as_f64().unwrap_or(0.0) as f32,
+                a: arr[3].as_f64().unwrap_or(1.0) as f32,
+            },
+            _ => default_color,
+        };
 
-        let result = input_sim::mouse_up_at(screen_x, screen_y);
-        if result.success {
-            DebugResponse::ok(
-                request.id,
-                serde_json::json!({
-                    "mouse_up_at": { "x": x, "y": y },
-                    "screen": { "x": screen_x, "y": screen_y }
-                }),
-            )
+        let guide = (step > 0.0).then_some(GuideLines { step, color });
+        let mut guard = overlays.lock().unwrap_or_else(|e| e.into_inner());
+        if request.method == "debug.show_baseline_grid" {
+            guard.baseline_grid = guide;
         } else {
-            DebugResponse::err(request.id, -32000, result.message)
+            guard.spacing = guide;
         }
+        drop(guard);
+        wake_app(capture);
+
+        DebugResponse::ok(
+            request.id,
+            serde_json::json!({ "enabled": step > 0.0, step_param: step }),
+        )
     }
 
-    fn handle_input_drag(
+    fn handle_screenshot(
         request: &DebugRequest,
-        window_position: &Arc<Mutex<WindowPosition>>,
+        window_id: &Arc<Mutex<Option<u32>>>,
+        capture: &Arc<Mutex<FrameCapture>>,
     ) -> DebugResponse {
         let params = match &request.params {
             Some(p) => p,
@@ -665,60 +2794,731 @@ impl DebugServer {
                 return DebugResponse::err(
                     request.id,
                     -32602,
-                    "input.drag requires params: { from_x, from_y, to_x, to_y }",
+                    "screenshot requires params: { \"path\": \"/path/to/output.png\" }",
                 )
             }
         };
 
-        let from_x = params.get("from_x").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
-        let from_y = params.get("from_y").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
-        let to_x = params.get("to_x").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
-        let to_y = params.get("to_y").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+        let path = match params.get("path").and_then(|v| v.as_str()) {
+            Some(p) => p,
+            None => {
+                return DebugResponse::err(
+                    request.id,
+                    -32602,
+                    "screenshot requires a \"path\" parameter",
+                )
+            }
+        };
 
-        let pos = window_position.lock().unwrap_or_else(|e| e.into_inner());
-        let (screen_from_x, screen_from_y) = pos.to_screen(from_x, from_y);
-        let (screen_to_x, screen_to_y) = pos.to_screen(to_x, to_y);
+        // Prefer renderer readback when the app supports it.
+        let renderer_capture = capture
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .request_redraw
+            .is_some();
+        let use_renderer = match params.get("source").and_then(|v| v.as_str()) {
+            None => renderer_capture,
+            Some("renderer") => true,
+            Some("window") => false,
+            Some(other) => {
+                return DebugResponse::err(
+                    request.id,
+                    -32602,
+                    format!("unknown screenshot source {other:?} (expected renderer or window)"),
+                )
+            }
+        };
 
-        let result = input_sim::drag(screen_from_x, screen_from_y, screen_to_x, screen_to_y);
-        if result.success {
-            DebugResponse::ok(
+        let options = match capture_options(params) {
+            Ok(options) => options,
+            Err(message) => return DebugResponse::err(request.id, -32602, message),
+        };
+        if !use_renderer && !options.is_window_frame() {
+            return DebugResponse::err(
                 request.id,
-                serde_json::json!({
-                    "dragged": {
-                        "from": { "x": from_x, "y": from_y },
-                        "to": { "x": to_x, "y": to_y }
-                    }
-                }),
-            )
-        } else {
-            DebugResponse::err(request.id, -32000, result.message)
+                -32602,
+                "scale, width, height and include_overlays need the renderer screenshot source",
+            );
         }
-    }
-}
 
-impl Drop for DebugServer {
-    fn drop(&mut self) {
-        // Signal shutdown to the accept loop.
-        if let Ok(mut guard) = self._shutdown.lock() {
-            *guard = true;
+        if use_renderer {
+            return match Self::capture_rendered_frame(capture, options) {
+                Ok(image) => match screenshot::save_rendered_png(&image, path) {
+                    Ok(()) => DebugResponse::ok(
+                        request.id,
+                        serde_json::json!({
+                            "path": path,
+                            "source": "renderer",
+                            "width": image.width(),
+                            "height": image.height(),
+                        }),
+                    ),
+                    Err(e) => DebugResponse::err(
+                        request.id,
+                        -32000,
+                        format!("Failed to save screenshot: {e}"),
+                    ),
+                },
+                Err(message) => DebugResponse::err(request.id, -32000, message),
+            };
         }
-        // Clean up the socket file.
-        if self.socket_path.exists() {
-            let _ = std::fs::remove_file(&self.socket_path);
+
+        let wid = window_id.lock().unwrap_or_else(|e| e.into_inner());
+        let wid = match *wid {
+            Some(id) => id,
+            None => {
+                return DebugResponse::err(
+                    request.id,
+                    -32000,
+                    "No window ID set — call set_window_id() on the debug server",
+                )
+            }
+        };
+
+        match screenshot::capture_window_to_png(wid, path) {
+            Ok(()) => DebugResponse::ok(
+                request.id,
+                serde_json::json!({ "path": path, "source": "window" }),
+            ),
+            Err(e) => DebugResponse::err(
+                request.id,
+                -32000,
+                format!("Failed to capture screenshot: {e}"),
+            ),
         }
-        eprintln!("[motif-debug] server stopped, socket removed");
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::io::{BufRead, BufReader, Write};
-    use std::os::unix::net::UnixStream;
-    use std::sync::atomic::{AtomicU64, Ordering};
+    /// `framebuffer.get`: the current frame's pixels as base64 in the result.
+    /// Binary requests are answered by the connection, which can write the
+    /// bytes after the response line.
+    fn handle_framebuffer_get(
+        request: &DebugRequest,
+        capture: &Arc<Mutex<FrameCapture>>,
+    ) -> DebugResponse {
+        if framebuffer_encoding(request) == Some("binary") {
+            return DebugResponse::err(
+                request.id,
+                -32602,
+                "binary framebuffers are only sent over a socket connection",
+            );
+        }
+        match Self::read_framebuffer(request, capture) {
+            Ok(image) => {
+                let data = encode_base64(image.as_raw());
+                DebugResponse::ok(request.id, framebuffer_json(&image, "base64", Some(data)))
+            }
+            Err(response) => response,
+        }
+    }
 
-    /// Generate a unique socket path for each test to avoid collisions.
-    fn test_socket_path() -> PathBuf {
+    /// Validate a `framebuffer.get` request and read the app's current frame
+    /// back as straight-alpha RGBA8.
+    fn read_framebuffer(
+        request: &DebugRequest,
+        capture: &Arc<Mutex<FrameCapture>>,
+    ) -> Result<RgbaImage, DebugResponse> {
+        if framebuffer_encoding(request).is_none() {
+            return Err(DebugResponse::err(
+                request.id,
+                -32602,
+                "framebuffer.get takes params: { encoding: \"base64\" | \"binary\" }",
+            ));
+        }
+        let mut image = Self::capture_rendered_frame(capture, CaptureOptions::default())
+            .map_err(|message| DebugResponse::err(request.id, -32000, message))?;
+        screenshot::unpremultiply(&mut image);
+        Ok(image)
+    }
+
+    /// `capture.start`: record `frames` window frames, keeping one in every
+    /// `every`, and save them as an animated PNG at `path`. Answers once the
+    /// recording is complete, or ended early by `capture.stop`.
+    fn handle_capture_start(
+        request: &DebugRequest,
+        capture: &Arc<Mutex<FrameCapture>>,
+    ) -> DebugResponse {
+        let params = request.params.as_ref();
+        let Some(path) = params.and_then(|p| p.get("path")).and_then(|v| v.as_str()) else {
+            return DebugResponse::err(
+                request.id,
+                -32602,
+                "capture.start requires params: { path, frames, every? }",
+            );
+        };
+        let count = |name: &str, default: u64| match params.and_then(|p| p.get(name)) {
+            None | Some(serde_json::Value::Null) => Some(default),
+            Some(value) => value.as_u64().filter(|n| *n >= 1),
+        };
+        let Some(frames) = count("frames", 0).filter(|n| (1..=MAX_RECORDING_FRAMES).contains(n))
+        else {
+            return DebugResponse::err(
+                request.id,
+                -32602,
+                format!("capture.start frames must be between 1 and {MAX_RECORDING_FRAMES}"),
+            );
+        };
+        let Some(every) = count("every", 1) else {
+            return DebugResponse::err(
+                request.id,
+                -32602,
+                "capture.start every must be a positive integer",
+            );
+        };
+
+        let (sender, receiver) = mpsc::channel();
+        let id = {
+            let mut guard = capture.lock().unwrap_or_else(|e| e.into_inner());
+            if guard.request_redraw.is_none() {
+                return DebugResponse::err(
+                    request.id,
+                    -32000,
+                    "Renderer capture not enabled — call enable_frame_capture() on the debug server",
+                );
+            }
+            if guard.recording.is_some() {
+                return DebugResponse::err(request.id, -32000, "A capture is already recording");
+            }
+            let id = guard.next_recording;
+            guard.next_recording += 1;
+            guard.recording = Some(Recording {
+                id,
+                every,
+                seen: 0,
+                remaining: frames,
+                frames: sender,
+            });
+            id
+        };
+        wake_app(capture);
+
+        let mut recorded: Vec<(RgbaImage, Instant)> = Vec::new();
+        let mut stopped = false;
+        while (recorded.len() as u64) < frames {
+            match receiver.recv_timeout(CAPTURE_TIMEOUT * every as u32) {
+                Ok((image, at)) => {
+                    // A resized window ends the recording; APNG frames share one size.
+                    if recorded
+                        .first()
+                        .is_some_and(|(first, _)| first.dimensions() != image.dimensions())
+                    {
+                        break;
+                    }
+                    recorded.push((image, at));
+                }
+                Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    stopped = true;
+                    break;
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => break,
+            }
+        }
+        {
+            let mut guard = capture.lock().unwrap_or_else(|e| e.into_inner());
+            if guard.recording.as_ref().is_some_and(|r| r.id == id) {
+                guard.recording = None;
+            }
+        }
+
+        let Some((first, _)) = recorded.first() else {
+            return DebugResponse::err(
+                request.id,
+                -32000,
+                if stopped {
+                    "Capture stopped before any frame was recorded"
+                } else {
+                    "Timed out waiting for the app to render a frame"
+                },
+            );
+        };
+        let (width, height) = first.dimensions();
+        let mut delays: Vec<Duration> = recorded
+            .windows(2)
+            .map(|pair| pair[1].1.duration_since(pair[0].1))
+            .collect();
+        delays.push(delays.last().copied().unwrap_or(DEFAULT_FRAME_DELAY));
+        let duration: Duration = delays.iter().sum();
+        let frames: Vec<(RgbaImage, Duration)> = recorded
+            .into_iter()
+            .map(|(image, _)| image)
+            .zip(delays)
+            .collect();
+
+        match screenshot::save_rendered_apng(&frames, path) {
+            Ok(()) => DebugResponse::ok(
+                request.id,
+                serde_json::json!({
+                    "path": path,
+                    "format": "apng",
+                    "frames": frames.len(),
+                    "width": width,
+                    "height": height,
+                    "duration_ms": duration.as_secs_f64() * 1000.0,
+                    "stopped": stopped,
+                }),
+            ),
+            Err(e) => {
+                DebugResponse::err(request.id, -32000, format!("Failed to save capture: {e}"))
+            }
+        }
+    }
+
+    /// `capture.stop`: end the recording in progress. Its `capture.start`
+    /// saves the frames recorded so far.
+    fn handle_capture_stop(
+        request: &DebugRequest,
+        capture: &Arc<Mutex<FrameCapture>>,
+    ) -> DebugResponse {
+        let stopped = capture
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .recording
+            .take()
+            .is_some();
+        DebugResponse::ok(request.id, serde_json::json!({ "stopped": stopped }))
+    }
+
+    /// `pixel.color { x, y }`: the color at a window point in logical pixels,
+    /// and the topmost primitive there. Reads the pixel back from the
+    /// renderer when the app enabled frame capture, and otherwise
+    /// approximates it from the scene snapshot's quads.
+    fn handle_pixel_color(
+        request: &DebugRequest,
+        snapshot: &Arc<Mutex<Option<SceneSnapshot>>>,
+        capture: &Arc<Mutex<FrameCapture>>,
+    ) -> DebugResponse {
+        let params = request.params.as_ref();
+        let coord = |name| params.and_then(|p| p.get(name)).and_then(|v| v.as_f64());
+        let (Some(x), Some(y)) = (coord("x"), coord("y")) else {
+            return DebugResponse::err(request.id, -32602, "pixel.color requires params: { x, y }");
+        };
+        let Some(snap) = snapshot.lock().unwrap_or_else(|e| e.into_inner()).clone() else {
+            return DebugResponse::err(request.id, -32000, "No scene snapshot available yet");
+        };
+        let scale = if snap.scale_factor > 0.0 {
+            snap.scale_factor
+        } else {
+            1.0
+        };
+        let (dx, dy) = (x as f32 * scale, y as f32 * scale);
+        let (width, height) = snap.viewport_size;
+        if dx < 0.0 || dy < 0.0 || dx >= width || dy >= height {
+            return DebugResponse::err(
+                request.id,
+                -32602,
+                format!(
+                    "({x}, {y}) is outside the {}x{} window",
+                    width / scale,
+                    height / scale
+                ),
+            );
+        }
+
+        let renderer = capture
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .request_redraw
+            .is_some();
+        let (color, source) = if renderer {
+            let mut image = match Self::capture_rendered_frame(capture, CaptureOptions::default()) {
+                Ok(image) => image,
+                Err(message) => return DebugResponse::err(request.id, -32000, message),
+            };
+            screenshot::unpremultiply(&mut image);
+            let (px, py) = (dx as u32, dy as u32);
+            let Some(pixel) = image.get_pixel_checked(px, py) else {
+                return DebugResponse::err(
+                    request.id,
+                    -32000,
+                    format!(
+                        "({px}, {py}) is outside the {}x{} frame the app rendered",
+                        image.width(),
+                        image.height()
+                    ),
+                );
+            };
+            let [r, g, b, a] = pixel.0.map(|c| f32::from(c) / 255.0);
+            (ColorInfo { r, g, b, a }, "renderer")
+        } else {
+            // Sample the pixel's center, as the rasterizer does.
+            (snap.sample(dx.floor() + 0.5, dy.floor() + 0.5), "snapshot")
+        };
+
+        let primitive = snap.primitive_at(dx, dy).map(|primitive| {
+            let (list, index) = match primitive {
+                Primitive::Quad(index) => (snap.quads_json(), index),
+                Primitive::TextRun(index) => (snap.text_runs_json(), index),
+            };
+            let mut value = serde_json::to_value(primitive).unwrap_or_default();
+            value["info"] = list[index].clone();
+            value
+        });
+        let byte = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+        let hex = format!(
+            "#{:02x}{:02x}{:02x}{:02x}",
+            byte(color.r),
+            byte(color.g),
+            byte(color.b),
+            byte(color.a)
+        );
+
+        DebugResponse::ok(
+            request.id,
+            serde_json::json!({
+                "x": x,
+                "y": y,
+                "device": [dx.floor(), dy.floor()],
+                "color": color,
+                "hex": hex,
+                "source": source,
+                "primitive": primitive,
+            }),
+        )
+    }
+
+    fn handle_atlas_dump(
+        request: &DebugRequest,
+        capture: &Arc<Mutex<FrameCapture>>,
+    ) -> DebugResponse {
+        let Some(path) = request
+            .params
+            .as_ref()
+            .and_then(|p| p.get("path"))
+            .and_then(|v| v.as_str())
+        else {
+            return DebugResponse::err(
+                request.id,
+                -32602,
+                "atlas.dump requires params: { \"path\": \"/path/to/atlas.png\" }",
+            );
+        };
+
+        let (sender, receiver) = mpsc::channel();
+        let request_redraw = {
+            let mut guard = capture.lock().unwrap_or_else(|e| e.into_inner());
+            let Some(request_redraw) = guard.request_redraw.clone() else {
+                return DebugResponse::err(
+                    request.id,
+                    -32000,
+                    "Renderer capture not enabled — call enable_frame_capture() on the debug server",
+                );
+            };
+            guard.atlas_waiting.push(sender);
+            request_redraw
+        };
+        request_redraw();
+        let dump = match receiver.recv_timeout(CAPTURE_TIMEOUT) {
+            Ok(Some(dump)) => dump,
+            Ok(None) => return DebugResponse::err(
+                request.id,
+                -32000,
+                "The renderer has no glyph atlas (the software backend rasterizes text directly)",
+            ),
+            Err(_) => {
+                return DebugResponse::err(
+                    request.id,
+                    -32000,
+                    "Timed out waiting for the app to render a frame",
+                )
+            }
+        };
+
+        let image = dump.to_image();
+        if let Err(e) = image.save(Path::new(path)) {
+            return DebugResponse::err(request.id, -32000, format!("Failed to save atlas: {e}"));
+        }
+
+        let page_size = dump.page_size;
+        let pages: Vec<serde_json::Value> = dump
+            .pages
+            .iter()
+            .enumerate()
+            .map(|(index, page)| {
+                let rows: Vec<serde_json::Value> = page
+                    .stats
+                    .rows
+                    .iter()
+                    .map(|row| {
+                        let area = u64::from(row.height) * u64::from(page_size);
+                        serde_json::json!({
+                            "y": row.y,
+                            "height": row.height,
+                            "glyphs": row.glyphs,
+                            "used_width": row.used_width,
+                            "fill": row.glyph_pixels as f64 / area.max(1) as f64,
+                        })
+                    })
+                    .collect();
+                let used_height = page.stats.rows.last().map_or(0, |row| row.y + row.height);
+                serde_json::json!({
+                    "index": index,
+                    "glyphs": page.stats.glyphs,
+                    "last_used": page.stats.last_used,
+                    "used_height": used_height,
+                    "rows": rows,
+                })
+            })
+            .collect();
+
+        DebugResponse::ok(
+            request.id,
+            serde_json::json!({
+                "path": path,
+                "width": image.width(),
+                "height": image.height(),
+                "page_size": page_size,
+                "pages": pages,
+            }),
+        )
+    }
+
+    /// Ask the app for a frame and wait for it to arrive via `provide_capture`.
+    fn capture_rendered_frame(
+        capture: &Arc<Mutex<FrameCapture>>,
+        options: CaptureOptions,
+    ) -> Result<RgbaImage, String> {
+        let (sender, receiver) = mpsc::channel();
+        let request_redraw = {
+            let mut guard = capture.lock().unwrap_or_else(|e| e.into_inner());
+            let Some(request_redraw) = guard.request_redraw.clone() else {
+                return Err(
+                    "Renderer capture not enabled — call enable_frame_capture() on the debug server"
+                        .to_string(),
+                );
+            };
+            guard.waiting.push((options, sender));
+            request_redraw
+        };
+        request_redraw();
+        receiver
+            .recv_timeout(CAPTURE_TIMEOUT)
+            .map_err(|_| "Timed out waiting for the app to render a frame".to_string())
+    }
+
+    // --- Input simulation handlers ---
+
+    fn handle_input_activate(
+        request: &DebugRequest,
+        window_position: &Arc<Mutex<WindowPosition>>,
+    ) -> DebugResponse {
+        let pos = window_position.lock().unwrap_or_else(|e| e.into_inner());
+        let result = input_sim::activate_window(pos.x, pos.y);
+        if result.success {
+            DebugResponse::ok(
+                request.id,
+                serde_json::json!({ "activated": true, "message": result.message }),
+            )
+        } else {
+            DebugResponse::err(request.id, -32000, result.message)
+        }
+    }
+
+    fn handle_input_move(
+        request: &DebugRequest,
+        window_position: &Arc<Mutex<WindowPosition>>,
+    ) -> DebugResponse {
+        let params = match &request.params {
+            Some(p) => p,
+            None => {
+                return DebugResponse::err(
+                    request.id,
+                    -32602,
+                    "input.move_to requires params: { x, y }",
+                )
+            }
+        };
+
+        let x = params.get("x").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+        let y = params.get("y").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+
+        let pos = window_position.lock().unwrap_or_else(|e| e.into_inner());
+        let (screen_x, screen_y) = pos.to_screen(x, y);
+
+        let result = input_sim::move_mouse_to(screen_x, screen_y);
+        if result.success {
+            DebugResponse::ok(
+                request.id,
+                serde_json::json!({
+                    "moved_to": { "x": x, "y": y },
+                    "screen": { "x": screen_x, "y": screen_y }
+                }),
+            )
+        } else {
+            DebugResponse::err(request.id, -32000, result.message)
+        }
+    }
+
+    fn handle_input_click(
+        request: &DebugRequest,
+        window_position: &Arc<Mutex<WindowPosition>>,
+    ) -> DebugResponse {
+        let params = match &request.params {
+            Some(p) => p,
+            None => {
+                return DebugResponse::err(
+                    request.id,
+                    -32602,
+                    "input.click requires params: { x, y }",
+                )
+            }
+        };
+
+        let x = params.get("x").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+        let y = params.get("y").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+
+        let pos = window_position.lock().unwrap_or_else(|e| e.into_inner());
+        let (screen_x, screen_y) = pos.to_screen(x, y);
+
+        let result = input_sim::click_at(screen_x, screen_y);
+        if result.success {
+            DebugResponse::ok(
+                request.id,
+                serde_json::json!({
+                    "clicked_at": { "x": x, "y": y },
+                    "screen": { "x": screen_x, "y": screen_y }
+                }),
+            )
+        } else {
+            DebugResponse::err(request.id, -32000, result.message)
+        }
+    }
+
+    fn handle_input_mouse_down(
+        request: &DebugRequest,
+        window_position: &Arc<Mutex<WindowPosition>>,
+    ) -> DebugResponse {
+        let params = match &request.params {
+            Some(p) => p,
+            None => {
+                return DebugResponse::err(
+                    request.id,
+                    -32602,
+                    "input.mouse_down requires params: { x, y }",
+                )
+            }
+        };
+
+        let x = params.get("x").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+        let y = params.get("y").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+
+        let pos = window_position.lock().unwrap_or_else(|e| e.into_inner());
+        let (screen_x, screen_y) = pos.to_screen(x, y);
+
+        let result = input_sim::mouse_down_at(screen_x, screen_y);
+        if result.success {
+            DebugResponse::ok(
+                request.id,
+                serde_json::json!({
+                    "mouse_down_at": { "x": x, "y": y },
+                    "screen": { "x": screen_x, "y": screen_y }
+                }),
+            )
+        } else {
+            DebugResponse::err(request.id, -32000, result.message)
+        }
+    }
+
+    fn handle_input_mouse_up(
+        request: &DebugRequest,
+        window_position: &Arc<Mutex<WindowPosition>>,
+    ) -> DebugResponse {
+        let params = match &request.params {
+            Some(p) => p,
+            None => {
+                return DebugResponse::err(
+                    request.id,
+                    -32602,
+                    "input.mouse_up requires params: { x, y }",
+                )
+            }
+        };
+
+        let x = params.get("x").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+        let y = params.get("y").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+
+        let pos = window_position.lock().unwrap_or_else(|e| e.into_inner());
+        let (screen_x, screen_y) = pos.to_screen(x, y);
+
+        let result = input_sim::mouse_up_at(screen_x, screen_y);
+        if result.success {
+            DebugResponse::ok(
+                request.id,
+                serde_json::json!({
+                    "mouse_up_at": { "x": x, "y": y },
+                    "screen": { "x": screen_x, "y": screen_y }
+                }),
+            )
+        } else {
+            DebugResponse::err(request.id, -32000, result.message)
+        }
+    }
+
+    fn handle_input_drag(
+        request: &DebugRequest,
+        window_position: &Arc<Mutex<WindowPosition>>,
+    ) -> DebugResponse {
+        let params = match &request.params {
+            Some(p) => p,
+            None => {
+                return DebugResponse::err(
+                    request.id,
+                    -32602,
+                    "input.drag requires params: { from_x, from_y, to_x, to_y }",
+                )
+            }
+        };
+
+        let from_x = params.get("from_x").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+        let from_y = params.get("from_y").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+        let to_x = params.get("to_x").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+        let to_y = params.get("to_y").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+
+        let pos = window_position.lock().unwrap_or_else(|e| e.into_inner());
+        let (screen_from_x, screen_from_y) = pos.to_screen(from_x, from_y);
+        let (screen_to_x, screen_to_y) = pos.to_screen(to_x, to_y);
+
+        let result = input_sim::drag(screen_from_x, screen_from_y, screen_to_x, screen_to_y);
+        if result.success {
+            DebugResponse::ok(
+                request.id,
+                serde_json::json!({
+                    "dragged": {
+                        "from": { "x": from_x, "y": from_y },
+                        "to": { "x": to_x, "y": to_y }
+                    }
+                }),
+            )
+        } else {
+            DebugResponse::err(request.id, -32000, result.message)
+        }
+    }
+}
+
+impl Drop for DebugServer {
+    fn drop(&mut self) {
+        // Signal shutdown to the accept loop.
+        if let Ok(mut guard) = self._shutdown.lock() {
+            *guard = true;
+        }
+        // Clean up the socket file and registry entry.
+        if self.socket_path.exists() {
+            let _ = std::fs::remove_file(&self.socket_path);
+        }
+        if let Ok(Some(entry)) = self.registry_entry.get_mut().map(Option::take) {
+            let _ = std::fs::remove_file(entry.file_path(Path::new(REGISTRY_DIR)));
+        }
+        eprintln!("[motif-debug] server stopped, socket removed");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixStream;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    /// Generate a unique socket path for each test to avoid collisions.
+    fn test_socket_path() -> PathBuf {
         static COUNTER: AtomicU64 = AtomicU64::new(0);
         let id = COUNTER.fetch_add(1, Ordering::SeqCst);
         let pid = std::process::id();
@@ -726,21 +3526,812 @@ mod tests {
     }
 
     #[test]
-    fn server_creates_socket_and_cleans_up() {
+    fn server_creates_socket_and_cleans_up() {
+        let path = test_socket_path();
+        let server = DebugServer::with_path(path.clone()).expect("server should start");
+        assert!(path.exists(), "socket file should exist");
+
+        drop(server);
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        assert!(!path.exists(), "socket file should be removed on drop");
+    }
+
+    #[test]
+    fn server_responds_to_scene_stats_without_snapshot() {
+        let path = test_socket_path();
+        let _server = DebugServer::with_path(path.clone()).expect("server should start");
+
+        std::thread::sleep(std::time::Duration::from_millis(100));
+
+        let mut stream = UnixStream::connect(&path).expect("should connect");
+        stream
+            .set_read_timeout(Some(std::time::Duration::from_secs(2)))
+            .unwrap();
+
+        let request = r#"{"method":"scene.stats","params":null,"id":1}"#;
+        writeln!(stream, "{request}").unwrap();
+
+        let mut reader = BufReader::new(stream);
+        let mut response_line = String::new();
+        reader.read_line(&mut response_line).unwrap();
+
+        let resp: DebugResponse = serde_json::from_str(&response_line).unwrap();
+        assert_eq!(resp.id, 1);
+        assert!(
+            resp.error.is_some(),
+            "should error when no snapshot available"
+        );
+        assert_eq!(resp.error.unwrap().code, -32000);
+    }
+
+    #[test]
+    fn server_responds_to_scene_stats_with_snapshot() {
+        let path = test_socket_path();
+        let server = DebugServer::with_path(path.clone()).expect("server should start");
+
+        // Provide a snapshot.
+        use motif_core::{DevicePoint, DeviceRect, DeviceSize, Quad, Scene, Srgba};
+        let mut scene = Scene::new();
+        scene.push_quad(Quad::new(
+            DeviceRect::new(DevicePoint::new(0.0, 0.0), DeviceSize::new(10.0, 10.0)),
+            Srgba::new(1.0, 0.0, 0.0, 1.0),
+        ));
+        let snap = SceneSnapshot::from_scene(&scene, (800.0, 600.0), 2.0);
+        server.update_scene(snap);
+
+        std::thread::sleep(std::time::Duration::from_millis(100));
+
+        let mut stream = UnixStream::connect(&path).expect("should connect");
+        stream
+            .set_read_timeout(Some(std::time::Duration::from_secs(2)))
+            .unwrap();
+
+        let request = r#"{"method":"scene.stats","params":null,"id":42}"#;
+        writeln!(stream, "{request}").unwrap();
+
+        let mut reader = BufReader::new(stream);
+        let mut response_line = String::new();
+        reader.read_line(&mut response_line).unwrap();
+
+        let resp: DebugResponse = serde_json::from_str(&response_line).unwrap();
+        assert_eq!(resp.id, 42);
+        assert!(resp.error.is_none());
+        let result = resp.result.unwrap();
+        assert_eq!(result["quad_count"], 1);
+        assert_eq!(result["text_run_count"], 0);
+        assert_eq!(result["viewport_size"][0], 800.0);
+        assert_eq!(result["scale_factor"], 2.0);
+    }
+
+    #[test]
+    fn scene_query_filters_quads_and_text_runs() {
+        let path = test_socket_path();
+        let server = DebugServer::with_path(path.clone()).expect("server should start");
+
+        use motif_core::{DevicePoint, DeviceRect, DeviceSize, Quad, Scene, Srgba};
+        let mut scene = Scene::new();
+        for (w, a) in [(600.0, 0.5), (600.0, 1.0), (100.0, 0.5)] {
+            scene.push_quad(Quad::new(
+                DeviceRect::new(DevicePoint::new(0.0, 0.0), DeviceSize::new(w, 10.0)),
+                Srgba::new(1.0, 0.0, 0.0, a),
+            ));
+        }
+        server.update_scene(SceneSnapshot::from_scene(&scene, (800.0, 600.0), 1.0));
+        std::thread::sleep(std::time::Duration::from_millis(100));
+
+        let mut stream = UnixStream::connect(&path).expect("should connect");
+        stream
+            .set_read_timeout(Some(std::time::Duration::from_secs(2)))
+            .unwrap();
+
+        let resp = send_request(
+            &mut stream,
+            r#"{"method":"scene.query","params":{"expr":"bounds.w > 500 && color.a < 1.0"},"id":1}"#,
+        );
+        let result = resp.result.unwrap();
+        assert_eq!(result["count"], 1);
+        assert_eq!(result["quads"][0]["index"], 0);
+        assert_eq!(result["text_runs"], serde_json::json!([]));
+
+        let resp = send_request(
+            &mut stream,
+            r#"{"method":"scene.query","params":{"expr":"bounds.w >","from":"quads"},"id":2}"#,
+        );
+        assert_eq!(resp.error.unwrap().code, -32602);
+    }
+
+    #[test]
+    fn server_handles_unknown_method() {
+        let path = test_socket_path();
+        let _server = DebugServer::with_path(path.clone()).expect("server should start");
+
+        std::thread::sleep(std::time::Duration::from_millis(100));
+
+        let mut stream = UnixStream::connect(&path).expect("should connect");
+        stream
+            .set_read_timeout(Some(std::time::Duration::from_secs(2)))
+            .unwrap();
+
+        let request = r#"{"method":"nonexistent.method","params":null,"id":5}"#;
+        writeln!(stream, "{request}").unwrap();
+
+        let mut reader = BufReader::new(stream);
+        let mut response_line = String::new();
+        reader.read_line(&mut response_line).unwrap();
+
+        let resp: DebugResponse = serde_json::from_str(&response_line).unwrap();
+        assert_eq!(resp.id, 5);
+        assert!(resp.error.is_some());
+        assert_eq!(resp.error.unwrap().code, -32601);
+    }
+
+    #[test]
+    fn server_handles_malformed_json() {
+        let path = test_socket_path();
+        let _server = DebugServer::with_path(path.clone()).expect("server should start");
+
+        std::thread::sleep(std::time::Duration::from_millis(100));
+
+        let mut stream = UnixStream::connect(&path).expect("should connect");
+        stream
+            .set_read_timeout(Some(std::time::Duration::from_secs(2)))
+            .unwrap();
+
+        writeln!(stream, "not valid json").unwrap();
+
+        let mut reader = BufReader::new(stream);
+        let mut response_line = String::new();
+        reader.read_line(&mut response_line).unwrap();
+
+        let resp: DebugResponse = serde_json::from_str(&response_line).unwrap();
+        assert_eq!(resp.id, 0);
+        assert!(resp.error.is_some());
+        assert_eq!(resp.error.unwrap().code, -32700);
+    }
+
+    #[test]
+    fn server_responds_to_scene_quads() {
+        let path = test_socket_path();
+        let server = DebugServer::with_path(path.clone()).expect("server should start");
+
+        use motif_core::{DevicePoint, DeviceRect, DeviceSize, Quad, Scene, Srgba};
+        let mut scene = Scene::new();
+        scene.push_quad(Quad::new(
+            DeviceRect::new(DevicePoint::new(10.0, 20.0), DeviceSize::new(100.0, 50.0)),
+            Srgba::new(1.0, 0.0, 0.0, 1.0),
+        ));
+        let snap = SceneSnapshot::from_scene(&scene, (800.0, 600.0), 1.0);
+        server.update_scene(snap);
+
+        std::thread::sleep(std::time::Duration::from_millis(100));
+
+        let mut stream = UnixStream::connect(&path).expect("should connect");
+        stream
+            .set_read_timeout(Some(std::time::Duration::from_secs(2)))
+            .unwrap();
+
+        let request = r#"{"method":"scene.quads","params":null,"id":10}"#;
+        writeln!(stream, "{request}").unwrap();
+
+        let mut reader = BufReader::new(stream);
+        let mut response_line = String::new();
+        reader.read_line(&mut response_line).unwrap();
+
+        let resp: DebugResponse = serde_json::from_str(&response_line).unwrap();
+        assert_eq!(resp.id, 10);
+        assert!(resp.error.is_none());
+        let result = resp.result.unwrap();
+        let arr = result.as_array().expect("should be an array");
+        assert_eq!(arr.len(), 1);
+        assert_eq!(arr[0]["bounds"]["x"], 10.0);
+    }
+
+    #[test]
+    fn server_responds_to_scene_text_runs() {
+        let path = test_socket_path();
+        let server = DebugServer::with_path(path.clone()).expect("server should start");
+
+        // Empty scene -- no text runs.
+        use motif_core::Scene;
+        let scene = Scene::new();
+        let snap = SceneSnapshot::from_scene(&scene, (800.0, 600.0), 1.0);
+        server.update_scene(snap);
+
+        std::thread::sleep(std::time::Duration::from_millis(100));
+
+        let mut stream = UnixStream::connect(&path).expect("should connect");
+        stream
+            .set_read_timeout(Some(std::time::Duration::from_secs(2)))
+            .unwrap();
+
+        let request = r#"{"method":"scene.text_runs","params":null,"id":11}"#;
+        writeln!(stream, "{request}").unwrap();
+
+        let mut reader = BufReader::new(stream);
+        let mut response_line = String::new();
+        reader.read_line(&mut response_line).unwrap();
+
+        let resp: DebugResponse = serde_json::from_str(&response_line).unwrap();
+        assert_eq!(resp.id, 11);
+        assert!(resp.error.is_none());
+        let result = resp.result.unwrap();
+        let arr = result.as_array().expect("should be an array");
+        assert!(arr.is_empty());
+    }
+
+    #[test]
+    fn server_screenshot_without_window_id_returns_error() {
+        let path = test_socket_path();
+        let server = DebugServer::with_path(path.clone()).expect("server should start");
+
+        use motif_core::Scene;
+        let scene = Scene::new();
+        let snap = SceneSnapshot::from_scene(&scene, (100.0, 100.0), 1.0);
+        server.update_scene(snap);
+        // Note: NOT calling set_window_id()
+
+        std::thread::sleep(std::time::Duration::from_millis(100));
+
+        let mut stream = UnixStream::connect(&path).expect("should connect");
+        stream
+            .set_read_timeout(Some(std::time::Duration::from_secs(2)))
+            .unwrap();
+
+        let request = r#"{"method":"screenshot","params":{"path":"/tmp/test.png"},"id":20}"#;
+        writeln!(stream, "{request}").unwrap();
+
+        let mut reader = BufReader::new(stream);
+        let mut response_line = String::new();
+        reader.read_line(&mut response_line).unwrap();
+
+        let resp: DebugResponse = serde_json::from_str(&response_line).unwrap();
+        assert_eq!(resp.id, 20);
+        assert!(resp.error.is_some(), "should error without window_id");
+        assert_eq!(resp.error.unwrap().code, -32000);
+    }
+
+    #[test]
+    fn server_screenshot_missing_params_returns_error() {
+        let path = test_socket_path();
+        let server = DebugServer::with_path(path.clone()).expect("server should start");
+
+        use motif_core::Scene;
+        let scene = Scene::new();
+        let snap = SceneSnapshot::from_scene(&scene, (800.0, 600.0), 1.0);
+        server.update_scene(snap);
+
+        std::thread::sleep(std::time::Duration::from_millis(100));
+
+        let mut stream = UnixStream::connect(&path).expect("should connect");
+        stream
+            .set_read_timeout(Some(std::time::Duration::from_secs(2)))
+            .unwrap();
+
+        // Send without params.
+        let request = r#"{"method":"screenshot","params":null,"id":21}"#;
+        writeln!(stream, "{request}").unwrap();
+
+        let mut reader = BufReader::new(stream);
+        let mut response_line = String::new();
+        reader.read_line(&mut response_line).unwrap();
+
+        let resp: DebugResponse = serde_json::from_str(&response_line).unwrap();
+        assert_eq!(resp.id, 21);
+        assert!(resp.error.is_some());
+        assert_eq!(resp.error.unwrap().code, -32602);
+    }
+
+    #[test]
+    fn server_screenshot_with_invalid_window_id_returns_error() {
+        let path = test_socket_path();
+        let server = DebugServer::with_path(path.clone()).expect("server should start");
+
+        use motif_core::Scene;
+        let scene = Scene::new();
+        let snap = SceneSnapshot::from_scene(&scene, (320.0, 240.0), 1.0);
+        server.update_scene(snap);
+        server.set_window_id(999999); // Invalid window ID
+
+        std::thread::sleep(std::time::Duration::from_millis(100));
+
+        let mut stream = UnixStream::connect(&path).expect("should connect");
+        stream
+            .set_read_timeout(Some(std::time::Duration::from_secs(2)))
+            .unwrap();
+
+        let request =
+            r#"{"method":"screenshot","params":{"path":"/tmp/test-invalid.png"},"id":22}"#;
+        writeln!(stream, "{request}").unwrap();
+
+        let mut reader = BufReader::new(stream);
+        let mut response_line = String::new();
+        reader.read_line(&mut response_line).unwrap();
+
+        let resp: DebugResponse = serde_json::from_str(&response_line).unwrap();
+        assert_eq!(resp.id, 22);
+        assert!(resp.error.is_some(), "invalid window ID should error");
+    }
+
+    #[test]
+    fn server_screenshot_reads_back_provided_frame() {
+        let path = test_socket_path();
+        let server = DebugServer::with_path(path.clone()).expect("server should start");
+        let (redraw_tx, redraw_rx) = std::sync::mpsc::channel();
+        let redraw_tx = Mutex::new(redraw_tx);
+        server.enable_frame_capture(move || {
+            let _ = redraw_tx.lock().unwrap().send(());
+        });
+        std::thread::sleep(std::time::Duration::from_millis(100));
+
+        let png = std::env::temp_dir().join(format!("motif-readback-{}.png", std::process::id()));
+        let request = serde_json::json!({
+            "method": "screenshot",
+            "params": { "path": png },
+            "id": 23,
+        });
+
+        let resp = std::thread::scope(|scope| {
+            // Stand in for the app's render loop.
+            let server = &server;
+            scope.spawn(move || {
+                redraw_rx
+                    .recv_timeout(std::time::Duration::from_secs(2))
+                    .expect("server should request a redraw");
+                assert!(server.capture_requested());
+                server.provide_capture(RgbaImage::from_pixel(3, 2, image::Rgba([255, 0, 0, 255])));
+            });
+            let mut stream = UnixStream::connect(&path).expect("should connect");
+            send_request(&mut stream, &request.to_string())
+        });
+
+        assert!(resp.error.is_none(), "{:?}", resp.error);
+        let result = resp.result.unwrap();
+        assert_eq!(result["source"], "renderer");
+        assert_eq!(
+            (result["width"].as_u64(), result["height"].as_u64()),
+            (Some(3), Some(2))
+        );
+        assert!(!server.capture_requested());
+
+        let saved = image::open(&png).expect("png should be written").to_rgba8();
+        let _ = std::fs::remove_file(&png);
+        assert_eq!(saved.get_pixel(2, 1).0, [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn framebuffer_get_returns_pixels_as_base64_or_raw_bytes() {
+        use crate::protocol::decode_base64;
+        use std::io::Read;
+
+        let path = test_socket_path();
+        let server = DebugServer::with_path(path.clone()).expect("server should start");
+        let (redraw_tx, redraw_rx) = std::sync::mpsc::channel();
+        let redraw_tx = Mutex::new(redraw_tx);
+        server.enable_frame_capture(move || {
+            let _ = redraw_tx.lock().unwrap().send(());
+        });
+        std::thread::sleep(std::time::Duration::from_millis(100));
+
+        let mut stream = UnixStream::connect(&path).expect("should connect");
+        stream
+            .set_read_timeout(Some(std::time::Duration::from_secs(2)))
+            .unwrap();
+        let resp = send_request(
+            &mut stream,
+            r#"{"method":"framebuffer.get","params":{"encoding":"hex"},"id":1}"#,
+        );
+        assert_eq!(resp.error.unwrap().code, -32602);
+
+        // Half-transparent red, premultiplied as the renderer reads it back.
+        let frame = RgbaImage::from_pixel(2, 1, image::Rgba([128, 0, 0, 128]));
+        let redraw_rx = Mutex::new(redraw_rx);
+        let provide = |server: &DebugServer| {
+            redraw_rx
+                .lock()
+                .unwrap()
+                .recv_timeout(std::time::Duration::from_secs(2))
+                .expect("server should request a redraw");
+            server.provide_capture(frame.clone());
+        };
+
+        let resp = std::thread::scope(|scope| {
+            scope.spawn(|| provide(&server));
+            send_request(
+                &mut stream,
+                r#"{"method":"framebuffer.get","params":null,"id":2}"#,
+            )
+        });
+        let result = resp.result.expect("framebuffer.get should succeed");
+        assert_eq!(
+            (result["width"].as_u64(), result["height"].as_u64()),
+            (Some(2), Some(1))
+        );
+        assert_eq!(result["encoding"], "base64");
+        let pixels = decode_base64(result["data"].as_str().unwrap()).unwrap();
+        assert_eq!(pixels, [255, 0, 0, 128, 255, 0, 0, 128]);
+
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let header = std::thread::scope(|scope| {
+            scope.spawn(|| provide(&server));
+            writeln!(
+                stream,
+                r#"{{"method":"framebuffer.get","params":{{"encoding":"binary"}},"id":3}}"#
+            )
+            .unwrap();
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            serde_json::from_str::<DebugResponse>(&line).unwrap()
+        });
+        let result = header
+            .result
+            .expect("binary framebuffer.get should succeed");
+        assert_eq!(result["bytes"], 8);
+        assert!(result.get("data").is_none());
+        let mut pixels = [0u8; 8];
+        reader.read_exact(&mut pixels).unwrap();
+        assert_eq!(pixels, [255, 0, 0, 128, 255, 0, 0, 128]);
+    }
+
+    #[test]
+    fn capture_start_records_every_nth_frame_into_an_apng() {
+        let path = test_socket_path();
+        let server = DebugServer::with_path(path.clone()).expect("server should start");
+        let (redraw_tx, redraw_rx) = std::sync::mpsc::channel();
+        let redraw_tx = Mutex::new(redraw_tx);
+        server.enable_frame_capture(move || {
+            let _ = redraw_tx.lock().unwrap().send(());
+        });
+        std::thread::sleep(std::time::Duration::from_millis(100));
+
+        let mut stream = UnixStream::connect(&path).expect("should connect");
+        stream
+            .set_read_timeout(Some(std::time::Duration::from_secs(5)))
+            .unwrap();
+        let resp = send_request(
+            &mut stream,
+            r#"{"method":"capture.stop","params":null,"id":1}"#,
+        );
+        assert_eq!(resp.result.unwrap()["stopped"], false);
+        let resp = send_request(
+            &mut stream,
+            r#"{"method":"capture.start","params":{"path":"/tmp/x.png","frames":0},"id":2}"#,
+        );
+        assert_eq!(resp.error.unwrap().code, -32602);
+
+        let output = std::env::temp_dir().join(format!("motif-capture-{}.png", std::process::id()));
+        let request = serde_json::json!({
+            "method": "capture.start",
+            "params": { "path": output, "frames": 3, "every": 2 },
+            "id": 3,
+        })
+        .to_string();
+        // Stand in for the app: render a frame whenever one is asked for.
+        let provided = std::thread::scope(|scope| {
+            let server = &server;
+            let app = scope.spawn(move || {
+                let mut provided = 0u8;
+                while redraw_rx
+                    .recv_timeout(std::time::Duration::from_millis(500))
+                    .is_ok()
+                {
+                    let pixel = image::Rgba([provided, 0, 0, 255]);
+                    server.provide_capture(RgbaImage::from_pixel(2, 2, pixel));
+                    provided += 1;
+                }
+                provided
+            });
+            let resp = send_request(&mut stream, &request);
+            let result = resp.result.expect("capture.start should succeed");
+            assert_eq!(result["format"], "apng");
+            assert_eq!(result["frames"], 3);
+            assert_eq!(
+                (result["width"].as_u64(), result["height"].as_u64()),
+                (Some(2), Some(2))
+            );
+            assert_eq!(result["stopped"], false);
+            app.join().unwrap()
+        });
+        // Frames 0, 2 and 4 are kept; the recording stops asking after that.
+        assert_eq!(provided, 5);
+        assert!(!server.capture_requested());
+
+        let file = std::fs::File::open(&output).expect("capture should be saved");
+        let reader = png::Decoder::new(std::io::BufReader::new(file))
+            .read_info()
+            .expect("capture should be a PNG");
+        let animation = reader
+            .info()
+            .animation_control
+            .expect("PNG should be animated");
+        assert_eq!(animation.num_frames, 3);
+        let _ = std::fs::remove_file(&output);
+    }
+
+    #[test]
+    fn screenshot_scale_size_and_overlays_are_passed_to_the_app() {
+        let path = test_socket_path();
+        let server = DebugServer::with_path(path.clone()).expect("server should start");
+        let (redraw_tx, redraw_rx) = std::sync::mpsc::channel();
+        let redraw_tx = Mutex::new(redraw_tx);
+        server.enable_frame_capture(move || {
+            let _ = redraw_tx.lock().unwrap().send(());
+        });
+        std::thread::sleep(std::time::Duration::from_millis(100));
+
+        let mut stream = UnixStream::connect(&path).expect("should connect");
+        for params in [
+            r#"{"path":"/tmp/x.png","scale":0}"#,
+            r#"{"path":"/tmp/x.png","scale":100}"#,
+            r#"{"path":"/tmp/x.png","width":10}"#,
+            r#"{"path":"/tmp/x.png","source":"window","scale":2}"#,
+            r#"{"path":"/tmp/x.png","include_overlays":"yes"}"#,
+            r#"{"path":"/tmp/x.png","source":"window","include_overlays":true}"#,
+        ] {
+            let request = format!(r#"{{"method":"screenshot","params":{params},"id":1}}"#);
+            let resp = send_request(&mut stream, &request);
+            assert_eq!(resp.error.unwrap().code, -32602, "{params}");
+        }
+
+        let png = std::env::temp_dir().join(format!("motif-scaled-{}.png", std::process::id()));
+        let request = serde_json::json!({
+            "method": "screenshot",
+            "params": {
+                "path": png,
+                "scale": 2,
+                "width": 4,
+                "height": 3,
+                "include_overlays": true,
+            },
+            "id": 2,
+        });
+        let resp = std::thread::scope(|scope| {
+            let server = &server;
+            scope.spawn(move || {
+                redraw_rx
+                    .recv_timeout(std::time::Duration::from_secs(2))
+                    .expect("server should request a redraw");
+                let options = CaptureOptions {
+                    scale: Some(2.0),
+                    size: Some((4.0, 3.0)),
+                    include_overlays: true,
+                };
+                assert_eq!(server.capture_requests(), vec![options]);
+                // A frame for other options doesn't answer the request.
+                server.provide_capture_for(CaptureOptions::default(), RgbaImage::new(1, 1));
+                assert!(server.capture_requested());
+                server.provide_capture_for(options, RgbaImage::new(8, 6));
+            });
+            send_request(&mut stream, &request.to_string())
+        });
+        let _ = std::fs::remove_file(&png);
+
+        let result = resp.result.expect("screenshot should succeed");
+        assert_eq!(
+            (result["width"].as_u64(), result["height"].as_u64()),
+            (Some(8), Some(6))
+        );
+        assert!(!server.capture_requested());
+    }
+
+    #[test]
+    fn server_registers_and_unregisters() {
+        let path = test_socket_path();
+        let server = DebugServer::with_path(path.clone()).expect("server should start");
+        server.set_app_name("registry test");
+
+        let find = || {
+            crate::registry::list_entries(Path::new(REGISTRY_DIR))
+                .into_iter()
+                .find(|entry| entry.socket_path == path)
+        };
+        let entry = find().expect("server should be registered");
+        assert_eq!(entry.app_name, "registry test");
+        assert_eq!(entry.pid, std::process::id());
+
+        drop(server);
+        assert!(find().is_none());
+    }
+
+    #[test]
+    fn server_screenshot_source_is_validated() {
+        let path = test_socket_path();
+        let _server = DebugServer::with_path(path.clone()).expect("server should start");
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        let mut stream = UnixStream::connect(&path).expect("should connect");
+
+        // Renderer capture was never enabled.
+        let resp = send_request(
+            &mut stream,
+            r#"{"method":"screenshot","params":{"path":"/tmp/x.png","source":"renderer"},"id":24}"#,
+        );
+        assert_eq!(resp.error.unwrap().code, -32000);
+
+        let resp = send_request(
+            &mut stream,
+            r#"{"method":"screenshot","params":{"path":"/tmp/x.png","source":"gpu"},"id":25}"#,
+        );
+        assert_eq!(resp.error.unwrap().code, -32602);
+    }
+
+    // --- Overlay tests ---
+
+    /// Helper: send a request and read the response on an existing connection.
+    fn send_request(stream: &mut UnixStream, request: &str) -> DebugResponse {
+        writeln!(stream, "{request}").unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut response_line = String::new();
+        reader.read_line(&mut response_line).unwrap();
+        serde_json::from_str(&response_line).unwrap()
+    }
+
+    #[test]
+    fn server_info_reports_frames_and_renderer() {
+        let path = test_socket_path();
+        let server = DebugServer::with_path(path.clone()).expect("server should start");
+        std::thread::sleep(std::time::Duration::from_millis(100));
+
+        let mut stream = UnixStream::connect(&path).expect("should connect");
+        stream
+            .set_read_timeout(Some(std::time::Duration::from_secs(2)))
+            .unwrap();
+
+        let resp = send_request(
+            &mut stream,
+            r#"{"method":"server.info","params":null,"id":1}"#,
+        );
+        let result = resp.result.unwrap();
+        assert_eq!(result["protocol_version"], PROTOCOL_VERSION);
+        assert_eq!(result["pid"], std::process::id());
+        assert_eq!(result["frame"], 0);
+        assert_eq!(result["has_snapshot"], false);
+        assert!(result["renderer"].is_null());
+        assert!(result["gpu_time_ms"].is_null());
+
+        let scene = motif_core::Scene::new();
+        server.update_scene(SceneSnapshot::from_scene(&scene, (800.0, 600.0), 1.0));
+        server.update_scene(SceneSnapshot::from_scene(&scene, (800.0, 600.0), 1.0));
+        server.set_renderer_info("metal", "Apple M1");
+        server.set_gpu_time(Some(std::time::Duration::from_micros(1500)));
+
+        let resp = send_request(
+            &mut stream,
+            r#"{"method":"server.info","params":null,"id":2}"#,
+        );
+        let result = resp.result.unwrap();
+        assert_eq!(result["frame"], 2);
+        assert_eq!(result["has_snapshot"], true);
+        assert_eq!(result["renderer"]["backend"], "metal");
+        assert_eq!(result["renderer"]["version"], "Apple M1");
+        assert_eq!(result["gpu_time_ms"], 1.5);
+        assert_eq!(result["encodings"], serde_json::json!(["json", "cbor"]));
+    }
+
+    #[test]
+    fn server_stats_counts_connections_and_requests() {
+        let path = test_socket_path();
+        let server = DebugServer::with_path(path.clone()).expect("server should start");
+        std::thread::sleep(std::time::Duration::from_millis(100));
+
+        let mut stream = UnixStream::connect(&path).expect("should connect");
+        stream
+            .set_read_timeout(Some(std::time::Duration::from_secs(2)))
+            .unwrap();
+
+        let stats = r#"{"method":"server.stats","params":null,"id":1}"#;
+        let result = send_request(&mut stream, stats).result.unwrap();
+        assert_eq!(result["connections"], 1);
+        assert_eq!(result["requests"]["server.stats"], 1);
+        assert!(result["snapshot_age_ms"].is_null());
+        assert!(result["snapshot_size"].is_null());
+
+        send_request(
+            &mut stream,
+            r#"{"method":"scene.stats","params":null,"id":2}"#,
+        );
+        send_request(&mut stream, "not json");
+        server.update_scene(SceneSnapshot::from_scene(
+            &motif_core::Scene::new(),
+            (400.0, 200.0),
+            1.0,
+        ));
+        let second = UnixStream::connect(&path).expect("should connect");
+        std::thread::sleep(std::time::Duration::from_millis(100));
+
+        let result = send_request(&mut stream, stats).result.unwrap();
+        assert_eq!(result["connections"], 2);
+        assert_eq!(result["total_connections"], 2);
+        assert_eq!(result["requests"]["server.stats"], 2);
+        assert_eq!(result["requests"]["scene.stats"], 1);
+        assert_eq!(result["total_requests"], 3);
+        assert_eq!(result["malformed_requests"], 1);
+        assert!(result["snapshot_age_ms"].as_f64().unwrap() >= 0.0);
+        assert_eq!(result["snapshot_size"]["quads"], 0);
+        assert!(result["snapshot_size"]["json_bytes"].as_u64().unwrap() > 0);
+
+        drop(second);
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        let result = send_request(&mut stream, stats).result.unwrap();
+        assert_eq!(result["connections"], 1);
+    }
+
+    #[test]
+    fn log_set_level_layers_overrides_on_the_app_filter() {
         let path = test_socket_path();
         let server = DebugServer::with_path(path.clone()).expect("server should start");
-        assert!(path.exists(), "socket file should exist");
-
-        drop(server);
         std::thread::sleep(std::time::Duration::from_millis(100));
-        assert!(!path.exists(), "socket file should be removed on drop");
+
+        let mut stream = UnixStream::connect(&path).expect("should connect");
+        stream
+            .set_read_timeout(Some(std::time::Duration::from_secs(2)))
+            .unwrap();
+
+        let trace_wgpu =
+            r#"{"method":"log.set_level","params":{"level":"trace","target":"wgpu"},"id":1}"#;
+        let resp = send_request(&mut stream, trace_wgpu);
+        assert_eq!(resp.error.unwrap().code, -32000);
+
+        let installed = Arc::new(Mutex::new(Vec::<String>::new()));
+        let sink = Arc::clone(&installed);
+        server.enable_log_control("info,wgpu=warn", move |filter| {
+            if filter.contains("naga") {
+                return Err("naga is not allowed".into());
+            }
+            sink.lock().unwrap().push(filter.to_string());
+            Ok(())
+        });
+
+        let resp = send_request(&mut stream, trace_wgpu);
+        assert_eq!(resp.result.unwrap()["filter"], "info,wgpu=trace");
+
+        let resp = send_request(
+            &mut stream,
+            r#"{"method":"log.set_level","params":{"level":"loud"},"id":2}"#,
+        );
+        assert_eq!(resp.error.unwrap().code, -32602);
+
+        // A rejected filter leaves the previous one in place.
+        let resp = send_request(
+            &mut stream,
+            r#"{"method":"log.set_level","params":{"level":"debug","target":"naga"},"id":3}"#,
+        );
+        assert_eq!(resp.error.unwrap().code, -32000);
+
+        // A timed override reverts on its own.
+        let resp = send_request(
+            &mut stream,
+            r#"{"method":"log.set_level","params":{"level":"debug","duration_ms":50},"id":4}"#,
+        );
+        let result = resp.result.unwrap();
+        assert_eq!(result["filter"], "debug,wgpu=trace");
+        assert_eq!(result["expires_ms"], 50);
+        std::thread::sleep(std::time::Duration::from_millis(150));
+        assert_eq!(
+            installed.lock().unwrap().last().map(String::as_str),
+            Some("info,wgpu=trace")
+        );
+
+        let resp = send_request(
+            &mut stream,
+            r#"{"method":"log.set_level","params":{"reset":true},"id":5}"#,
+        );
+        assert_eq!(resp.result.unwrap()["filter"], "info,wgpu=warn");
+        assert_eq!(installed.lock().unwrap().len(), 4);
     }
 
     #[test]
-    fn server_responds_to_scene_stats_without_snapshot() {
-        let path = test_socket_path();
-        let _server = DebugServer::with_path(path.clone()).expect("server should start");
+    fn scene_patch_queues_quad_overrides() {
+        use motif_core::{DevicePoint, DeviceRect, DeviceSize, Quad, Srgba};
 
+        let path = test_socket_path();
+        let server = DebugServer::with_path(path.clone()).expect("server should start");
+        let mut scene = motif_core::Scene::new();
+        for x in [0.0, 100.0] {
+            scene.push_quad(Quad::new(
+                DeviceRect::new(DevicePoint::new(x, 0.0), DeviceSize::new(50.0, 50.0)),
+                Srgba::new(1.0, 1.0, 1.0, 1.0),
+            ));
+        }
+        server.update_scene(SceneSnapshot::from_scene(&scene, (400.0, 200.0), 1.0));
         std::thread::sleep(std::time::Duration::from_millis(100));
 
         let mut stream = UnixStream::connect(&path).expect("should connect");
@@ -748,37 +4339,65 @@ mod tests {
             .set_read_timeout(Some(std::time::Duration::from_secs(2)))
             .unwrap();
 
-        let request = r#"{"method":"scene.stats","params":null,"id":1}"#;
-        writeln!(stream, "{request}").unwrap();
-
-        let mut reader = BufReader::new(stream);
-        let mut response_line = String::new();
-        reader.read_line(&mut response_line).unwrap();
+        send_request(
+            &mut stream,
+            r#"{"method":"scene.patch","params":{"quad":1,"dx":10,"color":[1,0,0,1]},"id":1}"#,
+        );
+        send_request(
+            &mut stream,
+            r#"{"method":"scene.patch","params":{"quad":1,"dx":5,"dy":-2},"id":2}"#,
+        );
+        let resp = send_request(
+            &mut stream,
+            r#"{"method":"scene.patch","params":{"quad":0,"hidden":true},"id":3}"#,
+        );
+        let patches = &resp.result.unwrap()["patches"];
+        assert_eq!(patches[0]["quad"], 0);
+        assert_eq!(patches[1]["dx"], 15.0);
+
+        QuadPatch::apply_all(&server.patches(), &mut scene);
+        assert_eq!(scene.quads()[0].background.alpha, 0.0);
+        let moved = &scene.quads()[1];
+        assert_eq!(
+            (moved.bounds.origin.x, moved.bounds.origin.y),
+            (115.0, -2.0)
+        );
+        assert_eq!(moved.background, Srgba::new(1.0, 0.0, 0.0, 1.0));
+
+        for (bad, code) in [
+            (r#"{"quad":2,"dx":1}"#, -32602),
+            (r#"{"quad":0,"color":"red"}"#, -32602),
+            (r#"{"dx":1}"#, -32602),
+        ] {
+            let req = format!(r#"{{"method":"scene.patch","params":{bad},"id":4}}"#);
+            assert_eq!(send_request(&mut stream, &req).error.unwrap().code, code);
+        }
 
-        let resp: DebugResponse = serde_json::from_str(&response_line).unwrap();
-        assert_eq!(resp.id, 1);
-        assert!(
-            resp.error.is_some(),
-            "should error when no snapshot available"
+        let resp = send_request(
+            &mut stream,
+            r#"{"method":"scene.patch","params":{"quad":0,"reset":true},"id":5}"#,
         );
-        assert_eq!(resp.error.unwrap().code, -32000);
+        assert_eq!(resp.result.unwrap()["patches"].as_array().unwrap().len(), 1);
+        let resp = send_request(
+            &mut stream,
+            r#"{"method":"scene.patch","params":{"clear":true},"id":6}"#,
+        );
+        assert_eq!(resp.result.unwrap()["patches"], serde_json::json!([]));
+        assert!(server.patches().is_empty());
     }
 
     #[test]
-    fn server_responds_to_scene_stats_with_snapshot() {
+    fn pixel_color_samples_the_snapshot_or_the_renderer() {
+        use motif_core::{DevicePoint, DeviceRect, DeviceSize, Quad, Srgba};
+
         let path = test_socket_path();
         let server = DebugServer::with_path(path.clone()).expect("server should start");
-
-        // Provide a snapshot.
-        use motif_core::{DevicePoint, DeviceRect, DeviceSize, Quad, Scene, Srgba};
-        let mut scene = Scene::new();
+        let mut scene = motif_core::Scene::new();
         scene.push_quad(Quad::new(
-            DeviceRect::new(DevicePoint::new(0.0, 0.0), DeviceSize::new(10.0, 10.0)),
+            DeviceRect::new(DevicePoint::new(20.0, 20.0), DeviceSize::new(40.0, 40.0)),
             Srgba::new(1.0, 0.0, 0.0, 1.0),
         ));
-        let snap = SceneSnapshot::from_scene(&scene, (800.0, 600.0), 2.0);
-        server.update_scene(snap);
-
+        server.update_scene(SceneSnapshot::from_scene(&scene, (100.0, 100.0), 2.0));
         std::thread::sleep(std::time::Duration::from_millis(100));
 
         let mut stream = UnixStream::connect(&path).expect("should connect");
@@ -786,120 +4405,220 @@ mod tests {
             .set_read_timeout(Some(std::time::Duration::from_secs(2)))
             .unwrap();
 
-        let request = r#"{"method":"scene.stats","params":null,"id":42}"#;
-        writeln!(stream, "{request}").unwrap();
-
-        let mut reader = BufReader::new(stream);
-        let mut response_line = String::new();
-        reader.read_line(&mut response_line).unwrap();
+        // Logical (15, 15) is device (30, 30), inside the quad.
+        let pick = r#"{"method":"pixel.color","params":{"x":15,"y":15},"id":1}"#;
+        let result = send_request(&mut stream, pick).result.unwrap();
+        assert_eq!(result["source"], "snapshot");
+        assert_eq!(result["hex"], "#ff0000ff");
+        assert_eq!(result["device"], serde_json::json!([30.0, 30.0]));
+        assert_eq!(result["primitive"]["kind"], "quad");
+        assert_eq!(result["primitive"]["index"], 0);
+        assert_eq!(result["primitive"]["info"]["bounds"]["w"], 40.0);
+
+        let resp = send_request(
+            &mut stream,
+            r#"{"method":"pixel.color","params":{"x":50,"y":10},"id":2}"#,
+        );
+        assert_eq!(resp.error.unwrap().code, -32602);
+        let resp = send_request(
+            &mut stream,
+            r#"{"method":"pixel.color","params":{"x":1},"id":3}"#,
+        );
+        assert_eq!(resp.error.unwrap().code, -32602);
 
-        let resp: DebugResponse = serde_json::from_str(&response_line).unwrap();
-        assert_eq!(resp.id, 42);
-        assert!(resp.error.is_none());
+        // With renderer capture on, the pixel comes from the frame.
+        let (redraw_tx, redraw_rx) = std::sync::mpsc::channel();
+        let redraw_tx = Mutex::new(redraw_tx);
+        server.enable_frame_capture(move || {
+            let _ = redraw_tx.lock().unwrap().send(());
+        });
+        let mut frame = RgbaImage::new(100, 100);
+        frame.put_pixel(30, 30, image::Rgba([0, 0, 255, 255]));
+        let resp = std::thread::scope(|scope| {
+            scope.spawn(move || {
+                redraw_rx
+                    .recv_timeout(std::time::Duration::from_secs(2))
+                    .expect("server should request a redraw");
+                server.provide_capture(frame);
+            });
+            send_request(&mut stream, pick)
+        });
         let result = resp.result.unwrap();
-        assert_eq!(result["quad_count"], 1);
-        assert_eq!(result["text_run_count"], 0);
-        assert_eq!(result["viewport_size"][0], 800.0);
-        assert_eq!(result["scale_factor"], 2.0);
+        assert_eq!(result["source"], "renderer");
+        assert_eq!(result["hex"], "#0000ffff");
+        assert_eq!(result["primitive"]["index"], 0);
     }
 
     #[test]
-    fn server_handles_unknown_method() {
-        let path = test_socket_path();
-        let _server = DebugServer::with_path(path.clone()).expect("server should start");
+    fn atlas_dump_saves_pages_with_row_stats() {
+        use motif_core::{AtlasAllocator, AtlasPageDump};
 
+        let path = test_socket_path();
+        let server = DebugServer::with_path(path.clone()).expect("server should start");
         std::thread::sleep(std::time::Duration::from_millis(100));
-
         let mut stream = UnixStream::connect(&path).expect("should connect");
         stream
             .set_read_timeout(Some(std::time::Duration::from_secs(2)))
             .unwrap();
 
-        let request = r#"{"method":"nonexistent.method","params":null,"id":5}"#;
-        writeln!(stream, "{request}").unwrap();
+        let out = std::env::temp_dir().join(format!("motif-atlas-{}.png", std::process::id()));
+        let dump_request = format!(
+            r#"{{"method":"atlas.dump","params":{{"path":{:?}}},"id":1}}"#,
+            out.to_str().unwrap()
+        );
+        let resp = send_request(&mut stream, &dump_request);
+        assert_eq!(resp.error.unwrap().code, -32000, "capture isn't enabled");
+        let resp = send_request(&mut stream, r#"{"method":"atlas.dump","id":2}"#);
+        assert_eq!(resp.error.unwrap().code, -32602);
 
-        let mut reader = BufReader::new(stream);
-        let mut response_line = String::new();
-        reader.read_line(&mut response_line).unwrap();
+        let mut allocator = AtlasAllocator::new(32, 2);
+        allocator.insert((1, 1, 0), 10, 10).unwrap();
+        allocator.insert((1, 2, 0), 25, 6).unwrap();
+        allocator.insert((1, 3, 0), 30, 30).unwrap();
+        let dump = AtlasDump {
+            page_size: 32,
+            pages: allocator
+                .page_stats()
+                .into_iter()
+                .map(|stats| AtlasPageDump {
+                    pixels: image::GrayImage::new(32, 32),
+                    stats,
+                })
+                .collect(),
+        };
 
-        let resp: DebugResponse = serde_json::from_str(&response_line).unwrap();
-        assert_eq!(resp.id, 5);
-        assert!(resp.error.is_some());
-        assert_eq!(resp.error.unwrap().code, -32601);
+        let (redraw_tx, redraw_rx) = std::sync::mpsc::channel();
+        let redraw_tx = Mutex::new(redraw_tx);
+        server.enable_frame_capture(move || {
+            let _ = redraw_tx.lock().unwrap().send(());
+        });
+        let (resp, no_atlas) = std::thread::scope(|scope| {
+            scope.spawn(move || {
+                for dump in [Some(dump), None] {
+                    redraw_rx
+                        .recv_timeout(std::time::Duration::from_secs(2))
+                        .expect("server should request a redraw");
+                    assert!(server.atlas_requested());
+                    server.provide_atlas(dump);
+                }
+            });
+            (
+                send_request(&mut stream, &dump_request),
+                send_request(&mut stream, &dump_request),
+            )
+        });
+        assert_eq!(no_atlas.error.unwrap().code, -32000);
+
+        let result = resp.result.unwrap();
+        assert_eq!(
+            (result["width"].as_u64(), result["height"].as_u64()),
+            (Some(32), Some(64))
+        );
+        let pages = result["pages"].as_array().unwrap();
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0]["glyphs"], 2);
+        assert_eq!(pages[0]["rows"][0]["used_width"], 11);
+        assert_eq!(pages[0]["rows"][1]["y"], 11);
+        assert_eq!(pages[0]["used_height"], 18);
+        assert_eq!(pages[1]["rows"][0]["height"], 31);
+        let saved = image::open(&out).expect("atlas PNG should be written");
+        assert_eq!((saved.width(), saved.height()), (32, 64));
+        let _ = std::fs::remove_file(&out);
     }
 
     #[test]
-    fn server_handles_malformed_json() {
+    fn ping_echoes_its_data() {
         let path = test_socket_path();
         let _server = DebugServer::with_path(path.clone()).expect("server should start");
-
         std::thread::sleep(std::time::Duration::from_millis(100));
-
         let mut stream = UnixStream::connect(&path).expect("should connect");
         stream
             .set_read_timeout(Some(std::time::Duration::from_secs(2)))
             .unwrap();
 
-        writeln!(stream, "not valid json").unwrap();
-
-        let mut reader = BufReader::new(stream);
-        let mut response_line = String::new();
-        reader.read_line(&mut response_line).unwrap();
-
-        let resp: DebugResponse = serde_json::from_str(&response_line).unwrap();
-        assert_eq!(resp.id, 0);
-        assert!(resp.error.is_some());
-        assert_eq!(resp.error.unwrap().code, -32700);
+        let resp = send_request(
+            &mut stream,
+            r#"{"method":"ping","params":{"data":[1,2]},"id":1}"#,
+        );
+        let result = resp.result.unwrap();
+        assert_eq!(result["pong"], true);
+        assert_eq!(result["data"], serde_json::json!([1, 2]));
     }
 
     #[test]
-    fn server_responds_to_scene_quads() {
+    fn idle_connections_are_closed() {
         let path = test_socket_path();
         let server = DebugServer::with_path(path.clone()).expect("server should start");
-
-        use motif_core::{DevicePoint, DeviceRect, DeviceSize, Quad, Scene, Srgba};
-        let mut scene = Scene::new();
-        scene.push_quad(Quad::new(
-            DeviceRect::new(DevicePoint::new(10.0, 20.0), DeviceSize::new(100.0, 50.0)),
-            Srgba::new(1.0, 0.0, 0.0, 1.0),
-        ));
-        let snap = SceneSnapshot::from_scene(&scene, (800.0, 600.0), 1.0);
-        server.update_scene(snap);
-
+        server.set_idle_timeout(Some(std::time::Duration::from_millis(300)));
         std::thread::sleep(std::time::Duration::from_millis(100));
-
         let mut stream = UnixStream::connect(&path).expect("should connect");
         stream
             .set_read_timeout(Some(std::time::Duration::from_secs(2)))
             .unwrap();
 
-        let request = r#"{"method":"scene.quads","params":null,"id":10}"#;
-        writeln!(stream, "{request}").unwrap();
+        // Pings inside the timeout keep the connection open.
+        for id in 1..=3 {
+            std::thread::sleep(std::time::Duration::from_millis(150));
+            let resp = send_request(
+                &mut stream,
+                &format!(r#"{{"method":"ping","params":null,"id":{id}}}"#),
+            );
+            assert_eq!(resp.id, id);
+        }
 
+        // Silence past it closes the connection from the server side.
         let mut reader = BufReader::new(stream);
-        let mut response_line = String::new();
-        reader.read_line(&mut response_line).unwrap();
+        let mut line = String::new();
+        assert_eq!(reader.read_line(&mut line).unwrap(), 0);
+    }
 
-        let resp: DebugResponse = serde_json::from_str(&response_line).unwrap();
-        assert_eq!(resp.id, 10);
-        assert!(resp.error.is_none());
+    #[test]
+    fn describe_lists_every_dispatched_method() {
+        // The method names matched in `dispatch`, read from this file so a
+        // new arm without a table entry fails here.
+        let source = include_str!("server.rs");
+        let start = source.find("match request.method.as_str() {").unwrap();
+        let end = start + source[start..].find("_ => DebugResponse::err(").unwrap();
+        let dispatched: Vec<&str> = source[start..end]
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix('"')?.split_once("\" =>"))
+            .map(|(name, _)| name)
+            .collect();
+        let described: Vec<&str> = METHODS.iter().map(|method| method.name).collect();
+        assert_eq!(dispatched, described);
+
+        let path = test_socket_path();
+        let _server = DebugServer::with_path(path.clone()).expect("server should start");
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        let mut stream = UnixStream::connect(&path).expect("should connect");
+        stream
+            .set_read_timeout(Some(std::time::Duration::from_secs(2)))
+            .unwrap();
+
+        let resp = send_request(
+            &mut stream,
+            r#"{"method":"rpc.describe","params":null,"id":1}"#,
+        );
         let result = resp.result.unwrap();
-        let arr = result.as_array().expect("should be an array");
-        assert_eq!(arr.len(), 1);
-        assert_eq!(arr[0]["bounds"]["x"], 10.0);
+        assert_eq!(result["protocol_version"], PROTOCOL_VERSION);
+        let methods = result["methods"].as_array().unwrap();
+        assert_eq!(methods.len(), METHODS.len());
+        let drag = methods
+            .iter()
+            .find(|method| method["name"] == "input.drag")
+            .unwrap();
+        assert_eq!(
+            drag["params"]["required"],
+            serde_json::json!(["from_x", "from_y", "to_x", "to_y"])
+        );
     }
 
     #[test]
-    fn server_responds_to_scene_text_runs() {
+    fn handshake_switches_the_connection_to_cbor() {
         let path = test_socket_path();
         let server = DebugServer::with_path(path.clone()).expect("server should start");
-
-        // Empty scene -- no text runs.
-        use motif_core::Scene;
-        let scene = Scene::new();
-        let snap = SceneSnapshot::from_scene(&scene, (800.0, 600.0), 1.0);
-        server.update_scene(snap);
-
+        let scene = motif_core::Scene::new();
+        server.update_scene(SceneSnapshot::from_scene(&scene, (800.0, 600.0), 1.0));
         std::thread::sleep(std::time::Duration::from_millis(100));
 
         let mut stream = UnixStream::connect(&path).expect("should connect");
@@ -907,32 +4626,38 @@ mod tests {
             .set_read_timeout(Some(std::time::Duration::from_secs(2)))
             .unwrap();
 
-        let request = r#"{"method":"scene.text_runs","params":null,"id":11}"#;
-        writeln!(stream, "{request}").unwrap();
+        let resp = send_request(
+            &mut stream,
+            r#"{"method":"rpc.handshake","params":{"encoding":"msgpack"},"id":1}"#,
+        );
+        assert_eq!(resp.error.unwrap().code, -32602);
 
-        let mut reader = BufReader::new(stream);
-        let mut response_line = String::new();
-        reader.read_line(&mut response_line).unwrap();
+        // Answered in JSON, after which both directions are CBOR.
+        let resp = send_request(
+            &mut stream,
+            r#"{"method":"rpc.handshake","params":{"encoding":"cbor"},"id":2}"#,
+        );
+        assert_eq!(resp.result.unwrap()["encoding"], "cbor");
 
-        let resp: DebugResponse = serde_json::from_str(&response_line).unwrap();
-        assert_eq!(resp.id, 11);
-        assert!(resp.error.is_none());
-        let result = resp.result.unwrap();
-        let arr = result.as_array().expect("should be an array");
-        assert!(arr.is_empty());
+        let request = DebugRequest {
+            method: "scene.stats".into(),
+            params: None,
+            id: 3,
+        };
+        stream
+            .write_all(&Encoding::Cbor.encode(&request).unwrap())
+            .unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let frame = Encoding::Cbor.read_frame(&mut reader).unwrap().unwrap();
+        let resp: DebugResponse = Encoding::Cbor.decode(&frame).unwrap();
+        assert_eq!(resp.id, 3);
+        assert_eq!(resp.result.unwrap()["quad_count"], 0);
     }
 
     #[test]
-    fn server_screenshot_without_window_id_returns_error() {
+    fn scene_profile_reports_last_and_average_phase_times() {
         let path = test_socket_path();
         let server = DebugServer::with_path(path.clone()).expect("server should start");
-
-        use motif_core::Scene;
-        let scene = Scene::new();
-        let snap = SceneSnapshot::from_scene(&scene, (100.0, 100.0), 1.0);
-        server.update_scene(snap);
-        // Note: NOT calling set_window_id()
-
         std::thread::sleep(std::time::Duration::from_millis(100));
 
         let mut stream = UnixStream::connect(&path).expect("should connect");
@@ -940,29 +4665,35 @@ mod tests {
             .set_read_timeout(Some(std::time::Duration::from_secs(2)))
             .unwrap();
 
-        let request = r#"{"method":"screenshot","params":{"path":"/tmp/test.png"},"id":20}"#;
-        writeln!(stream, "{request}").unwrap();
+        let resp = send_request(
+            &mut stream,
+            r#"{"method":"scene.profile","params":null,"id":1}"#,
+        );
+        assert_eq!(resp.error.unwrap().code, -32000);
 
-        let mut reader = BufReader::new(stream);
-        let mut response_line = String::new();
-        reader.read_line(&mut response_line).unwrap();
+        for ms in [2, 4] {
+            let mut profile = FrameProfile::default();
+            profile.total = Duration::from_millis(ms * 2);
+            profile.record(FramePhase::Layout, Duration::from_millis(ms));
+            server.push_frame_profile(profile);
+        }
 
-        let resp: DebugResponse = serde_json::from_str(&response_line).unwrap();
-        assert_eq!(resp.id, 20);
-        assert!(resp.error.is_some(), "should error without window_id");
-        assert_eq!(resp.error.unwrap().code, -32000);
+        let resp = send_request(
+            &mut stream,
+            r#"{"method":"scene.profile","params":{"frames":10},"id":2}"#,
+        );
+        let result = resp.result.unwrap();
+        assert_eq!(result["frames"], 2);
+        assert_eq!(result["last"]["total_ms"], 8.0);
+        assert_eq!(result["last"]["phases"]["layout"], 4.0);
+        assert_eq!(result["last"]["phases"]["submit"], 0.0);
+        assert_eq!(result["average"]["phases"]["layout"], 3.0);
     }
 
     #[test]
-    fn server_screenshot_missing_params_returns_error() {
+    fn frames_over_budget_are_pushed_to_the_client_that_set_it() {
         let path = test_socket_path();
         let server = DebugServer::with_path(path.clone()).expect("server should start");
-
-        use motif_core::Scene;
-        let scene = Scene::new();
-        let snap = SceneSnapshot::from_scene(&scene, (800.0, 600.0), 1.0);
-        server.update_scene(snap);
-
         std::thread::sleep(std::time::Duration::from_millis(100));
 
         let mut stream = UnixStream::connect(&path).expect("should connect");
@@ -970,31 +4701,57 @@ mod tests {
             .set_read_timeout(Some(std::time::Duration::from_secs(2)))
             .unwrap();
 
-        // Send without params.
-        let request = r#"{"method":"screenshot","params":null,"id":21}"#;
-        writeln!(stream, "{request}").unwrap();
+        let resp = send_request(
+            &mut stream,
+            r#"{"method":"perf.set_budget","params":{"ms":-1},"id":1}"#,
+        );
+        assert_eq!(resp.error.unwrap().code, -32602);
+        let resp = send_request(
+            &mut stream,
+            r#"{"method":"perf.set_budget","params":{"ms":16},"id":2}"#,
+        );
+        assert_eq!(resp.result.unwrap()["budget_ms"], 16.0);
 
-        let mut reader = BufReader::new(stream);
-        let mut response_line = String::new();
-        reader.read_line(&mut response_line).unwrap();
+        let frame = |ms: u64| {
+            let mut profile = FrameProfile::default();
+            profile.total = Duration::from_millis(ms);
+            profile.record(FramePhase::Layout, Duration::from_millis(ms / 2));
+            profile
+        };
+        server.push_frame_profile(frame(10));
+        server.push_frame_profile(frame(30));
 
-        let resp: DebugResponse = serde_json::from_str(&response_line).unwrap();
-        assert_eq!(resp.id, 21);
-        assert!(resp.error.is_some());
-        assert_eq!(resp.error.unwrap().code, -32602);
+        let mut line = String::new();
+        BufReader::new(stream.try_clone().unwrap())
+            .read_line(&mut line)
+            .unwrap();
+        let alert: DebugNotification = serde_json::from_str(&line).unwrap();
+        assert_eq!(alert.method, "perf.budget_exceeded");
+        assert_eq!(alert.params["budget_ms"], 16.0);
+        assert_eq!(alert.params["total_ms"], 30.0);
+        assert_eq!(alert.params["phases"]["layout"], 15.0);
+
+        // Clearing the budget stops the alerts: the next line is a response.
+        let resp = send_request(
+            &mut stream,
+            r#"{"method":"perf.set_budget","params":{"ms":0},"id":3}"#,
+        );
+        assert!(resp.result.unwrap()["budget_ms"].is_null());
+        server.push_frame_profile(frame(30));
+        let resp = send_request(
+            &mut stream,
+            r#"{"method":"scene.profile","params":null,"id":4}"#,
+        );
+        assert_eq!(resp.id, 4);
     }
 
     #[test]
-    fn server_screenshot_with_invalid_window_id_returns_error() {
+    fn time_scale_is_queued_for_the_app_once() {
         let path = test_socket_path();
         let server = DebugServer::with_path(path.clone()).expect("server should start");
-
-        use motif_core::Scene;
-        let scene = Scene::new();
-        let snap = SceneSnapshot::from_scene(&scene, (320.0, 240.0), 1.0);
-        server.update_scene(snap);
-        server.set_window_id(999999); // Invalid window ID
-
+        let redraws = Arc::new(Mutex::new(0));
+        let counter = redraws.clone();
+        server.enable_frame_capture(move || *counter.lock().unwrap() += 1);
         std::thread::sleep(std::time::Duration::from_millis(100));
 
         let mut stream = UnixStream::connect(&path).expect("should connect");
@@ -1002,28 +4759,63 @@ mod tests {
             .set_read_timeout(Some(std::time::Duration::from_secs(2)))
             .unwrap();
 
-        let request =
-            r#"{"method":"screenshot","params":{"path":"/tmp/test-invalid.png"},"id":22}"#;
-        writeln!(stream, "{request}").unwrap();
+        let resp = send_request(
+            &mut stream,
+            r#"{"method":"debug.time_scale","params":{"scale":-1},"id":1}"#,
+        );
+        assert_eq!(resp.error.unwrap().code, -32602);
+        assert_eq!(server.take_time_scale(), None);
+
+        let resp = send_request(
+            &mut stream,
+            r#"{"method":"debug.time_scale","params":{"scale":0.1},"id":2}"#,
+        );
+        assert_eq!(resp.result.unwrap()["scale"], 0.1);
+        assert_eq!(*redraws.lock().unwrap(), 1);
+        assert_eq!(server.take_time_scale(), Some(0.1));
+        assert_eq!(server.take_time_scale(), None);
+    }
+
+    #[test]
+    fn overdraw_toggles_the_render_mode_and_requests_a_redraw() {
+        let path = test_socket_path();
+        let server = DebugServer::with_path(path.clone()).expect("server should start");
+        let redraws = Arc::new(Mutex::new(0));
+        let counter = redraws.clone();
+        server.enable_frame_capture(move || *counter.lock().unwrap() += 1);
+        std::thread::sleep(std::time::Duration::from_millis(100));
 
-        let mut reader = BufReader::new(stream);
-        let mut response_line = String::new();
-        reader.read_line(&mut response_line).unwrap();
+        let mut stream = UnixStream::connect(&path).expect("should connect");
+        stream
+            .set_read_timeout(Some(std::time::Duration::from_secs(2)))
+            .unwrap();
 
-        let resp: DebugResponse = serde_json::from_str(&response_line).unwrap();
-        assert_eq!(resp.id, 22);
-        assert!(resp.error.is_some(), "invalid window ID should error");
-    }
+        assert_eq!(server.render_mode(), RenderMode::Normal);
+        let resp = send_request(
+            &mut stream,
+            r#"{"method":"debug.overdraw","params":null,"id":1}"#,
+        );
+        assert_eq!(resp.result.unwrap()["enabled"], true);
+        assert_eq!(server.render_mode(), RenderMode::Overdraw);
+        assert_eq!(*redraws.lock().unwrap(), 1);
 
-    // --- Overlay tests ---
+        let resp = send_request(
+            &mut stream,
+            r#"{"method":"debug.overdraw","params":{"enabled":true},"id":2}"#,
+        );
+        assert_eq!(resp.result.unwrap()["enabled"], true);
+        let resp = send_request(
+            &mut stream,
+            r#"{"method":"debug.overdraw","params":null,"id":3}"#,
+        );
+        assert_eq!(resp.result.unwrap()["enabled"], false);
+        assert_eq!(server.render_mode(), RenderMode::Normal);
 
-    /// Helper: send a request and read the response on an existing connection.
-    fn send_request(stream: &mut UnixStream, request: &str) -> DebugResponse {
-        writeln!(stream, "{request}").unwrap();
-        let mut reader = BufReader::new(stream.try_clone().unwrap());
-        let mut response_line = String::new();
-        reader.read_line(&mut response_line).unwrap();
-        serde_json::from_str(&response_line).unwrap()
+        let resp = send_request(
+            &mut stream,
+            r#"{"method":"debug.overdraw","params":{"enabled":"yes"},"id":4}"#,
+        );
+        assert_eq!(resp.error.unwrap().code, -32602);
     }
 
     #[test]
@@ -1131,6 +4923,285 @@ mod tests {
         assert_eq!(arr[0]["id"], 1);
     }
 
+    #[test]
+    fn draw_arrow_adds_quads_and_a_label() {
+        let path = test_socket_path();
+        let server = DebugServer::with_path(path.clone()).expect("server should start");
+        std::thread::sleep(std::time::Duration::from_millis(100));
+
+        let mut stream = UnixStream::connect(&path).expect("should connect");
+        stream
+            .set_read_timeout(Some(std::time::Duration::from_secs(2)))
+            .unwrap();
+
+        let draw = r#"{"method":"debug.draw_arrow","params":{"from":[10,50],"to":[110,50],"label":"gap"},"id":1}"#;
+        let id = send_request(&mut stream, draw).result.unwrap()["id"].clone();
+        let overlays = server.overlays();
+        assert!(overlays.len() >= 3);
+        assert!(overlays.iter().all(|q| q.id == id));
+        let labels = server.overlay_labels();
+        assert_eq!(labels.len(), 1);
+        assert_eq!(labels[0].text, "gap");
+        assert!(labels[0].x < 10.0);
+
+        let list = send_request(
+            &mut stream,
+            r#"{"method":"debug.list","params":null,"id":2}"#,
+        );
+        assert_eq!(list.result.unwrap()[0]["label"], "gap");
+
+        let bad = send_request(
+            &mut stream,
+            r#"{"method":"debug.draw_arrow","params":{"from":[10]},"id":3}"#,
+        );
+        assert_eq!(bad.error.unwrap().code, -32602);
+
+        let remove = format!(r#"{{"method":"debug.remove","params":{{"id":{id}}},"id":4}}"#);
+        assert_eq!(
+            send_request(&mut stream, &remove).result.unwrap()["removed"],
+            true
+        );
+        assert!(server.overlays().is_empty());
+        assert!(server.overlay_labels().is_empty());
+    }
+
+    #[test]
+    fn scoped_overlays_go_away_with_their_connection() {
+        let path = test_socket_path();
+        let server = DebugServer::with_path(path.clone()).expect("server should start");
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        let connect = || {
+            let stream = UnixStream::connect(&path).expect("should connect");
+            stream
+                .set_read_timeout(Some(std::time::Duration::from_secs(2)))
+                .unwrap();
+            stream
+        };
+
+        let mut script = connect();
+        let scoped = send_request(
+            &mut script,
+            r#"{"method":"debug.draw_quad","params":{"x":0,"y":0,"w":10,"h":10},"id":1}"#,
+        );
+        let scoped = scoped.result.unwrap()["id"].clone();
+        let kept = send_request(
+            &mut script,
+            r#"{"method":"debug.draw_quad","params":{"x":20,"y":0,"w":10,"h":10,"persist":true},"id":2}"#,
+        );
+        let kept = kept.result.unwrap()["id"].clone();
+
+        let mut watcher = connect();
+        let list = send_request(&mut watcher, r#"{"method":"debug.list","id":3}"#);
+        let list = list.result.unwrap();
+        assert_eq!(list[0]["id"], scoped);
+        assert!(list[0]["connection"].is_u64());
+        assert_eq!(list[1]["connection"], serde_json::Value::Null);
+
+        drop(script);
+        let deadline = Instant::now() + std::time::Duration::from_secs(2);
+        while server.overlays().len() > 1 && Instant::now() < deadline {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        let overlays = server.overlays();
+        assert_eq!(overlays.len(), 1, "scoped quad should be removed");
+        assert_eq!(serde_json::json!(overlays[0].id), kept);
+
+        // Closing a connection that drew nothing leaves the rest alone.
+        drop(watcher);
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        assert_eq!(server.overlays().len(), 1);
+    }
+
+    #[test]
+    fn crosshair_follows_the_reported_pointer() {
+        let path = test_socket_path();
+        let server = DebugServer::with_path(path.clone()).expect("server should start");
+        server.update_scene(SceneSnapshot::from_scene(
+            &motif_core::Scene::new(),
+            (400.0, 200.0),
+            2.0,
+        ));
+        std::thread::sleep(std::time::Duration::from_millis(100));
+
+        let mut stream = UnixStream::connect(&path).expect("should connect");
+        stream
+            .set_read_timeout(Some(std::time::Duration::from_secs(2)))
+            .unwrap();
+
+        // Moving the pointer doesn't ask for a redraw until the crosshair is on.
+        assert!(!server.set_mouse_position(Some((30.0, 40.0))));
+        let resp = send_request(
+            &mut stream,
+            r#"{"method":"debug.crosshair","params":null,"id":1}"#,
+        );
+        let result = resp.result.unwrap();
+        assert_eq!(result["on"], true);
+        assert_eq!(result["pointer"]["x"], 30.0);
+
+        let quads = server.overlays();
+        assert_eq!(quads.len(), 2);
+        assert_eq!((quads[0].y, quads[0].w), (40.0, 200.0));
+        assert_eq!((quads[1].x, quads[1].h), (30.0, 100.0));
+        assert_eq!(server.overlay_labels()[0].text, "30, 40");
+
+        assert!(server.set_mouse_position(Some((50.0, 40.0))));
+        assert!(!server.set_mouse_position(Some((50.0, 40.0))));
+        assert!(server.set_mouse_position(None));
+        assert!(server.overlays().is_empty());
+
+        let resp = send_request(
+            &mut stream,
+            r#"{"method":"debug.crosshair","params":{"on":false},"id":2}"#,
+        );
+        assert_eq!(resp.result.unwrap()["on"], false);
+    }
+
+    #[test]
+    fn highlight_access_outlines_a_node() {
+        use motif_core::{AccessId, AccessNode, AccessRole, Point, Rect, Size};
+
+        let path = test_socket_path();
+        let server = DebugServer::with_path(path.clone()).expect("server should start");
+        server.update_scene(SceneSnapshot::from_scene(
+            &motif_core::Scene::new(),
+            (400.0, 200.0),
+            2.0,
+        ));
+        std::thread::sleep(std::time::Duration::from_millis(100));
+
+        let mut stream = UnixStream::connect(&path).expect("should connect");
+        stream
+            .set_read_timeout(Some(std::time::Duration::from_secs(2)))
+            .unwrap();
+
+        let highlight = r#"{"method":"debug.highlight_access","params":{"id":7},"id":1}"#;
+        let resp = send_request(&mut stream, highlight);
+        assert_eq!(resp.error.unwrap().code, -32000);
+
+        let mut tree = AccessTree::new(AccessId(0));
+        tree.push(AccessNode::new(
+            AccessId(0),
+            AccessRole::Window,
+            "Window".into(),
+        ));
+        tree.push_child(
+            AccessNode::new(AccessId(7), AccessRole::Button, "Save".into())
+                .with_bounds(Rect::new(Point::new(20.0, 40.0), Size::new(100.0, 60.0))),
+        );
+        server.update_access_tree(&tree);
+
+        // Device-pixel bounds come back in logical pixels.
+        let resp = send_request(&mut stream, highlight);
+        let result = resp.result.unwrap();
+        assert_eq!(result["role"], "Button");
+        assert_eq!(result["name"], "Save");
+        assert_eq!(result["bounds"]["x"], 10.0);
+        assert_eq!(result["bounds"]["w"], 50.0);
+
+        let quads = server.overlays();
+        assert_eq!(quads.len(), 1);
+        assert_eq!(
+            (quads[0].x, quads[0].y, quads[0].w, quads[0].h),
+            (10.0, 20.0, 50.0, 30.0)
+        );
+        assert_eq!(quads[0].border_width, 2.0);
+
+        // The root has no bounds, and unknown ids are rejected.
+        let resp = send_request(
+            &mut stream,
+            r#"{"method":"debug.highlight_access","params":{"id":0},"id":2}"#,
+        );
+        assert!(resp.error.unwrap().message.contains("no bounds"));
+        let resp = send_request(
+            &mut stream,
+            r#"{"method":"debug.highlight_access","params":{"id":8},"id":3}"#,
+        );
+        assert_eq!(resp.error.unwrap().code, -32000);
+        let resp = send_request(
+            &mut stream,
+            r#"{"method":"debug.highlight_access","params":{},"id":4}"#,
+        );
+        assert_eq!(resp.error.unwrap().code, -32602);
+
+        // The outline expires on its own.
+        let resp = send_request(
+            &mut stream,
+            r#"{"method":"debug.highlight_access","params":{"id":7,"duration_ms":50},"id":5}"#,
+        );
+        assert_eq!(resp.result.unwrap()["duration_ms"], 50);
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        assert!(server.overlays().is_empty());
+    }
+
+    #[test]
+    fn fps_stats_come_from_frame_intervals() {
+        let start = Instant::now();
+        let times: VecDeque<Instant> = [0, 10, 30, 40]
+            .into_iter()
+            .map(|ms| start + Duration::from_millis(ms))
+            .collect();
+        let stats = FpsStats::measure(&times).unwrap();
+        assert_eq!(stats.samples, 3);
+        assert!((stats.current - 100.0).abs() < 1e-6);
+        assert!((stats.average - 75.0).abs() < 1e-6);
+        assert!((stats.min - 50.0).abs() < 1e-6);
+        assert!((stats.max - 100.0).abs() < 1e-6);
+        assert!((stats.frame_time_ms - 40.0 / 3.0).abs() < 1e-6);
+
+        assert_eq!(
+            FpsStats::measure(&times.iter().take(1).copied().collect()),
+            None
+        );
+    }
+
+    #[test]
+    fn fps_and_fps_overlay() {
+        let path = test_socket_path();
+        let server = DebugServer::with_path(path.clone()).expect("server should start");
+        let scene = motif_core::Scene::new();
+        server.update_scene(SceneSnapshot::from_scene(&scene, (800.0, 600.0), 2.0));
+        std::thread::sleep(std::time::Duration::from_millis(100));
+
+        let mut stream = UnixStream::connect(&path).expect("should connect");
+        stream
+            .set_read_timeout(Some(std::time::Duration::from_secs(2)))
+            .unwrap();
+
+        let fps = r#"{"method":"fps","params":null,"id":1}"#;
+        let resp = send_request(&mut stream, fps);
+        assert_eq!(resp.error.unwrap().code, -32000);
+
+        server.update_scene(SceneSnapshot::from_scene(&scene, (800.0, 600.0), 2.0));
+        let result = send_request(&mut stream, fps).result.unwrap();
+        assert_eq!(result["samples"], 1);
+        assert_eq!(result["frame"], 2);
+        assert!(result["current"].as_f64().unwrap() > 0.0);
+
+        let resp = send_request(
+            &mut stream,
+            r#"{"method":"debug.fps_overlay","params":null,"id":2}"#,
+        );
+        assert_eq!(resp.result.unwrap()["on"], true);
+        assert!(server.fps_overlay_enabled());
+
+        // A dark panel in the top-right corner with the readout on it.
+        let quads = server.overlays();
+        assert_eq!(quads.len(), 1);
+        assert_eq!((quads[0].x, quads[0].y), (400.0 - 150.0 - 8.0, 8.0));
+        let labels = server.overlay_labels();
+        assert_eq!(labels.len(), 3);
+        assert!(labels[0].text.ends_with(" fps"));
+        assert!(labels[0].x > quads[0].x);
+
+        let resp = send_request(
+            &mut stream,
+            r#"{"method":"debug.fps_overlay","params":{"on":false},"id":3}"#,
+        );
+        assert_eq!(resp.result.unwrap()["on"], false);
+        assert!(server.overlays().is_empty());
+        assert!(server.overlay_labels().is_empty());
+    }
+
     #[test]
     fn list_returns_all_overlays() {
         let path = test_socket_path();
@@ -1193,6 +5264,338 @@ mod tests {
         assert_eq!(overlays[0].h, 25.0);
     }
 
+    #[test]
+    fn guide_quads_cover_viewport() {
+        let mut overlays = DebugOverlays::default();
+        let color = ColorInfo {
+            r: 1.0,
+            g: 1.0,
+            b: 1.0,
+            a: 1.0,
+        };
+        overlays.baseline_grid = Some(GuideLines {
+            step: 8.0,
+            color: color.clone(),
+        });
+        overlays.spacing = Some(GuideLines { step: 25.0, color });
+
+        let quads = overlays.guide_quads(None, 100.0, 40.0);
+        // Baselines at y = 8, 16, 24, 32; spacing rules at x = 25, 50, 75.
+        assert_eq!(quads.len(), 7);
+        assert_eq!(quads[0].y, 8.0);
+        assert_eq!(quads[0].w, 100.0);
+        assert_eq!(quads[4].x, 25.0);
+        assert_eq!(quads[4].h, 40.0);
+        assert!(quads.iter().all(|q| q.id == GUIDE_OVERLAY_ID));
+
+        overlays.clear();
+        assert!(overlays.guide_quads(None, 100.0, 40.0).is_empty());
+    }
+
+    #[test]
+    fn margin_guides_are_scoped_to_window() {
+        let mut overlays = DebugOverlays::default();
+        overlays.margins.insert(
+            Some(7),
+            MarginGuides {
+                insets: EdgesInfo {
+                    top: 10.0,
+                    right: 20.0,
+                    bottom: 30.0,
+                    left: 40.0,
+                },
+                color: ColorInfo {
+                    r: 0.0,
+                    g: 1.0,
+                    b: 0.0,
+                    a: 1.0,
+                },
+            },
+        );
+
+        assert!(overlays.guide_quads(Some(8), 200.0, 100.0).is_empty());
+
+        let quads = overlays.guide_quads(Some(7), 200.0, 100.0);
+        assert_eq!(quads.len(), 4);
+        assert_eq!(quads[0].y, 10.0);
+        assert_eq!(quads[1].y, 69.0);
+        assert_eq!(quads[2].x, 40.0);
+        assert_eq!(quads[3].x, 179.0);
+    }
+
+    #[test]
+    fn show_guides_sets_and_hides_margins() {
+        let path = test_socket_path();
+        let server = DebugServer::with_path(path.clone()).expect("server should start");
+        server.set_window_id(3);
+        server.update_scene(SceneSnapshot::from_scene(
+            &motif_core::Scene::new(),
+            (400.0, 300.0),
+            1.0,
+        ));
+        std::thread::sleep(std::time::Duration::from_millis(100));
+
+        let mut stream = UnixStream::connect(&path).expect("should connect");
+        stream
+            .set_read_timeout(Some(std::time::Duration::from_secs(2)))
+            .unwrap();
+
+        let show = r#"{"method":"debug.show_guides","params":{"insets":[16,24,16,24]},"id":1}"#;
+        let resp = send_request(&mut stream, show);
+        assert!(resp.error.is_none());
+        let result = resp.result.unwrap();
+        assert_eq!(result["window"], 3);
+        assert_eq!(result["insets"]["right"], 24.0);
+        assert_eq!(server.overlays().len(), 4);
+
+        // Guides for another window don't show up in this one.
+        let other = r#"{"method":"debug.show_guides","params":{"insets":8,"window":9},"id":2}"#;
+        send_request(&mut stream, other);
+        assert_eq!(server.overlays().len(), 4);
+
+        let hide = r#"{"method":"debug.show_guides","params":{"insets":0},"id":3}"#;
+        let resp = send_request(&mut stream, hide);
+        assert_eq!(resp.result.unwrap()["enabled"], false);
+        assert!(server.overlays().is_empty());
+
+        let bad = r#"{"method":"debug.show_guides","params":{"insets":"wide"},"id":4}"#;
+        let resp = send_request(&mut stream, bad);
+        assert_eq!(resp.error.unwrap().code, -32602);
+    }
+
+    #[test]
+    fn show_baseline_grid_adds_guides_to_overlays() {
+        let path = test_socket_path();
+        let server = DebugServer::with_path(path.clone()).expect("server should start");
+        server.update_scene(SceneSnapshot::from_scene(
+            &motif_core::Scene::new(),
+            (200.0, 100.0),
+            2.0,
+        ));
+        std::thread::sleep(std::time::Duration::from_millis(100));
+
+        let mut stream = UnixStream::connect(&path).expect("should connect");
+        stream
+            .set_read_timeout(Some(std::time::Duration::from_secs(2)))
+            .unwrap();
+
+        let show = r#"{"method":"debug.show_baseline_grid","params":{"line_height":10},"id":1}"#;
+        let resp = send_request(&mut stream, show);
+        assert!(resp.error.is_none());
+        assert_eq!(resp.result.unwrap()["enabled"], true);
+
+        // Logical viewport is 100x50, so baselines at 10, 20, 30, 40.
+        let overlays = server.overlays();
+        assert_eq!(overlays.len(), 4);
+        assert_eq!(overlays[3].y, 40.0);
+
+        // debug.list only reports user quads.
+        let list = r#"{"method":"debug.list","params":null,"id":2}"#;
+        let resp = send_request(&mut stream, list);
+        assert_eq!(resp.result.unwrap().as_array().unwrap().len(), 0);
+
+        let hide = r#"{"method":"debug.show_baseline_grid","params":{"line_height":0},"id":3}"#;
+        let resp = send_request(&mut stream, hide);
+        assert_eq!(resp.result.unwrap()["enabled"], false);
+        assert!(server.overlays().is_empty());
+    }
+
+    #[test]
+    fn show_density_overlays_cells_and_reports_the_busiest() {
+        use motif_core::{DevicePoint, DeviceRect, DeviceSize, Quad, Scene, Srgba};
+
+        let path = test_socket_path();
+        let server = DebugServer::with_path(path.clone()).expect("server should start");
+        let mut scene = Scene::new();
+        for (x, count) in [(0.0, 1), (60.0, 3)] {
+            for _ in 0..count {
+                scene.push_quad(Quad::new(
+                    DeviceRect::new(DevicePoint::new(x, 0.0), DeviceSize::new(10.0, 10.0)),
+                    Srgba::new(1.0, 1.0, 1.0, 1.0),
+                ));
+            }
+        }
+        server.update_scene(SceneSnapshot::from_scene(&scene, (100.0, 50.0), 1.0));
+        std::thread::sleep(std::time::Duration::from_millis(100));
+
+        let mut stream = UnixStream::connect(&path).expect("should connect");
+        stream
+            .set_read_timeout(Some(std::time::Duration::from_secs(2)))
+            .unwrap();
+
+        let show = r#"{"method":"debug.show_density","params":{"cell":50},"id":1}"#;
+        let result = send_request(&mut stream, show).result.unwrap();
+        assert_eq!(result["busiest"][0]["x"], 50.0);
+        assert_eq!(result["busiest"][0]["quads"], 3);
+        assert_eq!(result["busiest"][1]["quads"], 1);
+
+        let overlays = server.overlays();
+        assert_eq!(overlays.len(), 2);
+        assert!(overlays[1].color.r > overlays[0].color.r);
+        assert_eq!(overlays[1].w, 50.0);
+
+        let hide = r#"{"method":"debug.show_density","params":{"cell":0},"id":2}"#;
+        assert_eq!(
+            send_request(&mut stream, hide).result.unwrap()["enabled"],
+            false
+        );
+        assert!(server.overlays().is_empty());
+
+        let bad = r#"{"method":"debug.show_density","params":{"cell":0.5},"id":3}"#;
+        assert_eq!(send_request(&mut stream, bad).error.unwrap().code, -32602);
+    }
+
+    #[test]
+    fn show_spacing_rejects_missing_or_tiny_scale() {
+        let path = test_socket_path();
+        let _server = DebugServer::with_path(path.clone()).expect("server should start");
+        std::thread::sleep(std::time::Duration::from_millis(100));
+
+        let mut stream = UnixStream::connect(&path).expect("should connect");
+        stream
+            .set_read_timeout(Some(std::time::Duration::from_secs(2)))
+            .unwrap();
+
+        let missing = r#"{"method":"debug.show_spacing","params":null,"id":1}"#;
+        let resp = send_request(&mut stream, missing);
+        assert_eq!(resp.error.unwrap().code, -32602);
+
+        let tiny = r#"{"method":"debug.show_spacing","params":{"scale":0.25},"id":2}"#;
+        let resp = send_request(&mut stream, tiny);
+        assert_eq!(resp.error.unwrap().code, -32602);
+
+        let ok = r#"{"method":"debug.show_spacing","params":{"scale":4},"id":3}"#;
+        let resp = send_request(&mut stream, ok);
+        assert!(resp.error.is_none());
+        assert_eq!(resp.result.unwrap()["scale"], 4.0);
+    }
+
+    #[test]
+    fn overlay_changes_wake_the_app() {
+        let path = test_socket_path();
+        let server = DebugServer::with_path(path.clone()).expect("server should start");
+        let (redraw_tx, redraw_rx) = std::sync::mpsc::channel();
+        let redraw_tx = Mutex::new(redraw_tx);
+        server.enable_frame_capture(move || {
+            let _ = redraw_tx.lock().unwrap().send(());
+        });
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        let mut stream = UnixStream::connect(&path).expect("should connect");
+        stream
+            .set_read_timeout(Some(std::time::Duration::from_secs(2)))
+            .unwrap();
+
+        for request in [
+            r#"{"method":"debug.draw_quad","params":{"x":1,"y":2},"id":1}"#,
+            r#"{"method":"debug.draw_arrow","params":{"from":[0,0],"to":[5,5]},"id":2}"#,
+            r#"{"method":"debug.remove","params":{"id":0},"id":3}"#,
+            r#"{"method":"debug.show_guides","params":{"insets":8},"id":4}"#,
+            r#"{"method":"debug.show_baseline_grid","params":{"line_height":4},"id":5}"#,
+            r#"{"method":"debug.show_spacing","params":{"scale":8},"id":6}"#,
+            r#"{"method":"debug.show_density","params":{"cell":0},"id":7}"#,
+            r#"{"method":"debug.inspect_text","params":{"index":null},"id":8}"#,
+            r#"{"method":"debug.clear","id":9}"#,
+        ] {
+            let resp = send_request(&mut stream, request);
+            assert!(resp.error.is_none(), "{request}");
+            assert!(
+                redraw_rx.try_recv().is_ok(),
+                "{request} should wake the app"
+            );
+        }
+    }
+
+    #[test]
+    fn inspect_text_overlays_line_box_baseline_and_ticks() {
+        use motif_core::{DevicePoint, PositionedGlyph, Scene, Srgba, TextRun};
+
+        fn scene() -> Scene {
+            let mut scene = Scene::new();
+            let run = TextRun::new(
+                DevicePoint::new(20.0, 40.0),
+                Srgba::new(1.0, 1.0, 1.0, 1.0),
+                16.0,
+                motif_core::FontData::new(linebender_resource_handle::Blob::from(vec![0u8; 4]), 0),
+            )
+            .with_line_metrics(24.0, 8.0);
+            scene.push_text_run(run);
+            for (id, x) in [(1, 0.0), (2, 10.0)] {
+                scene.push_positioned_glyph(&PositionedGlyph {
+                    id,
+                    x,
+                    y: 0.0,
+                    advance: 10.0,
+                });
+            }
+            scene
+        }
+
+        let path = test_socket_path();
+        let server = DebugServer::with_path(path.clone()).expect("server should start");
+        server.update_scene(SceneSnapshot::from_scene(&scene(), (800.0, 600.0), 2.0));
+        std::thread::sleep(std::time::Duration::from_millis(100));
+
+        let mut stream = UnixStream::connect(&path).expect("should connect");
+        stream
+            .set_read_timeout(Some(std::time::Duration::from_secs(2)))
+            .unwrap();
+
+        let out_of_range = r#"{"method":"debug.inspect_text","params":{"index":3},"id":1}"#;
+        let resp = send_request(&mut stream, out_of_range);
+        assert_eq!(resp.error.unwrap().code, -32602);
+
+        // Line box and baseline come from the plain snapshot; the glyph
+        // ticks wait for a frame that captured the run's glyphs.
+        let inspect = r#"{"method":"debug.inspect_text","params":{"index":0},"id":2}"#;
+        let resp = send_request(&mut stream, inspect);
+        assert_eq!(resp.error.unwrap().code, -32000, "capture isn't enabled");
+        assert_eq!(server.overlays().len(), 2);
+
+        let (redraw_tx, redraw_rx) = std::sync::mpsc::channel();
+        let redraw_tx = Mutex::new(redraw_tx);
+        server.enable_frame_capture(move || {
+            let _ = redraw_tx.lock().unwrap().send(());
+        });
+        let (resp, redraw_rx) = std::thread::scope(|scope| {
+            let server = &server;
+            let frame = scope.spawn(move || {
+                redraw_rx
+                    .recv_timeout(std::time::Duration::from_secs(2))
+                    .expect("server should request a redraw");
+                let scene = scene();
+                let mut snapshot = SceneSnapshot::from_scene(&scene, (800.0, 600.0), 2.0);
+                snapshot.capture_text_glyphs(&scene, server.inspected_text_run().unwrap());
+                server.update_scene(snapshot);
+                redraw_rx
+            });
+            let resp = send_request(&mut stream, inspect);
+            (resp, frame.join().unwrap())
+        });
+        assert!(resp.error.is_none());
+        let result = resp.result.unwrap();
+        assert_eq!(result["lines"].as_array().unwrap().len(), 1);
+        assert_eq!(result["glyphs"][1]["advance"], 10.0);
+
+        // Already captured: answered from the current snapshot, since no
+        // frame comes to the redraw it still asks for.
+        let resp = send_request(&mut stream, inspect);
+        assert_eq!(resp.result.unwrap()["glyphs"][0]["x"], 0.0);
+        assert!(redraw_rx.try_recv().is_ok());
+
+        // Line box + baseline + two pen ticks + end tick, in logical pixels.
+        let overlays = server.overlays();
+        assert_eq!(overlays.len(), 5);
+        assert_eq!(overlays[0].x, 10.0);
+        assert_eq!(overlays[0].y, 8.0);
+        assert_eq!(overlays[0].h, 16.0);
+        assert_eq!(overlays[1].y, 20.0);
+        assert_eq!(overlays[4].x, 20.0);
+
+        let stop = r#"{"method":"debug.inspect_text","params":{"index":null},"id":3}"#;
+        send_request(&mut stream, stop);
+        assert!(server.overlays().is_empty());
+    }
+
     #[test]
     fn server_responds_to_input_state_without_snapshot() {
         let path = test_socket_path();
@@ -1,7 +1,10 @@
 //! Scene snapshot: a serializable capture of the current scene state.
 
 use motif_core::input::{InputState, MouseButton};
-use motif_core::Scene;
+use motif_core::{
+    AccessId, AccessTree, Corners, DevicePoint, DeviceRect, DeviceSize, Edges, GlyphInstance, Quad,
+    Scene, Srgba, TextRun,
+};
 use serde::Serialize;
 
 /// A debug overlay quad injected via the debug CLI.
@@ -20,6 +23,208 @@ pub struct OverlayQuad {
     pub corner_radius: f32,
 }
 
+impl OverlayQuad {
+    /// The scene quad that draws this overlay, at `scale` device pixels per
+    /// logical pixel.
+    pub fn to_quad(&self, scale: f32) -> Quad {
+        let mut quad = Quad::new(
+            DeviceRect::new(
+                DevicePoint::new(self.x * scale, self.y * scale),
+                DeviceSize::new(self.w * scale, self.h * scale),
+            ),
+            self.color.to_srgba(),
+        );
+        quad.border_color = self.border_color.to_srgba();
+        quad.border_widths = Edges::all(self.border_width * scale);
+        quad.corner_radii = Corners::all(self.corner_radius * scale);
+        quad
+    }
+}
+
+/// An override for one scene quad, queued with `scene.patch` and applied by
+/// the app to each frame it builds until cleared. Offsets are in device
+/// pixels, like the bounds `scene.quads` reports.
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct QuadPatch {
+    /// Index of the quad in paint order, as listed by `scene.quads`.
+    pub quad: usize,
+    pub dx: f32,
+    pub dy: f32,
+    /// Replaces the quad's background (and any gradient).
+    pub color: Option<ColorInfo>,
+    /// Draws the quad fully transparent, keeping later indices stable.
+    pub hidden: bool,
+}
+
+impl QuadPatch {
+    /// Apply the override to `quad`.
+    pub fn apply(&self, quad: &mut Quad) {
+        quad.bounds.origin.x += self.dx;
+        quad.bounds.origin.y += self.dy;
+        if let Some(color) = &self.color {
+            quad.background = color.to_srgba();
+            quad.gradient = None;
+        }
+        if self.hidden {
+            let clear = Srgba::new(0.0, 0.0, 0.0, 0.0);
+            quad.background = clear;
+            quad.border_color = clear;
+            quad.gradient = None;
+            quad.shadow = None;
+        }
+    }
+
+    /// Apply each patch to the quad it names in `scene`, skipping patches
+    /// for quads this frame doesn't have. Call after building the scene and
+    /// before rendering it.
+    pub fn apply_all(patches: &[QuadPatch], scene: &mut Scene) {
+        let quads = scene.quads_mut();
+        for patch in patches {
+            if let Some(quad) = quads.get_mut(patch.quad) {
+                patch.apply(quad);
+            }
+        }
+    }
+}
+
+/// A debug overlay arrow injected via the debug CLI (`debug.draw_arrow`),
+/// in logical pixels.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct OverlayArrow {
+    pub id: u64,
+    pub from: PointInfo,
+    pub to: PointInfo,
+    pub color: ColorInfo,
+    /// Stroke width of the shaft and head.
+    pub width: f32,
+    /// Text drawn beside the tail, if the app draws overlay labels.
+    pub label: Option<String>,
+}
+
+/// Text an overlay asks to be drawn at a point, in logical pixels. See
+/// `DebugServer::overlay_labels`.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct OverlayLabel {
+    pub x: f32,
+    pub y: f32,
+    pub text: String,
+    pub color: ColorInfo,
+}
+
+impl OverlayArrow {
+    /// The quads that draw this arrow: its shaft, then the two strokes of
+    /// its head.
+    ///
+    /// Diagonal strokes are built as a staircase of quads, one per stroke
+    /// width along the shorter axis, so straight arrows cost one quad each.
+    pub fn to_quads(&self) -> Vec<OverlayQuad> {
+        let (dx, dy) = (self.to.x - self.from.x, self.to.y - self.from.y);
+        let length = dx.hypot(dy);
+        let mut quads = self.stroke_quads(self.from.x, self.from.y, self.to.x, self.to.y);
+        if length == 0.0 {
+            return quads;
+        }
+
+        let head = (self.width * 4.0).max(8.0).min(length);
+        let back = (-dx / length, -dy / length);
+        let (sin, cos) = 25f32.to_radians().sin_cos();
+        for side in [-1.0, 1.0] {
+            let x = back.0 * cos - side * back.1 * sin;
+            let y = side * back.0 * sin + back.1 * cos;
+            quads.extend(self.stroke_quads(
+                self.to.x,
+                self.to.y,
+                self.to.x + x * head,
+                self.to.y + y * head,
+            ));
+        }
+        quads
+    }
+
+    /// Where this arrow's label goes: just past the tail, away from the head.
+    pub fn label(&self) -> Option<OverlayLabel> {
+        let text = self.label.clone()?;
+        let (dx, dy) = (self.to.x - self.from.x, self.to.y - self.from.y);
+        let length = dx.hypot(dy).max(1.0);
+        let gap = self.width + 4.0;
+        Some(OverlayLabel {
+            x: self.from.x - dx / length * gap,
+            y: self.from.y - dy / length * gap,
+            text,
+            color: self.color.clone(),
+        })
+    }
+
+    fn stroke_quads(&self, x0: f32, y0: f32, x1: f32, y1: f32) -> Vec<OverlayQuad> {
+        let (dx, dy) = (x1 - x0, y1 - y0);
+        let width = self.width.max(0.5);
+        let steps = (dx.abs().min(dy.abs()) / width).ceil().max(1.0) as usize;
+        (0..steps)
+            .map(|i| {
+                let (t0, t1) = (i as f32 / steps as f32, (i + 1) as f32 / steps as f32);
+                let (ax, ay) = (x0 + dx * t0, y0 + dy * t0);
+                let (bx, by) = (x0 + dx * t1, y0 + dy * t1);
+                OverlayQuad {
+                    id: self.id,
+                    x: ax.min(bx) - width / 2.0,
+                    y: ay.min(by) - width / 2.0,
+                    w: (bx - ax).abs() + width,
+                    h: (by - ay).abs() + width,
+                    color: self.color.clone(),
+                    border_color: ColorInfo {
+                        r: 0.0,
+                        g: 0.0,
+                        b: 0.0,
+                        a: 0.0,
+                    },
+                    border_width: 0.0,
+                    corner_radius: 0.0,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Serializable summary of an accessibility node, as pushed with
+/// `DebugServer::update_access_tree`.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct AccessNodeInfo {
+    pub id: u64,
+    pub role: String,
+    pub name: String,
+    /// Window-relative device pixels, as AccessKit expects.
+    pub bounds: Option<BoundsInfo>,
+}
+
+impl AccessNodeInfo {
+    /// Every node reachable from the tree's root, in tree order.
+    pub fn collect(tree: &AccessTree) -> Vec<Self> {
+        fn visit(tree: &AccessTree, id: AccessId, nodes: &mut Vec<AccessNodeInfo>) {
+            let Some(node) = tree.get(id) else {
+                return;
+            };
+            nodes.push(AccessNodeInfo {
+                id: node.id.0,
+                role: format!("{:?}", node.role),
+                name: node.name.clone(),
+                bounds: node.bounds.map(|b| BoundsInfo {
+                    x: b.origin.x,
+                    y: b.origin.y,
+                    w: b.size.width,
+                    h: b.size.height,
+                }),
+            });
+            for &child in &node.children {
+                visit(tree, child, nodes);
+            }
+        }
+
+        let mut nodes = Vec::new();
+        visit(tree, tree.root_id(), &mut nodes);
+        nodes
+    }
+}
+
 /// Serializable info about a single quad.
 #[derive(Debug, Clone, Serialize, PartialEq)]
 pub struct QuadInfo {
@@ -50,6 +255,12 @@ pub struct ColorInfo {
     pub a: f32,
 }
 
+impl ColorInfo {
+    pub fn to_srgba(&self) -> Srgba {
+        Srgba::new(self.r, self.g, self.b, self.a)
+    }
+}
+
 /// Serializable edge values (top, right, bottom, left).
 #[derive(Debug, Clone, Serialize, PartialEq)]
 pub struct EdgesInfo {
@@ -76,6 +287,71 @@ pub struct TextRunInfo {
     pub font_size: f32,
     pub glyph_count: usize,
     pub color: ColorInfo,
+    /// Line ascent from the layout (device pixels).
+    pub ascent: f32,
+    /// Line descent from the layout (device pixels).
+    pub descent: f32,
+    /// Line boxes, one per baseline.
+    pub lines: Vec<TextLineInfo>,
+}
+
+/// Serializable glyph position within a text run (device pixels, relative
+/// to the run origin).
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct GlyphInfo {
+    pub id: u32,
+    pub x: f32,
+    pub y: f32,
+    pub advance: f32,
+}
+
+/// Glyph positions of one text run, captured only for the run
+/// `debug.inspect_text` is looking at.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TextRunGlyphs {
+    /// Index of the run in [`SceneSnapshot::text_runs`].
+    pub index: usize,
+    pub glyphs: Vec<GlyphInfo>,
+}
+
+/// A laid-out line within a text run, in device pixels.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TextLineInfo {
+    /// Left edge of the first glyph's pen position.
+    pub x: f32,
+    /// Baseline y position.
+    pub baseline: f32,
+    /// Sum of glyph advances on the line.
+    pub width: f32,
+    /// Index range of glyphs on this line.
+    pub glyph_start: usize,
+    pub glyph_end: usize,
+}
+
+/// Split a run's glyphs into lines by baseline.
+///
+/// Glyphs sharing a y offset are on the same line; positions are absolute
+/// device pixels.
+fn line_boxes(run: &TextRun, glyphs: &[GlyphInstance]) -> Vec<TextLineInfo> {
+    let mut lines: Vec<TextLineInfo> = Vec::new();
+    for (i, glyph) in glyphs.iter().enumerate() {
+        let baseline = run.origin.y + glyph.y;
+        let x = run.origin.x + glyph.x;
+        match lines.last_mut() {
+            Some(line) if line.baseline == baseline => {
+                line.width = x + glyph.advance - line.x;
+                line.glyph_end = i + 1;
+            }
+            _ => lines.push(TextLineInfo {
+                x,
+                baseline,
+                width: glyph.advance,
+                glyph_start: i,
+                glyph_end: i + 1,
+            }),
+        }
+    }
+    lines
 }
 
 /// A serializable snapshot of the current input state.
@@ -165,6 +441,128 @@ impl InputStateSnapshot {
     }
 }
 
+/// Primitives overlapping one cell of a density grid (`debug.show_density`).
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct DensityCell {
+    /// Top-left corner in logical pixels.
+    pub x: f32,
+    pub y: f32,
+    pub quads: usize,
+    pub text_runs: usize,
+}
+
+impl DensityCell {
+    pub fn count(&self) -> usize {
+        self.quads + self.text_runs
+    }
+}
+
+/// The topmost primitive at a point (`pixel.color`), by its index in
+/// `scene.quads` or `scene.text_runs`.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(tag = "kind", content = "index", rename_all = "snake_case")]
+pub enum Primitive {
+    Quad(usize),
+    TextRun(usize),
+}
+
+impl QuadInfo {
+    /// The color this quad paints at device pixel `(x, y)`: its border
+    /// color within a border, its fill inside, `None` outside its rounded
+    /// bounds or clip. Gradients and shadows aren't captured in snapshots
+    /// and so are ignored.
+    pub fn color_at(&self, x: f32, y: f32) -> Option<&ColorInfo> {
+        let b = &self.bounds;
+        let inside = |r: &BoundsInfo| x >= r.x && x < r.x + r.w && y >= r.y && y < r.y + r.h;
+        if !inside(b) || self.clip_bounds.as_ref().is_some_and(|clip| !inside(clip)) {
+            return None;
+        }
+        // Past a rounded corner: beyond its circle's center on both axes,
+        // and farther from it than the radius.
+        let (right, bottom) = (b.x + b.w, b.y + b.h);
+        let r = &self.corner_radii;
+        for (radius, left, top) in [
+            (r.top_left, true, true),
+            (r.top_right, false, true),
+            (r.bottom_right, false, false),
+            (r.bottom_left, true, false),
+        ] {
+            let cx = if left { b.x + radius } else { right - radius };
+            let cy = if top { b.y + radius } else { bottom - radius };
+            let past_center = (x < cx) == left && (y < cy) == top;
+            if radius > 0.0 && past_center && (x - cx).hypot(y - cy) > radius {
+                return None;
+            }
+        }
+        let w = &self.border_widths;
+        let in_border =
+            x < b.x + w.left || x >= right - w.right || y < b.y + w.top || y >= bottom - w.bottom;
+        Some(if in_border {
+            &self.border_color
+        } else {
+            &self.color
+        })
+    }
+}
+
+/// Where a scene's primitives fall relative to its viewport
+/// (`scene.bounds`). Lengths are in device pixels, like `scene.quads`.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SceneBounds {
+    /// Union of every quad and text line box, or `None` for an empty scene.
+    pub bounds: Option<BoundsInfo>,
+    /// Percentage of the viewport's area covered by at least one primitive.
+    pub coverage_percent: f32,
+    /// Area of `bounds` relative to the viewport's; above 1 means the scene
+    /// paints beyond the window.
+    pub extent_ratio: f32,
+    /// Quads entirely outside the viewport.
+    pub outside_quads: usize,
+    /// Text runs whose every line lies outside the viewport.
+    pub outside_text_runs: usize,
+}
+
+/// Area covered by the union of `rects` (`[x0, y0, x1, y1]`), found by
+/// sweeping across the distinct x edges and merging the y spans in each slab.
+fn union_area(rects: &[[f32; 4]]) -> f64 {
+    let mut xs: Vec<f32> = rects.iter().flat_map(|r| [r[0], r[2]]).collect();
+    xs.sort_by(f32::total_cmp);
+    xs.dedup();
+
+    let mut spans = Vec::new();
+    let mut area = 0.0;
+    for slab in xs.windows(2) {
+        let (left, right) = (slab[0], slab[1]);
+        spans.clear();
+        spans.extend(
+            rects
+                .iter()
+                .filter(|r| r[0] <= left && r[2] >= right)
+                .map(|r| (r[1], r[3])),
+        );
+        spans.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut covered = 0.0;
+        let mut current: Option<(f32, f32)> = None;
+        for &(top, bottom) in &spans {
+            match &mut current {
+                Some((_, end)) if top <= *end => *end = end.max(bottom),
+                _ => {
+                    if let Some((start, end)) = current {
+                        covered += f64::from(end - start);
+                    }
+                    current = Some((top, bottom));
+                }
+            }
+        }
+        if let Some((start, end)) = current {
+            covered += f64::from(end - start);
+        }
+        area += covered * f64::from(right - left);
+    }
+    area
+}
+
 /// A serializable snapshot of the current scene state.
 #[derive(Debug, Clone, Serialize)]
 pub struct SceneSnapshot {
@@ -174,11 +572,23 @@ pub struct SceneSnapshot {
     pub quad_count: usize,
     pub viewport_size: (f32, f32),
     pub scale_factor: f32,
+    /// Glyph positions of a single run, set with
+    /// [`capture_text_glyphs`](Self::capture_text_glyphs). Every other run
+    /// only carries its line boxes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text_glyphs: Option<TextRunGlyphs>,
 }
 
 impl SceneSnapshot {
     /// Create a snapshot from a scene and viewport metadata.
     pub fn from_scene(scene: &Scene, viewport_size: (f32, f32), scale_factor: f32) -> Self {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!(
+            "scene_snapshot",
+            quads = scene.quad_count(),
+            text_runs = scene.text_runs().len()
+        )
+        .entered();
         let quads: Vec<QuadInfo> = scene
             .quads()
             .iter()
@@ -246,6 +656,9 @@ impl SceneSnapshot {
                         b: c.blue,
                         a: c.alpha,
                     },
+                    ascent: tr.ascent,
+                    descent: tr.descent,
+                    lines: line_boxes(tr, scene.glyphs(tr)),
                 }
             })
             .collect();
@@ -257,6 +670,166 @@ impl SceneSnapshot {
             text_runs,
             viewport_size,
             scale_factor,
+            text_glyphs: None,
+        }
+    }
+
+    /// Record the glyph positions of text run `index`, or clear them if the
+    /// scene has no such run.
+    pub fn capture_text_glyphs(&mut self, scene: &Scene, index: usize) {
+        self.text_glyphs = scene.text_runs().get(index).map(|run| TextRunGlyphs {
+            index,
+            glyphs: scene
+                .glyphs(run)
+                .iter()
+                .map(|g| GlyphInfo {
+                    id: g.glyph_id,
+                    x: g.x,
+                    y: g.y,
+                    advance: g.advance,
+                })
+                .collect(),
+        });
+    }
+
+    /// Count the quads and text runs overlapping each square of a grid with
+    /// `cell` logical-pixel cells laid over the viewport.
+    ///
+    /// A primitive counts once in every cell it overlaps; text runs are
+    /// measured by their line boxes. Only non-empty cells are returned, in
+    /// row-major order.
+    pub fn density(&self, cell: f32) -> Vec<DensityCell> {
+        let scale = if self.scale_factor > 0.0 {
+            self.scale_factor
+        } else {
+            1.0
+        };
+        let columns = (self.viewport_size.0 / scale / cell).ceil().max(0.0) as usize;
+        let rows = (self.viewport_size.1 / scale / cell).ceil().max(0.0) as usize;
+        let mut counts = vec![(0, 0); columns * rows];
+
+        // Cells covered by a device-pixel rect, as indices into `counts`.
+        let cells = |x: f32, y: f32, w: f32, h: f32| {
+            let span = |start: f32, len: f32, limit: usize| {
+                let first = (start / scale / cell).floor().max(0.0) as usize;
+                let last = ((start + len.max(0.0)) / scale / cell).ceil().max(0.0) as usize;
+                first.min(limit)..last.max(first + 1).min(limit)
+            };
+            let xs = span(x, w, columns);
+            span(y, h, rows).flat_map(move |row| xs.clone().map(move |col| row * columns + col))
+        };
+
+        for quad in &self.quads {
+            let b = &quad.bounds;
+            for index in cells(b.x, b.y, b.w, b.h) {
+                counts[index].0 += 1;
+            }
+        }
+        let mut touched = std::collections::HashSet::new();
+        for run in &self.text_runs {
+            touched.clear();
+            for line in &run.lines {
+                let top = line.baseline - run.ascent;
+                touched.extend(cells(line.x, top, line.width, run.ascent + run.descent));
+            }
+            for &index in &touched {
+                counts[index].1 += 1;
+            }
+        }
+
+        counts
+            .into_iter()
+            .enumerate()
+            .filter(|(_, (quads, text_runs))| quads + text_runs > 0)
+            .map(|(index, (quads, text_runs))| DensityCell {
+                x: (index % columns) as f32 * cell,
+                y: (index / columns) as f32 * cell,
+                quads,
+                text_runs,
+            })
+            .collect()
+    }
+
+    /// The union bounding box of every primitive, how much of the viewport
+    /// they cover, and how many lie entirely outside it. Text runs are
+    /// measured by their line boxes.
+    pub fn bounds(&self) -> SceneBounds {
+        let (width, height) = self.viewport_size;
+        let outside = |r: &[f32; 4]| r[2] <= 0.0 || r[3] <= 0.0 || r[0] >= width || r[1] >= height;
+
+        let mut rects: Vec<[f32; 4]> = Vec::new();
+        let mut outside_quads = 0;
+        for quad in &self.quads {
+            let b = &quad.bounds;
+            let rect = [b.x, b.y, b.x + b.w.max(0.0), b.y + b.h.max(0.0)];
+            if outside(&rect) {
+                outside_quads += 1;
+            }
+            rects.push(rect);
+        }
+        let mut outside_text_runs = 0;
+        for run in &self.text_runs {
+            let lines: Vec<[f32; 4]> = run
+                .lines
+                .iter()
+                .map(|line| {
+                    let top = line.baseline - run.ascent;
+                    [
+                        line.x,
+                        top,
+                        line.x + line.width,
+                        line.baseline + run.descent,
+                    ]
+                })
+                .collect();
+            if !lines.is_empty() && lines.iter().all(outside) {
+                outside_text_runs += 1;
+            }
+            rects.extend(lines);
+        }
+
+        let union = rects.iter().copied().reduce(|a, b| {
+            [
+                a[0].min(b[0]),
+                a[1].min(b[1]),
+                a[2].max(b[2]),
+                a[3].max(b[3]),
+            ]
+        });
+        let viewport_area = f64::from(width) * f64::from(height);
+        let (coverage_percent, extent_ratio) = if viewport_area > 0.0 {
+            let visible: Vec<[f32; 4]> = rects
+                .iter()
+                .filter(|r| !outside(r))
+                .map(|r| {
+                    [
+                        r[0].max(0.0),
+                        r[1].max(0.0),
+                        r[2].min(width),
+                        r[3].min(height),
+                    ]
+                })
+                .collect();
+            let extent = union.map_or(0.0, |u| f64::from(u[2] - u[0]) * f64::from(u[3] - u[1]));
+            (
+                (union_area(&visible) / viewport_area * 100.0) as f32,
+                (extent / viewport_area) as f32,
+            )
+        } else {
+            (0.0, 0.0)
+        };
+
+        SceneBounds {
+            bounds: union.map(|u| BoundsInfo {
+                x: u[0],
+                y: u[1],
+                w: u[2] - u[0],
+                h: u[3] - u[1],
+            }),
+            coverage_percent,
+            extent_ratio,
+            outside_quads,
+            outside_text_runs,
         }
     }
 
@@ -270,6 +843,52 @@ impl SceneSnapshot {
         })
     }
 
+    /// The topmost primitive covering device pixel `(x, y)`. Text runs draw
+    /// above quads and are hit anywhere in their line boxes.
+    pub fn primitive_at(&self, x: f32, y: f32) -> Option<Primitive> {
+        let text_run = self.text_runs.iter().rposition(|run| {
+            run.lines.iter().any(|line| {
+                x >= line.x
+                    && x < line.x + line.width
+                    && y >= line.baseline - run.ascent
+                    && y < line.baseline + run.descent
+            })
+        });
+        if let Some(index) = text_run {
+            return Some(Primitive::TextRun(index));
+        }
+        self.quads
+            .iter()
+            .rposition(|quad| quad.color_at(x, y).is_some())
+            .map(Primitive::Quad)
+    }
+
+    /// Approximate the color at device pixel `(x, y)` by blending the quads
+    /// covering it over a transparent background. Text, paths, images,
+    /// gradients and shadows aren't in the snapshot, so aren't drawn.
+    pub fn sample(&self, x: f32, y: f32) -> ColorInfo {
+        let mut out = ColorInfo {
+            r: 0.0,
+            g: 0.0,
+            b: 0.0,
+            a: 0.0,
+        };
+        for src in self.quads.iter().filter_map(|quad| quad.color_at(x, y)) {
+            let a = src.a + out.a * (1.0 - src.a);
+            if a <= 0.0 {
+                continue;
+            }
+            let blend = |s: f32, d: f32| (s * src.a + d * out.a * (1.0 - src.a)) / a;
+            out = ColorInfo {
+                r: blend(src.r, out.r),
+                g: blend(src.g, out.g),
+                b: blend(src.b, out.b),
+                a,
+            };
+        }
+        out
+    }
+
     /// Return quads as a JSON array (for the `scene.quads` command).
     pub fn quads_json(&self) -> serde_json::Value {
         let quads: Vec<serde_json::Value> = self
@@ -531,16 +1150,16 @@ mod tests {
     #[test]
     fn snapshot_captures_text_run_info() {
         let mut scene = Scene::new();
-        let mut run = TextRun::new(
+        let run = TextRun::new(
             DevicePoint::new(50.0, 100.0),
             Srgba::new(0.0, 0.0, 0.0, 1.0),
             16.0,
             dummy_font(),
         );
-        run.push_glyph(1, 0.0, 0.0);
-        run.push_glyph(2, 10.0, 0.0);
-        run.push_glyph(3, 20.0, 0.0);
         scene.push_text_run(run);
+        scene.push_glyph(1, 0.0, 0.0);
+        scene.push_glyph(2, 10.0, 0.0);
+        scene.push_glyph(3, 20.0, 0.0);
 
         let snap = SceneSnapshot::from_scene(&scene, (800.0, 600.0), 1.0);
 
@@ -554,6 +1173,49 @@ mod tests {
         assert_eq!(tri.color.a, 1.0);
     }
 
+    #[test]
+    fn text_run_info_splits_lines_by_baseline() {
+        let mut scene = Scene::new();
+        let run = TextRun::new(
+            DevicePoint::new(10.0, 100.0),
+            Srgba::new(1.0, 1.0, 1.0, 1.0),
+            16.0,
+            dummy_font(),
+        )
+        .with_line_metrics(12.0, 4.0);
+        scene.push_text_run(run);
+        for (id, x, y) in [(1, 0.0, 0.0), (2, 8.0, 0.0), (3, 0.0, 20.0)] {
+            scene.push_positioned_glyph(&motif_core::PositionedGlyph {
+                id,
+                x,
+                y,
+                advance: 8.0,
+            });
+        }
+
+        let mut snap = SceneSnapshot::from_scene(&scene, (800.0, 600.0), 1.0);
+        let tri = &snap.text_runs[0];
+        assert_eq!(tri.ascent, 12.0);
+        assert_eq!(tri.glyph_count, 3);
+
+        let lines = &tri.lines;
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].baseline, 100.0);
+        assert_eq!(lines[0].width, 16.0);
+        assert_eq!((lines[0].glyph_start, lines[0].glyph_end), (0, 2));
+        assert_eq!(lines[1].baseline, 120.0);
+        assert_eq!(lines[1].x, 10.0);
+
+        assert!(snap.text_glyphs.is_none(), "glyphs are captured on request");
+        snap.capture_text_glyphs(&scene, 0);
+        let captured = snap.text_glyphs.as_ref().unwrap();
+        assert_eq!(captured.index, 0);
+        assert_eq!(captured.glyphs.len(), 3);
+        assert_eq!(captured.glyphs[1].x, 8.0);
+        snap.capture_text_glyphs(&scene, 1);
+        assert!(snap.text_glyphs.is_none());
+    }
+
     #[test]
     fn quads_json_returns_array() {
         let mut scene = Scene::new();
@@ -613,15 +1275,15 @@ mod tests {
     #[test]
     fn text_runs_json_returns_array() {
         let mut scene = Scene::new();
-        let mut run = TextRun::new(
+        let run = TextRun::new(
             DevicePoint::new(10.0, 20.0),
             Srgba::new(0.2, 0.3, 0.4, 1.0),
             14.0,
             dummy_font(),
         );
-        run.push_glyph(1, 0.0, 0.0);
-        run.push_glyph(2, 8.0, 0.0);
         scene.push_text_run(run);
+        scene.push_glyph(1, 0.0, 0.0);
+        scene.push_glyph(2, 8.0, 0.0);
 
         let snap = SceneSnapshot::from_scene(&scene, (800.0, 600.0), 1.0);
         let json = snap.text_runs_json();
@@ -634,6 +1296,162 @@ mod tests {
         assert_eq!(arr[0]["glyph_count"], 2);
     }
 
+    fn arrow(from: (f32, f32), to: (f32, f32)) -> OverlayArrow {
+        OverlayArrow {
+            id: 3,
+            from: PointInfo {
+                x: from.0,
+                y: from.1,
+            },
+            to: PointInfo { x: to.0, y: to.1 },
+            color: ColorInfo {
+                r: 1.0,
+                g: 0.0,
+                b: 0.0,
+                a: 1.0,
+            },
+            width: 2.0,
+            label: None,
+        }
+    }
+
+    #[test]
+    fn straight_arrow_shaft_is_one_quad() {
+        let quads = arrow((10.0, 50.0), (110.0, 50.0)).to_quads();
+        let shaft = &quads[0];
+        assert_eq!(
+            (shaft.x, shaft.y, shaft.w, shaft.h),
+            (9.0, 49.0, 102.0, 2.0)
+        );
+        // Both head strokes end behind the tip, one on each side.
+        let head = &quads[1..];
+        assert!(head.iter().all(|q| q.x + q.w <= 111.0 && q.x >= 100.0));
+        assert!(head.iter().any(|q| q.y < 49.0));
+        assert!(head.iter().any(|q| q.y + q.h > 51.0));
+    }
+
+    #[test]
+    fn diagonal_arrow_is_a_staircase_of_stroke_sized_steps() {
+        let arrow = arrow((0.0, 0.0), (40.0, 40.0));
+        let quads = arrow.to_quads();
+        // 40px along each axis in 2px steps, the last ending at the tip.
+        let shaft = &quads[..20];
+        assert!(shaft.iter().all(|q| q.w == 4.0 && q.h == 4.0));
+        assert_eq!(
+            (shaft[19].x + shaft[19].w, shaft[19].y + shaft[19].h),
+            (41.0, 41.0)
+        );
+        assert!(quads.len() > 20);
+        assert!(arrow.label().is_none());
+    }
+
+    #[test]
+    fn bounds_reports_union_coverage_and_offscreen_primitives() {
+        let mut scene = Scene::new();
+        let quad = |x: f32, y: f32, w: f32, h: f32| {
+            Quad::new(
+                DeviceRect::new(DevicePoint::new(x, y), DeviceSize::new(w, h)),
+                Srgba::new(1.0, 1.0, 1.0, 1.0),
+            )
+        };
+        // Two overlapping quads covering the left half of a 100x100 viewport.
+        scene.push_quad(quad(0.0, 0.0, 50.0, 60.0));
+        scene.push_quad(quad(0.0, 40.0, 50.0, 60.0));
+        // Offscreen, stretching the bounds to 300 wide.
+        scene.push_quad(quad(200.0, 0.0, 100.0, 10.0));
+        let run = TextRun::new(
+            DevicePoint::new(-100.0, 20.0),
+            Srgba::new(0.0, 0.0, 0.0, 1.0),
+            14.0,
+            dummy_font(),
+        );
+        scene.push_text_run(run);
+        scene.push_glyph(1, 0.0, 0.0);
+
+        let bounds = SceneSnapshot::from_scene(&scene, (100.0, 100.0), 1.0).bounds();
+        assert_eq!(bounds.outside_quads, 1);
+        assert_eq!(bounds.outside_text_runs, 1);
+        assert_eq!(bounds.coverage_percent, 50.0);
+        let union = bounds.bounds.unwrap();
+        assert_eq!((union.x, union.w), (-100.0, 400.0));
+        assert!(bounds.extent_ratio >= 4.0);
+
+        let empty = SceneSnapshot::from_scene(&Scene::new(), (100.0, 100.0), 1.0).bounds();
+        assert_eq!(empty.bounds, None);
+        assert_eq!(empty.coverage_percent, 0.0);
+    }
+
+    #[test]
+    fn density_counts_primitives_per_cell() {
+        let mut scene = Scene::new();
+        let quad = |x: f32, y: f32| {
+            Quad::new(
+                DeviceRect::new(DevicePoint::new(x, y), DeviceSize::new(20.0, 20.0)),
+                Srgba::new(1.0, 1.0, 1.0, 1.0),
+            )
+        };
+        scene.push_quad(quad(0.0, 0.0));
+        scene.push_quad(quad(4.0, 4.0));
+        // Straddles the first two columns.
+        scene.push_quad(quad(40.0, 0.0));
+        // Offscreen.
+        scene.push_quad(quad(500.0, 500.0));
+        let run = TextRun::new(
+            DevicePoint::new(10.0, 20.0),
+            Srgba::new(0.0, 0.0, 0.0, 1.0),
+            14.0,
+            dummy_font(),
+        );
+        scene.push_text_run(run);
+        scene.push_glyph(1, 0.0, 0.0);
+
+        // 100x50 logical pixels: a 4x2 grid of 25px cells.
+        let snap = SceneSnapshot::from_scene(&scene, (200.0, 100.0), 2.0);
+        let cells = snap.density(25.0);
+
+        assert_eq!(cells.len(), 2);
+        assert_eq!(
+            cells[0],
+            DensityCell {
+                x: 0.0,
+                y: 0.0,
+                quads: 3,
+                text_runs: 1,
+            }
+        );
+        assert_eq!((cells[1].x, cells[1].y, cells[1].count()), (25.0, 0.0, 1));
+    }
+
+    #[test]
+    fn sample_blends_quads_and_finds_the_topmost_primitive() {
+        let rect = |x: f32, y: f32, w: f32, h: f32| {
+            DeviceRect::new(DevicePoint::new(x, y), DeviceSize::new(w, h))
+        };
+        let mut scene = Scene::new();
+        let mut base = Quad::new(rect(0.0, 0.0, 100.0, 100.0), Srgba::new(0.0, 0.0, 1.0, 1.0));
+        base.border_widths = Edges::all(4.0);
+        base.border_color = Srgba::new(0.0, 1.0, 0.0, 1.0);
+        base.corner_radii = Corners::all(20.0);
+        scene.push_quad(base);
+        scene.push_quad(Quad::new(
+            rect(50.0, 50.0, 50.0, 50.0),
+            Srgba::new(1.0, 0.0, 0.0, 0.5),
+        ));
+        let snap = SceneSnapshot::from_scene(&scene, (200.0, 200.0), 1.0);
+
+        let rgba = |c: ColorInfo| [c.r, c.g, c.b, c.a];
+        assert_eq!(rgba(snap.sample(30.0, 30.0)), [0.0, 0.0, 1.0, 1.0]);
+        assert_eq!(rgba(snap.sample(30.0, 1.0)), [0.0, 1.0, 0.0, 1.0]);
+        // Cut off by the rounded top-left corner.
+        assert_eq!(snap.sample(1.0, 1.0).a, 0.0);
+        assert_eq!(snap.primitive_at(1.0, 1.0), None);
+        // Half-transparent red over blue.
+        assert_eq!(rgba(snap.sample(60.0, 60.0)), [0.5, 0.0, 0.5, 1.0]);
+        assert_eq!(snap.primitive_at(60.0, 60.0), Some(Primitive::Quad(1)));
+        assert_eq!(snap.primitive_at(30.0, 30.0), Some(Primitive::Quad(0)));
+        assert_eq!(snap.sample(150.0, 150.0).a, 0.0);
+    }
+
     #[test]
     fn text_runs_json_empty_scene() {
         let scene = Scene::new();
@@ -1,15 +1,49 @@
 //! IPC client for connecting to a running motif debug server.
 
-use std::io::{self, BufRead, BufReader, Write};
+use std::io::{self, BufReader, Write};
 use std::os::unix::net::UnixStream;
+use std::time::Duration;
 
-use motif_debug::{DebugRequest, DebugResponse};
+use motif_debug::{
+    list_entries, DebugNotification, DebugRequest, DebugResponse, Encoding, RegistryEntry,
+    REGISTRY_DIR,
+};
+
+/// Running apps from the debug registry, most recently started first.
+/// Test servers are left out.
+pub fn registered_apps() -> Vec<RegistryEntry> {
+    list_entries(std::path::Path::new(REGISTRY_DIR))
+        .into_iter()
+        .filter(|entry| !entry.socket_path.to_string_lossy().contains("-test-"))
+        .collect()
+}
+
+/// The registered app whose name matches `name` (case-insensitive), or
+/// whose pid is `name`.
+pub fn find_app(name: &str) -> io::Result<RegistryEntry> {
+    registered_apps()
+        .into_iter()
+        .find(|entry| entry.app_name.eq_ignore_ascii_case(name) || entry.pid.to_string() == name)
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no running motif app named {name:?} (see `motif-debug apps`)"),
+            )
+        })
+}
+
+/// How long a request waits for its response by default.
+pub const READ_TIMEOUT: Duration = Duration::from_secs(5);
 
 /// A client that connects to a motif debug server over a Unix domain socket.
 pub struct DebugClient {
+    path: String,
     reader: BufReader<UnixStream>,
     writer: UnixStream,
     next_id: u64,
+    encoding: Encoding,
+    /// Notifications that arrived while waiting for a response.
+    notifications: Vec<DebugNotification>,
 }
 
 impl DebugClient {
@@ -17,21 +51,34 @@ impl DebugClient {
     pub fn connect(path: &str) -> io::Result<Self> {
         let stream = UnixStream::connect(path)?;
         let read_stream = stream.try_clone()?;
-        read_stream.set_read_timeout(Some(std::time::Duration::from_secs(5)))?;
+        read_stream.set_read_timeout(Some(READ_TIMEOUT))?;
         let reader = BufReader::new(read_stream);
         let writer = stream;
         Ok(Self {
+            path: path.to_string(),
             reader,
             writer,
             next_id: 1,
+            encoding: Encoding::Json,
+            notifications: Vec::new(),
         })
     }
 
-    /// Discover a running motif debug server by scanning for sockets in /tmp.
+    /// Discover a running motif debug server.
     ///
-    /// Connects to the first `motif-debug-*.sock` socket found.
+    /// Connects to the most recently started app in the registry, falling
+    /// back to the first `motif-debug-*.sock` socket in /tmp for servers
+    /// that predate the registry.
     pub fn discover() -> io::Result<Self> {
-        let sockets = Self::find_sockets()?;
+        let mut sockets: Vec<String> = registered_apps()
+            .into_iter()
+            .map(|entry| entry.socket_path.to_string_lossy().into_owned())
+            .collect();
+        for path in Self::find_sockets()? {
+            if !sockets.contains(&path) {
+                sockets.push(path);
+            }
+        }
         if sockets.is_empty() {
             return Err(io::Error::new(
                 io::ErrorKind::NotFound,
@@ -53,6 +100,31 @@ impl DebugClient {
         ))
     }
 
+    /// How long to wait for a response. `None` waits indefinitely, for
+    /// requests like `capture.start` that answer once the app is done.
+    pub fn set_read_timeout(&mut self, timeout: Option<Duration>) -> io::Result<()> {
+        self.reader.get_ref().set_read_timeout(timeout)
+    }
+
+    /// Switch the connection to `encoding` with an `rpc.handshake`. CBOR
+    /// makes large results like `scene.quads` much cheaper to move.
+    pub fn set_encoding(&mut self, encoding: Encoding) -> io::Result<()> {
+        let response = self.send(
+            "rpc.handshake",
+            Some(serde_json::json!({ "encoding": encoding.name() })),
+        )?;
+        if let Some(error) = response.error {
+            return Err(io::Error::new(io::ErrorKind::Unsupported, error.message));
+        }
+        self.encoding = encoding;
+        Ok(())
+    }
+
+    /// The socket path this client is connected to.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
     /// Find all motif debug socket paths in /tmp.
     fn find_sockets() -> io::Result<Vec<String>> {
         let mut sockets = Vec::new();
@@ -87,22 +159,61 @@ impl DebugClient {
         };
         self.next_id += 1;
 
-        let json = serde_json::to_string(&request)
-            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
-        writeln!(self.writer, "{json}")?;
+        let frame = self.encoding.encode(&request)?;
+        self.writer.write_all(&frame)?;
         self.writer.flush()?;
 
-        let mut line = String::new();
-        self.reader.read_line(&mut line)?;
+        // Notifications can arrive ahead of the response; keep them for
+        // `take_notifications`.
+        loop {
+            let frame = self.read_frame()?;
+            match self.encoding.decode::<DebugResponse>(&frame) {
+                Ok(response) => return Ok(response),
+                Err(e) => match self.encoding.decode::<DebugNotification>(&frame) {
+                    Ok(notification) => self.notifications.push(notification),
+                    Err(_) => return Err(e),
+                },
+            }
+        }
+    }
 
-        if line.is_empty() {
-            return Err(io::Error::new(
-                io::ErrorKind::UnexpectedEof,
-                "server closed connection",
-            ));
+    /// Notifications received while waiting for responses, oldest first.
+    pub fn take_notifications(&mut self) -> Vec<DebugNotification> {
+        std::mem::take(&mut self.notifications)
+    }
+
+    /// Wait, without a timeout, for the next notification from the server.
+    /// Pings whenever a read times out, so servers with an idle timeout
+    /// keep the connection open.
+    pub fn next_notification(&mut self) -> io::Result<DebugNotification> {
+        loop {
+            if !self.notifications.is_empty() {
+                return Ok(self.notifications.remove(0));
+            }
+            let frame = match self.read_frame() {
+                Ok(frame) => frame,
+                Err(e)
+                    if matches!(
+                        e.kind(),
+                        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    self.send("ping", None)?;
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            // A stray response belongs to no pending request; skip it.
+            if let Ok(notification) = self.encoding.decode(&frame) {
+                return Ok(notification);
+            }
         }
+    }
 
-        serde_json::from_str(&line).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    fn read_frame(&mut self) -> io::Result<Vec<u8>> {
+        self.encoding
+            .read_frame(&mut self.reader)?
+            .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "server closed connection"))
     }
 }
 
@@ -187,4 +298,64 @@ mod tests {
         // Keep server alive until all assertions pass.
         drop(server);
     }
+
+    #[test]
+    fn cbor_connection_round_trips_requests() {
+        use motif_debug::DebugServer;
+        use std::path::PathBuf;
+
+        let pid = std::process::id();
+        let path = format!("/tmp/motif-debug-test-cli-cbor-{pid}.sock");
+
+        let server = DebugServer::with_path(PathBuf::from(&path)).expect("server should start");
+        std::thread::sleep(std::time::Duration::from_millis(100));
+
+        let mut client = DebugClient::connect(&path).expect("should connect");
+        client
+            .set_encoding(Encoding::Cbor)
+            .expect("server should accept cbor");
+        let resp = client
+            .send("server.info", None)
+            .expect("should get response");
+        assert_eq!(resp.id, 2);
+        assert_eq!(resp.result.unwrap()["pid"], pid);
+
+        drop(server);
+    }
+
+    #[test]
+    fn budget_alerts_arrive_between_responses() {
+        use motif_core::FrameProfile;
+        use motif_debug::DebugServer;
+        use std::path::PathBuf;
+
+        let pid = std::process::id();
+        let path = format!("/tmp/motif-debug-test-cli-budget-{pid}.sock");
+
+        let server = DebugServer::with_path(PathBuf::from(&path)).expect("server should start");
+        std::thread::sleep(std::time::Duration::from_millis(100));
+
+        let mut client = DebugClient::connect(&path).expect("should connect");
+        let resp = client
+            .send("perf.set_budget", Some(serde_json::json!({ "ms": 5 })))
+            .expect("should set budget");
+        assert!(resp.error.is_none());
+
+        let mut slow = FrameProfile::default();
+        slow.total = std::time::Duration::from_millis(20);
+        server.push_frame_profile(slow);
+
+        // Whether the alert lands before or after this response, the
+        // response is matched and the alert is kept.
+        let resp = client
+            .send("scene.profile", None)
+            .expect("should get response");
+        assert_eq!(resp.id, 2);
+        let alert = client.next_notification().expect("should get alert");
+        assert_eq!(alert.method, "perf.budget_exceeded");
+        assert_eq!(alert.params["total_ms"], 20.0);
+        assert!(client.take_notifications().is_empty());
+
+        drop(server);
+    }
 }
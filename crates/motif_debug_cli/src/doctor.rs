@@ -0,0 +1,420 @@
+//! `motif-debug doctor`: one report covering everything the tooling relies on.
+//!
+//! Runs a fixed sequence of checks against a debug server — connection,
+//! handshake, round-trip latency, snapshot freshness, screenshot permissions
+//! and renderer details — so "the tooling isn't working" reports can be
+//! triaged from a single command.
+
+use std::time::{Duration, Instant};
+
+use motif_debug::PROTOCOL_VERSION;
+
+use crate::client::DebugClient;
+
+/// Number of round trips used to measure latency.
+const LATENCY_SAMPLES: u32 = 10;
+/// Average round-trip time above which latency is flagged.
+const LATENCY_WARN_MS: f64 = 50.0;
+/// How long to wait for the frame counter to advance.
+const FRESHNESS_WINDOW: Duration = Duration::from_millis(500);
+
+/// Outcome of a single doctor check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    Warn,
+    Fail,
+}
+
+impl Status {
+    fn label(self) -> &'static str {
+        match self {
+            Status::Ok => "ok",
+            Status::Warn => "warn",
+            Status::Fail => "FAIL",
+        }
+    }
+}
+
+/// A named check with its outcome and a human-readable detail line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: Status,
+    pub detail: String,
+}
+
+impl Check {
+    fn new(name: &'static str, status: Status, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status,
+            detail: detail.into(),
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "name": self.name,
+            "status": self.status.label().to_lowercase(),
+            "detail": self.detail,
+        })
+    }
+}
+
+/// Run every check against the server at `socket`, or the first discovered
+/// server if `None`. Stops early if the connection is lost.
+pub fn run(socket: Option<&str>) -> Vec<Check> {
+    let mut checks = Vec::new();
+
+    let connected = match socket {
+        Some(path) => DebugClient::connect(path),
+        None => DebugClient::discover(),
+    };
+    let mut client = match connected {
+        Ok(client) => {
+            checks.push(Check::new("connection", Status::Ok, client.path()));
+            client
+        }
+        Err(e) => {
+            checks.push(Check::new("connection", Status::Fail, e.to_string()));
+            return checks;
+        }
+    };
+
+    // Handshake
+    let info = match server_info(&mut client) {
+        Ok(info) => info,
+        Err(detail) => {
+            checks.push(Check::new("handshake", Status::Fail, detail));
+            return checks;
+        }
+    };
+    checks.push(check_handshake(&info));
+
+    // Latency
+    let mut samples = Vec::with_capacity(LATENCY_SAMPLES as usize);
+    for _ in 0..LATENCY_SAMPLES {
+        let start = Instant::now();
+        if let Err(detail) = server_info(&mut client) {
+            checks.push(Check::new("latency", Status::Fail, detail));
+            return checks;
+        }
+        samples.push(start.elapsed());
+    }
+    checks.push(check_latency(&samples));
+
+    // Snapshot freshness
+    let frame_before = info["frame"].as_u64().unwrap_or(0);
+    let frame_after = if info["has_snapshot"].as_bool().unwrap_or(false) {
+        std::thread::sleep(FRESHNESS_WINDOW);
+        match server_info(&mut client) {
+            Ok(later) => later["frame"].as_u64(),
+            Err(detail) => {
+                checks.push(Check::new("snapshot", Status::Fail, detail));
+                return checks;
+            }
+        }
+    } else {
+        None
+    };
+    checks.push(check_snapshot(frame_before, frame_after));
+
+    checks.push(check_screenshot(&info));
+    checks.push(check_renderer(&info));
+    checks
+}
+
+/// Whether any check failed outright.
+pub fn has_failures(checks: &[Check]) -> bool {
+    checks.iter().any(|check| check.status == Status::Fail)
+}
+
+fn server_info(client: &mut DebugClient) -> Result<serde_json::Value, String> {
+    let response = client
+        .send("server.info", None)
+        .map_err(|e| format!("request failed: {e}"))?;
+    if let Some(err) = response.error {
+        return Err(if err.code == -32601 {
+            "server does not support server.info (built against an older motif_debug?)".into()
+        } else {
+            format!("error [{}]: {}", err.code, err.message)
+        });
+    }
+    response
+        .result
+        .ok_or_else(|| "server.info returned no result".to_string())
+}
+
+fn check_handshake(info: &serde_json::Value) -> Check {
+    let protocol = info["protocol_version"].as_u64().unwrap_or(0);
+    let detail = format!(
+        "motif_debug {}, protocol {}, pid {} ({})",
+        info["version"].as_str().unwrap_or("?"),
+        protocol,
+        info["pid"],
+        info["platform"].as_str().unwrap_or("?"),
+    );
+
+    if protocol == u64::from(PROTOCOL_VERSION) {
+        Check::new("handshake", Status::Ok, detail)
+    } else {
+        Check::new(
+            "handshake",
+            Status::Warn,
+            format!("{detail}; this CLI speaks protocol {PROTOCOL_VERSION}"),
+        )
+    }
+}
+
+fn check_latency(samples: &[Duration]) -> Check {
+    let to_ms = |d: &Duration| d.as_secs_f64() * 1000.0;
+    let total: f64 = samples.iter().map(to_ms).sum();
+    let avg = total / samples.len().max(1) as f64;
+    let max = samples.iter().map(to_ms).fold(0.0, f64::max);
+
+    let status = if avg > LATENCY_WARN_MS {
+        Status::Warn
+    } else {
+        Status::Ok
+    };
+    Check::new(
+        "latency",
+        status,
+        format!(
+            "avg {avg:.2}ms, max {max:.2}ms over {} round trips",
+            samples.len()
+        ),
+    )
+}
+
+fn check_snapshot(frame_before: u64, frame_after: Option<u64>) -> Check {
+    match frame_after {
+        None => Check::new(
+            "snapshot",
+            Status::Fail,
+            "no scene snapshot received; is the app calling DebugServer::update_scene?",
+        ),
+        Some(after) if after > frame_before => {
+            let frames = after - frame_before;
+            let fps = frames as f64 / FRESHNESS_WINDOW.as_secs_f64();
+            Check::new(
+                "snapshot",
+                Status::Ok,
+                format!(
+                    "frame {after}, {frames} new in {}ms (~{fps:.0} fps)",
+                    FRESHNESS_WINDOW.as_millis()
+                ),
+            )
+        }
+        Some(after) => Check::new(
+            "snapshot",
+            Status::Warn,
+            format!(
+                "frame counter stuck at {after} for {}ms; app is idle or not updating snapshots",
+                FRESHNESS_WINDOW.as_millis()
+            ),
+        ),
+    }
+}
+
+fn check_screenshot(info: &serde_json::Value) -> Check {
+    let screenshot = &info["screenshot"];
+    if screenshot["renderer_capture"].as_bool().unwrap_or(false) {
+        return Check::new("screenshot", Status::Ok, "renderer readback");
+    }
+    if !screenshot["supported"].as_bool().unwrap_or(false) {
+        return Check::new(
+            "screenshot",
+            Status::Warn,
+            format!(
+                "window capture not supported on {}",
+                info["platform"].as_str().unwrap_or("this platform")
+            ),
+        );
+    }
+    if !screenshot["permitted"].as_bool().unwrap_or(false) {
+        return Check::new(
+            "screenshot",
+            Status::Fail,
+            "Screen Recording permission not granted (System Settings > Privacy & Security)",
+        );
+    }
+    match screenshot["window_id"].as_u64() {
+        Some(id) => Check::new("screenshot", Status::Ok, format!("window {id}")),
+        None => Check::new(
+            "screenshot",
+            Status::Warn,
+            "no window registered; call DebugServer::set_window_id",
+        ),
+    }
+}
+
+fn check_renderer(info: &serde_json::Value) -> Check {
+    let renderer = &info["renderer"];
+    match renderer["backend"].as_str() {
+        Some(backend) => {
+            let mut detail = format!(
+                "{backend} ({})",
+                renderer["version"].as_str().unwrap_or("unknown version")
+            );
+            if let Some(gpu_ms) = info["gpu_time_ms"].as_f64() {
+                detail.push_str(&format!(", gpu {gpu_ms:.2}ms/frame"));
+            }
+            Check::new("renderer", Status::Ok, detail)
+        }
+        None => Check::new(
+            "renderer",
+            Status::Warn,
+            "not reported; call DebugServer::set_renderer_info",
+        ),
+    }
+}
+
+/// Format checks as an aligned report with a summary line.
+pub fn format_report(checks: &[Check]) -> String {
+    let mut out = String::from("motif-debug doctor\n\n");
+    for check in checks {
+        out.push_str(&format!(
+            "  {:<5} {:<11} {}\n",
+            check.status.label(),
+            check.name,
+            check.detail
+        ));
+    }
+
+    let count = |status| checks.iter().filter(|c| c.status == status).count();
+    out.push_str(&format!(
+        "\n  {} ok, {} warnings, {} failed\n",
+        count(Status::Ok),
+        count(Status::Warn),
+        count(Status::Fail)
+    ));
+    out
+}
+
+/// Format checks as a JSON array for `--json`.
+pub fn report_json(checks: &[Check]) -> serde_json::Value {
+    serde_json::Value::Array(checks.iter().map(Check::to_json).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use motif_debug::{DebugServer, SceneSnapshot};
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    fn test_socket_path() -> String {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let id = COUNTER.fetch_add(1, Ordering::SeqCst);
+        let pid = std::process::id();
+        format!("/tmp/motif-debug-test-doctor-{pid}-{id}.sock")
+    }
+
+    fn status_of(checks: &[Check], name: &str) -> Option<Status> {
+        checks.iter().find(|c| c.name == name).map(|c| c.status)
+    }
+
+    #[test]
+    fn missing_socket_fails_connection_only() {
+        let checks = run(Some("/tmp/motif-debug-nonexistent-doctor.sock"));
+        assert_eq!(checks.len(), 1);
+        assert_eq!(checks[0].name, "connection");
+        assert_eq!(checks[0].status, Status::Fail);
+        assert!(has_failures(&checks));
+    }
+
+    #[test]
+    fn server_without_snapshot_fails_snapshot_check() {
+        let path = test_socket_path();
+        let _server = DebugServer::with_path(PathBuf::from(&path)).expect("server should start");
+        std::thread::sleep(Duration::from_millis(100));
+
+        let checks = run(Some(&path));
+        assert_eq!(status_of(&checks, "connection"), Some(Status::Ok));
+        assert_eq!(status_of(&checks, "handshake"), Some(Status::Ok));
+        assert_eq!(status_of(&checks, "latency"), Some(Status::Ok));
+        assert_eq!(status_of(&checks, "snapshot"), Some(Status::Fail));
+        assert_eq!(status_of(&checks, "renderer"), Some(Status::Warn));
+    }
+
+    #[test]
+    fn advancing_frames_pass_snapshot_check() {
+        let path = test_socket_path();
+        let server = DebugServer::with_path(PathBuf::from(&path)).expect("server should start");
+        server.set_renderer_info("test", "1.0");
+        let scene = motif_core::Scene::new();
+        server.update_scene(SceneSnapshot::from_scene(&scene, (100.0, 100.0), 1.0));
+        std::thread::sleep(Duration::from_millis(100));
+
+        let server = std::sync::Arc::new(server);
+        let stop = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let renderer = {
+            let server = std::sync::Arc::clone(&server);
+            let stop = std::sync::Arc::clone(&stop);
+            std::thread::spawn(move || {
+                while !stop.load(Ordering::SeqCst) {
+                    let scene = motif_core::Scene::new();
+                    server.update_scene(SceneSnapshot::from_scene(&scene, (100.0, 100.0), 1.0));
+                    std::thread::sleep(Duration::from_millis(16));
+                }
+            })
+        };
+
+        let checks = run(Some(&path));
+        stop.store(true, Ordering::SeqCst);
+        renderer.join().unwrap();
+
+        assert_eq!(status_of(&checks, "snapshot"), Some(Status::Ok));
+        assert_eq!(status_of(&checks, "renderer"), Some(Status::Ok));
+    }
+
+    #[test]
+    fn stuck_frame_counter_warns() {
+        assert_eq!(check_snapshot(5, Some(5)).status, Status::Warn);
+        assert_eq!(check_snapshot(5, Some(9)).status, Status::Ok);
+        assert_eq!(check_snapshot(0, None).status, Status::Fail);
+    }
+
+    #[test]
+    fn renderer_check_includes_gpu_time() {
+        let info = serde_json::json!({
+            "renderer": { "backend": "metal", "version": "Apple M1" },
+            "gpu_time_ms": 1.25,
+        });
+        assert_eq!(
+            check_renderer(&info).detail,
+            "metal (Apple M1), gpu 1.25ms/frame"
+        );
+    }
+
+    #[test]
+    fn renderer_capture_skips_permission_check() {
+        let info = serde_json::json!({
+            "screenshot": { "supported": true, "permitted": false, "renderer_capture": true },
+        });
+        assert_eq!(check_screenshot(&info).status, Status::Ok);
+
+        let info = serde_json::json!({
+            "screenshot": { "supported": true, "permitted": false, "renderer_capture": false },
+        });
+        assert_eq!(check_screenshot(&info).status, Status::Fail);
+    }
+
+    #[test]
+    fn report_summarizes_statuses() {
+        let checks = vec![
+            Check::new("connection", Status::Ok, "/tmp/x.sock"),
+            Check::new("renderer", Status::Warn, "not reported"),
+            Check::new("snapshot", Status::Fail, "none"),
+        ];
+        let report = format_report(&checks);
+        assert!(report.contains("ok    connection  /tmp/x.sock"));
+        assert!(report.contains("FAIL  snapshot"));
+        assert!(report.contains("1 ok, 1 warnings, 1 failed"));
+
+        let json = report_json(&checks);
+        assert_eq!(json[2]["status"], "fail");
+    }
+}
@@ -4,28 +4,39 @@
 //!   motif-debug 'scene.stats'          — single command mode
 //!   motif-debug                         — REPL mode
 //!   motif-debug --json 'scene.stats'   — raw JSON output
+//!   motif-debug --cbor 'scene.quads'   — talk CBOR to the server
 //!   motif-debug --socket /path/to/sock  — connect to specific socket
+//!   motif-debug --app playground        — connect to a registered app by name
+//!   motif-debug apps                    — list running apps
+//!   motif-debug doctor                  — check that the tooling works
 
 mod client;
+mod doctor;
 
 use client::DebugClient;
 
 struct Args {
     json: bool,
+    /// Switch the connection to CBOR, for large results.
+    cbor: bool,
     socket: Option<String>,
+    app: Option<String>,
     command: Option<String>,
 }
 
 fn parse_args() -> Args {
     let args: Vec<String> = std::env::args().skip(1).collect();
     let mut json = false;
+    let mut cbor = false;
     let mut socket = None;
+    let mut app = None;
     let mut positional = Vec::new();
     let mut i = 0;
 
     while i < args.len() {
         match args[i].as_str() {
             "--json" => json = true,
+            "--cbor" => cbor = true,
             "--socket" => {
                 i += 1;
                 if i >= args.len() {
@@ -34,6 +45,14 @@ fn parse_args() -> Args {
                 }
                 socket = Some(args[i].clone());
             }
+            "--app" => {
+                i += 1;
+                if i >= args.len() {
+                    eprintln!("error: --app requires a name or pid");
+                    std::process::exit(1);
+                }
+                app = Some(args[i].clone());
+            }
             "--help" | "-h" => {
                 print_usage();
                 std::process::exit(0);
@@ -61,7 +80,9 @@ fn parse_args() -> Args {
 
     Args {
         json,
+        cbor,
         socket,
+        app,
         command,
     }
 }
@@ -74,21 +95,73 @@ fn print_usage() {
     eprintln!();
     eprintln!("OPTIONS:");
     eprintln!("  --json             Output raw JSON (for scripting)");
+    eprintln!("  --cbor             Use CBOR on the wire instead of JSON (faster for big scenes)");
     eprintln!("  --socket <path>    Connect to a specific socket path");
+    eprintln!("  --app <name|pid>   Connect to a running app from `apps`");
     eprintln!("  -h, --help         Show this help message");
     eprintln!();
     eprintln!("COMMANDS:");
     eprintln!("  scene.stats              Show scene statistics");
     eprintln!("  scene.quads              List all quads in the scene");
     eprintln!("  scene.text_runs          List all text runs in the scene");
+    eprintln!(
+        "  scene.bounds             Union bounds, viewport coverage and offscreen primitives"
+    );
+    eprintln!("  scene.query <expr>       Quads and text runs matching e.g. bounds.w > 500");
+    eprintln!("                           prefix quads: or text_runs: to search only one kind");
+    eprintln!("  scene.patch <n> move <dx> <dy>");
+    eprintln!("  scene.patch <n> color <r> <g> <b> <a>");
+    eprintln!("  scene.patch <n> hide|show|reset");
+    eprintln!("                           Override quad <n> in the next frames (device px)");
+    eprintln!("  scene.patch clear        Drop every quad override");
+    eprintln!("  scene.profile [frames]   Time spent in each frame phase, last and average");
+    eprintln!("  perf.set_budget <ms>     Report each frame slower than <ms> (0 clears)");
+    eprintln!("  fps                      Current, average and min frame rate");
+    eprintln!("  log.set_level <level> [target] [secs]");
+    eprintln!("                           Change the app's log level, for secs if given");
+    eprintln!("  log.set_level reset      Back to the app's own log filter");
     eprintln!("  input.state              Show current input state (cursor, buttons, modifiers)");
-    eprintln!("  screenshot <path.png>    Capture scene to a PNG file");
+    eprintln!("  screenshot [src] <path>  Capture scene to a PNG (src: renderer or window)");
+    eprintln!("                           @2x renders at scale 2, 800x600 at that logical size");
+    eprintln!("                           overlays draws debug overlays into the capture");
+    eprintln!(
+        "  framebuffer.get          Read the current frame's RGBA pixels (use --json for data)"
+    );
+    eprintln!("  pixel.color <x> <y>      Color at a window point, and the primitive drawn there");
+    eprintln!("  atlas.dump [path]        Save the glyph atlas to a PNG and show row occupancy");
+    eprintln!("  capture.start <n> [every] [path]");
+    eprintln!("                           Record n frames (one in every) to an animated PNG");
+    eprintln!("  capture.stop             End a recording early, keeping its frames");
+    eprintln!("  server.info              Show server version, frame counter and renderer");
+    eprintln!("  server.stats             Connections, requests per method, snapshot age");
+    eprintln!("  rpc.describe             List every method the server supports, with params");
+    eprintln!("  ping                     Check the connection is alive");
+    eprintln!("  doctor                   Check connection, latency, snapshots and permissions");
+    eprintln!("  apps                     List running motif apps");
+    eprintln!("  diff <a> <b> [out]       Compare two PNGs, optionally saving a diff image");
     eprintln!();
     eprintln!("DEBUG OVERLAY COMMANDS:");
     eprintln!("  draw.quad x y w h r g b a      Draw a debug overlay quad");
+    eprintln!("  draw.arrow x1 y1 x2 y2 [label] Draw an arrow from (x1, y1) to (x2, y2)");
     eprintln!("  debug.remove <id>              Remove a specific debug overlay");
     eprintln!("  debug.clear                    Clear all debug overlays");
     eprintln!("  debug.list                     List all debug overlays");
+    eprintln!("  debug.show_guides <insets...>  Draw margin guides (1, 2 or 4 values, 0 hides)");
+    eprintln!(
+        "  debug.inspect_text <i|off>     Overlay line boxes, baseline and advances of run i"
+    );
+    eprintln!("  debug.show_baseline_grid <lh>  Draw baseline rules every <lh> px (0 hides)");
+    eprintln!("  debug.show_spacing <scale>     Draw spacing rules every <scale> px (0 hides)");
+    eprintln!(
+        "  debug.show_density <cell>      Shade a <cell> px grid by primitive count (0 hides)"
+    );
+    eprintln!("  debug.overdraw [on|off]        Shade pixels by how often they're drawn (toggles)");
+    eprintln!(
+        "  debug.crosshair [on|off]       Hairlines and coordinates at the pointer (toggles)"
+    );
+    eprintln!("  debug.fps_overlay [on|off]     Live frame-rate widget in the corner (toggles)");
+    eprintln!("  debug.highlight_access <id> [ms] Flash an outline over an accessibility node");
+    eprintln!("  debug.time_scale <scale>       Run timers and animations at <scale>x (0 pauses)");
     eprintln!();
     eprintln!("INPUT SIMULATION COMMANDS:");
     eprintln!("  input.activate                 Bring the app window to front");
@@ -108,20 +181,53 @@ fn print_usage() {
 fn parse_command(input: &str) -> (&str, Option<serde_json::Value>) {
     let trimmed = input.trim();
     if trimmed == "screenshot" {
-        let path = default_screenshot_path();
-        ("screenshot", Some(serde_json::json!({ "path": path })))
-    } else if let Some(path) = trimmed.strip_prefix("screenshot ") {
-        let path = path.trim();
-        if path.is_empty() {
-            let path = default_screenshot_path();
-            ("screenshot", Some(serde_json::json!({ "path": path })))
-        } else {
-            ("screenshot", Some(serde_json::json!({ "path": path })))
-        }
+        parse_screenshot("")
+    } else if let Some(args) = trimmed.strip_prefix("screenshot ") {
+        parse_screenshot(args)
     } else if let Some(args) = trimmed.strip_prefix("draw.quad ") {
         parse_draw_quad(args)
+    } else if let Some(args) = trimmed.strip_prefix("draw.arrow ") {
+        parse_draw_arrow(args)
+    } else if let Some(args) = trimmed.strip_prefix("scene.query ") {
+        parse_scene_query(args)
+    } else if let Some(args) = trimmed.strip_prefix("scene.patch ") {
+        parse_scene_patch(args)
+    } else if let Some(args) = trimmed.strip_prefix("scene.profile ") {
+        parse_scene_profile(args)
+    } else if let Some(args) = trimmed.strip_prefix("pixel.color ") {
+        parse_pixel_color(args)
+    } else if trimmed == "atlas.dump" {
+        parse_atlas_dump("")
+    } else if let Some(args) = trimmed.strip_prefix("atlas.dump ") {
+        parse_atlas_dump(args)
+    } else if let Some(args) = trimmed.strip_prefix("capture.start ") {
+        parse_capture_start(args)
+    } else if let Some(args) = trimmed.strip_prefix("log.set_level ") {
+        parse_log_set_level(args)
+    } else if let Some(args) = trimmed.strip_prefix("perf.set_budget ") {
+        parse_set_budget(args)
     } else if let Some(args) = trimmed.strip_prefix("debug.remove ") {
         parse_debug_remove(args)
+    } else if let Some(args) = trimmed.strip_prefix("debug.show_density ") {
+        parse_guide("debug.show_density", "cell", args)
+    } else if let Some(args) = trimmed.strip_prefix("debug.overdraw ") {
+        parse_switch("debug.overdraw", "enabled", args)
+    } else if let Some(args) = trimmed.strip_prefix("debug.crosshair ") {
+        parse_switch("debug.crosshair", "on", args)
+    } else if let Some(args) = trimmed.strip_prefix("debug.fps_overlay ") {
+        parse_switch("debug.fps_overlay", "on", args)
+    } else if let Some(args) = trimmed.strip_prefix("debug.highlight_access ") {
+        parse_highlight_access(args)
+    } else if let Some(args) = trimmed.strip_prefix("debug.time_scale ") {
+        parse_guide("debug.time_scale", "scale", args)
+    } else if let Some(args) = trimmed.strip_prefix("debug.inspect_text ") {
+        parse_inspect_text(args)
+    } else if let Some(args) = trimmed.strip_prefix("debug.show_guides ") {
+        parse_show_guides(args)
+    } else if let Some(args) = trimmed.strip_prefix("debug.show_baseline_grid ") {
+        parse_guide("debug.show_baseline_grid", "line_height", args)
+    } else if let Some(args) = trimmed.strip_prefix("debug.show_spacing ") {
+        parse_guide("debug.show_spacing", "scale", args)
     } else if let Some(args) = trimmed.strip_prefix("input.move_to ") {
         parse_input_xy("input.move_to", args)
     } else if let Some(args) = trimmed.strip_prefix("input.click ") {
@@ -159,6 +265,187 @@ fn parse_draw_quad(args: &str) -> (&'static str, Option<serde_json::Value>) {
     ("debug.draw_quad", Some(params))
 }
 
+/// Parse `draw.arrow x1 y1 x2 y2 [label...]` into a debug.draw_arrow
+/// request. Everything after the coordinates is the label.
+fn parse_draw_arrow(args: &str) -> (&'static str, Option<serde_json::Value>) {
+    let mut words = args.split_whitespace();
+    let coords: Vec<f64> = words
+        .by_ref()
+        .take(4)
+        .filter_map(|s| s.parse::<f64>().ok())
+        .collect();
+    if coords.len() < 4 {
+        eprintln!("usage: draw.arrow x1 y1 x2 y2 [label]");
+        return ("debug.draw_arrow", None);
+    }
+
+    let mut params = serde_json::json!({
+        "from": [coords[0], coords[1]],
+        "to": [coords[2], coords[3]],
+    });
+    let label = words.collect::<Vec<_>>().join(" ");
+    if !label.is_empty() {
+        params["label"] = label.into();
+    }
+    ("debug.draw_arrow", Some(params))
+}
+
+/// Parse `scene.profile <frames>` into a scene.profile request.
+fn parse_scene_profile(args: &str) -> (&'static str, Option<serde_json::Value>) {
+    match args.trim().parse::<u64>() {
+        Ok(frames) => (
+            "scene.profile",
+            Some(serde_json::json!({ "frames": frames })),
+        ),
+        Err(_) => {
+            eprintln!("usage: scene.profile [frames]");
+            ("scene.profile", None)
+        }
+    }
+}
+
+/// Parse `scene.query <expr>`; the rest of the line is the expression, so
+/// it needs no quoting in the REPL. A leading `quads:` or `text_runs:`
+/// limits the search.
+fn parse_scene_query(args: &str) -> (&'static str, Option<serde_json::Value>) {
+    let args = args.trim();
+    let (from, expr) = match args.split_once(':') {
+        Some((from, expr)) if from == "quads" || from == "text_runs" => (Some(from), expr),
+        _ => (None, args),
+    };
+    let mut params = serde_json::json!({ "expr": expr.trim() });
+    if let Some(from) = from {
+        params["from"] = from.into();
+    }
+    ("scene.query", Some(params))
+}
+
+/// Parse `scene.patch <n> move <dx> <dy>`, `scene.patch <n> color r g b a`,
+/// `scene.patch <n> hide|show|reset` or `scene.patch clear`.
+fn parse_scene_patch(args: &str) -> (&'static str, Option<serde_json::Value>) {
+    let words: Vec<&str> = args.split_whitespace().collect();
+    let numbers = |words: &[&str]| -> Option<Vec<f64>> {
+        words.iter().map(|w| w.parse::<f64>().ok()).collect()
+    };
+    let params =
+        match words.as_slice() {
+            ["clear"] => Some(serde_json::json!({ "clear": true })),
+            [quad, action, rest @ ..] => quad.parse::<u64>().ok().and_then(|quad| {
+                match (*action, numbers(rest).as_deref()) {
+                    ("move", Some(&[dx, dy])) => Some(serde_json::json!({
+                        "quad": quad, "dx": dx, "dy": dy,
+                    })),
+                    ("color", Some(&[r, g, b, a])) => Some(serde_json::json!({
+                        "quad": quad, "color": [r, g, b, a],
+                    })),
+                    ("hide", Some(&[])) => {
+                        Some(serde_json::json!({ "quad": quad, "hidden": true }))
+                    }
+                    ("show", Some(&[])) => {
+                        Some(serde_json::json!({ "quad": quad, "hidden": false }))
+                    }
+                    ("reset", Some(&[])) => {
+                        Some(serde_json::json!({ "quad": quad, "reset": true }))
+                    }
+                    _ => None,
+                }
+            }),
+            _ => None,
+        };
+    if params.is_none() {
+        eprintln!(
+            "usage: scene.patch <n> move <dx> <dy> | <n> color <r> <g> <b> <a> | <n> hide|show|reset | clear"
+        );
+    }
+    ("scene.patch", params)
+}
+
+/// Parse `perf.set_budget <ms>` into a perf.set_budget request.
+fn parse_set_budget(args: &str) -> (&'static str, Option<serde_json::Value>) {
+    match args.trim().parse::<f64>() {
+        Ok(ms) => ("perf.set_budget", Some(serde_json::json!({ "ms": ms }))),
+        Err(_) => {
+            eprintln!("usage: perf.set_budget <ms>");
+            ("perf.set_budget", None)
+        }
+    }
+}
+
+/// Parse `pixel.color <x> <y>` into a pixel.color request.
+fn parse_pixel_color(args: &str) -> (&'static str, Option<serde_json::Value>) {
+    let coords: Vec<f64> = args
+        .split_whitespace()
+        .filter_map(|s| s.parse::<f64>().ok())
+        .collect();
+    match coords.as_slice() {
+        [x, y] => ("pixel.color", Some(serde_json::json!({ "x": x, "y": y }))),
+        _ => {
+            eprintln!("usage: pixel.color <x> <y>");
+            ("pixel.color", None)
+        }
+    }
+}
+
+/// Parse `atlas.dump [path]`, generating a /tmp path when none is given.
+fn parse_atlas_dump(args: &str) -> (&'static str, Option<serde_json::Value>) {
+    let path = match args.trim() {
+        "" => default_output_path("atlas"),
+        path => path.to_string(),
+    };
+    ("atlas.dump", Some(serde_json::json!({ "path": path })))
+}
+
+/// Parse `capture.start <frames> [every] [path]` into a capture.start request.
+fn parse_capture_start(args: &str) -> (&'static str, Option<serde_json::Value>) {
+    let mut tokens = args.split_whitespace().peekable();
+    let Some(frames) = tokens.next().and_then(|t| t.parse::<u64>().ok()) else {
+        eprintln!("usage: capture.start <frames> [every] [path]");
+        return ("capture.start", None);
+    };
+    let every = tokens.next_if(|t| t.parse::<u64>().is_ok());
+    let path = tokens
+        .next()
+        .map(str::to_string)
+        .unwrap_or_else(|| default_output_path("capture"));
+    let mut params = serde_json::json!({ "path": path, "frames": frames });
+    if let Some(every) = every.and_then(|t| t.parse::<u64>().ok()) {
+        params["every"] = serde_json::json!(every);
+    }
+    ("capture.start", Some(params))
+}
+
+/// Parse `log.set_level <level> [target] [secs]` or `log.set_level reset`.
+/// A number is the duration; anything else after the level is the target.
+fn parse_log_set_level(args: &str) -> (&'static str, Option<serde_json::Value>) {
+    let mut parts = args.split_whitespace();
+    let params = match parts.next() {
+        Some("reset") => Some(serde_json::json!({ "reset": true })),
+        Some(level) => {
+            let mut params = serde_json::json!({ "level": level });
+            for part in parts {
+                match part.parse::<f64>() {
+                    Ok(secs) if secs > 0.0 => {
+                        params["duration_ms"] = serde_json::json!((secs * 1000.0).round() as u64)
+                    }
+                    Ok(_) => return log_set_level_usage(),
+                    Err(_) => params["target"] = serde_json::json!(part),
+                }
+            }
+            Some(params)
+        }
+        None => None,
+    };
+    match params {
+        Some(params) => ("log.set_level", Some(params)),
+        None => log_set_level_usage(),
+    }
+}
+
+fn log_set_level_usage() -> (&'static str, Option<serde_json::Value>) {
+    eprintln!("usage: log.set_level <level> [target] [secs] | log.set_level reset");
+    ("log.set_level", None)
+}
+
 /// Parse `debug.remove <id>` into a debug.remove request.
 fn parse_debug_remove(args: &str) -> (&'static str, Option<serde_json::Value>) {
     match args.trim().parse::<u64>() {
@@ -170,6 +457,86 @@ fn parse_debug_remove(args: &str) -> (&'static str, Option<serde_json::Value>) {
     }
 }
 
+/// Parse `on|off` for a switch like `debug.overdraw` into `{ param: bool }`.
+/// Without an argument the switch toggles.
+fn parse_switch(
+    method: &'static str,
+    param: &str,
+    args: &str,
+) -> (&'static str, Option<serde_json::Value>) {
+    let on = match args.trim() {
+        "on" => true,
+        "off" => false,
+        _ => {
+            eprintln!("usage: {method} [on|off]");
+            return (method, None);
+        }
+    };
+    (method, Some(serde_json::json!({ param: on })))
+}
+
+/// Parse `debug.inspect_text <index>` (or `off` to stop inspecting).
+fn parse_inspect_text(args: &str) -> (&'static str, Option<serde_json::Value>) {
+    let arg = args.trim();
+    if arg == "off" {
+        return (
+            "debug.inspect_text",
+            Some(serde_json::json!({ "index": null })),
+        );
+    }
+    match arg.parse::<u64>() {
+        Ok(index) => (
+            "debug.inspect_text",
+            Some(serde_json::json!({ "index": index })),
+        ),
+        Err(_) => {
+            eprintln!("usage: debug.inspect_text <index|off>");
+            ("debug.inspect_text", None)
+        }
+    }
+}
+
+/// Parse `debug.show_guides <insets...>` using CSS-style shorthand:
+/// one value for all edges, two for vertical/horizontal, or four for
+/// top/right/bottom/left.
+fn parse_show_guides(args: &str) -> (&'static str, Option<serde_json::Value>) {
+    let parts: Vec<f64> = args
+        .split_whitespace()
+        .filter_map(|s| s.parse::<f64>().ok())
+        .collect();
+
+    let insets = match parts.as_slice() {
+        [all] => [*all, *all, *all, *all],
+        [vertical, horizontal] => [*vertical, *horizontal, *vertical, *horizontal],
+        [top, right, bottom, left] => [*top, *right, *bottom, *left],
+        _ => {
+            eprintln!("usage: debug.show_guides <all> | <vertical> <horizontal> | <top> <right> <bottom> <left>");
+            return ("debug.show_guides", None);
+        }
+    };
+
+    (
+        "debug.show_guides",
+        Some(serde_json::json!({ "insets": insets })),
+    )
+}
+
+/// Parse `debug.show_baseline_grid <line_height>`, `debug.show_spacing <scale>`
+/// and `debug.show_density <cell>`.
+fn parse_guide(
+    method: &'static str,
+    param: &str,
+    args: &str,
+) -> (&'static str, Option<serde_json::Value>) {
+    match args.trim().parse::<f64>() {
+        Ok(step) => (method, Some(serde_json::json!({ param: step }))),
+        Err(_) => {
+            eprintln!("usage: {} <{}>", method, param);
+            (method, None)
+        }
+    }
+}
+
 /// Parse `input.move_to x y` (and similar) into input simulation requests.
 fn parse_input_xy(method: &'static str, args: &str) -> (&'static str, Option<serde_json::Value>) {
     let parts: Vec<f64> = args
@@ -210,14 +577,75 @@ fn parse_input_drag(args: &str) -> (&'static str, Option<serde_json::Value>) {
     ("input.drag", Some(params))
 }
 
-fn default_screenshot_path() -> String {
+/// Parse `screenshot [renderer|window] [path]`. Without a source the app's
+/// preferred capture method is used.
+fn parse_screenshot(args: &str) -> (&'static str, Option<serde_json::Value>) {
+    let mut args = args.trim();
+    let mut source = None;
+    for name in ["renderer", "window"] {
+        if let Some(rest) = args.strip_prefix(name) {
+            if rest.is_empty() || rest.starts_with(' ') {
+                source = Some(name);
+                args = rest.trim();
+            }
+        }
+    }
+    // `@2x` sets the scale, `800x600` the logical size, and `overlays`
+    // draws the debug overlays into the capture.
+    let mut scale = None;
+    let mut size = None;
+    let mut overlays = false;
+    loop {
+        let (token, rest) = args.split_once(' ').unwrap_or((args, ""));
+        if token == "overlays" {
+            overlays = true;
+        } else if let Some(value) = token
+            .strip_prefix('@')
+            .and_then(|t| t.strip_suffix('x'))
+            .and_then(|t| t.parse::<f64>().ok())
+        {
+            scale = Some(value);
+        } else if let Some((width, height)) = token
+            .split_once('x')
+            .and_then(|(w, h)| Some((w.parse::<f64>().ok()?, h.parse::<f64>().ok()?)))
+        {
+            size = Some((width, height));
+        } else {
+            break;
+        }
+        args = rest.trim();
+    }
+    let path = if args.is_empty() {
+        default_output_path("screenshot")
+    } else {
+        args.to_string()
+    };
+    let mut params = serde_json::json!({ "path": path });
+    if let Some(source) = source {
+        params["source"] = serde_json::json!(source);
+    }
+    if let Some(scale) = scale {
+        params["scale"] = serde_json::json!(scale);
+    }
+    if let Some((width, height)) = size {
+        params["width"] = serde_json::json!(width);
+        params["height"] = serde_json::json!(height);
+    }
+    if overlays {
+        params["include_overlays"] = serde_json::json!(true);
+    }
+    ("screenshot", Some(params))
+}
+
+/// A timestamped `/tmp/motif-{kind}-*.png` path for commands given no path.
+fn default_output_path(kind: &str) -> String {
     use std::time::SystemTime;
     let now = SystemTime::now()
         .duration_since(SystemTime::UNIX_EPOCH)
         .unwrap_or_default();
     let secs = now.as_secs();
     let millis = now.subsec_millis();
-    format!("/tmp/motif-screenshot-{secs}-{millis:03}.png")
+    format!("/tmp/motif-{kind}-{secs}-{millis:03}.png")
 }
 
 fn format_screenshot(value: &serde_json::Value) -> String {
@@ -225,7 +653,105 @@ fn format_screenshot(value: &serde_json::Value) -> String {
         .get("path")
         .and_then(|v| v.as_str())
         .unwrap_or("unknown");
-    format!("Screenshot saved to {path}\n")
+    match value.get("source").and_then(|v| v.as_str()) {
+        Some("renderer") => format!(
+            "Screenshot saved to {path} (renderer readback, {}x{})\n",
+            value["width"].as_u64().unwrap_or(0),
+            value["height"].as_u64().unwrap_or(0)
+        ),
+        _ => format!("Screenshot saved to {path}\n"),
+    }
+}
+
+fn format_pixel_color(value: &serde_json::Value) -> String {
+    let c = &value["color"];
+    let channel = |k: &str| c[k].as_f64().unwrap_or(0.0);
+    let mut out = format!(
+        "({}, {}): {}  rgba({:.3}, {:.3}, {:.3}, {:.3})  [{}]\n",
+        value["x"],
+        value["y"],
+        value["hex"].as_str().unwrap_or("?"),
+        channel("r"),
+        channel("g"),
+        channel("b"),
+        channel("a"),
+        value["source"].as_str().unwrap_or("?")
+    );
+    let primitive = &value["primitive"];
+    match primitive["kind"].as_str() {
+        Some(kind) => out.push_str(&format!(
+            "  topmost: {} {}\n",
+            kind.replace('_', " "),
+            primitive["index"].as_u64().unwrap_or(0)
+        )),
+        None => out.push_str("  topmost: nothing\n"),
+    }
+    out
+}
+
+fn format_atlas_dump(value: &serde_json::Value) -> String {
+    let page_size = value["page_size"].as_u64().unwrap_or(0);
+    let pages = value["pages"].as_array().map(Vec::as_slice).unwrap_or(&[]);
+    let mut out = format!(
+        "Atlas saved to {} ({} pages of {page_size}x{page_size})\n",
+        value["path"].as_str().unwrap_or("unknown"),
+        pages.len()
+    );
+    for page in pages {
+        let rows = page["rows"].as_array().map(Vec::as_slice).unwrap_or(&[]);
+        out.push_str(&format!(
+            "page {}: {} glyphs in {} rows, {}/{page_size} px high\n",
+            page["index"].as_u64().unwrap_or(0),
+            page["glyphs"].as_u64().unwrap_or(0),
+            rows.len(),
+            page["used_height"].as_u64().unwrap_or(0)
+        ));
+        for row in rows {
+            out.push_str(&format!(
+                "  y {:>4}  h {:>3}  {:>4} glyphs  {:>4} px wide  {:>3.0}% filled\n",
+                row["y"].as_u64().unwrap_or(0),
+                row["height"].as_u64().unwrap_or(0),
+                row["glyphs"].as_u64().unwrap_or(0),
+                row["used_width"].as_u64().unwrap_or(0),
+                row["fill"].as_f64().unwrap_or(0.0) * 100.0
+            ));
+        }
+    }
+    out
+}
+
+fn format_framebuffer(value: &serde_json::Value) -> String {
+    format!(
+        "Frame {}x{} {}, {} bytes\n",
+        value["width"].as_u64().unwrap_or(0),
+        value["height"].as_u64().unwrap_or(0),
+        value["format"].as_str().unwrap_or("?"),
+        value["bytes"].as_u64().unwrap_or(0)
+    )
+}
+
+fn format_capture(value: &serde_json::Value) -> String {
+    format!(
+        "Recorded {} frames ({}x{}, {:.0} ms) to {}{}\n",
+        value["frames"].as_u64().unwrap_or(0),
+        value["width"].as_u64().unwrap_or(0),
+        value["height"].as_u64().unwrap_or(0),
+        value["duration_ms"].as_f64().unwrap_or(0.0),
+        value["path"].as_str().unwrap_or("unknown"),
+        if value["stopped"].as_bool() == Some(true) {
+            " (stopped early)"
+        } else {
+            ""
+        }
+    )
+}
+
+fn format_capture_stop(value: &serde_json::Value) -> String {
+    if value["stopped"].as_bool() == Some(true) {
+        "Capture stopped\n".to_string()
+    } else {
+        "No capture recording\n".to_string()
+    }
 }
 
 fn format_draw_quad(value: &serde_json::Value) -> String {
@@ -233,6 +759,31 @@ fn format_draw_quad(value: &serde_json::Value) -> String {
     format!("Created overlay quad #{id}\n")
 }
 
+fn format_draw_arrow(value: &serde_json::Value) -> String {
+    let id = value.get("id").and_then(|v| v.as_u64()).unwrap_or(0);
+    format!("Created overlay arrow #{id}\n")
+}
+/// Parse `debug.highlight_access <id> [duration_ms]`.
+fn parse_highlight_access(args: &str) -> (&'static str, Option<serde_json::Value>) {
+    let parts: Vec<&str> = args.split_whitespace().collect();
+    let id = parts.first().and_then(|s| s.parse::<u64>().ok());
+    let duration = parts.get(1).map(|s| s.parse::<u64>());
+    match (id, duration, parts.len()) {
+        (Some(id), None, 1) => (
+            "debug.highlight_access",
+            Some(serde_json::json!({ "id": id })),
+        ),
+        (Some(id), Some(Ok(ms)), 2) => (
+            "debug.highlight_access",
+            Some(serde_json::json!({ "id": id, "duration_ms": ms })),
+        ),
+        _ => {
+            eprintln!("usage: debug.highlight_access <id> [duration_ms]");
+            ("debug.highlight_access", None)
+        }
+    }
+}
+
 fn format_debug_clear(value: &serde_json::Value) -> String {
     let count = value.get("cleared").and_then(|v| v.as_u64()).unwrap_or(0);
     format!("Cleared {count} overlays\n")
@@ -250,6 +801,183 @@ fn format_debug_remove(value: &serde_json::Value) -> String {
     }
 }
 
+fn format_show_guide(value: &serde_json::Value, param: &str, label: &str) -> String {
+    let enabled = value
+        .get("enabled")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    if enabled {
+        let step = value.get(param).and_then(|v| v.as_f64()).unwrap_or(0.0);
+        format!("Showing {label} every {step}px\n")
+    } else {
+        format!("Hid {label}\n")
+    }
+}
+
+fn format_show_density(value: &serde_json::Value) -> String {
+    let Some(cell) = value.get("cell").and_then(|v| v.as_f64()) else {
+        return "Hid density heatmap\n".to_string();
+    };
+    let mut out = format!("Showing density heatmap in {cell}px cells\n");
+    let busiest = value.get("busiest").and_then(|v| v.as_array());
+    if let Some(busiest) = busiest.filter(|cells| !cells.is_empty()) {
+        out.push_str(&format!(
+            "  {:<20}  {:>7}  {:>9}\n",
+            "CELL", "QUADS", "TEXT RUNS"
+        ));
+        for c in busiest {
+            let x = c["x"].as_f64().unwrap_or(0.0);
+            let y = c["y"].as_f64().unwrap_or(0.0);
+            out.push_str(&format!(
+                "  ({:>7.1}, {:>7.1})    {:>7}  {:>9}\n",
+                x,
+                y,
+                c["quads"].as_u64().unwrap_or(0),
+                c["text_runs"].as_u64().unwrap_or(0)
+            ));
+        }
+    }
+    out
+}
+
+fn format_fps(value: &serde_json::Value) -> String {
+    let rate = |key: &str| value.get(key).and_then(|v| v.as_f64()).unwrap_or(0.0);
+    let mut out = format!(
+        "{:.1} fps (avg {:.1}, min {:.1}, max {:.1}) over {} frames\n",
+        rate("current"),
+        rate("average"),
+        rate("min"),
+        rate("max"),
+        value["samples"].as_u64().unwrap_or(0)
+    );
+    out.push_str(&format!(
+        "  frame time: {:.2}ms avg\n",
+        rate("frame_time_ms")
+    ));
+    let idle = rate("since_last_frame_ms");
+    if idle > 1000.0 {
+        out.push_str(&format!(
+            "  no frame for {:.1}s (the app redraws on demand)\n",
+            idle / 1000.0
+        ));
+    }
+    out
+}
+
+fn format_log_set_level(value: &serde_json::Value) -> String {
+    let filter = value["filter"].as_str().unwrap_or("");
+    match value["expires_ms"].as_f64() {
+        Some(ms) => format!("Log filter: {filter} (reverts in {:.1}s)\n", ms / 1000.0),
+        None => format!("Log filter: {filter}\n"),
+    }
+}
+
+fn format_fps_overlay(value: &serde_json::Value) -> String {
+    if value.get("on").and_then(|v| v.as_bool()) == Some(true) {
+        "FPS widget on\n".to_string()
+    } else {
+        "FPS widget off\n".to_string()
+    }
+}
+
+fn format_overdraw(value: &serde_json::Value) -> String {
+    if value.get("enabled").and_then(|v| v.as_bool()) == Some(true) {
+        "Overdraw view on\n".to_string()
+    } else {
+        "Overdraw view off\n".to_string()
+    }
+}
+
+fn format_crosshair(value: &serde_json::Value) -> String {
+    if value.get("on").and_then(|v| v.as_bool()) != Some(true) {
+        return "Crosshair off\n".to_string();
+    }
+    match value.get("pointer").filter(|p| !p.is_null()) {
+        Some(p) => format!(
+            "Crosshair on, pointer at ({:.0}, {:.0})\n",
+            p["x"].as_f64().unwrap_or(0.0),
+            p["y"].as_f64().unwrap_or(0.0)
+        ),
+        None => "Crosshair on (pointer outside the window)\n".to_string(),
+    }
+}
+
+fn format_highlight_access(value: &serde_json::Value) -> String {
+    let b = &value["bounds"];
+    format!(
+        "Highlighting {} {:?} (id {}) at ({:.1}, {:.1}) {:.1}x{:.1} for {}ms\n",
+        value["role"].as_str().unwrap_or("?"),
+        value["name"].as_str().unwrap_or(""),
+        value["id"].as_u64().unwrap_or(0),
+        b["x"].as_f64().unwrap_or(0.0),
+        b["y"].as_f64().unwrap_or(0.0),
+        b["w"].as_f64().unwrap_or(0.0),
+        b["h"].as_f64().unwrap_or(0.0),
+        value["duration_ms"].as_u64().unwrap_or(0)
+    )
+}
+
+fn format_time_scale(value: &serde_json::Value) -> String {
+    match value.get("scale").and_then(|v| v.as_f64()) {
+        Some(0.0) => "Time paused\n".to_string(),
+        Some(scale) => format!("Time scale {scale}x\n"),
+        None => String::new(),
+    }
+}
+
+fn format_inspect_text(value: &serde_json::Value) -> String {
+    let index = match value.get("inspecting").and_then(|v| v.as_u64()) {
+        Some(index) => index,
+        None => return "Stopped text inspection\n".to_string(),
+    };
+
+    let f = |v: &serde_json::Value, key: &str| v.get(key).and_then(|v| v.as_f64()).unwrap_or(0.0);
+    let mut out = format!(
+        "Inspecting text run #{index} (size {:.1}, ascent {:.1}, descent {:.1})\n",
+        f(value, "font_size"),
+        f(value, "ascent"),
+        f(value, "descent")
+    );
+
+    let lines = value.get("lines").and_then(|v| v.as_array());
+    for (i, line) in lines.into_iter().flatten().enumerate() {
+        out.push_str(&format!(
+            "  line {i}: baseline {:.1}, x {:.1}, width {:.1}\n",
+            f(line, "baseline"),
+            f(line, "x"),
+            f(line, "width")
+        ));
+    }
+
+    let glyphs = value.get("glyphs").and_then(|v| v.as_array());
+    let advances: Vec<String> = glyphs
+        .into_iter()
+        .flatten()
+        .map(|g| format!("{:.1}", f(g, "advance")))
+        .collect();
+    out.push_str(&format!("  advances: [{}]\n", advances.join(", ")));
+    out
+}
+
+fn format_show_guides(value: &serde_json::Value) -> String {
+    let enabled = value
+        .get("enabled")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    if !enabled {
+        return "Hid margin guides\n".to_string();
+    }
+    let insets = value.get("insets").unwrap_or(&serde_json::Value::Null);
+    let edge = |name: &str| insets.get(name).and_then(|v| v.as_f64()).unwrap_or(0.0);
+    format!(
+        "Showing margin guides (top {}, right {}, bottom {}, left {})\n",
+        edge("top"),
+        edge("right"),
+        edge("bottom"),
+        edge("left")
+    )
+}
+
 fn format_input_activate(value: &serde_json::Value) -> String {
     let pid = value.get("pid").and_then(|v| v.as_u64()).unwrap_or(0);
     format!("Activated app (pid: {})\n", pid)
@@ -323,6 +1051,141 @@ fn format_input_drag(value: &serde_json::Value) -> String {
     )
 }
 
+fn format_scene_patch(value: &serde_json::Value) -> String {
+    let patches = value["patches"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or(&[]);
+    if patches.is_empty() {
+        return "No quad overrides\n".to_string();
+    }
+    let mut out = format!("{} quad overrides:\n", patches.len());
+    for patch in patches {
+        let mut changes = Vec::new();
+        let (dx, dy) = (
+            patch["dx"].as_f64().unwrap_or(0.0),
+            patch["dy"].as_f64().unwrap_or(0.0),
+        );
+        if dx != 0.0 || dy != 0.0 {
+            changes.push(format!("moved ({dx:+.1}, {dy:+.1})"));
+        }
+        if let Some(c) = patch["color"].as_object() {
+            let channel = |k: &str| c.get(k).and_then(|v| v.as_f64()).unwrap_or(0.0);
+            changes.push(format!(
+                "color ({:.2}, {:.2}, {:.2}, {:.2})",
+                channel("r"),
+                channel("g"),
+                channel("b"),
+                channel("a")
+            ));
+        }
+        if patch["hidden"].as_bool() == Some(true) {
+            changes.push("hidden".to_string());
+        }
+        if changes.is_empty() {
+            changes.push("unchanged".to_string());
+        }
+        out.push_str(&format!(
+            "  quad {:>5}  {}\n",
+            patch["quad"].as_u64().unwrap_or(0),
+            changes.join(", ")
+        ));
+    }
+    out
+}
+
+fn format_server_stats(value: &serde_json::Value) -> String {
+    let count = |key: &str| value[key].as_u64().unwrap_or(0);
+    let mut out = format!(
+        "Uptime:       {:.1}s\n",
+        value["uptime_ms"].as_f64().unwrap_or(0.0) / 1000.0
+    );
+    out.push_str(&format!(
+        "Connections:  {} open, {} total\n",
+        count("connections"),
+        count("total_connections")
+    ));
+    out.push_str(&format!(
+        "Requests:     {} ({} malformed)\n",
+        count("total_requests"),
+        count("malformed_requests")
+    ));
+    if let Some(requests) = value["requests"].as_object() {
+        let mut requests: Vec<(&String, u64)> = requests
+            .iter()
+            .map(|(method, n)| (method, n.as_u64().unwrap_or(0)))
+            .collect();
+        requests.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        for (method, n) in requests {
+            out.push_str(&format!("  {n:>8}  {method}\n"));
+        }
+    }
+    match value["snapshot_age_ms"].as_f64() {
+        Some(age) => {
+            let size = &value["snapshot_size"];
+            out.push_str(&format!(
+                "Snapshot:     frame {}, {:.0}ms old, {} quads, {} text runs, {} bytes\n",
+                count("frame"),
+                age,
+                size["quads"].as_u64().unwrap_or(0),
+                size["text_runs"].as_u64().unwrap_or(0),
+                size["json_bytes"].as_u64().unwrap_or(0)
+            ));
+        }
+        None => out.push_str("Snapshot:     none received yet\n"),
+    }
+    out
+}
+
+/// One line per method from `rpc.describe`: name, params (optional ones
+/// marked `?`) and description.
+fn format_describe(value: &serde_json::Value) -> String {
+    let Some(methods) = value["methods"].as_array() else {
+        return "No method data.\n".to_string();
+    };
+
+    let signatures: Vec<String> = methods
+        .iter()
+        .map(|method| {
+            let params = &method["params"];
+            let required = params["required"].as_array();
+            let names: Vec<String> = params["properties"]
+                .as_object()
+                .into_iter()
+                .flatten()
+                .map(|(name, _)| {
+                    let is_required =
+                        required.is_some_and(|r| r.iter().any(|n| n.as_str() == Some(name)));
+                    if is_required {
+                        name.clone()
+                    } else {
+                        format!("{name}?")
+                    }
+                })
+                .collect();
+            let name = method["name"].as_str().unwrap_or("?");
+            if names.is_empty() {
+                name.to_string()
+            } else {
+                format!("{name} {{ {} }}", names.join(", "))
+            }
+        })
+        .collect();
+
+    let mut out = format!(
+        "Methods (protocol v{})\n",
+        value["protocol_version"].as_u64().unwrap_or(0)
+    );
+    out.push_str("───────────────────────────────────────────────────────────────\n");
+    for (method, signature) in methods.iter().zip(&signatures) {
+        out.push_str(&format!("  {signature}\n"));
+        if let Some(description) = method["description"].as_str() {
+            out.push_str(&format!("      {description}\n"));
+        }
+    }
+    out
+}
+
 fn format_debug_list(value: &serde_json::Value) -> String {
     let mut out = String::new();
     let arr = match value.as_array() {
@@ -344,6 +1207,19 @@ fn format_debug_list(value: &serde_json::Value) -> String {
 
     for q in arr.iter() {
         let id = q["id"].as_u64().unwrap_or(0);
+        if !q["from"].is_null() {
+            let label = q["label"].as_str().map(|l| format!("  {l:?}"));
+            out.push_str(&format!(
+                "  {:<5}  arrow ({:.1}, {:.1}) -> ({:.1}, {:.1}){}\n",
+                id,
+                q["from"]["x"].as_f64().unwrap_or(0.0),
+                q["from"]["y"].as_f64().unwrap_or(0.0),
+                q["to"]["x"].as_f64().unwrap_or(0.0),
+                q["to"]["y"].as_f64().unwrap_or(0.0),
+                label.unwrap_or_default(),
+            ));
+            continue;
+        }
         let x = q["x"].as_f64().unwrap_or(0.0);
         let y = q["y"].as_f64().unwrap_or(0.0);
         let w = q["w"].as_f64().unwrap_or(0.0);
@@ -440,6 +1316,110 @@ fn format_scene_quads(value: &serde_json::Value) -> String {
     out
 }
 
+/// Frame phases in pipeline order; JSON objects come back sorted by key.
+const FRAME_PHASES: [&str; 6] = [
+    "render",
+    "layout",
+    "paint",
+    "text_shaping",
+    "instance_build",
+    "submit",
+];
+
+fn format_scene_profile(value: &serde_json::Value) -> String {
+    let mut out = String::new();
+    let frames = value.get("frames").and_then(|v| v.as_u64()).unwrap_or(0);
+    out.push_str("Frame Profile\n");
+    out.push_str("─────────────────────────────────────\n");
+    out.push_str(&format!(
+        "  {:<16}  {:>8}  {:>8}\n",
+        "PHASE", "LAST", "AVERAGE"
+    ));
+
+    let last = &value["last"];
+    let average = &value["average"];
+    let ms =
+        |profile: &serde_json::Value, phase: &str| profile["phases"][phase].as_f64().unwrap_or(0.0);
+    for phase in FRAME_PHASES {
+        out.push_str(&format!(
+            "  {:<16}  {:>6.2}ms  {:>6.2}ms\n",
+            phase,
+            ms(last, phase),
+            ms(average, phase)
+        ));
+    }
+    out.push_str(&format!(
+        "  {:<16}  {:>6.2}ms  {:>6.2}ms\n",
+        "total",
+        last["total_ms"].as_f64().unwrap_or(0.0),
+        average["total_ms"].as_f64().unwrap_or(0.0)
+    ));
+    out.push_str(&format!("\n  Averaged over {frames} frames\n"));
+    out
+}
+
+fn format_set_budget(value: &serde_json::Value) -> String {
+    match value.get("budget_ms").and_then(|v| v.as_f64()) {
+        Some(ms) => format!("Frame budget {ms}ms\n"),
+        None => "Frame budget cleared\n".to_string(),
+    }
+}
+
+/// One line per `perf.budget_exceeded` alert: the frame, its time, and its
+/// slowest phases.
+fn format_budget_alert(params: &serde_json::Value) -> String {
+    let mut phases: Vec<(&str, f64)> = FRAME_PHASES
+        .iter()
+        .map(|&phase| (phase, params["phases"][phase].as_f64().unwrap_or(0.0)))
+        .filter(|&(_, ms)| ms > 0.0)
+        .collect();
+    phases.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let breakdown: Vec<String> = phases
+        .iter()
+        .map(|(phase, ms)| format!("{phase} {ms:.2}ms"))
+        .collect();
+    format!(
+        "frame {}: {:.2}ms over {}ms budget  ({})\n",
+        params["frame"].as_u64().unwrap_or(0),
+        params["total_ms"].as_f64().unwrap_or(0.0),
+        params["budget_ms"].as_f64().unwrap_or(0.0),
+        breakdown.join(", ")
+    )
+}
+
+fn print_notifications(client: &mut DebugClient, json_mode: bool) {
+    for notification in client.take_notifications() {
+        print_notification(&notification, json_mode);
+    }
+}
+
+fn print_notification(notification: &motif_debug::DebugNotification, json_mode: bool) {
+    if json_mode {
+        println!(
+            "{}",
+            serde_json::to_string(notification).unwrap_or_default()
+        );
+    } else if notification.method == "perf.budget_exceeded" {
+        print!("{}", format_budget_alert(&notification.params));
+    } else {
+        println!("{}: {}", notification.method, notification.params);
+    }
+}
+
+/// After `perf.set_budget`, print alerts as they arrive until interrupted.
+fn watch_budget(client: &mut DebugClient, json_mode: bool) {
+    eprintln!("waiting for slow frames (Ctrl-C to stop)");
+    loop {
+        match client.next_notification() {
+            Ok(notification) => print_notification(&notification, json_mode),
+            Err(e) => {
+                eprintln!("error: {e}");
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
 fn format_input_state(value: &serde_json::Value) -> String {
     let mut out = String::new();
     out.push_str("Input State\n");
@@ -550,6 +1530,85 @@ fn format_scene_text_runs(value: &serde_json::Value) -> String {
     out
 }
 
+fn format_scene_bounds(value: &serde_json::Value) -> String {
+    let mut out = String::new();
+    out.push_str("Scene Bounds\n");
+    out.push_str("───────────────────────\n");
+    let b = &value["bounds"];
+    if b.is_null() {
+        out.push_str("  Bounds:        (empty scene)\n");
+    } else {
+        out.push_str(&format!(
+            "  Bounds:        ({:.1}, {:.1}) {:.0} x {:.0}\n",
+            b["x"].as_f64().unwrap_or(0.0),
+            b["y"].as_f64().unwrap_or(0.0),
+            b["w"].as_f64().unwrap_or(0.0),
+            b["h"].as_f64().unwrap_or(0.0),
+        ));
+    }
+    out.push_str(&format!(
+        "  Coverage:      {:.1}% of viewport\n",
+        value["coverage_percent"].as_f64().unwrap_or(0.0)
+    ));
+    out.push_str(&format!(
+        "  Extent:        {:.2}x viewport area\n",
+        value["extent_ratio"].as_f64().unwrap_or(0.0)
+    ));
+    out.push_str(&format!(
+        "  Outside:       {} quads, {} text runs\n",
+        value["outside_quads"].as_u64().unwrap_or(0),
+        value["outside_text_runs"].as_u64().unwrap_or(0)
+    ));
+    out
+}
+
+/// `scene.query` matches, listed by their index in the scene.
+fn format_scene_query(value: &serde_json::Value) -> String {
+    let quads = value["quads"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let runs = value["text_runs"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    if quads.is_empty() && runs.is_empty() {
+        return "No matches.\n".to_string();
+    }
+
+    let mut out = String::new();
+    for q in quads {
+        out.push_str(&format!(
+            "  quad {:<5}  ({:>7.1}, {:>7.1})  {:>5.0} x {:<5.0}  rgba({:.2},{:.2},{:.2},{:.2})\n",
+            q["index"].as_u64().unwrap_or(0),
+            q["bounds"]["x"].as_f64().unwrap_or(0.0),
+            q["bounds"]["y"].as_f64().unwrap_or(0.0),
+            q["bounds"]["w"].as_f64().unwrap_or(0.0),
+            q["bounds"]["h"].as_f64().unwrap_or(0.0),
+            q["color"]["r"].as_f64().unwrap_or(0.0),
+            q["color"]["g"].as_f64().unwrap_or(0.0),
+            q["color"]["b"].as_f64().unwrap_or(0.0),
+            q["color"]["a"].as_f64().unwrap_or(0.0),
+        ));
+    }
+    for tr in runs {
+        out.push_str(&format!(
+            "  text {:<5}  ({:>7.1}, {:>7.1})  {:>5.1}px  {} glyphs\n",
+            tr["index"].as_u64().unwrap_or(0),
+            tr["origin"]["x"].as_f64().unwrap_or(0.0),
+            tr["origin"]["y"].as_f64().unwrap_or(0.0),
+            tr["font_size"].as_f64().unwrap_or(0.0),
+            tr["glyph_count"].as_u64().unwrap_or(0),
+        ));
+    }
+    out.push_str(&format!(
+        "\n  {} quads, {} text runs matched\n",
+        quads.len(),
+        runs.len()
+    ));
+    out
+}
+
 fn print_response(method: &str, response: &motif_debug::DebugResponse, json_mode: bool) {
     if let Some(err) = &response.error {
         if json_mode {
@@ -579,15 +1638,45 @@ fn print_response(method: &str, response: &motif_debug::DebugResponse, json_mode
 
     // Pretty-print known result types.
     match method {
+        "server.stats" => print!("{}", format_server_stats(result)),
+        "rpc.describe" => print!("{}", format_describe(result)),
         "scene.stats" => print!("{}", format_scene_stats(result)),
         "scene.quads" => print!("{}", format_scene_quads(result)),
         "scene.text_runs" => print!("{}", format_scene_text_runs(result)),
+        "scene.query" => print!("{}", format_scene_query(result)),
+        "scene.bounds" => print!("{}", format_scene_bounds(result)),
+        "scene.patch" => print!("{}", format_scene_patch(result)),
+        "scene.profile" => print!("{}", format_scene_profile(result)),
+        "perf.set_budget" => print!("{}", format_set_budget(result)),
+        "fps" => print!("{}", format_fps(result)),
+        "log.set_level" => print!("{}", format_log_set_level(result)),
         "input.state" => print!("{}", format_input_state(result)),
         "screenshot" => print!("{}", format_screenshot(result)),
+        "framebuffer.get" => print!("{}", format_framebuffer(result)),
+        "pixel.color" => print!("{}", format_pixel_color(result)),
+        "atlas.dump" => print!("{}", format_atlas_dump(result)),
+        "capture.start" => print!("{}", format_capture(result)),
+        "capture.stop" => print!("{}", format_capture_stop(result)),
         "debug.draw_quad" => print!("{}", format_draw_quad(result)),
+        "debug.draw_arrow" => print!("{}", format_draw_arrow(result)),
         "debug.clear" => print!("{}", format_debug_clear(result)),
         "debug.remove" => print!("{}", format_debug_remove(result)),
         "debug.list" => print!("{}", format_debug_list(result)),
+        "debug.show_guides" => print!("{}", format_show_guides(result)),
+        "debug.inspect_text" => print!("{}", format_inspect_text(result)),
+        "debug.overdraw" => print!("{}", format_overdraw(result)),
+        "debug.crosshair" => print!("{}", format_crosshair(result)),
+        "debug.fps_overlay" => print!("{}", format_fps_overlay(result)),
+        "debug.highlight_access" => print!("{}", format_highlight_access(result)),
+        "debug.time_scale" => print!("{}", format_time_scale(result)),
+        "debug.show_density" => print!("{}", format_show_density(result)),
+        "debug.show_baseline_grid" => {
+            print!(
+                "{}",
+                format_show_guide(result, "line_height", "baseline grid")
+            )
+        }
+        "debug.show_spacing" => print!("{}", format_show_guide(result, "scale", "spacing rules")),
         "input.activate" => print!("{}", format_input_activate(result)),
         "input.move_to" => print!("{}", format_input_move(result)),
         "input.click" => print!("{}", format_input_click(result)),
@@ -632,8 +1721,11 @@ fn run_repl(mut client: DebugClient, json_mode: bool) {
         }
 
         let (method, params) = parse_command(cmd);
-        match client.send(method, params) {
-            Ok(response) => print_response(method, &response, json_mode),
+        match send_command(&mut client, method, params) {
+            Ok(response) => {
+                print_notifications(&mut client, json_mode);
+                print_response(method, &response, json_mode);
+            }
             Err(e) => {
                 eprintln!("error: {e}");
                 break;
@@ -642,21 +1734,193 @@ fn run_repl(mut client: DebugClient, json_mode: bool) {
     }
 }
 
+fn format_apps(apps: &[motif_debug::RegistryEntry]) -> String {
+    if apps.is_empty() {
+        return "No running motif apps\n".to_string();
+    }
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::SystemTime::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let mut out = String::new();
+    out.push_str(&format!(
+        "  {:<20} {:>7}  {:>8}  SOCKET\n",
+        "APP", "PID", "UPTIME"
+    ));
+    for app in apps {
+        let uptime = now.saturating_sub(app.started_at);
+        out.push_str(&format!(
+            "  {:<20} {:>7}  {:>7}s  {}\n",
+            app.app_name,
+            app.pid,
+            uptime,
+            app.socket_path.display()
+        ));
+    }
+    out
+}
+
+fn run_apps(json_mode: bool) {
+    let apps = client::registered_apps();
+    if json_mode {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&apps).unwrap_or_default()
+        );
+    } else {
+        print!("{}", format_apps(&apps));
+    }
+}
+
+/// Compare two PNGs, optionally saving the diff image. Exits non-zero if
+/// they differ, like `diff`.
+fn run_diff(args: &str, json_mode: bool) {
+    let paths: Vec<&str> = args.split_whitespace().collect();
+    let [a, b, rest @ ..] = paths.as_slice() else {
+        eprintln!("usage: diff <a.png> <b.png> [diff.png]");
+        std::process::exit(2);
+    };
+    let report = match motif_debug::diff::compare_png_files(a, b) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("error: {e}");
+            std::process::exit(2);
+        }
+    };
+    if let Some(out) = rest.first() {
+        if let Err(e) = report.save_image(out) {
+            eprintln!("error: {e}");
+            std::process::exit(2);
+        }
+    }
+    if json_mode {
+        let pretty = serde_json::to_string_pretty(&report.to_json()).unwrap_or_default();
+        println!("{pretty}");
+    } else {
+        print!("{}", format_diff(&report.to_json()));
+    }
+    if !report.is_identical() {
+        std::process::exit(1);
+    }
+}
+
+fn format_diff(value: &serde_json::Value) -> String {
+    let changed = value["changed_pixels"].as_u64().unwrap_or(0);
+    if changed == 0 {
+        return "Images are identical\n".to_string();
+    }
+    let mut out = format!(
+        "{changed} pixels differ (largest channel difference {})\n",
+        value["max_delta"].as_u64().unwrap_or(0)
+    );
+    for region in value["regions"].as_array().into_iter().flatten() {
+        out.push_str(&format!(
+            "  {}x{} at ({}, {})\n",
+            region["width"], region["height"], region["x"], region["y"]
+        ));
+    }
+    out
+}
+
+fn run_doctor(socket: Option<&str>, json_mode: bool) {
+    let checks = doctor::run(socket);
+    if json_mode {
+        let pretty =
+            serde_json::to_string_pretty(&doctor::report_json(&checks)).unwrap_or_default();
+        println!("{pretty}");
+    } else {
+        print!("{}", doctor::format_report(&checks));
+    }
+    if doctor::has_failures(&checks) {
+        std::process::exit(1);
+    }
+}
+
+/// Requests that answer only once the app has finished something lengthy,
+/// so they wait without the usual read timeout.
+const LONG_RUNNING: &[&str] = &["capture.start"];
+
+fn send_command(
+    client: &mut DebugClient,
+    method: &str,
+    params: Option<serde_json::Value>,
+) -> std::io::Result<motif_debug::DebugResponse> {
+    if !LONG_RUNNING.contains(&method) {
+        return client.send(method, params);
+    }
+    client.set_read_timeout(None)?;
+    let response = client.send(method, params);
+    client.set_read_timeout(Some(client::READ_TIMEOUT))?;
+    response
+}
+
 fn main() {
     let args = parse_args();
-    let mut client = connect(args.socket.as_deref());
+
+    if let Some(paths) = args
+        .command
+        .as_deref()
+        .and_then(|cmd| cmd.trim().strip_prefix("diff "))
+    {
+        run_diff(paths, args.json);
+        return;
+    }
+
+    if args.command.as_deref().map(str::trim) == Some("apps") {
+        run_apps(args.json);
+        return;
+    }
+
+    let socket = match (args.socket, args.app) {
+        (Some(socket), _) => Some(socket),
+        (None, Some(name)) => match client::find_app(&name) {
+            Ok(entry) => Some(entry.socket_path.to_string_lossy().into_owned()),
+            Err(e) => {
+                eprintln!("error: {e}");
+                std::process::exit(1);
+            }
+        },
+        (None, None) => None,
+    };
+
+    // Doctor manages its own connection so it can report connection failures.
+    if args.command.as_deref().map(str::trim) == Some("doctor") {
+        run_doctor(socket.as_deref(), args.json);
+        return;
+    }
+
+    let mut client = connect(socket.as_deref());
+    if args.cbor {
+        if let Err(e) = client.set_encoding(motif_debug::Encoding::Cbor) {
+            eprintln!("error: {e}");
+            std::process::exit(1);
+        }
+    }
 
     match args.command {
         Some(cmd) => {
             // Single command mode.
-            let (method, params) = parse_command(&cmd);
-            match client.send(method, params) {
+            let (method, mut params) = parse_command(&cmd);
+            // The connection closes as soon as this returns, which would
+            // take a scoped overlay with it.
+            if matches!(method, "debug.draw_quad" | "debug.draw_arrow") {
+                if let Some(params) = params.as_mut() {
+                    params["persist"] = true.into();
+                }
+            }
+            match send_command(&mut client, method, params) {
                 Ok(response) => {
                     let has_error = response.error.is_some();
                     print_response(method, &response, args.json);
                     if has_error {
                         std::process::exit(1);
                     }
+                    let budget_set = response
+                        .result
+                        .as_ref()
+                        .is_some_and(|r| !r["budget_ms"].is_null());
+                    if method == "perf.set_budget" && budget_set {
+                        watch_budget(&mut client, args.json);
+                    }
                 }
                 Err(e) => {
                     eprintln!("error: {e}");
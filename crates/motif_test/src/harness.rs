@@ -3,7 +3,8 @@
 //! Provides a real Metal rendering environment for tests.
 
 use motif_core::{
-    DeviceRect, ElementId, HitTree, Point, Quad, Rect, ScaleFactor, Scene, Size, TextContext,
+    DeviceRect, ElementId, HitEntry, HitTree, Point, Quad, Rect, ScaleFactor, Scene, Size,
+    TextContext,
 };
 use palette::Srgba;
 
@@ -77,6 +78,12 @@ impl TestHarness {
         self.hit_tree.hit_test_all(point)
     }
 
+    /// Hit test at a point, returning each element's bounds and z-order
+    /// (topmost first).
+    pub fn query(&self, point: Point) -> Vec<HitEntry> {
+        self.hit_tree.query(point)
+    }
+
     /// Assert that a point hits a specific element.
     #[track_caller]
     pub fn assert_hit(&self, point: Point, expected: ElementId) {
@@ -1,10 +1,15 @@
 //! Integration test harness for motif.
 //!
 //! Provides a full testing environment with real Metal rendering,
-//! hit testing, and debug server integration.
+//! hit testing, and debug server integration. [`TestWindow`] runs views
+//! headlessly through the real layout and paint path, for CI.
 
 pub mod harness;
+pub mod snapshot;
+pub mod window;
 
 pub use harness::{TestHarness, TestRenderContext};
+pub use snapshot::SnapshotOptions;
+pub use window::TestWindow;
 // Re-export hit testing types from motif_core
 pub use motif_core::{ElementId, HitEntry, HitTree};
@@ -0,0 +1,327 @@
+//! Golden-image snapshot tests.
+//!
+//! ```ignore
+//! let mut window = TestWindow::new(Size::new(200.0, 100.0), |_cx| Toolbar::default());
+//! window.draw();
+//! assert_snapshot!(&mut window, "toolbar");
+//! ```
+//!
+//! [`assert_snapshot!`](crate::assert_snapshot) renders its subject in
+//! software and compares it with `tests/snapshots/<name>.png` in the calling
+//! crate. Channels may differ by [`SnapshotOptions::tolerance`] and up to
+//! [`SnapshotOptions::max_diff_pixels`] pixels may differ by more. On
+//! failure the new render is written to `<name>.actual.png` and the
+//! differences, in red over a faded copy of it, to `<name>.diff.png`.
+//!
+//! A missing snapshot is written and the test passes, except on CI (when
+//! `CI` is set), where it fails. Set `MOTIF_UPDATE_SNAPSHOTS=1` to
+//! overwrite snapshots with the new renders.
+
+use std::path::{Path, PathBuf};
+
+use motif_core::software::{RgbaImage, SoftwareRenderer};
+use motif_core::{Render, Scene};
+
+use crate::TestWindow;
+
+/// How close a render has to be to its snapshot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SnapshotOptions {
+    /// Largest difference allowed in any channel of a pixel. The default,
+    /// 2, absorbs rounding from storing snapshots with straight alpha.
+    pub tolerance: u8,
+    /// Pixels allowed to differ by more than `tolerance`.
+    pub max_diff_pixels: usize,
+}
+
+impl Default for SnapshotOptions {
+    fn default() -> Self {
+        Self {
+            tolerance: 2,
+            max_diff_pixels: 0,
+        }
+    }
+}
+
+impl SnapshotOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn tolerance(mut self, tolerance: u8) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    pub fn max_diff_pixels(mut self, max_diff_pixels: usize) -> Self {
+        self.max_diff_pixels = max_diff_pixels;
+        self
+    }
+}
+
+/// Something that can be rendered for a snapshot.
+pub trait Snapshot {
+    /// The subject as premultiplied, sRGB-encoded RGBA8, like a renderer
+    /// readback.
+    fn render_snapshot(self) -> RgbaImage;
+}
+
+/// The window's last frame.
+impl<V: Render> Snapshot for &mut TestWindow<V> {
+    fn render_snapshot(self) -> RgbaImage {
+        self.screenshot()
+    }
+}
+
+/// A scene, rendered at the given size in device pixels.
+impl Snapshot for (&Scene, (u32, u32)) {
+    fn render_snapshot(self) -> RgbaImage {
+        let (scene, (width, height)) = self;
+        SoftwareRenderer::new().render_to_image(scene, (width as f32, height as f32), 1.0)
+    }
+}
+
+impl Snapshot for RgbaImage {
+    fn render_snapshot(self) -> RgbaImage {
+        self
+    }
+}
+
+impl Snapshot for &RgbaImage {
+    fn render_snapshot(self) -> RgbaImage {
+        self.clone()
+    }
+}
+
+/// How two images differ.
+#[derive(Clone, Debug)]
+pub struct SnapshotDiff {
+    /// Pixels with a channel differing by more than the tolerance.
+    pub diff_pixels: usize,
+    /// Largest channel difference anywhere.
+    pub max_delta: u8,
+    /// The actual image faded, with differing pixels in red.
+    pub image: RgbaImage,
+}
+
+/// Compare two images of the same size.
+///
+/// # Panics
+///
+/// If the sizes differ.
+pub fn diff(expected: &RgbaImage, actual: &RgbaImage, tolerance: u8) -> SnapshotDiff {
+    assert_eq!(
+        expected.dimensions(),
+        actual.dimensions(),
+        "diffed images must be the same size"
+    );
+    let report = motif_debug::diff::compare_images_within(expected, actual, tolerance);
+    SnapshotDiff {
+        diff_pixels: report.changed_pixels,
+        max_delta: report.max_delta,
+        image: report.image,
+    }
+}
+
+/// Compare `actual` with the snapshot `name` in `dir`, writing it if it's
+/// new or being updated. Returns why it failed.
+///
+/// [`assert_snapshot!`](crate::assert_snapshot) calls this with the calling
+/// crate's `tests/snapshots`.
+pub fn check_snapshot(
+    actual: &RgbaImage,
+    dir: &Path,
+    name: &str,
+    options: &SnapshotOptions,
+) -> Result<(), String> {
+    let update = std::env::var_os("MOTIF_UPDATE_SNAPSHOTS").is_some_and(|value| value != "0");
+    let create = std::env::var_os("CI").is_none();
+    compare_snapshot(actual, dir, name, options, update, create)
+}
+
+/// [`check_snapshot`] with the environment read: `update` overwrites the
+/// snapshot and `create` writes a missing one.
+fn compare_snapshot(
+    actual: &RgbaImage,
+    dir: &Path,
+    name: &str,
+    options: &SnapshotOptions,
+    update: bool,
+    create: bool,
+) -> Result<(), String> {
+    let path = dir.join(format!("{name}.png"));
+    let actual_path = dir.join(format!("{name}.actual.png"));
+    let diff_path = dir.join(format!("{name}.diff.png"));
+
+    if update || !path.exists() {
+        if !update && !create {
+            return Err(format!("snapshot {} doesn't exist", path.display()));
+        }
+        write_png(actual, &path)?;
+        remove_stale(&[&actual_path, &diff_path]);
+        return Ok(());
+    }
+
+    let expected = image::open(&path)
+        .map_err(|err| format!("failed to read snapshot {}: {err}", path.display()))?
+        .to_rgba8();
+    let expected = premultiply(expected);
+
+    let failure = if expected.dimensions() != actual.dimensions() {
+        let (ew, eh) = expected.dimensions();
+        let (aw, ah) = actual.dimensions();
+        Some(format!(
+            "snapshot {name} is {ew}x{eh} but the render is {aw}x{ah}"
+        ))
+    } else {
+        let diff = diff(&expected, actual, options.tolerance);
+        if diff.diff_pixels > options.max_diff_pixels {
+            write_png(&diff.image, &diff_path)?;
+            Some(format!(
+                "snapshot {name}: {} pixels differ by more than {} (allowed {}, largest \
+                 difference {}); see {}",
+                diff.diff_pixels,
+                options.tolerance,
+                options.max_diff_pixels,
+                diff.max_delta,
+                diff_path.display()
+            ))
+        } else {
+            None
+        }
+    };
+
+    match failure {
+        Some(message) => {
+            write_png(actual, &actual_path)?;
+            Err(format!(
+                "{message}\nnew render: {}\nrerun with MOTIF_UPDATE_SNAPSHOTS=1 to accept it",
+                actual_path.display()
+            ))
+        }
+        None => {
+            remove_stale(&[&actual_path, &diff_path]);
+            Ok(())
+        }
+    }
+}
+
+/// Where [`assert_snapshot!`](crate::assert_snapshot) keeps snapshots for
+/// the crate at `manifest_dir`.
+pub fn snapshot_dir(manifest_dir: &str) -> PathBuf {
+    Path::new(manifest_dir).join("tests").join("snapshots")
+}
+
+fn write_png(image: &RgbaImage, path: &Path) -> Result<(), String> {
+    let written = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|()| motif_debug::save_rendered_png(image, &path.to_string_lossy()));
+    written.map_err(|err| format!("failed to write {}: {err}", path.display()))
+}
+
+fn remove_stale(paths: &[&Path]) {
+    for path in paths {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// Snapshots are stored with straight alpha; renders are premultiplied.
+fn premultiply(mut image: RgbaImage) -> RgbaImage {
+    for pixel in image.pixels_mut() {
+        let [r, g, b, a] = pixel.0;
+        let scale = |c: u8| ((c as u32 * a as u32 + 127) / 255) as u8;
+        pixel.0 = [scale(r), scale(g), scale(b), a];
+    }
+    image
+}
+
+/// Assert that a render matches its golden image.
+///
+/// `assert_snapshot!(subject, "name")` renders `subject` (a
+/// `&mut TestWindow`, a `(&Scene, (width, height))` pair or an image, see
+/// [`Snapshot`](crate::snapshot::Snapshot)) and compares it with
+/// `tests/snapshots/name.png` using the default
+/// [`SnapshotOptions`](crate::SnapshotOptions); pass options as a third
+/// argument to loosen the comparison.
+#[macro_export]
+macro_rules! assert_snapshot {
+    ($subject:expr, $name:expr $(,)?) => {
+        $crate::assert_snapshot!($subject, $name, $crate::SnapshotOptions::default())
+    };
+    ($subject:expr, $name:expr, $options:expr $(,)?) => {{
+        let image = $crate::snapshot::Snapshot::render_snapshot($subject);
+        let dir = $crate::snapshot::snapshot_dir(env!("CARGO_MANIFEST_DIR"));
+        if let Err(message) = $crate::snapshot::check_snapshot(&image, &dir, $name, &$options) {
+            panic!("{message}");
+        }
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    fn solid(color: [u8; 4]) -> RgbaImage {
+        RgbaImage::from_pixel(8, 4, Rgba(color))
+    }
+
+    fn temp_dir(test: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("motif-snapshots-{}-{test}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn diff_counts_pixels_beyond_the_tolerance() {
+        let expected = solid([100, 100, 100, 255]);
+        let mut actual = expected.clone();
+        actual.put_pixel(0, 0, Rgba([103, 100, 100, 255]));
+        actual.put_pixel(1, 0, Rgba([101, 100, 100, 255]));
+
+        let diff = diff(&expected, &actual, 2);
+        assert_eq!(diff.diff_pixels, 1);
+        assert_eq!(diff.max_delta, 3);
+        assert_eq!(diff.image.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_ne!(diff.image.get_pixel(1, 0).0, [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn snapshots_are_written_then_compared() {
+        let dir = temp_dir("compare");
+        let options = SnapshotOptions::default();
+        let image = solid([0, 64, 128, 255]);
+
+        let check = |image: &RgbaImage, options: &SnapshotOptions| {
+            compare_snapshot(image, &dir, "swatch", options, false, true)
+        };
+        assert!(compare_snapshot(&image, &dir, "swatch", &options, false, false).is_err());
+        check(&image, &options).unwrap();
+        assert!(dir.join("swatch.png").exists());
+        check(&image, &options).unwrap();
+
+        let mut changed = image.clone();
+        changed.put_pixel(3, 2, Rgba([255, 255, 255, 255]));
+        let err = check(&changed, &options).unwrap_err();
+        assert!(err.contains("1 pixels differ"), "{err}");
+        assert!(dir.join("swatch.diff.png").exists());
+        assert!(dir.join("swatch.actual.png").exists());
+
+        check(&changed, &options.max_diff_pixels(1)).unwrap();
+        assert!(!dir.join("swatch.diff.png").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn translucent_snapshots_round_trip_through_straight_alpha() {
+        let dir = temp_dir("alpha");
+        // Premultiplied, as a renderer reads it back.
+        let image = solid([40, 20, 10, 128]);
+        let options = SnapshotOptions::default();
+        compare_snapshot(&image, &dir, "translucent", &options, false, true).unwrap();
+        compare_snapshot(&image, &dir, "translucent", &options, false, true).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,629 @@
+//! Headless windows for testing views.
+//!
+//! A [`TestWindow`] runs a view through the same render, layout and paint
+//! path as an app window, at a fixed size and scale factor, without winit
+//! or a GPU. The resulting [`Scene`] and hit tree can be inspected
+//! directly, and pixels come from the [`SoftwareRenderer`]:
+//!
+//! ```ignore
+//! let mut window = TestWindow::new(Size::new(200.0, 100.0), |_cx| Swatch);
+//! window.draw();
+//! assert_eq!(window.scene().quad_count(), 1);
+//! assert_eq!(window.pixel(Point::new(10.0, 10.0)), [255, 0, 0, 255]);
+//! ```
+//!
+//! Synthetic input goes through the same hit testing and dispatch as
+//! winit events do, and the window redraws afterwards if a view changed:
+//!
+//! ```ignore
+//! window.click(Point::new(20.0, 10.0));
+//! window.focus(input_id);
+//! window.type_text("hello");
+//! window.scroll(ScrollDelta::Lines { x: 0.0, y: -3.0 });
+//! ```
+//!
+//! Time stands still in a test window: its [`Clock`] is manual, and
+//! [`advance`](TestWindow::advance) moves it, running due timers and
+//! animation frames.
+
+use std::time::Duration;
+
+use motif_core::software::{Framebuffer, RgbaImage, SoftwareRenderer};
+use motif_core::{
+    render_view, CallbackRegistry, Clock, DirtyFlag, ElementId, ElementState, EventDispatcher,
+    FrameProfiler, HitTree, InputAction, Key, LayoutEngine, ModifiersState, MouseButton, NamedKey,
+    PhysicalKey, Point, Render, ScaleFactor, Scene, ScrollDelta, Size, Tasks, TextContext, Theme,
+    Timers, WindowContext,
+};
+use winit::event::WindowEvent;
+use winit::keyboard::NativeKeyCode;
+
+/// A view rendered offscreen at a fixed size and scale factor.
+pub struct TestWindow<V> {
+    root: V,
+    size: Size,
+    scale_factor: ScaleFactor,
+    theme: Theme,
+    scene: Scene,
+    text_ctx: TextContext,
+    layout_engine: LayoutEngine,
+    hit_tree: HitTree,
+    callbacks: CallbackRegistry,
+    dispatcher: EventDispatcher,
+    dirty: DirtyFlag,
+    timers: Timers,
+    tasks: Tasks,
+    profiler: FrameProfiler,
+    renderer: SoftwareRenderer,
+    /// Pixels of the last frame, rasterized on first use.
+    framebuffer: Option<Framebuffer>,
+}
+
+impl<V: Render> TestWindow<V> {
+    /// A window of `size` logical pixels at scale factor 1, with the root
+    /// view from `build`.
+    pub fn new(size: Size, build: impl FnOnce(&mut WindowContext) -> V) -> Self {
+        Self::with_scale_factor(size, 1.0, build)
+    }
+
+    /// A window of `size` logical pixels at `scale` device pixels per
+    /// logical pixel.
+    pub fn with_scale_factor(
+        size: Size,
+        scale: f32,
+        build: impl FnOnce(&mut WindowContext) -> V,
+    ) -> Self {
+        let scale_factor = ScaleFactor(scale);
+        let theme = Theme::default();
+        let dirty = DirtyFlag::new();
+        // Nothing's drawn yet.
+        dirty.mark();
+        let timers = Timers::with_clock(Clock::manual());
+        let tasks = Tasks::new();
+        let mut scene = Scene::new();
+        let mut text_ctx = TextContext::new();
+        let root = {
+            let mut cx = WindowContext::new(&mut scene, &mut text_ctx, scale_factor)
+                .with_dirty_flag(dirty.clone())
+                .with_timers(timers.clone())
+                .with_tasks(tasks.clone())
+                .with_theme(theme.clone());
+            build(&mut cx)
+        };
+        Self {
+            root,
+            size,
+            scale_factor,
+            theme,
+            scene,
+            text_ctx,
+            layout_engine: LayoutEngine::new(),
+            hit_tree: HitTree::new(),
+            callbacks: CallbackRegistry::new(),
+            dispatcher: EventDispatcher::new(),
+            dirty,
+            timers,
+            tasks,
+            profiler: FrameProfiler::new(),
+            renderer: SoftwareRenderer::new(),
+            framebuffer: None,
+        }
+    }
+
+    /// Render, lay out and paint a frame, replacing the previous one.
+    pub fn draw(&mut self) {
+        self.profiler.begin_frame();
+        self.timers.run_frame_callbacks();
+        self.scene.clear();
+        self.hit_tree.clear();
+        self.callbacks.clear();
+        self.dirty.take();
+        self.framebuffer = None;
+
+        let mut cx = WindowContext::new(&mut self.scene, &mut self.text_ctx, self.scale_factor)
+            .with_dirty_flag(self.dirty.clone())
+            .with_timers(self.timers.clone())
+            .with_tasks(self.tasks.clone())
+            .with_theme(self.theme.clone())
+            .with_callbacks(&mut self.callbacks);
+        render_view(
+            &mut self.root,
+            &mut cx,
+            &mut self.layout_engine,
+            &mut self.hit_tree,
+            self.size,
+        );
+        self.profiler.end_frame();
+        self.dispatcher.update_cursor(&self.hit_tree);
+    }
+
+    /// Draw a frame only if a view changed since the last one, or a frame
+    /// is due. Returns whether it drew.
+    pub fn draw_if_dirty(&mut self) -> bool {
+        let now = self.clock().now();
+        if self.dirty.needs_redraw(now) || self.timers.has_frame_callbacks() {
+            self.draw();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// The root view.
+    pub fn root(&self) -> &V {
+        &self.root
+    }
+
+    /// Change the root view. Takes effect on the next [`draw`](Self::draw).
+    pub fn update_root<R>(&mut self, f: impl FnOnce(&mut V) -> R) -> R {
+        self.dirty.mark();
+        f(&mut self.root)
+    }
+
+    /// Change the window's size in logical pixels.
+    pub fn resize(&mut self, size: Size) {
+        self.size = size;
+        self.dirty.mark();
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+        self.dirty.mark();
+    }
+
+    pub fn size(&self) -> Size {
+        self.size
+    }
+
+    pub fn scale_factor(&self) -> ScaleFactor {
+        self.scale_factor
+    }
+
+    /// The last frame's primitives.
+    pub fn scene(&self) -> &Scene {
+        &self.scene
+    }
+
+    /// Mutable access to the scene, e.g. to set its background.
+    pub fn scene_mut(&mut self) -> &mut Scene {
+        self.framebuffer = None;
+        &mut self.scene
+    }
+
+    /// Bounds of the last frame's interactive elements.
+    pub fn hit_tree(&self) -> &HitTree {
+        &self.hit_tree
+    }
+
+    /// Callbacks registered by the last frame's elements.
+    pub fn callbacks(&mut self) -> &mut CallbackRegistry {
+        &mut self.callbacks
+    }
+
+    pub fn dispatcher(&self) -> &EventDispatcher {
+        &self.dispatcher
+    }
+
+    pub fn dispatcher_mut(&mut self) -> &mut EventDispatcher {
+        &mut self.dispatcher
+    }
+
+    pub fn timers(&self) -> &Timers {
+        &self.timers
+    }
+
+    /// Phase timings of recent frames. Rasterizing for pixels happens
+    /// outside the frame and isn't counted.
+    pub fn profiler(&self) -> &FrameProfiler {
+        &self.profiler
+    }
+
+    /// The window's manual clock.
+    pub fn clock(&self) -> Clock {
+        self.timers.clock()
+    }
+
+    /// Move the clock forward by `by`, running timers as they come due and
+    /// drawing any frames requested along the way, as the app would.
+    /// Returns whether it drew.
+    pub fn advance(&mut self, by: Duration) -> bool {
+        let clock = self.clock();
+        let end = clock.now() + by;
+        let mut drew = self.draw_if_dirty();
+        // Step from deadline to deadline so intervals tick once per period
+        // and scheduled frames draw on time.
+        loop {
+            let next = [self.timers.next_deadline(), self.dirty.next_frame()]
+                .into_iter()
+                .flatten()
+                .min();
+            let Some(due) = next.filter(|&due| due <= end) else {
+                break;
+            };
+            clock.advance(due.saturating_duration_since(clock.now()));
+            self.timers.run_due(due);
+            drew |= self.draw_if_dirty();
+        }
+        clock.advance(end.saturating_duration_since(clock.now()));
+        drew |= self.draw_if_dirty();
+        drew
+    }
+
+    pub fn tasks(&self) -> &Tasks {
+        &self.tasks
+    }
+
+    pub fn text_ctx(&mut self) -> &mut TextContext {
+        &mut self.text_ctx
+    }
+
+    /// The last frame rasterized in software, `size * scale` device pixels.
+    pub fn framebuffer(&mut self) -> &Framebuffer {
+        let (width, height) = self.device_size();
+        self.framebuffer.get_or_insert_with(|| {
+            let mut framebuffer = Framebuffer::new(width, height);
+            self.renderer
+                .render_to_framebuffer(&self.scene, &mut framebuffer);
+            framebuffer
+        })
+    }
+
+    /// The last frame as an image.
+    pub fn screenshot(&mut self) -> RgbaImage {
+        let (width, height) = self.device_size();
+        RgbaImage::from_raw(width, height, self.framebuffer().to_rgba8())
+            .expect("pixel buffer matches image dimensions")
+    }
+
+    /// The sRGB, premultiplied RGBA8 color of the device pixel under a
+    /// logical point in the last frame.
+    ///
+    /// # Panics
+    ///
+    /// If the point is outside the window.
+    #[track_caller]
+    pub fn pixel(&mut self, point: Point) -> [u8; 4] {
+        let (width, height) = self.device_size();
+        let x = (point.x * self.scale_factor.0).floor();
+        let y = (point.y * self.scale_factor.0).floor();
+        assert!(
+            x >= 0.0 && y >= 0.0 && (x as u32) < width && (y as u32) < height,
+            "({}, {}) is outside the {}x{} window",
+            point.x,
+            point.y,
+            self.size.width,
+            self.size.height
+        );
+        self.framebuffer().rgba8_at(x as u32, y as u32)
+    }
+
+    // --- Synthetic input ---
+
+    /// Move the pointer to `point`, in logical pixels. Returns whether a
+    /// handler ran.
+    pub fn move_mouse(&mut self, point: Point) -> bool {
+        self.prepare_input();
+        let scale = self.scale_factor.0;
+        self.dispatcher.input_mut().handle_cursor_moved(
+            (point.x * scale) as f64,
+            (point.y * scale) as f64,
+            scale,
+        );
+        self.dispatch()
+    }
+
+    /// Move the pointer to `point` and press `button`.
+    pub fn mouse_down(&mut self, point: Point, button: MouseButton) -> bool {
+        let moved = self.move_mouse(point);
+        self.dispatcher
+            .input_mut()
+            .handle_mouse_button(button, true);
+        self.dispatch() || moved
+    }
+
+    /// Move the pointer to `point` and release `button`.
+    pub fn mouse_up(&mut self, point: Point, button: MouseButton) -> bool {
+        let moved = self.move_mouse(point);
+        self.dispatcher
+            .input_mut()
+            .handle_mouse_button(button, false);
+        self.dispatch() || moved
+    }
+
+    /// Press and release the left button at `point`.
+    pub fn click(&mut self, point: Point) -> bool {
+        let down = self.mouse_down(point, MouseButton::Left);
+        self.mouse_up(point, MouseButton::Left) || down
+    }
+
+    /// Scroll at the pointer's position; move it first with
+    /// [`move_mouse`](Self::move_mouse).
+    pub fn scroll(&mut self, delta: ScrollDelta) -> bool {
+        self.prepare_input();
+        self.dispatcher.input_mut().handle_scroll(delta);
+        self.dispatch()
+    }
+
+    /// Give keyboard focus to `id`, or take it away.
+    pub fn focus(&mut self, id: impl Into<Option<ElementId>>) {
+        self.dispatcher.set_focused(id.into());
+    }
+
+    /// Hold `modifiers` for the events that follow.
+    pub fn set_modifiers(&mut self, modifiers: ModifiersState) -> bool {
+        self.prepare_input();
+        self.dispatcher
+            .input_mut()
+            .handle_modifiers_changed(modifiers);
+        self.dispatch()
+    }
+
+    /// Press and release `key` on the focused element.
+    pub fn press_key(&mut self, key: Key) -> bool {
+        self.prepare_input();
+        let physical = PhysicalKey::Unidentified(NativeKeyCode::Unidentified);
+        let input = self.dispatcher.input_mut();
+        input.handle_key(key.clone(), physical, ElementState::Pressed);
+        input.handle_key(key, physical, ElementState::Released);
+        self.dispatch()
+    }
+
+    /// Press a named key, such as [`NamedKey::Enter`].
+    pub fn press_named_key(&mut self, key: NamedKey) -> bool {
+        self.press_key(Key::Named(key))
+    }
+
+    /// Type `text` into the focused element, one key press per character.
+    pub fn type_text(&mut self, text: &str) -> bool {
+        let mut handled = false;
+        for ch in text.chars() {
+            handled |= self.press_key(Key::Character(ch.to_string().into()));
+        }
+        handled
+    }
+
+    /// Dispatch a winit event the way the app runner does, with positions
+    /// in physical pixels. Returns whether a handler ran.
+    pub fn handle_window_event(&mut self, event: &WindowEvent) -> bool {
+        self.prepare_input();
+        let handled = self.dispatcher.handle_window_event(
+            event,
+            self.scale_factor.0,
+            &self.hit_tree,
+            &mut self.callbacks,
+        );
+        self.finish_input(handled)
+    }
+
+    /// Send an editing action, as an Edit menu item would, to the focused
+    /// element.
+    pub fn input_action(&mut self, action: InputAction) -> bool {
+        self.prepare_input();
+        let handled = self
+            .dispatcher
+            .handle_input_action(action, &mut self.callbacks);
+        self.finish_input(handled)
+    }
+
+    /// Bring the frame up to date, so events hit what's on screen.
+    fn prepare_input(&mut self) {
+        self.draw_if_dirty();
+    }
+
+    fn dispatch(&mut self) -> bool {
+        let handled = self
+            .dispatcher
+            .dispatch_pending(&self.hit_tree, &mut self.callbacks);
+        self.finish_input(handled)
+    }
+
+    /// Redraw if handlers changed a view, like the app does after input.
+    fn finish_input(&mut self, handled: bool) -> bool {
+        self.dispatcher.update_cursor(&self.hit_tree);
+        self.draw_if_dirty();
+        handled
+    }
+
+    fn device_size(&self) -> (u32, u32) {
+        let width = (self.size.width * self.scale_factor.0).round() as u32;
+        let height = (self.size.height * self.scale_factor.0).round() as u32;
+        (width.max(1), height.max(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use motif_core::{div, Animation, IntoElement, MouseEventKind, Srgba, View, ViewContext};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    struct Swatch {
+        color: Srgba,
+    }
+
+    impl Render for Swatch {
+        fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+            div().size(Size::new(50.0, 20.0)).background(self.color)
+        }
+    }
+
+    fn swatch(scale: f32) -> TestWindow<Swatch> {
+        TestWindow::with_scale_factor(Size::new(100.0, 40.0), scale, |_cx| Swatch {
+            color: Srgba::new(1.0, 0.0, 0.0, 1.0),
+        })
+    }
+
+    #[test]
+    fn draws_through_layout_into_the_scene() {
+        let mut window = swatch(2.0);
+        window.draw();
+
+        let quads = window.scene().quads();
+        assert_eq!(quads.len(), 1);
+        assert_eq!(quads[0].bounds.size.width, 100.0);
+        assert_eq!(quads[0].bounds.size.height, 40.0);
+    }
+
+    #[test]
+    fn frames_are_profiled() {
+        let mut window = swatch(1.0);
+        assert!(window.profiler().last().is_none());
+        window.draw();
+        window.draw();
+
+        assert_eq!(window.profiler().recent().count(), 2);
+        let profile = window.profiler().last().unwrap();
+        assert!(profile.total >= profile.phase(motif_core::FramePhase::Layout));
+        assert!(profile.total > std::time::Duration::ZERO);
+    }
+
+    #[test]
+    fn pixels_come_from_the_software_renderer() {
+        let mut window = swatch(2.0);
+        window
+            .scene_mut()
+            .set_background(Srgba::new(1.0, 1.0, 1.0, 1.0));
+        window.draw();
+
+        assert_eq!(window.pixel(Point::new(10.0, 10.0)), [255, 0, 0, 255]);
+        assert_eq!(window.pixel(Point::new(75.0, 10.0)), [255, 255, 255, 255]);
+        let screenshot = window.screenshot();
+        assert_eq!(screenshot.dimensions(), (200, 80));
+    }
+
+    #[test]
+    fn updating_the_root_marks_the_window_dirty() {
+        let mut window = swatch(1.0);
+        assert!(window.draw_if_dirty());
+        assert!(!window.draw_if_dirty());
+
+        window.update_root(|swatch| swatch.color = Srgba::new(0.0, 0.0, 1.0, 1.0));
+        assert!(window.draw_if_dirty());
+        assert_eq!(window.pixel(Point::new(10.0, 10.0)), [0, 0, 255, 255]);
+    }
+
+    const BUTTON: ElementId = ElementId(1);
+
+    /// A button that counts its clicks and turns green once clicked.
+    struct Clicker {
+        clicks: View<u32>,
+    }
+
+    impl Render for Clicker {
+        fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+            let clicks = self.clicks.clone();
+            let color = if *self.clicks.read() > 0 {
+                Srgba::new(0.0, 1.0, 0.0, 1.0)
+            } else {
+                Srgba::new(1.0, 0.0, 0.0, 1.0)
+            };
+            div()
+                .id(BUTTON)
+                .size(Size::new(50.0, 20.0))
+                .background(color)
+                .on_click(move |_, _| clicks.update(|clicks| *clicks += 1))
+        }
+    }
+
+    fn clicker() -> TestWindow<Clicker> {
+        TestWindow::new(Size::new(100.0, 40.0), |cx| Clicker {
+            clicks: cx.new_view(|_| 0),
+        })
+    }
+
+    #[test]
+    fn clicks_run_handlers_and_redraw() {
+        let mut window = clicker();
+        assert!(!window.click(Point::new(75.0, 10.0)));
+        assert_eq!(*window.root().clicks.read(), 0);
+
+        assert!(window.click(Point::new(10.0, 10.0)));
+        assert_eq!(*window.root().clicks.read(), 1);
+        assert_eq!(window.pixel(Point::new(10.0, 10.0)), [0, 255, 0, 255]);
+        assert_eq!(window.dispatcher().input().hovered(), Some(BUTTON));
+    }
+
+    #[test]
+    fn typed_text_reaches_the_focused_element() {
+        let mut window = clicker();
+        window.draw();
+        let typed = Rc::new(RefCell::new(String::new()));
+        let log = typed.clone();
+        window.callbacks().on_key_down(BUTTON, move |event, _| {
+            if let Key::Character(text) = &event.key {
+                log.borrow_mut().push_str(text);
+            }
+        });
+
+        assert!(!window.type_text("ignored"));
+        window.focus(BUTTON);
+        assert!(window.type_text("abc"));
+        assert_eq!(*typed.borrow(), "abc");
+    }
+
+    #[test]
+    fn scrolling_happens_under_the_pointer() {
+        let mut window = clicker();
+        window.draw();
+        let scrolled = Rc::new(RefCell::new(Vec::new()));
+        let log = scrolled.clone();
+        window.callbacks().on_scroll(BUTTON, move |event, _| {
+            if let MouseEventKind::Scroll { delta } = event.kind {
+                log.borrow_mut().push(delta);
+            }
+        });
+
+        window.move_mouse(Point::new(20.0, 10.0));
+        let delta = ScrollDelta::Pixels { x: 0.0, y: -12.0 };
+        assert!(window.scroll(delta));
+        assert_eq!(*scrolled.borrow(), vec![delta]);
+    }
+
+    /// A bar growing from 0 to 100 wide over 200ms.
+    struct Grow {
+        animation: Option<Animation>,
+    }
+
+    impl Render for Grow {
+        fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+            let clock = cx.clock();
+            let animation = self
+                .animation
+                .get_or_insert_with(|| Animation::new(&clock, Duration::from_millis(200)));
+            if !animation.is_finished() {
+                cx.request_animation_frame(|| {});
+            }
+            div()
+                .size(Size::new(animation.lerp(0.0, 100.0), 10.0))
+                .background(Srgba::new(1.0, 1.0, 1.0, 1.0))
+        }
+    }
+
+    #[test]
+    fn advancing_the_clock_steps_animations_exactly() {
+        let mut window = TestWindow::new(Size::new(100.0, 10.0), |_cx| Grow { animation: None });
+        window.draw();
+        assert_eq!(window.scene().quads()[0].bounds.size.width, 0.0);
+
+        assert!(window.advance(Duration::from_millis(100)));
+        assert_eq!(window.scene().quads()[0].bounds.size.width, 50.0);
+
+        window.advance(Duration::from_millis(150));
+        assert_eq!(window.scene().quads()[0].bounds.size.width, 100.0);
+        assert!(!window.advance(Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn timers_fire_as_the_clock_passes_them() {
+        let mut window = clicker();
+        let ticks = Rc::new(RefCell::new(0));
+        let count = ticks.clone();
+        window
+            .timers()
+            .set_interval(Duration::from_millis(10), move || *count.borrow_mut() += 1);
+
+        window.advance(Duration::from_millis(35));
+        assert_eq!(*ticks.borrow(), 3);
+    }
+}